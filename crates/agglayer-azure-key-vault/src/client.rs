@@ -0,0 +1,310 @@
+//! The [`KeyVaultClient`] calls the REST API of Azure Key Vault, authenticated
+//! with the access tokens of a managed identity, refreshed before they expire.
+
+use std::time::{Duration, Instant};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use ethers::types::H256;
+use k256::ecdsa::VerifyingKey;
+use reqwest::{Method, RequestBuilder};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::Error;
+
+/// The version of the Key Vault REST API.
+const API_VERSION: &str = "7.4";
+
+/// The endpoint of the Azure Instance Metadata Service, serving the access
+/// tokens of the managed identities of the virtual machines and AKS nodes.
+const IMDS_TOKEN_ENDPOINT: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+
+/// The margin before their expiry at which the access tokens are refreshed.
+const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(5 * 60);
+
+/// An access token, along with its expiry.
+struct AccessToken {
+    value: String,
+    expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// The lifetime of the token in seconds, as a string or a number.
+    #[serde(default)]
+    expires_in: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct KeyResponse {
+    key: JsonWebKey,
+}
+
+#[derive(Deserialize)]
+struct JsonWebKey {
+    kid: String,
+    kty: String,
+    #[serde(default)]
+    crv: Option<String>,
+    #[serde(default)]
+    x: Option<String>,
+    #[serde(default)]
+    y: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    value: String,
+}
+
+/// A client of the REST API of Azure Key Vault.
+pub(crate) struct KeyVaultClient {
+    http: reqwest::Client,
+    vault_url: Url,
+    /// The client ID of the user-assigned managed identity, if any.
+    client_id: Option<String>,
+    token: Mutex<Option<AccessToken>>,
+}
+
+impl KeyVaultClient {
+    pub(crate) fn new(vault_url: Url, client_id: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            vault_url,
+            client_id,
+            token: Mutex::new(None),
+        }
+    }
+
+    /// Get an access token of the managed identity, fetching a new one if the
+    /// current one is about to expire.
+    async fn access_token(&self) -> Result<String, Error> {
+        let mut token = self.token.lock().await;
+
+        if let Some(token) = &*token {
+            if token.expires_at > Instant::now() + TOKEN_REFRESH_MARGIN {
+                return Ok(token.value.clone());
+            }
+        }
+
+        let fresh = self.fetch_access_token().await?;
+        let value = fresh.value.clone();
+        *token = Some(fresh);
+
+        Ok(value)
+    }
+
+    /// Fetch an access token of the managed identity, from the identity
+    /// endpoint of App Service and Container Apps if set, or from IMDS.
+    async fn fetch_access_token(&self) -> Result<AccessToken, Error> {
+        let resource = resource(&self.vault_url)?;
+        let mut query = vec![("resource", resource)];
+        if let Some(client_id) = &self.client_id {
+            query.push(("client_id", client_id.clone()));
+        }
+
+        let request = match (
+            std::env::var("IDENTITY_ENDPOINT"),
+            std::env::var("IDENTITY_HEADER"),
+        ) {
+            (Ok(endpoint), Ok(header)) => {
+                query.push(("api-version", "2019-08-01".to_string()));
+                self.http.get(endpoint).header("X-IDENTITY-HEADER", header)
+            }
+            _ => {
+                query.push(("api-version", "2018-02-01".to_string()));
+                self.http
+                    .get(IMDS_TOKEN_ENDPOINT)
+                    .header("Metadata", "true")
+            }
+        };
+
+        let requested_at = Instant::now();
+        let response: TokenResponse = send(request.query(&query)).await?;
+        let expires_in = response
+            .expires_in
+            .as_ref()
+            .and_then(|expires_in| match expires_in {
+                Value::String(seconds) => seconds.parse().ok(),
+                value => value.as_u64(),
+            })
+            .unwrap_or_default();
+
+        Ok(AccessToken {
+            value: response.access_token,
+            expires_at: requested_at + Duration::from_secs(expires_in),
+        })
+    }
+
+    /// Send a request to the given path of the Key Vault.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<T, Error> {
+        let url = self
+            .vault_url
+            .join(path)
+            .map_err(|error| Error::InvalidResponse(error.to_string()))?;
+
+        let mut request = self
+            .http
+            .request(method, url)
+            .query(&[("api-version", API_VERSION)])
+            .bearer_auth(self.access_token().await?);
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        send(request).await
+    }
+
+    /// Get the identifier, including its version, and the public key of the
+    /// given key, at the given version or at its latest one.
+    pub(crate) async fn public_key(
+        &self,
+        key_name: &str,
+        version: Option<&str>,
+    ) -> Result<(String, VerifyingKey), Error> {
+        let path = match version {
+            Some(version) => format!("keys/{key_name}/{version}"),
+            None => format!("keys/{key_name}"),
+        };
+        let response: KeyResponse = self.request(Method::GET, &path, None).await?;
+
+        Ok((response.key.kid.clone(), parse_public_key(&response.key)?))
+    }
+
+    /// Sign the given digest with the key of the given identifier, returning
+    /// the concatenated `r` and `s` of the signature.
+    pub(crate) async fn sign(&self, kid: &str, digest: H256) -> Result<Vec<u8>, Error> {
+        let kid = Url::parse(kid).map_err(|error| Error::InvalidResponse(error.to_string()))?;
+        let response: SignResponse = self
+            .request(
+                Method::POST,
+                &format!("{}/sign", kid.path().trim_start_matches('/')),
+                Some(json!({ "alg": "ES256K", "value": URL_SAFE_NO_PAD.encode(digest) })),
+            )
+            .await?;
+
+        URL_SAFE_NO_PAD
+            .decode(response.value)
+            .map_err(|error| Error::InvalidResponse(error.to_string()))
+    }
+}
+
+/// Send the given request, turning the error responses into [`Error::Azure`].
+async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, Error> {
+    let response = request.send().await?;
+    let status = response.status();
+
+    if !status.is_success() {
+        let body = response.json::<Value>().await.unwrap_or_default();
+        // Key Vault nests the message under `error`, the identity endpoints
+        // put it in `error_description`.
+        let message = body
+            .pointer("/error/message")
+            .or_else(|| body.get("error_description"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+
+        return Err(Error::Azure {
+            status: status.as_u16(),
+            message,
+        });
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|error| Error::InvalidResponse(error.to_string()))
+}
+
+/// Get the resource the access tokens are requested for, from the URL of the
+/// vault, e.g. `https://vault.azure.net` for `https://name.vault.azure.net`.
+fn resource(vault_url: &Url) -> Result<String, Error> {
+    vault_url
+        .host_str()
+        .and_then(|host| host.split_once('.'))
+        .map(|(_, domain)| format!("https://{domain}"))
+        .ok_or(Error::KeyVaultConfig("VaultUrl"))
+}
+
+/// Parse the public key of a secp256k1 JSON web key.
+fn parse_public_key(key: &JsonWebKey) -> Result<VerifyingKey, Error> {
+    if !key.kty.starts_with("EC") || key.crv.as_deref() != Some("P-256K") {
+        return Err(Error::InvalidPublicKey(format!(
+            "expected a P-256K key, got {} {}",
+            key.kty,
+            key.crv.as_deref().unwrap_or_default()
+        )));
+    }
+
+    let coordinate = |coordinate: &Option<String>| {
+        URL_SAFE_NO_PAD
+            .decode(coordinate.as_deref().unwrap_or_default())
+            .map_err(|error| Error::InvalidPublicKey(error.to_string()))
+    };
+    let point = [vec![0x04], coordinate(&key.x)?, coordinate(&key.y)?].concat();
+
+    VerifyingKey::from_sec1_bytes(&point)
+        .map_err(|error| Error::InvalidPublicKey(error.to_string()))
+}
+
+impl std::fmt::Debug for KeyVaultClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Do not log the access token.
+        f.debug_struct("KeyVaultClient")
+            .field("vault_url", &self.vault_url.as_str())
+            .field("client_id", &self.client_id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::core::rand::thread_rng;
+    use k256::ecdsa::SigningKey;
+
+    use super::*;
+
+    #[test]
+    fn resource_matches_the_cloud_of_the_vault() {
+        let resource = |url: &str| resource(&url.parse().unwrap()).unwrap();
+
+        assert_eq!(
+            resource("https://agglayer.vault.azure.net"),
+            "https://vault.azure.net"
+        );
+        assert_eq!(
+            resource("https://agglayer.vault.usgovcloudapi.net/"),
+            "https://vault.usgovcloudapi.net"
+        );
+    }
+
+    #[test]
+    fn public_key_is_parsed_from_the_json_web_key() {
+        let public_key = *SigningKey::random(&mut thread_rng()).verifying_key();
+        let point = public_key.to_encoded_point(false);
+        let mut key = JsonWebKey {
+            kid: "https://agglayer.vault.azure.net/keys/agglayer/1".to_string(),
+            kty: "EC-HSM".to_string(),
+            crv: Some("P-256K".to_string()),
+            x: point.x().map(|x| URL_SAFE_NO_PAD.encode(x)),
+            y: point.y().map(|y| URL_SAFE_NO_PAD.encode(y)),
+        };
+
+        assert_eq!(parse_public_key(&key).unwrap(), public_key);
+
+        key.crv = Some("P-256".to_string());
+        assert!(matches!(
+            parse_public_key(&key),
+            Err(Error::InvalidPublicKey(_))
+        ));
+    }
+}
@@ -0,0 +1,41 @@
+//! The [`Error`] enum represents errors that can occur in the Azure Key Vault
+//! operations. It includes errors from the Key Vault and managed identity
+//! endpoints, and configuration errors.
+
+use thiserror::Error;
+
+/// Represents errors that can occur in the Azure Key Vault operations.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// An error occurred with the Azure Key Vault configuration.
+    ///
+    /// This variant is used when a required value or environment variable is
+    /// missing.
+    #[error("Azure Key Vault configuration error: missing value or env {0}")]
+    KeyVaultConfig(&'static str),
+
+    /// The Key Vault or the managed identity endpoint couldn't be reached.
+    #[error("Azure request error: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// The Key Vault or the managed identity endpoint rejected a request.
+    #[error("Azure responded with status {status}: {message}")]
+    Azure { status: u16, message: String },
+
+    /// The Key Vault or the managed identity endpoint responded with an
+    /// unexpected payload.
+    #[error("invalid Azure response: {0}")]
+    InvalidResponse(String),
+
+    /// The key isn't a secp256k1 (`P-256K`) key.
+    #[error("invalid public key of the Key Vault key: {0}")]
+    InvalidPublicKey(String),
+
+    /// The signature doesn't match the public key of the Key Vault key.
+    #[error("invalid signature of the Key Vault key")]
+    InvalidSignature,
+
+    /// The typed data couldn't be encoded.
+    #[error("EIP-712 encoding error: {0}")]
+    Eip712(String),
+}
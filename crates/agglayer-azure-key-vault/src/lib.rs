@@ -0,0 +1,189 @@
+//! The [`AzureKeyVaultSigner`] signs with a secp256k1 (`P-256K`) key of Azure
+//! Key Vault, the key never leaving the vault.
+//!
+//! The signer authenticates with the managed identity of the host, through
+//! the identity endpoint of App Service and Container Apps if available, or
+//! through the Azure Instance Metadata Service otherwise.
+
+use agglayer_config::AzureKeyVaultConfig;
+use ethers::{
+    signers::to_eip155_v,
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature, H256, U256,
+    },
+    utils::{hash_message, public_key_to_address},
+};
+use k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, VerifyingKey};
+
+pub(crate) mod client;
+pub(crate) mod error;
+
+use client::KeyVaultClient;
+pub use error::Error;
+
+/// A signer backed by an Azure Key Vault key.
+#[derive(Debug)]
+pub struct AzureKeyVaultSigner {
+    client: KeyVaultClient,
+    /// The identifier of the key, including its version.
+    kid: String,
+    public_key: VerifyingKey,
+    address: Address,
+    chain_id: u64,
+}
+
+impl AzureKeyVaultSigner {
+    /// Creates an Azure Key Vault signer from the configuration.
+    ///
+    /// The client ID of the user-assigned managed identity defaults to the
+    /// `AZURE_CLIENT_ID` environment variable, the system-assigned managed
+    /// identity being used if neither is set.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if it fails to authenticate to the
+    /// Key Vault or if the key isn't a secp256k1 key.
+    pub async fn new(chain_id: u64, config: &AzureKeyVaultConfig) -> Result<Self, Error> {
+        let client_id = config
+            .client_id
+            .clone()
+            .or_else(|| std::env::var("AZURE_CLIENT_ID").ok());
+        let client = KeyVaultClient::new(config.vault_url.clone(), client_id);
+
+        let (kid, public_key) = client
+            .public_key(&config.key_name, config.key_version.as_deref())
+            .await?;
+
+        Ok(Self {
+            client,
+            kid,
+            address: public_key_to_address(&public_key),
+            public_key,
+            chain_id,
+        })
+    }
+
+    /// Signs the given digest, with `v` set to the recovery id plus 27.
+    async fn sign_hash(&self, hash: H256) -> Result<Signature, Error> {
+        let signature = self.client.sign(&self.kid, hash).await?;
+
+        recoverable_signature(&self.public_key, hash, &signature)
+    }
+
+    /// Signs a message, prefixed as per EIP-191.
+    pub async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Error> {
+        self.sign_hash(hash_message(message)).await
+    }
+
+    /// Signs a transaction, with `v` normalized as per EIP-155 using the
+    /// chain id of the transaction, or the one of the signer if it has none.
+    pub async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Error> {
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        let mut tx = tx.clone();
+        tx.set_chain_id(chain_id);
+
+        let mut signature = self.sign_hash(tx.sighash()).await?;
+        signature.v = to_eip155_v(signature.v as u8 - 27, chain_id);
+
+        Ok(signature)
+    }
+
+    /// Signs typed data, encoded as per EIP-712.
+    pub async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Error> {
+        let encoded = payload
+            .encode_eip712()
+            .map_err(|error| Error::Eip712(error.to_string()))?;
+
+        self.sign_hash(H256::from(encoded)).await
+    }
+
+    /// Returns the address of the Key Vault key.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns the chain ID associated with the signer.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Sets a new chain ID for the signer.
+    pub fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// Turn the concatenated `r` and `s` of the signature of the given digest into
+/// a recoverable signature, with a low `s` and `v` set to the recovery id plus
+/// 27.
+fn recoverable_signature(
+    public_key: &VerifyingKey,
+    hash: H256,
+    signature: &[u8],
+) -> Result<Signature, Error> {
+    let signature = EcdsaSignature::from_slice(signature).map_err(|_| Error::InvalidSignature)?;
+    // Ethereum only accepts the signatures with a low `s`.
+    let signature = signature.normalize_s().unwrap_or(signature);
+
+    let recovery_id = [0, 1]
+        .into_iter()
+        .filter_map(RecoveryId::from_byte)
+        .find(|recovery_id| {
+            VerifyingKey::recover_from_prehash(hash.as_bytes(), &signature, *recovery_id)
+                .is_ok_and(|recovered| recovered == *public_key)
+        })
+        .ok_or(Error::InvalidSignature)?;
+
+    Ok(Signature {
+        r: U256::from_big_endian(&signature.r().to_bytes()),
+        s: U256::from_big_endian(&signature.s().to_bytes()),
+        v: u64::from(recovery_id.to_byte()) + 27,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::core::rand::thread_rng;
+    use k256::ecdsa::{signature::hazmat::PrehashSigner as _, SigningKey};
+
+    use super::*;
+
+    #[test]
+    fn raw_signatures_are_made_recoverable() {
+        let signing_key = SigningKey::random(&mut thread_rng());
+        let public_key = *signing_key.verifying_key();
+        let hash = hash_message("agglayer");
+
+        // The signatures produced by k256 have a low `s`.
+        let signature: EcdsaSignature = signing_key.sign_prehash(hash.as_bytes()).unwrap();
+        let high_s =
+            EcdsaSignature::from_scalars(signature.r().to_bytes(), (-*signature.s()).to_bytes())
+                .unwrap();
+
+        for raw in [signature.to_bytes(), high_s.to_bytes()] {
+            let recovered = recoverable_signature(&public_key, hash, &raw).unwrap();
+
+            assert_eq!(
+                recovered.recover(hash).unwrap(),
+                public_key_to_address(&public_key)
+            );
+            assert_eq!(
+                recovered.s,
+                U256::from_big_endian(&signature.s().to_bytes())
+            );
+        }
+
+        assert!(matches!(
+            recoverable_signature(&public_key, hash, &[0; 63]),
+            Err(Error::InvalidSignature)
+        ));
+    }
+}
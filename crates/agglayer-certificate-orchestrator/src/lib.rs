@@ -12,7 +12,7 @@ use tokio::{
     task::{JoinHandle, JoinSet},
 };
 use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 #[cfg(test)]
 mod tests;
@@ -22,7 +22,7 @@ const MAX_POLL_READS: usize = 1_000;
 /// Certificate orchestrator that receives certificates from CDKs.
 /// It collects certificates and sends them to the epoch packer when an epoch
 /// ends.
-pub struct CertificateOrchestrator<C, A> {
+pub struct CertificateOrchestrator<C, A: EpochPacker> {
     /// Epoch packing task resolver.
     epoch_packing_tasks: JoinSet<Result<(), Error>>,
     /// Epoch packing task builder.
@@ -30,20 +30,23 @@ pub struct CertificateOrchestrator<C, A> {
     /// Clock stream to receive EpochEnded events.
     clock: C,
     /// Certificates received from CDKs.
-    received_certificates: VecDeque<()>,
+    received_certificates: VecDeque<A::Certificate>,
+    /// Certificates of the epoch whose ending was notified, the certificates
+    /// received since then being left to the next epoch.
+    pub(crate) closing: Option<(u64, VecDeque<A::Certificate>)>,
     /// Certificates to pack for each epoch.
-    pub(crate) to_pack: BTreeMap<u64, VecDeque<()>>,
+    pub(crate) to_pack: BTreeMap<u64, VecDeque<A::Certificate>>,
     /// Receiver for certificates coming from CDKs.
-    data_receiver: Receiver<()>,
+    data_receiver: Receiver<A::Certificate>,
     /// Cancellation token for graceful shutdown.
     cancellation_token: Pin<Box<WaitForCancellationFutureOwned>>,
 }
 
-impl<C, A> CertificateOrchestrator<C, A> {
+impl<C, A: EpochPacker> CertificateOrchestrator<C, A> {
     /// Creates a new CertificateOrchestrator instance.
     pub(crate) fn new(
         clock: C,
-        data_receiver: Receiver<()>,
+        data_receiver: Receiver<A::Certificate>,
         cancellation_token: CancellationToken,
         epoch_packing_task_builder: A,
     ) -> Self {
@@ -53,6 +56,7 @@ impl<C, A> CertificateOrchestrator<C, A> {
             epoch_packing_task_builder,
             data_receiver,
             received_certificates: VecDeque::new(),
+            closing: None,
             to_pack: BTreeMap::default(),
             cancellation_token: Box::pin(cancellation_token.cancelled_owned()),
         }
@@ -97,11 +101,13 @@ where
     /// }
     ///
     /// impl EpochPacker for AggregatorNotifier {
+    ///     type Certificate = ();
+    ///
     ///     fn pack<T: IntoIterator<Item = ()>>(
     ///         &self,
     ///         epoch: u64,
     ///         to_pack: T,
-    ///     ) -> Result<BoxFuture<Result<(), Error>>, Error> {Ok(Box::pin(async move {Ok(())}))}
+    ///     ) -> Result<BoxFuture<'static, Result<(), Error>>, Error> {Ok(Box::pin(async move {Ok(())}))}
     /// }
     ///
     /// async fn start() -> Result<(), ()> {
@@ -131,7 +137,7 @@ where
     #[builder(entry = "builder", exit = "start", visibility = "pub")]
     pub async fn start(
         clock: C,
-        data_receiver: Receiver<()>,
+        data_receiver: Receiver<A::Certificate>,
         cancellation_token: CancellationToken,
         epoch_packing_task_builder: A,
     ) -> anyhow::Result<JoinHandle<()>> {
@@ -176,11 +182,15 @@ where
 
         if let Some((epoch, certificates)) = self.to_pack.pop_first() {
             debug!("Packing certificates for epoch {}", epoch);
-            // Create a new task to pack the certificates for this epoch
-            let task = self.epoch_packing_task_builder.clone();
-
-            self.epoch_packing_tasks
-                .spawn(async move { task.pack(epoch, certificates)?.await });
+            // Start packing the certificates of this epoch right away, so that the
+            // epochs are packed in the order they ended, and create a new task to
+            // complete it.
+            match self.epoch_packing_task_builder.pack(epoch, certificates) {
+                Ok(task) => {
+                    self.epoch_packing_tasks.spawn(task);
+                }
+                Err(error) => error!("Error during epoch packing: {:?}", error),
+            }
         }
 
         let mut received = vec![];
@@ -193,13 +203,78 @@ where
             return self.poll(cx);
         }
 
-        if let Poll::Ready(Some(Event::EpochEnded(epoch))) = self.clock.poll_next_unpin(cx) {
-            debug!("Epoch change event received: {}", epoch);
+        match self.clock.poll_next_unpin(cx) {
+            Poll::Ready(Some(Event::EpochEnding { epoch, .. })) => {
+                debug!("Epoch ending event received: {}", epoch);
 
-            let to_pack = std::mem::take(&mut self.received_certificates);
-            self.to_pack.insert(epoch, to_pack);
+                // Stop collecting certificates for the ending epoch, the ones received
+                // from now on are packed with the next epoch.
+                let mut closing = self
+                    .closing
+                    .take()
+                    .map(|(_, certificates)| certificates)
+                    .unwrap_or_default();
+                closing.append(&mut self.received_certificates);
+                self.closing = Some((epoch, closing));
 
-            return self.poll(cx);
+                return self.poll(cx);
+            }
+            Poll::Ready(Some(Event::EpochEnded { epoch, .. })) => {
+                debug!("Epoch change event received: {}", epoch);
+
+                let to_pack = match self.closing.take() {
+                    Some((closing, certificates)) if closing == epoch => certificates,
+                    // The ending of another epoch was notified, none of the certificates
+                    // are left behind.
+                    Some((_, mut certificates)) => {
+                        certificates.append(&mut self.received_certificates);
+                        certificates
+                    }
+                    None => std::mem::take(&mut self.received_certificates),
+                };
+                self.to_pack.insert(epoch, to_pack);
+
+                return self.poll(cx);
+            }
+            Poll::Ready(Some(Event::EpochReverted { from, to })) => {
+                warn!("Epochs {}..={} reverted by an L1 reorg", to, from);
+
+                // The certificates of the reverted epochs that aren't packed yet go back
+                // to the current epoch.
+                let mut reverted: VecDeque<A::Certificate> = self
+                    .to_pack
+                    .split_off(&to)
+                    .into_values()
+                    .flatten()
+                    .collect();
+                if let Some((_, mut closing)) = self.closing.take() {
+                    reverted.append(&mut closing);
+                }
+                reverted.append(&mut self.received_certificates);
+                self.received_certificates = reverted;
+
+                return self.poll(cx);
+            }
+            Poll::Ready(Some(Event::Resynced { epoch })) => {
+                debug!("Clock resynced to epoch {}", epoch);
+
+                return self.poll(cx);
+            }
+            Poll::Ready(Some(Event::MissedEpochs {
+                skipped_events,
+                epoch,
+            })) => {
+                // The certificates of the epochs whose ending was missed are packed
+                // along with the next epoch ending.
+                warn!(
+                    "Missed {} clock events before epoch {}, packing their certificates with the \
+                     next epoch",
+                    skipped_events, epoch
+                );
+
+                return self.poll(cx);
+            }
+            _ => {}
         }
 
         Poll::Pending
@@ -207,12 +282,23 @@ where
 }
 
 pub trait EpochPacker: Clone + Unpin + Send + 'static {
-    fn pack<T: IntoIterator<Item = ()>>(
+    /// The certificates received from the CDKs and packed at the end of each
+    /// epoch.
+    type Certificate: Send + Unpin + 'static;
+
+    /// Start packing the certificates of the given epoch, returning the task
+    /// completing it.
+    ///
+    /// The epochs are packed in the order they ended.
+    fn pack<T: IntoIterator<Item = Self::Certificate>>(
         &self,
         epoch: u64,
         to_pack: T,
-    ) -> Result<BoxFuture<Result<(), Error>>, Error>;
+    ) -> Result<BoxFuture<'static, Result<(), Error>>, Error>;
 }
 
 #[derive(Debug)]
-pub enum Error {}
+pub enum Error {
+    /// The certificates of the epoch couldn't be handed over for proving.
+    Unavailable { epoch: u64 },
+}
@@ -7,6 +7,16 @@ use tokio_util::sync::CancellationToken;
 
 use crate::{CertificateOrchestrator, EpochPacker, Error};
 
+fn epoch_ended(epoch: u64) -> agglayer_clock::Event {
+    agglayer_clock::Event::EpochEnded {
+        epoch,
+        previous_epoch: epoch.checked_sub(1),
+        block_range: epoch * 10..(epoch + 1) * 10,
+        started_at: chrono::Utc::now(),
+        ended_at: chrono::Utc::now(),
+    }
+}
+
 // CertificateOrchestrator can be stopped
 #[tokio::test]
 async fn test_certificate_orchestrator_can_stop() {
@@ -49,7 +59,7 @@ async fn test_collect_certificates() {
         CertificateOrchestrator::new(clock, data_receiver, cancellation_token, check);
 
     _ = data_sender.send(()).await;
-    _ = clock_sender.send(agglayer_clock::Event::EpochEnded(1));
+    _ = clock_sender.send(epoch_ended(1));
 
     let _poll = poll!(&mut orchestrator);
 
@@ -75,7 +85,7 @@ async fn test_collect_certificates_after_epoch() {
     let mut orchestrator =
         CertificateOrchestrator::new(clock, data_receiver, cancellation_token, check);
 
-    _ = clock_sender.send(agglayer_clock::Event::EpochEnded(1));
+    _ = clock_sender.send(epoch_ended(1));
     let _poll = poll!(&mut orchestrator);
 
     _ = data_sender.send(()).await;
@@ -104,13 +114,105 @@ async fn test_collect_certificates_when_empty() {
     let mut orchestrator =
         CertificateOrchestrator::new(clock, data_receiver, cancellation_token, check);
 
-    _ = clock_sender.send(agglayer_clock::Event::EpochEnded(1));
+    _ = clock_sender.send(epoch_ended(1));
     let _poll = poll!(&mut orchestrator);
 
     assert!(orchestrator.received_certificates.is_empty());
     assert!(check_receiver.recv().await.is_some());
 }
 
+// A certificate received after an EpochEnding is stored for next epoch
+#[tokio::test]
+async fn test_collect_certificates_after_epoch_ending() {
+    let (clock_sender, receiver) = broadcast::channel(2);
+    let clock = BroadcastStream::new(receiver).filter_map(|value| value.ok());
+    let (data_sender, data_receiver) = mpsc::channel(10);
+    let cancellation_token = CancellationToken::new();
+
+    let (check_sender, mut check_receiver) = mpsc::channel(1);
+    let check = Check::builder()
+        .executed(check_sender)
+        .expected_epoch(1)
+        .expected_certificates_len(1)
+        .build();
+
+    let mut orchestrator =
+        CertificateOrchestrator::new(clock, data_receiver, cancellation_token, check);
+
+    _ = data_sender.send(()).await;
+    _ = clock_sender.send(agglayer_clock::Event::EpochEnding {
+        epoch: 1,
+        ends_at_block: 20,
+    });
+    let _poll = poll!(&mut orchestrator);
+
+    assert!(orchestrator.received_certificates.is_empty());
+    assert!(check_receiver.try_recv().is_err());
+
+    _ = data_sender.send(()).await;
+    let _poll = poll!(&mut orchestrator);
+
+    _ = clock_sender.send(epoch_ended(1));
+    let _poll = poll!(&mut orchestrator);
+
+    assert!(check_receiver.recv().await.is_some());
+    assert!(orchestrator.closing.is_none());
+    assert_eq!(orchestrator.received_certificates.len(), 1);
+}
+
+// A Resynced event doesn't pack the certificates collected so far
+#[tokio::test]
+async fn test_resynced_keeps_certificates() {
+    let (clock_sender, receiver) = broadcast::channel(1);
+    let clock = BroadcastStream::new(receiver).filter_map(|value| value.ok());
+    let (data_sender, data_receiver) = mpsc::channel(10);
+    let cancellation_token = CancellationToken::new();
+
+    let (check_sender, mut check_receiver) = mpsc::channel(1);
+    let check = Check::builder().executed(check_sender).build();
+
+    let mut orchestrator =
+        CertificateOrchestrator::new(clock, data_receiver, cancellation_token, check);
+
+    _ = data_sender.send(()).await;
+    _ = clock_sender.send(agglayer_clock::Event::Resynced { epoch: 3 });
+
+    let _poll = poll!(&mut orchestrator);
+
+    assert!(orchestrator.to_pack.is_empty());
+    assert_eq!(orchestrator.received_certificates.len(), 1);
+    assert!(check_receiver.try_recv().is_err());
+}
+
+// The certificates of reverted epochs waiting to be packed are collected again
+#[tokio::test]
+async fn test_epoch_reverted_restores_certificates() {
+    let (clock_sender, receiver) = broadcast::channel(1);
+    let clock = BroadcastStream::new(receiver).filter_map(|value| value.ok());
+    let (_data_sender, data_receiver) = mpsc::channel(10);
+    let cancellation_token = CancellationToken::new();
+
+    let (check_sender, mut check_receiver) = mpsc::channel(1);
+    let check = Check::builder()
+        .executed(check_sender)
+        .expected_epoch(3)
+        .build();
+
+    let mut orchestrator =
+        CertificateOrchestrator::new(clock, data_receiver, cancellation_token, check);
+    orchestrator.to_pack.insert(3, [()].into());
+    orchestrator.to_pack.insert(4, [(), ()].into());
+
+    _ = clock_sender.send(agglayer_clock::Event::EpochReverted { from: 5, to: 4 });
+
+    let _poll = poll!(&mut orchestrator);
+
+    // The epoch 3 is packed, the epoch 4 is reverted.
+    assert!(check_receiver.recv().await.is_some());
+    assert!(orchestrator.to_pack.is_empty());
+    assert_eq!(orchestrator.received_certificates.len(), 2);
+}
+
 #[derive(buildstructor::Builder, Clone)]
 struct Check {
     executed: mpsc::Sender<()>,
@@ -119,7 +221,13 @@ struct Check {
 }
 
 impl EpochPacker for Check {
-    fn pack<T>(&self, epoch: u64, to_pack: T) -> Result<BoxFuture<Result<(), Error>>, Error>
+    type Certificate = ();
+
+    fn pack<T>(
+        &self,
+        epoch: u64,
+        to_pack: T,
+    ) -> Result<BoxFuture<'static, Result<(), Error>>, Error>
     where
         T: IntoIterator<Item = ()>,
     {
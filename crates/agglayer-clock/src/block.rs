@@ -1,18 +1,34 @@
 use std::{
-    num::NonZeroU64,
+    collections::VecDeque,
+    num::{NonZeroU64, NonZeroUsize},
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
-use ethers::providers::{Middleware, PubsubClient};
+use agglayer_telemetry::{CLOCK_BLOCK_RESUBSCRIPTIONS, CLOCK_MISSED_TICKS};
+use chrono::{DateTime, Utc};
+use ethers::{
+    providers::{Middleware, PubsubClient, SubscriptionStream},
+    types::{Block, H256, U256},
+};
 use futures::StreamExt as _;
-use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
+
+use crate::{
+    epoch_block_range, epoch_ending, replayed_epochs, CatchUp, Clock, ClockConfiguration, ClockRef,
+    Error, Event, EventSender, OverflowPolicy, DEFAULT_BROADCAST_CAPACITY,
+};
+
+/// The delay before subscribing again to the L1 Block stream once it ended.
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
 
-use crate::{Clock, ClockRef, Error, Event, BROADCAST_CHANNEL_SIZE};
+/// The number of observed L1 Blocks whose hash is kept to find where the L1
+/// chain forked, reorgs being rarely deeper.
+const REORG_WINDOW: usize = 64;
 
 /// Block based [`Clock`] implementation.
 pub struct BlockClock<P> {
@@ -27,6 +43,16 @@ pub struct BlockClock<P> {
     epoch_duration: NonZeroU64,
     /// The current local Epoch number.
     current_epoch: Arc<AtomicU64>,
+    /// The behavior of the Clock when it starts after some Epochs already
+    /// ended.
+    catch_up: CatchUp,
+    /// The capacity of the broadcast channel.
+    broadcast_capacity: usize,
+    /// The behavior of the Clock when the broadcast channel is full.
+    overflow_policy: OverflowPolicy,
+    /// The number of Blocks ahead of the end of each Epoch at which its ending
+    /// is notified, if any.
+    ending_notice: Option<NonZeroU64>,
 }
 
 #[async_trait::async_trait]
@@ -36,13 +62,19 @@ where
     <P as Middleware>::Provider: PubsubClient,
 {
     async fn spawn(mut self, cancellation_token: CancellationToken) -> Result<ClockRef, Error> {
-        let (sender, _receiver) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
-
-        let clock_ref = ClockRef {
-            sender: sender.clone(),
-            current_epoch: self.current_epoch.clone(),
-            block_height: self.block_height.clone(),
-        };
+        let (sender, receiver) =
+            EventSender::channel(self.broadcast_capacity, self.overflow_policy);
+        let sender = sender.cancelled_by(cancellation_token.clone());
+
+        let clock_ref = sender.clock_ref(
+            receiver,
+            self.current_epoch.clone(),
+            self.block_height.clone(),
+            ClockConfiguration::Block {
+                genesis_block: self.genesis_block,
+                epoch_duration: self.epoch_duration,
+            },
+        );
 
         // Spawn the Clock task directly
         tokio::spawn(async move {
@@ -66,9 +98,42 @@ impl<P> BlockClock<P> {
             block_height: Arc::new(AtomicU64::new(0)),
             epoch_duration,
             current_epoch: Arc::new(AtomicU64::new(0)),
+            catch_up: CatchUp::default(),
+            broadcast_capacity: DEFAULT_BROADCAST_CAPACITY,
+            overflow_policy: OverflowPolicy::default(),
+            ending_notice: None,
         }
     }
 
+    /// Set the behavior of the Clock when it starts after some Epochs already
+    /// ended.
+    pub fn with_catch_up(mut self, catch_up: CatchUp) -> Self {
+        self.catch_up = catch_up;
+
+        self
+    }
+
+    /// Set the capacity of the broadcast channel and the behavior of the Clock
+    /// when it is full.
+    pub fn with_broadcast(
+        mut self,
+        capacity: NonZeroUsize,
+        overflow_policy: OverflowPolicy,
+    ) -> Self {
+        self.broadcast_capacity = capacity.get();
+        self.overflow_policy = overflow_policy;
+
+        self
+    }
+
+    /// Notify the ending of each Epoch the given number of Blocks ahead, with
+    /// an [`Event::EpochEnding`](crate::Event::EpochEnding).
+    pub fn with_ending_notice(mut self, notice: NonZeroU64) -> Self {
+        self.ending_notice = Some(notice);
+
+        self
+    }
+
     /// Updates the current Epoch of this [`TimeClock`].
     ///
     /// This method is used to update the current Epoch number based on the
@@ -96,6 +161,21 @@ impl<P> BlockClock<P> {
         }
     }
 
+    /// Rewind the Block height and the Epoch number of this [`BlockClock`] to
+    /// an L1 Block replacing already observed ones.
+    ///
+    /// Returns the previous and the new Epoch numbers if the reorg crossed an
+    /// Epoch boundary.
+    fn rewind(&mut self, l1_block: u64) -> Option<(u64, u64)> {
+        let block_height = self.calculate_block_number(l1_block);
+        self.block_height.store(block_height, Ordering::Release);
+
+        let epoch = self.calculate_epoch_number(block_height);
+        let previous = self.current_epoch.fetch_min(epoch, Ordering::AcqRel);
+
+        (epoch < previous).then_some((previous, epoch))
+    }
+
     /// Calculate an Epoch number based on a Block number.
     fn calculate_epoch_number(&self, from_block: u64) -> u64 {
         from_block / self.epoch_duration
@@ -105,12 +185,27 @@ impl<P> BlockClock<P> {
     fn calculate_block_number(&self, from_block: u64) -> u64 {
         from_block.saturating_sub(self.genesis_block)
     }
+
+    /// Calculate an L1 Block number based on a Block number.
+    fn calculate_l1_block_number(&self, from_block: u64) -> u64 {
+        from_block.saturating_add(self.genesis_block)
+    }
+}
+
+/// Convert an L1 Block timestamp into a datetime.
+fn block_datetime(timestamp: U256) -> DateTime<Utc> {
+    i64::try_from(timestamp.low_u64())
+        .ok()
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
 }
 
 #[derive(Debug, thiserror::Error)]
 enum BlockClockError {
     #[error("Failed to get the current L1 Block number")]
     GetBlockNumber,
+    #[error("Failed to get the L1 Block {0}")]
+    GetBlock(u64),
     #[error("Failed to subscribe to the L1 Block stream")]
     SubscribeBlocks,
     #[error("The current block height is less than the genesis block number: {0} < {1}")]
@@ -123,15 +218,209 @@ enum BlockClockError {
     SetEpochNumber(u64, u64),
 }
 
+impl<P> BlockClock<P>
+where
+    P: Middleware,
+{
+    /// Fetch the time at which a Block height was reached, from the timestamp
+    /// of the associated L1 Block.
+    async fn block_time(&self, block_height: u64) -> Result<DateTime<Utc>, BlockClockError> {
+        let l1_block = self.calculate_l1_block_number(block_height);
+
+        self.provider
+            .get_block(l1_block)
+            .await
+            .ok()
+            .flatten()
+            .map(|block| block_datetime(block.timestamp))
+            .ok_or(BlockClockError::GetBlock(l1_block))
+    }
+
+    /// Find the first L1 Block replaced by the given one, of the given parent,
+    /// among the observed ones.
+    ///
+    /// The observed L1 Blocks are checked against the L1 chain from the most
+    /// recent one, the L1 chain having forked right after the first one still
+    /// in it. The reorgs deeper than the observed L1 Blocks fork from the
+    /// oldest one.
+    async fn fork_point(
+        &self,
+        number: u64,
+        parent_hash: H256,
+        observed: &VecDeque<(u64, H256)>,
+    ) -> u64 {
+        for &(observed_number, observed_hash) in observed.iter().rev() {
+            if observed_number >= number {
+                continue;
+            }
+
+            let hash = if observed_number + 1 == number {
+                Some(parent_hash)
+            } else {
+                self.provider
+                    .get_block(observed_number)
+                    .await
+                    .ok()
+                    .flatten()
+                    .and_then(|block| block.hash)
+            };
+            if hash == Some(observed_hash) {
+                return observed_number + 1;
+            }
+        }
+
+        observed
+            .front()
+            .map_or(number, |(observed_number, _)| *observed_number)
+    }
+
+    /// Produce the Blocks up to the given height, reached by an L1 Block
+    /// timestamped at the given time, notifying the subscribers of the Epochs
+    /// they end and of the endings they reach the notice of.
+    ///
+    /// The Epochs ended by the Blocks missed on the way end at the time of
+    /// their L1 Block, or of the received one if it can't be fetched.
+    async fn produce_blocks(
+        &mut self,
+        sender: &EventSender,
+        target: u64,
+        reached_at: DateTime<Utc>,
+        epoch_started_at: &mut DateTime<Utc>,
+    ) -> Result<(), BlockClockError> {
+        let mut current_block = self.block_height.load(Ordering::Acquire);
+
+        while current_block < target {
+            current_block += 1;
+            self.block_height.store(current_block, Ordering::Release);
+
+            // If the current Block height is a multiple of the Epoch duration, the current
+            // Epoch has ended. In this case, we need to update the new Epoch number and
+            // send an `EpochEnded` event to the subscribers.
+            if current_block % self.epoch_duration == 0 {
+                let epoch_ended =
+                    self.update_epoch_number(current_block)
+                        .map_err(|(previous, expected)| {
+                            BlockClockError::SetEpochNumber(previous, expected)
+                        })?;
+                let epoch_ended_at = if current_block == target {
+                    reached_at
+                } else {
+                    self.block_time(current_block)
+                        .await
+                        .unwrap_or_else(|error| {
+                            warn!("{error}, ending the Epoch {epoch_ended} at {reached_at}");
+
+                            reached_at
+                        })
+                };
+
+                sender
+                    .send(Event::epoch_ended(
+                        epoch_ended,
+                        self.epoch_duration,
+                        *epoch_started_at,
+                        epoch_ended_at,
+                    ))
+                    .await;
+
+                *epoch_started_at = epoch_ended_at;
+            }
+
+            // Notify the ending of the current Epoch ahead, if configured.
+            if let Some(event) = self
+                .ending_notice
+                .and_then(|notice| epoch_ending(current_block, self.epoch_duration, notice))
+            {
+                sender.send(event).await;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl<P> BlockClock<P>
 where
     P: Middleware,
     <P as Middleware>::Provider: PubsubClient,
 {
+    /// Notify the subscribers about the Epochs that ended before the Clock
+    /// started, according to the [`CatchUp`] behavior.
+    async fn catch_up(
+        &self,
+        sender: &EventSender,
+        current_epoch: u64,
+    ) -> Result<(), BlockClockError> {
+        if current_epoch == 0 {
+            return Ok(());
+        }
+
+        match self.catch_up {
+            CatchUp::Resync => {
+                sender
+                    .send(Event::Resynced {
+                        epoch: current_epoch,
+                    })
+                    .await;
+            }
+            CatchUp::Replay => {
+                let epochs = replayed_epochs(current_epoch, self.broadcast_capacity);
+                let mut started_at = self
+                    .block_time(epoch_block_range(epochs.start, self.epoch_duration).start)
+                    .await?;
+
+                for epoch in epochs {
+                    let ended_at = self
+                        .block_time(epoch_block_range(epoch, self.epoch_duration).end)
+                        .await?;
+
+                    sender
+                        .send(Event::epoch_ended(
+                            epoch,
+                            self.epoch_duration,
+                            started_at,
+                            ended_at,
+                        ))
+                        .await;
+
+                    started_at = ended_at;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Subscribe again to the L1 Block stream once it ended, as its
+    /// connection dropped for instance, until the Clock task is cancelled.
+    ///
+    /// The L1 Blocks received in the meantime are missed ticks, produced once
+    /// the next L1 Block is received.
+    async fn resubscribe<'a>(
+        provider: &'a P,
+        cancellation_token: &CancellationToken,
+    ) -> Option<SubscriptionStream<'a, P::Provider, Block<H256>>> {
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return None,
+                _ = tokio::time::sleep(RESUBSCRIBE_DELAY) => {}
+            }
+
+            match provider.subscribe_blocks().await {
+                Ok(stream) => {
+                    CLOCK_BLOCK_RESUBSCRIPTIONS.add(1, &[]);
+
+                    return Some(stream);
+                }
+                Err(error) => warn!("Failed to subscribe again to the L1 Block stream: {error}"),
+            }
+        }
+    }
+
     /// Run the Clock task.
     async fn run(
         &mut self,
-        sender: broadcast::Sender<Event>,
+        sender: EventSender,
         cancellation_token: CancellationToken,
     ) -> Result<(), BlockClockError> {
         // Start by setting the current Block height based on the current L1 Block
@@ -179,6 +468,7 @@ where
                     .map_err(|(previous, expected)| {
                         BlockClockError::SetEpochNumber(previous, expected)
                     })?;
+                sender.start();
             }
             Ok(block) => {
                 return Err(BlockClockError::BlockHeightAlreadySet(block));
@@ -188,13 +478,47 @@ where
             }
         }
 
+        let current_epoch = self.current_epoch.load(Ordering::Acquire);
+        self.catch_up(&sender, current_epoch).await?;
+
+        // Fetch the time at which the current Epoch started, from the first L1
+        // Block of the Epoch.
+        let mut epoch_started_at = self
+            .block_time(epoch_block_range(current_epoch, self.epoch_duration).start)
+            .await?;
+
+        let mut last_l1_block = current_l1_block;
+        // The last observed L1 Blocks, to find where the L1 chain forked on reorgs.
+        let mut observed = VecDeque::with_capacity(REORG_WINDOW + 1);
+        if let Some(hash) = self
+            .provider
+            .get_block(current_l1_block)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|block| block.hash)
+        {
+            observed.push_back((current_l1_block, hash));
+        }
+
         loop {
             tokio::select! {
                 _ = cancellation_token.cancelled() => {
                     debug!("Clock task cancelled");
                     break;
                 }
-                Some(block) = stream.next() => {
+                block = stream.next() => {
+                    let Some(block) = block else {
+                        warn!("The L1 Block stream ended, subscribing again");
+
+                        match Self::resubscribe(&provider, &cancellation_token).await {
+                            Some(resubscribed) => stream = resubscribed,
+                            None => break,
+                        }
+
+                        continue;
+                    };
+
                     debug!(
                         "L1 Block received: timestamp={}, number={}, hash={}",
                         block.timestamp,
@@ -202,26 +526,61 @@ where
                         block.hash.unwrap()
                     );
 
-                    // Increase the Block height by 1. The `fetch_add` method returns the previous
-                    // value, so we need to add 1 to it to get the current Block height.
-                    if let Some(current_block) = self
-                        .block_height
-                            .fetch_add(1, Ordering::Release)
-                            .checked_add(1)
-                    {
-                        // If the current Block height is a multiple of the Epoch duration, the current
-                        // Epoch has ended. In this case, we need to update the new Epoch number and
-                        // send an `EpochEnded` event to the subscribers.
-                        if current_block % self.epoch_duration == 0 {
-                            match self.update_epoch_number(current_block) {
-                                Err((previous, expected)) => {
-                                    return Err(BlockClockError::SetEpochNumber(previous, expected));
-                                }
-                                Ok(epoch_ended) => {
-                                    _ = sender.send(Event::EpochEnded(epoch_ended));
+                    agglayer_telemetry::record_clock_drift(
+                        (Utc::now() - block_datetime(block.timestamp)).num_milliseconds() as f64
+                            / 1000.0,
+                    );
+
+                    if let Some(number) = block.number.map(|number| number.as_u64()) {
+                        // A Block not extending the last observed one replaces already observed
+                        // Blocks if the L1 chain got reorganized, at the same height or below,
+                        // or on a different parent.
+                        let extends_last = number == last_l1_block + 1
+                            && observed.back().is_none_or(|(observed_number, hash)| {
+                                *observed_number != last_l1_block || *hash == block.parent_hash
+                            });
+                        if !extends_last {
+                            let fork = self.fork_point(number, block.parent_hash, &observed).await;
+                            observed.retain(|(observed_number, _)| *observed_number < fork);
+
+                            if fork <= last_l1_block {
+                                if let Some((from, to)) = self.rewind(fork.saturating_sub(1)) {
+                                    warn!(
+                                        "L1 reorg from Block {fork} reverted the Epochs {to}..={from}"
+                                    );
+
+                                    sender.send(Event::EpochReverted { from, to }).await;
+
+                                    epoch_started_at = self
+                                        .block_time(
+                                            epoch_block_range(to, self.epoch_duration).start,
+                                        )
+                                        .await?;
                                 }
                             }
                         }
+                        if let Some(hash) = block.hash {
+                            observed.push_back((number, hash));
+                            if observed.len() > REORG_WINDOW {
+                                observed.pop_front();
+                            }
+                        }
+
+                        // Blocks skipped by the L1 stream, while resubscribing for instance, are
+                        // ticks missed by the Clock, produced along with the received one.
+                        let missed_ticks = number.saturating_sub(last_l1_block).saturating_sub(1);
+                        if missed_ticks > 0 {
+                            CLOCK_MISSED_TICKS.add(missed_ticks, &[]);
+                        }
+                        last_l1_block = number;
+
+                        self.produce_blocks(
+                            &sender,
+                            self.calculate_block_number(number),
+                            block_datetime(block.timestamp),
+                            &mut epoch_started_at,
+                        )
+                        .await?;
                     }
                 }
             }
@@ -233,17 +592,21 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::{num::NonZeroU64, time::Duration};
+    use std::{collections::VecDeque, num::NonZeroU64, sync::atomic::Ordering, time::Duration};
 
+    use chrono::DateTime;
     use ethers::{
         providers::{Provider, Ws},
+        types::{Block, H256},
         utils::Anvil,
     };
     use fail::FailScenario;
-    use tokio::sync::broadcast;
     use tokio_util::sync::CancellationToken;
 
-    use crate::{block::BlockClockError, BlockClock, Clock, Event, BROADCAST_CHANNEL_SIZE};
+    use crate::{
+        block::BlockClockError, BlockClock, Clock, Event, EventSender, OverflowPolicy,
+        DEFAULT_BROADCAST_CAPACITY,
+    };
 
     #[test]
     fn test_block_calculation() {
@@ -265,6 +628,104 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rewind() {
+        let mut clock = BlockClock::new((), 2, NonZeroU64::new(3).unwrap());
+        clock.block_height.store(7, Ordering::Release);
+        clock.current_epoch.store(2, Ordering::Release);
+
+        // Reorg within the current Epoch
+        assert_eq!(clock.rewind(8), None);
+        assert_eq!(clock.block_height.load(Ordering::Acquire), 6);
+        assert_eq!(clock.current_epoch.load(Ordering::Acquire), 2);
+
+        // Reorg crossing an Epoch boundary
+        assert_eq!(clock.rewind(4), Some((2, 0)));
+        assert_eq!(clock.block_height.load(Ordering::Acquire), 2);
+        assert_eq!(clock.current_epoch.load(Ordering::Acquire), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fork_point() {
+        let (provider, mock) = Provider::mocked();
+        let clock = BlockClock::new(provider, 0, NonZeroU64::new(3).unwrap());
+        let hashes: Vec<_> = (0..4).map(H256::from_low_u64_be).collect();
+        let observed = VecDeque::from([(5, hashes[1]), (6, hashes[2]), (7, hashes[3])]);
+
+        // Same height reorg, on the same parent.
+        assert_eq!(clock.fork_point(7, hashes[2], &observed).await, 7);
+
+        // Next Block on a different parent, the L1 Block 6 still being in the chain.
+        mock.push(Block::<H256> {
+            hash: Some(hashes[2]),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(clock.fork_point(8, H256::random(), &observed).await, 7);
+
+        // Reorg deeper than the observed L1 Blocks.
+        for _ in 0..2 {
+            mock.push(Block::<H256> {
+                hash: Some(H256::random()),
+                ..Default::default()
+            })
+            .unwrap();
+        }
+        assert_eq!(clock.fork_point(8, H256::random(), &observed).await, 5);
+
+        // Nothing observed, the Block replaces the one at its height.
+        assert_eq!(
+            clock.fork_point(8, H256::random(), &VecDeque::new()).await,
+            8
+        );
+    }
+
+    #[tokio::test]
+    async fn test_produce_missed_blocks() {
+        let (provider, mock) = Provider::mocked();
+        let mut clock = BlockClock::new(provider, 10, NonZeroU64::new(3).unwrap());
+        let (sender, mut recv) =
+            EventSender::channel(DEFAULT_BROADCAST_CAPACITY, OverflowPolicy::DropOldest);
+        let at = |timestamp| DateTime::from_timestamp(timestamp, 0).unwrap();
+        let l1_block = |timestamp: u64| Block::<H256> {
+            timestamp: timestamp.into(),
+            ..Default::default()
+        };
+
+        // The L1 Block ending the first Epoch is fetched, the received one ends
+        // the second.
+        mock.push(l1_block(1_030)).unwrap();
+
+        let mut epoch_started_at = at(1_000);
+        clock
+            .produce_blocks(&sender, 6, at(1_060), &mut epoch_started_at)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            recv.try_recv(),
+            Ok(Event::epoch_ended(
+                0,
+                clock.epoch_duration,
+                at(1_000),
+                at(1_030)
+            ))
+        );
+        assert_eq!(
+            recv.try_recv(),
+            Ok(Event::epoch_ended(
+                1,
+                clock.epoch_duration,
+                at(1_030),
+                at(1_060)
+            ))
+        );
+        assert!(recv.try_recv().is_err());
+        assert_eq!(clock.block_height.load(Ordering::Acquire), 6);
+        assert_eq!(clock.current_epoch.load(Ordering::Acquire), 2);
+        assert_eq!(epoch_started_at, at(1_060));
+    }
+
     #[tokio::test]
     async fn test_block_clock() {
         let anvil = Anvil::new().block_time(1u64).spawn();
@@ -277,9 +738,13 @@ mod tests {
 
         let mut recv = clock_ref.subscribe().unwrap();
 
-        assert_eq!(recv.recv().await, Ok(Event::EpochEnded(0)));
+        assert!(matches!(
+            recv.recv().await,
+            Ok(Event::EpochEnded { epoch: 0, previous_epoch: None, block_range, .. })
+                if block_range == (0..3)
+        ));
         assert_eq!(clock_ref.current_epoch(), 1);
-        assert!(clock_ref.current_block_height() >= 3);
+        assert!(clock_ref.current_block() >= 3);
     }
 
     #[tokio::test]
@@ -296,9 +761,13 @@ mod tests {
 
         let mut recv = clock_ref.subscribe().unwrap();
 
-        assert_eq!(recv.recv().await, Ok(Event::EpochEnded(0)));
+        assert!(matches!(
+            recv.recv().await,
+            Ok(Event::EpochEnded { epoch: 0, previous_epoch: None, block_range, .. })
+                if block_range == (0..3)
+        ));
         assert_eq!(clock_ref.current_epoch(), 1);
-        assert!(clock_ref.current_block_height() >= 3);
+        assert!(clock_ref.current_block() >= 3);
     }
 
     #[tokio::test]
@@ -308,7 +777,8 @@ mod tests {
 
         tokio::time::sleep(Duration::from_secs(1)).await;
         let mut clock = BlockClock::new(client, 2, NonZeroU64::new(3).unwrap());
-        let (sender, _receiver) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
+        let (sender, _receiver) =
+            EventSender::channel(DEFAULT_BROADCAST_CAPACITY, OverflowPolicy::DropOldest);
 
         let token = CancellationToken::new();
         let handle = tokio::spawn(async move { clock.run(sender, token).await });
@@ -332,7 +802,8 @@ mod tests {
 
         let mut clock = BlockClock::new(client, 0, NonZeroU64::new(3).unwrap());
         let blocks = clock.block_height.clone();
-        let (sender, _receiver) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
+        let (sender, _receiver) =
+            EventSender::channel(DEFAULT_BROADCAST_CAPACITY, OverflowPolicy::DropOldest);
 
         let token = CancellationToken::new();
 
@@ -368,7 +839,8 @@ mod tests {
 
         let mut clock = BlockClock::new(client, 0, NonZeroU64::new(3).unwrap());
         let epoch = clock.current_epoch.clone();
-        let (sender, _receiver) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
+        let (sender, _receiver) =
+            EventSender::channel(DEFAULT_BROADCAST_CAPACITY, OverflowPolicy::DropOldest);
 
         let token = CancellationToken::new();
         fail::cfg_callback(
@@ -1,23 +1,53 @@
 //! This crate is responsible for managing the Clock pace.
 //!
 //! The Clock is responsible for providing information about Epoch timing by
-//! exposing references to the data and by broadcasting `EpochChange` events.
+//! exposing references to the data and by broadcasting `EpochEnded` events.
+//!
+//! When a Clock starts after some Epochs already ended, it either emits a
+//! single `Resynced` event or replays the missed `EpochEnded` events, depending
+//! on its [`CatchUp`] behavior.
+//!
+//! A Clock configured with an ending notice also emits an `EpochEnding` event
+//! the given number of Blocks ahead of each `EpochEnded` one.
+//!
+//! The subscribers lagging behind the Clock through [`ClockRef::events`] are
+//! notified of the Events they skipped with a `MissedEpochs` event.
 
-use std::sync::{
-    atomic::{AtomicU64, Ordering},
-    Arc,
+use std::{
+    num::NonZeroU64,
+    ops::Range,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
 };
 
-use tokio::sync::broadcast;
+use agglayer_telemetry::{KeyValue, CLOCK_SUBSCRIBER_LAG};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, BoxStream, StreamExt as _};
+use tokio::sync::{
+    broadcast::{
+        self,
+        error::{RecvError, TryRecvError},
+    },
+    Notify,
+};
+use tracing::warn;
 
 mod block;
+#[cfg(any(test, feature = "testutils"))]
+mod manual;
 mod time;
 
 pub use block::BlockClock;
-pub use time::TimeClock;
-use tokio_util::sync::CancellationToken;
+#[cfg(any(test, feature = "testutils"))]
+pub use manual::{ManualClock, ManualClockError, ManualClockHandle};
+pub use time::{TimeClock, TimeClockError};
+use tokio_util::sync::{CancellationToken, DropGuard};
 
-const BROADCAST_CHANNEL_SIZE: usize = 100;
+/// The default capacity of the Clock broadcast channel.
+pub const DEFAULT_BROADCAST_CAPACITY: usize = 100;
 
 /// The Clock trait is responsible for exposing methods to access relevant
 /// information regarding the Block height and Epoch numbers.
@@ -27,26 +57,382 @@ pub trait Clock {
     async fn spawn(self, cancellation_token: CancellationToken) -> Result<ClockRef, Error>;
 }
 
+/// The duration of an Epoch, either as a number of Blocks or as a wall-clock
+/// duration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EpochDuration {
+    /// A number of Blocks.
+    Blocks(NonZeroU64),
+    /// A wall-clock duration, translated into Blocks by the Clocks based on
+    /// their Block time.
+    Time(Duration),
+}
+
+impl EpochDuration {
+    /// Translate the Epoch duration into a number of Blocks, given the time
+    /// between two Blocks.
+    ///
+    /// Wall-clock durations are rounded to the closest number of Blocks.
+    /// Returns `None` if the duration is shorter than half a Block or if the
+    /// Block time is zero.
+    pub fn as_blocks(&self, block_time: Duration) -> Option<NonZeroU64> {
+        match self {
+            EpochDuration::Blocks(blocks) => Some(*blocks),
+            EpochDuration::Time(duration) => {
+                let block_time = block_time.as_millis();
+                let blocks = duration
+                    .as_millis()
+                    .saturating_add(block_time / 2)
+                    .checked_div(block_time)?;
+
+                NonZeroU64::new(u64::try_from(blocks).unwrap_or(u64::MAX))
+            }
+        }
+    }
+}
+
+impl From<NonZeroU64> for EpochDuration {
+    fn from(blocks: NonZeroU64) -> Self {
+        EpochDuration::Blocks(blocks)
+    }
+}
+
+/// The configuration of a Clock, exposed by its [`ClockRef`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockConfiguration {
+    /// A [`TimeClock`] producing a Block every [`TimeClock::BLOCK_TIME`].
+    Time {
+        /// The datetime of the first Block.
+        genesis: DateTime<Utc>,
+        /// The Epoch duration in Blocks.
+        epoch_duration: NonZeroU64,
+    },
+    /// A [`BlockClock`] following the L1 Blocks.
+    Block {
+        /// The L1 Block number of the first Block.
+        genesis_block: u64,
+        /// The Epoch duration in Blocks.
+        epoch_duration: NonZeroU64,
+    },
+}
+
+impl ClockConfiguration {
+    /// Returns the Epoch duration in Blocks.
+    pub fn epoch_duration(&self) -> NonZeroU64 {
+        match self {
+            ClockConfiguration::Time { epoch_duration, .. }
+            | ClockConfiguration::Block { epoch_duration, .. } => *epoch_duration,
+        }
+    }
+}
+
+/// The behavior of a Clock starting after some Epochs already ended since
+/// genesis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CatchUp {
+    /// Emit a single [`Event::Resynced`] with the current Epoch number.
+    #[default]
+    Resync,
+    /// Emit an [`Event::EpochEnded`] for each of the Epochs that ended before
+    /// the Clock started, up to the capacity of the broadcast channel.
+    Replay,
+}
+
+/// The behavior of a Clock when the broadcast channel is full because a
+/// subscriber lags behind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the oldest Event, the lagging subscribers are notified of the
+    /// number of Events they missed through
+    /// [`broadcast::error::RecvError::Lagged`].
+    #[default]
+    DropOldest,
+    /// Block the Clock until the slowest subscriber makes room for the next
+    /// Event. The Clock catches up with the time or the L1 Blocks once
+    /// unblocked.
+    ///
+    /// The first subscription is reserved from the start, the Clock blocks
+    /// once the channel is full if nobody subscribed, until the reserved
+    /// subscription is released with [`ClockRef::release_first_subscription`].
+    Block,
+}
+
+/// Sending half of the broadcast channel of a Clock, applying its
+/// [`OverflowPolicy`].
+#[derive(Clone, Debug)]
+pub(crate) struct EventSender {
+    sender: broadcast::Sender<Event>,
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+    /// Notified whenever a subscriber receives an Event or goes away.
+    consumed: Arc<Notify>,
+    /// Cancels the sending of an Event blocked by a slow subscriber.
+    cancellation_token: CancellationToken,
+    /// Cancelled once the Clock task initialised its current Epoch.
+    started: CancellationToken,
+    /// Cancelled once the Clock task stopped.
+    stopped: CancellationToken,
+    /// Dropped along with the last [`EventSender`], once the Clock task
+    /// stopped, cancelling `stopped`.
+    _stop_on_drop: Arc<DropGuard>,
+}
+
+impl EventSender {
+    /// Create a new broadcast channel, along with the receiver reserved for
+    /// the first subscriber.
+    pub(crate) fn channel(
+        capacity: usize,
+        overflow_policy: OverflowPolicy,
+    ) -> (Self, EventReceiver) {
+        let (sender, receiver) = broadcast::channel(capacity);
+        let consumed = Arc::new(Notify::new());
+        let stopped = CancellationToken::new();
+
+        (
+            Self {
+                sender,
+                capacity,
+                overflow_policy,
+                consumed: consumed.clone(),
+                cancellation_token: CancellationToken::new(),
+                started: CancellationToken::new(),
+                _stop_on_drop: Arc::new(stopped.clone().drop_guard()),
+                stopped,
+            },
+            EventReceiver::new(receiver, consumed),
+        )
+    }
+
+    /// Give up on the Events blocked by a slow subscriber once the given
+    /// token is cancelled.
+    pub(crate) fn cancelled_by(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = cancellation_token;
+        self
+    }
+
+    /// Build the [`ClockRef`] exposing this channel, the given atomics and
+    /// the configuration of the Clock.
+    pub(crate) fn clock_ref(
+        &self,
+        first_receiver: EventReceiver,
+        current_epoch: Arc<AtomicU64>,
+        block_height: Arc<AtomicU64>,
+        configuration: ClockConfiguration,
+    ) -> ClockRef {
+        ClockRef {
+            sender: self.sender.clone(),
+            consumed: self.consumed.clone(),
+            first_receiver: Mutex::new(Some(first_receiver)),
+            current_epoch,
+            block_height,
+            configuration,
+            started: self.started.clone(),
+            stopped: self.stopped.clone(),
+        }
+    }
+
+    /// Notify that the Clock task initialised its current Epoch.
+    pub(crate) fn start(&self) {
+        self.started.cancel();
+    }
+
+    /// Broadcast an Event to the subscribers.
+    ///
+    /// Under the [`OverflowPolicy::Block`] policy, waits for the slowest
+    /// subscriber to make room for the Event, which is dropped if the
+    /// cancellation token is cancelled in the meantime.
+    pub(crate) async fn send(&self, event: Event) {
+        if self.overflow_policy == OverflowPolicy::Block {
+            loop {
+                // Register for the next receipt ahead of checking the room left,
+                // not to miss one in between.
+                let consumed = self.consumed.notified();
+                if self.sender.len() < self.capacity || self.sender.receiver_count() == 0 {
+                    break;
+                }
+
+                tokio::select! {
+                    _ = consumed => {}
+                    _ = self.cancellation_token.cancelled() => return,
+                }
+            }
+        }
+
+        _ = self.sender.send(event);
+    }
+}
+
+/// Receiving half of the broadcast channel of a Clock, notifying the Clock
+/// blocked by a full channel whenever it receives an Event or goes away.
+#[derive(Debug)]
+pub struct EventReceiver {
+    receiver: broadcast::Receiver<Event>,
+    /// Dropped after the receiver, notifying the Clock once the Events left
+    /// to receive are released.
+    consumed: NotifyOnDrop,
+}
+
+impl EventReceiver {
+    fn new(receiver: broadcast::Receiver<Event>, consumed: Arc<Notify>) -> Self {
+        Self {
+            receiver,
+            consumed: NotifyOnDrop(consumed),
+        }
+    }
+
+    /// Receive the next Event, see [`broadcast::Receiver::recv`].
+    pub async fn recv(&mut self) -> Result<Event, RecvError> {
+        let event = self.receiver.recv().await;
+        self.consumed.0.notify_waiters();
+
+        event
+    }
+
+    /// Receive the next Event if any, see [`broadcast::Receiver::try_recv`].
+    pub fn try_recv(&mut self) -> Result<Event, TryRecvError> {
+        let event = self.receiver.try_recv();
+        self.consumed.0.notify_waiters();
+
+        event
+    }
+}
+
+/// Notifies the Clock when dropped.
+#[derive(Debug)]
+struct NotifyOnDrop(Arc<Notify>);
+
+impl Drop for NotifyOnDrop {
+    fn drop(&mut self) {
+        self.0.notify_waiters();
+    }
+}
+
 /// The ClockRef is a reference to the Clock instance.
 pub struct ClockRef {
     pub(crate) sender: broadcast::Sender<Event>,
+    /// Notified whenever a subscriber receives an Event or goes away.
+    pub(crate) consumed: Arc<Notify>,
+    /// The receiver created along with the Clock, handed to the first
+    /// subscriber so that it doesn't miss the Events emitted at startup.
+    pub(crate) first_receiver: Mutex<Option<EventReceiver>>,
     /// The current Epoch number.
     /// This value is updated by the Clock task.
     pub(crate) current_epoch: Arc<AtomicU64>,
     /// The Block height.
     /// This value is updated by the Clock task.
     pub(crate) block_height: Arc<AtomicU64>,
+    /// The configuration of the Clock.
+    pub(crate) configuration: ClockConfiguration,
+    /// Cancelled once the Clock task initialised its current Epoch.
+    pub(crate) started: CancellationToken,
+    /// Cancelled once the Clock task stopped.
+    pub(crate) stopped: CancellationToken,
 }
 
 impl ClockRef {
     /// Subscribe to the Clock events.
     ///
+    /// The first subscriber receives every Event emitted since the Clock
+    /// started, including the [`CatchUp`] ones, the next subscribers only
+    /// receive the Events emitted after they subscribed.
+    ///
+    /// # Errors
+    ///
+    /// This function can't fail but returns a Result for convenience and future
+    /// evolution.
+    pub fn subscribe(&self) -> Result<EventReceiver, Error> {
+        if let Some(receiver) = self
+            .first_receiver
+            .lock()
+            .ok()
+            .and_then(|mut receiver| receiver.take())
+        {
+            return Ok(receiver);
+        }
+
+        Ok(self.subscribe_from_now())
+    }
+
+    /// Release the subscription reserved for the first subscriber, if nobody
+    /// took it, so that it doesn't hold the Clock back under the
+    /// [`OverflowPolicy::Block`] policy.
+    pub fn release_first_subscription(&self) {
+        if let Ok(mut receiver) = self.first_receiver.lock() {
+            receiver.take();
+        }
+    }
+
+    /// Subscribe to the Clock events emitted from now on, leaving the
+    /// subscription reserved for the first subscriber to it.
+    pub fn subscribe_from_now(&self) -> EventReceiver {
+        EventReceiver::new(self.sender.subscribe(), self.consumed.clone())
+    }
+
+    /// Subscribe to the Clock events on behalf of the given subscriber, like
+    /// [`ClockRef::subscribe`].
+    ///
+    /// The Events skipped while the subscriber lags behind the Clock are
+    /// notified with an [`Event::MissedEpochs`], and counted by the lag metric
+    /// of the subscriber. The stream ends once the Clock task stops.
+    ///
     /// # Errors
     ///
     /// This function can't fail but returns a Result for convenience and future
     /// evolution.
-    pub fn subscribe(&self) -> Result<broadcast::Receiver<Event>, Error> {
-        Ok(self.sender.subscribe())
+    pub fn events(&self, subscriber: &'static str) -> Result<BoxStream<'static, Event>, Error> {
+        let receiver = self.subscribe()?;
+        let current_epoch = self.current_epoch.clone();
+        let stopped = self.stopped.clone();
+
+        Ok(stream::unfold(receiver, move |mut receiver| {
+            let current_epoch = current_epoch.clone();
+            let stopped = stopped.clone();
+
+            async move {
+                // The Events emitted before the Clock task stopped are delivered first.
+                let event = tokio::select! {
+                    biased;
+
+                    event = receiver.recv() => event,
+                    _ = stopped.cancelled() => return None,
+                };
+                let event = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("The {subscriber} lagged behind the Clock, {skipped} events skipped");
+                        CLOCK_SUBSCRIBER_LAG
+                            .add(skipped, &[KeyValue::new("subscriber", subscriber)]);
+
+                        Event::MissedEpochs {
+                            skipped_events: skipped,
+                            epoch: current_epoch.load(Ordering::Acquire),
+                        }
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+
+                Some((event, receiver))
+            }
+        })
+        .boxed())
+    }
+
+    /// Wait for the Clock task to initialise its current Epoch, returning it.
+    ///
+    /// The current Epoch is only meaningful once initialised, a Clock starting
+    /// after some Epochs ended since its genesis starts on Epoch 0 until then.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the Clock task stops before
+    /// initialising its current Epoch.
+    pub async fn started(&self) -> Result<u64, Error> {
+        tokio::select! {
+            biased;
+
+            _ = self.started.cancelled() => Ok(self.current_epoch()),
+            _ = self.stopped.cancelled() => Err(Error::Stopped),
+        }
     }
 
     /// Returns the current Epoch.
@@ -55,18 +441,350 @@ impl ClockRef {
     }
 
     /// Returns the current Block height.
-    pub fn current_block_height(&self) -> u64 {
+    pub fn current_block(&self) -> u64 {
         self.block_height.load(Ordering::Acquire)
     }
+
+    /// Returns the current Block height.
+    #[deprecated(note = "use `ClockRef::current_block` instead")]
+    pub fn current_block_height(&self) -> u64 {
+        self.current_block()
+    }
+
+    /// Wait for the given Epoch to start, returning the current Epoch.
+    ///
+    /// Returns right away if the given Epoch already started, even if the
+    /// current one is further ahead.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the Clock task stops before the
+    /// Epoch starts.
+    pub async fn wait_for_epoch(&self, epoch: u64) -> Result<u64, Error> {
+        // Subscribe ahead of checking the current Epoch, not to miss its
+        // change in between.
+        let mut events = self.subscribe_from_now();
+
+        loop {
+            let current_epoch = self.current_epoch();
+            if current_epoch >= epoch {
+                return Ok(current_epoch);
+            }
+
+            // The current Epoch is checked again on any Event, or once some
+            // were missed.
+            tokio::select! {
+                _ = events.recv() => {}
+                _ = self.stopped.cancelled() => return Err(Error::Stopped),
+            }
+        }
+    }
+
+    /// Wait for the current Epoch to end, returning the Epoch that started.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the Clock task stops before the
+    /// current Epoch ends.
+    pub async fn next_epoch(&self) -> Result<u64, Error> {
+        self.wait_for_epoch(self.current_epoch().saturating_add(1))
+            .await
+    }
+
+    /// Returns whether the Clock task is still running.
+    pub fn is_running(&self) -> bool {
+        !self.stopped.is_cancelled()
+    }
+
+    /// Returns the configuration of the Clock.
+    pub fn configuration(&self) -> ClockConfiguration {
+        self.configuration
+    }
 }
 
 /// Events broadcasted by the Clock.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Event {
-    /// Notify that an Epoch just ended with the associated Epoch number.
-    EpochEnded(u64),
+    /// Notify that an Epoch just ended.
+    EpochEnded {
+        /// The number of the Epoch that ended.
+        epoch: u64,
+        /// The number of the Epoch preceding the one that ended, if any.
+        previous_epoch: Option<u64>,
+        /// The range of Block heights covered by the Epoch that ended.
+        block_range: Range<u64>,
+        /// The time at which the Epoch started.
+        started_at: DateTime<Utc>,
+        /// The time at which the Epoch ended.
+        ended_at: DateTime<Utc>,
+    },
+    /// Notify that the given Epoch ends in the number of Blocks of the ending
+    /// notice of the Clock, ahead of its [`Event::EpochEnded`].
+    EpochEnding {
+        /// The number of the Epoch about to end.
+        epoch: u64,
+        /// The Block height at which the Epoch ends.
+        ends_at_block: u64,
+    },
+    /// Notify that an L1 reorg reverted the Epochs `to..=from`, the Clock is
+    /// back in the Epoch `to` and the Epochs following it will end again.
+    EpochReverted {
+        /// The Epoch number before the reorg.
+        from: u64,
+        /// The Epoch number after the reorg.
+        to: u64,
+    },
+    /// Notify that the Clock started in the middle of the given Epoch, after
+    /// the preceding Epochs ended without being notified.
+    Resynced {
+        /// The number of the current Epoch.
+        epoch: u64,
+    },
+    /// Notify a subscriber that it lagged behind the Clock, skipping Events
+    /// and so missing the Epoch changes they notified. Never broadcasted, see
+    /// [`ClockRef::events`].
+    MissedEpochs {
+        /// The number of Events skipped, the Epochs they span are unknown.
+        skipped_events: u64,
+        /// The number of the current Epoch once the lag was noticed.
+        epoch: u64,
+    },
+}
+
+impl Event {
+    /// Build an [`Event::EpochEnded`] for the given Epoch.
+    pub(crate) fn epoch_ended(
+        epoch: u64,
+        epoch_duration: NonZeroU64,
+        started_at: DateTime<Utc>,
+        ended_at: DateTime<Utc>,
+    ) -> Self {
+        Event::EpochEnded {
+            epoch,
+            previous_epoch: epoch.checked_sub(1),
+            block_range: epoch_block_range(epoch, epoch_duration),
+            started_at,
+            ended_at,
+        }
+    }
+}
+
+/// The Epochs to replay when a Clock starts in the given Epoch with
+/// [`CatchUp::Replay`].
+///
+/// Only the most recent Epochs fitting in the broadcast channel are replayed,
+/// older ones would be overwritten before the subscribers get to them.
+pub(crate) fn replayed_epochs(current_epoch: u64, capacity: usize) -> Range<u64> {
+    current_epoch.saturating_sub(capacity as u64)..current_epoch
+}
+
+/// Calculate the time at which a Block height is reached, a Block being
+/// produced every [`TimeClock::BLOCK_TIME`] since genesis.
+///
+/// This is the genesis datetime shifted by the Block height in seconds.
+pub(crate) fn time_block_timestamp(genesis: DateTime<Utc>, block_height: u64) -> DateTime<Utc> {
+    checked_time_block_timestamp(genesis, block_height).unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// Calculate the time at which a Block height is reached, like
+/// [`time_block_timestamp`], or `None` if it overflows the datetime range.
+pub(crate) fn checked_time_block_timestamp(
+    genesis: DateTime<Utc>,
+    block_height: u64,
+) -> Option<DateTime<Utc>> {
+    i64::try_from(block_height)
+        .ok()
+        .and_then(chrono::TimeDelta::try_seconds)
+        .and_then(|elapsed| genesis.checked_add_signed(elapsed))
+}
+
+/// The [`Event::EpochEnding`] to emit once the given Block height is reached,
+/// if the ending of an Epoch is to be notified at this height.
+///
+/// A notice longer than the Epoch duration notifies the ending of the Epochs
+/// as soon as they start.
+pub(crate) fn epoch_ending(
+    block_height: u64,
+    epoch_duration: NonZeroU64,
+    notice: NonZeroU64,
+) -> Option<Event> {
+    let ends_at_block = block_height.checked_add(notice.min(epoch_duration).get())?;
+
+    (ends_at_block % epoch_duration == 0).then(|| Event::EpochEnding {
+        epoch: ends_at_block / epoch_duration - 1,
+        ends_at_block,
+    })
+}
+
+/// Compute the range of Block heights covered by an Epoch.
+pub(crate) fn epoch_block_range(epoch: u64, epoch_duration: NonZeroU64) -> Range<u64> {
+    let start = epoch.saturating_mul(epoch_duration.get());
+
+    start..start.saturating_add(epoch_duration.get())
 }
 
 /// Errors that can be returned by the Clock.
 #[derive(Debug, thiserror::Error)]
-pub enum Error {}
+pub enum Error {
+    #[error("The Clock task stopped")]
+    Stopped,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        num::{NonZeroU64, NonZeroUsize},
+        time::Duration,
+    };
+
+    use futures::StreamExt as _;
+    use tokio::sync::broadcast::error::TryRecvError;
+    use tokio_util::sync::CancellationToken;
+
+    use crate::{
+        epoch_ending, Clock, EpochDuration, Error, Event, EventSender, ManualClock, OverflowPolicy,
+    };
+
+    #[test]
+    fn test_epoch_ending() {
+        let blocks = |n| NonZeroU64::new(n).unwrap();
+
+        assert_eq!(epoch_ending(7, blocks(10), blocks(2)), None);
+        assert_eq!(
+            epoch_ending(8, blocks(10), blocks(2)),
+            Some(Event::EpochEnding {
+                epoch: 0,
+                ends_at_block: 10
+            })
+        );
+        assert_eq!(
+            epoch_ending(28, blocks(10), blocks(2)),
+            Some(Event::EpochEnding {
+                epoch: 2,
+                ends_at_block: 30
+            })
+        );
+        // A notice longer than the Epochs notifies their ending once they start.
+        assert_eq!(
+            epoch_ending(10, blocks(10), blocks(15)),
+            Some(Event::EpochEnding {
+                epoch: 1,
+                ends_at_block: 20
+            })
+        );
+        assert_eq!(epoch_ending(u64::MAX, blocks(10), blocks(2)), None);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_epoch() {
+        let clock = ManualClock::new(NonZeroU64::new(5).unwrap());
+        let handle = clock.handle();
+
+        let token = CancellationToken::new();
+        let clock_ref = clock.spawn(token.clone()).await.unwrap();
+        assert_eq!(clock_ref.wait_for_epoch(0).await.unwrap(), 0);
+
+        let (next, _) = tokio::join!(clock_ref.next_epoch(), handle.advance_blocks(7));
+        assert_eq!(next.unwrap(), 1);
+
+        let (third, _) = tokio::join!(clock_ref.wait_for_epoch(3), async {
+            handle.advance_blocks(5).await.unwrap();
+            handle.advance_blocks(5).await.unwrap();
+        });
+        assert_eq!(third.unwrap(), 3);
+
+        let (never, _) = tokio::join!(clock_ref.wait_for_epoch(10), async { token.cancel() });
+        assert!(matches!(never, Err(Error::Stopped)));
+    }
+
+    #[tokio::test]
+    async fn test_events_notify_missed_epochs() {
+        let clock = ManualClock::new(NonZeroU64::new(5).unwrap())
+            .with_broadcast(NonZeroUsize::new(2).unwrap(), OverflowPolicy::DropOldest);
+        let handle = clock.handle();
+
+        let token = CancellationToken::new();
+        let clock_ref = clock.spawn(token.clone()).await.unwrap();
+        let mut events = clock_ref.events("test").unwrap();
+
+        handle.advance_blocks(15).await.unwrap();
+
+        assert_eq!(
+            events.next().await,
+            Some(Event::MissedEpochs {
+                skipped_events: 1,
+                epoch: 3
+            })
+        );
+        assert!(matches!(
+            events.next().await,
+            Some(Event::EpochEnded { epoch: 1, .. })
+        ));
+        assert!(matches!(
+            events.next().await,
+            Some(Event::EpochEnded { epoch: 2, .. })
+        ));
+
+        token.cancel();
+        assert_eq!(events.next().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_blocked_send_waits_for_room_or_cancellation() {
+        let token = CancellationToken::new();
+        let (sender, mut receiver) = EventSender::channel(1, OverflowPolicy::Block);
+        let sender = sender.cancelled_by(token.clone());
+
+        sender.send(Event::Resynced { epoch: 0 }).await;
+
+        // Receiving the first Event makes room for the blocked one.
+        let (_, first) = tokio::join!(sender.send(Event::Resynced { epoch: 1 }), async {
+            tokio::task::yield_now().await;
+            receiver.recv().await
+        });
+        assert_eq!(first, Ok(Event::Resynced { epoch: 0 }));
+        assert_eq!(receiver.try_recv(), Ok(Event::Resynced { epoch: 1 }));
+
+        sender.send(Event::Resynced { epoch: 2 }).await;
+        let blocked = sender.send(Event::Resynced { epoch: 3 });
+        tokio::pin!(blocked);
+        assert!(futures::poll!(blocked.as_mut()).is_pending());
+
+        // The blocked Event is dropped once cancelled.
+        token.cancel();
+        blocked.await;
+        assert_eq!(receiver.try_recv(), Ok(Event::Resynced { epoch: 2 }));
+        assert_eq!(receiver.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn epoch_duration_as_blocks() {
+        let blocks = |n| NonZeroU64::new(n);
+        let twelve_seconds = Duration::from_secs(12);
+
+        assert_eq!(
+            EpochDuration::Blocks(blocks(7).unwrap()).as_blocks(twelve_seconds),
+            blocks(7)
+        );
+        assert_eq!(
+            EpochDuration::Time(Duration::from_secs(600)).as_blocks(twelve_seconds),
+            blocks(50)
+        );
+        assert_eq!(
+            EpochDuration::Time(Duration::from_secs(605)).as_blocks(twelve_seconds),
+            blocks(50)
+        );
+        assert_eq!(
+            EpochDuration::Time(Duration::from_secs(607)).as_blocks(twelve_seconds),
+            blocks(51)
+        );
+        assert_eq!(
+            EpochDuration::Time(Duration::from_secs(5)).as_blocks(twelve_seconds),
+            None
+        );
+        assert_eq!(
+            EpochDuration::Time(Duration::from_secs(5)).as_blocks(Duration::ZERO),
+            None
+        );
+    }
+}
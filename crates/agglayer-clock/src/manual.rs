@@ -0,0 +1,365 @@
+use std::{
+    num::{NonZeroU64, NonZeroUsize},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use chrono::{DateTime, Utc};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+use crate::{
+    epoch_block_range, time_block_timestamp, Clock, ClockConfiguration, ClockRef, Error, Event,
+    EventSender, OverflowPolicy, DEFAULT_BROADCAST_CAPACITY,
+};
+
+/// Manually driven [`Clock`] implementation, for tests.
+///
+/// The Blocks are only produced when asked to through a
+/// [`ManualClockHandle`], so that the tests advance the Epochs right away
+/// instead of waiting for a [`TimeClock`](crate::TimeClock) to tick. The
+/// Blocks are timestamped as if produced every
+/// [`TimeClock::BLOCK_TIME`](crate::TimeClock::BLOCK_TIME) since genesis, the
+/// Clock being exposed as a time based one.
+pub struct ManualClock {
+    genesis: DateTime<Utc>,
+    current_block: Arc<AtomicU64>,
+    epoch_duration: NonZeroU64,
+    current_epoch: Arc<AtomicU64>,
+    /// The capacity of the broadcast channel.
+    broadcast_capacity: usize,
+    /// The behavior of the Clock when the broadcast channel is full.
+    overflow_policy: OverflowPolicy,
+    /// The number of Blocks ahead of the end of each Epoch at which its ending
+    /// is notified, if any.
+    ending_notice: Option<NonZeroU64>,
+    commands: mpsc::UnboundedReceiver<Command>,
+    handle: ManualClockHandle,
+}
+
+/// A request to advance a [`ManualClock`], acknowledged with the Block height
+/// reached once the Events are broadcasted.
+struct Command {
+    advance: Advance,
+    reached: oneshot::Sender<u64>,
+}
+
+enum Advance {
+    /// Produce the given number of Blocks.
+    Blocks(u64),
+    /// Produce the Blocks up to the end of the current Epoch.
+    EndOfEpoch,
+}
+
+#[async_trait::async_trait]
+impl Clock for ManualClock {
+    async fn spawn(mut self, cancellation_token: CancellationToken) -> Result<ClockRef, Error> {
+        let (sender, receiver) =
+            EventSender::channel(self.broadcast_capacity, self.overflow_policy);
+        let sender = sender.cancelled_by(cancellation_token.clone());
+
+        let clock_ref = sender.clock_ref(
+            receiver,
+            self.current_epoch.clone(),
+            self.current_block.clone(),
+            self.configuration(),
+        );
+
+        tokio::spawn(async move {
+            self.run(sender, cancellation_token).await;
+        });
+
+        Ok(clock_ref)
+    }
+}
+
+impl ManualClock {
+    /// Create a new [`ManualClock`] starting at genesis, now.
+    pub fn new(epoch_duration: NonZeroU64) -> Self {
+        let (commands, receiver) = mpsc::unbounded_channel();
+
+        Self {
+            genesis: Utc::now(),
+            current_block: Arc::new(AtomicU64::new(0)),
+            epoch_duration,
+            current_epoch: Arc::new(AtomicU64::new(0)),
+            broadcast_capacity: DEFAULT_BROADCAST_CAPACITY,
+            overflow_policy: OverflowPolicy::default(),
+            ending_notice: None,
+            commands: receiver,
+            handle: ManualClockHandle { commands },
+        }
+    }
+
+    /// Set the genesis datetime, from which the Blocks are timestamped.
+    pub fn with_genesis(mut self, genesis: DateTime<Utc>) -> Self {
+        self.genesis = genesis;
+
+        self
+    }
+
+    /// Set the capacity of the broadcast channel and the behavior of the Clock
+    /// when it is full.
+    pub fn with_broadcast(
+        mut self,
+        capacity: NonZeroUsize,
+        overflow_policy: OverflowPolicy,
+    ) -> Self {
+        self.broadcast_capacity = capacity.get();
+        self.overflow_policy = overflow_policy;
+
+        self
+    }
+
+    /// Notify the ending of each Epoch the given number of Blocks ahead, with
+    /// an [`Event::EpochEnding`].
+    pub fn with_ending_notice(mut self, notice: NonZeroU64) -> Self {
+        self.ending_notice = Some(notice);
+
+        self
+    }
+
+    /// Get a handle advancing this Clock once spawned.
+    pub fn handle(&self) -> ManualClockHandle {
+        self.handle.clone()
+    }
+
+    /// The configuration of this [`ManualClock`].
+    fn configuration(&self) -> ClockConfiguration {
+        ClockConfiguration::Time {
+            genesis: self.genesis,
+            epoch_duration: self.epoch_duration,
+        }
+    }
+
+    /// Run the Clock task.
+    async fn run(&mut self, sender: EventSender, cancellation_token: CancellationToken) {
+        // The Clock starts on its first Epoch.
+        sender.start();
+
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    debug!("Clock task cancelled");
+                    break;
+                }
+                Some(command) = self.commands.recv() => {
+                    let current_block = self.current_block.load(Ordering::Acquire);
+                    let target = match command.advance {
+                        Advance::Blocks(blocks) => current_block.saturating_add(blocks),
+                        Advance::EndOfEpoch => {
+                            epoch_block_range(
+                                current_block / self.epoch_duration,
+                                self.epoch_duration,
+                            )
+                            .end
+                        }
+                    };
+
+                    self.advance_to(&sender, target).await;
+                    _ = command.reached.send(target);
+                }
+            }
+        }
+    }
+
+    /// Produce the Blocks up to the given height, notifying the subscribers
+    /// of the Epochs they end, and of the endings they reach the notice of.
+    async fn advance_to(&self, sender: &EventSender, target: u64) {
+        let current_block = self.current_block.load(Ordering::Acquire);
+
+        for epoch in current_block / self.epoch_duration..=target / self.epoch_duration {
+            let block_range = epoch_block_range(epoch, self.epoch_duration);
+
+            if let Some(notice) = self.ending_notice {
+                let notified_at = block_range
+                    .end
+                    .saturating_sub(notice.min(self.epoch_duration).get());
+
+                if current_block < notified_at && notified_at <= target {
+                    self.current_block.store(notified_at, Ordering::Release);
+
+                    sender
+                        .send(Event::EpochEnding {
+                            epoch,
+                            ends_at_block: block_range.end,
+                        })
+                        .await;
+                }
+            }
+
+            if block_range.end > target {
+                break;
+            }
+
+            self.current_block.store(block_range.end, Ordering::Release);
+            self.current_epoch.store(epoch + 1, Ordering::Release);
+
+            sender
+                .send(Event::epoch_ended(
+                    epoch,
+                    self.epoch_duration,
+                    time_block_timestamp(self.genesis, block_range.start),
+                    time_block_timestamp(self.genesis, block_range.end),
+                ))
+                .await;
+        }
+
+        self.current_block.store(target, Ordering::Release);
+    }
+}
+
+/// Handle advancing a spawned [`ManualClock`].
+#[derive(Clone, Debug)]
+pub struct ManualClockHandle {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl ManualClockHandle {
+    /// Produce the given number of Blocks, ending the Epochs they complete.
+    ///
+    /// Returns the Block height reached, once the Events of the ended Epochs
+    /// are broadcasted.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the Clock task isn't running.
+    pub async fn advance_blocks(&self, blocks: u64) -> Result<u64, ManualClockError> {
+        self.advance(Advance::Blocks(blocks)).await
+    }
+
+    /// Produce the Blocks up to the end of the current Epoch.
+    ///
+    /// Returns the Block height reached, the first one of the next Epoch, once
+    /// the Event of the ended Epoch is broadcasted.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the Clock task isn't running.
+    pub async fn end_epoch(&self) -> Result<u64, ManualClockError> {
+        self.advance(Advance::EndOfEpoch).await
+    }
+
+    async fn advance(&self, advance: Advance) -> Result<u64, ManualClockError> {
+        let (reached, receiver) = oneshot::channel();
+
+        self.commands
+            .send(Command { advance, reached })
+            .map_err(|_| ManualClockError::NotRunning)?;
+
+        receiver.await.map_err(|_| ManualClockError::NotRunning)
+    }
+}
+
+/// Errors related to the advance of a [`ManualClock`].
+#[derive(Debug, thiserror::Error)]
+pub enum ManualClockError {
+    #[error("The Clock task isn't running")]
+    NotRunning,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use chrono::{Duration, Utc};
+    use tokio_util::sync::CancellationToken;
+
+    use super::ManualClockError;
+    use crate::{Clock, Event, ManualClock};
+
+    #[tokio::test]
+    async fn test_manual_clock_ending_notice() {
+        let clock = ManualClock::new(NonZeroU64::new(5).unwrap())
+            .with_ending_notice(NonZeroU64::new(2).unwrap());
+        let handle = clock.handle();
+
+        let clock_ref = clock.spawn(CancellationToken::new()).await.unwrap();
+        let mut recv = clock_ref.subscribe().unwrap();
+
+        handle.advance_blocks(2).await.unwrap();
+        assert!(recv.try_recv().is_err());
+
+        handle.advance_blocks(6).await.unwrap();
+        assert_eq!(
+            recv.try_recv(),
+            Ok(Event::EpochEnding {
+                epoch: 0,
+                ends_at_block: 5
+            })
+        );
+        assert!(matches!(
+            recv.try_recv(),
+            Ok(Event::EpochEnded { epoch: 0, .. })
+        ));
+        assert_eq!(
+            recv.try_recv(),
+            Ok(Event::EpochEnding {
+                epoch: 1,
+                ends_at_block: 10
+            })
+        );
+        assert!(recv.try_recv().is_err());
+
+        handle.end_epoch().await.unwrap();
+        assert!(matches!(
+            recv.try_recv(),
+            Ok(Event::EpochEnded { epoch: 1, .. })
+        ));
+        assert!(recv.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manual_clock() {
+        let genesis = Utc::now();
+        let clock = ManualClock::new(NonZeroU64::new(5).unwrap()).with_genesis(genesis);
+        let handle = clock.handle();
+
+        let token = CancellationToken::new();
+        let clock_ref = clock.spawn(token.clone()).await.unwrap();
+        let mut recv = clock_ref.subscribe().unwrap();
+
+        assert_eq!(handle.advance_blocks(3).await.unwrap(), 3);
+        assert!(recv.try_recv().is_err());
+        assert_eq!(clock_ref.current_block(), 3);
+        assert_eq!(clock_ref.current_epoch(), 0);
+
+        assert_eq!(handle.advance_blocks(8).await.unwrap(), 11);
+        assert_eq!(
+            recv.try_recv(),
+            Ok(Event::EpochEnded {
+                epoch: 0,
+                previous_epoch: None,
+                block_range: 0..5,
+                started_at: genesis,
+                ended_at: genesis + Duration::seconds(5),
+            })
+        );
+        assert!(matches!(
+            recv.try_recv(),
+            Ok(Event::EpochEnded { epoch: 1, .. })
+        ));
+        assert!(recv.try_recv().is_err());
+        assert_eq!(clock_ref.current_block(), 11);
+        assert_eq!(clock_ref.current_epoch(), 2);
+
+        assert_eq!(handle.end_epoch().await.unwrap(), 15);
+        assert!(matches!(
+            recv.try_recv(),
+            Ok(Event::EpochEnded { epoch: 2, .. })
+        ));
+        assert_eq!(clock_ref.current_epoch(), 3);
+
+        token.cancel();
+        while clock_ref.is_running() {
+            tokio::task::yield_now().await;
+        }
+        assert!(matches!(
+            handle.end_epoch().await,
+            Err(ManualClockError::NotRunning)
+        ));
+    }
+}
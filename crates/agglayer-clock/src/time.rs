@@ -1,5 +1,5 @@
 use std::{
-    num::NonZeroU64,
+    num::{NonZeroU64, NonZeroUsize},
     sync::{
         atomic::{AtomicU64, Ordering},
         Arc,
@@ -7,15 +7,17 @@ use std::{
     time::Duration,
 };
 
-use chrono::{DateTime, Utc};
-use tokio::{
-    sync::broadcast,
-    time::{interval_at, Instant},
-};
+use agglayer_telemetry::CLOCK_MISSED_TICKS;
+use chrono::{DateTime, TimeDelta, Utc};
+use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error};
 
-use crate::{Clock, ClockRef, Error, Event, BROADCAST_CHANNEL_SIZE};
+use crate::{
+    checked_time_block_timestamp, epoch_block_range, epoch_ending, replayed_epochs,
+    time_block_timestamp, CatchUp, Clock, ClockConfiguration, ClockRef, Error, Event, EventSender,
+    OverflowPolicy, DEFAULT_BROADCAST_CAPACITY,
+};
 
 /// Time based [`Clock`] implementation.
 ///
@@ -26,18 +28,29 @@ pub struct TimeClock {
     current_block: Arc<AtomicU64>,
     epoch_duration: NonZeroU64,
     current_epoch: Arc<AtomicU64>,
+    catch_up: CatchUp,
+    /// The capacity of the broadcast channel.
+    broadcast_capacity: usize,
+    /// The behavior of the Clock when the broadcast channel is full.
+    overflow_policy: OverflowPolicy,
+    /// The number of Blocks ahead of the end of each Epoch at which its ending
+    /// is notified, if any.
+    ending_notice: Option<NonZeroU64>,
 }
 
 #[async_trait::async_trait]
 impl Clock for TimeClock {
     async fn spawn(mut self, cancellation_token: CancellationToken) -> Result<ClockRef, Error> {
-        let (sender, _receiver) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
+        let (sender, receiver) =
+            EventSender::channel(self.broadcast_capacity, self.overflow_policy);
+        let sender = sender.cancelled_by(cancellation_token.clone());
 
-        let clock_ref = ClockRef {
-            sender: sender.clone(),
-            current_epoch: self.current_epoch.clone(),
-            block_height: self.current_block.clone(),
-        };
+        let clock_ref = sender.clock_ref(
+            receiver,
+            self.current_epoch.clone(),
+            self.current_block.clone(),
+            self.configuration(),
+        );
 
         // Spawn the Clock task directly
         tokio::spawn(async move {
@@ -49,31 +62,104 @@ impl Clock for TimeClock {
 }
 
 impl TimeClock {
+    /// The simulated time between two Blocks.
+    pub const BLOCK_TIME: Duration = Duration::from_secs(1);
+
+    /// How far in the future the genesis datetime is allowed to be. The Clock
+    /// waits for the genesis before producing Blocks.
+    pub const GENESIS_TOLERANCE: Duration = Duration::from_secs(10);
+
     /// Create a new [`TimeClock`] instance based on the current datetime and an
     /// Epoch.
-    pub fn new_now(epoch_duration: NonZeroU64) -> Self {
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the Epoch duration overflows the
+    /// datetime range.
+    pub fn new_now(epoch_duration: NonZeroU64) -> Result<Self, TimeClockError> {
         Self::new(Utc::now(), epoch_duration)
     }
 
     /// Create a new [`TimeClock`] instance based on a genesis datetime and an
     /// Epoch duration.
-    pub fn new(genesis: DateTime<Utc>, epoch_duration: NonZeroU64) -> Self {
-        Self {
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The genesis is further in the future than the
+    ///   [`TimeClock::GENESIS_TOLERANCE`].
+    /// - The end of the first Epoch overflows the datetime range.
+    pub fn new(genesis: DateTime<Utc>, epoch_duration: NonZeroU64) -> Result<Self, TimeClockError> {
+        let tolerance =
+            TimeDelta::from_std(Self::GENESIS_TOLERANCE).expect("The genesis tolerance is valid");
+        if Utc::now()
+            .checked_add_signed(tolerance)
+            .is_some_and(|limit| genesis > limit)
+        {
+            return Err(TimeClockError::GenesisInFuture(genesis));
+        }
+
+        i64::try_from(epoch_duration.get())
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .and_then(|duration| genesis.checked_add_signed(duration))
+            .ok_or(TimeClockError::EpochDurationOverflow(epoch_duration))?;
+
+        Ok(Self {
             genesis,
             current_block: Arc::new(AtomicU64::new(0)),
             epoch_duration,
             current_epoch: Arc::new(AtomicU64::new(0)),
+            catch_up: CatchUp::default(),
+            broadcast_capacity: DEFAULT_BROADCAST_CAPACITY,
+            overflow_policy: OverflowPolicy::default(),
+            ending_notice: None,
+        })
+    }
+
+    /// Set the behavior of the Clock when it starts after some Epochs already
+    /// ended.
+    pub fn with_catch_up(mut self, catch_up: CatchUp) -> Self {
+        self.catch_up = catch_up;
+
+        self
+    }
+
+    /// Set the capacity of the broadcast channel and the behavior of the Clock
+    /// when it is full.
+    pub fn with_broadcast(
+        mut self,
+        capacity: NonZeroUsize,
+        overflow_policy: OverflowPolicy,
+    ) -> Self {
+        self.broadcast_capacity = capacity.get();
+        self.overflow_policy = overflow_policy;
+
+        self
+    }
+
+    /// Notify the ending of each Epoch the given number of Blocks ahead, with
+    /// an [`Event::EpochEnding`](crate::Event::EpochEnding).
+    pub fn with_ending_notice(mut self, notice: NonZeroU64) -> Self {
+        self.ending_notice = Some(notice);
+
+        self
+    }
+
+    /// The configuration of this [`TimeClock`].
+    fn configuration(&self) -> ClockConfiguration {
+        ClockConfiguration::Time {
+            genesis: self.genesis,
+            epoch_duration: self.epoch_duration,
         }
     }
 
     /// Run the Clock task.
-    async fn run(
-        &mut self,
-        sender: broadcast::Sender<Event>,
-        cancellation_token: CancellationToken,
-    ) {
-        let mut interval = interval_at(Instant::now(), Duration::from_secs(1));
-
+    ///
+    /// The Clock ticks on the wall-clock time of each Block since genesis,
+    /// rather than on a fixed interval, so that its Block height never drifts
+    /// from the one derived from the genesis.
+    async fn run(&mut self, sender: EventSender, cancellation_token: CancellationToken) {
         // Compute the current Block height and Epoch number
         let current_block = self.update_block_height();
         let current_epoch = self.calculate_epoch_number(current_block);
@@ -98,49 +184,132 @@ impl TimeClock {
             #[cfg(test)]
             panic!("{}", error_message);
         }
+        sender.start();
+
+        self.catch_up(&sender, current_epoch).await;
+
+        while !cancellation_token.is_cancelled() {
+            // A genesis in the future, within the tolerance, delays the first Block.
+            let Some(next_block_at) = self
+                .current_block
+                .load(Ordering::Acquire)
+                .checked_add(1)
+                .and_then(|next_block| checked_time_block_timestamp(self.genesis, next_block))
+            else {
+                error!(
+                    "Block height overflowed the u64 limit. This is an unexpected situation and \
+                     could lead to unexpected behavior. Please report this issue to the \
+                     developers. https://github.com/agglayer/agglayer/issues/new The node will \
+                     now kill itself to prevent further damage."
+                );
+
+                cancellation_token.cancel();
+                break;
+            };
+            let until_next_block = (next_block_at - Utc::now()).to_std().unwrap_or_default();
 
-        loop {
             tokio::select! {
-                _ = cancellation_token.cancelled() => {
-                    debug!("Clock task cancelled");
-                    break;
+                _ = cancellation_token.cancelled() => break,
+                _ = sleep(until_next_block) => {}
+            }
+
+            // Produce the Blocks up to the one derived from the genesis, the ones beyond
+            // the next one were missed while the Clock task fell behind.
+            let target_block = self.calculate_block_height();
+            let mut current_block = self.current_block.load(Ordering::Acquire);
+            let missed_ticks = target_block.saturating_sub(current_block).saturating_sub(1);
+            if missed_ticks > 0 {
+                CLOCK_MISSED_TICKS.add(missed_ticks, &[]);
+            }
+
+            while current_block < target_block && !cancellation_token.is_cancelled() {
+                current_block += 1;
+                self.current_block.store(current_block, Ordering::Release);
+
+                self.produce_block(&sender, current_block, &cancellation_token)
+                    .await;
+            }
+        }
+
+        debug!("Clock task cancelled");
+    }
+
+    /// Notify the subscribers about the Epoch ended, or whose ending is
+    /// notified, once the given Block height is reached.
+    async fn produce_block(
+        &mut self,
+        sender: &EventSender,
+        current_block: u64,
+        cancellation_token: &CancellationToken,
+    ) {
+        agglayer_telemetry::record_clock_drift(
+            (Utc::now() - self.block_timestamp(current_block)).num_milliseconds() as f64 / 1000.0,
+        );
+
+        // If the current Block height is a multiple of the Epoch duration, the
+        // current Epoch has ended. In this case, we need to update the new Epoch
+        // number and send an `EpochEnded` event to the subscribers.
+        if current_block % self.epoch_duration == 0 {
+            match self.update_epoch_number() {
+                Ok(epoch_ended) => {
+                    let block_range = epoch_block_range(epoch_ended, self.epoch_duration);
+
+                    sender
+                        .send(Event::epoch_ended(
+                            epoch_ended,
+                            self.epoch_duration,
+                            self.block_timestamp(block_range.start),
+                            self.block_timestamp(block_range.end),
+                        ))
+                        .await;
                 }
-                _ = interval.tick() => {
-
-                    // Increase the Block height by 1.
-                    // The `fetch_add` method returns the previous value, so we need to add 1 to it
-                    // to get the current Block height.
-                    if let Some(current_block) = self
-                        .current_block
-                            .fetch_add(1, Ordering::Release)
-                            .checked_add(1)
-                    {
-                        // If the current Block height is a multiple of the Epoch duration,
-                        // the current Epoch has ended. In this case, we need to update the
-                        // new Epoch number and send an `EpochEnded` event to the subscribers.
-                        if current_block % self.epoch_duration == 0 {
-                            match self.update_epoch_number() {
-                                Ok(epoch_ended) => {
-                                    _ = sender.send(Event::EpochEnded(epoch_ended));
-                                }
-                                Err((current_epoch, expected)) => {
-                                    error!(
-                                        "Unexpected error computing the current Epoch: current_epoch={}, \
-                                        expected_epoch={}, current_block={}",
-                                        current_epoch, expected, current_block
-                                    );
-                                    cancellation_token.cancel();
-                                }
-                            }
-                        }
-                    } else {
-                       error!("Block height overflowed the u64 limit. \
-                           This is an unexpected situation and could lead to unexpected behavior. \
-                           Please report this issue to the developers. https://github.com/agglayer/agglayer/issues/new \
-                           The node will now kill itself to prevent further damage.");
-
-                        cancellation_token.cancel();
-                    }
+                Err((current_epoch, expected)) => {
+                    error!(
+                        "Unexpected error computing the current Epoch: current_epoch={}, \
+                         expected_epoch={}, current_block={}",
+                        current_epoch, expected, current_block
+                    );
+                    cancellation_token.cancel();
+                }
+            }
+        }
+
+        // Notify the ending of the current Epoch ahead, if configured.
+        if let Some(event) = self
+            .ending_notice
+            .and_then(|notice| epoch_ending(current_block, self.epoch_duration, notice))
+        {
+            sender.send(event).await;
+        }
+    }
+
+    /// Notify the subscribers about the Epochs that ended before the Clock
+    /// started, according to the [`CatchUp`] behavior.
+    async fn catch_up(&self, sender: &EventSender, current_epoch: u64) {
+        if current_epoch == 0 {
+            return;
+        }
+
+        match self.catch_up {
+            CatchUp::Resync => {
+                sender
+                    .send(Event::Resynced {
+                        epoch: current_epoch,
+                    })
+                    .await;
+            }
+            CatchUp::Replay => {
+                for epoch in replayed_epochs(current_epoch, self.broadcast_capacity) {
+                    let block_range = epoch_block_range(epoch, self.epoch_duration);
+
+                    sender
+                        .send(Event::epoch_ended(
+                            epoch,
+                            self.epoch_duration,
+                            self.block_timestamp(block_range.start),
+                            self.block_timestamp(block_range.end),
+                        ))
+                        .await;
                 }
             }
         }
@@ -189,27 +358,46 @@ impl TimeClock {
         from_block / self.epoch_duration
     }
 
+    /// Calculate the time at which a Block height is reached.
+    fn block_timestamp(&self, block_height: u64) -> DateTime<Utc> {
+        time_block_timestamp(self.genesis, block_height)
+    }
+
     /// Calculate the Block height.
+    ///
+    /// The Block height stays at 0 until the genesis is reached.
     fn calculate_block_height(&self) -> u64 {
-        std::cmp::max(
-            Utc::now()
-                .naive_utc()
-                .signed_duration_since(self.genesis.naive_utc())
-                .num_seconds(),
-            0,
-        ) as u64
+        u64::try_from(Utc::now().signed_duration_since(self.genesis).num_seconds()).unwrap_or(0)
     }
 }
 
+/// Errors related to the configuration of a [`TimeClock`].
+#[derive(Debug, thiserror::Error)]
+pub enum TimeClockError {
+    #[error(
+        "The genesis {0} is too far in the future, the tolerance is {}s",
+        TimeClock::GENESIS_TOLERANCE.as_secs()
+    )]
+    GenesisInFuture(DateTime<Utc>),
+    #[error("The Epoch duration of {0} Blocks overflows the datetime range")]
+    EpochDurationOverflow(NonZeroU64),
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{num::NonZeroU64, sync::atomic::Ordering};
+    use std::{
+        num::{NonZeroU64, NonZeroUsize},
+        sync::atomic::Ordering,
+    };
 
     use chrono::{Duration, Utc};
-    use tokio::sync::broadcast;
+    use tokio::sync::broadcast::error::RecvError;
     use tokio_util::sync::CancellationToken;
 
-    use crate::{Clock, ClockRef, Event, TimeClock, BROADCAST_CHANNEL_SIZE};
+    use super::TimeClockError;
+    use crate::{
+        CatchUp, Clock, Event, EventSender, OverflowPolicy, TimeClock, DEFAULT_BROADCAST_CAPACITY,
+    };
 
     #[tokio::test]
     async fn test_time_clock() {
@@ -217,16 +405,64 @@ mod tests {
             .checked_sub_signed(Duration::seconds(30))
             .unwrap();
 
-        let clock = TimeClock::new(genesis, NonZeroU64::new(5).unwrap());
+        let clock = TimeClock::new(genesis, NonZeroU64::new(5).unwrap()).unwrap();
 
         let token = CancellationToken::new();
         let clock_ref = clock.spawn(token.clone()).await.unwrap();
 
         let mut recv = clock_ref.subscribe().unwrap();
 
-        assert_eq!(recv.recv().await, Ok(Event::EpochEnded(6)));
+        assert_eq!(recv.recv().await, Ok(Event::Resynced { epoch: 6 }));
+        assert_eq!(
+            recv.recv().await,
+            Ok(Event::EpochEnded {
+                epoch: 6,
+                previous_epoch: Some(5),
+                block_range: 30..35,
+                started_at: genesis + Duration::seconds(30),
+                ended_at: genesis + Duration::seconds(35),
+            })
+        );
         assert_eq!(clock_ref.current_epoch(), 7);
-        assert!(clock_ref.current_block_height() >= 30);
+        assert!(clock_ref.current_block() >= 30);
+    }
+
+    #[tokio::test]
+    async fn test_time_clock_keeps_up_with_genesis() {
+        let genesis = Utc::now()
+            .checked_sub_signed(Duration::milliseconds(10_500))
+            .unwrap();
+        let genesis_height = || (Utc::now() - genesis).num_seconds() as u64;
+
+        let clock = TimeClock::new(genesis, NonZeroU64::new(5).unwrap()).unwrap();
+        let clock_ref = clock.spawn(CancellationToken::new()).await.unwrap();
+
+        // Stall the runtime, so that the Clock task misses its ticks.
+        std::thread::sleep(std::time::Duration::from_secs(3));
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+        let before = genesis_height();
+        let current_block = clock_ref.current_block();
+        assert!(before <= current_block && current_block <= genesis_height());
+        assert_eq!(clock_ref.current_epoch(), current_block / 5);
+    }
+
+    #[tokio::test]
+    async fn test_time_clock_stops_running_once_cancelled() {
+        let clock = TimeClock::new_now(NonZeroU64::new(5).unwrap()).unwrap();
+
+        let token = CancellationToken::new();
+        let clock_ref = clock.spawn(token.clone()).await.unwrap();
+        assert!(clock_ref.is_running());
+
+        token.cancel();
+        tokio::time::timeout(std::time::Duration::from_secs(1), async {
+            while clock_ref.is_running() {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("The Clock task stops once cancelled");
     }
 
     #[tokio::test]
@@ -235,23 +471,174 @@ mod tests {
             .checked_sub_signed(Duration::seconds(30))
             .unwrap();
 
-        let clock = TimeClock::new(genesis, NonZeroU64::new(2).unwrap());
+        let clock = TimeClock::new(genesis, NonZeroU64::new(2).unwrap()).unwrap();
 
         let token = CancellationToken::new();
         let clock_ref = clock.spawn(token.clone()).await.unwrap();
 
         let mut recv = clock_ref.subscribe().unwrap();
-        assert_eq!(recv.recv().await, Ok(Event::EpochEnded(15)));
+        assert_eq!(recv.recv().await, Ok(Event::Resynced { epoch: 15 }));
+        assert!(matches!(
+            recv.recv().await,
+            Ok(Event::EpochEnded { epoch: 15, .. })
+        ));
         assert!(recv.try_recv().is_err());
         assert_eq!(clock_ref.current_epoch(), 16);
-        assert!(clock_ref.current_block_height() >= 30);
+        assert!(clock_ref.current_block() >= 30);
         tokio::time::sleep(std::time::Duration::from_secs(5)).await;
 
-        assert_eq!(recv.recv().await, Ok(Event::EpochEnded(16)));
-        assert_eq!(recv.recv().await, Ok(Event::EpochEnded(17)));
+        assert!(matches!(
+            recv.recv().await,
+            Ok(Event::EpochEnded { epoch: 16, .. })
+        ));
+        assert!(matches!(
+            recv.recv().await,
+            Ok(Event::EpochEnded { epoch: 17, .. })
+        ));
 
         assert_eq!(clock_ref.current_epoch(), 18);
-        assert!(clock_ref.current_block_height() >= 35);
+        assert!(clock_ref.current_block() >= 35);
+    }
+
+    #[tokio::test]
+    async fn test_time_clock_replay() {
+        let genesis = Utc::now()
+            .checked_sub_signed(Duration::seconds(30))
+            .unwrap();
+
+        let clock = TimeClock::new(genesis, NonZeroU64::new(5).unwrap())
+            .unwrap()
+            .with_catch_up(CatchUp::Replay);
+
+        let token = CancellationToken::new();
+        let clock_ref = clock.spawn(token.clone()).await.unwrap();
+
+        let mut recv = clock_ref.subscribe().unwrap();
+
+        assert_eq!(
+            recv.recv().await,
+            Ok(Event::EpochEnded {
+                epoch: 0,
+                previous_epoch: None,
+                block_range: 0..5,
+                started_at: genesis,
+                ended_at: genesis + Duration::seconds(5),
+            })
+        );
+        for expected in 1..=6 {
+            assert!(matches!(
+                recv.recv().await,
+                Ok(Event::EpochEnded { epoch, .. }) if epoch == expected
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_time_clock_late_subscriber() {
+        let genesis = Utc::now()
+            .checked_sub_signed(Duration::seconds(30))
+            .unwrap();
+
+        let clock = TimeClock::new(genesis, NonZeroU64::new(5).unwrap()).unwrap();
+
+        let token = CancellationToken::new();
+        let clock_ref = clock.spawn(token.clone()).await.unwrap();
+
+        let mut first = clock_ref.subscribe().unwrap();
+        assert_eq!(first.recv().await, Ok(Event::Resynced { epoch: 6 }));
+
+        let mut late = clock_ref.subscribe().unwrap();
+        assert!(matches!(
+            late.recv().await,
+            Ok(Event::EpochEnded { epoch: 6, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_time_clock_drop_oldest() {
+        let genesis = Utc::now()
+            .checked_sub_signed(Duration::seconds(30))
+            .unwrap();
+
+        let clock = TimeClock::new(genesis, NonZeroU64::new(1).unwrap())
+            .unwrap()
+            .with_broadcast(NonZeroUsize::new(1).unwrap(), OverflowPolicy::DropOldest);
+
+        let token = CancellationToken::new();
+        let clock_ref = clock.spawn(token.clone()).await.unwrap();
+
+        let mut recv = clock_ref.subscribe().unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+
+        assert!(matches!(recv.recv().await, Err(RecvError::Lagged(_))));
+    }
+
+    #[tokio::test]
+    async fn test_time_clock_block() {
+        let genesis = Utc::now()
+            .checked_sub_signed(Duration::seconds(30))
+            .unwrap();
+
+        let clock = TimeClock::new(genesis, NonZeroU64::new(1).unwrap())
+            .unwrap()
+            .with_broadcast(NonZeroUsize::new(1).unwrap(), OverflowPolicy::Block);
+
+        let token = CancellationToken::new();
+        let clock_ref = clock.spawn(token.clone()).await.unwrap();
+
+        let mut recv = clock_ref.subscribe().unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+
+        assert_eq!(recv.recv().await, Ok(Event::Resynced { epoch: 30 }));
+        assert!(matches!(
+            recv.recv().await,
+            Ok(Event::EpochEnded { epoch: 30, .. })
+        ));
+        assert!(matches!(
+            recv.recv().await,
+            Ok(Event::EpochEnded { epoch: 31, .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_genesis() {
+        let genesis = Utc::now() + Duration::seconds(60);
+
+        assert!(matches!(
+            TimeClock::new(genesis, NonZeroU64::new(5).unwrap()),
+            Err(TimeClockError::GenesisInFuture(future)) if future == genesis
+        ));
+        assert!(matches!(
+            TimeClock::new(Utc::now(), NonZeroU64::MAX),
+            Err(TimeClockError::EpochDurationOverflow(NonZeroU64::MAX))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_time_clock_waits_for_genesis() {
+        let genesis = Utc::now() + Duration::seconds(2);
+
+        let clock = TimeClock::new(genesis, NonZeroU64::new(2).unwrap()).unwrap();
+
+        let token = CancellationToken::new();
+        let clock_ref = clock.spawn(token.clone()).await.unwrap();
+
+        let mut recv = clock_ref.subscribe().unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        assert_eq!(clock_ref.current_block(), 0);
+
+        assert_eq!(
+            recv.recv().await,
+            Ok(Event::EpochEnded {
+                epoch: 0,
+                previous_epoch: None,
+                block_range: 0..2,
+                started_at: genesis,
+                ended_at: genesis + Duration::seconds(2),
+            })
+        );
+        assert!(Utc::now() >= genesis + Duration::milliseconds(1900));
     }
 
     #[tokio::test]
@@ -260,15 +647,17 @@ mod tests {
             .checked_sub_signed(Duration::seconds(30))
             .unwrap();
 
-        let mut clock = TimeClock::new(genesis, NonZeroU64::new(2).unwrap());
+        let mut clock = TimeClock::new(genesis, NonZeroU64::new(2).unwrap()).unwrap();
         let blocks = clock.current_block.clone();
-        let (sender, _receiver) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
+        let (sender, receiver) =
+            EventSender::channel(DEFAULT_BROADCAST_CAPACITY, OverflowPolicy::DropOldest);
 
-        let clock_ref = ClockRef {
-            sender: sender.clone(),
-            current_epoch: clock.current_epoch.clone(),
-            block_height: clock.current_block.clone(),
-        };
+        let clock_ref = sender.clock_ref(
+            receiver,
+            clock.current_epoch.clone(),
+            clock.current_block.clone(),
+            clock.configuration(),
+        );
 
         let token = CancellationToken::new();
         let mut fut = Box::pin(clock.run(sender, token.clone()));
@@ -278,7 +667,11 @@ mod tests {
         tokio::time::sleep(std::time::Duration::from_secs(2)).await;
 
         _ = futures::poll!(&mut fut);
-        assert_eq!(recv.try_recv(), Ok(Event::EpochEnded(15)));
+        assert_eq!(recv.try_recv(), Ok(Event::Resynced { epoch: 15 }));
+        assert!(matches!(
+            recv.try_recv(),
+            Ok(Event::EpochEnded { epoch: 15, .. })
+        ));
 
         // Overflow the block height on next poll
         blocks.store(u64::MAX - 1, std::sync::atomic::Ordering::SeqCst);
@@ -296,10 +689,11 @@ mod tests {
             .checked_sub_signed(Duration::seconds(30))
             .unwrap();
 
-        let mut clock = TimeClock::new(genesis, NonZeroU64::new(5).unwrap());
+        let mut clock = TimeClock::new(genesis, NonZeroU64::new(5).unwrap()).unwrap();
         clock.current_epoch.store(1, Ordering::Relaxed);
 
-        let (sender, _receiver) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
+        let (sender, _receiver) =
+            EventSender::channel(DEFAULT_BROADCAST_CAPACITY, OverflowPolicy::DropOldest);
 
         let token = CancellationToken::new();
         let _ = clock.run(sender, token).await;
@@ -0,0 +1,54 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use serde::Deserialize;
+
+/// The admin RPC server configuration.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct AdminConfig {
+    /// The socket address of the admin RPC server. If absent, the admin RPC
+    /// server is disabled. It is meant to be reachable by the operators only,
+    /// on a private or local interface.
+    #[serde(default)]
+    pub listen: Option<SocketAddr>,
+    /// The file persisting the rollups registered at runtime. If present, the
+    /// persisted rollups take precedence over the `FullNodeRPCs`,
+    /// `ProofFormats` and `ShadowRollups` of the configuration.
+    #[serde(default)]
+    pub registry_path: Option<PathBuf>,
+    /// The file persisting the rollups allowed and denied at runtime.
+    #[serde(default)]
+    pub access_list_path: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_is_disabled_by_default() {
+        let config = toml::from_str::<AdminConfig>("").unwrap();
+
+        assert!(config.listen.is_none());
+        assert!(config.registry_path.is_none());
+        assert!(config.access_list_path.is_none());
+
+        let toml = r#"
+            Listen = "127.0.0.1:9091"
+            RegistryPath = "/data/rollups.json"
+            AccessListPath = "/data/access-list.json"
+            "#;
+
+        let config = toml::from_str::<AdminConfig>(toml).unwrap();
+
+        assert_eq!(config.listen, Some("127.0.0.1:9091".parse().unwrap()));
+        assert_eq!(
+            config.registry_path,
+            Some(PathBuf::from("/data/rollups.json"))
+        );
+        assert_eq!(
+            config.access_list_path,
+            Some(PathBuf::from("/data/access-list.json"))
+        );
+    }
+}
@@ -1,8 +1,12 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
+use ethers::types::Address;
 use serde::de::{self, Deserializer};
 use serde::Deserialize;
-use serde_with::{serde_as, NoneAsEmptyString};
+use serde_with::{serde_as, DurationSeconds, NoneAsEmptyString};
+use url::Url;
+
+use crate::secret::{deserialize_optional_secret, deserialize_secret};
 
 /// The transaction management configuration.
 ///
@@ -23,6 +27,10 @@ use serde_with::{serde_as, NoneAsEmptyString};
 pub enum AuthConfig {
     Local(LocalConfig),
     GcpKms(GcpKmsConfig),
+    Vault(VaultConfig),
+    AzureKeyVault(AzureKeyVaultConfig),
+    Web3Signer(Web3SignerConfig),
+    Ledger(LedgerConfig),
 }
 
 impl Default for AuthConfig {
@@ -33,7 +41,8 @@ impl Default for AuthConfig {
 
 /// Local configuration.
 ///
-/// It includes private keys for a local wallet.
+/// It includes private keys for a local wallet, as encrypted JSON keystores
+/// (scrypt or pbkdf2).
 #[serde_as]
 #[derive(Deserialize, Debug, Default)]
 #[serde(rename_all = "PascalCase")]
@@ -45,8 +54,20 @@ pub struct LocalConfig {
 #[cfg_attr(any(test, feature = "testutils"), derive(Default))]
 #[serde(rename_all = "PascalCase")]
 pub struct PrivateKey {
+    /// The encrypted JSON keystore.
     pub path: PathBuf,
+    /// The passphrase of the keystore, used if neither `PasswordEnv` nor
+    /// `PasswordFile` is set. It may be given as a `file://` path.
+    #[serde(default, deserialize_with = "deserialize_secret")]
     pub password: String,
+    /// The environment variable holding the passphrase of the keystore. Takes
+    /// precedence over `PasswordFile` and `Password`.
+    #[serde(default)]
+    pub password_env: Option<String>,
+    /// The file holding the passphrase of the keystore, with any trailing
+    /// newline ignored. Takes precedence over `Password`.
+    #[serde(default)]
+    pub password_file: Option<PathBuf>,
 }
 
 /// GCP KMS configuration.
@@ -78,6 +99,154 @@ pub struct GcpKmsConfig {
     pub key_version: Option<u64>,
 }
 
+/// HashiCorp Vault transit configuration.
+///
+/// The settlement transactions are signed by a secp256k1 key of a transit
+/// secrets engine, which never leaves Vault. The stock transit engine lacks
+/// secp256k1 keys, the mount must be served by a transit-compatible plugin
+/// supporting them.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct VaultConfig {
+    /// The address of the Vault server. Defaults to the `VAULT_ADDR`
+    /// environment variable.
+    #[serde(default)]
+    pub address: Option<Url>,
+    /// The Vault Enterprise namespace of the transit mount and of the auth
+    /// method, if any. Defaults to the `VAULT_NAMESPACE` environment variable.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// The path the transit secrets engine is mounted at.
+    #[serde(default = "default_transit_mount")]
+    pub mount: String,
+    /// The name of the transit key.
+    pub key_name: String,
+    /// The version of the transit key signing the transactions. Defaults to
+    /// the latest version.
+    #[serde(default)]
+    pub key_version: Option<u64>,
+    /// The authentication to Vault.
+    #[serde(default)]
+    pub auth: VaultAuth,
+}
+
+/// The authentication to Vault, whose token is renewed in the background
+/// before it expires.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "Method")]
+pub enum VaultAuth {
+    /// A token, defaulting to the `VAULT_TOKEN` environment variable. It may
+    /// be given as a `file://` path, e.g. the sink of a Vault agent.
+    Token {
+        #[serde(
+            rename = "Token",
+            default,
+            deserialize_with = "deserialize_optional_secret"
+        )]
+        token: Option<String>,
+    },
+    /// An AppRole login, logged in again once the token can no longer be
+    /// renewed.
+    AppRole {
+        /// The path the AppRole auth method is mounted at.
+        #[serde(rename = "Mount", default = "default_approle_mount")]
+        mount: String,
+        #[serde(rename = "RoleId")]
+        role_id: String,
+        /// The secret ID of the role. It may be given as a `file://` path.
+        #[serde(rename = "SecretId", deserialize_with = "deserialize_secret")]
+        secret_id: String,
+    },
+}
+
+impl Default for VaultAuth {
+    fn default() -> Self {
+        Self::Token { token: None }
+    }
+}
+
+/// Azure Key Vault configuration.
+///
+/// The settlement transactions are signed by a secp256k1 (`P-256K`) key of the
+/// Key Vault, which never leaves it. The signer authenticates with the managed
+/// identity of the host.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct AzureKeyVaultConfig {
+    /// The URL of the Key Vault, e.g. `https://name.vault.azure.net`.
+    pub vault_url: Url,
+    /// The name of the key.
+    pub key_name: String,
+    /// The version of the key signing the transactions. Defaults to the
+    /// latest version.
+    #[serde(default)]
+    pub key_version: Option<String>,
+    /// The client ID of the user-assigned managed identity. Defaults to the
+    /// `AZURE_CLIENT_ID` environment variable, or to the system-assigned
+    /// managed identity if unset.
+    #[serde(default)]
+    pub client_id: Option<String>,
+}
+
+/// Web3Signer configuration.
+///
+/// The settlement transactions are signed by a remote Web3Signer, or a
+/// Consensys EthSigner, over its `eth1` JSON-RPC API, the keys never residing
+/// on the agglayer host.
+#[serde_as]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct Web3SignerConfig {
+    /// The URL of the signer, using TLS if it is an `https` one.
+    pub url: Url,
+    /// The address of the key to sign with. Defaults to the first account
+    /// served by the signer.
+    #[serde(default)]
+    pub address: Option<Address>,
+    /// The timeout of the requests to the signer, in seconds.
+    #[serde_as(as = "DurationSeconds")]
+    #[serde(default = "default_web3signer_timeout")]
+    pub timeout: Duration,
+    /// A PEM encoded CA certificate trusted in addition to the system ones,
+    /// e.g. the one of a self-signed signer.
+    #[serde(default)]
+    pub ca_cert: Option<PathBuf>,
+    /// A PEM file holding the client certificate and its private key, used to
+    /// authenticate to a signer requiring mutual TLS.
+    #[serde(default)]
+    pub client_identity: Option<PathBuf>,
+}
+
+/// Ledger configuration.
+///
+/// The settlement transactions are signed by a Ledger device connected to the
+/// host, each of them having to be confirmed on the device. This is meant for
+/// low-volume or emergency manual operation, and requires the agglayer to be
+/// built with the `ledger` feature.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct LedgerConfig {
+    /// The BIP-32 derivation path of the key on the device.
+    #[serde(default = "default_ledger_derivation_path")]
+    pub derivation_path: String,
+}
+
+fn default_ledger_derivation_path() -> String {
+    "m/44'/60'/0'/0/0".to_string()
+}
+
+fn default_web3signer_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+fn default_transit_mount() -> String {
+    "transit".to_string()
+}
+
+fn default_approle_mount() -> String {
+    "approle".to_string()
+}
+
 // This is a workaround to support `EthTxManager` for PrivateKeys as it is used
 // by kurtosis.
 #[derive(Deserialize)]
@@ -86,13 +255,23 @@ struct IntermediateAuthConfig {
     local: Option<LocalConfig>,
     #[serde(default)]
     gcpkms: Option<GcpKmsConfig>,
+    #[serde(default)]
+    vault: Option<VaultConfig>,
+    #[serde(default)]
+    azurekeyvault: Option<AzureKeyVaultConfig>,
+    #[serde(default)]
+    web3signer: Option<Web3SignerConfig>,
+    #[serde(default)]
+    ledger: Option<LedgerConfig>,
     #[serde(default, rename = "PrivateKeys")]
     private_keys: Option<Vec<PrivateKey>>,
     #[serde(flatten)]
     kms: Option<GcpKmsConfig>,
 }
 
-pub(crate) fn deserialize_auth<'de, D>(deserializer: D) -> Result<AuthConfig, D::Error>
+/// Deserialize the `auth` section of the configuration, keyed by the signing
+/// backend, e.g. `gcpkms`, or in the legacy `PrivateKeys` form.
+pub fn deserialize_auth<'de, D>(deserializer: D) -> Result<AuthConfig, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -102,6 +281,14 @@ where
         Ok(AuthConfig::Local(local))
     } else if let Some(gcpkms) = intermediate.gcpkms {
         Ok(AuthConfig::GcpKms(gcpkms))
+    } else if let Some(vault) = intermediate.vault {
+        Ok(AuthConfig::Vault(vault))
+    } else if let Some(azure) = intermediate.azurekeyvault {
+        Ok(AuthConfig::AzureKeyVault(azure))
+    } else if let Some(web3signer) = intermediate.web3signer {
+        Ok(AuthConfig::Web3Signer(web3signer))
+    } else if let Some(ledger) = intermediate.ledger {
+        Ok(AuthConfig::Ledger(ledger))
     } else if let Some(private_keys) = intermediate.private_keys {
         Ok(AuthConfig::Local(LocalConfig { private_keys }))
     } else if let Some(kms) = intermediate.kms {
@@ -110,3 +297,148 @@ where
         Err(de::Error::custom("Invalid auth configuration"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Wrapper {
+        #[serde(deserialize_with = "deserialize_auth")]
+        auth: AuthConfig,
+    }
+
+    #[test]
+    fn deserialize_keystore_passphrase_sources() {
+        let toml = r#"
+            [auth]
+            PrivateKeys = [
+                { Path = "/pk/inline.keystore", Password = "testonly" },
+                { Path = "/pk/env.keystore", PasswordEnv = "AGGLAYER_KEYSTORE_PASSWORD" },
+                { Path = "/pk/file.keystore", PasswordFile = "/run/secrets/keystore" },
+            ]
+            "#;
+
+        let AuthConfig::Local(local) = toml::from_str::<Wrapper>(toml).unwrap().auth else {
+            panic!("expected a local configuration");
+        };
+
+        assert_eq!(local.private_keys[0].password, "testonly");
+        assert!(local.private_keys[0].password_env.is_none());
+        assert_eq!(
+            local.private_keys[1].password_env.as_deref(),
+            Some("AGGLAYER_KEYSTORE_PASSWORD")
+        );
+        assert!(local.private_keys[1].password.is_empty());
+        assert_eq!(
+            local.private_keys[2].password_file,
+            Some(PathBuf::from("/run/secrets/keystore"))
+        );
+    }
+
+    #[test]
+    fn deserialize_vault() {
+        let toml = r#"
+            [auth.vault]
+            Address = "https://vault:8200"
+            KeyName = "agglayer"
+            "#;
+
+        let AuthConfig::Vault(vault) = toml::from_str::<Wrapper>(toml).unwrap().auth else {
+            panic!("expected a vault configuration");
+        };
+
+        assert_eq!(vault.address.unwrap().as_str(), "https://vault:8200/");
+        assert_eq!(vault.mount, "transit");
+        assert_eq!(vault.key_name, "agglayer");
+        assert!(vault.key_version.is_none());
+        assert!(matches!(vault.auth, VaultAuth::Token { token: None }));
+
+        let toml = r#"
+            [auth.vault]
+            Mount = "ethereum"
+            KeyName = "agglayer"
+            KeyVersion = 2
+            Auth = { Method = "AppRole", RoleId = "agglayer", SecretId = "secret" }
+            "#;
+
+        let AuthConfig::Vault(vault) = toml::from_str::<Wrapper>(toml).unwrap().auth else {
+            panic!("expected a vault configuration");
+        };
+
+        assert_eq!(vault.mount, "ethereum");
+        assert_eq!(vault.key_version, Some(2));
+        assert!(matches!(
+            vault.auth,
+            VaultAuth::AppRole { mount, role_id, secret_id }
+                if mount == "approle" && role_id == "agglayer" && secret_id == "secret"
+        ));
+    }
+
+    #[test]
+    fn deserialize_azure_key_vault() {
+        let toml = r#"
+            [auth.azurekeyvault]
+            VaultUrl = "https://agglayer.vault.azure.net"
+            KeyName = "agglayer"
+            "#;
+
+        let AuthConfig::AzureKeyVault(azure) = toml::from_str::<Wrapper>(toml).unwrap().auth else {
+            panic!("expected an azure key vault configuration");
+        };
+
+        assert_eq!(
+            azure.vault_url.as_str(),
+            "https://agglayer.vault.azure.net/"
+        );
+        assert_eq!(azure.key_name, "agglayer");
+        assert!(azure.key_version.is_none());
+        assert!(azure.client_id.is_none());
+    }
+
+    #[test]
+    fn deserialize_web3signer() {
+        let toml = r#"
+            [auth.web3signer]
+            Url = "https://web3signer:9000"
+            Address = "0x0000000000000000000000000000000000000001"
+            CaCert = "/etc/agglayer/web3signer-ca.pem"
+            "#;
+
+        let AuthConfig::Web3Signer(web3signer) = toml::from_str::<Wrapper>(toml).unwrap().auth
+        else {
+            panic!("expected a web3signer configuration");
+        };
+
+        assert_eq!(web3signer.url.as_str(), "https://web3signer:9000/");
+        assert_eq!(web3signer.address, Some(Address::from_low_u64_be(1)));
+        assert_eq!(web3signer.timeout, Duration::from_secs(10));
+        assert_eq!(
+            web3signer.ca_cert,
+            Some(PathBuf::from("/etc/agglayer/web3signer-ca.pem"))
+        );
+        assert!(web3signer.client_identity.is_none());
+    }
+
+    #[test]
+    fn deserialize_ledger() {
+        let toml = r#"
+            [auth.ledger]
+            "#;
+
+        let AuthConfig::Ledger(ledger) = toml::from_str::<Wrapper>(toml).unwrap().auth else {
+            panic!("expected a ledger configuration");
+        };
+        assert_eq!(ledger.derivation_path, "m/44'/60'/0'/0/0");
+
+        let toml = r#"
+            [auth.ledger]
+            DerivationPath = "m/44'/60'/1'/0/0"
+            "#;
+
+        let AuthConfig::Ledger(ledger) = toml::from_str::<Wrapper>(toml).unwrap().auth else {
+            panic!("expected a ledger configuration");
+        };
+        assert_eq!(ledger.derivation_path, "m/44'/60'/1'/0/0");
+    }
+}
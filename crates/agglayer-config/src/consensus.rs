@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// The consensus of a rollup, which determines the pipeline serving its
+/// submissions.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConsensusType {
+    /// Full execution proofs submitted through `interop_sendTx`, verified
+    /// against the ZkEVM node and settled with
+    /// `verifyBatchesTrustedAggregator`.
+    #[default]
+    Fep,
+    /// Certificates submitted through `interop_sendCertificate`, proven by
+    /// the pessimistic proof pipeline.
+    ///
+    /// The certificates are rejected as unsupported until the rollup manager
+    /// contract verifies the pessimistic proofs.
+    Pessimistic,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use serde_with::{serde_as, DisplayFromStr};
+
+    use super::*;
+
+    #[serde_as]
+    #[derive(Deserialize)]
+    struct Rollups {
+        #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+        #[serde(rename = "ConsensusTypes")]
+        consensus_types: HashMap<u32, ConsensusType>,
+    }
+
+    #[test]
+    fn deserialize_consensus_types() {
+        let toml = r#"
+            [ConsensusTypes]
+            1 = "Fep"
+            2 = "Pessimistic"
+            "#;
+
+        let rollups = toml::from_str::<Rollups>(toml).unwrap();
+
+        assert_eq!(rollups.consensus_types[&1], ConsensusType::Fep);
+        assert_eq!(rollups.consensus_types[&2], ConsensusType::Pessimistic);
+    }
+}
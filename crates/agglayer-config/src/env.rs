@@ -0,0 +1,313 @@
+//! Overrides of the configuration by environment variables.
+//!
+//! Every field of the configuration can be overridden by an environment
+//! variable prefixed with `AGGLAYER_`, the nested fields being separated by
+//! `__`, e.g. `AGGLAYER_RPC__PORT=9090` or
+//! `AGGLAYER_L1__ROLLUP_MANAGER_CONTRACT=0x...`.
+//!
+//! The names of the fields are matched regardless of their case and
+//! underscores, so that `AGGLAYER_FULL_NODE_RPCS__1` overrides the endpoint of
+//! rollup 1 in `FullNodeRPCs`. The values are parsed as TOML values, falling
+//! back to strings, so that `AGGLAYER_RPC__ASYNC_SETTLEMENT=true` sets a
+//! boolean and `AGGLAYER_L1__NODE_URL=http://l1:8545` a string.
+use serde::{
+    de::{
+        value::{MapDeserializer, SeqDeserializer},
+        IntoDeserializer, Visitor,
+    },
+    forward_to_deserialize_any, Deserializer,
+};
+use toml::{Table, Value};
+
+/// The prefix of the environment variables overriding the configuration.
+pub(crate) const ENV_PREFIX: &str = "AGGLAYER_";
+
+/// The separator of the nested fields in the names of the environment
+/// variables.
+pub(crate) const ENV_SEPARATOR: &str = "__";
+
+/// Normalize the name of a field, ignoring its case and underscores.
+fn normalize(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Parse the value of an environment variable as a TOML value, falling back to
+/// a string.
+///
+/// The `0x`-prefixed values are kept as strings, as they're addresses or keys
+/// rather than hexadecimal integers.
+fn parse_value(raw: &str) -> Value {
+    if raw.starts_with("0x") {
+        return Value::String(raw.to_string());
+    }
+
+    toml::from_str::<Table>(&format!("value = {raw}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+/// Override the given configuration table with the given environment
+/// variables, ignoring the ones not prefixed with [`ENV_PREFIX`].
+pub(crate) fn apply_overrides(table: &mut Table, vars: impl IntoIterator<Item = (String, String)>) {
+    for (name, raw) in vars {
+        let Some(path) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let keys: Vec<&str> = path.split(ENV_SEPARATOR).collect();
+        if keys.iter().any(|key| key.is_empty()) {
+            continue;
+        }
+
+        let (last, parents) = keys.split_last().expect("split yields a key");
+        let mut current = &mut *table;
+        for key in parents {
+            let key = matching_key(current, key);
+            let entry = current
+                .entry(key)
+                .or_insert_with(|| Value::Table(Table::new()));
+            if !entry.is_table() {
+                *entry = Value::Table(Table::new());
+            }
+            current = entry.as_table_mut().expect("entry is a table");
+        }
+
+        let key = matching_key(current, last);
+        current.insert(key, parse_value(&raw));
+    }
+}
+
+/// Get the key of the given table matching the given name regardless of its
+/// case and underscores, or the name itself if none matches.
+fn matching_key(table: &Table, name: &str) -> String {
+    let normalized = normalize(name);
+
+    table
+        .keys()
+        .find(|key| normalize(key) == normalized)
+        .cloned()
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// A deserializer of a TOML value matching the names of the struct fields
+/// regardless of their case and underscores, as named by the environment
+/// variables.
+pub(crate) struct EnvDeserializer(pub(crate) Value);
+
+impl EnvDeserializer {
+    fn visit_table<'de, V: Visitor<'de>>(
+        table: Table,
+        visitor: V,
+    ) -> Result<V::Value, toml::de::Error> {
+        let mut map = MapDeserializer::new(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, EnvDeserializer(value))),
+        );
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+
+        Ok(value)
+    }
+}
+
+impl<'de> Deserializer<'de> for EnvDeserializer {
+    type Error = toml::de::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Table(table) => Self::visit_table(table, visitor),
+            Value::Array(array) => {
+                let mut seq = SeqDeserializer::new(array.into_iter().map(EnvDeserializer));
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+
+                Ok(value)
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let Value::Table(table) = self.0 else {
+            return self.0.deserialize_struct(name, fields, visitor);
+        };
+
+        // Rename the keys not matching any field exactly to the field they
+        // match regardless of their case and underscores.
+        let table = table
+            .into_iter()
+            .map(|(key, value)| {
+                if fields.contains(&key.as_str()) {
+                    return (key, value);
+                }
+                let normalized = normalize(&key);
+                let key = fields
+                    .iter()
+                    .find(|field| normalize(field) == normalized)
+                    .map_or(key, |field| field.to_string());
+
+                (key, value)
+            })
+            .collect();
+
+        Self::visit_table(table, visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    // The values of the environment variables parsed as numbers or booleans
+    // may be meant as strings.
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Integer(value) => visitor.visit_string(value.to_string()),
+            Value::Float(value) => visitor.visit_string(value.to_string()),
+            Value::Boolean(value) => visitor.visit_string(value.to_string()),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, toml::de::Error> for EnvDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize as _;
+
+    use super::*;
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn overrides_match_the_existing_keys() {
+        let mut table: Table = toml::from_str(
+            r#"
+            [RPC]
+            Port = 9090
+            "#,
+        )
+        .unwrap();
+
+        apply_overrides(
+            &mut table,
+            vars(&[
+                ("AGGLAYER_RPC__PORT", "8080"),
+                ("AGGLAYER_RPC__ASYNC_SETTLEMENT", "true"),
+                (
+                    "AGGLAYER_L1__ROLLUP_MANAGER_CONTRACT",
+                    "0x0000000000000000000000000000000000000001",
+                ),
+                ("AGGLAYER_", "ignored"),
+                ("AGGLAYER_RPC____PORT", "ignored"),
+                ("PATH", "/usr/bin"),
+            ]),
+        );
+
+        assert_eq!(table["RPC"]["Port"], Value::Integer(8080));
+        assert_eq!(table["RPC"]["ASYNC_SETTLEMENT"], Value::Boolean(true));
+        assert_eq!(
+            table["L1"]["ROLLUP_MANAGER_CONTRACT"],
+            Value::String("0x0000000000000000000000000000000000000001".to_string())
+        );
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn env_vars_override_the_config_file() {
+        let config = crate::Config::from_toml(
+            include_str!("../../../agglayer.toml"),
+            vars(&[
+                ("AGGLAYER_RPC__PORT", "8080"),
+                ("AGGLAYER_FULL_NODE_RPCS__2", "http://zkevm-node-2:8123"),
+                ("AGGLAYER_OUTBOUND__RPC__SETTLE__CONFIRMATIONS", "6"),
+                ("AGGLAYER_LOG__FORMAT", "json"),
+                ("AGGLAYER_ADMIN__LISTEN", "127.0.0.1:9091"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(config.rpc.port, 8080);
+        assert_eq!(config.full_node_rpcs.len(), 2);
+        assert_eq!(
+            config.full_node_rpcs[&2].as_str(),
+            "http://zkevm-node-2:8123/"
+        );
+        assert_eq!(config.outbound.rpc.settle.confirmations, 6);
+        assert_eq!(config.outbound.rpc.settle.max_retries, 10);
+        assert!(matches!(config.log.format, crate::log::LogFormat::Json));
+        assert_eq!(config.admin.listen, Some("127.0.0.1:9091".parse().unwrap()));
+    }
+
+    #[test]
+    fn fields_are_matched_regardless_of_case_and_underscores() {
+        #[derive(serde::Deserialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        struct Section {
+            async_settlement: bool,
+            api_key: String,
+            port: Option<u16>,
+        }
+
+        let mut table = Table::new();
+        apply_overrides(
+            &mut table,
+            vars(&[
+                ("AGGLAYER_ASYNC_SETTLEMENT", "true"),
+                ("AGGLAYER_APIKEY", "1234"),
+                ("AGGLAYER_PORT", "8080"),
+            ]),
+        );
+
+        let section = Section::deserialize(EnvDeserializer(Value::Table(table))).unwrap();
+
+        assert!(section.async_settlement);
+        assert_eq!(section.api_key, "1234");
+        assert_eq!(section.port, Some(8080));
+    }
+}
@@ -1,12 +1,19 @@
-use std::time::Duration;
+use std::{
+    num::{NonZeroU64, NonZeroUsize},
+    str::FromStr,
+    time::Duration,
+};
 
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use serde_with::{serde_as, DurationSeconds};
+use url::Url;
 
 /// The Epoch configuration.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub enum Epoch {
     TimeClock(TimeClockConfig),
+    BlockClock(BlockClockConfig),
 }
 
 impl Default for Epoch {
@@ -23,35 +30,225 @@ pub struct TimeClockConfig {
         deserialize_with = "deserialize_duration",
         rename = "EpochDuration"
     )]
-    pub epoch_duration: Duration,
+    pub epoch_duration: EpochDuration,
+    #[serde(flatten)]
+    pub events: ClockEventsConfig,
 }
 
 impl Default for TimeClockConfig {
     fn default() -> Self {
         Self {
             epoch_duration: default_epoch_duration(),
+            events: ClockEventsConfig::default(),
         }
     }
 }
 
-fn default_epoch_duration() -> Duration {
-    Duration::from_secs(5)
+/// The configuration of a clock following the L1 blocks.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockClockConfig {
+    /// The websocket endpoint of the L1 node, subscribed to for new blocks.
+    #[serde(rename = "WsNodeURL")]
+    pub ws_node_url: Url,
+    /// The L1 block at which the first epoch starts.
+    #[serde(rename = "GenesisBlock")]
+    pub genesis_block: u64,
+    #[serde(
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration",
+        rename = "EpochDuration"
+    )]
+    pub epoch_duration: EpochDuration,
+    /// The time between two L1 blocks, used to translate wall-clock epoch
+    /// durations into blocks.
+    #[serde_as(as = "DurationSeconds")]
+    #[serde(default = "default_l1_block_time", rename = "L1BlockTime")]
+    pub l1_block_time: Duration,
+    #[serde(flatten)]
+    pub events: ClockEventsConfig,
+}
+
+/// The delivery of the clock events, common to every clock.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClockEventsConfig {
+    /// The behavior of the clock when the node starts after some epochs
+    /// already ended.
+    #[serde(default, rename = "CatchUp")]
+    pub catch_up: EpochCatchUp,
+    /// The capacity of the clock broadcast channel.
+    #[serde(default = "default_broadcast_capacity", rename = "BroadcastCapacity")]
+    pub broadcast_capacity: NonZeroUsize,
+    /// The behavior of the clock when a subscriber lags behind by the capacity
+    /// of the broadcast channel.
+    #[serde(default, rename = "OverflowPolicy")]
+    pub overflow_policy: EpochOverflowPolicy,
+    /// How long ahead of the end of each epoch its ending is notified, if at
+    /// all.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_duration",
+        deserialize_with = "deserialize_optional_duration",
+        rename = "EpochEndingNotice"
+    )]
+    pub ending_notice: Option<EpochDuration>,
+}
+
+impl Default for ClockEventsConfig {
+    fn default() -> Self {
+        Self {
+            catch_up: EpochCatchUp::default(),
+            broadcast_capacity: default_broadcast_capacity(),
+            overflow_policy: EpochOverflowPolicy::default(),
+            ending_notice: None,
+        }
+    }
+}
+
+/// The behavior of the clock when the node starts after some epochs already
+/// ended.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EpochCatchUp {
+    /// Notify a single resync to the current epoch.
+    #[default]
+    Resync,
+    /// Replay the end of every missed epoch.
+    Replay,
+}
+
+/// The behavior of the clock when a subscriber lags behind by the capacity of
+/// the broadcast channel.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EpochOverflowPolicy {
+    /// Drop the oldest events, the lagging subscribers are notified of the
+    /// number of events they missed.
+    #[default]
+    DropOldest,
+    /// Block the clock until the slowest subscriber catches up.
+    Block,
+}
+
+fn default_l1_block_time() -> Duration {
+    Duration::from_secs(12)
+}
+
+fn default_broadcast_capacity() -> NonZeroUsize {
+    NonZeroUsize::new(100).expect("The default broadcast capacity is not zero")
 }
 
-fn serialize_duration<S>(value: &Duration, s: S) -> Result<S::Ok, S::Error>
+/// The duration of an epoch, either as a wall-clock duration, as a number of
+/// blocks, or as set on L1.
+///
+/// Wall-clock durations are given as a number of seconds, or as a string with
+/// a `s`, `m` or `h` unit (e.g. `"10m"`). Numbers of blocks are given as a
+/// string with a `blocks` unit (e.g. `"300 blocks"`). The duration set on L1
+/// is given as `"RollupManager"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EpochDuration {
+    Time(Duration),
+    Blocks(NonZeroU64),
+    /// The verification time target of the batches, `verifyBatchTimeTarget`,
+    /// read from the rollup manager contract when the node starts.
+    ///
+    /// The node must be restarted to follow a change of the target on L1.
+    /// Until then, the epochs keep the duration read on start, and the node
+    /// is reported as not ready.
+    RollupManager,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EpochDurationParseError {
+    #[error(
+        "invalid epoch duration `{0}`, expected a number followed by `s`, `m`, `h` or `blocks`, \
+         or `RollupManager`"
+    )]
+    Invalid(String),
+    #[error("epoch duration `{0}` is out of range")]
+    OutOfRange(String),
+}
+
+impl FromStr for EpochDuration {
+    type Err = EpochDurationParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "RollupManager" {
+            return Ok(Self::RollupManager);
+        }
+
+        let (value, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+
+        let value: u64 = value
+            .parse()
+            .map_err(|_| EpochDurationParseError::Invalid(s.to_string()))?;
+
+        let seconds = |multiplier: u64| {
+            value
+                .checked_mul(multiplier)
+                .map(|seconds| Self::Time(Duration::from_secs(seconds)))
+                .ok_or_else(|| EpochDurationParseError::OutOfRange(s.to_string()))
+        };
+
+        match unit.trim() {
+            "" | "s" => seconds(1),
+            "m" => seconds(60),
+            "h" => seconds(60 * 60),
+            "block" | "blocks" => NonZeroU64::new(value)
+                .map(Self::Blocks)
+                .ok_or_else(|| EpochDurationParseError::OutOfRange(s.to_string())),
+            _ => Err(EpochDurationParseError::Invalid(s.to_string())),
+        }
+    }
+}
+
+fn default_epoch_duration() -> EpochDuration {
+    EpochDuration::Time(Duration::from_secs(5))
+}
+
+fn serialize_duration<S>(value: &EpochDuration, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    s.serialize_u64(value.as_secs())
+    match value {
+        EpochDuration::Time(duration) => s.serialize_u64(duration.as_secs()),
+        EpochDuration::Blocks(blocks) => s.serialize_str(&format!("{blocks} blocks")),
+        EpochDuration::RollupManager => s.serialize_str("RollupManager"),
+    }
 }
 
-fn deserialize_duration<'de, D>(d: D) -> Result<Duration, D::Error>
+fn deserialize_duration<'de, D>(d: D) -> Result<EpochDuration, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let seconds = u64::deserialize(d)?;
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Seconds(u64),
+        Text(String),
+    }
+
+    match Repr::deserialize(d)? {
+        Repr::Seconds(seconds) => Ok(EpochDuration::Time(Duration::from_secs(seconds))),
+        Repr::Text(text) => text.parse().map_err(D::Error::custom),
+    }
+}
+
+fn serialize_optional_duration<S>(value: &Option<EpochDuration>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(value) => serialize_duration(value, s),
+        None => s.serialize_none(),
+    }
+}
 
-    Ok(Duration::from_secs(seconds))
+fn deserialize_optional_duration<'de, D>(d: D) -> Result<Option<EpochDuration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_duration(d).map(Some)
 }
 
 #[cfg(test)]
@@ -63,18 +260,162 @@ mod tests {
         let epoch = Epoch::TimeClock(TimeClockConfig::default());
         let serialized = serde_json::to_string(&epoch).unwrap();
 
-        assert_eq!(serialized, r#"{"TimeClock":{"EpochDuration":5}}"#);
+        assert_eq!(
+            serialized,
+            r#"{"TimeClock":{"EpochDuration":5,"CatchUp":"Resync","BroadcastCapacity":100,"OverflowPolicy":"DropOldest"}}"#
+        );
     }
 
     #[test]
     fn deserialize_epoch() {
         let config = r#"{"TimeClock":{"EpochDuration":3600}}"#;
 
-        let expected_duration = Duration::from_secs(3600);
+        let expected_duration = EpochDuration::Time(Duration::from_secs(3600));
         let epoch: Epoch = serde_json::from_str(config).unwrap();
 
         assert!(
-            matches!(epoch, Epoch::TimeClock(TimeClockConfig { epoch_duration }) if epoch_duration == expected_duration)
+            matches!(epoch, Epoch::TimeClock(TimeClockConfig { epoch_duration, events: ClockEventsConfig { catch_up: EpochCatchUp::Resync, .. } }) if epoch_duration == expected_duration)
+        );
+    }
+
+    #[test]
+    fn deserialize_epoch_duration() {
+        let epoch_duration = |value: &str| {
+            serde_json::from_str::<Epoch>(&format!(
+                r#"{{"TimeClock":{{"EpochDuration":{value}}}}}"#
+            ))
+            .map(|epoch| match epoch {
+                Epoch::TimeClock(config) => config.epoch_duration,
+                Epoch::BlockClock(config) => config.epoch_duration,
+            })
+        };
+
+        assert_eq!(
+            epoch_duration(r#""90s""#).unwrap(),
+            EpochDuration::Time(Duration::from_secs(90))
+        );
+        assert_eq!(
+            epoch_duration(r#""10m""#).unwrap(),
+            EpochDuration::Time(Duration::from_secs(600))
+        );
+        assert_eq!(
+            epoch_duration(r#""2h""#).unwrap(),
+            EpochDuration::Time(Duration::from_secs(7200))
+        );
+        assert_eq!(
+            epoch_duration(r#""300 blocks""#).unwrap(),
+            EpochDuration::Blocks(NonZeroU64::new(300).unwrap())
+        );
+        assert_eq!(
+            epoch_duration(r#""RollupManager""#).unwrap(),
+            EpochDuration::RollupManager
+        );
+        assert!(epoch_duration(r#""0 blocks""#).is_err());
+        assert!(epoch_duration(r#""10 days""#).is_err());
+        assert!(epoch_duration(r#""18446744073709551615h""#).is_err());
+    }
+
+    #[test]
+    fn serialize_epoch_duration_in_blocks() {
+        let epoch = Epoch::TimeClock(TimeClockConfig {
+            epoch_duration: EpochDuration::Blocks(NonZeroU64::new(300).unwrap()),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            serde_json::to_string(&epoch).unwrap(),
+            r#"{"TimeClock":{"EpochDuration":"300 blocks","CatchUp":"Resync","BroadcastCapacity":100,"OverflowPolicy":"DropOldest"}}"#
+        );
+    }
+
+    #[test]
+    fn deserialize_epoch_broadcast() {
+        let config = r#"{"TimeClock":{"BroadcastCapacity":10,"OverflowPolicy":"Block"}}"#;
+
+        let epoch: Epoch = serde_json::from_str(config).unwrap();
+
+        assert!(matches!(
+            epoch,
+            Epoch::TimeClock(TimeClockConfig {
+                events: ClockEventsConfig {
+                    broadcast_capacity,
+                    overflow_policy: EpochOverflowPolicy::Block,
+                    ..
+                },
+                ..
+            }) if broadcast_capacity.get() == 10
+        ));
+        assert!(serde_json::from_str::<Epoch>(r#"{"TimeClock":{"BroadcastCapacity":0}}"#).is_err());
+    }
+
+    #[test]
+    fn deserialize_epoch_catch_up() {
+        let config = r#"{"TimeClock":{"EpochDuration":3600,"CatchUp":"Replay"}}"#;
+
+        let epoch: Epoch = serde_json::from_str(config).unwrap();
+
+        assert!(matches!(
+            epoch,
+            Epoch::TimeClock(TimeClockConfig {
+                events: ClockEventsConfig {
+                    catch_up: EpochCatchUp::Replay,
+                    ..
+                },
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn deserialize_epoch_ending_notice() {
+        let config = r#"{"TimeClock":{"EpochDuration":3600,"EpochEndingNotice":"30s"}}"#;
+
+        let Epoch::TimeClock(config) = serde_json::from_str::<Epoch>(config).unwrap() else {
+            panic!("Expected a TimeClock configuration");
+        };
+        assert_eq!(
+            config.events.ending_notice,
+            Some(EpochDuration::Time(Duration::from_secs(30)))
+        );
+
+        let config = r#"{"TimeClock":{"EpochDuration":3600,"EpochEndingNotice":"2 blocks"}}"#;
+
+        let Epoch::TimeClock(config) = serde_json::from_str::<Epoch>(config).unwrap() else {
+            panic!("Expected a TimeClock configuration");
+        };
+        assert_eq!(
+            config.events.ending_notice,
+            Some(EpochDuration::Blocks(NonZeroU64::new(2).unwrap()))
+        );
+        assert_eq!(
+            serde_json::to_string(&Epoch::TimeClock(config)).unwrap(),
+            r#"{"TimeClock":{"EpochDuration":3600,"CatchUp":"Resync","BroadcastCapacity":100,"OverflowPolicy":"DropOldest","EpochEndingNotice":"2 blocks"}}"#
+        );
+    }
+
+    #[test]
+    fn deserialize_block_clock() {
+        let config = r#"
+            [BlockClock]
+            WsNodeURL = "ws://localhost:8546"
+            GenesisBlock = 100
+            EpochDuration = "10m"
+            CatchUp = "Replay"
+        "#;
+
+        let epoch: Epoch = toml::from_str(config).unwrap();
+
+        let Epoch::BlockClock(config) = epoch else {
+            panic!("Expected a BlockClock configuration");
+        };
+        assert_eq!(config.ws_node_url.as_str(), "ws://localhost:8546/");
+        assert_eq!(config.genesis_block, 100);
+        assert_eq!(
+            config.epoch_duration,
+            EpochDuration::Time(Duration::from_secs(600))
         );
+        assert_eq!(config.l1_block_time, Duration::from_secs(12));
+        assert_eq!(config.events.catch_up, EpochCatchUp::Replay);
+        assert_eq!(config.events.broadcast_capacity.get(), 100);
     }
 }
@@ -0,0 +1,149 @@
+//! The formats of the configuration file.
+//!
+//! The configuration is written in TOML by default, or in YAML or JSON for
+//! the tooling templating those more easily. Whatever the format, the fields
+//! are named as in TOML, e.g. `FullNodeRPCs` or `outbound.rpc.settle`.
+use std::path::Path;
+
+use toml::Table;
+
+use crate::ConfigError;
+
+/// The format of a configuration file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfigFormat {
+    #[default]
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detect the format of the given configuration file from its extension,
+    /// falling back to TOML.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("yaml" | "yml") => Self::Yaml,
+            Some("json") => Self::Json,
+            _ => Self::Toml,
+        }
+    }
+
+    /// Parse the given configuration into a TOML table.
+    ///
+    /// TOML having no null value, the null values of YAML and JSON aren't
+    /// supported, the fields are to be left out instead.
+    pub(crate) fn parse(self, contents: &str) -> Result<Table, ConfigError> {
+        Ok(match self {
+            Self::Toml => toml::from_str(contents)?,
+            // The keys of the YAML mappings may be integers, e.g. the rollup IDs
+            // of `FullNodeRPCs`, which JSON turns into strings as TOML expects.
+            Self::Yaml => {
+                serde_json::to_value(serde_yaml::from_str::<serde_yaml::Value>(contents)?)
+                    .and_then(serde_json::from_value)
+                    .map_err(<serde_yaml::Error as serde::de::Error>::custom)?
+            }
+            Self::Json => serde_json::from_str(contents)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    fn format_is_detected_from_the_extension() {
+        assert_eq!(ConfigFormat::from_path("agglayer.toml"), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path("agglayer.yaml"), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path("agglayer.YML"), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path("agglayer.json"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path("agglayer"), ConfigFormat::Toml);
+    }
+
+    #[test]
+    fn formats_are_parsed_alike() {
+        let yaml = r#"
+            FullNodeRPCs:
+              1: "http://zkevm-node:8123"
+            ShadowRollups: [1]
+            RPC:
+              Port: 9091
+            Log:
+              Level: debug
+              Outputs: [stderr]
+            L1:
+              ChainID: 1337
+              NodeURL: "http://l1:8545"
+              RollupManagerContract: "0xB7f8BC63BbcaD18155201308C8f3540b07f84F5e"
+            auth:
+              local:
+                PrivateKeys:
+                  - Path: /pk/agglayer.keystore
+                    Password: testonly
+            Telemetry: {}
+            outbound:
+              rpc:
+                settle:
+                  confirmations: 3
+        "#;
+        let json = r#"{
+            "FullNodeRPCs": { "1": "http://zkevm-node:8123" },
+            "ShadowRollups": [1],
+            "RPC": { "Port": 9091 },
+            "Log": { "Level": "debug", "Outputs": ["stderr"] },
+            "L1": {
+                "ChainID": 1337,
+                "NodeURL": "http://l1:8545",
+                "RollupManagerContract": "0xB7f8BC63BbcaD18155201308C8f3540b07f84F5e"
+            },
+            "auth": {
+                "local": {
+                    "PrivateKeys": [{ "Path": "/pk/agglayer.keystore", "Password": "testonly" }]
+                }
+            },
+            "Telemetry": {},
+            "outbound": { "rpc": { "settle": { "confirmations": 3 } } }
+        }"#;
+
+        for (format, contents) in [(ConfigFormat::Yaml, yaml), (ConfigFormat::Json, json)] {
+            let config = Config::parse(
+                contents,
+                format,
+                [("AGGLAYER_RPC__PORT".to_string(), "9092".to_string())],
+            )
+            .unwrap();
+
+            assert_eq!(
+                config.full_node_rpcs[&1].as_str(),
+                "http://zkevm-node:8123/"
+            );
+            assert!(config.shadow_rollups.contains(&1));
+            assert_eq!(config.rpc.port, 9092);
+            assert_eq!(config.l1.chain_id, 1337);
+            assert_eq!(config.outbound.rpc.settle.confirmations, 3);
+            assert!(
+                matches!(&config.auth, crate::AuthConfig::Local(local) if local.private_keys.len() == 1)
+            );
+        }
+    }
+
+    #[test]
+    fn null_values_are_rejected() {
+        assert!(matches!(
+            ConfigFormat::Json.parse(r#"{ "Storage": { "AuditLogPath": null } }"#),
+            Err(ConfigError::Json(_))
+        ));
+        assert!(matches!(
+            ConfigFormat::Yaml.parse("Storage:\n  AuditLogPath: ~\n"),
+            Err(ConfigError::Yaml(_))
+        ));
+    }
+}
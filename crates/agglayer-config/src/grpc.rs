@@ -0,0 +1,30 @@
+use std::net::SocketAddr;
+
+use serde::Deserialize;
+
+/// The gRPC server configuration.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct GrpcConfig {
+    /// The socket address of the gRPC server. If absent, the gRPC server is
+    /// disabled. It serves the same send and query operations as the RPC
+    /// server, with the same API keys.
+    #[serde(default)]
+    pub listen: Option<SocketAddr>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grpc_is_disabled_by_default() {
+        let config = toml::from_str::<GrpcConfig>("").unwrap();
+
+        assert!(config.listen.is_none());
+
+        let config = toml::from_str::<GrpcConfig>(r#"Listen = "0.0.0.0:9092""#).unwrap();
+
+        assert_eq!(config.listen, Some("0.0.0.0:9092".parse().unwrap()));
+    }
+}
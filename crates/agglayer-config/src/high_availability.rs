@@ -0,0 +1,176 @@
+use std::{path::PathBuf, time::Duration};
+
+use serde::Deserialize;
+use serde_with::{serde_as, DurationSeconds};
+use url::Url;
+
+use crate::secret::deserialize_optional_secret;
+
+/// The high-availability configuration.
+///
+/// Several agglayer nodes sharing the same keys accept and verify the
+/// submissions, but only the elected leader settles them on L1. The
+/// followers forward the submissions they verified to the leader.
+#[serde_as]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct HighAvailabilityConfig {
+    /// Whether the node takes part in the leader election. If disabled, the
+    /// node always settles the submissions itself.
+    #[serde(default)]
+    pub enabled: bool,
+    /// The identifier of the node, unique among the nodes sharing the
+    /// leadership. Defaults to the `HOSTNAME` environment variable.
+    #[serde(default = "default_node_id")]
+    pub node_id: String,
+    /// The URL of the RPC server of the node, to which the followers forward
+    /// the submissions while this node is the leader.
+    #[serde(default)]
+    pub advertised_url: Option<Url>,
+    /// The API key of the followers, sent along with the submissions they
+    /// forward to the leader, which must be one of its `RPC.ApiKeys` scoped to
+    /// every rollup. It may be given as a `file://` path. If the requests are
+    /// to be signed, the forwarded submissions are signed with the key of the
+    /// agglayer, whose address must then be one of the `RPC.RequestSigners`.
+    #[serde(default, deserialize_with = "deserialize_optional_secret")]
+    pub leader_api_key: Option<String>,
+    /// The backend holding the leadership lease.
+    #[serde(default)]
+    pub backend: LeaderElectionBackend,
+    /// The backend holding the settlement locks, which guarantee that a proof
+    /// is never settled twice, be it by different nodes. The locks must be
+    /// shared by the nodes if enabled.
+    #[serde(default)]
+    pub settlement_locks: SettlementLockBackend,
+    /// How long a settlement lock lasts, after which the proof can be settled
+    /// again, e.g. once the node holding it crashed. It must outlast the
+    /// settlement of a proof, up to its finality.
+    #[serde(default = "default_settlement_lock_lease")]
+    #[serde_as(as = "DurationSeconds")]
+    pub settlement_lock_lease: Duration,
+    /// How long the leadership lease lasts without being renewed.
+    #[serde(default = "default_lease_duration")]
+    #[serde_as(as = "DurationSeconds")]
+    pub lease_duration: Duration,
+    /// Interval at which the leadership lease is renewed or campaigned for.
+    #[serde(default = "default_renew_interval")]
+    #[serde_as(as = "DurationSeconds")]
+    pub renew_interval: Duration,
+}
+
+impl Default for HighAvailabilityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            node_id: default_node_id(),
+            advertised_url: None,
+            leader_api_key: None,
+            backend: LeaderElectionBackend::default(),
+            settlement_locks: SettlementLockBackend::default(),
+            settlement_lock_lease: default_settlement_lock_lease(),
+            lease_duration: default_lease_duration(),
+            renew_interval: default_renew_interval(),
+        }
+    }
+}
+
+/// The backend holding the leadership lease.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "Type")]
+pub enum LeaderElectionBackend {
+    /// A lease file on a volume shared by all the nodes. The clocks of the
+    /// nodes are expected to be kept in sync.
+    File {
+        #[serde(rename = "Path")]
+        path: PathBuf,
+    },
+}
+
+impl Default for LeaderElectionBackend {
+    fn default() -> Self {
+        Self::File {
+            path: PathBuf::from("agglayer-leader.json"),
+        }
+    }
+}
+
+/// The backend holding the settlement locks.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "Type")]
+pub enum SettlementLockBackend {
+    /// Locks held in memory, only guarding against the settlements of a
+    /// single node.
+    #[default]
+    Memory,
+    /// One lock file per proof in a directory shared by all the nodes.
+    Directory {
+        #[serde(rename = "Path")]
+        path: PathBuf,
+    },
+}
+
+fn default_node_id() -> String {
+    std::env::var("HOSTNAME").unwrap_or_else(|_| "agglayer".to_string())
+}
+
+const fn default_lease_duration() -> Duration {
+    Duration::from_secs(15)
+}
+
+const fn default_renew_interval() -> Duration {
+    Duration::from_secs(5)
+}
+
+const fn default_settlement_lock_lease() -> Duration {
+    Duration::from_secs(3600)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_file_backend() {
+        let toml = r#"
+            Enabled = true
+            NodeId = "agglayer-0"
+            AdvertisedUrl = "http://agglayer-0:9090/"
+            LeaderApiKey = "followers"
+            LeaseDuration = 30
+            SettlementLockLease = 7200
+
+            [Backend]
+            Type = "File"
+            Path = "/shared/leader.json"
+
+            [SettlementLocks]
+            Type = "Directory"
+            Path = "/shared/settlements"
+            "#;
+
+        let config = toml::from_str::<HighAvailabilityConfig>(toml).unwrap();
+
+        assert!(config.enabled);
+        assert_eq!(config.node_id, "agglayer-0");
+        assert_eq!(
+            config.advertised_url.unwrap().as_str(),
+            "http://agglayer-0:9090/"
+        );
+        assert_eq!(config.leader_api_key.as_deref(), Some("followers"));
+        assert_eq!(
+            config.backend,
+            LeaderElectionBackend::File {
+                path: PathBuf::from("/shared/leader.json")
+            }
+        );
+        assert_eq!(
+            config.settlement_locks,
+            SettlementLockBackend::Directory {
+                path: PathBuf::from("/shared/settlements")
+            }
+        );
+        assert_eq!(config.lease_duration, Duration::from_secs(30));
+        assert_eq!(config.renew_interval, Duration::from_secs(5));
+        assert_eq!(config.settlement_lock_lease, Duration::from_secs(7200));
+    }
+}
@@ -9,10 +9,21 @@ pub struct L1 {
     pub chain_id: u64,
     #[serde(rename = "NodeURL")]
     pub node_url: Url,
+    /// The L1 nodes to fail over to, in order, when the node at `NodeURL` is
+    /// unreachable.
+    #[serde(rename = "FallbackNodeURLs", default)]
+    pub fallback_node_urls: Vec<Url>,
     #[serde(rename = "RollupManagerContract")]
     pub rollup_manager_contract: Address,
 }
 
+impl L1 {
+    /// The URLs of the L1 nodes, the primary one first, then the fallbacks.
+    pub fn node_urls(&self) -> impl Iterator<Item = &Url> {
+        std::iter::once(&self.node_url).chain(&self.fallback_node_urls)
+    }
+}
+
 #[cfg(any(test, feature = "testutils"))]
 impl Default for L1 {
     fn default() -> Self {
@@ -20,9 +31,49 @@ impl Default for L1 {
         Self {
             chain_id: 1337,
             node_url: "http://zkevm-mock-l1-network:8545".parse().unwrap(),
+            fallback_node_urls: Vec::new(),
             rollup_manager_contract: "0xB7f8BC63BbcaD18155201308C8f3540b07f84F5e"
                 .parse()
                 .unwrap(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_fallback_node_urls() {
+        let toml = r#"
+            ChainID = 1
+            NodeURL = "http://primary:8545"
+            RollupManagerContract = "0xB7f8BC63BbcaD18155201308C8f3540b07f84F5e"
+            "#;
+
+        let config = toml::from_str::<L1>(toml).unwrap();
+
+        assert_eq!(
+            config.node_urls().map(Url::as_str).collect::<Vec<_>>(),
+            ["http://primary:8545/"]
+        );
+
+        let toml = r#"
+            ChainID = 1
+            NodeURL = "http://primary:8545"
+            FallbackNodeURLs = ["http://secondary:8545", "http://tertiary:8545"]
+            RollupManagerContract = "0xB7f8BC63BbcaD18155201308C8f3540b07f84F5e"
+            "#;
+
+        let config = toml::from_str::<L1>(toml).unwrap();
+
+        assert_eq!(
+            config.node_urls().map(Url::as_str).collect::<Vec<_>>(),
+            [
+                "http://primary:8545/",
+                "http://secondary:8545/",
+                "http://tertiary:8545/"
+            ]
+        );
+    }
+}
@@ -1,37 +1,95 @@
 //! Agglayer configuration.
 //!
 //! The agglayer is configured via its TOML configuration file, `agglayer.toml`
-//! by default, which is deserialized into the [`Config`] struct.
+//! by default, which is deserialized into the [`Config`] struct. The
+//! configuration file may be written in YAML or JSON instead, see
+//! [`ConfigFormat`].
+//!
+//! Every field of the configuration file can be overridden by an `AGGLAYER_*`
+//! environment variable, see [`Config::load`]. The secrets may be read from
+//! files mounted alongside it, given as `file://` paths.
 
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 
-use auth::deserialize_auth;
 use outbound::OutboundConfig;
 use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
 use shutdown::ShutdownConfig;
 use url::Url;
 
-use self::{rpc::deserialize_rpc_map, telemetry::TelemetryConfig};
+use self::rpc::deserialize_rpc_map;
 
 pub(crate) const DEFAULT_IP: std::net::Ipv4Addr = std::net::Ipv4Addr::new(0, 0, 0, 0);
 
+pub(crate) mod admin;
 pub(crate) mod auth;
 pub(crate) mod certificate_orchestrator;
+pub(crate) mod consensus;
+pub(crate) mod env;
 pub(crate) mod epoch;
+pub(crate) mod format;
+pub(crate) mod grpc;
+pub(crate) mod high_availability;
 pub(crate) mod l1;
 pub mod log;
 pub(crate) mod outbound;
+pub(crate) mod proof_format;
+pub(crate) mod prover;
+pub(crate) mod rollup_sync;
 pub(crate) mod rpc;
+pub(crate) mod secret;
+pub(crate) mod settlement_indexer;
+pub(crate) mod settlement_signer;
 pub mod shutdown;
+pub(crate) mod signatures;
+pub(crate) mod spend;
+pub(crate) mod storage;
 pub(crate) mod telemetry;
+pub(crate) mod validate;
 
-pub use auth::{AuthConfig, GcpKmsConfig, LocalConfig, PrivateKey};
-pub use epoch::Epoch;
+pub use admin::AdminConfig;
+pub use auth::{
+    deserialize_auth, AuthConfig, AzureKeyVaultConfig, GcpKmsConfig, LedgerConfig, LocalConfig,
+    PrivateKey, VaultAuth, VaultConfig, Web3SignerConfig,
+};
+pub use consensus::ConsensusType;
+pub use epoch::{
+    BlockClockConfig, ClockEventsConfig, Epoch, EpochCatchUp, EpochDuration, EpochOverflowPolicy,
+    TimeClockConfig,
+};
+pub use format::ConfigFormat;
+pub use grpc::GrpcConfig;
+pub use high_availability::{HighAvailabilityConfig, LeaderElectionBackend, SettlementLockBackend};
 pub use l1::L1;
 pub use log::Log;
-pub use rpc::RpcConfig;
+pub use outbound::{
+    EpochPackingConfig, GasBumpConfig, OracleGasCategory, OutboundConnectionsConfig,
+    OutboundProxyConfig, OutboundRpcL1CircuitBreakerConfig, OutboundRpcRetryConfig,
+    OutboundRpcVerifyProofConfig, SettlementFees, SettlementFinality,
+};
+pub use proof_format::{ProofFormat, ProofSystem};
+pub use prover::ProverConfig;
+pub use rollup_sync::RollupSyncConfig;
+pub use rpc::{
+    AccessLogConfig, ApiKeyConfig, EvictionPolicy, JwtConfig, PendingSubmissionsConfig, RateLimit,
+    RateLimitConfig, RpcConfig, TlsConfig,
+};
+pub use settlement_indexer::SettlementIndexerConfig;
+pub use settlement_signer::SettlementSignerConfig;
+pub use signatures::SignaturesConfig;
+pub use spend::SpendReportsConfig;
+pub use storage::StorageConfig;
+pub use telemetry::{TelemetryConfig, TracesConfig};
+pub use validate::ValidationError;
+
+/// The default configuration, with every field commented.
+pub const DEFAULT_CONFIG: &str = include_str!("default.toml");
 
 /// The Agglayer configuration.
+#[serde_as]
 #[derive(Deserialize, Debug)]
 #[cfg_attr(any(test, feature = "testutils"), derive(Default))]
 pub struct Config {
@@ -41,12 +99,33 @@ pub struct Config {
     /// endpoint.
     #[serde(rename = "FullNodeRPCs", deserialize_with = "deserialize_rpc_map")]
     pub full_node_rpcs: HashMap<u32, Url>,
+    /// The expected shape of the proofs of each rollup.
+    ///
+    /// The key is the rollup ID. Rollups without a format submit the fflonk
+    /// proofs of 24 words expected by the rollup manager contract.
+    #[serde(rename = "ProofFormats", default)]
+    #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+    pub proof_formats: HashMap<u32, ProofFormat>,
+    /// The consensus of each rollup.
+    ///
+    /// The key is the rollup ID. Rollups without a consensus type submit full
+    /// execution proofs.
+    #[serde(rename = "ConsensusTypes", default)]
+    #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+    pub consensus_types: HashMap<u32, ConsensusType>,
+    /// The rollups in shadow mode, whose submissions are verified and
+    /// reported but never settled on L1.
+    #[serde(rename = "ShadowRollups", default)]
+    pub shadow_rollups: HashSet<u32>,
     /// The log configuration.
     #[serde(rename = "Log")]
     pub log: Log,
     /// The local RPC server configuration.
     #[serde(rename = "RPC")]
     pub rpc: RpcConfig,
+    /// The gRPC server configuration.
+    #[serde(rename = "Grpc", default)]
+    pub grpc: GrpcConfig,
     /// The configuration for every outbound network component.
     #[serde(default)]
     pub outbound: OutboundConfig,
@@ -56,6 +135,10 @@ pub struct Config {
     /// The authentication configuration.
     #[serde(alias = "EthTxManager", default, deserialize_with = "deserialize_auth")]
     pub auth: AuthConfig,
+    /// The signers dedicated to the settlements of some rollups, which settle
+    /// with their own account rather than the one of `auth`.
+    #[serde(rename = "SettlementSigners", default)]
+    pub settlement_signers: Vec<SettlementSignerConfig>,
     /// Telemetry configuration.
     #[serde(rename = "Telemetry")]
     pub telemetry: TelemetryConfig,
@@ -71,11 +154,105 @@ pub struct Config {
     /// The certificate orchestrator configuration.
     #[serde(rename = "CertificateOrchestrator", default)]
     pub certificate_orchestrator: certificate_orchestrator::CertificateOrchestrator,
+
+    /// The L1 settlement indexer configuration.
+    #[serde(rename = "SettlementIndexer", default)]
+    pub settlement_indexer: SettlementIndexerConfig,
+
+    /// The sync of the rollups registered on L1.
+    #[serde(rename = "RollupSync", default)]
+    pub rollup_sync: RollupSyncConfig,
+
+    /// The high-availability configuration.
+    #[serde(rename = "HighAvailability", default)]
+    pub high_availability: HighAvailabilityConfig,
+
+    /// The admin RPC server configuration.
+    #[serde(rename = "Admin", default)]
+    pub admin: AdminConfig,
+
+    /// The accounting of the L1 costs of the settlements.
+    #[serde(rename = "SpendReports", default)]
+    pub spend_reports: SpendReportsConfig,
+
+    /// The persistent storage configuration.
+    #[serde(rename = "Storage", default)]
+    pub storage: StorageConfig,
+
+    /// The prover of the pessimistic proofs.
+    #[serde(rename = "Prover", default)]
+    pub prover: ProverConfig,
+
+    /// The signatures of the submitted proofs.
+    #[serde(rename = "Signatures", default)]
+    pub signatures: SignaturesConfig,
+}
+
+/// Errors that can occur when loading the configuration.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("unable to read the configuration file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid configuration: {0}")]
+    Invalid(#[from] toml::de::Error),
+    #[error("invalid YAML configuration: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("invalid JSON configuration: {0}")]
+    Json(#[from] serde_json::Error),
 }
 
 impl Config {
+    /// Load the configuration file at the given path, overridden by the
+    /// `AGGLAYER_*` environment variables.
+    ///
+    /// The format of the file is detected from its extension, see
+    /// [`ConfigFormat::from_path`].
+    ///
+    /// The nested fields are separated by `__` in the names of the environment
+    /// variables, e.g. `AGGLAYER_RPC__PORT=9090` overrides the `Port` of the
+    /// `[RPC]` section.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let format = ConfigFormat::from_path(&path);
+
+        Self::parse(&std::fs::read_to_string(path)?, format, std::env::vars())
+    }
+
+    /// Parse the given TOML configuration, overridden by the given
+    /// `AGGLAYER_*` environment variables.
+    pub fn from_toml(
+        toml: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        Self::parse(toml, ConfigFormat::Toml, vars)
+    }
+
+    /// Parse the given configuration in the given format, overridden by the
+    /// given `AGGLAYER_*` environment variables.
+    pub fn parse(
+        contents: &str,
+        format: ConfigFormat,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let mut table = format.parse(contents)?;
+        env::apply_overrides(&mut table, vars);
+
+        Ok(Self::deserialize(env::EnvDeserializer(
+            toml::Value::Table(table),
+        ))?)
+    }
+
     /// Get the target RPC socket address from the configuration.
     pub fn rpc_addr(&self) -> std::net::SocketAddr {
         std::net::SocketAddr::from((self.rpc.host, self.rpc.port))
     }
+
+    /// Get all the socket addresses the RPC server listens on, falling back
+    /// to [`Config::rpc_addr`] if none is listed.
+    pub fn rpc_addrs(&self) -> Vec<std::net::SocketAddr> {
+        if self.rpc.listen.is_empty() {
+            vec![self.rpc_addr()]
+        } else {
+            self.rpc.listen.clone()
+        }
+    }
 }
@@ -1,4 +1,12 @@
-use std::{fmt::Display, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use serde::{Deserialize, Deserializer};
 use tracing_subscriber::{fmt::writer::BoxMakeWriter, EnvFilter};
@@ -11,9 +19,73 @@ pub struct Log {
     /// configuration log level.
     #[serde(default)]
     pub level: LogLevel,
+    /// The outputs the logs are written to, each one receiving every log.
     pub outputs: Vec<LogOutput>,
     #[serde(default)]
     pub format: LogFormat,
+    /// The levels of specific modules, by target, overriding the
+    /// configuration log level, e.g. `"agglayer::access" = "warn"`.
+    #[serde(default)]
+    pub filters: BTreeMap<String, LogLevel>,
+    /// The rotation of the log files.
+    #[serde(default)]
+    pub rotation: LogRotation,
+}
+
+impl Log {
+    /// Build the filter of the logs, from the `RUST_LOG` environment variable
+    /// if set, or from the configuration log level and module filters.
+    pub fn env_filter(&self) -> EnvFilter {
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+            let mut directives = format!("warn,agglayer={}", self.level);
+            for (target, level) in &self.filters {
+                directives.push_str(&format!(",{target}={level}"));
+            }
+
+            EnvFilter::new(directives)
+        })
+    }
+}
+
+/// The rotation of the log files, once reaching a given size or at the end of
+/// every period, whichever comes first.
+///
+/// The rotated files are suffixed with `.1`, `.2`, etc. from the most recent
+/// to the oldest one.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct LogRotation {
+    /// The period of the rotation. Defaults to never rotating on time.
+    #[serde(default)]
+    pub period: RotationPeriod,
+    /// The size in bytes above which the log files are rotated. If absent, the
+    /// log files are never rotated on size.
+    #[serde(default)]
+    pub max_size: Option<u64>,
+    /// The number of rotated files to keep, the oldest ones being deleted. If
+    /// absent, the rotated files are all kept.
+    #[serde(default)]
+    pub max_files: Option<usize>,
+}
+
+/// The period of the rotation of the log files, in UTC.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RotationPeriod {
+    Hourly,
+    Daily,
+    #[default]
+    Never,
+}
+
+impl RotationPeriod {
+    fn duration(self) -> Option<Duration> {
+        match self {
+            RotationPeriod::Hourly => Some(Duration::from_secs(60 * 60)),
+            RotationPeriod::Daily => Some(Duration::from_secs(24 * 60 * 60)),
+            RotationPeriod::Never => None,
+        }
+    }
 }
 
 /// The log format.
@@ -92,17 +164,199 @@ impl<'de> Deserialize<'de> for LogOutput {
 }
 
 impl LogOutput {
-    /// Get a [`BoxMakeWriter`] for the log output.
+    /// Get a [`BoxMakeWriter`] for the log output, the log files being rotated
+    /// as configured.
     ///
     /// This can be used to plug the log output into the tracing subscriber.
-    pub fn as_make_writer(&self) -> BoxMakeWriter {
-        match self {
+    pub fn as_make_writer(&self, rotation: &LogRotation) -> io::Result<BoxMakeWriter> {
+        Ok(match self {
             LogOutput::Stdout => BoxMakeWriter::new(std::io::stdout),
             LogOutput::Stderr => BoxMakeWriter::new(std::io::stderr),
             LogOutput::File(path) => {
-                let appender = tracing_appender::rolling::never(".", path);
-                BoxMakeWriter::new(appender)
+                let file = Arc::new(Mutex::new(RotatingFile::open(path, rotation.clone())?));
+                BoxMakeWriter::new(move || RotatingFileWriter(file.clone()))
+            }
+        })
+    }
+}
+
+/// A log file, rotated as configured.
+#[derive(Debug)]
+struct RotatingFile {
+    path: PathBuf,
+    rotation: LogRotation,
+    file: File,
+    size: u64,
+    /// The index of the rotation period the file was opened in.
+    period: Option<u64>,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, rotation: LogRotation) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            period: Self::current_period(&rotation),
+            rotation,
+            file,
+            size,
+        })
+    }
+
+    /// Get the index of the current rotation period, if rotating on time.
+    fn current_period(rotation: &LogRotation) -> Option<u64> {
+        let period = rotation.period.duration()?;
+        let elapsed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        Some(elapsed.as_secs() / period.as_secs())
+    }
+
+    /// Get the path of the `n`-th rotated file.
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{n}"));
+
+        PathBuf::from(path)
+    }
+
+    /// Rotate the file if it reached its maximum size or if its period is
+    /// over, shifting the rotated files.
+    fn rotate_if_needed(&mut self, incoming: usize) -> io::Result<()> {
+        let period = Self::current_period(&self.rotation);
+        let oversized = self
+            .rotation
+            .max_size
+            .is_some_and(|max_size| self.size > 0 && self.size + incoming as u64 > max_size);
+        if !oversized && period == self.period {
+            return Ok(());
+        }
+
+        self.file.flush()?;
+
+        // Find the oldest rotated file to keep, deleting the next one if any.
+        let mut oldest = 1;
+        while self.rotated_path(oldest).exists() {
+            oldest += 1;
+        }
+        if let Some(max_files) = self.rotation.max_files {
+            for n in max_files.max(1)..oldest {
+                std::fs::remove_file(self.rotated_path(n))?;
             }
+            oldest = oldest.min(max_files.max(1));
         }
+        for n in (1..oldest).rev() {
+            std::fs::rename(self.rotated_path(n), self.rotated_path(n + 1))?;
+        }
+
+        if self.rotation.max_files == Some(0) {
+            std::fs::remove_file(&self.path)?;
+        } else {
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        self.period = period;
+
+        Ok(())
+    }
+}
+
+/// A handle on a [`RotatingFile`], writing to it.
+struct RotatingFileWriter(Arc<Mutex<RotatingFile>>);
+
+impl Write for RotatingFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut file = self
+            .0
+            .lock()
+            .map_err(|_| io::Error::other("log file lock poisoned"))?;
+        file.rotate_if_needed(buf.len())?;
+
+        let written = file.file.write(buf)?;
+        file.size += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0
+            .lock()
+            .map_err(|_| io::Error::other("log file lock poisoned"))?
+            .file
+            .flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_log() {
+        let toml = r#"
+            Level = "debug"
+            Outputs = ["stderr", "/var/log/agglayer.log"]
+            Format = "json"
+            Filters = { "agglayer::access" = "warn" }
+
+            [Rotation]
+            Period = "daily"
+            MaxSize = 1048576
+            MaxFiles = 7
+            "#;
+
+        let log = toml::from_str::<Log>(toml).unwrap();
+
+        assert!(
+            matches!(log.outputs[1], LogOutput::File(ref path) if path == Path::new("/var/log/agglayer.log"))
+        );
+        assert!(matches!(log.filters["agglayer::access"], LogLevel::Warn));
+        assert_eq!(log.rotation.period, RotationPeriod::Daily);
+        assert_eq!(log.rotation.max_size, Some(1048576));
+        assert_eq!(log.rotation.max_files, Some(7));
+
+        let log = toml::from_str::<Log>(r#"Outputs = ["stdout"]"#).unwrap();
+
+        assert!(log.filters.is_empty());
+        assert_eq!(log.rotation.period, RotationPeriod::Never);
+        assert_eq!(log.rotation.max_size, None);
+    }
+
+    #[test]
+    fn log_files_are_rotated_on_size() {
+        let dir = std::env::temp_dir().join(format!("agglayer-log-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("agglayer.log");
+        let rotation = LogRotation {
+            period: RotationPeriod::Never,
+            max_size: Some(10),
+            max_files: Some(2),
+        };
+
+        let file = Arc::new(Mutex::new(RotatingFile::open(&path, rotation).unwrap()));
+        let mut writer = RotatingFileWriter(file);
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            writer.write_all(line.as_bytes()).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let read = |path: &Path| std::fs::read_to_string(path).unwrap();
+        assert_eq!(read(&path), "fourth\n");
+        assert_eq!(read(&dir.join("agglayer.log.1")), "third\n");
+        assert_eq!(read(&dir.join("agglayer.log.2")), "second\n");
+        assert!(!dir.join("agglayer.log.3").exists());
+
+        _ = std::fs::remove_dir_all(dir);
     }
 }
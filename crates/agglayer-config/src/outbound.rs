@@ -1,14 +1,27 @@
-use std::time::Duration;
+use std::{collections::HashMap, num::NonZeroUsize, time::Duration};
 
+use ethers::types::Address;
 use serde::Deserialize;
 use serde_with::serde_as;
-use serde_with::DurationSeconds;
+use serde_with::{DurationMilliSeconds, DurationSeconds};
+use url::Url;
+
+use crate::secret::deserialize_optional_secret;
 
 /// Outbound configuration.
 #[derive(Default, Debug, Deserialize)]
 #[serde(rename = "outbound")]
 pub struct OutboundConfig {
+    #[serde(default)]
     pub rpc: OutboundRpcConfig,
+    /// The proxy through which the outbound connections are made. If absent,
+    /// the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables
+    /// are honoured.
+    #[serde(default)]
+    pub proxy: Option<OutboundProxyConfig>,
+    /// The lifecycle of the long-lived outbound connections.
+    #[serde(default)]
+    pub connections: OutboundConnectionsConfig,
 }
 
 /// Outbound RPC configuration that is used to configure the outbound RPC
@@ -17,7 +30,351 @@ pub struct OutboundConfig {
 #[serde(rename = "rpc")]
 pub struct OutboundRpcConfig {
     /// Outbound configuration of the RPC settle function call.
+    #[serde(default)]
     pub settle: OutboundRpcSettleConfig,
+
+    /// Outbound configuration of the ZkEVM node RPC calls.
+    #[serde(default)]
+    pub zkevm_node: OutboundRpcZkevmNodeConfig,
+
+    /// Outbound configuration of the dry-runs verifying the proofs on L1.
+    #[serde(default)]
+    pub verify_proof: OutboundRpcVerifyProofConfig,
+
+    /// Outbound configuration of the retries of the calls to L1 and to the
+    /// ZkEVM nodes.
+    #[serde(default)]
+    pub retry: OutboundRpcRetryConfig,
+
+    /// Outbound configuration of the circuit breaker of the calls to L1.
+    #[serde(default)]
+    pub l1_circuit_breaker: OutboundRpcL1CircuitBreakerConfig,
+
+    /// Outbound configuration of the timeouts of each stage of the processing
+    /// of the submissions.
+    #[serde(default)]
+    pub timeouts: OutboundRpcTimeoutsConfig,
+}
+
+/// Outbound RPC configuration of the timeouts of each stage of the processing
+/// of the submissions, so that a hung upstream doesn't hold on to the
+/// connections of the clients indefinitely.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename = "timeouts")]
+pub struct OutboundRpcTimeoutsConfig {
+    /// Timeout of the verification of the signature, fetching the trusted
+    /// sequencer from L1, in seconds.
+    #[serde(default = "default_verify_signature_timeout")]
+    #[serde_as(as = "DurationSeconds")]
+    pub verify_signature: Duration,
+
+    /// Timeout of the dry-run of the proof on L1, in seconds.
+    #[serde(default = "default_verify_proof_timeout")]
+    #[serde_as(as = "DurationSeconds")]
+    pub verify_proof: Duration,
+
+    /// Timeout of the query of the batch to the ZkEVM node, in seconds.
+    #[serde(default = "default_zkevm_node_timeout")]
+    #[serde_as(as = "DurationSeconds")]
+    pub zkevm_node: Duration,
+
+    /// Timeout of the submission of the settlement transaction to L1, in
+    /// seconds. The wait for its receipt is not bounded by it.
+    #[serde(default = "default_settle_submission_timeout")]
+    #[serde_as(as = "DurationSeconds")]
+    pub settle_submission: Duration,
+}
+
+impl Default for OutboundRpcTimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            verify_signature: default_verify_signature_timeout(),
+            verify_proof: default_verify_proof_timeout(),
+            zkevm_node: default_zkevm_node_timeout(),
+            settle_submission: default_settle_submission_timeout(),
+        }
+    }
+}
+
+/// Default timeout of the verification of the signature.
+const fn default_verify_signature_timeout() -> Duration {
+    Duration::from_secs(15)
+}
+
+/// Default timeout of the dry-run of the proof.
+const fn default_verify_proof_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+/// Default timeout of the query of the batch to the ZkEVM node.
+const fn default_zkevm_node_timeout() -> Duration {
+    Duration::from_secs(15)
+}
+
+/// Default timeout of the submission of the settlement transaction.
+const fn default_settle_submission_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+/// Outbound RPC configuration of the circuit breaker tripping once L1 is
+/// unreachable, the submissions being rejected right away until L1 answers
+/// again.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename = "l1_circuit_breaker")]
+pub struct OutboundRpcL1CircuitBreakerConfig {
+    /// Number of consecutive calls to L1 failing, once retried, after which
+    /// the breaker trips. The breaker is disabled if zero.
+    #[serde(default = "default_l1_circuit_breaker_failure_threshold")]
+    pub failure_threshold: usize,
+
+    /// Interval at which L1 is probed while the breaker is tripped.
+    #[serde(default = "default_l1_circuit_breaker_probe_interval")]
+    #[serde_as(as = "DurationSeconds")]
+    pub probe_interval: Duration,
+}
+
+impl Default for OutboundRpcL1CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_l1_circuit_breaker_failure_threshold(),
+            probe_interval: default_l1_circuit_breaker_probe_interval(),
+        }
+    }
+}
+
+/// Default number of consecutive failures tripping the L1 circuit breaker.
+const fn default_l1_circuit_breaker_failure_threshold() -> usize {
+    5
+}
+
+/// Default interval at which L1 is probed while the breaker is tripped.
+const fn default_l1_circuit_breaker_probe_interval() -> Duration {
+    Duration::from_secs(5)
+}
+
+/// Outbound RPC configuration of the retries of the calls to L1 and to the
+/// ZkEVM nodes failing transiently, e.g. as the node is unreachable, with an
+/// exponential backoff. The settlement transactions are never retried this
+/// way.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename = "retry")]
+pub struct OutboundRpcRetryConfig {
+    /// Maximum number of attempts of a call, the first one included. The
+    /// calls aren't retried if one.
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: usize,
+
+    /// Delay before the first retry, in milliseconds, doubled on each
+    /// subsequent retry.
+    #[serde(default = "default_retry_base_delay")]
+    #[serde_as(as = "DurationMilliSeconds")]
+    pub base_delay: Duration,
+
+    /// Maximum delay between two attempts, in milliseconds.
+    #[serde(default = "default_retry_max_delay")]
+    #[serde_as(as = "DurationMilliSeconds")]
+    pub max_delay: Duration,
+
+    /// Fraction of each delay drawn at random, between 0 and 1, so that the
+    /// calls failing together don't retry together.
+    #[serde(default = "default_retry_jitter")]
+    pub jitter: f64,
+}
+
+impl Default for OutboundRpcRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            base_delay: default_retry_base_delay(),
+            max_delay: default_retry_max_delay(),
+            jitter: default_retry_jitter(),
+        }
+    }
+}
+
+/// Default maximum number of attempts of a call.
+const fn default_retry_max_attempts() -> usize {
+    3
+}
+
+/// Default delay before the first retry.
+const fn default_retry_base_delay() -> Duration {
+    Duration::from_millis(100)
+}
+
+/// Default maximum delay between two attempts.
+const fn default_retry_max_delay() -> Duration {
+    Duration::from_secs(2)
+}
+
+/// Default fraction of each delay drawn at random.
+const fn default_retry_jitter() -> f64 {
+    0.5
+}
+
+/// Outbound RPC configuration of the `verifyBatchesTrustedAggregator` dry-runs
+/// verifying the submitted proofs, whose successes are cached so that the
+/// retried submissions don't run them again.
+#[serde_as]
+#[derive(Debug, Deserialize)]
+#[serde(rename = "verify_proof")]
+pub struct OutboundRpcVerifyProofConfig {
+    /// Maximum number of successful dry-runs cached, the least recently used
+    /// ones being evicted beyond it. The cache is disabled if zero.
+    #[serde(default = "default_verify_proof_cache_capacity")]
+    pub cache_capacity: usize,
+
+    /// Time during which a successful dry-run is cached.
+    #[serde(default = "default_verify_proof_cache_ttl")]
+    #[serde_as(as = "DurationSeconds")]
+    pub cache_ttl: Duration,
+}
+
+impl Default for OutboundRpcVerifyProofConfig {
+    fn default() -> Self {
+        Self {
+            cache_capacity: default_verify_proof_cache_capacity(),
+            cache_ttl: default_verify_proof_cache_ttl(),
+        }
+    }
+}
+
+/// Default maximum number of successful dry-runs cached.
+const fn default_verify_proof_cache_capacity() -> usize {
+    1024
+}
+
+/// Default time during which a successful dry-run is cached.
+const fn default_verify_proof_cache_ttl() -> Duration {
+    Duration::from_secs(60)
+}
+
+/// Outbound RPC configuration of the calls to the ZkEVM nodes, made through one
+/// pooled client per rollup.
+#[derive(Debug, Deserialize)]
+#[serde(rename = "zkevm_node")]
+pub struct OutboundRpcZkevmNodeConfig {
+    /// Maximum number of concurrent requests to the ZkEVM node of a rollup.
+    /// The requests above it wait for a slot.
+    #[serde(default = "default_zkevm_node_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+}
+
+impl Default for OutboundRpcZkevmNodeConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: default_zkevm_node_max_concurrent_requests(),
+        }
+    }
+}
+
+/// Default maximum number of concurrent requests to a ZkEVM node.
+const fn default_zkevm_node_max_concurrent_requests() -> usize {
+    32
+}
+
+/// Outbound proxy configuration, applying to the connections to L1, the ZkEVM
+/// nodes and the KMS backends.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(rename = "proxy")]
+pub struct OutboundProxyConfig {
+    /// The proxy of every outbound connection, unless overridden per host.
+    #[serde(default)]
+    pub url: Option<Url>,
+    /// The hosts reached directly, bypassing the proxy.
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+    /// The proxies of specific hosts, overriding `url`. KMS backends only
+    /// honour `url` and `no_proxy`.
+    #[serde(default)]
+    pub hosts: HashMap<String, Url>,
+}
+
+impl OutboundProxyConfig {
+    /// Get the proxy through which the given endpoint is reached, if any.
+    pub fn proxy_for(&self, endpoint: &Url) -> Option<&Url> {
+        let host = endpoint.host_str()?;
+        if self.no_proxy.iter().any(|no_proxy| no_proxy == host) {
+            return None;
+        }
+
+        self.hosts.get(host).or(self.url.as_ref())
+    }
+}
+
+/// Outbound connections configuration, recycling the long-lived connections so
+/// that the endpoints are periodically re-resolved.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename = "connections")]
+pub struct OutboundConnectionsConfig {
+    /// Interval after which the HTTP connections to L1 are dropped and the
+    /// endpoint re-resolved. A failed request triggers it early.
+    #[serde(default = "default_refresh_interval")]
+    #[serde_as(as = "DurationSeconds")]
+    pub refresh_interval: Duration,
+
+    /// Maximum number of reconnections of the L1 websocket connection, each
+    /// one re-resolving the endpoint.
+    #[serde(default = "default_ws_max_reconnects")]
+    pub ws_max_reconnects: usize,
+
+    /// Time after which the idle HTTP connections of the pool to an endpoint
+    /// are closed.
+    #[serde(default = "default_pool_idle_timeout")]
+    #[serde_as(as = "DurationSeconds")]
+    pub pool_idle_timeout: Duration,
+
+    /// Maximum number of idle HTTP connections kept in the pool to an
+    /// endpoint, ready for the next requests.
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
+
+    /// Interval of the TCP keepalive probes of the HTTP connections, keeping
+    /// the idle connections open through the NATs and load balancers.
+    #[serde(default = "default_tcp_keepalive")]
+    #[serde_as(as = "DurationSeconds")]
+    pub tcp_keepalive: Duration,
+}
+
+impl Default for OutboundConnectionsConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval: default_refresh_interval(),
+            ws_max_reconnects: default_ws_max_reconnects(),
+            pool_idle_timeout: default_pool_idle_timeout(),
+            pool_max_idle_per_host: default_pool_max_idle_per_host(),
+            tcp_keepalive: default_tcp_keepalive(),
+        }
+    }
+}
+
+/// Default interval after which the HTTP connections are recycled.
+const fn default_refresh_interval() -> Duration {
+    Duration::from_secs(300)
+}
+
+/// Default maximum number of reconnections of the websocket connection.
+const fn default_ws_max_reconnects() -> usize {
+    64
+}
+
+/// Default time after which the idle HTTP connections are closed.
+const fn default_pool_idle_timeout() -> Duration {
+    Duration::from_secs(90)
+}
+
+/// Default maximum number of idle HTTP connections per endpoint.
+const fn default_pool_max_idle_per_host() -> usize {
+    32
+}
+
+/// Default interval of the TCP keepalive probes.
+const fn default_tcp_keepalive() -> Duration {
+    Duration::from_secs(60)
 }
 
 /// Outbound RPC settle configuration that is used to configure the outbound
@@ -39,6 +396,146 @@ pub struct OutboundRpcSettleConfig {
     /// receipt.
     #[serde(default = "default_rpc_confirmations")]
     pub confirmations: usize,
+
+    /// What makes a settlement final, and thus reported as done.
+    #[serde(default)]
+    pub finality: SettlementFinality,
+
+    /// The replacement of the settlement transactions stuck in the mempool.
+    /// If absent, the settlement transactions are never replaced.
+    #[serde(default)]
+    pub gas_bump: Option<GasBumpConfig>,
+
+    /// The fees of the settlement transactions, sent as EIP-1559 (type 2)
+    /// transactions.
+    #[serde(default)]
+    pub fees: SettlementFees,
+
+    /// The packing of the proofs received within an epoch into a single
+    /// settlement transaction, sent at the end of the epoch. If absent, every
+    /// proof is settled with its own transaction as soon as it is verified.
+    #[serde(default)]
+    pub epoch_packing: Option<EpochPackingConfig>,
+}
+
+/// Settlement of the proofs received within an epoch through a multicall
+/// contract, in a single transaction.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename = "epoch_packing")]
+pub struct EpochPackingConfig {
+    /// The Multicall3-compatible contract relaying the settlement calls to
+    /// the rollup manager contract. It must be granted the trusted aggregator
+    /// role, and only relay the calls of the agglayer.
+    pub multicall_contract: Address,
+
+    /// Maximum number of proofs settled in a single transaction, the proofs
+    /// of an epoch beyond it are settled in several transactions. The packs
+    /// reaching it are settled right away, without waiting for the end of
+    /// the epoch.
+    #[serde(default = "default_max_packed_proofs")]
+    pub max_proofs: NonZeroUsize,
+
+    /// Interval at which the proofs packed so far are settled. If absent, the
+    /// proofs are settled at the end of their epoch, or once their pack is
+    /// full.
+    #[serde(default)]
+    #[serde_as(as = "Option<DurationSeconds>")]
+    pub flush_interval: Option<Duration>,
+}
+
+/// Default maximum number of proofs settled in a single transaction.
+fn default_max_packed_proofs() -> NonZeroUsize {
+    NonZeroUsize::new(16).expect("The default number of packed proofs is not zero")
+}
+
+/// The strategy setting the `max_fee_per_gas` and `max_priority_fee_per_gas`
+/// of the settlement transactions, matching the L1 network they're sent to.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum SettlementFees {
+    /// The fees are estimated by the L1 node, from the fee history of the
+    /// last blocks.
+    #[default]
+    Provider,
+    /// The fees are fixed, in wei.
+    Static {
+        max_fee_per_gas: u64,
+        max_priority_fee_per_gas: u64,
+    },
+    /// The fees are fetched from the Blocknative gas oracle.
+    Oracle {
+        #[serde(default, deserialize_with = "deserialize_optional_secret")]
+        api_key: Option<String>,
+        #[serde(default)]
+        category: OracleGasCategory,
+    },
+    /// The priority fee is the median of the given percentile of the priority
+    /// fees paid in the given number of recent blocks, and the max fee twice
+    /// the next base fee on top of it.
+    Percentile {
+        #[serde(default = "default_fee_history_blocks")]
+        blocks: u64,
+        #[serde(default = "default_fee_history_percentile")]
+        percentile: f64,
+    },
+}
+
+/// The speed category of the fees fetched from a gas oracle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleGasCategory {
+    SafeLow,
+    #[default]
+    Standard,
+    Fast,
+    Fastest,
+}
+
+/// Default number of blocks of the fee history.
+const fn default_fee_history_blocks() -> u64 {
+    10
+}
+
+/// Default percentile of the priority fees paid in the fee history.
+const fn default_fee_history_percentile() -> f64 {
+    50.0
+}
+
+/// Replacement of the settlement transactions left unmined, with an escalated
+/// gas price.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename = "gas_bump")]
+pub struct GasBumpConfig {
+    /// Time after which an unmined settlement transaction is replaced.
+    #[serde(default = "default_gas_bump_stuck_after")]
+    #[serde_as(as = "DurationSeconds")]
+    pub stuck_after: Duration,
+
+    /// Percentage by which the gas price is bumped on every replacement. Most
+    /// nodes reject the replacements bumping it by less than 10%.
+    #[serde(default = "default_gas_bump_percent")]
+    pub percent: u64,
+
+    /// Gas price, in wei, above which the settlement transactions are no
+    /// longer replaced.
+    pub max_gas_price: u64,
+}
+
+/// The source of finality of the settlements.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlementFinality {
+    /// The settlement is final once included in a block below the latest one.
+    #[default]
+    Confirmations,
+    /// The settlement is final once included in a block at or below the
+    /// `safe` block.
+    Safe,
+    /// The settlement is final once included in a block at or below the
+    /// `finalized` block.
+    Finalized,
 }
 
 impl Default for OutboundRpcSettleConfig {
@@ -47,10 +544,24 @@ impl Default for OutboundRpcSettleConfig {
             max_retries: default_rpc_retries(),
             retry_interval: default_rpc_retry_interval(),
             confirmations: default_rpc_confirmations(),
+            finality: SettlementFinality::default(),
+            gas_bump: None,
+            fees: SettlementFees::default(),
+            epoch_packing: None,
         }
     }
 }
 
+/// Default time after which an unmined settlement transaction is replaced.
+const fn default_gas_bump_stuck_after() -> Duration {
+    Duration::from_secs(180)
+}
+
+/// Default percentage by which the gas price of a replacement is bumped.
+const fn default_gas_bump_percent() -> u64 {
+    20
+}
+
 /// Default number of retries for the transaction. It matches the ethers default
 /// value.
 const fn default_rpc_retries() -> usize {
@@ -92,11 +603,208 @@ mod tests {
             assert_eq!(config.outbound.rpc.settle.max_retries, 10);
         }
 
+        mod proxy {
+            use crate::outbound::OutboundConfig;
+
+            #[test]
+            fn proxy_per_host() {
+                let toml = r#"
+                    [proxy]
+                    url = "http://proxy:3128"
+                    no_proxy = ["localhost"]
+
+                    [proxy.hosts]
+                    "zkevm-node-2" = "http://other-proxy:3128"
+                    "#;
+
+                let config = toml::from_str::<OutboundConfig>(toml).unwrap();
+                let proxy = config.proxy.unwrap();
+                let proxy_for = |endpoint: &str| {
+                    proxy
+                        .proxy_for(&endpoint.parse().unwrap())
+                        .map(|url| url.to_string())
+                };
+
+                assert_eq!(config.rpc.settle.max_retries, 3);
+                assert_eq!(
+                    proxy_for("https://l1.example.com"),
+                    Some("http://proxy:3128/".to_string())
+                );
+                assert_eq!(
+                    proxy_for("http://zkevm-node-2:8123"),
+                    Some("http://other-proxy:3128/".to_string())
+                );
+                assert_eq!(proxy_for("http://localhost:8545"), None);
+            }
+        }
+
+        mod connections {
+            use std::time::Duration;
+
+            use crate::outbound::OutboundConfig;
+
+            #[test]
+            fn connections_refresh() {
+                let config = toml::from_str::<OutboundConfig>("").unwrap();
+
+                assert_eq!(
+                    config.connections.refresh_interval,
+                    Duration::from_secs(300)
+                );
+                assert_eq!(config.connections.ws_max_reconnects, 64);
+                assert_eq!(
+                    config.connections.pool_idle_timeout,
+                    Duration::from_secs(90)
+                );
+                assert_eq!(config.connections.pool_max_idle_per_host, 32);
+                assert_eq!(config.connections.tcp_keepalive, Duration::from_secs(60));
+
+                let toml = r#"
+                    [connections]
+                    refresh_interval = 60
+                    ws_max_reconnects = 3
+                    pool_idle_timeout = 30
+                    pool_max_idle_per_host = 4
+                    tcp_keepalive = 15
+                    "#;
+
+                let config = toml::from_str::<OutboundConfig>(toml).unwrap();
+
+                assert_eq!(config.connections.refresh_interval, Duration::from_secs(60));
+                assert_eq!(config.connections.ws_max_reconnects, 3);
+                assert_eq!(
+                    config.connections.pool_idle_timeout,
+                    Duration::from_secs(30)
+                );
+                assert_eq!(config.connections.pool_max_idle_per_host, 4);
+                assert_eq!(config.connections.tcp_keepalive, Duration::from_secs(15));
+            }
+        }
+
         mod rpc {
+            mod verify_proof {
+                use std::time::Duration;
+
+                use crate::outbound::OutboundRpcConfig;
+
+                #[test]
+                fn cache() {
+                    let config = toml::from_str::<OutboundRpcConfig>("").unwrap();
+
+                    assert_eq!(config.verify_proof.cache_capacity, 1024);
+                    assert_eq!(config.verify_proof.cache_ttl, Duration::from_secs(60));
+
+                    let toml = r#"
+                        [verify_proof]
+                        cache_capacity = 0
+                        cache_ttl = 5
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcConfig>(toml).unwrap();
+
+                    assert_eq!(config.verify_proof.cache_capacity, 0);
+                    assert_eq!(config.verify_proof.cache_ttl, Duration::from_secs(5));
+                }
+            }
+
+            mod retry {
+                use std::time::Duration;
+
+                use crate::outbound::OutboundRpcConfig;
+
+                #[test]
+                fn backoff() {
+                    let config = toml::from_str::<OutboundRpcConfig>("").unwrap();
+
+                    assert_eq!(config.retry.max_attempts, 3);
+                    assert_eq!(config.retry.base_delay, Duration::from_millis(100));
+                    assert_eq!(config.retry.max_delay, Duration::from_secs(2));
+                    assert_eq!(config.retry.jitter, 0.5);
+
+                    let toml = r#"
+                        [retry]
+                        max_attempts = 5
+                        base_delay = 250
+                        max_delay = 10000
+                        jitter = 0.0
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcConfig>(toml).unwrap();
+
+                    assert_eq!(config.retry.max_attempts, 5);
+                    assert_eq!(config.retry.base_delay, Duration::from_millis(250));
+                    assert_eq!(config.retry.max_delay, Duration::from_secs(10));
+                    assert_eq!(config.retry.jitter, 0.0);
+                }
+            }
+
+            mod l1_circuit_breaker {
+                use std::time::Duration;
+
+                use crate::outbound::OutboundRpcConfig;
+
+                #[test]
+                fn threshold_and_probes() {
+                    let config = toml::from_str::<OutboundRpcConfig>("").unwrap();
+
+                    assert_eq!(config.l1_circuit_breaker.failure_threshold, 5);
+                    assert_eq!(
+                        config.l1_circuit_breaker.probe_interval,
+                        Duration::from_secs(5)
+                    );
+
+                    let toml = r#"
+                        [l1_circuit_breaker]
+                        failure_threshold = 0
+                        probe_interval = 1
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcConfig>(toml).unwrap();
+
+                    assert_eq!(config.l1_circuit_breaker.failure_threshold, 0);
+                    assert_eq!(
+                        config.l1_circuit_breaker.probe_interval,
+                        Duration::from_secs(1)
+                    );
+                }
+            }
+
+            mod timeouts {
+                use std::time::Duration;
+
+                use crate::outbound::OutboundRpcConfig;
+
+                #[test]
+                fn stage_timeouts() {
+                    let config = toml::from_str::<OutboundRpcConfig>("").unwrap();
+
+                    assert_eq!(config.timeouts.verify_signature, Duration::from_secs(15));
+                    assert_eq!(config.timeouts.verify_proof, Duration::from_secs(30));
+                    assert_eq!(config.timeouts.zkevm_node, Duration::from_secs(15));
+                    assert_eq!(config.timeouts.settle_submission, Duration::from_secs(30));
+
+                    let toml = r#"
+                        [timeouts]
+                        verify_signature = 1
+                        zkevm_node = 2
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcConfig>(toml).unwrap();
+
+                    assert_eq!(config.timeouts.verify_signature, Duration::from_secs(1));
+                    assert_eq!(config.timeouts.verify_proof, Duration::from_secs(30));
+                    assert_eq!(config.timeouts.zkevm_node, Duration::from_secs(2));
+                    assert_eq!(config.timeouts.settle_submission, Duration::from_secs(30));
+                }
+            }
+
             mod settle {
                 use std::time::Duration;
 
-                use crate::outbound::OutboundRpcSettleConfig;
+                use crate::outbound::{
+                    EpochPackingConfig, OracleGasCategory, OutboundRpcSettleConfig, SettlementFees,
+                    SettlementFinality,
+                };
 
                 #[test]
                 fn test_default() {
@@ -108,6 +816,7 @@ mod tests {
                     assert_eq!(config.max_retries, 3);
                     assert_eq!(config.retry_interval, Duration::from_secs(7));
                     assert_eq!(config.confirmations, 1);
+                    assert_eq!(config.finality, SettlementFinality::Confirmations);
                 }
 
                 #[test]
@@ -116,6 +825,7 @@ mod tests {
                         max_retries = 10
                         retry_interval = 1
                         confirmations = 5
+                        finality = "finalized"
                         "#;
 
                     let config = toml::from_str::<OutboundRpcSettleConfig>(toml).unwrap();
@@ -123,6 +833,152 @@ mod tests {
                     assert_eq!(config.max_retries, 10);
                     assert_eq!(config.retry_interval, Duration::from_secs(1));
                     assert_eq!(config.confirmations, 5);
+                    assert_eq!(config.finality, SettlementFinality::Finalized);
+                    assert!(config.gas_bump.is_none());
+                }
+
+                #[test]
+                fn gas_bump() {
+                    let toml = r#"
+                        [gas_bump]
+                        max_gas_price = 200000000000
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcSettleConfig>(toml).unwrap();
+                    let gas_bump = config.gas_bump.unwrap();
+
+                    assert_eq!(gas_bump.stuck_after, Duration::from_secs(180));
+                    assert_eq!(gas_bump.percent, 20);
+                    assert_eq!(gas_bump.max_gas_price, 200_000_000_000);
+
+                    let toml = r#"
+                        [gas_bump]
+                        stuck_after = 60
+                        percent = 50
+                        "#;
+
+                    assert!(toml::from_str::<OutboundRpcSettleConfig>(toml).is_err());
+                }
+
+                #[test]
+                fn epoch_packing() {
+                    let config = toml::from_str::<OutboundRpcSettleConfig>("").unwrap();
+
+                    assert!(config.epoch_packing.is_none());
+
+                    let toml = r#"
+                        [epoch_packing]
+                        multicall_contract = "0xcA11bde05977b3631167028862bE2a173976CA11"
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcSettleConfig>(toml).unwrap();
+
+                    assert_eq!(
+                        config.epoch_packing,
+                        Some(EpochPackingConfig {
+                            multicall_contract: "0xcA11bde05977b3631167028862bE2a173976CA11"
+                                .parse()
+                                .unwrap(),
+                            max_proofs: 16.try_into().unwrap(),
+                            flush_interval: None,
+                        })
+                    );
+
+                    let toml = r#"
+                        [epoch_packing]
+                        multicall_contract = "0xcA11bde05977b3631167028862bE2a173976CA11"
+                        max_proofs = 4
+                        flush_interval = 30
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcSettleConfig>(toml).unwrap();
+                    let packing = config.epoch_packing.unwrap();
+
+                    assert_eq!(packing.max_proofs.get(), 4);
+                    assert_eq!(packing.flush_interval, Some(Duration::from_secs(30)));
+
+                    let toml = r#"
+                        [epoch_packing]
+                        max_proofs = 4
+                        "#;
+
+                    assert!(toml::from_str::<OutboundRpcSettleConfig>(toml).is_err());
+                }
+
+                #[test]
+                fn fees() {
+                    let config = toml::from_str::<OutboundRpcSettleConfig>("").unwrap();
+
+                    assert_eq!(config.fees, SettlementFees::Provider);
+
+                    let toml = r#"
+                        [fees]
+                        strategy = "static"
+                        max_fee_per_gas = 30000000000
+                        max_priority_fee_per_gas = 1000000000
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcSettleConfig>(toml).unwrap();
+
+                    assert_eq!(
+                        config.fees,
+                        SettlementFees::Static {
+                            max_fee_per_gas: 30_000_000_000,
+                            max_priority_fee_per_gas: 1_000_000_000,
+                        }
+                    );
+
+                    let toml = r#"
+                        [fees]
+                        strategy = "oracle"
+                        category = "fast"
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcSettleConfig>(toml).unwrap();
+
+                    assert_eq!(
+                        config.fees,
+                        SettlementFees::Oracle {
+                            api_key: None,
+                            category: OracleGasCategory::Fast,
+                        }
+                    );
+
+                    let toml = r#"
+                        [fees]
+                        strategy = "percentile"
+                        percentile = 75.0
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcSettleConfig>(toml).unwrap();
+
+                    assert_eq!(
+                        config.fees,
+                        SettlementFees::Percentile {
+                            blocks: 10,
+                            percentile: 75.0,
+                        }
+                    );
+                }
+            }
+
+            mod zkevm_node {
+                use crate::outbound::OutboundRpcConfig;
+
+                #[test]
+                fn max_concurrent_requests() {
+                    let config = toml::from_str::<OutboundRpcConfig>("").unwrap();
+
+                    assert_eq!(config.zkevm_node.max_concurrent_requests, 32);
+
+                    let toml = r#"
+                        [zkevm_node]
+                        max_concurrent_requests = 4
+                        "#;
+
+                    let config = toml::from_str::<OutboundRpcConfig>(toml).unwrap();
+
+                    assert_eq!(config.zkevm_node.max_concurrent_requests, 4);
                 }
             }
         }
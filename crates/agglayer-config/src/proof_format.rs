@@ -0,0 +1,84 @@
+use ethers::types::Bytes;
+use serde::{Deserialize, Serialize};
+
+/// The proof system producing the proofs of a rollup.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProofSystem {
+    #[default]
+    Fflonk,
+    Plonk,
+    Groth16,
+}
+
+/// The expected shape of the proofs submitted by a rollup.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ProofFormat {
+    #[serde(default)]
+    pub proof_system: ProofSystem,
+    /// The number of 32-byte words of the proofs.
+    #[serde(default = "default_proof_length")]
+    pub proof_length: usize,
+    /// The prefix of the proofs identifying the verifier able to check them,
+    /// if the verifier of the rollup routes the proofs by selector.
+    #[serde(default)]
+    pub verifier_selector: Option<Bytes>,
+}
+
+impl Default for ProofFormat {
+    fn default() -> Self {
+        Self {
+            proof_system: ProofSystem::default(),
+            proof_length: default_proof_length(),
+            verifier_selector: None,
+        }
+    }
+}
+
+/// The length of the fflonk proofs expected by the rollup manager contract.
+const fn default_proof_length() -> usize {
+    24
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use serde_with::{serde_as, DisplayFromStr};
+
+    use super::*;
+
+    #[test]
+    fn deserialize_proof_formats() {
+        #[serde_as]
+        #[derive(Deserialize)]
+        struct Config {
+            #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+            #[serde(rename = "ProofFormats")]
+            proof_formats: HashMap<u32, ProofFormat>,
+        }
+
+        let config: Config = toml::from_str(
+            r#"
+            [ProofFormats.1]
+
+            [ProofFormats.2]
+            ProofSystem = "Plonk"
+            ProofLength = 27
+            VerifierSelector = "0x0a1b2c3d"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.proof_formats[&1], ProofFormat::default());
+        assert_eq!(
+            config.proof_formats[&2],
+            ProofFormat {
+                proof_system: ProofSystem::Plonk,
+                proof_length: 27,
+                verifier_selector: Some(vec![0x0a, 0x1b, 0x2c, 0x3d].into()),
+            }
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use serde::Deserialize;
+
+/// The prover of the pessimistic proofs.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "Type")]
+pub enum ProverConfig {
+    /// Prove on the CPUs of the node.
+    #[default]
+    Local,
+    /// Delegate the proving to the SP1 prover network.
+    Network {
+        /// The environment variable holding the private key of the prover
+        /// network account requesting the proofs.
+        #[serde(rename = "PrivateKeyEnv", default = "default_private_key_env")]
+        private_key_env: String,
+    },
+}
+
+fn default_private_key_env() -> String {
+    "SP1_PRIVATE_KEY".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_network_prover() {
+        let config = toml::from_str::<ProverConfig>(r#"Type = "Local""#).unwrap();
+
+        assert_eq!(config, ProverConfig::Local);
+
+        let config = toml::from_str::<ProverConfig>(r#"Type = "Network""#).unwrap();
+
+        assert_eq!(
+            config,
+            ProverConfig::Network {
+                private_key_env: "SP1_PRIVATE_KEY".to_string()
+            }
+        );
+
+        let toml = r#"
+            Type = "Network"
+            PrivateKeyEnv = "PROVER_KEY"
+            "#;
+        let config = toml::from_str::<ProverConfig>(toml).unwrap();
+
+        assert_eq!(
+            config,
+            ProverConfig::Network {
+                private_key_env: "PROVER_KEY".to_string()
+            }
+        );
+    }
+}
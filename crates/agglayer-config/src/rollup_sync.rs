@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_with::{serde_as, DurationSeconds};
+
+/// The configuration of the sync of the rollups registered on the rollup
+/// manager contract.
+#[serde_as]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct RollupSyncConfig {
+    /// Whether the registered rollups are synced from L1, the submissions of
+    /// the rollups missing from the rollup manager contract being rejected.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Interval at which L1 is polled for the rollups created, added or
+    /// updated since the last poll.
+    #[serde(default = "default_poll_interval")]
+    #[serde_as(as = "DurationSeconds")]
+    pub poll_interval: Duration,
+    /// Interval at which every registered rollup is fetched again, catching
+    /// up on the changes of their trusted sequencer.
+    #[serde(default = "default_resync_interval")]
+    #[serde_as(as = "DurationSeconds")]
+    pub resync_interval: Duration,
+    /// The maximum number of blocks queried at once.
+    #[serde(default = "default_max_block_range")]
+    pub max_block_range: u64,
+}
+
+impl Default for RollupSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval: default_poll_interval(),
+            resync_interval: default_resync_interval(),
+            max_block_range: default_max_block_range(),
+        }
+    }
+}
+
+const fn default_poll_interval() -> Duration {
+    Duration::from_secs(12)
+}
+
+const fn default_resync_interval() -> Duration {
+    Duration::from_secs(300)
+}
+
+const fn default_max_block_range() -> u64 {
+    1_000
+}
@@ -1,12 +1,22 @@
-use std::{collections::HashMap, net::Ipv4Addr, str::FromStr, time::Duration};
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
 
+use ethers::types::Address;
 use jsonrpsee::core::TEN_MB_SIZE_BYTES;
 use serde::{
     de::{MapAccess, Visitor},
     Deserialize, Deserializer,
 };
+use serde_with::{serde_as, DisplayFromStr, DurationSeconds};
 use url::Url;
 
+use crate::secret::deserialize_secret;
+
 /// The default port for the local RPC server.
 const DEFAULT_PORT: u16 = 9090;
 
@@ -20,6 +30,49 @@ pub struct RpcConfig {
     pub port: u16,
     #[serde(default = "default_host")]
     pub host: Ipv4Addr,
+    /// The socket addresses to listen on, IPv4 or IPv6. If empty, the server
+    /// listens on `Host` and `Port` only.
+    #[serde(default)]
+    pub listen: Vec<SocketAddr>,
+    /// The API keys allowed to submit proofs, each one scoped to a set of
+    /// rollup ids. If empty, the submission endpoints are open to anyone.
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    /// The JWT bearer tokens allowed to submit proofs, each one scoped to the
+    /// rollup ids of its `rollupIds` claim. If absent, bearer tokens are
+    /// ignored.
+    #[serde(rename = "JWT", default)]
+    pub jwt: Option<JwtConfig>,
+    /// The addresses allowed to sign request bodies. If non-empty, every
+    /// request, on the admin RPC as well, must carry an ECDSA signature of its
+    /// `x-request-timestamp` header and its body, produced by one of these
+    /// addresses, in the `x-request-signature` header.
+    #[serde(default)]
+    pub request_signers: Vec<Address>,
+    /// The HTTP access log configuration. If absent, access logs are disabled.
+    #[serde(default)]
+    pub access_log: Option<AccessLogConfig>,
+    /// The memory budget of the submissions pending verification or
+    /// settlement.
+    #[serde(default)]
+    pub pending_submissions: PendingSubmissionsConfig,
+    /// The rate limits of the submissions of the rollups. If absent, the
+    /// submissions aren't rate limited.
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// The rate limit of the requests, per IP address of the client. If
+    /// absent, the requests aren't rate limited.
+    #[serde(rename = "IPRateLimit", default)]
+    pub ip_rate_limit: Option<RateLimit>,
+    /// Whether `interop_sendTx` returns the hash of the proof as soon as it is
+    /// verified, the proof being settled in the background. Its settlement is
+    /// then tracked with `interop_getTxStatus`, by proof hash.
+    #[serde(default)]
+    pub async_settlement: bool,
+    /// The TLS configuration of the RPC server. If absent, the RPC server
+    /// serves plain HTTP.
+    #[serde(rename = "TLS", default)]
+    pub tls: Option<TlsConfig>,
 
     // Skip serialization of these fields as we don't need to expose them in the
     // configuration yet.
@@ -46,6 +99,16 @@ impl Default for RpcConfig {
         Self {
             port: default_port(),
             host: default_host(),
+            listen: Vec::new(),
+            api_keys: Vec::new(),
+            jwt: None,
+            request_signers: Vec::new(),
+            access_log: None,
+            pending_submissions: PendingSubmissionsConfig::default(),
+            rate_limit: None,
+            ip_rate_limit: None,
+            async_settlement: false,
+            tls: None,
             max_request_body_size: default_body_size(),
             max_response_body_size: default_body_size(),
             max_connections: default_max_connections(),
@@ -55,6 +118,141 @@ impl Default for RpcConfig {
     }
 }
 
+/// An API key along with the rollups it is allowed to submit proofs for.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiKeyConfig {
+    /// The secret value expected in the `x-api-key` header. It may be given
+    /// as a `file://` path.
+    #[serde(deserialize_with = "deserialize_secret")]
+    pub key: String,
+    /// The rollup ids this API key is allowed to submit proofs for.
+    #[serde(rename = "RollupIDs")]
+    pub rollup_ids: Vec<u32>,
+}
+
+/// The verification of the JWT bearer tokens, signed with HMAC-SHA256.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct JwtConfig {
+    /// The secret shared with the issuer of the tokens. It may be given as a
+    /// `file://` path.
+    #[serde(deserialize_with = "deserialize_secret")]
+    pub secret: String,
+    /// The expected `iss` claim of the tokens, if any.
+    #[serde(default)]
+    pub issuer: Option<String>,
+    /// The expected `aud` claim of the tokens, if any.
+    #[serde(default)]
+    pub audience: Option<String>,
+}
+
+/// The HTTP access log configuration.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct AccessLogConfig {
+    /// The ratio of requests to log, between `0.0` and `1.0`. Defaults to
+    /// logging every request.
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f64,
+}
+
+/// The TLS configuration of the RPC server.
+#[serde_as]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct TlsConfig {
+    /// The path of the PEM encoded certificate chain.
+    pub cert_path: PathBuf,
+    /// The path of the PEM encoded private key.
+    pub key_path: PathBuf,
+    /// Interval at which the certificate and the key are checked for
+    /// rotation, and reloaded once modified.
+    #[serde(default = "default_tls_reload_interval")]
+    #[serde_as(as = "DurationSeconds")]
+    pub reload_interval: Duration,
+}
+
+/// The memory budget of the submissions pending verification or settlement.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct PendingSubmissionsConfig {
+    /// The maximum number of bytes held by the pending submissions.
+    #[serde(default = "default_memory_budget")]
+    pub memory_budget: usize,
+    /// What to do with a submission that doesn't fit in the budget.
+    #[serde(default)]
+    pub eviction_policy: EvictionPolicy,
+}
+
+impl Default for PendingSubmissionsConfig {
+    fn default() -> Self {
+        Self {
+            memory_budget: default_memory_budget(),
+            eviction_policy: EvictionPolicy::default(),
+        }
+    }
+}
+
+/// The rate limits of the submissions, per rollup.
+#[serde_as]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct RateLimitConfig {
+    /// The rate limit of the rollups without a rate limit of their own.
+    pub default: RateLimit,
+    /// The rate limits of specific rollups, by rollup ID.
+    #[serde(default)]
+    #[serde_as(as = "HashMap<DisplayFromStr, _>")]
+    pub rollups: HashMap<u32, RateLimit>,
+}
+
+impl RateLimitConfig {
+    /// Get the rate limit of the given rollup.
+    pub fn of(&self, rollup_id: u32) -> &RateLimit {
+        self.rollups.get(&rollup_id).unwrap_or(&self.default)
+    }
+}
+
+/// A token bucket rate limit.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct RateLimit {
+    /// The number of submissions per second allowed over time.
+    pub rate: f64,
+    /// The number of submissions allowed in a burst.
+    pub burst: u32,
+}
+
+/// The policy applied to the submissions that don't fit in the memory budget.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EvictionPolicy {
+    /// Reject the new submission, leaving the pending ones untouched.
+    #[default]
+    RejectNew,
+    /// Evict the oldest submissions of the same rollup still pending
+    /// verification to make room for the new one, so that a rollup flooding
+    /// the agglayer only ever evicts its own submissions. The new submission
+    /// is rejected if evicting them all isn't enough. Submissions already
+    /// being settled on L1 are never evicted.
+    EvictOldestPerRollup,
+}
+
+/// The default memory budget of the pending submissions, 256MB.
+const fn default_memory_budget() -> usize {
+    256 * 1024 * 1024
+}
+
+/// The default interval at which the TLS certificate is checked for rotation.
+const fn default_tls_reload_interval() -> Duration {
+    Duration::from_secs(60)
+}
+
+/// The default access log sample rate.
+const fn default_sample_rate() -> f64 {
+    1.0
+}
+
 /// The default maximum number of connections.
 fn default_max_connections() -> u32 {
     100
@@ -124,3 +322,229 @@ fn from_env_or_default<T: FromStr>(key: &str, default: T) -> T {
         .and_then(|value| value.parse().ok())
         .unwrap_or(default)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_api_keys() {
+        let toml = r#"
+            [[ApiKeys]]
+            Key = "team-a"
+            RollupIDs = [1, 2]
+
+            [[ApiKeys]]
+            Key = "team-b"
+            RollupIDs = [3]
+            "#;
+
+        let config = toml::from_str::<RpcConfig>(toml).unwrap();
+
+        assert_eq!(config.api_keys.len(), 2);
+        assert_eq!(config.api_keys[0].key, "team-a");
+        assert_eq!(config.api_keys[0].rollup_ids, vec![1, 2]);
+        assert_eq!(config.api_keys[1].rollup_ids, vec![3]);
+    }
+
+    #[test]
+    fn api_keys_default_to_empty() {
+        let config = toml::from_str::<RpcConfig>("").unwrap();
+
+        assert!(config.api_keys.is_empty());
+        assert!(config.request_signers.is_empty());
+        assert!(config.access_log.is_none());
+    }
+
+    #[test]
+    fn deserialize_access_log() {
+        let toml = r#"
+            [AccessLog]
+            SampleRate = 0.25
+            "#;
+
+        let config = toml::from_str::<RpcConfig>(toml).unwrap();
+
+        assert_eq!(config.access_log.unwrap().sample_rate, 0.25);
+
+        let toml = r#"
+            [AccessLog]
+            "#;
+
+        let config = toml::from_str::<RpcConfig>(toml).unwrap();
+
+        assert_eq!(config.access_log.unwrap().sample_rate, 1.0);
+    }
+
+    #[test]
+    fn deserialize_request_signers() {
+        let toml = r#"
+            RequestSigners = ["0xB7f8BC63BbcaD18155201308C8f3540b07f84F5e"]
+            "#;
+
+        let config = toml::from_str::<RpcConfig>(toml).unwrap();
+
+        assert_eq!(
+            config.request_signers,
+            vec!["0xB7f8BC63BbcaD18155201308C8f3540b07f84F5e"
+                .parse::<Address>()
+                .unwrap()]
+        );
+    }
+
+    #[test]
+    fn deserialize_async_settlement() {
+        let config = toml::from_str::<RpcConfig>("").unwrap();
+
+        assert!(!config.async_settlement);
+
+        let config = toml::from_str::<RpcConfig>("AsyncSettlement = true").unwrap();
+
+        assert!(config.async_settlement);
+    }
+
+    #[test]
+    fn deserialize_jwt() {
+        let config = toml::from_str::<RpcConfig>("").unwrap();
+
+        assert!(config.jwt.is_none());
+
+        let toml = r#"
+            [JWT]
+            Secret = "shared-secret"
+            Audience = "agglayer"
+            "#;
+
+        let jwt = toml::from_str::<RpcConfig>(toml).unwrap().jwt.unwrap();
+
+        assert_eq!(jwt.secret, "shared-secret");
+        assert_eq!(jwt.issuer, None);
+        assert_eq!(jwt.audience.as_deref(), Some("agglayer"));
+    }
+
+    #[test]
+    fn deserialize_tls() {
+        let config = toml::from_str::<RpcConfig>("").unwrap();
+
+        assert!(config.tls.is_none());
+
+        let toml = r#"
+            [TLS]
+            CertPath = "/etc/agglayer/tls.crt"
+            KeyPath = "/etc/agglayer/tls.key"
+            "#;
+
+        let tls = toml::from_str::<RpcConfig>(toml).unwrap().tls.unwrap();
+
+        assert_eq!(tls.cert_path, PathBuf::from("/etc/agglayer/tls.crt"));
+        assert_eq!(tls.key_path, PathBuf::from("/etc/agglayer/tls.key"));
+        assert_eq!(tls.reload_interval, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn deserialize_pending_submissions() {
+        let config = toml::from_str::<RpcConfig>("").unwrap();
+
+        assert_eq!(config.pending_submissions.memory_budget, 256 * 1024 * 1024);
+        assert_eq!(
+            config.pending_submissions.eviction_policy,
+            EvictionPolicy::RejectNew
+        );
+
+        let toml = r#"
+            [PendingSubmissions]
+            MemoryBudget = 1048576
+            EvictionPolicy = "EvictOldestPerRollup"
+            "#;
+
+        let config = toml::from_str::<RpcConfig>(toml).unwrap();
+
+        assert_eq!(config.pending_submissions.memory_budget, 1048576);
+        assert_eq!(
+            config.pending_submissions.eviction_policy,
+            EvictionPolicy::EvictOldestPerRollup
+        );
+    }
+
+    #[test]
+    fn deserialize_rate_limit() {
+        let config = toml::from_str::<RpcConfig>("").unwrap();
+
+        assert!(config.rate_limit.is_none());
+
+        let toml = r#"
+            [RateLimit.Default]
+            Rate = 0.5
+            Burst = 4
+
+            [RateLimit.Rollups.2]
+            Rate = 10.0
+            Burst = 20
+            "#;
+
+        let rate_limit = toml::from_str::<RpcConfig>(toml)
+            .unwrap()
+            .rate_limit
+            .unwrap();
+
+        assert_eq!(
+            rate_limit.of(1),
+            &RateLimit {
+                rate: 0.5,
+                burst: 4
+            }
+        );
+        assert_eq!(
+            rate_limit.of(2),
+            &RateLimit {
+                rate: 10.0,
+                burst: 20
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_ip_rate_limit() {
+        let config = toml::from_str::<RpcConfig>("").unwrap();
+
+        assert!(config.ip_rate_limit.is_none());
+
+        let toml = r#"
+            [IPRateLimit]
+            Rate = 20.0
+            Burst = 50
+            "#;
+
+        let config = toml::from_str::<RpcConfig>(toml).unwrap();
+
+        assert_eq!(
+            config.ip_rate_limit,
+            Some(RateLimit {
+                rate: 20.0,
+                burst: 50
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_listen_addresses() {
+        let config = toml::from_str::<RpcConfig>("").unwrap();
+
+        assert!(config.listen.is_empty());
+
+        let toml = r#"
+            Listen = ["0.0.0.0:9090", "[::]:9090", "127.0.0.1:9091"]
+            "#;
+
+        let config = toml::from_str::<RpcConfig>(toml).unwrap();
+
+        assert_eq!(
+            config.listen,
+            vec![
+                "0.0.0.0:9090".parse::<SocketAddr>().unwrap(),
+                "[::]:9090".parse().unwrap(),
+                "127.0.0.1:9091".parse().unwrap(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Secrets read from files.
+//!
+//! The sensitive values of the configuration, such as the API keys, the JWT
+//! secret or the keystore passphrases, may be given as a `file://` path
+//! instead of being inlined, e.g. `Key = "file:///run/secrets/api-key"`, so
+//! that they can be mounted by Kubernetes or a Vault agent. The file is read
+//! once, when the configuration is loaded, with any trailing newline ignored.
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer};
+
+/// The scheme of the secrets read from files.
+const FILE_SCHEME: &str = "file://";
+
+/// Resolve the given secret, reading it from its file if given as a `file://`
+/// path.
+fn resolve<E: serde::de::Error>(value: String) -> Result<String, E> {
+    let Some(path) = value.strip_prefix(FILE_SCHEME) else {
+        return Ok(value);
+    };
+
+    std::fs::read_to_string(path)
+        .map(|secret| secret.trim_end_matches(['\r', '\n']).to_string())
+        .map_err(|error| E::custom(format!("unable to read the secret file {path}: {error}")))
+}
+
+/// Deserialize a secret, read from its file if given as a `file://` path.
+pub(crate) fn deserialize_secret<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    resolve(String::deserialize(deserializer)?)
+}
+
+/// Deserialize an optional secret, read from its file if given as a `file://`
+/// path.
+pub(crate) fn deserialize_optional_secret<'de, D>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(resolve)
+        .transpose()
+}
+
+/// Deserialize a map of secrets, each one read from its file if given as a
+/// `file://` path.
+pub(crate) fn deserialize_secret_map<'de, D>(
+    deserializer: D,
+) -> Result<HashMap<String, String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    HashMap::<String, String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(key, value)| Ok((key, resolve(value)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Secrets {
+        #[serde(deserialize_with = "deserialize_secret")]
+        inline: String,
+        #[serde(deserialize_with = "deserialize_secret")]
+        file: String,
+        #[serde(default, deserialize_with = "deserialize_optional_secret")]
+        missing: Option<String>,
+    }
+
+    #[test]
+    fn secrets_are_read_from_files() {
+        let path = std::env::temp_dir().join(format!("agglayer-secret-{:x}", H256::random()));
+        std::fs::write(&path, "s3cr3t\n").unwrap();
+
+        let secrets: Secrets = toml::from_str(&format!(
+            r#"
+            inline = "inlined"
+            file = "file://{}"
+            "#,
+            path.display()
+        ))
+        .unwrap();
+
+        assert_eq!(secrets.inline, "inlined");
+        assert_eq!(secrets.file, "s3cr3t");
+        assert_eq!(secrets.missing, None);
+
+        std::fs::remove_file(&path).unwrap();
+
+        let error = toml::from_str::<Secrets>(&format!(
+            r#"
+            inline = "inlined"
+            file = "file://{}"
+            "#,
+            path.display()
+        ))
+        .err()
+        .unwrap();
+
+        assert!(error.to_string().contains("unable to read the secret file"));
+    }
+}
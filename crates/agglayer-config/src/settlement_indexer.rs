@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_with::{serde_as, DurationSeconds};
+
+/// The configuration of the indexer of the settlement events emitted on L1.
+#[serde_as]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct SettlementIndexerConfig {
+    /// Whether the settlement events are indexed.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// The L1 block from which to start indexing. Defaults to the latest block
+    /// minus the reorg depth.
+    #[serde(default)]
+    pub start_block: Option<u64>,
+    /// Interval at which L1 is polled for new settlement events.
+    #[serde(default = "default_poll_interval")]
+    #[serde_as(as = "DurationSeconds")]
+    pub poll_interval: Duration,
+    /// The maximum number of blocks queried at once.
+    #[serde(default = "default_max_block_range")]
+    pub max_block_range: u64,
+    /// The number of the latest blocks indexed again on every poll to detect
+    /// the settlements reverted by L1 reorgs.
+    #[serde(default = "default_reorg_depth")]
+    pub reorg_depth: u64,
+}
+
+impl Default for SettlementIndexerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            start_block: None,
+            poll_interval: default_poll_interval(),
+            max_block_range: default_max_block_range(),
+            reorg_depth: default_reorg_depth(),
+        }
+    }
+}
+
+const fn default_enabled() -> bool {
+    true
+}
+
+const fn default_poll_interval() -> Duration {
+    Duration::from_secs(12)
+}
+
+const fn default_max_block_range() -> u64 {
+    1_000
+}
+
+const fn default_reorg_depth() -> u64 {
+    64
+}
@@ -0,0 +1,56 @@
+use serde::Deserialize;
+
+use crate::{deserialize_auth, AuthConfig};
+
+/// The signer dedicated to the settlements of some rollups, which settle
+/// with its own account rather than the one of the `auth` section.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct SettlementSignerConfig {
+    /// The rollups settling with this signer.
+    pub rollups: Vec<u32>,
+    /// The signer, in the form of the `auth` section.
+    #[serde(deserialize_with = "deserialize_auth")]
+    pub auth: AuthConfig,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_settlement_signers() {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Wrapper {
+            settlement_signers: Vec<SettlementSignerConfig>,
+        }
+
+        let toml = r#"
+            [[SettlementSigners]]
+            Rollups = [1, 2]
+            [SettlementSigners.Auth.gcpkms]
+            ProjectId = "project"
+            Location = "location"
+            Keyring = "keyring"
+            KeyName = "key"
+
+            [[SettlementSigners]]
+            Rollups = [3]
+            [SettlementSigners.Auth]
+            PrivateKeys = [{ Path = "/pk/rollup-3.keystore", Password = "" }]
+            "#;
+
+        let signers = toml::from_str::<Wrapper>(toml).unwrap().settlement_signers;
+
+        assert_eq!(signers.len(), 2);
+        assert_eq!(signers[0].rollups, vec![1, 2]);
+        assert!(
+            matches!(&signers[0].auth, AuthConfig::GcpKms(kms) if kms.key_name.as_deref() == Some("key"))
+        );
+        assert_eq!(signers[1].rollups, vec![3]);
+        assert!(
+            matches!(&signers[1].auth, AuthConfig::Local(local) if local.private_keys.len() == 1)
+        );
+    }
+}
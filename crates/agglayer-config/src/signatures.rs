@@ -0,0 +1,40 @@
+use serde::Deserialize;
+
+/// The configuration of the signatures of the submitted proofs.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct SignaturesConfig {
+    /// Whether the signatures of the legacy hash of the proofs are still
+    /// accepted, alongside the EIP-712 ones. To be turned off once every
+    /// trusted sequencer signs the EIP-712 typed data.
+    #[serde(default = "default_accept_legacy_hash")]
+    pub accept_legacy_hash: bool,
+}
+
+impl Default for SignaturesConfig {
+    fn default() -> Self {
+        Self {
+            accept_legacy_hash: default_accept_legacy_hash(),
+        }
+    }
+}
+
+fn default_accept_legacy_hash() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_signatures() {
+        let config = toml::from_str::<SignaturesConfig>("").unwrap();
+
+        assert!(config.accept_legacy_hash);
+
+        let config = toml::from_str::<SignaturesConfig>("AcceptLegacyHash = false").unwrap();
+
+        assert!(!config.accept_legacy_hash);
+    }
+}
@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+/// The configuration of the accounting of the L1 costs of the settlements.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct SpendReportsConfig {
+    /// The file persisting the gas and ETH spent settling the proofs of each
+    /// rollup, per epoch. If absent, the spend reports only cover the
+    /// settlements since the node started.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_spend_reports() {
+        let config = toml::from_str::<SpendReportsConfig>("").unwrap();
+
+        assert!(config.path.is_none());
+
+        let toml = r#"
+            Path = "/var/lib/agglayer/spend.json"
+            "#;
+
+        let config = toml::from_str::<SpendReportsConfig>(toml).unwrap();
+
+        assert_eq!(
+            config.path,
+            Some(PathBuf::from("/var/lib/agglayer/spend.json"))
+        );
+    }
+}
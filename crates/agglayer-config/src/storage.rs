@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+/// The persistent storage configuration.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct StorageConfig {
+    /// The directory of the database persisting the proofs being settled,
+    /// settled again on restart if the node crashed in the meantime. If
+    /// absent, the proofs being settled are lost on crash.
+    #[serde(default)]
+    pub pending_settlements_path: Option<PathBuf>,
+    /// The directory of the database indexing the settled proofs, whose new
+    /// submissions are answered with their existing settlement. If absent,
+    /// the settled proofs submitted again are verified again before being
+    /// rejected.
+    #[serde(default)]
+    pub settled_proofs_path: Option<PathBuf>,
+    /// The directory of the database recording the received submissions,
+    /// their verification and their settlement in a hash-chained audit log.
+    /// If absent, the submissions are not audited.
+    #[serde(default)]
+    pub audit_log_path: Option<PathBuf>,
+    /// The directory of the database persisting the last epoch reached by
+    /// the clock, the node refusing to start on an earlier epoch, along with
+    /// the genesis of the time clock. If absent, the time clock starts over
+    /// from epoch 0 on each restart, and the epochs may move backwards across
+    /// restarts once the epoch configuration changes.
+    #[serde(default)]
+    pub clock_state_path: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_storage() {
+        let config = toml::from_str::<StorageConfig>("").unwrap();
+
+        assert!(config.pending_settlements_path.is_none());
+        assert!(config.settled_proofs_path.is_none());
+        assert!(config.audit_log_path.is_none());
+        assert!(config.clock_state_path.is_none());
+
+        let toml = r#"
+            PendingSettlementsPath = "/var/lib/agglayer/pending"
+            SettledProofsPath = "/var/lib/agglayer/settled"
+            AuditLogPath = "/var/lib/agglayer/audit"
+            ClockStatePath = "/var/lib/agglayer/clock"
+            "#;
+
+        let config = toml::from_str::<StorageConfig>(toml).unwrap();
+
+        assert_eq!(
+            config.pending_settlements_path,
+            Some(PathBuf::from("/var/lib/agglayer/pending"))
+        );
+        assert_eq!(
+            config.settled_proofs_path,
+            Some(PathBuf::from("/var/lib/agglayer/settled"))
+        );
+        assert_eq!(
+            config.audit_log_path,
+            Some(PathBuf::from("/var/lib/agglayer/audit"))
+        );
+        assert_eq!(
+            config.clock_state_path,
+            Some(PathBuf::from("/var/lib/agglayer/clock"))
+        );
+    }
+}
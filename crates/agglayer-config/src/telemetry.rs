@@ -1,24 +1,77 @@
-use std::net::SocketAddr;
+use std::{collections::HashMap, net::SocketAddr};
 
 use serde::Deserialize;
+use url::Url;
 
 use super::DEFAULT_IP;
+use crate::secret::deserialize_secret_map;
 
-#[derive(Deserialize, Debug, Clone, Copy)]
+#[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
 pub struct TelemetryConfig {
     #[serde(rename = "PrometheusAddr", default = "default_metrics_api_addr")]
     pub addr: SocketAddr,
+    /// The export of the traces. If absent, the spans are only logged.
+    #[serde(default)]
+    pub traces: Option<TracesConfig>,
 }
 
 impl Default for TelemetryConfig {
     fn default() -> Self {
         Self {
             addr: default_metrics_api_addr(),
+            traces: None,
         }
     }
 }
 
+/// The export of the traces to an OpenTelemetry collector, over OTLP/gRPC.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct TracesConfig {
+    /// The OTLP/gRPC endpoint of the collector, e.g. Jaeger or Tempo.
+    pub endpoint: Url,
+    /// The headers sent along with the exported traces, e.g. to authenticate
+    /// with the collector. Their values may be given as `file://` paths.
+    #[serde(default, deserialize_with = "deserialize_secret_map")]
+    pub headers: HashMap<String, String>,
+    /// The ratio of the traces to export, between `0.0` and `1.0`. Defaults to
+    /// exporting every trace.
+    #[serde(default = "default_sample_ratio")]
+    pub sample_ratio: f64,
+}
+
 const fn default_metrics_api_addr() -> SocketAddr {
     SocketAddr::V4(std::net::SocketAddrV4::new(DEFAULT_IP, 3000))
 }
+
+const fn default_sample_ratio() -> f64 {
+    1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_traces() {
+        let config = toml::from_str::<TelemetryConfig>("").unwrap();
+
+        assert!(config.traces.is_none());
+
+        let toml = r#"
+            [Traces]
+            Endpoint = "http://tempo:4317"
+            Headers = { "x-scope-orgid" = "agglayer" }
+            "#;
+
+        let traces = toml::from_str::<TelemetryConfig>(toml)
+            .unwrap()
+            .traces
+            .unwrap();
+
+        assert_eq!(traces.endpoint.as_str(), "http://tempo:4317/");
+        assert_eq!(traces.headers["x-scope-orgid"], "agglayer");
+        assert_eq!(traces.sample_ratio, 1.0);
+    }
+}
@@ -0,0 +1,253 @@
+//! Static validation of the configuration.
+//!
+//! These checks catch the inconsistencies the deserialization lets through,
+//! without reaching any external service.
+use std::{collections::HashSet, path::PathBuf};
+
+use crate::{AuthConfig, Config, RateLimit, SettlementLockBackend};
+
+/// An inconsistency of the configuration.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ValidationError {
+    #[error("{section} references rollup {rollup_id}, which is missing from FullNodeRPCs")]
+    UnknownRollup {
+        section: &'static str,
+        rollup_id: u32,
+    },
+    #[error("{field} must be between 0.0 and 1.0, got {value}")]
+    InvalidRatio { field: &'static str, value: f64 },
+    #[error("{field} must have a positive rate and a non-zero burst, got {rate}/s and {burst}")]
+    InvalidRateLimit {
+        field: String,
+        rate: f64,
+        burst: u32,
+    },
+    #[error("{field} {} does not exist", path.display())]
+    MissingFile { field: &'static str, path: PathBuf },
+    #[error("auth.local must list at least one private key")]
+    NoPrivateKey,
+    #[error("rollup {rollup_id} is assigned to several SettlementSigners")]
+    DuplicateSettlementSigner { rollup_id: u32 },
+    #[error("HighAvailability.RenewInterval must be shorter than HighAvailability.LeaseDuration")]
+    LeaseRenewedTooLate,
+    #[error(
+        "HighAvailability.SettlementLocks must be shared by the nodes, the Memory ones only guard \
+         the settlements of a single node"
+    )]
+    SettlementLocksNotShared,
+}
+
+impl Config {
+    /// Check the consistency of the configuration, returning every
+    /// inconsistency found.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        let unknown_rollups = [
+            (
+                "ProofFormats",
+                self.proof_formats.keys().copied().collect::<Vec<_>>(),
+            ),
+            (
+                "ShadowRollups",
+                self.shadow_rollups.iter().copied().collect(),
+            ),
+            (
+                "SettlementSigners",
+                self.settlement_signers
+                    .iter()
+                    .flat_map(|signer| signer.rollups.iter().copied())
+                    .collect(),
+            ),
+        ];
+        for (section, mut rollup_ids) in unknown_rollups {
+            rollup_ids.sort_unstable();
+            errors.extend(
+                rollup_ids
+                    .into_iter()
+                    .filter(|rollup_id| !self.full_node_rpcs.contains_key(rollup_id))
+                    .map(|rollup_id| ValidationError::UnknownRollup { section, rollup_id }),
+            );
+        }
+
+        let mut settling_rollups = HashSet::new();
+        for signer in &self.settlement_signers {
+            errors.extend(
+                signer
+                    .rollups
+                    .iter()
+                    .filter(|rollup_id| !settling_rollups.insert(**rollup_id))
+                    .map(|&rollup_id| ValidationError::DuplicateSettlementSigner { rollup_id }),
+            );
+        }
+
+        let mut ratios = Vec::new();
+        if let Some(access_log) = &self.rpc.access_log {
+            ratios.push(("RPC.AccessLog.SampleRate", access_log.sample_rate));
+        }
+        if let Some(traces) = &self.telemetry.traces {
+            ratios.push(("Telemetry.Traces.SampleRatio", traces.sample_ratio));
+        }
+        errors.extend(
+            ratios
+                .into_iter()
+                .filter(|(_, value)| !(0.0..=1.0).contains(value))
+                .map(|(field, value)| ValidationError::InvalidRatio { field, value }),
+        );
+
+        let mut rate_limits = Vec::new();
+        if let Some(rate_limit) = &self.rpc.rate_limit {
+            rate_limits.push(("RPC.RateLimit.Default".to_string(), rate_limit.default));
+
+            let mut rollups: Vec<_> = rate_limit.rollups.iter().collect();
+            rollups.sort_unstable_by_key(|(rollup_id, _)| **rollup_id);
+            rate_limits.extend(
+                rollups.into_iter().map(|(rollup_id, limit)| {
+                    (format!("RPC.RateLimit.Rollups.{rollup_id}"), *limit)
+                }),
+            );
+        }
+        if let Some(ip_rate_limit) = self.rpc.ip_rate_limit {
+            rate_limits.push(("RPC.IPRateLimit".to_string(), ip_rate_limit));
+        }
+        errors.extend(
+            rate_limits
+                .into_iter()
+                .filter(|(_, limit)| !is_valid_rate_limit(limit))
+                .map(|(field, limit)| ValidationError::InvalidRateLimit {
+                    field,
+                    rate: limit.rate,
+                    burst: limit.burst,
+                }),
+        );
+
+        if let Some(tls) = &self.rpc.tls {
+            for (field, path) in [
+                ("RPC.TLS.CertPath", &tls.cert_path),
+                ("RPC.TLS.KeyPath", &tls.key_path),
+            ] {
+                if !path.exists() {
+                    errors.push(ValidationError::MissingFile {
+                        field,
+                        path: path.clone(),
+                    });
+                }
+            }
+        }
+
+        if let AuthConfig::Local(local) = &self.auth {
+            if local.private_keys.is_empty() {
+                errors.push(ValidationError::NoPrivateKey);
+            }
+        }
+
+        if self.high_availability.enabled
+            && self.high_availability.renew_interval >= self.high_availability.lease_duration
+        {
+            errors.push(ValidationError::LeaseRenewedTooLate);
+        }
+
+        if self.high_availability.enabled
+            && self.high_availability.settlement_locks == SettlementLockBackend::Memory
+        {
+            errors.push(ValidationError::SettlementLocksNotShared);
+        }
+
+        errors
+    }
+}
+
+fn is_valid_rate_limit(limit: &RateLimit) -> bool {
+    limit.rate.is_finite() && limit.rate > 0.0 && limit.burst > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_is_valid() {
+        let config = Config::from_toml(crate::DEFAULT_CONFIG, []).unwrap();
+
+        assert_eq!(config.validate(), Vec::new());
+        assert_eq!(config.full_node_rpcs.len(), 1);
+        assert_eq!(config.rpc.port, 9090);
+        assert_eq!(config.outbound.rpc.settle.max_retries, 3);
+        assert!(config.settlement_indexer.enabled);
+        assert!(!config.high_availability.enabled);
+        assert!(!config.rollup_sync.enabled);
+    }
+
+    #[test]
+    fn inconsistencies_are_all_reported() {
+        let mut config = Config::default();
+        config
+            .full_node_rpcs
+            .insert(1, "http://zkevm-node:8123".parse().unwrap());
+        config.proof_formats.insert(2, Default::default());
+        config.shadow_rollups.insert(3);
+        config.settlement_signers = vec![
+            crate::SettlementSignerConfig {
+                rollups: vec![1],
+                auth: AuthConfig::default(),
+            },
+            crate::SettlementSignerConfig {
+                rollups: vec![1, 4],
+                auth: AuthConfig::default(),
+            },
+        ];
+        config.rpc.access_log = Some(crate::AccessLogConfig { sample_rate: 1.5 });
+        config.rpc.rate_limit = Some(crate::RateLimitConfig {
+            default: RateLimit {
+                rate: 0.0,
+                burst: 10,
+            },
+            rollups: [(
+                1,
+                RateLimit {
+                    rate: 1.0,
+                    burst: 0,
+                },
+            )]
+            .into(),
+        });
+        config.high_availability.enabled = true;
+        config.high_availability.renew_interval = config.high_availability.lease_duration;
+
+        assert_eq!(
+            config.validate(),
+            vec![
+                ValidationError::UnknownRollup {
+                    section: "ProofFormats",
+                    rollup_id: 2
+                },
+                ValidationError::UnknownRollup {
+                    section: "ShadowRollups",
+                    rollup_id: 3
+                },
+                ValidationError::UnknownRollup {
+                    section: "SettlementSigners",
+                    rollup_id: 4
+                },
+                ValidationError::DuplicateSettlementSigner { rollup_id: 1 },
+                ValidationError::InvalidRatio {
+                    field: "RPC.AccessLog.SampleRate",
+                    value: 1.5
+                },
+                ValidationError::InvalidRateLimit {
+                    field: "RPC.RateLimit.Default".to_string(),
+                    rate: 0.0,
+                    burst: 10
+                },
+                ValidationError::InvalidRateLimit {
+                    field: "RPC.RateLimit.Rollups.1".to_string(),
+                    rate: 1.0,
+                    burst: 0
+                },
+                ValidationError::NoPrivateKey,
+                ValidationError::LeaseRenewedTooLate,
+                ValidationError::SettlementLocksNotShared,
+            ]
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! Agglayer smart-contract bindings.
+//!
+//! This crate exposes the bindings generated from the ABIs of the contracts
+//! the agglayer interacts with, along with the [`RollupContract`] trait
+//! wrapping the calls made to them. The [`L1RpcClient`] implements it against
+//! an L1 RPC provider.
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_trait::async_trait;
+use ethers::{
+    abi::{Abi, Function, Param, ParamType, StateMutability, Token},
+    contract::multicall_contract::{Call3, Multicall3, Result as MulticallResult},
+    prelude::*,
+};
+
+pub mod polygon_rollup_manager {
+    use ethers::contract::abigen;
+
+    abigen!(
+        PolygonRollupManager,
+        "./src/contracts/polygonrollupmanager.json",
+    );
+}
+
+pub mod polygon_zk_evm {
+    use ethers::contract::abigen;
+
+    abigen!(PolygonZkEvm, "./src/contracts/polygonzkevm.json",);
+}
+
+use polygon_rollup_manager::{
+    AddExistingRollupFilter, CreateNewRollupFilter, PolygonRollupManager,
+    PolygonRollupManagerEvents, RollupIDToRollupDataReturn, UpdateRollupFilter,
+    VerifyBatchesFilter, VerifyBatchesTrustedAggregatorFilter,
+};
+use polygon_zk_evm::PolygonZkEvm;
+
+#[cfg(test)]
+mod tests;
+
+/// Batches of a rollup verified on L1, as notified by a `VerifyBatches` or
+/// `VerifyBatchesTrustedAggregator` event of the rollup manager contract.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedBatches {
+    pub rollup_id: u32,
+    /// The last batch verified.
+    pub num_batch: u64,
+    pub state_root: H256,
+    pub exit_root: H256,
+    pub aggregator: Address,
+    /// Whether the batches were verified by the trusted aggregator.
+    pub trusted_aggregator: bool,
+    pub block_number: u64,
+    pub block_hash: H256,
+    pub transaction_hash: H256,
+}
+
+/// The arguments of a `verifyBatchesTrustedAggregator` call.
+///
+/// Proofs of 24 words are settled through the rollup manager ABI. Proofs of
+/// other lengths are encoded as a `bytes32[N]` array, for the rollup manager
+/// deployments taking proofs of that length.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyBatchesTrustedAggregator {
+    pub rollup_id: u32,
+    pub pending_state_num: u64,
+    pub init_num_batch: u64,
+    pub final_new_batch: u64,
+    pub new_local_exit_root: [u8; 32],
+    pub new_state_root: [u8; 32],
+    pub beneficiary: Address,
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// The calls made by the agglayer to the rollup manager contract and to the
+/// rollup contracts.
+#[async_trait]
+pub trait RollupContract {
+    type M: Middleware;
+
+    /// Get the rollup metadata for the given rollup id.
+    ///
+    /// This calls `rollupIDToRollupData` (`0xf9c4c2ae`) on the rollup manager
+    /// contract.
+    async fn get_rollup_metadata(
+        &self,
+        rollup_id: u32,
+    ) -> Result<RollupIDToRollupDataReturn, ContractError<Self::M>>;
+
+    /// Get the number of rollups attached to the rollup manager contract.
+    ///
+    /// This calls `rollupCount` (`0xf4e92675`) on the rollup manager contract.
+    async fn get_rollup_count(&self) -> Result<u32, ContractError<Self::M>>;
+
+    /// Get the verification time target of the batches, in seconds.
+    ///
+    /// This calls `verifyBatchTimeTarget` (`0x0a0d9fbe`) on the rollup manager
+    /// contract.
+    async fn get_verify_batch_time_target(&self) -> Result<u64, ContractError<Self::M>>;
+
+    /// Get the address of the trusted sequencer for the given rollup id.
+    ///
+    /// This calls `trustedSequencer` (`0xcfa8ed47`) on the rollup contract.
+    async fn get_trusted_sequencer_address(
+        &self,
+        rollup_id: u32,
+    ) -> Result<Address, ContractError<Self::M>>;
+
+    /// Build a call to `verifyBatchesTrustedAggregator` (`0x1489ed10`) on the
+    /// rollup manager contract.
+    ///
+    /// Note that this does not actually invoke the function, but rather
+    /// constructs a [`ContractCall`] that can be used to create a dry-run or
+    /// send a transaction.
+    fn build_verify_batches_trusted_aggregator_call(
+        &self,
+        call: VerifyBatchesTrustedAggregator,
+    ) -> Result<ContractCall<Self::M, ()>, ContractError<Self::M>>;
+
+    /// Build a call to `aggregate3` (`0x82ad56cb`) on the given
+    /// Multicall3-compatible contract, relaying the given calls in a single
+    /// transaction. The whole transaction reverts if any of the calls does.
+    ///
+    /// Note that this does not actually invoke the function either.
+    fn build_multicall(
+        &self,
+        multicall_contract: Address,
+        calls: Vec<ContractCall<Self::M, ()>>,
+    ) -> ContractCall<Self::M, Vec<MulticallResult>>;
+
+    /// Get the batches verified on L1 between the given blocks, inclusive,
+    /// ordered by block.
+    ///
+    /// This queries the `VerifyBatches` and `VerifyBatchesTrustedAggregator`
+    /// events of the rollup manager contract.
+    async fn get_verified_batches(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<VerifiedBatches>, ContractError<Self::M>>;
+
+    /// Get the ids of the rollups created, added or updated between the given
+    /// blocks, inclusive, in the order of the events.
+    ///
+    /// This queries the `CreateNewRollup`, `AddExistingRollup` and
+    /// `UpdateRollup` events of the rollup manager contract.
+    async fn get_updated_rollups(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<u32>, ContractError<Self::M>>;
+
+    /// Get the last verified batch of the given rollup.
+    async fn get_last_verified_batch(&self, rollup_id: u32) -> Result<u64, ContractError<Self::M>>
+    where
+        Self: Sync,
+    {
+        Ok(self
+            .get_rollup_metadata(rollup_id)
+            .await?
+            .last_verified_batch)
+    }
+}
+
+/// [`RollupContract`] implementation reading and calling the contracts through
+/// an L1 RPC provider.
+#[derive(Debug)]
+pub struct L1RpcClient<RpcProvider> {
+    rpc: Arc<RpcProvider>,
+    rollup_manager: PolygonRollupManager<RpcProvider>,
+}
+
+impl<RpcProvider> L1RpcClient<RpcProvider>
+where
+    RpcProvider: Middleware + 'static,
+{
+    /// Create a new client for the rollup manager contract deployed at the
+    /// given address.
+    pub fn new(rpc: Arc<RpcProvider>, rollup_manager_contract: Address) -> Self {
+        Self {
+            rollup_manager: PolygonRollupManager::new(rollup_manager_contract, rpc.clone()),
+            rpc,
+        }
+    }
+
+    /// Get a [`ContractInstance`], [`PolygonZkEvm`], of the rollup contract at
+    /// the given rollup id.
+    async fn get_rollup_contract(
+        &self,
+        rollup_id: u32,
+    ) -> Result<PolygonZkEvm<RpcProvider>, ContractError<RpcProvider>> {
+        let rollup_metadata = self.get_rollup_metadata(rollup_id).await?;
+
+        Ok(PolygonZkEvm::new(
+            rollup_metadata.rollup_contract,
+            self.rpc.clone(),
+        ))
+    }
+}
+
+#[async_trait]
+impl<RpcProvider> RollupContract for L1RpcClient<RpcProvider>
+where
+    RpcProvider: Middleware + 'static,
+{
+    type M = RpcProvider;
+
+    async fn get_rollup_metadata(
+        &self,
+        rollup_id: u32,
+    ) -> Result<RollupIDToRollupDataReturn, ContractError<RpcProvider>> {
+        let tuple = self
+            .rollup_manager
+            .rollup_id_to_rollup_data(rollup_id)
+            .await?;
+
+        Ok(RollupIDToRollupDataReturn {
+            rollup_contract: tuple.0,
+            chain_id: tuple.1,
+            verifier: tuple.2,
+            fork_id: tuple.3,
+            last_local_exit_root: tuple.4,
+            last_batch_sequenced: tuple.5,
+            last_verified_batch: tuple.6,
+            last_pending_state: tuple.7,
+            last_pending_state_consolidated: tuple.8,
+            last_verified_batch_before_upgrade: tuple.9,
+            rollup_type_id: tuple.10,
+            rollup_compatibility_id: tuple.11,
+        })
+    }
+
+    async fn get_rollup_count(&self) -> Result<u32, ContractError<RpcProvider>> {
+        self.rollup_manager.rollup_count().await
+    }
+
+    async fn get_verify_batch_time_target(&self) -> Result<u64, ContractError<RpcProvider>> {
+        self.rollup_manager.verify_batch_time_target().await
+    }
+
+    async fn get_trusted_sequencer_address(
+        &self,
+        rollup_id: u32,
+    ) -> Result<Address, ContractError<RpcProvider>> {
+        self.get_rollup_contract(rollup_id)
+            .await?
+            .trusted_sequencer()
+            .await
+    }
+
+    async fn get_verified_batches(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<VerifiedBatches>, ContractError<RpcProvider>> {
+        let events = self
+            .rollup_manager
+            .events()
+            .from_block(from_block)
+            .to_block(to_block)
+            .topic0(vec![
+                VerifyBatchesFilter::signature(),
+                VerifyBatchesTrustedAggregatorFilter::signature(),
+            ])
+            .query_with_meta()
+            .await?;
+
+        Ok(events
+            .into_iter()
+            .filter_map(|(event, meta)| {
+                let verified_batches =
+                    |rollup_id, num_batch, state_root, exit_root, aggregator| VerifiedBatches {
+                        rollup_id,
+                        num_batch,
+                        state_root: H256(state_root),
+                        exit_root: H256(exit_root),
+                        aggregator,
+                        trusted_aggregator: false,
+                        block_number: meta.block_number.as_u64(),
+                        block_hash: meta.block_hash,
+                        transaction_hash: meta.transaction_hash,
+                    };
+
+                match event {
+                    PolygonRollupManagerEvents::VerifyBatchesFilter(event) => {
+                        Some(verified_batches(
+                            event.rollup_id,
+                            event.num_batch,
+                            event.state_root,
+                            event.exit_root,
+                            event.aggregator,
+                        ))
+                    }
+                    PolygonRollupManagerEvents::VerifyBatchesTrustedAggregatorFilter(event) => {
+                        Some(VerifiedBatches {
+                            trusted_aggregator: true,
+                            ..verified_batches(
+                                event.rollup_id,
+                                event.num_batch,
+                                event.state_root,
+                                event.exit_root,
+                                event.aggregator,
+                            )
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .collect())
+    }
+
+    async fn get_updated_rollups(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Vec<u32>, ContractError<RpcProvider>> {
+        let events = self
+            .rollup_manager
+            .events()
+            .from_block(from_block)
+            .to_block(to_block)
+            .topic0(vec![
+                CreateNewRollupFilter::signature(),
+                AddExistingRollupFilter::signature(),
+                UpdateRollupFilter::signature(),
+            ])
+            .query()
+            .await?;
+
+        Ok(events
+            .into_iter()
+            .filter_map(|event| match event {
+                PolygonRollupManagerEvents::CreateNewRollupFilter(event) => Some(event.rollup_id),
+                PolygonRollupManagerEvents::AddExistingRollupFilter(event) => Some(event.rollup_id),
+                PolygonRollupManagerEvents::UpdateRollupFilter(event) => Some(event.rollup_id),
+                _ => None,
+            })
+            .collect())
+    }
+
+    fn build_verify_batches_trusted_aggregator_call(
+        &self,
+        call: VerifyBatchesTrustedAggregator,
+    ) -> Result<ContractCall<RpcProvider, ()>, ContractError<RpcProvider>> {
+        if let Ok(proof) = <[[u8; 32]; 24]>::try_from(call.proof.as_slice()) {
+            return Ok(self.rollup_manager.verify_batches_trusted_aggregator(
+                call.rollup_id,
+                call.pending_state_num,
+                call.init_num_batch,
+                call.final_new_batch,
+                call.new_local_exit_root,
+                call.new_state_root,
+                call.beneficiary,
+                proof,
+            ));
+        }
+
+        let function = verify_batches_trusted_aggregator_function(call.proof.len());
+        let name = function.name.clone();
+        let abi = Abi {
+            functions: BTreeMap::from([(name.clone(), vec![function])]),
+            ..Default::default()
+        };
+
+        let tokens = vec![
+            Token::Uint(call.rollup_id.into()),
+            Token::Uint(call.pending_state_num.into()),
+            Token::Uint(call.init_num_batch.into()),
+            Token::Uint(call.final_new_batch.into()),
+            Token::FixedBytes(call.new_local_exit_root.to_vec()),
+            Token::FixedBytes(call.new_state_root.to_vec()),
+            Token::Address(call.beneficiary),
+            Token::FixedArray(
+                call.proof
+                    .iter()
+                    .map(|word| Token::FixedBytes(word.to_vec()))
+                    .collect(),
+            ),
+        ];
+
+        Contract::new(self.rollup_manager.address(), abi, self.rpc.clone())
+            .method(&name, tokens.as_slice())
+            .map_err(ContractError::from)
+    }
+
+    fn build_multicall(
+        &self,
+        multicall_contract: Address,
+        calls: Vec<ContractCall<RpcProvider, ()>>,
+    ) -> ContractCall<RpcProvider, Vec<MulticallResult>> {
+        let calls = calls
+            .into_iter()
+            .map(|call| Call3 {
+                target: call.tx.to_addr().copied().unwrap_or_default(),
+                allow_failure: false,
+                call_data: call.calldata().unwrap_or_default(),
+            })
+            .collect();
+
+        Multicall3::new(multicall_contract, self.rpc.clone()).aggregate_3(calls)
+    }
+}
+
+/// The `verifyBatchesTrustedAggregator` function taking a proof of the given
+/// number of words.
+#[allow(deprecated)]
+fn verify_batches_trusted_aggregator_function(proof_length: usize) -> Function {
+    let param = |name: &str, kind| Param {
+        name: name.to_string(),
+        kind,
+        internal_type: None,
+    };
+
+    Function {
+        name: "verifyBatchesTrustedAggregator".to_string(),
+        inputs: vec![
+            param("rollupID", ParamType::Uint(32)),
+            param("pendingStateNum", ParamType::Uint(64)),
+            param("initNumBatch", ParamType::Uint(64)),
+            param("finalNewBatch", ParamType::Uint(64)),
+            param("newLocalExitRoot", ParamType::FixedBytes(32)),
+            param("newStateRoot", ParamType::FixedBytes(32)),
+            param("beneficiary", ParamType::Address),
+            param(
+                "proof",
+                ParamType::FixedArray(Box::new(ParamType::FixedBytes(32)), proof_length),
+            ),
+        ],
+        outputs: vec![],
+        constant: None,
+        state_mutability: StateMutability::NonPayable,
+    }
+}
@@ -0,0 +1,214 @@
+use std::sync::Arc;
+
+use ethers::{
+    abi::{AbiDecode, AbiEncode},
+    contract::multicall_contract::Aggregate3Call,
+    prelude::*,
+};
+
+use crate::{
+    polygon_rollup_manager::{RollupIDToRollupDataReturn, VerifyBatchesTrustedAggregatorCall},
+    polygon_zk_evm::TrustedSequencerReturn,
+    verify_batches_trusted_aggregator_function, L1RpcClient, RollupContract,
+    VerifyBatchesTrustedAggregator,
+};
+
+fn rollup_data(rollup_contract: Address) -> RollupIDToRollupDataReturn {
+    RollupIDToRollupDataReturn {
+        chain_id: 1,
+        rollup_contract,
+        verifier: H160::random(),
+        fork_id: 0,
+        last_local_exit_root: [0; 32],
+        last_batch_sequenced: 10,
+        last_verified_batch: 7,
+        last_pending_state: 0,
+        last_pending_state_consolidated: 0,
+        last_verified_batch_before_upgrade: 0,
+        rollup_type_id: 1,
+        rollup_compatibility_id: 0,
+    }
+}
+
+#[tokio::test]
+async fn get_last_verified_batch() {
+    let (provider, mock) = Provider::mocked();
+    let client = L1RpcClient::new(Arc::new(provider), Address::random());
+
+    mock.push_response(MockResponse::Value(serde_json::Value::String(
+        rollup_data(Address::random()).encode_hex(),
+    )));
+
+    assert_eq!(client.get_last_verified_batch(1).await.unwrap(), 7);
+}
+
+#[tokio::test]
+async fn get_trusted_sequencer_address() {
+    let (provider, mock) = Provider::mocked();
+    let client = L1RpcClient::new(Arc::new(provider), Address::random());
+
+    let rollup_contract = Address::random();
+    let sequencer_address = Address::random();
+
+    // The mocked responses are served in reverse order.
+    mock.push_response(MockResponse::Value(serde_json::Value::String(
+        TrustedSequencerReturn(sequencer_address).encode_hex(),
+    )));
+    mock.push_response(MockResponse::Value(serde_json::Value::String(
+        rollup_data(rollup_contract).encode_hex(),
+    )));
+
+    assert_eq!(
+        client.get_trusted_sequencer_address(1).await.unwrap(),
+        sequencer_address
+    );
+}
+
+#[tokio::test]
+async fn get_rollup_count() {
+    let (provider, mock) = Provider::mocked();
+    let client = L1RpcClient::new(Arc::new(provider), Address::random());
+
+    mock.push_response(MockResponse::Value(serde_json::Value::String(
+        3u32.encode_hex(),
+    )));
+
+    assert_eq!(client.get_rollup_count().await.unwrap(), 3);
+}
+
+#[tokio::test]
+async fn get_verify_batch_time_target() {
+    let (provider, mock) = Provider::mocked();
+    let client = L1RpcClient::new(Arc::new(provider), Address::random());
+
+    mock.push_response(MockResponse::Value(serde_json::Value::String(
+        1800u64.encode_hex(),
+    )));
+
+    assert_eq!(client.get_verify_batch_time_target().await.unwrap(), 1800);
+}
+
+#[tokio::test]
+async fn get_updated_rollups() {
+    use ethers::abi::{encode, Token};
+
+    use crate::polygon_rollup_manager::{CreateNewRollupFilter, UpdateRollupFilter};
+
+    let (provider, mock) = Provider::mocked();
+    let rollup_manager = Address::random();
+    let client = L1RpcClient::new(Arc::new(provider), rollup_manager);
+
+    let log = |signature, rollup_id: u32, data| Log {
+        address: rollup_manager,
+        topics: vec![signature, H256::from_low_u64_be(rollup_id.into())],
+        data: Bytes::from(data),
+        ..Default::default()
+    };
+    let logs = vec![
+        log(
+            CreateNewRollupFilter::signature(),
+            4,
+            encode(&[
+                Token::Uint(1.into()),
+                Token::Address(Address::random()),
+                Token::Uint(1001.into()),
+                Token::Address(Address::zero()),
+            ]),
+        ),
+        log(
+            UpdateRollupFilter::signature(),
+            2,
+            encode(&[Token::Uint(2.into()), Token::Uint(7.into())]),
+        ),
+    ];
+    mock.push::<Vec<Log>, _>(logs).unwrap();
+
+    assert_eq!(
+        client.get_updated_rollups(10, 20).await.unwrap(),
+        vec![4, 2]
+    );
+}
+
+#[test]
+fn verify_batches_trusted_aggregator_function_matches_the_abi() {
+    assert_eq!(
+        verify_batches_trusted_aggregator_function(24).short_signature(),
+        VerifyBatchesTrustedAggregatorCall::selector()
+    );
+}
+
+#[tokio::test]
+async fn build_verify_batches_trusted_aggregator_call_with_any_proof_length() {
+    let (provider, _mock) = Provider::mocked();
+    let client = L1RpcClient::new(Arc::new(provider), Address::random());
+
+    let call = |proof_length| {
+        client
+            .build_verify_batches_trusted_aggregator_call(VerifyBatchesTrustedAggregator {
+                rollup_id: 1,
+                pending_state_num: 0,
+                init_num_batch: 0,
+                final_new_batch: 1,
+                new_local_exit_root: [1; 32],
+                new_state_root: [2; 32],
+                beneficiary: Address::random(),
+                proof: vec![[3; 32]; proof_length],
+            })
+            .unwrap()
+    };
+
+    let fflonk = call(24);
+    let other = call(27);
+
+    assert_eq!(
+        fflonk.calldata().unwrap()[..4],
+        VerifyBatchesTrustedAggregatorCall::selector()
+    );
+    assert_eq!(fflonk.calldata().unwrap().len(), 4 + 32 * (7 + 24));
+    assert_eq!(
+        other.calldata().unwrap()[..4],
+        verify_batches_trusted_aggregator_function(27).short_signature()
+    );
+    assert_eq!(other.calldata().unwrap().len(), 4 + 32 * (7 + 27));
+}
+
+#[tokio::test]
+async fn build_multicall_relays_the_settlement_calls() {
+    let (provider, _mock) = Provider::mocked();
+    let rollup_manager = Address::random();
+    let client = L1RpcClient::new(Arc::new(provider), rollup_manager);
+
+    let settlement = |rollup_id| {
+        client
+            .build_verify_batches_trusted_aggregator_call(VerifyBatchesTrustedAggregator {
+                rollup_id,
+                pending_state_num: 0,
+                init_num_batch: 0,
+                final_new_batch: 1,
+                new_local_exit_root: [1; 32],
+                new_state_root: [2; 32],
+                beneficiary: Address::random(),
+                proof: vec![[3; 32]; 24],
+            })
+            .unwrap()
+    };
+    let settlements = vec![settlement(1), settlement(2)];
+    let calldata = settlements
+        .iter()
+        .map(|call| call.calldata().unwrap())
+        .collect::<Vec<_>>();
+
+    let multicall_contract = Address::random();
+    let multicall = client.build_multicall(multicall_contract, settlements);
+
+    assert_eq!(multicall.tx.to_addr(), Some(&multicall_contract));
+
+    let Aggregate3Call { calls } = Aggregate3Call::decode(multicall.calldata().unwrap()).unwrap();
+
+    assert_eq!(calls.len(), 2);
+    for (call, calldata) in calls.into_iter().zip(calldata) {
+        assert_eq!(call.target, rollup_manager);
+        assert!(!call.allow_failure);
+        assert_eq!(call.call_data, calldata);
+    }
+}
@@ -0,0 +1,8 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Don't require protoc to be installed on the build machines.
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+    tonic_build::compile_protos("proto/agglayer/interop/v1/interop.proto")?;
+
+    Ok(())
+}
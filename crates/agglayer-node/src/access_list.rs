@@ -0,0 +1,193 @@
+//! Access lists of the rollups submitting to the agglayer.
+//!
+//! Operators allow and deny rollups through the admin RPC server, to block the
+//! submissions of a compromised rollup without rolling out a new
+//! configuration. The lists are persisted to the configured access list file,
+//! and restored from it on startup.
+use std::{
+    collections::BTreeSet,
+    io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use agglayer_config::Config;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The rollups allowed and denied to submit to the agglayer.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AccessLists {
+    /// If not empty, the only rollups allowed to submit.
+    #[serde(default)]
+    pub(crate) allowed: BTreeSet<u32>,
+    /// The rollups denied to submit, whether allowed or not.
+    #[serde(default)]
+    pub(crate) denied: BTreeSet<u32>,
+}
+
+impl AccessLists {
+    fn allows(&self, rollup_id: u32) -> bool {
+        !self.denied.contains(&rollup_id)
+            && (self.allowed.is_empty() || self.allowed.contains(&rollup_id))
+    }
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum AccessListError {
+    #[error("access list I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("malformed access list: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
+
+/// The access lists of the rollups, shared with the admin RPC server.
+#[derive(Clone, Debug)]
+pub(crate) struct RollupAccessList {
+    lists: Arc<RwLock<AccessLists>>,
+    /// The file persisting the access lists.
+    path: Option<PathBuf>,
+}
+
+impl RollupAccessList {
+    pub(crate) fn new(config: &Config) -> Self {
+        Self {
+            lists: Arc::default(),
+            path: config.admin.access_list_path.clone(),
+        }
+    }
+
+    /// Restore the access lists persisted in the access list file, if any.
+    pub(crate) fn restore(&self) -> Result<(), AccessListError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let persisted: AccessLists = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+
+        *self.write() = persisted;
+
+        Ok(())
+    }
+
+    /// Whether the given rollup is allowed to submit.
+    pub(crate) fn allows(&self, rollup_id: u32) -> bool {
+        self.read().allows(rollup_id)
+    }
+
+    /// Get the current access lists.
+    pub(crate) fn lists(&self) -> AccessLists {
+        self.read().clone()
+    }
+
+    /// Add the given rollup to the allowlist, returning whether it was
+    /// missing from it.
+    pub(crate) fn allow(&self, rollup_id: u32) -> Result<bool, AccessListError> {
+        self.update(|lists| lists.allowed.insert(rollup_id))
+    }
+
+    /// Remove the given rollup from the allowlist, returning whether it was
+    /// in it.
+    pub(crate) fn disallow(&self, rollup_id: u32) -> Result<bool, AccessListError> {
+        self.update(|lists| lists.allowed.remove(&rollup_id))
+    }
+
+    /// Add the given rollup to the denylist, returning whether it was missing
+    /// from it.
+    pub(crate) fn deny(&self, rollup_id: u32) -> Result<bool, AccessListError> {
+        self.update(|lists| lists.denied.insert(rollup_id))
+    }
+
+    /// Remove the given rollup from the denylist, returning whether it was in
+    /// it.
+    pub(crate) fn undeny(&self, rollup_id: u32) -> Result<bool, AccessListError> {
+        self.update(|lists| lists.denied.remove(&rollup_id))
+    }
+
+    /// Apply the given change to the access lists, persisting them if
+    /// changed.
+    ///
+    /// The change applies even if it fails to be persisted, so that the
+    /// rollup is blocked right away.
+    fn update(
+        &self,
+        change: impl FnOnce(&mut AccessLists) -> bool,
+    ) -> Result<bool, AccessListError> {
+        let mut lists = self.write();
+        if !change(&mut lists) {
+            return Ok(false);
+        }
+
+        self.persist(&lists)?;
+
+        Ok(true)
+    }
+
+    fn persist(&self, lists: &AccessLists) -> Result<(), AccessListError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+
+        std::fs::write(&tmp, serde_json::to_vec_pretty(lists)?)?;
+        std::fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, AccessLists> {
+        self.lists.read().expect("Access list lock poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, AccessLists> {
+        self.lists.write().expect("Access list lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agglayer_config::Config;
+    use ethers::types::H256;
+
+    use super::RollupAccessList;
+
+    #[test]
+    fn access_lists_are_enforced_and_persisted() {
+        let path =
+            std::env::temp_dir().join(format!("agglayer-access-list-{:x}.json", H256::random()));
+        let mut config = Config::default();
+        config.admin.access_list_path = Some(path.clone());
+
+        let access_list = RollupAccessList::new(&config);
+        assert!(access_list.allows(1));
+        assert!(access_list.allows(2));
+
+        assert!(access_list.allow(1).unwrap());
+        assert!(!access_list.allow(1).unwrap());
+        assert!(access_list.allows(1));
+        assert!(!access_list.allows(2));
+
+        assert!(access_list.deny(1).unwrap());
+        assert!(!access_list.allows(1));
+
+        let restored = RollupAccessList::new(&config);
+        restored.restore().unwrap();
+        assert_eq!(restored.lists(), access_list.lists());
+        assert!(!restored.allows(1));
+
+        assert!(restored.undeny(1).unwrap());
+        assert!(restored.disallow(1).unwrap());
+        assert!(!restored.disallow(1).unwrap());
+        assert!(restored.allows(1));
+        assert!(restored.allows(2));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
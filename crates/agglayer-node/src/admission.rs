@@ -0,0 +1,119 @@
+//! Admission of the new proofs submitted to the agglayer.
+//!
+//! Operators pause the admission through the admin RPC server to stop
+//! accepting new proofs without stopping the node, and drain it to
+//! additionally wait for the proofs already accepted to be processed.
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use tokio::sync::watch;
+
+/// Control the admission of the new proofs, and keep track of the ones
+/// admitted and still being processed.
+#[derive(Clone, Debug)]
+pub(crate) struct Admission {
+    paused: Arc<AtomicBool>,
+    in_flight: Arc<watch::Sender<usize>>,
+}
+
+impl Default for Admission {
+    fn default() -> Self {
+        Self {
+            paused: Arc::default(),
+            in_flight: Arc::new(watch::channel(0).0),
+        }
+    }
+}
+
+impl Admission {
+    /// Admit a new proof, unless paused. The proof counts as in flight until
+    /// the returned [`Admitted`] guard gets dropped.
+    pub(crate) fn admit(&self) -> Option<Admitted> {
+        // Count the proof in first, so that a concurrent drain either waits for
+        // it or sees it rejected.
+        self.in_flight.send_modify(|n| *n += 1);
+        let admitted = Admitted {
+            in_flight: self.in_flight.clone(),
+        };
+
+        (!self.is_paused()).then_some(admitted)
+    }
+
+    /// Whether the new proofs are rejected.
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Reject the new proofs, returning whether the admission was running.
+    pub(crate) fn pause(&self) -> bool {
+        !self.paused.swap(true, Ordering::SeqCst)
+    }
+
+    /// Admit the new proofs again, returning whether the admission was
+    /// paused.
+    pub(crate) fn resume(&self) -> bool {
+        self.paused.swap(false, Ordering::SeqCst)
+    }
+
+    /// The number of admitted proofs still being processed.
+    pub(crate) fn in_flight(&self) -> usize {
+        *self.in_flight.borrow()
+    }
+
+    /// Reject the new proofs, and wait for the admitted ones to be processed.
+    pub(crate) async fn drain(&self) {
+        self.pause();
+
+        let mut in_flight = self.in_flight.subscribe();
+        // The sender is owned by `self`, so the channel can't be closed.
+        _ = in_flight.wait_for(|n| *n == 0).await;
+    }
+}
+
+/// An admitted proof, counted as in flight until dropped.
+#[derive(Debug)]
+pub(crate) struct Admitted {
+    in_flight: Arc<watch::Sender<usize>>,
+}
+
+impl Drop for Admitted {
+    fn drop(&mut self) {
+        self.in_flight.send_modify(|n| *n -= 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn drain_waits_for_the_admitted_proofs() {
+        let admission = Admission::default();
+        let admitted = admission.admit().unwrap();
+
+        assert_eq!(admission.in_flight(), 1);
+
+        let drain = tokio::spawn({
+            let admission = admission.clone();
+            async move { admission.drain().await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(admission.is_paused());
+        assert!(admission.admit().is_none());
+        assert_eq!(admission.in_flight(), 1);
+        assert!(!drain.is_finished());
+
+        drop(admitted);
+        drain.await.unwrap();
+
+        assert_eq!(admission.in_flight(), 0);
+        assert!(admission.resume());
+        assert!(admission.admit().is_some());
+        assert_eq!(admission.in_flight(), 0);
+    }
+}
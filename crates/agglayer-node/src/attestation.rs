@@ -0,0 +1,65 @@
+//! Acceptance attestations signed by the agglayer.
+//!
+//! Once a [`SignedTx`](crate::signed_tx::SignedTx) passes verification, the
+//! agglayer signs an [`Attestation`] binding its hash to the current Epoch.
+//! Rollups can present it as evidence of acceptance before the settlement is
+//! confirmed on L1.
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use ethers::{prelude::*, utils::keccak256};
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_with::{serde_as, DisplayFromStr};
+
+/// The attestation that a submission was accepted by the agglayer.
+#[serde_as]
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Attestation {
+    /// The hash of the accepted submission.
+    #[schemars(with = "String")]
+    pub(crate) tx_hash: H256,
+    /// The Epoch in which the submission was accepted.
+    pub(crate) epoch: u64,
+    /// The address of the agglayer key.
+    #[schemars(with = "String")]
+    pub(crate) signer: Address,
+    /// The EIP-191 signature of the [`Attestation::message`].
+    #[serde_as(as = "DisplayFromStr")]
+    #[schemars(with = "String")]
+    pub(crate) signature: Signature,
+}
+
+impl Attestation {
+    /// The message signed by the agglayer, the hash of the submission hash
+    /// followed by the big-endian Epoch number.
+    pub(crate) fn message(tx_hash: H256, epoch: u64) -> H256 {
+        keccak256([tx_hash.as_bytes(), &epoch.to_be_bytes()].concat()).into()
+    }
+}
+
+/// Store the attestations issued by the agglayer, by submission hash.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AttestationStore {
+    attestations: Arc<Mutex<HashMap<H256, Attestation>>>,
+}
+
+impl AttestationStore {
+    pub(crate) fn insert(&self, attestation: Attestation) {
+        self.attestations
+            .lock()
+            .expect("Attestation store lock poisoned")
+            .insert(attestation.tx_hash, attestation);
+    }
+
+    pub(crate) fn get(&self, tx_hash: &H256) -> Option<Attestation> {
+        self.attestations
+            .lock()
+            .expect("Attestation store lock poisoned")
+            .get(tx_hash)
+            .cloned()
+    }
+}
@@ -0,0 +1,172 @@
+//! Audit of the submissions to the agglayer.
+//!
+//! Every received [`SignedTx`], the outcome of its verification and the result
+//! of its settlement are recorded in the hash-chained [`AuditLog`], so that
+//! the record can't be altered without breaking the chain. The record is
+//! exported with `admin_exportAuditLog`.
+use std::sync::Arc;
+
+use agglayer_storage::{AuditEntry, AuditLog};
+use ethers::types::{Bytes, H256};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{kernel::ErrorKind, signed_tx::SignedTx};
+
+/// The maximum number of entries exported at once.
+pub(crate) const MAX_EXPORTED_ENTRIES: u64 = 10_000;
+
+/// An event of the lifecycle of a submission.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub(crate) enum AuditEvent {
+    /// The submission was received.
+    #[serde(rename_all = "camelCase")]
+    Received {
+        tx_hash: H256,
+        /// The ID of the request carrying the submission.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>,
+        signed_tx: SignedTx,
+    },
+    /// The submission passed the verification checks.
+    #[serde(rename_all = "camelCase")]
+    Verified { tx_hash: H256 },
+    /// The submission failed the verification checks.
+    #[serde(rename_all = "camelCase")]
+    Rejected {
+        tx_hash: H256,
+        kind: ErrorKind,
+        error: String,
+    },
+    /// The settlement transaction of the submission was mined.
+    #[serde(rename_all = "camelCase")]
+    Settled {
+        tx_hash: H256,
+        settlement_tx_hash: H256,
+        block_number: Option<u64>,
+    },
+    /// The settlement of the submission failed.
+    #[serde(rename_all = "camelCase")]
+    SettlementFailed { tx_hash: H256, error: String },
+}
+
+/// An exported entry of the audit log.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditRecord {
+    pub(crate) sequence: u64,
+    /// The UNIX timestamp of the entry, in seconds.
+    pub(crate) timestamp: u64,
+    pub(crate) prev_hash: H256,
+    /// The keccak256 hash of the big-endian sequence number and timestamp,
+    /// the previous hash and the payload.
+    pub(crate) hash: H256,
+    /// The JSON-encoded event, as hashed.
+    pub(crate) payload: Bytes,
+    /// The decoded event.
+    pub(crate) event: Option<AuditEvent>,
+}
+
+impl From<AuditEntry> for AuditRecord {
+    fn from(entry: AuditEntry) -> Self {
+        Self {
+            sequence: entry.sequence,
+            timestamp: entry.timestamp,
+            prev_hash: entry.prev_hash,
+            hash: entry.hash,
+            event: serde_json::from_slice(&entry.payload).ok(),
+            payload: entry.payload.into(),
+        }
+    }
+}
+
+/// Record the events of the submissions in the audit log, if enabled.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Auditor {
+    log: Option<Arc<AuditLog>>,
+}
+
+impl Auditor {
+    pub(crate) fn new(log: AuditLog) -> Self {
+        Self {
+            log: Some(Arc::new(log)),
+        }
+    }
+
+    /// Whether the audit log is enabled.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.log.is_some()
+    }
+
+    /// Record the given event.
+    ///
+    /// Failing to record an event doesn't fail the submission, the failure is
+    /// only logged.
+    pub(crate) fn record(&self, event: AuditEvent) {
+        let Some(log) = &self.log else {
+            return;
+        };
+
+        let payload = serde_json::to_vec(&event).expect("AuditEvent is serializable");
+        if let Err(error) = log.append(&payload) {
+            error!("Failed to record {event:?} in the audit log: {error}");
+        }
+    }
+
+    /// Export the entries within the given inclusive range of sequence
+    /// numbers, checking that they're chained together.
+    pub(crate) fn export(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<AuditRecord>, agglayer_storage::Error> {
+        let Some(log) = &self.log else {
+            return Ok(Vec::new());
+        };
+
+        Ok(log
+            .range(from, to)?
+            .into_iter()
+            .map(AuditRecord::from)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::*;
+
+    #[test]
+    fn exported_records_decode_the_events() {
+        let path = std::env::temp_dir().join(format!("agglayer-audit-{:x}", H256::random()));
+        let auditor = Auditor::new(AuditLog::open(&path).unwrap());
+        let tx_hash = H256::random();
+
+        auditor.record(AuditEvent::Verified { tx_hash });
+        auditor.record(AuditEvent::Rejected {
+            tx_hash,
+            kind: ErrorKind::StateMismatch,
+            error: "invalid state root".to_string(),
+        });
+
+        let records = auditor.export(0, 1).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].prev_hash, records[0].hash);
+        assert!(matches!(
+            records[1].event,
+            Some(AuditEvent::Rejected { tx_hash: hash, kind: ErrorKind::StateMismatch, .. })
+                if hash == tx_hash
+        ));
+        assert_eq!(
+            serde_json::to_value(&records[0]).unwrap()["event"],
+            serde_json::json!({ "event": "verified", "txHash": tx_hash })
+        );
+
+        drop(auditor);
+        _ = std::fs::remove_dir_all(path);
+    }
+}
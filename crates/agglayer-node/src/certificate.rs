@@ -0,0 +1,380 @@
+//! The input of the rollups with a pessimistic consensus.
+//!
+//! Rather than batch proofs, these rollups submit [`Certificate`]s listing the
+//! bridge exits leaving the network and the bridge exits it imported, along
+//! with its new local exit root. The certificates are proven by the
+//! pessimistic proof pipeline once their Epoch ends.
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use ethers::{prelude::*, utils::keccak256};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use thiserror::Error;
+
+use crate::kernel::ErrorKind;
+
+/// The token of a [`BridgeExit`], identified on its origin network.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TokenInfo {
+    pub(crate) origin_network: u32,
+    #[schemars(with = "String")]
+    pub(crate) origin_token_address: Address,
+}
+
+/// A transfer of tokens leaving a network.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BridgeExit {
+    pub(crate) leaf_type: u8,
+    pub(crate) token_info: TokenInfo,
+    pub(crate) dest_network: u32,
+    #[schemars(with = "String")]
+    pub(crate) dest_address: Address,
+    #[schemars(with = "String")]
+    pub(crate) amount: U256,
+    #[schemars(with = "String")]
+    pub(crate) metadata: Bytes,
+}
+
+impl BridgeExit {
+    /// The hash of the bridge exit, as a leaf of the local exit tree.
+    pub(crate) fn hash(&self) -> H256 {
+        let mut amount = [0; 32];
+        self.amount.to_big_endian(&mut amount);
+
+        keccak256(
+            [
+                &[self.leaf_type][..],
+                &self.token_info.origin_network.to_be_bytes(),
+                self.token_info.origin_token_address.as_bytes(),
+                &self.dest_network.to_be_bytes(),
+                self.dest_address.as_bytes(),
+                &amount,
+                &keccak256(&self.metadata),
+            ]
+            .concat(),
+        )
+        .into()
+    }
+}
+
+/// A bridge exit of another network, claimed on the network.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImportedBridgeExit {
+    pub(crate) bridge_exit: BridgeExit,
+    /// The index of the bridge exit in the global exit tree.
+    #[schemars(with = "String")]
+    pub(crate) global_index: U256,
+}
+
+impl ImportedBridgeExit {
+    pub(crate) fn hash(&self) -> H256 {
+        let mut global_index = [0; 32];
+        self.global_index.to_big_endian(&mut global_index);
+
+        keccak256([self.bridge_exit.hash().as_bytes(), &global_index].concat()).into()
+    }
+}
+
+/// Errors related to the validation of a [`Certificate`].
+#[derive(Error, Debug, PartialEq, Eq)]
+pub(crate) enum CertificateError {
+    #[error("bridge exit {index} targets network {network_id} itself")]
+    BridgeExitToItself { index: usize, network_id: u32 },
+    #[error("imported bridge exit {index} targets network {dest_network} instead of {network_id}")]
+    ImportedBridgeExitDestination {
+        index: usize,
+        dest_network: u32,
+        network_id: u32,
+    },
+    #[error("the local exit root must change if and only if there are bridge exits")]
+    LocalExitRootMismatch,
+    #[error("certificate {0} was already submitted")]
+    AlreadySubmitted(H256),
+    #[error(
+        "certificate height {height} of network {network_id} doesn't follow the last height {last}"
+    )]
+    StaleHeight {
+        network_id: u32,
+        height: u64,
+        last: u64,
+    },
+}
+
+impl CertificateError {
+    pub(crate) fn kind(&self) -> ErrorKind {
+        match self {
+            CertificateError::AlreadySubmitted(_) => ErrorKind::AlreadySettled,
+            _ => ErrorKind::InvalidCertificate,
+        }
+    }
+}
+
+/// A [`Certificate`] is the input of the rollups with a pessimistic consensus.
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Certificate {
+    /// The network emitting the certificate.
+    pub(crate) network_id: u32,
+    /// The position of the certificate among the ones of the network.
+    pub(crate) height: u64,
+    #[schemars(with = "String")]
+    pub(crate) prev_local_exit_root: H256,
+    #[schemars(with = "String")]
+    pub(crate) new_local_exit_root: H256,
+    pub(crate) bridge_exits: Vec<BridgeExit>,
+    pub(crate) imported_bridge_exits: Vec<ImportedBridgeExit>,
+    /// The signature of the [`Certificate::hash`] by the trusted sequencer.
+    #[serde_as(as = "DisplayFromStr")]
+    #[schemars(with = "String")]
+    pub(crate) signature: Signature,
+}
+
+impl Certificate {
+    /// Generate a hash that uniquely identifies this certificate.
+    pub(crate) fn hash(&self) -> H256 {
+        let bridge_exits: Vec<u8> = self
+            .bridge_exits
+            .iter()
+            .flat_map(|bridge_exit| bridge_exit.hash().0)
+            .collect();
+        let imported_bridge_exits: Vec<u8> = self
+            .imported_bridge_exits
+            .iter()
+            .flat_map(|imported| imported.hash().0)
+            .collect();
+
+        keccak256(
+            [
+                &self.network_id.to_be_bytes()[..],
+                &self.height.to_be_bytes(),
+                self.prev_local_exit_root.as_bytes(),
+                self.new_local_exit_root.as_bytes(),
+                &keccak256(bridge_exits),
+                &keccak256(imported_bridge_exits),
+            ]
+            .concat(),
+        )
+        .into()
+    }
+
+    /// Attempt to recover the address of the signer.
+    pub(crate) fn signer(&self) -> Result<Address, SignatureError> {
+        self.signature.recover(self.hash())
+    }
+
+    /// Check the consistency of the certificate on its own.
+    ///
+    /// The new local exit root can't be checked against the bridge exits
+    /// without the local exit tree of the network, this is left to the
+    /// pessimistic proof.
+    pub(crate) fn validate(&self) -> Result<(), CertificateError> {
+        if let Some(index) = self
+            .bridge_exits
+            .iter()
+            .position(|bridge_exit| bridge_exit.dest_network == self.network_id)
+        {
+            return Err(CertificateError::BridgeExitToItself {
+                index,
+                network_id: self.network_id,
+            });
+        }
+
+        if let Some((index, imported)) = self
+            .imported_bridge_exits
+            .iter()
+            .enumerate()
+            .find(|(_, imported)| imported.bridge_exit.dest_network != self.network_id)
+        {
+            return Err(CertificateError::ImportedBridgeExitDestination {
+                index,
+                dest_network: imported.bridge_exit.dest_network,
+                network_id: self.network_id,
+            });
+        }
+
+        if self.bridge_exits.is_empty() != (self.new_local_exit_root == self.prev_local_exit_root) {
+            return Err(CertificateError::LocalExitRootMismatch);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn sign(
+        &mut self,
+        signer: &Wallet<k256::ecdsa::SigningKey>,
+    ) -> Result<(), SignatureError> {
+        self.signature = signer.sign_hash(self.hash()).unwrap();
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+struct Certificates {
+    by_hash: HashMap<H256, Certificate>,
+    by_height: BTreeMap<(u32, u64), H256>,
+}
+
+/// Store the certificates accepted by the agglayer, by certificate hash.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct CertificateStore {
+    certificates: Arc<Mutex<Certificates>>,
+}
+
+impl CertificateStore {
+    /// Store the given certificate, unless already stored or not above the
+    /// last height of its network.
+    pub(crate) fn insert(&self, certificate: &Certificate) -> Result<H256, CertificateError> {
+        let hash = certificate.hash();
+        let mut certificates = self.lock();
+
+        if certificates.by_hash.contains_key(&hash) {
+            return Err(CertificateError::AlreadySubmitted(hash));
+        }
+
+        let network_id = certificate.network_id;
+        if let Some((&(_, last), _)) = certificates
+            .by_height
+            .range((network_id, 0)..=(network_id, u64::MAX))
+            .next_back()
+        {
+            if certificate.height <= last {
+                return Err(CertificateError::StaleHeight {
+                    network_id,
+                    height: certificate.height,
+                    last,
+                });
+            }
+        }
+
+        certificates
+            .by_height
+            .insert((network_id, certificate.height), hash);
+        certificates.by_hash.insert(hash, certificate.clone());
+
+        Ok(hash)
+    }
+
+    pub(crate) fn remove(&self, hash: &H256) -> Option<Certificate> {
+        let mut certificates = self.lock();
+        let certificate = certificates.by_hash.remove(hash)?;
+        certificates
+            .by_height
+            .remove(&(certificate.network_id, certificate.height));
+
+        Some(certificate)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Certificates> {
+        self.certificates
+            .lock()
+            .expect("Certificate store lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn certificate(height: u64) -> Certificate {
+        Certificate {
+            network_id: 1,
+            height,
+            prev_local_exit_root: H256::zero(),
+            new_local_exit_root: H256::repeat_byte(1),
+            bridge_exits: vec![BridgeExit {
+                leaf_type: 0,
+                token_info: TokenInfo {
+                    origin_network: 0,
+                    origin_token_address: Address::zero(),
+                },
+                dest_network: 2,
+                dest_address: Address::repeat_byte(2),
+                amount: U256::exp10(18),
+                metadata: Bytes::new(),
+            }],
+            imported_bridge_exits: Vec::new(),
+            signature: Signature {
+                r: U256::zero(),
+                s: U256::zero(),
+                v: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn certificates_are_validated() {
+        assert_eq!(certificate(0).validate(), Ok(()));
+
+        let mut to_itself = certificate(0);
+        to_itself.bridge_exits[0].dest_network = 1;
+        assert_eq!(
+            to_itself.validate(),
+            Err(CertificateError::BridgeExitToItself {
+                index: 0,
+                network_id: 1
+            })
+        );
+
+        let mut imported = certificate(0);
+        imported.imported_bridge_exits.push(ImportedBridgeExit {
+            bridge_exit: imported.bridge_exits[0].clone(),
+            global_index: U256::one(),
+        });
+        assert_eq!(
+            imported.validate(),
+            Err(CertificateError::ImportedBridgeExitDestination {
+                index: 0,
+                dest_network: 2,
+                network_id: 1
+            })
+        );
+
+        let mut unchanged = certificate(0);
+        unchanged.new_local_exit_root = unchanged.prev_local_exit_root;
+        assert_eq!(
+            unchanged.validate(),
+            Err(CertificateError::LocalExitRootMismatch)
+        );
+    }
+
+    #[test]
+    fn heights_of_a_network_increase() {
+        let store = CertificateStore::default();
+
+        let hash = store.insert(&certificate(1)).unwrap();
+        assert_eq!(
+            store.insert(&certificate(1)),
+            Err(CertificateError::AlreadySubmitted(hash))
+        );
+
+        let mut stale = certificate(0);
+        stale.new_local_exit_root = H256::repeat_byte(2);
+        assert_eq!(
+            store.insert(&stale),
+            Err(CertificateError::StaleHeight {
+                network_id: 1,
+                height: 0,
+                last: 1
+            })
+        );
+
+        assert!(store.insert(&certificate(2)).is_ok());
+
+        // Removing a certificate frees its height.
+        assert!(store.remove(&hash).is_some());
+        assert!(store.remove(&hash).is_none());
+        assert!(store.insert(&certificate(1)).is_err());
+        store.remove(&store.insert(&certificate(3)).unwrap());
+        assert!(store.insert(&certificate(3)).is_ok());
+    }
+}
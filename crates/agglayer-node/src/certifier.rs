@@ -0,0 +1,138 @@
+//! Proving of the certificates of the ended epochs.
+//!
+//! The certificate orchestrator collects the certificates received on the RPC
+//! and hands the ones of each ended epoch to the [`Certification`] task. The
+//! task proves the epochs one after the other, so that each proof builds upon
+//! the state of the networks left by the previous one, and hands the
+//! resulting [`EpochProof`]s to the kernel for settlement:
+//!
+//! ```text
+//! RPC --Certificate--> orchestrator --EpochCertificates--> certification --EpochProof--> kernel
+//! ```
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_trait::async_trait;
+use ethers::types::{Bytes, H256};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+use crate::certificate::Certificate;
+
+mod sp1;
+
+pub(crate) use self::sp1::Sp1Certifier;
+
+#[cfg(test)]
+mod tests;
+
+/// The certificates received during an ended epoch.
+#[derive(Debug)]
+pub(crate) struct EpochCertificates {
+    pub(crate) epoch: u64,
+    pub(crate) certificates: Vec<Certificate>,
+}
+
+/// The proof of the certificates of an epoch.
+#[derive(Clone, Debug)]
+pub(crate) struct EpochProof {
+    pub(crate) epoch: u64,
+    /// The hashes of the proven certificates.
+    pub(crate) certificates: Vec<H256>,
+    /// The new local exit root of every network with a proven certificate.
+    pub(crate) new_local_exit_roots: BTreeMap<u32, H256>,
+    pub(crate) proof: Bytes,
+}
+
+/// Errors related to the proving of the certificates of an epoch.
+#[derive(Error, Debug)]
+pub(crate) enum CertifierError {
+    /// The certificates don't satisfy the pessimistic program.
+    #[error("certificates rejected: {0}")]
+    Rejected(String),
+    /// The prover failed to produce the proof.
+    #[error("proving failed: {0}")]
+    Proving(String),
+}
+
+/// The prover of the certificates of an epoch.
+#[async_trait]
+pub(crate) trait Certifier: Send + Sync + 'static {
+    /// Prove the given certificates, received during the given epoch.
+    async fn certify(
+        &self,
+        epoch: u64,
+        certificates: &[Certificate],
+    ) -> Result<EpochProof, CertifierError>;
+}
+
+/// The task proving the certificates of the ended epochs.
+pub(crate) struct Certification {
+    certifier: Arc<dyn Certifier>,
+    epochs: mpsc::Receiver<EpochCertificates>,
+    proofs: mpsc::Sender<EpochProof>,
+}
+
+impl Certification {
+    pub(crate) fn new(
+        certifier: Arc<dyn Certifier>,
+        epochs: mpsc::Receiver<EpochCertificates>,
+        proofs: mpsc::Sender<EpochProof>,
+    ) -> Self {
+        Self {
+            certifier,
+            epochs,
+            proofs,
+        }
+    }
+
+    /// Prove the epochs in the order they ended, until cancelled or the
+    /// orchestrator stops.
+    pub(crate) async fn run(mut self, cancellation_token: CancellationToken) {
+        loop {
+            let next = tokio::select! {
+                next = self.epochs.recv() => next,
+                _ = cancellation_token.cancelled() => None,
+            };
+            let Some(EpochCertificates {
+                epoch,
+                certificates,
+            }) = next
+            else {
+                debug!("Certification stopped");
+                return;
+            };
+
+            let proof = tokio::select! {
+                proof = self.certifier.certify(epoch, &certificates) => proof,
+                _ = cancellation_token.cancelled() => {
+                    debug!("Certification stopped while proving epoch {epoch}");
+                    return;
+                }
+            };
+
+            // The certificates of a failed epoch aren't proven again, the
+            // networks are expected to submit new certificates.
+            let proof = match proof {
+                Ok(proof) => proof,
+                Err(error) => {
+                    error!(
+                        "Failed to prove the {} certificates of epoch {epoch}: {error}",
+                        certificates.len()
+                    );
+                    continue;
+                }
+            };
+            info!(
+                "Proved the {} certificates of epoch {epoch}",
+                certificates.len()
+            );
+
+            if self.proofs.send(proof).await.is_err() {
+                error!("Unable to hand the proof of epoch {epoch} to the settlement");
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::{BTreeMap, HashMap};
+
+use agglayer_prover::Prover;
+use async_trait::async_trait;
+use ethers::types::H256;
+use pessimistic_proof::{
+    certificate::Certificate as PessimisticCertificate,
+    local_balance_tree::{merge_balance_trees, BalanceTree},
+    local_exit_tree::{hasher::Keccak256Hasher, LocalExitTree},
+    Address, U256,
+};
+use tokio::sync::Mutex;
+
+use super::{Certifier, CertifierError, EpochProof};
+use crate::certificate::{BridgeExit, Certificate};
+
+/// The state of a network, as of its last proven certificate.
+#[derive(Clone, Default)]
+struct NetworkState {
+    local_exit_tree: LocalExitTree<Keccak256Hasher>,
+    balance_tree: BalanceTree,
+}
+
+/// The [`Certifier`] proving the certificates with the SP1 pessimistic
+/// program.
+///
+/// The program proves the bridge exits of each network against its prior
+/// state, which is tracked from one epoch to the next.
+pub(crate) struct Sp1Certifier {
+    prover: Prover,
+    networks: Mutex<BTreeMap<u32, NetworkState>>,
+}
+
+impl Sp1Certifier {
+    pub(crate) fn new(prover: Prover) -> Self {
+        Self {
+            prover,
+            networks: Mutex::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl Certifier for Sp1Certifier {
+    async fn certify(
+        &self,
+        epoch: u64,
+        certificates: &[Certificate],
+    ) -> Result<EpochProof, CertifierError> {
+        let mut networks = self.networks.lock().await;
+
+        // The program takes a single certificate per network, the bridge exits
+        // of the certificates of a network are proven at once.
+        let mut bridge_exits: BTreeMap<u32, Vec<_>> = BTreeMap::new();
+        for certificate in certificates {
+            bridge_exits
+                .entry(certificate.network_id)
+                .or_default()
+                .extend(certificate.bridge_exits.iter().map(bridge_exit));
+        }
+
+        let inputs: Vec<PessimisticCertificate> = bridge_exits
+            .into_iter()
+            .map(|(network_id, bridge_exits)| {
+                let state = networks.get(&network_id).cloned().unwrap_or_default();
+                let prev_local_exit_root = state.local_exit_tree.get_root();
+
+                PessimisticCertificate::new(
+                    network_id.into(),
+                    state.local_exit_tree,
+                    prev_local_exit_root,
+                    state.balance_tree,
+                    bridge_exits,
+                )
+            })
+            .collect();
+
+        let proof = self
+            .prover
+            .prove(inputs.clone())
+            .await
+            .map_err(|error| match error {
+                agglayer_prover::Error::Rejected(_) => CertifierError::Rejected(error.to_string()),
+                error => CertifierError::Proving(error.to_string()),
+            })?;
+
+        // Advance the state of the networks past the proven bridge exits.
+        let balance_trees = merge_balance_trees(
+            &inputs
+                .iter()
+                .map(|input| (input.origin_network, input.compute_new_balance_tree()))
+                .collect::<HashMap<_, _>>(),
+        );
+        for (network, balance_tree) in balance_trees.iter() {
+            let state = networks.entry(u32::from(*network)).or_default();
+            match inputs.iter().find(|input| input.origin_network == *network) {
+                // The new balance tree of the networks with a certificate
+                // already accounts for their prior balance.
+                Some(input) => {
+                    let mut local_exit_tree = input.prev_local_exit_tree.clone();
+                    for bridge_exit in &input.bridge_exits {
+                        local_exit_tree.add_leaf(bridge_exit.hash());
+                    }
+
+                    state.local_exit_tree = local_exit_tree;
+                    state.balance_tree = balance_tree.clone();
+                }
+                None => state.balance_tree.merge(balance_tree),
+            }
+        }
+
+        Ok(EpochProof {
+            epoch,
+            certificates: certificates.iter().map(Certificate::hash).collect(),
+            new_local_exit_roots: proof
+                .output
+                .0
+                .iter()
+                .map(|(network, root)| (u32::from(*network), H256::from(*root)))
+                .collect(),
+            proof: proof.to_bytes().into(),
+        })
+    }
+}
+
+fn bridge_exit(bridge_exit: &BridgeExit) -> pessimistic_proof::BridgeExit {
+    let mut amount = [0; 32];
+    bridge_exit.amount.to_big_endian(&mut amount);
+
+    pessimistic_proof::BridgeExit::new(
+        bridge_exit.leaf_type,
+        bridge_exit.token_info.origin_network.into(),
+        Address::from(bridge_exit.token_info.origin_token_address.0),
+        bridge_exit.dest_network.into(),
+        Address::from(bridge_exit.dest_address.0),
+        U256::from_be_bytes(amount),
+        bridge_exit.metadata.to_vec(),
+    )
+}
@@ -0,0 +1,81 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_trait::async_trait;
+use ethers::types::{Bytes, Signature, H256, U256};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use super::{Certification, Certifier, CertifierError, EpochCertificates, EpochProof};
+use crate::certificate::Certificate;
+
+/// Proves the epochs, failing the ones with a certificate of network 0.
+struct Check;
+
+#[async_trait]
+impl Certifier for Check {
+    async fn certify(
+        &self,
+        epoch: u64,
+        certificates: &[Certificate],
+    ) -> Result<EpochProof, CertifierError> {
+        if certificates
+            .iter()
+            .any(|certificate| certificate.network_id == 0)
+        {
+            return Err(CertifierError::Rejected("network 0".to_string()));
+        }
+
+        Ok(EpochProof {
+            epoch,
+            certificates: certificates.iter().map(Certificate::hash).collect(),
+            new_local_exit_roots: BTreeMap::new(),
+            proof: Bytes::new(),
+        })
+    }
+}
+
+fn certificate(network_id: u32) -> Certificate {
+    Certificate {
+        network_id,
+        height: 0,
+        prev_local_exit_root: H256::zero(),
+        new_local_exit_root: H256::zero(),
+        bridge_exits: Vec::new(),
+        imported_bridge_exits: Vec::new(),
+        signature: Signature {
+            r: U256::zero(),
+            s: U256::zero(),
+            v: 0,
+        },
+    }
+}
+
+#[tokio::test]
+async fn epochs_are_proven_in_order() {
+    let (epochs_sender, epochs_receiver) = mpsc::channel(10);
+    let (proofs_sender, mut proofs_receiver) = mpsc::channel(10);
+    let certification = Certification::new(Arc::new(Check), epochs_receiver, proofs_sender);
+    let handle = tokio::spawn(certification.run(CancellationToken::new()));
+
+    for (epoch, network_id) in [(1, 1), (2, 0), (3, 2)] {
+        epochs_sender
+            .send(EpochCertificates {
+                epoch,
+                certificates: vec![certificate(network_id)],
+            })
+            .await
+            .unwrap();
+    }
+
+    let proof = proofs_receiver.recv().await.unwrap();
+    assert_eq!(proof.epoch, 1);
+    assert_eq!(proof.certificates, vec![certificate(1).hash()]);
+
+    // The epoch 2 failed to be proven, the certification carries on.
+    assert_eq!(proofs_receiver.recv().await.unwrap().epoch, 3);
+
+    // The certification stops along with the orchestrator.
+    drop(epochs_sender);
+    handle.await.unwrap();
+    assert!(proofs_receiver.recv().await.is_none());
+}
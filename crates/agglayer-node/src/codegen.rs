@@ -0,0 +1,394 @@
+//! Client type generation from the wire types of the agglayer RPC.
+//!
+//! The wire types derive [`schemars::JsonSchema`], their JSON schemas are then
+//! translated into TypeScript interfaces or Go structs, so that downstream
+//! clients can be kept in sync with the wire format.
+use std::{collections::BTreeMap, fmt::Write as _};
+
+use serde_json::Value;
+
+use crate::{
+    attestation::Attestation,
+    certificate::Certificate,
+    rpc::{
+        EpochChange, EpochConfiguration, ErrorData, HealthReport, LatestSettledBatch, RollupStatus,
+        SendTxResult,
+    },
+    signed_tx::SignedTx,
+    spend::SpendReport,
+    tx_updates::TxUpdate,
+};
+
+/// The languages client types can be generated for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    TypeScript,
+    Go,
+}
+
+/// Generate the client types of the agglayer RPC for the given language.
+pub fn generate(language: Language) -> String {
+    let roots = [
+        serde_json::to_value(schemars::schema_for!(SignedTx)),
+        serde_json::to_value(schemars::schema_for!(Certificate)),
+        serde_json::to_value(schemars::schema_for!(RollupStatus)),
+        serde_json::to_value(schemars::schema_for!(LatestSettledBatch)),
+        serde_json::to_value(schemars::schema_for!(ErrorData)),
+        serde_json::to_value(schemars::schema_for!(Attestation)),
+        serde_json::to_value(schemars::schema_for!(SpendReport)),
+        serde_json::to_value(schemars::schema_for!(EpochChange)),
+        serde_json::to_value(schemars::schema_for!(EpochConfiguration)),
+        serde_json::to_value(schemars::schema_for!(HealthReport)),
+        serde_json::to_value(schemars::schema_for!(TxUpdate)),
+        serde_json::to_value(schemars::schema_for!(SendTxResult)),
+    ];
+
+    let mut definitions = BTreeMap::new();
+    for root in roots {
+        collect_definitions(
+            root.expect("JSON schemas are serializable"),
+            &mut definitions,
+        );
+    }
+
+    match language {
+        Language::TypeScript => typescript(&definitions),
+        Language::Go => go(&definitions),
+    }
+}
+
+/// Collect the named definitions of a root schema, including the root itself.
+fn collect_definitions(mut root: Value, definitions: &mut BTreeMap<String, Value>) {
+    if let Some(Value::Object(nested)) = root
+        .as_object_mut()
+        .and_then(|root| root.remove("definitions"))
+    {
+        definitions.extend(nested);
+    }
+
+    if let Some(title) = root.get("title").and_then(Value::as_str) {
+        definitions.insert(title.to_string(), root);
+    }
+}
+
+/// The type of a schema, stripped from its nullability.
+enum SchemaType<'a> {
+    Ref(&'a str),
+    String,
+    Boolean,
+    Integer(Option<&'a str>),
+    Number,
+    Array(Option<&'a Value>),
+    Any,
+}
+
+/// Resolve the type of a schema along with its nullability.
+fn schema_type(schema: &Value) -> (SchemaType<'_>, bool) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let name = reference.rsplit('/').next().unwrap_or(reference);
+        return (SchemaType::Ref(name), false);
+    }
+
+    // References carrying a description are wrapped into an `allOf`.
+    if let Some([inner]) = schema
+        .get("allOf")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+    {
+        return schema_type(inner);
+    }
+
+    let (instance_type, nullable) = match schema.get("type") {
+        Some(Value::String(instance_type)) => (instance_type.as_str(), false),
+        Some(Value::Array(types)) => {
+            let nullable = types.iter().any(|t| t == "null");
+            let instance_type = types
+                .iter()
+                .filter_map(Value::as_str)
+                .find(|t| *t != "null")
+                .unwrap_or("null");
+
+            (instance_type, nullable)
+        }
+        // Documented enumeration variants are listed in a `oneOf`.
+        _ if string_variants(schema).is_some() => ("string", false),
+        _ => ("", false),
+    };
+
+    let schema_type = match instance_type {
+        "string" => SchemaType::String,
+        "boolean" => SchemaType::Boolean,
+        "integer" => SchemaType::Integer(schema.get("format").and_then(Value::as_str)),
+        "number" => SchemaType::Number,
+        "array" => SchemaType::Array(schema.get("items")),
+        _ => SchemaType::Any,
+    };
+
+    (schema_type, nullable)
+}
+
+/// The string variants of a schema, if it describes a string enumeration.
+fn string_variants(schema: &Value) -> Option<Vec<&str>> {
+    if let Some(variants) = schema.get("enum").and_then(Value::as_array) {
+        return variants.iter().map(Value::as_str).collect();
+    }
+
+    schema
+        .get("oneOf")
+        .and_then(Value::as_array)?
+        .iter()
+        .map(|variant| match string_variants(variant)?.as_slice() {
+            [variant] => Some(*variant),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The properties of an object schema, along with whether they're required.
+fn properties(schema: &Value) -> Vec<(&str, &Value, bool)> {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(name, schema)| (name.as_str(), schema, required.contains(&name.as_str())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn description(schema: &Value) -> Option<&str> {
+    schema.get("description").and_then(Value::as_str)
+}
+
+fn comment(out: &mut String, indent: &str, prefix: &str, description: Option<&str>) {
+    for line in description.into_iter().flat_map(str::lines) {
+        let _ = writeln!(out, "{indent}{prefix}{line}");
+    }
+}
+
+fn typescript(definitions: &BTreeMap<String, Value>) -> String {
+    fn ts_type(schema: &Value) -> String {
+        let (schema_type, nullable) = schema_type(schema);
+        let ty = match schema_type {
+            SchemaType::Ref(name) => name.to_string(),
+            SchemaType::String => string_variants(schema)
+                .map(|variants| {
+                    variants
+                        .iter()
+                        .map(|variant| format!("{variant:?}"))
+                        .collect::<Vec<_>>()
+                        .join(" | ")
+                })
+                .unwrap_or_else(|| "string".to_string()),
+            SchemaType::Boolean => "boolean".to_string(),
+            SchemaType::Integer(_) | SchemaType::Number => "number".to_string(),
+            SchemaType::Array(items) => format!("{}[]", items.map(ts_type).unwrap_or_default()),
+            SchemaType::Any => "unknown".to_string(),
+        };
+
+        if nullable {
+            format!("{ty} | null")
+        } else {
+            ty
+        }
+    }
+
+    let mut out = String::from("// Code generated by `agglayer codegen`. DO NOT EDIT.\n");
+
+    for (name, schema) in definitions {
+        out.push('\n');
+        comment(&mut out, "", "// ", description(schema));
+
+        if schema.get("properties").is_none() {
+            let _ = writeln!(out, "export type {name} = {};", ts_type(schema));
+            continue;
+        }
+
+        let _ = writeln!(out, "export interface {name} {{");
+        for (field, field_schema, required) in properties(schema) {
+            comment(&mut out, "  ", "// ", description(field_schema));
+            let optional = if required { "" } else { "?" };
+            let _ = writeln!(out, "  {field}{optional}: {};", ts_type(field_schema));
+        }
+        out.push_str("}\n");
+    }
+
+    out
+}
+
+fn go(definitions: &BTreeMap<String, Value>) -> String {
+    fn go_type(schema: &Value) -> String {
+        let (schema_type, nullable) = schema_type(schema);
+        let ty = match schema_type {
+            SchemaType::Ref(name) => name.to_string(),
+            SchemaType::String => "string".to_string(),
+            SchemaType::Boolean => "bool".to_string(),
+            SchemaType::Integer(Some("uint8" | "uint16" | "uint32")) => "uint32".to_string(),
+            SchemaType::Integer(Some("uint" | "uint64")) => "uint64".to_string(),
+            SchemaType::Integer(Some("int8" | "int16" | "int32")) => "int32".to_string(),
+            SchemaType::Integer(_) => "int64".to_string(),
+            SchemaType::Number => "float64".to_string(),
+            SchemaType::Array(items) => format!("[]{}", items.map(go_type).unwrap_or_default()),
+            SchemaType::Any => "interface{}".to_string(),
+        };
+
+        if nullable {
+            format!("*{ty}")
+        } else {
+            ty
+        }
+    }
+
+    /// Go field names must start with an uppercase letter to be exported.
+    fn go_field(name: &str) -> String {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    }
+
+    let mut out =
+        String::from("// Code generated by `agglayer codegen`. DO NOT EDIT.\n\npackage agglayer\n");
+
+    for (name, schema) in definitions {
+        out.push('\n');
+        comment(&mut out, "", "// ", description(schema));
+
+        if schema.get("properties").is_none() {
+            let _ = writeln!(out, "type {name} {}", go_type(schema));
+
+            if let Some(variants) = string_variants(schema) {
+                out.push_str("\nconst (\n");
+                for variant in variants {
+                    let _ = writeln!(out, "\t{name}{} {name} = {variant:?}", go_field(variant));
+                }
+                out.push_str(")\n");
+            }
+            continue;
+        }
+
+        let _ = writeln!(out, "type {name} struct {{");
+        for (field, field_schema, required) in properties(schema) {
+            comment(&mut out, "\t", "// ", description(field_schema));
+            let omitempty = if required { "" } else { ",omitempty" };
+            let _ = writeln!(
+                out,
+                "\t{} {} `json:\"{field}{omitempty}\"`",
+                go_field(field),
+                go_type(field_schema)
+            );
+        }
+        out.push_str("}\n");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definitions() -> BTreeMap<String, Value> {
+        let mut definitions = BTreeMap::new();
+        collect_definitions(
+            serde_json::json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "title": "Outer",
+                "description": "The outer type.",
+                "type": "object",
+                "required": ["inner", "ID"],
+                "properties": {
+                    "ID": { "type": "integer", "format": "uint32", "minimum": 0.0 },
+                    "inner": {
+                        "description": "The inner value.",
+                        "allOf": [{ "$ref": "#/definitions/Inner" }]
+                    },
+                    "batch": { "type": ["integer", "null"], "format": "uint64" },
+                    "state": { "$ref": "#/definitions/State" }
+                },
+                "definitions": {
+                    "Inner": {
+                        "type": "object",
+                        "required": ["roots"],
+                        "properties": {
+                            "roots": { "type": "array", "items": { "type": "string" } }
+                        }
+                    },
+                    "State": {
+                        "oneOf": [
+                            { "description": "Closed.", "type": "string", "enum": ["closed"] },
+                            { "description": "Open.", "type": "string", "enum": ["open"] }
+                        ]
+                    }
+                }
+            }),
+            &mut definitions,
+        );
+
+        definitions
+    }
+
+    #[test]
+    fn generate_typescript() {
+        assert_eq!(
+            typescript(&definitions()),
+            r#"// Code generated by `agglayer codegen`. DO NOT EDIT.
+
+export interface Inner {
+  roots: string[];
+}
+
+// The outer type.
+export interface Outer {
+  ID: number;
+  batch?: number | null;
+  // The inner value.
+  inner: Inner;
+  state?: State;
+}
+
+export type State = "closed" | "open";
+"#
+        );
+    }
+
+    #[test]
+    fn generate_go() {
+        assert_eq!(
+            go(&definitions()),
+            r#"// Code generated by `agglayer codegen`. DO NOT EDIT.
+
+package agglayer
+
+type Inner struct {
+	Roots []string `json:"roots"`
+}
+
+// The outer type.
+type Outer struct {
+	ID uint32 `json:"ID"`
+	Batch *uint64 `json:"batch,omitempty"`
+	// The inner value.
+	Inner Inner `json:"inner"`
+	State State `json:"state,omitempty"`
+}
+
+type State string
+
+const (
+	StateClosed State = "closed"
+	StateOpen State = "open"
+)
+"#
+        );
+    }
+}
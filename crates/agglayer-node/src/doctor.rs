@@ -0,0 +1,395 @@
+//! Preflight checks of a node configuration.
+//!
+//! `agglayer doctor` runs these checks against the configured L1, signer,
+//! ZkEVM nodes and storage before a node gets put into rotation, and reports
+//! the outcome of each one of them.
+//!
+//! `agglayer config validate` runs a subset of them, checking a configuration
+//! file before it gets deployed: its consistency, its contracts on L1 and its
+//! signer.
+use std::{
+    fmt,
+    io::Write as _,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub use agglayer_config::DEFAULT_CONFIG;
+use agglayer_config::{AuthConfig, Config, LeaderElectionBackend, SettlementLockBackend};
+use agglayer_contracts::{L1RpcClient, RollupContract as _};
+use agglayer_signer::{AgglayerSigner as _, ConfiguredSigner};
+use anyhow::{anyhow, bail, Context as _};
+use ethers::providers::{Middleware as _, Provider};
+
+use crate::{
+    proxy, refresh::RefreshingHttp, registry::RollupRegistry, zkevm_node_client::ZkevmNodeClient,
+};
+
+/// The message signed to check the signer.
+const SIGNED_MESSAGE: &str = "agglayer doctor";
+
+/// The outcome of a single preflight check.
+#[derive(Debug)]
+pub struct Check {
+    /// What is checked.
+    pub name: String,
+    /// What was found on success, the failure otherwise.
+    pub outcome: Result<String, String>,
+}
+
+/// The outcome of all the preflight checks.
+#[derive(Debug, Default)]
+pub struct Report {
+    checks: Vec<Check>,
+}
+
+impl Report {
+    /// Whether every check passed.
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.outcome.is_ok())
+    }
+
+    /// The checks, in the order they were run.
+    pub fn checks(&self) -> &[Check] {
+        &self.checks
+    }
+
+    fn record(&mut self, name: impl Into<String>, outcome: anyhow::Result<String>) {
+        self.checks.push(Check {
+            name: name.into(),
+            outcome: outcome.map_err(|error| format!("{error:#}")),
+        });
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match &check.outcome {
+                Ok(found) => writeln!(f, "[PASS] {}: {found}", check.name)?,
+                Err(error) => writeln!(f, "[FAIL] {}: {error}", check.name)?,
+            }
+        }
+
+        let failed = self.checks.iter().filter(|c| c.outcome.is_err()).count();
+        writeln!(
+            f,
+            "{} checks, {} passed, {failed} failed",
+            self.checks.len(),
+            self.checks.len() - failed
+        )
+    }
+}
+
+/// Run the preflight checks of the given configuration file.
+///
+/// # Errors
+///
+/// This function only returns an error if the configuration can't be loaded,
+/// the failed checks are recorded in the [`Report`].
+pub fn run(cfg: PathBuf) -> anyhow::Result<Report> {
+    let config = Config::load(cfg)?;
+
+    if let Some(proxy) = &config.outbound.proxy {
+        proxy::export_to_env(proxy);
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    Ok(runtime.block_on(checks(Arc::new(config))))
+}
+
+async fn checks(config: Arc<Config>) -> Report {
+    let mut report = Report::default();
+
+    check_l1(&config, &mut report).await;
+
+    for url in &config.l1.fallback_node_urls {
+        let check = match RefreshingHttp::new(
+            url.clone(),
+            config.outbound.proxy.clone(),
+            config.outbound.connections.clone(),
+        ) {
+            Ok(transport) => check_chain_id(&Provider::new(transport), url, &config).await,
+            Err(error) => Err(error.into()),
+        };
+        report.record("L1 fallback chain id", check);
+    }
+
+    check_signers(&config, &mut report).await;
+
+    let rollups = RollupRegistry::new(&config);
+    report.record(
+        "Rollup registry",
+        rollups
+            .restore()
+            .map(|_| format!("{} rollups registered", rollups.rollup_ids().len()))
+            .context("unable to restore the rollups registered at runtime"),
+    );
+
+    for rollup_id in rollups.rollup_ids() {
+        let Some(rollup) = rollups.get(rollup_id) else {
+            continue;
+        };
+
+        report.record(
+            format!("ZkEVM node of rollup {rollup_id}"),
+            check_zkevm_node(rollup_id, &rollup.full_node_rpc, &config).await,
+        );
+    }
+
+    for (name, dir) in storage(&config) {
+        report.record(
+            name,
+            check_writable(&dir).map(|_| format!("{} is writable", dir.display())),
+        );
+    }
+
+    report
+}
+
+/// Run the static checks of the given configuration file, then resolve its
+/// contracts on L1 and check its signer.
+///
+/// # Errors
+///
+/// This function only returns an error if the configuration can't be loaded,
+/// the failed checks are recorded in the [`Report`].
+pub fn validate(cfg: PathBuf) -> anyhow::Result<Report> {
+    let config = Config::load(cfg)?;
+
+    if let Some(proxy) = &config.outbound.proxy {
+        proxy::export_to_env(proxy);
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    Ok(runtime.block_on(async {
+        let mut report = Report::default();
+
+        let errors = config.validate();
+        report.record(
+            "Configuration",
+            if errors.is_empty() {
+                Ok("consistent".to_string())
+            } else {
+                Err(anyhow!(errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")))
+            },
+        );
+
+        let config = Arc::new(config);
+        check_l1(&config, &mut report).await;
+        check_signers(&config, &mut report).await;
+
+        report
+    }))
+}
+
+/// Check the chain id of the primary L1 node, then resolve the rollup manager
+/// contract and the contracts of the configured rollups.
+async fn check_l1(config: &Config, report: &mut Report) {
+    let transport = match RefreshingHttp::new(
+        config.l1.node_url.clone(),
+        config.outbound.proxy.clone(),
+        config.outbound.connections.clone(),
+    ) {
+        Ok(transport) => transport,
+        Err(error) => return report.record("L1 chain id", Err(error.into())),
+    };
+    let provider = Arc::new(Provider::new(transport));
+
+    report.record(
+        "L1 chain id",
+        check_chain_id(&provider, &config.l1.node_url, config).await,
+    );
+
+    let l1 = L1RpcClient::new(provider, config.l1.rollup_manager_contract);
+    report.record(
+        "Rollup manager",
+        l1.get_rollup_count()
+            .await
+            .map(|count| {
+                format!(
+                    "{count} rollups attached to {:?}",
+                    config.l1.rollup_manager_contract
+                )
+            })
+            .context("unable to read the rollup manager contract"),
+    );
+
+    let mut rollup_ids: Vec<u32> = config.full_node_rpcs.keys().copied().collect();
+    rollup_ids.sort_unstable();
+    for rollup_id in rollup_ids {
+        let contract = l1
+            .get_rollup_metadata(rollup_id)
+            .await
+            .context("unable to read the rollup manager contract")
+            .and_then(|metadata| {
+                if metadata.rollup_contract.is_zero() {
+                    bail!("no rollup attached to the rollup manager contract");
+                }
+                Ok(format!("{:?}", metadata.rollup_contract))
+            });
+        report.record(format!("Contract of rollup {rollup_id}"), contract);
+    }
+}
+
+async fn check_chain_id(
+    provider: &Provider<RefreshingHttp>,
+    node_url: &url::Url,
+    config: &Config,
+) -> anyhow::Result<String> {
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .with_context(|| format!("unable to reach L1 at {node_url}"))?;
+
+    if chain_id != config.l1.chain_id.into() {
+        bail!("expected chain id {}, got {chain_id}", config.l1.chain_id);
+    }
+
+    Ok(format!("{chain_id} at {node_url}"))
+}
+
+/// Check the signer of the settlements, then the ones dedicated to the
+/// settlements of some rollups.
+async fn check_signers(config: &Config, report: &mut Report) {
+    report.record(
+        "Signer",
+        check_signer(config.l1.chain_id, &config.auth).await,
+    );
+
+    for settlement_signer in &config.settlement_signers {
+        report.record(
+            format!(
+                "Settlement signer of rollups {:?}",
+                settlement_signer.rollups
+            ),
+            check_signer(config.l1.chain_id, &settlement_signer.auth).await,
+        );
+    }
+}
+
+async fn check_signer(chain_id: u64, auth: &AuthConfig) -> anyhow::Result<String> {
+    let signer = ConfiguredSigner::from_auth(chain_id, auth).await?;
+    let signature = signer.sign_message(SIGNED_MESSAGE).await?;
+    let recovered = signature.recover(SIGNED_MESSAGE)?;
+
+    if recovered != signer.address() {
+        bail!("signed as {:?}, recovered {recovered:?}", signer.address());
+    }
+
+    Ok(format!("signs and recovers as {recovered:?}"))
+}
+
+async fn check_zkevm_node(
+    rollup_id: u32,
+    endpoint: &url::Url,
+    config: &Config,
+) -> anyhow::Result<String> {
+    let transport = RefreshingHttp::new(
+        endpoint.clone(),
+        config.outbound.proxy.clone(),
+        config.outbound.connections.clone(),
+    )?;
+    let batch = ZkevmNodeClient::new(transport, rollup_id, 1)
+        .batch_number()
+        .await
+        .with_context(|| format!("unable to reach {endpoint}"))?;
+
+    Ok(format!("batch {batch} at {endpoint}"))
+}
+
+/// The directories the node writes to, along with what they store.
+fn storage(config: &Config) -> Vec<(&'static str, PathBuf)> {
+    let parent = |path: &Path| match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let mut storage = Vec::new();
+
+    if let Some(path) = &config.admin.registry_path {
+        storage.push(("Rollup registry storage", parent(path)));
+    }
+
+    if let Some(path) = &config.admin.access_list_path {
+        storage.push(("Access list storage", parent(path)));
+    }
+
+    if config.high_availability.enabled {
+        match &config.high_availability.backend {
+            LeaderElectionBackend::File { path } => {
+                storage.push(("Leader lease storage", parent(path)))
+            }
+        }
+    }
+
+    if let SettlementLockBackend::Directory { path } = &config.high_availability.settlement_locks {
+        storage.push(("Settlement lock storage", path.clone()));
+    }
+
+    storage
+}
+
+/// Check that a file can be created in the given directory.
+fn check_writable(dir: &Path) -> anyhow::Result<()> {
+    let path = dir.join(format!(".agglayer-doctor-{}", std::process::id()));
+
+    let result = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(SIGNED_MESSAGE.as_bytes()));
+    let removed = std::fs::remove_file(&path);
+
+    match (result, removed) {
+        (Err(error), _) | (Ok(()), Err(error)) => {
+            Err(error).with_context(|| format!("unable to write to {}", dir.display()))
+        }
+        (Ok(()), Ok(())) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::*;
+
+    #[test]
+    fn report_fails_if_any_check_fails() {
+        let mut report = Report::default();
+        report.record("First", Ok("found".to_string()));
+
+        assert!(report.passed());
+
+        report.record("Second", Err(std::io::Error::other("unreachable").into()));
+
+        assert!(!report.passed());
+        assert_eq!(
+            report.to_string(),
+            "[PASS] First: found\n[FAIL] Second: unreachable\n2 checks, 1 passed, 1 failed\n"
+        );
+    }
+
+    #[test]
+    fn storage_must_be_writable() {
+        let dir = std::env::temp_dir().join(format!("agglayer-doctor-{:x}", H256::random()));
+        std::fs::create_dir(&dir).unwrap();
+
+        assert!(check_writable(&dir).is_ok());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        assert!(check_writable(&dir.join("missing")).is_err());
+
+        std::fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,203 @@
+//! Failover between the configured L1 nodes.
+//!
+//! [`FailoverTransport`] sends the requests to the current L1 node, and moves
+//! on to the next configured node when it fails to get a response from it.
+//! The JSON-RPC errors returned by a node, a reverted call for instance, are
+//! answers of their own and never trigger a failover.
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use agglayer_telemetry::{KeyValue, L1_FAILOVERS, L1_REQUESTS, L1_REQUEST_ERRORS};
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, RpcError as _};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{info, warn};
+use url::Url;
+
+/// A JSON-RPC transport failing over a list of endpoints, in order.
+#[derive(Debug)]
+pub(crate) struct FailoverTransport<C> {
+    endpoints: Vec<Endpoint<C>>,
+    /// The index of the endpoint the requests are sent to first.
+    current: AtomicUsize,
+}
+
+#[derive(Debug)]
+struct Endpoint<C> {
+    url: Url,
+    /// The origin of the endpoint, labelling its metrics without leaking the
+    /// credentials its URL may carry.
+    origin: String,
+    client: C,
+}
+
+impl<C> FailoverTransport<C> {
+    /// Create a transport failing over the given endpoints, starting with the
+    /// first one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no endpoint is given.
+    pub(crate) fn new(endpoints: impl IntoIterator<Item = (Url, C)>) -> Self {
+        let endpoints: Vec<_> = endpoints
+            .into_iter()
+            .map(|(url, client)| Endpoint {
+                origin: url.origin().ascii_serialization(),
+                url,
+                client,
+            })
+            .collect();
+        assert!(!endpoints.is_empty(), "No endpoint to fail over");
+
+        Self {
+            endpoints,
+            current: AtomicUsize::new(0),
+        }
+    }
+
+    /// The URL of the endpoint the requests are sent to first.
+    #[cfg(test)]
+    pub(crate) fn current_endpoint(&self) -> &Url {
+        &self.endpoints[self.current.load(Ordering::Relaxed)].url
+    }
+}
+
+#[async_trait]
+impl<C> JsonRpcClient for FailoverTransport<C>
+where
+    C: JsonRpcClient,
+{
+    type Error = C::Error;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let first = self.current.load(Ordering::Relaxed);
+        let mut index = first;
+
+        loop {
+            let endpoint = &self.endpoints[index];
+            let attributes = [KeyValue::new("endpoint", endpoint.origin.clone())];
+            L1_REQUESTS.add(1, &attributes);
+
+            match endpoint.client.request(method, &params).await {
+                Err(error) if !error.is_error_response() => {
+                    L1_REQUEST_ERRORS.add(1, &attributes);
+
+                    // Give up once every endpoint failed.
+                    let next = (index + 1) % self.endpoints.len();
+                    if next == first {
+                        return Err(error);
+                    }
+
+                    warn!(
+                        "L1 node {} failed to answer {method}: {error}, trying {}",
+                        endpoint.url, self.endpoints[next].url
+                    );
+                    index = next;
+                }
+                result => {
+                    // Stick to the endpoint that answered, unless a concurrent
+                    // request failed over already.
+                    if index != first
+                        && self
+                            .current
+                            .compare_exchange(first, index, Ordering::Relaxed, Ordering::Relaxed)
+                            .is_ok()
+                    {
+                        L1_FAILOVERS.add(1, &attributes);
+                        info!(
+                            "Failed over from L1 node {} to {}",
+                            self.endpoints[first].url, endpoint.url
+                        );
+                    }
+
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        providers::{JsonRpcClient as _, JsonRpcError, MockProvider, MockResponse, RpcError as _},
+        types::U64,
+    };
+
+    use super::FailoverTransport;
+
+    fn transport(
+        primary: &MockProvider,
+        fallback: &MockProvider,
+    ) -> FailoverTransport<MockProvider> {
+        FailoverTransport::new([
+            ("http://primary:8545".parse().unwrap(), primary.clone()),
+            ("http://fallback:8545".parse().unwrap(), fallback.clone()),
+        ])
+    }
+
+    #[tokio::test]
+    async fn unreachable_nodes_are_failed_over() {
+        let (primary, fallback) = (MockProvider::new(), MockProvider::new());
+        fallback.push(U64::from(2)).unwrap();
+        fallback.push(U64::from(1)).unwrap();
+        let transport = transport(&primary, &fallback);
+
+        // The primary node has no response to give.
+        let block: U64 = transport
+            .request("eth_blockNumber", ["latest"])
+            .await
+            .unwrap();
+        assert_eq!(block, 1.into());
+        assert_eq!(
+            transport.current_endpoint().as_str(),
+            "http://fallback:8545/"
+        );
+
+        // The next requests are sent to the fallback node directly.
+        let block: U64 = transport
+            .request("eth_blockNumber", ["latest"])
+            .await
+            .unwrap();
+        assert_eq!(block, 2.into());
+        primary
+            .assert_request("eth_blockNumber", ["latest"])
+            .unwrap();
+        assert!(primary
+            .assert_request("eth_blockNumber", ["latest"])
+            .is_err());
+
+        // The requests fail once every node failed.
+        assert!(transport
+            .request::<_, U64>("eth_blockNumber", ["latest"])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn json_rpc_errors_are_not_failed_over() {
+        let (primary, fallback) = (MockProvider::new(), MockProvider::new());
+        primary.push_response(MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: None,
+        }));
+        fallback.push(U64::from(1)).unwrap();
+        let transport = transport(&primary, &fallback);
+
+        assert!(transport
+            .request::<_, U64>("eth_call", ["latest"])
+            .await
+            .unwrap_err()
+            .as_error_response()
+            .is_some());
+        assert_eq!(
+            transport.current_endpoint().as_str(),
+            "http://primary:8545/"
+        );
+        assert!(fallback.assert_request("eth_call", ["latest"]).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+//! Indexing of the settlement events emitted on L1.
+//!
+//! The [`SettlementIndexer`] polls the rollup manager contract for the
+//! `VerifyBatches` and `VerifyBatchesTrustedAggregator` events and ingests them
+//! into the [`SettlementIndex`], where they are linked back to the proofs
+//! settled by the agglayer.
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    sync::{Arc, RwLock},
+};
+
+use agglayer_config::SettlementIndexerConfig;
+use agglayer_contracts::{L1RpcClient, RollupContract, VerifiedBatches};
+use ethers::{
+    providers::Middleware,
+    types::{Address, H256},
+};
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+#[cfg(test)]
+mod tests;
+
+/// A settlement event indexed from L1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct IndexedSettlement {
+    pub(crate) verified_batches: VerifiedBatches,
+    /// The hash of the proof submitted to the agglayer and settled by this
+    /// event, if it was settled by this node.
+    pub(crate) proof_hash: Option<H256>,
+}
+
+#[derive(Debug, Default)]
+struct IndexState {
+    /// The last L1 block indexed.
+    last_indexed_block: Option<u64>,
+    /// The indexed settlement events, by block number.
+    by_block: BTreeMap<u64, Vec<VerifiedBatches>>,
+    /// The hashes of the proofs settled by the agglayer, by settlement
+    /// transaction hash.
+    proofs: HashMap<H256, H256>,
+}
+
+/// In-memory index of the settlement events emitted on L1.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SettlementIndex {
+    state: Arc<RwLock<IndexState>>,
+}
+
+impl SettlementIndex {
+    /// Get the last L1 block indexed.
+    pub(crate) fn last_indexed_block(&self) -> Option<u64> {
+        self.read().last_indexed_block
+    }
+
+    /// Link the settlement transaction sent by the agglayer to the hash of the
+    /// proof it settles.
+    pub(crate) fn link(&self, transaction_hash: H256, proof_hash: H256) {
+        self.write().proofs.insert(transaction_hash, proof_hash);
+    }
+
+    /// Get the settlement event emitted by the given transaction, if indexed.
+    pub(crate) fn settlement_by_tx(&self, transaction_hash: H256) -> Option<IndexedSettlement> {
+        let state = self.read();
+
+        state
+            .by_block
+            .values()
+            .flatten()
+            .find(|verified_batches| verified_batches.transaction_hash == transaction_hash)
+            .map(|verified_batches| IndexedSettlement {
+                verified_batches: verified_batches.clone(),
+                proof_hash: state.proofs.get(&transaction_hash).copied(),
+            })
+    }
+
+    /// Get the last batch of the given rollup verified on L1, if indexed.
+    pub(crate) fn last_verified_batch(&self, rollup_id: u32) -> Option<u64> {
+        self.read()
+            .by_block
+            .values()
+            .flatten()
+            .filter(|verified_batches| verified_batches.rollup_id == rollup_id)
+            .map(|verified_batches| verified_batches.num_batch)
+            .max()
+    }
+
+    /// Replace the settlement events indexed in the given block range.
+    ///
+    /// Returns the previously indexed events that are no longer part of the
+    /// range, i.e. the ones reverted by an L1 reorg.
+    pub(crate) fn replace(
+        &self,
+        blocks: RangeInclusive<u64>,
+        events: Vec<VerifiedBatches>,
+    ) -> Vec<VerifiedBatches> {
+        let mut state = self.write();
+
+        let mut replaced: Vec<VerifiedBatches> = state
+            .by_block
+            .range(blocks.clone())
+            .flat_map(|(_, events)| events.iter().cloned())
+            .collect();
+        state.by_block.retain(|block, _| !blocks.contains(block));
+
+        for event in events {
+            replaced.retain(|replaced| *replaced != event);
+            state
+                .by_block
+                .entry(event.block_number)
+                .or_default()
+                .push(event);
+        }
+
+        state.last_indexed_block = Some(
+            state
+                .last_indexed_block
+                .map_or(*blocks.end(), |last| last.max(*blocks.end())),
+        );
+
+        replaced
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, IndexState> {
+        self.state.read().expect("Settlement index lock poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, IndexState> {
+        self.state.write().expect("Settlement index lock poisoned")
+    }
+}
+
+/// Errors that can occur while indexing the settlement events.
+#[derive(Error, Debug)]
+pub(crate) enum IndexerError<RpcProvider: Middleware> {
+    #[error("middleware error: {0}")]
+    ProviderError(RpcProvider::Error),
+    #[error("contract error: {0}")]
+    ContractError(#[from] ethers::contract::ContractError<RpcProvider>),
+}
+
+/// Background task ingesting the settlement events emitted on L1 into a
+/// [`SettlementIndex`].
+pub(crate) struct SettlementIndexer<RpcProvider> {
+    rpc: Arc<RpcProvider>,
+    l1: L1RpcClient<RpcProvider>,
+    index: SettlementIndex,
+    config: SettlementIndexerConfig,
+}
+
+impl<RpcProvider> SettlementIndexer<RpcProvider>
+where
+    RpcProvider: Middleware + 'static,
+{
+    pub(crate) fn new(
+        rpc: Arc<RpcProvider>,
+        rollup_manager_contract: Address,
+        index: SettlementIndex,
+        config: SettlementIndexerConfig,
+    ) -> Self {
+        Self {
+            l1: L1RpcClient::new(rpc.clone(), rollup_manager_contract),
+            rpc,
+            index,
+            config,
+        }
+    }
+
+    /// Index the settlement events until cancelled.
+    pub(crate) async fn run(self, cancellation_token: CancellationToken) {
+        let mut interval = tokio::time::interval(self.config.poll_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    debug!("Settlement indexer cancelled by token");
+                    break;
+                }
+                _ = interval.tick() => {}
+            }
+
+            if let Err(error) = self.index_new_blocks().await {
+                error!("Failed to index the L1 settlement events: {error}");
+            }
+        }
+    }
+
+    /// Index the blocks produced since the last poll, along with the last
+    /// `reorg_depth` blocks already indexed.
+    async fn index_new_blocks(&self) -> Result<(), IndexerError<RpcProvider>> {
+        let latest_block = self
+            .rpc
+            .get_block_number()
+            .await
+            .map_err(IndexerError::ProviderError)?
+            .as_u64();
+
+        let from_block = match self.index.last_indexed_block() {
+            Some(last_indexed_block) => last_indexed_block
+                .saturating_sub(self.config.reorg_depth)
+                .max(self.config.start_block.unwrap_or(0)),
+            None => self
+                .config
+                .start_block
+                .unwrap_or_else(|| latest_block.saturating_sub(self.config.reorg_depth)),
+        };
+
+        for blocks in block_ranges(from_block, latest_block, self.config.max_block_range) {
+            let events = self
+                .l1
+                .get_verified_batches(*blocks.start(), *blocks.end())
+                .await?;
+
+            for reverted in self.index.replace(blocks, events) {
+                warn!(
+                    "Settlement of batch {} of rollup {} in transaction {:?} reverted by an L1 \
+                     reorg",
+                    reverted.num_batch, reverted.rollup_id, reverted.transaction_hash
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Split the blocks `from..=to` into ranges of at most `max_range` blocks.
+pub(crate) fn block_ranges(
+    from: u64,
+    to: u64,
+    max_range: u64,
+) -> impl Iterator<Item = RangeInclusive<u64>> {
+    let max_range = max_range.max(1);
+
+    (from..=to)
+        .step_by(usize::try_from(max_range).unwrap_or(usize::MAX))
+        .map(move |start| start..=start.saturating_add(max_range - 1).min(to))
+}
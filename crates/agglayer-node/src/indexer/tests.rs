@@ -0,0 +1,66 @@
+use agglayer_contracts::VerifiedBatches;
+use ethers::types::{Address, H256};
+
+use crate::indexer::{block_ranges, SettlementIndex};
+
+fn verified_batches(rollup_id: u32, num_batch: u64, block_number: u64) -> VerifiedBatches {
+    VerifiedBatches {
+        rollup_id,
+        num_batch,
+        state_root: H256::random(),
+        exit_root: H256::random(),
+        aggregator: Address::random(),
+        trusted_aggregator: true,
+        block_number,
+        block_hash: H256::random(),
+        transaction_hash: H256::random(),
+    }
+}
+
+#[test]
+fn settlements_are_linked_to_proofs() {
+    let index = SettlementIndex::default();
+    let settled = verified_batches(1, 10, 100);
+    let proof_hash = H256::random();
+
+    index.link(settled.transaction_hash, proof_hash);
+    assert!(index.replace(90..=110, vec![settled.clone()]).is_empty());
+
+    let settlement = index.settlement_by_tx(settled.transaction_hash).unwrap();
+    assert_eq!(settlement.verified_batches, settled);
+    assert_eq!(settlement.proof_hash, Some(proof_hash));
+    assert_eq!(index.last_indexed_block(), Some(110));
+    assert_eq!(index.last_verified_batch(1), Some(10));
+    assert_eq!(index.last_verified_batch(2), None);
+}
+
+#[test]
+fn reorged_settlements_are_reverted() {
+    let index = SettlementIndex::default();
+    let kept = verified_batches(1, 10, 100);
+    let reorged = verified_batches(1, 11, 105);
+
+    index.replace(90..=110, vec![kept.clone(), reorged.clone()]);
+    assert_eq!(index.last_verified_batch(1), Some(11));
+
+    // The last blocks are indexed again after the reorg, without the second
+    // settlement.
+    let reverted = index.replace(95..=112, vec![kept.clone()]);
+
+    assert_eq!(reverted, vec![reorged.clone()]);
+    assert!(index.settlement_by_tx(reorged.transaction_hash).is_none());
+    assert!(index.settlement_by_tx(kept.transaction_hash).is_some());
+    assert_eq!(index.last_verified_batch(1), Some(10));
+    assert_eq!(index.last_indexed_block(), Some(112));
+}
+
+#[test]
+fn block_ranges_are_bounded() {
+    assert_eq!(
+        block_ranges(10, 35, 10).collect::<Vec<_>>(),
+        vec![10..=19, 20..=29, 30..=35]
+    );
+    assert_eq!(block_ranges(10, 10, 10).collect::<Vec<_>>(), vec![10..=10]);
+    assert_eq!(block_ranges(11, 10, 10).count(), 0);
+    assert_eq!(block_ranges(0, 2, 0).count(), 3);
+}
@@ -0,0 +1,171 @@
+//! Circuit breaker of the calls to L1.
+//!
+//! Once L1 is unreachable, every submission would still run through the
+//! verification, only to time out slowly. [`L1CircuitBreaker`] trips after
+//! consecutive calls to L1 failed, the submissions then being rejected right
+//! away, and closes again once L1 answers one of the probes sent in the
+//! background.
+use std::{
+    fmt::Display,
+    future::Future,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use agglayer_config::OutboundRpcL1CircuitBreakerConfig;
+use agglayer_telemetry::L1_CIRCUIT_BREAKER_TRIPS;
+use ethers::prelude::*;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+use super::Kernel;
+
+/// The circuit breaker of the calls to L1.
+#[derive(Debug)]
+pub(super) struct L1CircuitBreaker {
+    /// The number of consecutive failures tripping the breaker, or zero if
+    /// disabled.
+    failure_threshold: usize,
+    consecutive_failures: AtomicUsize,
+    open: AtomicBool,
+}
+
+impl L1CircuitBreaker {
+    pub(super) fn new(config: &OutboundRpcL1CircuitBreakerConfig) -> Self {
+        Self {
+            failure_threshold: config.failure_threshold,
+            consecutive_failures: AtomicUsize::new(0),
+            open: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether the breaker tripped, L1 being unreachable.
+    pub(super) fn is_open(&self) -> bool {
+        self.open.load(Ordering::Relaxed)
+    }
+
+    /// Record a call answered by L1, closing the breaker.
+    pub(super) fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+
+        if self.open.swap(false, Ordering::Relaxed) {
+            info!("L1 is reachable again, closing the circuit breaker");
+        }
+    }
+
+    /// Record a call that failed to reach L1, tripping the breaker after too
+    /// many consecutive ones.
+    pub(super) fn record_failure(&self) {
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if self.failure_threshold > 0
+            && failures >= self.failure_threshold
+            && !self.open.swap(true, Ordering::Relaxed)
+        {
+            warn!(
+                "L1 is unreachable after {failures} consecutive failures, tripping the circuit \
+                 breaker"
+            );
+            L1_CIRCUIT_BREAKER_TRIPS.add(1, &[]);
+        }
+    }
+}
+
+impl<RpcProvider> Kernel<RpcProvider>
+where
+    RpcProvider: Middleware + 'static,
+{
+    /// Whether L1 is unreachable, the submissions being rejected right away.
+    pub(crate) fn l1_circuit_open(&self) -> bool {
+        self.l1_breaker.is_open()
+    }
+
+    /// Run the given call to L1 with retries, recording its outcome in the
+    /// circuit breaker.
+    ///
+    /// The errors answered by L1, a reverted call for instance, are successful
+    /// calls as far as the breaker is concerned.
+    pub(super) async fn call_l1<T, E, F>(
+        &self,
+        call: &'static str,
+        attempt: impl FnMut() -> F,
+        transient: impl Fn(&E) -> bool,
+    ) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+        E: Display,
+    {
+        let result = self.retry.retry(call, attempt, &transient).await;
+
+        match &result {
+            Err(error) if transient(error) => self.l1_breaker.record_failure(),
+            _ => self.l1_breaker.record_success(),
+        }
+
+        result
+    }
+
+    /// Probe L1 while the circuit breaker is tripped, closing it once L1
+    /// answers, until cancelled.
+    pub(crate) async fn probe_l1_recovery(&self, cancellation_token: CancellationToken) {
+        let mut interval =
+            tokio::time::interval(self.config.outbound.rpc.l1_circuit_breaker.probe_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = cancellation_token.cancelled() => break,
+            }
+
+            if !self.l1_breaker.is_open() {
+                continue;
+            }
+
+            match self.probe_l1().await {
+                Ok(_) => self.l1_breaker.record_success(),
+                Err(error) => debug!("L1 still unreachable: {error}"),
+            }
+        }
+
+        debug!("L1 recovery probes stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use agglayer_config::OutboundRpcL1CircuitBreakerConfig;
+
+    use super::L1CircuitBreaker;
+
+    #[test]
+    fn consecutive_failures_trip_the_breaker() {
+        let breaker = L1CircuitBreaker::new(&OutboundRpcL1CircuitBreakerConfig {
+            failure_threshold: 3,
+            probe_interval: Duration::from_secs(5),
+        });
+
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+
+        breaker.record_failure();
+        assert!(breaker.is_open());
+
+        breaker.record_success();
+        assert!(!breaker.is_open());
+
+        let disabled = L1CircuitBreaker::new(&OutboundRpcL1CircuitBreakerConfig {
+            failure_threshold: 0,
+            probe_interval: Duration::from_secs(5),
+        });
+        for _ in 0..10 {
+            disabled.record_failure();
+        }
+        assert!(!disabled.is_open());
+    }
+}
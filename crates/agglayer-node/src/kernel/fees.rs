@@ -0,0 +1,152 @@
+//! Estimation of the fees of the settlement transactions.
+use agglayer_config::{OracleGasCategory, OutboundProxyConfig, SettlementFees};
+use ethers::{
+    middleware::gas_oracle::{BlockNative, GasCategory, GasOracle as _, GasOracleError},
+    providers::Middleware,
+    types::{BlockNumber, FeeHistory, U256},
+};
+use thiserror::Error;
+use url::Url;
+
+use crate::proxy;
+
+/// The endpoint of the Blocknative gas oracle.
+const BLOCKNATIVE_URL: &str = "https://api.blocknative.com/gasprices/blockprices";
+
+/// Errors related to the estimation of the settlement fees.
+#[derive(Error, Debug)]
+pub(crate) enum FeeEstimationError<RpcProvider>
+where
+    RpcProvider: Middleware,
+{
+    #[error("gas oracle error: {0}")]
+    Oracle(#[from] GasOracleError),
+    #[error("fee history error: {0}")]
+    FeeHistory(RpcProvider::Error),
+    #[error("no priority fee in the fee history")]
+    EmptyFeeHistory,
+}
+
+/// Estimate the `max_fee_per_gas` and `max_priority_fee_per_gas` of the
+/// settlement transactions, with the configured strategy.
+#[derive(Debug, Default)]
+pub(crate) enum FeeEstimator {
+    /// Leave the fees to the provider.
+    #[default]
+    Provider,
+    Static {
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    },
+    Oracle(BlockNative),
+    Percentile {
+        blocks: u64,
+        percentile: f64,
+    },
+}
+
+impl FeeEstimator {
+    /// Create the estimator of the given strategy, reaching the gas oracle
+    /// through the given proxy if any.
+    pub(crate) fn new(
+        fees: &SettlementFees,
+        proxy: Option<&OutboundProxyConfig>,
+    ) -> reqwest::Result<Self> {
+        Ok(match fees {
+            SettlementFees::Provider => Self::Provider,
+            SettlementFees::Static {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => Self::Static {
+                max_fee_per_gas: (*max_fee_per_gas).into(),
+                max_priority_fee_per_gas: (*max_priority_fee_per_gas).into(),
+            },
+            SettlementFees::Oracle { api_key, category } => {
+                let endpoint = Url::parse(BLOCKNATIVE_URL).expect("valid Blocknative URL");
+                let oracle = BlockNative::with_client(
+                    proxy::http_client(proxy, &endpoint)?,
+                    api_key.clone(),
+                )
+                .category(match category {
+                    OracleGasCategory::SafeLow => GasCategory::SafeLow,
+                    OracleGasCategory::Standard => GasCategory::Standard,
+                    OracleGasCategory::Fast => GasCategory::Fast,
+                    OracleGasCategory::Fastest => GasCategory::Fastest,
+                });
+                Self::Oracle(oracle)
+            }
+            SettlementFees::Percentile { blocks, percentile } => Self::Percentile {
+                blocks: *blocks,
+                percentile: *percentile,
+            },
+        })
+    }
+
+    /// Estimate the max fee and the max priority fee per gas, or `None` to
+    /// leave them to the provider.
+    pub(crate) async fn estimate<RpcProvider: Middleware>(
+        &self,
+        rpc: &RpcProvider,
+    ) -> Result<Option<(U256, U256)>, FeeEstimationError<RpcProvider>> {
+        match self {
+            FeeEstimator::Provider => Ok(None),
+            FeeEstimator::Static {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => Ok(Some((*max_fee_per_gas, *max_priority_fee_per_gas))),
+            FeeEstimator::Oracle(oracle) => Ok(Some(oracle.estimate_eip1559_fees().await?)),
+            FeeEstimator::Percentile { blocks, percentile } => {
+                let history = rpc
+                    .fee_history(U256::from(*blocks), BlockNumber::Latest, &[*percentile])
+                    .await
+                    .map_err(FeeEstimationError::FeeHistory)?;
+
+                fees_from_history(&history)
+                    .map(Some)
+                    .ok_or(FeeEstimationError::EmptyFeeHistory)
+            }
+        }
+    }
+}
+
+/// Get the fees from a fee history of a single percentile: the priority fee
+/// is the median of the percentile over the blocks, and the max fee leaves
+/// room for the base fee to double.
+fn fees_from_history(history: &FeeHistory) -> Option<(U256, U256)> {
+    // The last base fee is the one of the next block.
+    let base_fee = *history.base_fee_per_gas.last()?;
+
+    let mut priority_fees: Vec<U256> = history
+        .reward
+        .iter()
+        .filter_map(|rewards| rewards.first().copied())
+        .collect();
+    priority_fees.sort_unstable();
+    let priority_fee = *priority_fees.get(priority_fees.len() / 2)?;
+
+    Some((base_fee * 2 + priority_fee, priority_fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fees_follow_the_median_of_the_percentile() {
+        let history = FeeHistory {
+            base_fee_per_gas: vec![10.into(), 12.into(), 15.into()],
+            gas_used_ratio: vec![0.9, 0.9],
+            oldest_block: 100.into(),
+            reward: vec![vec![3.into()], vec![1.into()], vec![2.into()]],
+        };
+
+        assert_eq!(fees_from_history(&history), Some((32.into(), 2.into())));
+
+        let history = FeeHistory {
+            reward: Vec::new(),
+            ..history
+        };
+
+        assert_eq!(fees_from_history(&history), None);
+    }
+}
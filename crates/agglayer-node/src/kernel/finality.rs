@@ -0,0 +1,259 @@
+//! Finality of the settlements, and their re-settlement when reorged out.
+//!
+//! Every mined settlement is followed until final. A settlement moved to
+//! another block by a reorg is followed in its new block, as is a settlement
+//! back in the mempool once mined again. A settlement reorged out of the chain
+//! and dropped from the mempool is handed to [`Kernel::resettle_reorged`] to be
+//! settled again, its nonce being assigned again to the new settlement.
+use std::sync::Mutex;
+
+use agglayer_config::SettlementFinality;
+use agglayer_telemetry::{KeyValue, SETTLEMENT_REORGS};
+use ethers::prelude::*;
+use tokio::{sync::mpsc, time::sleep};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+
+use super::{final_block_height, Kernel};
+use crate::{signed_tx::SignedTx, tx_updates::TxUpdate};
+
+/// The proofs whose settlement was reorged out, waiting to be settled again.
+#[derive(Debug)]
+pub(super) struct ReorgedSettlements {
+    sender: mpsc::UnboundedSender<SignedTx>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<SignedTx>>>,
+}
+
+impl Default for ReorgedSettlements {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        Self {
+            sender,
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+}
+
+impl<RpcProvider> Kernel<RpcProvider>
+where
+    RpcProvider: Middleware + 'static,
+{
+    /// Publish the finalization of the given mined settlement once its block
+    /// is final, in the background, or else hand the proof over to
+    /// [`Kernel::resettle_reorged`] if reorged out.
+    pub(super) fn watch_finality(&self, signed_tx: &SignedTx, receipt: &TransactionReceipt) {
+        if receipt.block_number.is_none() {
+            return;
+        }
+        let rpc = self.rpc.clone();
+        let finality = self.settlement_finality();
+        let interval = self.config.outbound.rpc.settle.retry_interval;
+        let tx_updates = self.tx_updates.clone();
+        let reorged = self.reorged.sender.clone();
+        let signed_tx = signed_tx.clone();
+        let receipt = receipt.clone();
+
+        tokio::spawn(async move {
+            let proof_hash = signed_tx.hash();
+            match final_receipt(rpc.as_ref(), finality, interval, receipt).await {
+                Some(receipt) => tx_updates.publish(TxUpdate::finalized(proof_hash, &receipt)),
+                None => {
+                    SETTLEMENT_REORGS.add(
+                        1,
+                        &[KeyValue::new(
+                            "rollup_id",
+                            signed_tx.tx.rollup_id.to_string(),
+                        )],
+                    );
+                    warn!("The settlement of proof {proof_hash} was reorged out");
+
+                    _ = reorged.send(signed_tx);
+                }
+            }
+        });
+    }
+
+    /// Settle again the proofs whose settlement was reorged out, until
+    /// cancelled.
+    pub(crate) async fn resettle_reorged(&self, cancellation_token: CancellationToken) {
+        let Some(mut reorged) = self.reorged.receiver.lock().unwrap().take() else {
+            return;
+        };
+
+        loop {
+            let signed_tx = tokio::select! {
+                Some(signed_tx) = reorged.recv() => signed_tx,
+                _ = cancellation_token.cancelled() => break,
+            };
+            let proof_hash = signed_tx.hash();
+
+            // The proof is no longer settled, it can be settled again.
+            self.release_settlement_lock(proof_hash).await;
+
+            match self.settle_proof(&signed_tx, false).await {
+                Ok(receipt) => {
+                    self.settlements.link(receipt.transaction_hash, proof_hash);
+                    info!("Settled the reorged proof {proof_hash} again => receipt {receipt:?}");
+                }
+                Err(error) => error!("Failed to settle the reorged proof {proof_hash}: {error}"),
+            }
+        }
+
+        debug!("Re-settlement of the reorged proofs stopped");
+    }
+}
+
+/// Wait for the given mined settlement to be final, following it across the
+/// reorgs moving it to another block or back to the mempool.
+///
+/// Returns `None` if the settlement was reorged out of the chain and dropped
+/// from the mempool.
+async fn final_receipt<RpcProvider: Middleware>(
+    rpc: &RpcProvider,
+    finality: SettlementFinality,
+    interval: std::time::Duration,
+    mut receipt: TransactionReceipt,
+) -> Option<TransactionReceipt> {
+    loop {
+        let block_number = receipt.block_number?;
+        match final_block_height(rpc, finality).await {
+            Ok(final_block) if final_block >= block_number => {
+                // Check that the settlement is still part of the chain, in the
+                // block it was mined in.
+                match rpc.get_transaction_receipt(receipt.transaction_hash).await {
+                    Ok(Some(current)) if current.block_hash == receipt.block_hash => {
+                        return Some(receipt)
+                    }
+                    Ok(Some(current)) => {
+                        warn!(
+                            "Settlement transaction {:?} moved from block {block_number} to {:?}",
+                            receipt.transaction_hash, current.block_number
+                        );
+                        receipt = current;
+                        continue;
+                    }
+                    // Settling again while the transaction is pending would
+                    // settle the proof twice.
+                    Ok(None) => match rpc.get_transaction(receipt.transaction_hash).await {
+                        Ok(Some(_)) => debug!(
+                            "Settlement transaction {:?} reorged out of block {block_number} is \
+                             pending again",
+                            receipt.transaction_hash
+                        ),
+                        Ok(None) => return None,
+                        Err(error) => warn!(
+                            "Failed to get settlement transaction {:?}: {error}",
+                            receipt.transaction_hash
+                        ),
+                    },
+                    Err(error) => warn!(
+                        "Failed to get the receipt of settlement transaction {:?}: {error}",
+                        receipt.transaction_hash
+                    ),
+                }
+            }
+            Ok(_) => {}
+            Err(error) => warn!("Failed to get the final L1 block: {error}"),
+        }
+        sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use agglayer_config::SettlementFinality;
+    use ethers::prelude::*;
+
+    use super::final_receipt;
+
+    fn receipt(block_number: u64, block_hash: H256) -> TransactionReceipt {
+        TransactionReceipt {
+            transaction_hash: H256::repeat_byte(1),
+            block_number: Some(block_number.into()),
+            block_hash: Some(block_hash),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn final_settlements_are_still_in_their_block() {
+        let (provider, mock) = Provider::mocked();
+        let mined = receipt(5, H256::repeat_byte(5));
+
+        // The responses are consumed in reverse order.
+        mock.push(mined.clone()).unwrap();
+        mock.push(U64::from(10)).unwrap();
+
+        assert_eq!(
+            final_receipt(
+                &provider,
+                SettlementFinality::Confirmations,
+                Duration::ZERO,
+                mined.clone()
+            )
+            .await,
+            Some(mined)
+        );
+    }
+
+    #[tokio::test]
+    async fn settlements_are_followed_across_reorgs() {
+        let (provider, mock) = Provider::mocked();
+        let mined = receipt(5, H256::repeat_byte(5));
+        let moved = receipt(6, H256::repeat_byte(6));
+
+        // The settlement moves to block 6, then gets reorged out and dropped.
+        mock.push(serde_json::Value::Null).unwrap();
+        mock.push(serde_json::Value::Null).unwrap();
+        mock.push(U64::from(10)).unwrap();
+        mock.push(moved).unwrap();
+        mock.push(U64::from(10)).unwrap();
+
+        assert_eq!(
+            final_receipt(
+                &provider,
+                SettlementFinality::Confirmations,
+                Duration::ZERO,
+                mined
+            )
+            .await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn reorged_settlements_are_followed_while_pending() {
+        let (provider, mock) = Provider::mocked();
+        let mined = receipt(5, H256::repeat_byte(5));
+        let moved = receipt(6, H256::repeat_byte(6));
+        let pending = Transaction {
+            hash: mined.transaction_hash,
+            nonce: 7.into(),
+            ..Default::default()
+        };
+
+        // The settlement is reorged out back to the mempool, then mined again
+        // in block 6.
+        mock.push(moved.clone()).unwrap();
+        mock.push(U64::from(10)).unwrap();
+        mock.push(moved.clone()).unwrap();
+        mock.push(U64::from(10)).unwrap();
+        mock.push(pending).unwrap();
+        mock.push(serde_json::Value::Null).unwrap();
+        mock.push(U64::from(10)).unwrap();
+
+        assert_eq!(
+            final_receipt(
+                &provider,
+                SettlementFinality::Confirmations,
+                Duration::ZERO,
+                mined
+            )
+            .await,
+            Some(moved)
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Escalation of the gas price of the stuck settlement transactions.
+use agglayer_config::GasBumpConfig;
+use ethers::types::{transaction::eip2718::TypedTransaction, U256};
+
+/// Bump the gas price of the given transaction by the configured percentage,
+/// up to the configured cap. Returns whether the gas price was bumped, it
+/// isn't once the cap is reached.
+pub(crate) fn bump(tx: &mut TypedTransaction, config: &GasBumpConfig) -> bool {
+    let cap = U256::from(config.max_gas_price);
+    let bumped = |price: U256| (price * (100 + config.percent) / 100).min(cap);
+
+    match tx {
+        TypedTransaction::Eip1559(tx) => {
+            let Some(max_fee) = tx.max_fee_per_gas else {
+                return false;
+            };
+            let new_max_fee = bumped(max_fee);
+            if new_max_fee <= max_fee {
+                return false;
+            }
+
+            tx.max_fee_per_gas = Some(new_max_fee);
+            // The replacement must bump the priority fee as well.
+            tx.max_priority_fee_per_gas = tx
+                .max_priority_fee_per_gas
+                .map(|priority_fee| bumped(priority_fee).min(new_max_fee));
+
+            true
+        }
+        _ => {
+            let Some(gas_price) = tx.gas_price() else {
+                return false;
+            };
+            let new_gas_price = bumped(gas_price);
+            if new_gas_price <= gas_price {
+                return false;
+            }
+
+            tx.set_gas_price(new_gas_price);
+
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ethers::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    use super::*;
+
+    const CONFIG: GasBumpConfig = GasBumpConfig {
+        stuck_after: Duration::from_secs(180),
+        percent: 20,
+        max_gas_price: 150,
+    };
+
+    #[test]
+    fn gas_price_is_bumped_up_to_the_cap() {
+        let mut tx: TypedTransaction = TransactionRequest::new().gas_price(100).into();
+
+        assert!(bump(&mut tx, &CONFIG));
+        assert_eq!(tx.gas_price(), Some(120.into()));
+        assert!(bump(&mut tx, &CONFIG));
+        assert_eq!(tx.gas_price(), Some(144.into()));
+        assert!(bump(&mut tx, &CONFIG));
+        assert_eq!(tx.gas_price(), Some(150.into()));
+        assert!(!bump(&mut tx, &CONFIG));
+        assert_eq!(tx.gas_price(), Some(150.into()));
+    }
+
+    #[test]
+    fn priority_fee_is_bumped_along_the_max_fee() {
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .max_fee_per_gas(100)
+            .max_priority_fee_per_gas(10)
+            .into();
+
+        assert!(bump(&mut tx, &CONFIG));
+
+        let TypedTransaction::Eip1559(tx) = tx else {
+            unreachable!();
+        };
+        assert_eq!(tx.max_fee_per_gas, Some(120.into()));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(12.into()));
+    }
+}
@@ -0,0 +1,121 @@
+//! Probes of the components the kernel depends on, reported by the health
+//! and readiness checks of the RPC server.
+use std::{sync::atomic::Ordering, time::Duration};
+
+use ethers::prelude::*;
+use thiserror::Error;
+
+use super::{Kernel, ZkevmNodeVerificationError};
+
+/// The message signed to probe the signer.
+const PROBED_MESSAGE: &[u8] = b"agglayer health check";
+
+/// Errors that can occur while probing the signer.
+#[derive(Error, Debug)]
+pub(crate) enum SignerProbeError<RpcProvider: Middleware> {
+    #[error("no signer configured")]
+    Missing,
+    #[error("signing failed: {0}")]
+    SigningFailed(RpcProvider::Error),
+}
+
+/// The reasons the kernel isn't ready to serve the submissions.
+#[derive(Error, Debug)]
+pub(crate) enum ReadinessError<RpcProvider: Middleware> {
+    #[error("no signer loaded")]
+    MissingSigner,
+    #[error("storage unavailable: {0}")]
+    Storage(#[from] agglayer_storage::Error),
+    #[error("rollup manager contract {0:?} not deployed")]
+    RollupManagerNotDeployed(Address),
+    #[error("failed to resolve the contracts: {0}")]
+    ProviderError(RpcProvider::Error),
+    #[error(
+        "epoch duration of {}s set on L1 since the clock started, restart the node to follow it",
+        .0.as_secs()
+    )]
+    StaleEpochDuration(Duration),
+}
+
+impl<RpcProvider> Kernel<RpcProvider>
+where
+    RpcProvider: Middleware + 'static,
+{
+    /// Get the current L1 block number, probing the L1 nodes.
+    pub(crate) async fn probe_l1(&self) -> Result<U64, RpcProvider::Error> {
+        self.rpc.get_block_number().await
+    }
+
+    /// Sign a message with the settlement signer, returning its address.
+    pub(crate) async fn probe_signer(&self) -> Result<Address, SignerProbeError<RpcProvider>> {
+        let address = self.rpc.default_sender().ok_or(SignerProbeError::Missing)?;
+
+        self.rpc
+            .sign(PROBED_MESSAGE.to_vec(), &address)
+            .await
+            .map_err(SignerProbeError::SigningFailed)?;
+
+        Ok(address)
+    }
+
+    /// Read from the configured storage, returning whether any is configured.
+    pub(crate) fn probe_storage(&self) -> Result<bool, agglayer_storage::Error> {
+        if let Some(queue) = &self.pending_settlements {
+            queue.pending()?;
+        }
+        if let Some(index) = &self.settled_proofs {
+            index.get(H256::zero())?;
+        }
+
+        Ok(self.pending_settlements.is_some() || self.settled_proofs.is_some())
+    }
+
+    /// Check that the signer is loaded, that the configured storage is open,
+    /// that the rollup manager contract is deployed on L1, and that the clock
+    /// follows the epoch duration set on L1.
+    ///
+    /// The contracts are resolved on L1 until found once.
+    pub(crate) async fn probe_readiness(&self) -> Result<(), ReadinessError<RpcProvider>> {
+        if self.rpc.default_sender().is_none() {
+            return Err(ReadinessError::MissingSigner);
+        }
+
+        self.probe_storage()?;
+
+        if let Some(epoch_duration) = *self
+            .stale_epoch_duration
+            .read()
+            .expect("Stale epoch duration lock poisoned")
+        {
+            return Err(ReadinessError::StaleEpochDuration(epoch_duration));
+        }
+
+        if !self.contracts_resolved.load(Ordering::Relaxed) {
+            let rollup_manager = self.config.l1.rollup_manager_contract;
+            let code = self
+                .rpc
+                .get_code(rollup_manager, None)
+                .await
+                .map_err(ReadinessError::ProviderError)?;
+            if code.is_empty() {
+                return Err(ReadinessError::RollupManagerNotDeployed(rollup_manager));
+            }
+
+            self.contracts_resolved.store(true, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    /// Get the latest batch known by the ZkEVM node of the given rollup,
+    /// probing the node.
+    pub(crate) async fn probe_zkevm_node(
+        &self,
+        rollup_id: u32,
+    ) -> Result<U64, ZkevmNodeVerificationError> {
+        Ok(self
+            .get_zkevm_node_client_for_rollup(rollup_id)?
+            .batch_number()
+            .await?)
+    }
+}
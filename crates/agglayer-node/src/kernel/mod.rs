@@ -1,22 +1,64 @@
 //! The core logic of the agglayer.
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{atomic::AtomicBool, Arc, RwLock},
+    time::Duration,
+};
 
-use agglayer_config::Config;
-use ethers::prelude::*;
+use agglayer_config::{
+    Config, ConsensusType, GasBumpConfig, PendingSubmissionsConfig, ProofFormat, RateLimit,
+    RateLimitConfig, SettlementFinality,
+};
+use agglayer_contracts::{L1RpcClient, RollupContract, VerifyBatchesTrustedAggregator};
+use agglayer_storage::{AuditLog, PendingSettlementQueue, SettledBatch, SettledProofIndex};
+use agglayer_telemetry::{KeyValue, LEGACY_SIGNATURES, PROVEN_CERTIFICATES, SETTLEMENT_GAS_BUMPS};
+use breaker::L1CircuitBreaker;
+use ethers::{abi::Detokenize, prelude::*, types::transaction::eip2718::TypedTransaction};
+pub(crate) use fees::FeeEstimator;
+use finality::ReorgedSettlements;
+use nonce::NonceManager;
+use packing::EpochPacking;
+use retry::RetryPolicy;
+pub(crate) use rotation::{RotatedSigner, SignerRotation, SignerRotationError};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use stage_timeout::StageDeadline;
 use thiserror::Error;
-use tracing::instrument;
+use tokio::{sync::mpsc, time::timeout};
+use tracing::{debug, error, info, instrument, warn};
+use verify_cache::VerifiedProofs;
 
 use crate::{
-    contracts::{
-        polygon_rollup_manager::{PolygonRollupManager, RollupIDToRollupDataReturn},
-        polygon_zk_evm::PolygonZkEvm,
-    },
+    access_list::RollupAccessList,
+    admission::Admission,
+    attestation::Attestation,
+    audit::{AuditEvent, Auditor},
+    certificate::Certificate,
+    certifier::EpochProof,
+    indexer::{SettlementIndex, SettlementIndexer},
+    refresh::RefreshingHttp,
+    registry::RollupRegistry,
+    rollup_sync::{OnchainRollups, RollupSync},
+    settlement_lock::{settlement_locks, SettlementLocks},
     signed_tx::SignedTx,
-    zkevm_node_client::ZkevmNodeClient,
+    spend::SpendLedger,
+    tx_updates::{TxUpdate, TxUpdates},
+    zkevm_node_client::{ZkevmNodeClient, ZkevmNodeClients},
 };
 
+mod breaker;
+mod fees;
+mod finality;
+mod gas_bump;
+mod health;
+mod nonce;
+mod packing;
+mod retry;
+mod rotation;
+mod stage_timeout;
 #[cfg(test)]
 pub(crate) mod tests;
+mod verify_cache;
 
 /// The core logic of the agglayer.
 ///
@@ -28,9 +70,142 @@ pub(crate) mod tests;
 #[derive(Debug)]
 pub(crate) struct Kernel<RpcProvider> {
     rpc: Arc<RpcProvider>,
+    l1: L1RpcClient<RpcProvider>,
+    settlements: SettlementIndex,
+    settlement_locks: Arc<dyn SettlementLocks>,
+    rollups: RollupRegistry,
+    /// The rollups registered on L1, if synced.
+    onchain_rollups: OnchainRollups,
+    zkevm_nodes: ZkevmNodeClients,
+    spending: SpendLedger,
+    pending_settlements: Option<PendingSettlementQueue>,
+    settled_proofs: Option<SettledProofIndex>,
+    auditor: Auditor,
+    admission: Admission,
+    access_list: RollupAccessList,
+    nonces: Arc<NonceManager>,
+    /// The accounts dedicated to the settlements of some rollups, the other
+    /// rollups settling with the default account of the provider.
+    settlement_accounts: HashMap<u32, Address>,
+    /// Held by the settlements in flight, and taken over to rotate their
+    /// signer in between.
+    signer_rotation: tokio::sync::RwLock<()>,
+    gas: RwLock<Arc<GasStrategy>>,
+    tx_updates: TxUpdates,
+    packing: EpochPacking<RpcProvider>,
+    reorged: ReorgedSettlements,
+    /// Whether the contracts were found on L1 already.
+    contracts_resolved: AtomicBool,
+    /// The epoch duration set on L1 since the clock started, if it differs
+    /// from the one the clock follows.
+    stale_epoch_duration: RwLock<Option<Duration>>,
+    /// The proofs recently dry-run successfully.
+    verified_proofs: VerifiedProofs,
+    /// The retries of the calls to L1 and to the ZkEVM nodes.
+    retry: RetryPolicy,
+    l1_breaker: L1CircuitBreaker,
     config: Arc<Config>,
 }
 
+/// The gas strategy of the settlement transactions, reloaded at runtime.
+#[derive(Debug, Default)]
+struct GasStrategy {
+    fees: FeeEstimator,
+    /// The replacement of the settlement transactions stuck in the mempool.
+    gas_bump: Option<GasBumpConfig>,
+}
+
+/// The kind of an error surfaced by the kernel.
+///
+/// Exposed to the clients alongside the error messages, so that they can
+/// decide whether to retry a call without having to match on the messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ErrorKind {
+    /// The rollup is not registered.
+    InvalidRollup,
+    /// The proof is malformed.
+    InvalidProof,
+    /// The signature is malformed or not produced by the trusted sequencer.
+    InvalidSignature,
+    /// The certificate is inconsistent, or doesn't follow the last one of its
+    /// network.
+    InvalidCertificate,
+    /// The caller isn't allowed to perform the call.
+    Unauthorized,
+    /// The proof was rejected by the rollup manager contract.
+    ProofRejected,
+    /// The proof doesn't match the ZkEVM node's local record.
+    StateMismatch,
+    /// L1 couldn't be reached.
+    L1Unavailable,
+    /// The ZkEVM node couldn't be reached.
+    ZkevmNodeUnavailable,
+    /// The settlement transaction didn't make it on L1.
+    SettlementFailed,
+    /// The requested resource doesn't exist.
+    NotFound,
+    /// The client deadline was exceeded before the call completed.
+    DeadlineExceeded,
+    /// L1 or the ZkEVM node didn't answer within the timeout of a stage of the
+    /// processing.
+    TimedOut,
+    /// The agglayer is out of capacity for pending submissions.
+    Overloaded,
+    /// The agglayer is paused by its operators and doesn't accept new proofs.
+    Paused,
+    /// The rollup exceeded its rate limit.
+    RateLimited,
+    /// No leader is elected to settle the submissions.
+    LeaderUnavailable,
+    /// The proof was already settled, or is being settled.
+    AlreadySettled,
+    /// The call isn't supported by the agglayer yet.
+    Unsupported,
+    /// An unexpected error occurred in the agglayer.
+    Internal,
+}
+
+impl ErrorKind {
+    /// Whether the same call may succeed if retried later on.
+    pub(crate) fn retriable(self) -> bool {
+        match self {
+            ErrorKind::InvalidRollup
+            | ErrorKind::InvalidProof
+            | ErrorKind::InvalidSignature
+            | ErrorKind::InvalidCertificate
+            | ErrorKind::Unauthorized
+            | ErrorKind::ProofRejected
+            | ErrorKind::AlreadySettled
+            | ErrorKind::Unsupported
+            | ErrorKind::NotFound => false,
+            // The ZkEVM node may not have caught up with the submitted batch yet.
+            ErrorKind::StateMismatch
+            | ErrorKind::L1Unavailable
+            | ErrorKind::ZkevmNodeUnavailable
+            | ErrorKind::SettlementFailed
+            | ErrorKind::DeadlineExceeded
+            | ErrorKind::TimedOut
+            | ErrorKind::Overloaded
+            | ErrorKind::Paused
+            | ErrorKind::RateLimited
+            | ErrorKind::LeaderUnavailable
+            | ErrorKind::Internal => true,
+        }
+    }
+
+    /// Get the kind of a [`ContractError`].
+    pub(crate) fn of_contract_error<M: Middleware>(error: &ContractError<M>) -> Self {
+        match error {
+            ContractError::Revert(_) => ErrorKind::ProofRejected,
+            ContractError::MiddlewareError { .. } | ContractError::ProviderError { .. } => {
+                ErrorKind::L1Unavailable
+            }
+            _ => ErrorKind::Internal,
+        }
+    }
+}
+
 /// Errors related to the ZkEVM node proof verification process.
 #[derive(Error, Debug)]
 pub(crate) enum ZkevmNodeVerificationError {
@@ -39,7 +214,7 @@ pub(crate) enum ZkevmNodeVerificationError {
     InvalidRollupId(u32),
     /// Generic error when communicating with the ZkEVM node.
     #[error("rpc error: {0}")]
-    RpcError(#[from] jsonrpsee::core::client::error::Error),
+    RpcError(#[from] HttpClientError),
     /// The state root in the proof does not match the ZkEVM node's local
     /// record.
     #[error("invalid state root. expected: {expected}, got: {got}")]
@@ -47,37 +222,358 @@ pub(crate) enum ZkevmNodeVerificationError {
     /// The exit root in the proof does not match the ZkEVM node's local record.
     #[error("invalid exit root. expected: {expected}, got: {got}")]
     InvalidExitRoot { expected: H256, got: H256 },
+    /// The ZkEVM node didn't answer within the timeout.
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
 }
 
-impl<RpcProvider> Kernel<RpcProvider> {
+impl ZkevmNodeVerificationError {
+    /// Get the kind of this error.
+    pub(crate) fn kind(&self) -> ErrorKind {
+        match self {
+            ZkevmNodeVerificationError::InvalidRollupId(_) => ErrorKind::InvalidRollup,
+            ZkevmNodeVerificationError::RpcError(_) => ErrorKind::ZkevmNodeUnavailable,
+            ZkevmNodeVerificationError::InvalidStateRoot { .. }
+            | ZkevmNodeVerificationError::InvalidExitRoot { .. } => ErrorKind::StateMismatch,
+            ZkevmNodeVerificationError::Timeout(_) => ErrorKind::TimedOut,
+        }
+    }
+}
+
+impl<RpcProvider> Kernel<RpcProvider>
+where
+    RpcProvider: Middleware + 'static,
+{
     pub(crate) fn new(rpc: RpcProvider, config: Arc<Config>) -> Self {
+        let rpc = Arc::new(rpc);
+
         Self {
-            rpc: Arc::new(rpc),
+            l1: L1RpcClient::new(rpc.clone(), config.l1.rollup_manager_contract),
+            rpc,
+            settlements: SettlementIndex::default(),
+            settlement_locks: settlement_locks(&config.high_availability),
+            rollups: RollupRegistry::new(&config),
+            onchain_rollups: OnchainRollups::default(),
+            zkevm_nodes: ZkevmNodeClients::default(),
+            spending: SpendLedger::new(&config.spend_reports),
+            pending_settlements: None,
+            settled_proofs: None,
+            auditor: Auditor::default(),
+            admission: Admission::default(),
+            access_list: RollupAccessList::new(&config),
+            nonces: Arc::default(),
+            settlement_accounts: HashMap::new(),
+            signer_rotation: tokio::sync::RwLock::default(),
+            gas: RwLock::new(Arc::new(GasStrategy {
+                fees: FeeEstimator::default(),
+                gas_bump: config.outbound.rpc.settle.gas_bump.clone(),
+            })),
+            tx_updates: TxUpdates::default(),
+            packing: EpochPacking::default(),
+            reorged: ReorgedSettlements::default(),
+            contracts_resolved: AtomicBool::new(false),
+            stale_epoch_duration: RwLock::new(None),
+            verified_proofs: VerifiedProofs::new(&config.outbound.rpc.verify_proof),
+            retry: RetryPolicy::new(&config.outbound.rpc.retry),
+            l1_breaker: L1CircuitBreaker::new(&config.outbound.rpc.l1_circuit_breaker),
             config,
         }
     }
 
-    /// Check if the given rollup id is registered in the configuration.
+    /// Persist the proofs until their settlement completes, in the given
+    /// queue.
+    pub(crate) fn with_pending_settlements(mut self, queue: PendingSettlementQueue) -> Self {
+        self.pending_settlements = Some(queue);
+        self
+    }
+
+    /// Record the settlement transaction of the settled proofs in the given
+    /// index.
+    pub(crate) fn with_settled_proofs(mut self, index: SettledProofIndex) -> Self {
+        self.settled_proofs = Some(index);
+        self
+    }
+
+    /// Record the events of the submissions in the given audit log.
+    pub(crate) fn with_audit_log(mut self, log: AuditLog) -> Self {
+        self.auditor = Auditor::new(log);
+        self
+    }
+
+    /// Settle the proofs of the given rollups from the given accounts, which
+    /// the provider must sign for.
+    pub(crate) fn with_settlement_accounts(mut self, accounts: HashMap<u32, Address>) -> Self {
+        self.settlement_accounts = accounts;
+        self
+    }
+
+    /// Set the fees of the settlement transactions with the given estimator.
+    pub(crate) fn with_settlement_fees(mut self, fees: FeeEstimator) -> Self {
+        let gas = self.gas.get_mut().expect("Gas strategy lock poisoned");
+        *gas = Arc::new(GasStrategy {
+            fees,
+            gas_bump: gas.gas_bump.clone(),
+        });
+        self
+    }
+
+    /// Reload the gas strategy of the settlements and the endpoints of the
+    /// ZkEVM nodes from the given configuration.
+    ///
+    /// The settlements in flight keep the gas strategy they started with.
+    pub(crate) fn reload(&self, config: &Config) -> reqwest::Result<()> {
+        let fees = FeeEstimator::new(
+            &config.outbound.rpc.settle.fees,
+            self.config.outbound.proxy.as_ref(),
+        )?;
+        *self.gas.write().expect("Gas strategy lock poisoned") = Arc::new(GasStrategy {
+            fees,
+            gas_bump: config.outbound.rpc.settle.gas_bump.clone(),
+        });
+        self.rollups.reload_endpoints(config);
+
+        Ok(())
+    }
+
+    /// Build the [`SettlementIndexer`] feeding the settlement index of this
+    /// kernel.
+    pub(crate) fn settlement_indexer(&self) -> SettlementIndexer<RpcProvider> {
+        SettlementIndexer::new(
+            self.rpc.clone(),
+            self.config.l1.rollup_manager_contract,
+            self.settlements.clone(),
+            self.config.settlement_indexer.clone(),
+        )
+    }
+
+    /// Build the [`RollupSync`] feeding the rollups registered on L1 to this
+    /// kernel.
+    pub(crate) fn rollup_sync(&self) -> RollupSync<RpcProvider> {
+        RollupSync::new(
+            self.rpc.clone(),
+            self.config.l1.rollup_manager_contract,
+            self.onchain_rollups.clone(),
+            self.config.rollup_sync.clone(),
+        )
+    }
+
+    /// Get the verification time target of the batches, set on the rollup
+    /// manager contract.
+    pub(crate) async fn verify_batch_time_target(
+        &self,
+    ) -> Result<Duration, ContractError<RpcProvider>> {
+        Ok(Duration::from_secs(
+            self.l1.get_verify_batch_time_target().await?,
+        ))
+    }
+}
+
+impl<RpcProvider> Kernel<RpcProvider> {
+    /// Report the epoch duration set on L1 if it differs from the one the
+    /// clock follows, failing the readiness checks until it's back to it.
+    pub(crate) fn report_stale_epoch_duration(&self, epoch_duration: Option<Duration>) {
+        *self
+            .stale_epoch_duration
+            .write()
+            .expect("Stale epoch duration lock poisoned") = epoch_duration;
+    }
+}
+
+impl<RpcProvider> Kernel<RpcProvider> {
+    /// Whether the proven certificates can be settled on L1.
+    ///
+    /// The rollup manager contract doesn't verify the pessimistic proofs yet,
+    /// so the certificates are rejected rather than proven and never settled.
+    pub(crate) fn settles_certificates(&self) -> bool {
+        false
+    }
+
+    /// Settle the proofs of the certificates of the ended epochs, as they're
+    /// handed over by the certification.
+    ///
+    /// No proof is expected until the certificates can be settled, see
+    /// [`Self::settles_certificates`], the proven certificates are only
+    /// accounted for.
+    pub(crate) async fn settle_epoch_proofs(&self, mut proofs: mpsc::Receiver<EpochProof>) {
+        while let Some(proof) = proofs.recv().await {
+            PROVEN_CERTIFICATES.add(proof.certificates.len() as u64, &[]);
+            error!(
+                "Epoch {} proves {} certificates with a proof of {} bytes, which can't be settled",
+                proof.epoch,
+                proof.certificates.len(),
+                proof.proof.len()
+            );
+
+            for (network_id, new_local_exit_root) in &proof.new_local_exit_roots {
+                info!(
+                    "Epoch {} proves the new local exit root {new_local_exit_root:?} of network \
+                     {network_id}",
+                    proof.epoch
+                );
+            }
+        }
+    }
+
+    /// Get the index of the settlement events emitted on L1.
+    pub(crate) fn settlements(&self) -> &SettlementIndex {
+        &self.settlements
+    }
+
+    /// Get the hash of the settlement transaction of the given proof, if it
+    /// was settled already.
+    pub(crate) fn settled_proof(
+        &self,
+        proof_hash: H256,
+    ) -> Result<Option<H256>, agglayer_storage::Error> {
+        match &self.settled_proofs {
+            Some(index) => index.get(proof_hash),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the last batch of the given rollup settled by the agglayer, if
+    /// the settled proofs are indexed.
+    pub(crate) fn last_settled_batch(
+        &self,
+        rollup_id: u32,
+    ) -> Result<Option<SettledBatch>, agglayer_storage::Error> {
+        match &self.settled_proofs {
+            Some(index) => index.last_batch(rollup_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Check if the given rollup id is registered in the registry, and on the
+    /// rollup manager contract once synced from L1.
     pub(crate) fn check_rollup_registered(&self, rollup_id: u32) -> bool {
-        self.config.full_node_rpcs.contains_key(&rollup_id)
+        self.rollups.get(rollup_id).is_some() && self.onchain_rollups.may_be_registered(rollup_id)
+    }
+
+    /// Get the consensus of the given rollup, if registered.
+    pub(crate) fn consensus_type(&self, rollup_id: u32) -> Option<ConsensusType> {
+        self.rollups
+            .get(rollup_id)
+            .map(|rollup| rollup.consensus_type)
+    }
+
+    /// Check if the given rollup is in shadow mode, its submissions never being
+    /// settled on L1.
+    pub(crate) fn is_shadow(&self, rollup_id: u32) -> bool {
+        self.rollups
+            .get(rollup_id)
+            .is_some_and(|rollup| rollup.shadow)
+    }
+
+    /// Get the maximum number of submissions of the given rollup processed at
+    /// once, if limited.
+    pub(crate) fn max_pending_submissions(&self, rollup_id: u32) -> Option<u64> {
+        self.rollups
+            .get(rollup_id)
+            .and_then(|rollup| rollup.max_pending_submissions)
+    }
+
+    /// Get the expected shape of the proofs of the given rollup.
+    pub(crate) fn proof_format(&self, rollup_id: u32) -> ProofFormat {
+        match self.rollups.get(rollup_id) {
+            Some(rollup) => rollup.proof_format(),
+            // Proofs are checked ahead of the rollup registration.
+            None => self
+                .config
+                .proof_formats
+                .get(&rollup_id)
+                .cloned()
+                .unwrap_or_default(),
+        }
     }
 
-    /// Get a [`ZkevmNodeClient`] instance for the given rollup id.
+    /// Get the memory budget of the pending submissions.
+    pub(crate) fn pending_submissions_config(&self) -> &PendingSubmissionsConfig {
+        &self.config.rpc.pending_submissions
+    }
+
+    /// Get the rate limits of the submissions, if any.
+    pub(crate) fn rate_limit_config(&self) -> Option<&RateLimitConfig> {
+        self.config.rpc.rate_limit.as_ref()
+    }
+
+    /// Get the rate limit of the requests per IP address, if any.
+    pub(crate) fn ip_rate_limit_config(&self) -> Option<&RateLimit> {
+        self.config.rpc.ip_rate_limit.as_ref()
+    }
+
+    /// Get the API key authenticating the transactions forwarded to the leader,
+    /// if any.
+    pub(crate) fn leader_api_key(&self) -> Option<&str> {
+        self.config.high_availability.leader_api_key.as_deref()
+    }
+
+    /// Whether the requests must be signed, the transactions forwarded to the
+    /// leader being signed with the key of the agglayer then.
+    pub(crate) fn signs_requests(&self) -> bool {
+        !self.config.rpc.request_signers.is_empty()
+    }
+
+    /// Whether the proofs are settled in the background, once verified.
+    pub(crate) fn async_settlement(&self) -> bool {
+        self.config.rpc.async_settlement
+    }
+
+    /// Get the ids of the registered rollups.
+    pub(crate) fn registered_rollups(&self) -> Vec<u32> {
+        self.rollups.rollup_ids()
+    }
+
+    /// Get the registry of the rollups served by the agglayer.
+    pub(crate) fn rollups(&self) -> &RollupRegistry {
+        &self.rollups
+    }
+
+    /// Get the ledger of the L1 costs of the settlements.
+    pub(crate) fn spending(&self) -> &SpendLedger {
+        &self.spending
+    }
+
+    /// Get the admission of the new proofs.
+    pub(crate) fn admission(&self) -> &Admission {
+        &self.admission
+    }
+
+    /// Get the access lists of the rollups.
+    pub(crate) fn access_list(&self) -> &RollupAccessList {
+        &self.access_list
+    }
+
+    /// Get the auditor of the submissions.
+    pub(crate) fn auditor(&self) -> &Auditor {
+        &self.auditor
+    }
+
+    /// Get the broadcast of the status transitions of the transactions.
+    pub(crate) fn tx_updates(&self) -> &TxUpdates {
+        &self.tx_updates
+    }
+
+    /// Get the pooled [`ZkevmNodeClient`] of the given rollup id.
     #[instrument(skip(self), level = "debug")]
     fn get_zkevm_node_client_for_rollup(
         &self,
         rollup_id: u32,
-    ) -> Result<ZkevmNodeClient<jsonrpsee::http_client::HttpClient>, ZkevmNodeVerificationError>
-    {
-        let url = self
-            .config
-            .full_node_rpcs
-            .get(&rollup_id)
+    ) -> Result<Arc<ZkevmNodeClient<RefreshingHttp>>, ZkevmNodeVerificationError> {
+        let rollup = self
+            .rollups
+            .get(rollup_id)
             .ok_or(ZkevmNodeVerificationError::InvalidRollupId(rollup_id))?;
 
-        Ok(ZkevmNodeClient::new(
-            jsonrpsee::http_client::HttpClientBuilder::new().build(url.as_str())?,
-        ))
+        Ok(self
+            .zkevm_nodes
+            .get_or_connect(
+                rollup_id,
+                &rollup.full_node_rpc,
+                self.config.outbound.proxy.as_ref(),
+                &self.config.outbound.connections,
+                self.config.outbound.rpc.zkevm_node.max_concurrent_requests,
+            )
+            .map_err(HttpClientError::from)?)
     }
 
     /// Verify that the given [`SignedProof`] is valid according to the ZkEVM
@@ -92,9 +588,16 @@ impl<RpcProvider> Kernel<RpcProvider> {
         signed_tx: &SignedTx,
     ) -> Result<(), ZkevmNodeVerificationError> {
         let client = self.get_zkevm_node_client_for_rollup(signed_tx.tx.rollup_id)?;
-        let batch = client
-            .batch_by_number(signed_tx.tx.new_verified_batch.as_u64())
-            .await?;
+        let batch_number = signed_tx.tx.new_verified_batch.as_u64();
+        let batch =
+            StageDeadline::start("zkevm_node", self.config.outbound.rpc.timeouts.zkevm_node)
+                .run(self.retry.retry(
+                    "zkevm_getBatchByNumber",
+                    || client.batch_by_number(batch_number),
+                    retry::is_transient,
+                ))
+                .await
+                .map_err(ZkevmNodeVerificationError::Timeout)??;
 
         if batch.state_root != signed_tx.tx.zkp.new_state_root {
             return Err(ZkevmNodeVerificationError::InvalidStateRoot {
@@ -114,23 +617,6 @@ impl<RpcProvider> Kernel<RpcProvider> {
     }
 }
 
-impl<RpcProvider> Kernel<RpcProvider>
-where
-    RpcProvider: Middleware,
-{
-    /// Get a [`ContractInstance`] of the rollup manager contract,
-    /// [`PolygonRollupManager`].
-    ///
-    /// The returned instance facilitates type-safe RPC interaction with the
-    /// rollup manager contract.
-    ///
-    /// The rollup manager contract address is specified by the given
-    /// configuration.
-    fn get_rollup_manager_contract(&self) -> PolygonRollupManager<RpcProvider> {
-        PolygonRollupManager::new(self.config.l1.rollup_manager_contract, self.rpc.clone())
-    }
-}
-
 /// Errors related to signature verification process.
 #[derive(Error, Debug)]
 pub(crate) enum SignatureVerificationError<RpcProvider>
@@ -153,6 +639,50 @@ where
     /// address from the rollup contract.
     #[error("contract error: {0}")]
     ContractError(#[from] ContractError<RpcProvider>),
+    /// L1 didn't answer within the timeout.
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+impl<RpcProvider> SignatureVerificationError<RpcProvider>
+where
+    RpcProvider: Middleware,
+{
+    /// Get the kind of this error.
+    pub(crate) fn kind(&self) -> ErrorKind {
+        match self {
+            SignatureVerificationError::CouldNotRecoverSigner(_)
+            | SignatureVerificationError::InvalidSigner { .. } => ErrorKind::InvalidSignature,
+            SignatureVerificationError::ContractError(error) => ErrorKind::of_contract_error(error),
+            SignatureVerificationError::Timeout(_) => ErrorKind::TimedOut,
+        }
+    }
+}
+
+/// Errors related to the dry-run of the proofs on L1.
+#[derive(Error, Debug)]
+pub(crate) enum ProofVerificationError<RpcProvider>
+where
+    RpcProvider: Middleware,
+{
+    #[error("contract error: {0}")]
+    ContractError(#[from] ContractError<RpcProvider>),
+    /// L1 didn't answer within the timeout.
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+impl<RpcProvider> ProofVerificationError<RpcProvider>
+where
+    RpcProvider: Middleware,
+{
+    /// Get the kind of this error.
+    pub(crate) fn kind(&self) -> ErrorKind {
+        match self {
+            ProofVerificationError::ContractError(error) => ErrorKind::of_contract_error(error),
+            ProofVerificationError::Timeout(_) => ErrorKind::TimedOut,
+        }
+    }
 }
 
 /// Errors related to settlement process.
@@ -164,10 +694,60 @@ where
     /// The transaction receipt is missing.
     #[error("no receipt")]
     NoReceipt,
+    /// The proof was left unsettled by the epoch packing, which stopped.
+    #[error("epoch packing stopped before the proof was settled")]
+    Unpacked,
+    /// The packed settlement transaction of the epoch failed.
+    #[error("settlement of epoch {epoch} failed: {reason}")]
+    PackedSettlementFailed {
+        epoch: u64,
+        kind: ErrorKind,
+        reason: String,
+    },
+    /// The proof was already settled, or is being settled.
+    #[error("proof {0} already settled")]
+    AlreadySettled(H256),
+    #[error("settlement lock error: {0}")]
+    LockError(std::io::Error),
+    #[error("pending settlement storage error: {0}")]
+    StorageError(agglayer_storage::Error),
     #[error("provider error: {0}")]
     ProviderError(ProviderError),
     #[error("contract error: {0}")]
     ContractError(ContractError<RpcProvider>),
+    /// L1 didn't accept the settlement transaction within the timeout.
+    #[error("submission timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+impl<RpcProvider> SettlementError<RpcProvider>
+where
+    RpcProvider: Middleware,
+{
+    /// Get the kind of this error.
+    pub(crate) fn kind(&self) -> ErrorKind {
+        match self {
+            SettlementError::NoReceipt
+            | SettlementError::Unpacked
+            | SettlementError::ProviderError(_) => ErrorKind::SettlementFailed,
+            SettlementError::PackedSettlementFailed { kind, .. } => *kind,
+            SettlementError::AlreadySettled(_) => ErrorKind::AlreadySettled,
+            SettlementError::LockError(_) | SettlementError::StorageError(_) => ErrorKind::Internal,
+            SettlementError::ContractError(error) => ErrorKind::of_contract_error(error),
+            SettlementError::Timeout(_) => ErrorKind::TimedOut,
+        }
+    }
+}
+
+/// Errors related to the signatures of the agglayer key, e.g. of the
+/// acceptance attestations.
+#[derive(Error, Debug)]
+pub(crate) enum AttestationError<RpcProvider: Middleware> {
+    /// The L1 provider isn't configured with a signer.
+    #[error("no signer configured")]
+    NoSigner,
+    #[error("middleware error: {0}")]
+    ProviderError(RpcProvider::Error),
 }
 
 #[derive(Error, Debug)]
@@ -180,64 +760,60 @@ impl<RpcProvider> Kernel<RpcProvider>
 where
     RpcProvider: Middleware + 'static,
 {
-    /// Get the rollup metadata for the given rollup id.
-    ///
-    /// This involves a contract read from the rollup manager contract. In
-    /// particular, it calls `rollupIDToRollupData` (`0xf9c4c2ae`) on the rollup
-    /// manager contract and returns the result.
+    /// Get the last verified batch of the given rollup according to the rollup
+    /// manager contract.
     #[instrument(skip(self), level = "debug")]
-    async fn get_rollup_metadata(
+    pub(crate) async fn get_last_verified_batch(
         &self,
         rollup_id: u32,
-    ) -> Result<RollupIDToRollupDataReturn, ContractError<RpcProvider>> {
-        let tuple = self
-            .get_rollup_manager_contract()
-            .rollup_id_to_rollup_data(rollup_id)
-            .await?;
-
-        Ok(RollupIDToRollupDataReturn {
-            rollup_contract: tuple.0,
-            chain_id: tuple.1,
-            verifier: tuple.2,
-            fork_id: tuple.3,
-            last_local_exit_root: tuple.4,
-            last_batch_sequenced: tuple.5,
-            last_verified_batch: tuple.6,
-            last_pending_state: tuple.7,
-            last_pending_state_consolidated: tuple.8,
-            last_verified_batch_before_upgrade: tuple.9,
-            rollup_type_id: tuple.10,
-            rollup_compatibility_id: tuple.11,
-        })
+    ) -> Result<u64, ContractError<RpcProvider>> {
+        self.call_l1(
+            "getLastVerifiedBatch",
+            || self.l1.get_last_verified_batch(rollup_id),
+            retry::is_transient_contract_error,
+        )
+        .await
     }
 
-    /// Get a [`ContractInstance`], [`PolygonZkEvm`], of the rollup contract at
-    /// the given rollup id.
-    #[instrument(skip(self), level = "debug")]
-    async fn get_rollup_contract(
+    /// Get the trusted sequencer of the given rollup, from the registry, the
+    /// rollups synced from L1, or else from the rollup contract.
+    async fn trusted_sequencer(
         &self,
         rollup_id: u32,
-    ) -> Result<PolygonZkEvm<RpcProvider>, ContractError<RpcProvider>> {
-        let rollup_metadata = self.get_rollup_metadata(rollup_id).await?;
-        Ok(PolygonZkEvm::new(
-            rollup_metadata.rollup_contract,
-            self.rpc.clone(),
-        ))
+    ) -> Result<Address, ContractError<RpcProvider>> {
+        match self
+            .rollups
+            .get(rollup_id)
+            .and_then(|rollup| rollup.trusted_sequencer)
+            .or_else(|| {
+                self.onchain_rollups
+                    .get(rollup_id)
+                    .map(|rollup| rollup.trusted_sequencer)
+            }) {
+            Some(trusted_sequencer) => Ok(trusted_sequencer),
+            None => {
+                self.call_l1(
+                    "trustedSequencer",
+                    || self.l1.get_trusted_sequencer_address(rollup_id),
+                    retry::is_transient_contract_error,
+                )
+                .await
+            }
+        }
     }
 
-    /// Get the address of the trusted sequencer for the given rollup id.
-    ///
-    /// This involves a contract read from the rollup contract. In particular,
-    /// it calls `trustedSequencer` (`0xcfa8ed47`) on the rollup contract.
-    #[instrument(skip(self), level = "debug")]
-    async fn get_trusted_sequencer_address(
+    /// Get the trusted sequencer of the given rollup to verify a signature
+    /// against, within the timeout of the signature verification.
+    async fn signature_trusted_sequencer(
         &self,
         rollup_id: u32,
-    ) -> Result<Address, ContractError<RpcProvider>> {
-        self.get_rollup_contract(rollup_id)
-            .await?
-            .trusted_sequencer()
+    ) -> Result<Address, SignatureVerificationError<RpcProvider>> {
+        let timeout = self.config.outbound.rpc.timeouts.verify_signature;
+
+        Ok(StageDeadline::start("verify_signature", timeout)
+            .run(self.trusted_sequencer(rollup_id))
             .await
+            .map_err(SignatureVerificationError::Timeout)??)
     }
 
     /// Construct a call to the `verifyBatchesTrustedAggregator` (`0x1489ed10`)
@@ -251,42 +827,91 @@ where
         &self,
         signed_tx: &SignedTx,
     ) -> Result<ContractCall<RpcProvider, ()>, ContractError<RpcProvider>> {
-        let sequencer_address = self
-            .get_trusted_sequencer_address(signed_tx.tx.rollup_id)
-            .await?;
+        let sequencer_address = self.trusted_sequencer(signed_tx.tx.rollup_id).await?;
+        let proof = signed_tx
+            .tx
+            .zkp
+            .proof
+            .to_words(&self.proof_format(signed_tx.tx.rollup_id))
+            .map_err(|e| ContractError::DecodingError(abi::Error::Other(e.to_string().into())))?;
 
         // TODO: pending state num is not yet supported
         const PENDING_STATE_NUM: u64 = 0;
 
-        let call = self
-            .get_rollup_manager_contract()
-            .verify_batches_trusted_aggregator(
-                signed_tx.tx.rollup_id,
-                PENDING_STATE_NUM,
-                signed_tx.tx.last_verified_batch.as_u64(),
-                signed_tx.tx.new_verified_batch.as_u64(),
-                signed_tx.tx.zkp.new_local_exit_root.to_fixed_bytes(),
-                signed_tx.tx.zkp.new_state_root.to_fixed_bytes(),
-                sequencer_address,
-                signed_tx.tx.zkp.proof.to_fixed_bytes(),
-            );
-
-        Ok(call)
+        self.l1
+            .build_verify_batches_trusted_aggregator_call(VerifyBatchesTrustedAggregator {
+                rollup_id: signed_tx.tx.rollup_id,
+                pending_state_num: PENDING_STATE_NUM,
+                init_num_batch: signed_tx.tx.last_verified_batch.as_u64(),
+                final_new_batch: signed_tx.tx.new_verified_batch.as_u64(),
+                new_local_exit_root: signed_tx.tx.zkp.new_local_exit_root.to_fixed_bytes(),
+                new_state_root: signed_tx.tx.zkp.new_state_root.to_fixed_bytes(),
+                beneficiary: sequencer_address,
+                proof,
+            })
     }
 
     /// Verify that the signer of the given [`SignedProof`] is the trusted
-    /// sequencer for the rollup id specified in the proof.
+    /// sequencer for the rollup id specified in the proof, for the configured
+    /// L1 network.
+    ///
+    /// The signatures of the legacy hash of the proof are accepted as well
+    /// during their deprecation window, if configured.
     #[instrument(skip(self), level = "debug")]
     pub(crate) async fn verify_signature(
         &self,
         signed_tx: &SignedTx,
     ) -> Result<(), SignatureVerificationError<RpcProvider>> {
-        let sequencer_address = self
-            .get_trusted_sequencer_address(signed_tx.tx.rollup_id)
-            .await?;
+        let rollup_id = signed_tx.tx.rollup_id;
+        let sequencer_address = self.signature_trusted_sequencer(rollup_id).await?;
         let signer = signed_tx
-            .signer()
-            .map_err(|e| SignatureVerificationError::CouldNotRecoverSigner(e))?;
+            .signer(&self.config.l1)
+            .map_err(SignatureVerificationError::CouldNotRecoverSigner)?;
+
+        if signer == sequencer_address {
+            return Ok(());
+        }
+
+        if self.config.signatures.accept_legacy_hash
+            && signed_tx
+                .legacy_signer()
+                .is_ok_and(|signer| signer == sequencer_address)
+        {
+            warn!(
+                "Rollup {rollup_id} signed the legacy hash of proof {}",
+                signed_tx.hash()
+            );
+            LEGACY_SIGNATURES.add(1, &[KeyValue::new("rollup_id", rollup_id.to_string())]);
+
+            return Ok(());
+        }
+
+        Err(SignatureVerificationError::InvalidSigner {
+            signer,
+            trusted_sequencer: sequencer_address,
+        })
+    }
+
+    /// Verify that the signer of the given [`Certificate`] is the trusted
+    /// sequencer of its network.
+    #[instrument(skip(self, certificate), fields(hash = ?certificate.hash()), level = "debug")]
+    pub(crate) async fn verify_certificate_signature(
+        &self,
+        certificate: &Certificate,
+    ) -> Result<(), SignatureVerificationError<RpcProvider>> {
+        self.verify_signer(certificate.network_id, certificate.signer())
+            .await
+    }
+
+    /// Verify that the recovered signer is the trusted sequencer of the given
+    /// rollup.
+    async fn verify_signer(
+        &self,
+        rollup_id: u32,
+        signer: Result<Address, SignatureError>,
+    ) -> Result<(), SignatureVerificationError<RpcProvider>> {
+        let sequencer_address = self.signature_trusted_sequencer(rollup_id).await?;
+        let signer = signer.map_err(SignatureVerificationError::CouldNotRecoverSigner)?;
 
         if signer != sequencer_address {
             return Err(SignatureVerificationError::InvalidSigner {
@@ -308,39 +933,459 @@ where
     pub(crate) async fn verify_proof_eth_call(
         &self,
         signed_tx: &SignedTx,
-    ) -> Result<(), ContractError<RpcProvider>> {
-        let f = self
-            .build_verify_batches_trusted_aggregator_call(signed_tx)
-            .await?;
-        f.call().await?;
+    ) -> Result<(), ProofVerificationError<RpcProvider>> {
+        // Skip the dry-run of the proofs verified shortly before, e.g. for a
+        // retried submission.
+        let hash = signed_tx.hash();
+        if self.verified_proofs.contains(&hash) {
+            debug!("Proof of transaction {hash:?} verified already");
+            return Ok(());
+        }
+
+        let dry_run = async {
+            let f = self
+                .build_verify_batches_trusted_aggregator_call(signed_tx)
+                .await?;
+            self.call_l1(
+                "verifyBatchesTrustedAggregator",
+                || f.call(),
+                retry::is_transient_contract_error,
+            )
+            .await
+        };
+        StageDeadline::start(
+            "verify_proof",
+            self.config.outbound.rpc.timeouts.verify_proof,
+        )
+        .run(dry_run)
+        .await
+        .map_err(ProofVerificationError::Timeout)??;
+        self.verified_proofs.insert(hash);
 
         Ok(())
     }
 
     /// Settle the given [`SignedProof`] to the rollup manager.
+    ///
+    /// If the epoch packing is configured, the proof is settled along with the
+    /// other proofs of the current epoch once it ends.
     #[instrument(skip(self), level = "debug")]
     pub(crate) async fn settle(
         &self,
         signed_tx: &SignedTx,
     ) -> Result<TransactionReceipt, SettlementError<RpcProvider>> {
-        let f = self
-            .build_verify_batches_trusted_aggregator_call(signed_tx)
-            .await
-            .map_err(SettlementError::ContractError)?;
+        self.settle_proof(
+            signed_tx,
+            self.config.outbound.rpc.settle.epoch_packing.is_some(),
+        )
+        .await
+    }
 
-        let tx = f
-            .send()
+    /// Settle the given [`SignedProof`] with its own transaction, or else
+    /// packed with the other proofs of the current epoch.
+    async fn settle_proof(
+        &self,
+        signed_tx: &SignedTx,
+        packed: bool,
+    ) -> Result<TransactionReceipt, SettlementError<RpcProvider>> {
+        // Never send the settlement transaction of a proof twice.
+        let proof_hash = signed_tx.hash();
+        if !self
+            .settlement_locks
+            .try_lock(proof_hash)
             .await
-            .map_err(SettlementError::ContractError)?
-            .interval(self.config.outbound.rpc.settle.retry_interval)
-            .retries(self.config.outbound.rpc.settle.max_retries)
-            .confirmations(self.config.outbound.rpc.settle.confirmations)
+            .map_err(SettlementError::LockError)?
+        {
+            return Err(SettlementError::AlreadySettled(proof_hash));
+        }
+
+        // Persist the proof until its settlement completes, to settle it again
+        // if the node crashes in the meantime.
+        if let Some(queue) = &self.pending_settlements {
+            let payload = serde_json::to_vec(signed_tx).expect("SignedTx is serializable");
+            if let Err(error) = queue.push(proof_hash, &payload) {
+                self.release_settlement_lock(proof_hash).await;
+                return Err(SettlementError::StorageError(error));
+            }
+        }
+
+        let settlement = if packed {
+            self.pack_settlement(signed_tx, proof_hash).await
+        } else {
+            self.send_settlement(signed_tx, proof_hash).await
+        };
+
+        match &settlement {
+            Ok(receipt) => {
+                if let Some(index) = &self.settled_proofs {
+                    let settled = SettledBatch {
+                        batch: signed_tx.tx.new_verified_batch.as_u64(),
+                        state_root: signed_tx.tx.zkp.new_state_root,
+                        local_exit_root: signed_tx.tx.zkp.new_local_exit_root,
+                        settlement_tx_hash: receipt.transaction_hash,
+                    };
+                    if let Err(error) = index.insert(proof_hash, signed_tx.tx.rollup_id, &settled) {
+                        error!("Failed to index the settlement of proof {proof_hash}: {error}");
+                    }
+                }
+                self.auditor.record(AuditEvent::Settled {
+                    tx_hash: proof_hash,
+                    settlement_tx_hash: receipt.transaction_hash,
+                    block_number: receipt.block_number.map(|number| number.as_u64()),
+                });
+                self.tx_updates
+                    .publish(TxUpdate::mined(proof_hash, receipt));
+                self.watch_finality(signed_tx, receipt);
+            }
+            Err(error) => {
+                self.auditor.record(AuditEvent::SettlementFailed {
+                    tx_hash: proof_hash,
+                    error: error.to_string(),
+                });
+                self.tx_updates
+                    .publish(TxUpdate::failed(proof_hash, error.to_string()))
+            }
+        }
+
+        // Only once indexed, for the proof not to be settled again if the node
+        // crashes in the meantime.
+        if let Some(queue) = &self.pending_settlements {
+            if let Err(error) = queue.remove(proof_hash) {
+                error!("Failed to remove the pending settlement of proof {proof_hash}: {error}");
+            }
+        }
+
+        settlement
+    }
+
+    /// Settle the proofs whose settlement didn't complete before the node
+    /// stopped, waiting for each one of them to complete.
+    ///
+    /// The proofs already settled, be it by the transaction sent before the
+    /// node stopped, are dropped rather than settled again, and the settlement
+    /// locks left by the previous run of the node are released.
+    pub(crate) async fn settle_pending(&self) -> Result<(), agglayer_storage::Error> {
+        let Some(queue) = &self.pending_settlements else {
+            return Ok(());
+        };
+
+        for (proof_hash, payload) in queue.pending()? {
+            let signed_tx = match serde_json::from_slice::<SignedTx>(&payload) {
+                Ok(signed_tx) => signed_tx,
+                Err(error) => {
+                    error!(
+                        "Dropping the undecodable pending settlement of proof {proof_hash}: \
+                         {error}"
+                    );
+                    queue.remove(proof_hash)?;
+                    continue;
+                }
+            };
+
+            if self.settled_proof(proof_hash)?.is_some() {
+                info!("Dropping the pending settlement of the settled proof {proof_hash}");
+                queue.remove(proof_hash)?;
+                continue;
+            }
+
+            let rollup_id = signed_tx.tx.rollup_id;
+            match self.get_last_verified_batch(rollup_id).await {
+                Ok(last_verified_batch)
+                    if last_verified_batch >= signed_tx.tx.new_verified_batch.as_u64() =>
+                {
+                    info!(
+                        "Dropping the pending settlement of proof {proof_hash}, batch \
+                         {last_verified_batch} of rollup {rollup_id} being verified on L1 already"
+                    );
+                    queue.remove(proof_hash)?;
+                    continue;
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    error!(
+                        "Failed to check the settlement of the pending proof {proof_hash}, \
+                         leaving it pending: {error}"
+                    );
+                    continue;
+                }
+            }
+
+            if let Err(error) = self.settlement_locks.release_stale(proof_hash).await {
+                error!(
+                    "Failed to release the stale settlement lock of proof {proof_hash}: {error}"
+                );
+            }
+
+            // The pending proofs are settled before the clock starts, they're
+            // never packed.
+            match self.settle_proof(&signed_tx, false).await {
+                Ok(receipt) => {
+                    self.settlements.link(receipt.transaction_hash, proof_hash);
+                    info!("Settled the pending proof {proof_hash} => receipt {receipt:?}");
+                }
+                // Another node is settling the proof.
+                Err(SettlementError::AlreadySettled(_)) => {
+                    info!(
+                        "Dropping the pending settlement of proof {proof_hash}, locked by another \
+                         node"
+                    );
+                    queue.remove(proof_hash)?;
+                }
+                Err(error) => error!("Failed to settle the pending proof {proof_hash}: {error}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Send the settlement transaction of the given proof, whose settlement
+    /// lock is held, and wait for its receipt.
+    async fn send_settlement(
+        &self,
+        signed_tx: &SignedTx,
+        proof_hash: H256,
+    ) -> Result<TransactionReceipt, SettlementError<RpcProvider>> {
+        // Release the lock as long as the transaction was not sent.
+        let f = match self
+            .build_verify_batches_trusted_aggregator_call(signed_tx)
             .await
-            .map_err(SettlementError::ProviderError)?
-            // If the result is `None`, it means the transaction is no longer in the mempool.
-            .ok_or(SettlementError::NoReceipt)?;
+        {
+            Ok(f) => f,
+            Err(error) => {
+                self.release_settlement_lock(proof_hash).await;
+                return Err(SettlementError::ContractError(error));
+            }
+        };
+
+        let account = self.settlement_account(signed_tx.tx.rollup_id);
+        self.send_settlement_call(f, &[proof_hash], account).await
+    }
+
+    /// The account dedicated to the settlements of the given rollup, if any.
+    fn settlement_account(&self, rollup_id: u32) -> Option<Address> {
+        self.settlement_accounts.get(&rollup_id).copied()
+    }
+
+    /// Send the given settlement call of the given proofs, whose settlement
+    /// locks are held, from the given account or else the default one of the
+    /// provider, and wait for its receipt.
+    async fn send_settlement_call<D: Detokenize>(
+        &self,
+        mut f: ContractCall<RpcProvider, D>,
+        proof_hashes: &[H256],
+        account: Option<Address>,
+    ) -> Result<TransactionReceipt, SettlementError<RpcProvider>> {
+        // Keep the signer until the transaction is mined or dropped.
+        let _signer = self.signer_rotation.read().await;
+
+        // The submission of the transaction is bounded, the wait for its
+        // receipt isn't.
+        let submission = StageDeadline::start(
+            "settle_submission",
+            self.config.outbound.rpc.timeouts.settle_submission,
+        );
+
+        // Set the fees of the transaction, unless left to the provider.
+        let gas = self.gas.read().expect("Gas strategy lock poisoned").clone();
+        let fees = match submission.run(gas.fees.estimate(self.rpc.as_ref())).await {
+            Ok(fees) => fees,
+            Err(timeout) => {
+                self.release_settlement_locks(proof_hashes).await;
+                return Err(SettlementError::Timeout(timeout));
+            }
+        };
+        match fees {
+            Ok(Some((max_fee_per_gas, max_priority_fee_per_gas))) => {
+                if let TypedTransaction::Eip1559(tx) = &mut f.tx {
+                    tx.max_fee_per_gas = Some(max_fee_per_gas);
+                    tx.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+                }
+            }
+            Ok(None) => {}
+            Err(error) => {
+                warn!(
+                    "Failed to estimate the settlement fees, leaving them to the provider: {error}"
+                )
+            }
+        }
+
+        // Send the transaction from the settlement account and assign its
+        // nonce, unless the provider has no signer to assign it for.
+        let sender = account.or_else(|| self.rpc.default_sender());
+        if let Some(sender) = sender {
+            f.tx.set_from(sender);
+        }
+        let nonce = match sender {
+            Some(sender) => match submission
+                .run(self.nonces.assign(self.rpc.as_ref(), sender))
+                .await
+            {
+                Ok(Ok(nonce)) => Some((sender, nonce)),
+                Ok(Err(error)) => {
+                    self.release_settlement_locks(proof_hashes).await;
+                    return Err(SettlementError::ContractError(
+                        ContractError::from_middleware_error(error),
+                    ));
+                }
+                Err(timeout) => {
+                    self.release_settlement_locks(proof_hashes).await;
+                    return Err(SettlementError::Timeout(timeout));
+                }
+            },
+            None => None,
+        };
+        if let Some((_, nonce)) = nonce {
+            f.tx.set_nonce(nonce);
+        }
+
+        // Fill in the gas price ahead of the sending, to escalate it if the
+        // transaction gets stuck.
+        if gas.gas_bump.is_some() {
+            let error = match submission
+                .run(self.rpc.fill_transaction(&mut f.tx, None))
+                .await
+            {
+                Ok(Ok(())) => None,
+                Ok(Err(error)) => Some(SettlementError::ContractError(
+                    ContractError::from_middleware_error(error),
+                )),
+                Err(timeout) => Some(SettlementError::Timeout(timeout)),
+            };
+            if let Some(error) = error {
+                self.release_nonce(nonce).await;
+                self.release_settlement_locks(proof_hashes).await;
+                return Err(error);
+            }
+        }
+
+        // The transaction may have been sent nonetheless if the submission
+        // timed out, its nonce gets assigned again like for any other failed
+        // submission.
+        let hash = match submission.run(f.send()).await {
+            Ok(Ok(pending)) => *pending,
+            Ok(Err(error)) => {
+                self.release_nonce(nonce).await;
+                self.release_settlement_locks(proof_hashes).await;
+                return Err(SettlementError::ContractError(error));
+            }
+            Err(timeout) => {
+                self.release_nonce(nonce).await;
+                self.release_settlement_locks(proof_hashes).await;
+                return Err(SettlementError::Timeout(timeout));
+            }
+        };
+        for proof_hash in proof_hashes {
+            self.tx_updates
+                .publish(TxUpdate::submitted(*proof_hash, hash));
+        }
+
+        let receipt = self
+            .watch_settlement(f.tx, proof_hashes, hash, gas.gas_bump.as_ref())
+            .await;
+
+        // Unless L1 couldn't be reached, the transaction is either mined or
+        // dropped, its nonce gets assigned again if it was dropped.
+        if receipt.is_ok() {
+            self.release_nonce(nonce).await;
+        }
+
+        // If the result is `None`, it means the transaction is no longer in the
+        // mempool, the proofs can be settled again.
+        match receipt? {
+            Some(receipt) => Ok(receipt),
+            None => {
+                self.release_settlement_locks(proof_hashes).await;
+                Err(SettlementError::NoReceipt)
+            }
+        }
+    }
+
+    /// Wait for the receipt of the given settlement transaction, replacing it
+    /// with an escalated gas price whenever it is left unmined for too long,
+    /// if configured.
+    async fn watch_settlement(
+        &self,
+        mut tx: TypedTransaction,
+        proof_hashes: &[H256],
+        mut hash: H256,
+        gas_bump: Option<&GasBumpConfig>,
+    ) -> Result<Option<TransactionReceipt>, SettlementError<RpcProvider>> {
+        let settle = &self.config.outbound.rpc.settle;
+        let mut replaced = Vec::new();
+
+        let receipt = loop {
+            let pending = PendingTransaction::new(hash, self.rpc.provider())
+                .interval(settle.retry_interval)
+                .retries(settle.max_retries)
+                .confirmations(settle.confirmations);
+
+            let mut replacement = tx.clone();
+            let Some(gas_bump) =
+                gas_bump.filter(|gas_bump| gas_bump::bump(&mut replacement, gas_bump))
+            else {
+                break pending.await.map_err(SettlementError::ProviderError)?;
+            };
+
+            let Ok(receipt) = timeout(gas_bump.stuck_after, pending).await else {
+                match self.rpc.send_transaction(replacement.clone(), None).await {
+                    Ok(pending) => {
+                        warn!(
+                            "Replaced the settlement transaction {hash:?}, unmined after {:?}, \
+                             with {:?}",
+                            gas_bump.stuck_after, *pending
+                        );
+                        SETTLEMENT_GAS_BUMPS.add(1, &[]);
+                        for proof_hash in proof_hashes {
+                            self.tx_updates
+                                .publish(TxUpdate::submitted(*proof_hash, *pending));
+                        }
+                        replaced.push(hash);
+                        hash = *pending;
+                        tx = replacement;
+                    }
+                    Err(error) => {
+                        warn!("Failed to replace the settlement transaction {hash:?}: {error}")
+                    }
+                }
+                continue;
+            };
+
+            break receipt.map_err(SettlementError::ProviderError)?;
+        };
 
-        Ok(tx)
+        if receipt.is_some() {
+            return Ok(receipt);
+        }
+
+        // The last replacement may have been dropped because one of the replaced
+        // transactions got mined.
+        for hash in replaced.into_iter().rev() {
+            if let Some(receipt) = self.rpc.get_transaction_receipt(hash).await.map_err(|e| {
+                SettlementError::ContractError(ContractError::from_middleware_error(e))
+            })? {
+                return Ok(Some(receipt));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn release_nonce(&self, nonce: Option<(Address, U256)>) {
+        if let Some((sender, nonce)) = nonce {
+            self.nonces.release(sender, nonce).await;
+        }
+    }
+
+    async fn release_settlement_lock(&self, proof_hash: H256) {
+        if let Err(error) = self.settlement_locks.release(proof_hash).await {
+            error!("Failed to release the settlement lock of proof {proof_hash}: {error}");
+        }
+    }
+
+    async fn release_settlement_locks(&self, proof_hashes: &[H256]) {
+        for proof_hash in proof_hashes {
+            self.release_settlement_lock(*proof_hash).await;
+        }
     }
 }
 
@@ -348,26 +1393,107 @@ impl<RpcProvider> Kernel<RpcProvider>
 where
     RpcProvider: Middleware + 'static,
 {
+    /// Sign an [`Attestation`] that the given [`SignedTx`] was accepted in
+    /// the given Epoch, with the key of the agglayer.
+    #[instrument(skip(self), level = "debug")]
+    pub(crate) async fn attest(
+        &self,
+        signed_tx: &SignedTx,
+        epoch: u64,
+    ) -> Result<Attestation, AttestationError<RpcProvider>> {
+        let tx_hash = signed_tx.hash();
+        let (signer, signature) = self
+            .sign(Attestation::message(tx_hash, epoch).as_bytes().to_vec())
+            .await?;
+
+        Ok(Attestation {
+            tx_hash,
+            epoch,
+            signer,
+            signature,
+        })
+    }
+
+    /// Sign the given message with the key of the agglayer, returning its
+    /// address along with the signature.
+    pub(crate) async fn sign(
+        &self,
+        message: Vec<u8>,
+    ) -> Result<(Address, Signature), AttestationError<RpcProvider>> {
+        let signer = self
+            .rpc
+            .default_sender()
+            .ok_or(AttestationError::NoSigner)?;
+        let signature = self
+            .rpc
+            .sign(message, &signer)
+            .await
+            .map_err(AttestationError::ProviderError)?;
+
+        Ok((signer, signature))
+    }
+
     /// Check the status of the given hash.
     #[instrument(skip(self), level = "debug")]
     pub(crate) async fn check_tx_status(
         &self,
         hash: H256,
     ) -> Result<Option<TransactionReceipt>, CheckTxStatusError<RpcProvider>> {
-        self.rpc
-            .get_transaction_receipt(hash)
-            .await
-            .map_err(CheckTxStatusError::ProviderError)
+        self.call_l1(
+            "eth_getTransactionReceipt",
+            || self.rpc.get_transaction_receipt(hash),
+            retry::is_transient_middleware_error,
+        )
+        .await
+        .map_err(CheckTxStatusError::ProviderError)
     }
 
-    /// Get the current L1 block height.
+    /// Whether the settlements are final according to a block tag rather than
+    /// to their confirmations.
+    pub(crate) fn settlement_finality(&self) -> SettlementFinality {
+        self.config.outbound.rpc.settle.finality
+    }
+
+    /// Get the highest L1 block whose settlements are final, according to the
+    /// configured [`SettlementFinality`].
+    ///
+    /// Falls back to the confirmations if L1 doesn't support the configured
+    /// block tag.
     #[instrument(skip(self), level = "debug")]
-    pub(crate) async fn current_l1_block_height(
+    pub(crate) async fn final_l1_block_height(
         &self,
     ) -> Result<U64, CheckTxStatusError<RpcProvider>> {
-        self.rpc
-            .get_block_number()
+        final_block_height(self.rpc.as_ref(), self.settlement_finality())
             .await
             .map_err(CheckTxStatusError::ProviderError)
     }
 }
+
+/// Get the highest L1 block whose settlements are final, according to the
+/// given [`SettlementFinality`].
+async fn final_block_height<RpcProvider: Middleware>(
+    rpc: &RpcProvider,
+    finality: SettlementFinality,
+) -> Result<U64, RpcProvider::Error> {
+    let tag = match finality {
+        SettlementFinality::Confirmations => None,
+        SettlementFinality::Safe => Some(BlockNumber::Safe),
+        SettlementFinality::Finalized => Some(BlockNumber::Finalized),
+    };
+
+    if let Some(tag) = tag {
+        match rpc.get_block(tag).await {
+            Ok(Some(Block {
+                number: Some(number),
+                ..
+            })) => return Ok(number),
+            Ok(_) => warn!("L1 returned no {tag:?} block, falling back to the confirmations"),
+            Err(error) => warn!(
+                "Failed to get the {tag:?} L1 block, falling back to the confirmations: {error}"
+            ),
+        }
+    }
+
+    // A settlement is confirmed once included below the latest block.
+    Ok(rpc.get_block_number().await?.saturating_sub(U64::one()))
+}
@@ -0,0 +1,193 @@
+//! Assignment of the nonces of the settlement transactions.
+//!
+//! The settlements go out concurrently, so the nonces are assigned by the
+//! kernel rather than left to the provider, which would hand out the same
+//! pending nonce to concurrent settlements. The nonces of the settlements
+//! that never made it on L1, dropped from the mempool or reorged out, leave a
+//! gap that stalls every later settlement until it is filled, so they are
+//! assigned again first.
+//!
+//! Each settlement account gets its own nonces, assigned independently of the
+//! other accounts.
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+};
+
+use agglayer_telemetry::SETTLEMENT_NONCE_GAPS;
+use ethers::{
+    providers::Middleware,
+    types::{Address, BlockNumber, U256},
+};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+/// Assign the nonces of the settlement transactions of the agglayer.
+#[derive(Debug, Default)]
+pub(crate) struct NonceManager {
+    /// The nonces of each sender.
+    accounts: std::sync::Mutex<HashMap<Address, Arc<Mutex<NonceState>>>>,
+}
+
+#[derive(Debug, Default)]
+struct NonceState {
+    /// The lowest nonce never assigned, once initialized from L1.
+    next: Option<U256>,
+    /// The assigned nonces whose transaction may still get mined.
+    pending: BTreeSet<U256>,
+}
+
+impl NonceManager {
+    /// Assign a nonce to a new transaction of the given sender, to be
+    /// [released](Self::release) once the transaction is mined or dropped.
+    pub(crate) async fn assign<M: Middleware>(
+        &self,
+        rpc: &M,
+        sender: Address,
+    ) -> Result<U256, M::Error> {
+        // Hold the lock while reaching out to L1, to serialize the assignments
+        // of the sender.
+        let account = self.account(sender);
+        let mut state = account.lock().await;
+
+        let mined = rpc
+            .get_transaction_count(sender, Some(BlockNumber::Latest.into()))
+            .await?;
+
+        if state.next.is_none() {
+            // The transactions sent before a restart are assumed to be still
+            // pending.
+            let next = rpc
+                .get_transaction_count(sender, Some(BlockNumber::Pending.into()))
+                .await?;
+            state.seed(mined, next);
+        }
+
+        Ok(state.assign(mined))
+    }
+
+    /// Release the nonce of a transaction either mined or not to be mined, so
+    /// that it gets assigned again if it isn't mined.
+    pub(crate) async fn release(&self, sender: Address, nonce: U256) {
+        self.account(sender).lock().await.pending.remove(&nonce);
+    }
+
+    /// Forget about the nonces assigned to the given sender, to fetch them
+    /// from L1 again if it sends transactions later on.
+    pub(crate) fn reset(&self, sender: Address) {
+        self.accounts
+            .lock()
+            .expect("Nonce accounts lock poisoned")
+            .remove(&sender);
+    }
+
+    fn account(&self, sender: Address) -> Arc<Mutex<NonceState>> {
+        self.accounts
+            .lock()
+            .expect("Nonce accounts lock poisoned")
+            .entry(sender)
+            .or_default()
+            .clone()
+    }
+}
+
+impl NonceState {
+    fn seed(&mut self, mined: U256, next: U256) {
+        let mut nonce = mined;
+        while nonce < next {
+            self.pending.insert(nonce);
+            nonce += U256::one();
+        }
+        self.next = Some(next.max(mined));
+    }
+
+    /// Assign the lowest nonce neither mined nor pending.
+    fn assign(&mut self, mined: U256) -> U256 {
+        // Forget about the mined transactions, and skip over the nonces used
+        // by someone else sharing the key.
+        self.pending = self.pending.split_off(&mined);
+        let next = self.next.unwrap_or_default().max(mined);
+
+        let mut nonce = mined;
+        while nonce < next && self.pending.contains(&nonce) {
+            nonce += U256::one();
+        }
+
+        if nonce < next {
+            warn!("Nonce gap detected, assigning the nonce {nonce} again");
+            SETTLEMENT_NONCE_GAPS.add(1, &[]);
+        }
+
+        self.pending.insert(nonce);
+        self.next = Some(next.max(nonce + 1));
+
+        nonce
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gaps_are_filled_first() {
+        let mut state = NonceState::default();
+        // Two transactions sent before a restart are still pending.
+        state.seed(10.into(), 12.into());
+
+        assert_eq!(state.assign(10.into()), 12.into());
+        assert_eq!(state.assign(10.into()), 13.into());
+        assert_eq!(state.assign(10.into()), 14.into());
+
+        // The transaction with nonce 11 gets dropped.
+        state.pending.remove(&11.into());
+
+        assert_eq!(state.assign(10.into()), 11.into());
+        assert_eq!(state.assign(10.into()), 15.into());
+
+        // Everything up to 13 gets mined, then 13 gets reorged out.
+        assert_eq!(state.assign(14.into()), 16.into());
+        assert_eq!(state.assign(13.into()), 13.into());
+
+        // Someone else sharing the key sent transactions up to nonce 19.
+        assert_eq!(state.assign(20.into()), 20.into());
+        assert_eq!(state.pending, BTreeSet::from([20.into()]));
+    }
+
+    #[tokio::test]
+    async fn accounts_are_assigned_their_own_nonces() {
+        let (provider, mock) = ethers::providers::Provider::mocked();
+        let nonces = NonceManager::default();
+        let first = Address::from_low_u64_be(1);
+        let second = Address::from_low_u64_be(2);
+
+        // The responses are popped from the back: the mined nonce of the
+        // account, then its pending one on the first assignment.
+        mock.push::<U256, _>(U256::from(6)).unwrap();
+        mock.push::<U256, _>(U256::from(5)).unwrap();
+        assert_eq!(nonces.assign(&provider, first).await.unwrap(), 6.into());
+
+        mock.push::<U256, _>(U256::zero()).unwrap();
+        mock.push::<U256, _>(U256::zero()).unwrap();
+        assert_eq!(nonces.assign(&provider, second).await.unwrap(), 0.into());
+
+        mock.push::<U256, _>(U256::from(5)).unwrap();
+        assert_eq!(nonces.assign(&provider, first).await.unwrap(), 7.into());
+
+        // The transaction with nonce 7 is mined, and another one is sent.
+        nonces.release(first, 7.into()).await;
+        mock.push::<U256, _>(U256::from(8)).unwrap();
+        assert_eq!(nonces.assign(&provider, first).await.unwrap(), 8.into());
+
+        // Then it is reorged out and dropped, its nonce goes to the settlement
+        // replacing it.
+        mock.push::<U256, _>(U256::from(7)).unwrap();
+        assert_eq!(nonces.assign(&provider, first).await.unwrap(), 7.into());
+
+        // Once reset, the nonces of the account are fetched from L1 again.
+        nonces.reset(first);
+        mock.push::<U256, _>(U256::from(8)).unwrap();
+        mock.push::<U256, _>(U256::from(8)).unwrap();
+        assert_eq!(nonces.assign(&provider, first).await.unwrap(), 8.into());
+    }
+}
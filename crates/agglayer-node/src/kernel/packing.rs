@@ -0,0 +1,367 @@
+//! Settlement of the proofs received within an epoch in a single transaction.
+//!
+//! The proofs are held until the end of their epoch, until their pack is full
+//! or until the configured flush interval elapses, and settled together
+//! through the configured multicall contract, which relays their
+//! `verifyBatchesTrustedAggregator` calls to the rollup manager contract.
+use std::{fmt, sync::Mutex};
+
+use agglayer_contracts::RollupContract;
+use agglayer_telemetry::{
+    EPOCH_SETTLED_PROOFS, EPOCH_SETTLEMENTS, EPOCH_SETTLEMENT_FAILURES, EPOCH_SETTLEMENT_GAS_USED,
+};
+use ethers::prelude::*;
+use futures::{stream::FuturesUnordered, Stream, StreamExt};
+use tokio::{
+    sync::{oneshot, Notify},
+    time::{interval_at, Instant, MissedTickBehavior},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+use super::{ErrorKind, Kernel, SettlementError};
+use crate::signed_tx::SignedTx;
+
+/// The failure of the settlement of a packed proof.
+struct PackingFailure {
+    epoch: u64,
+    kind: ErrorKind,
+    reason: String,
+}
+
+/// A proof waiting for the end of its epoch to be settled.
+struct PackedProof<RpcProvider> {
+    proof_hash: H256,
+    /// The account dedicated to the settlements of the rollup of the proof.
+    account: Option<Address>,
+    call: ContractCall<RpcProvider, ()>,
+    settled: oneshot::Sender<Result<TransactionReceipt, PackingFailure>>,
+}
+
+/// The proofs waiting for the end of the current epoch to be settled.
+pub(super) struct EpochPacking<RpcProvider> {
+    proofs: Mutex<Vec<PackedProof<RpcProvider>>>,
+    /// Notified once enough proofs are waiting to fill a pack.
+    full: Notify,
+}
+
+impl<RpcProvider> EpochPacking<RpcProvider> {
+    /// Add the given proof, returning the number of proofs waiting.
+    fn push(&self, proof: PackedProof<RpcProvider>) -> usize {
+        let mut proofs = self.proofs.lock().unwrap();
+        proofs.push(proof);
+        proofs.len()
+    }
+
+    fn take(&self) -> Vec<PackedProof<RpcProvider>> {
+        std::mem::take(&mut *self.proofs.lock().unwrap())
+    }
+}
+
+impl<RpcProvider> Default for EpochPacking<RpcProvider> {
+    fn default() -> Self {
+        Self {
+            proofs: Mutex::default(),
+            full: Notify::new(),
+        }
+    }
+}
+
+impl<RpcProvider> fmt::Debug for EpochPacking<RpcProvider> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EpochPacking")
+            .field("proofs", &self.proofs.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl<RpcProvider> Kernel<RpcProvider>
+where
+    RpcProvider: Middleware + 'static,
+{
+    /// Wait for the settlement of the given proof, whose settlement lock is
+    /// held, along with the other proofs of the current epoch.
+    pub(super) async fn pack_settlement(
+        &self,
+        signed_tx: &SignedTx,
+        proof_hash: H256,
+    ) -> Result<TransactionReceipt, SettlementError<RpcProvider>> {
+        let call = match self
+            .build_verify_batches_trusted_aggregator_call(signed_tx)
+            .await
+        {
+            Ok(call) => call,
+            Err(error) => {
+                self.release_settlement_lock(proof_hash).await;
+                return Err(SettlementError::ContractError(error));
+            }
+        };
+
+        let (settled, receipt) = oneshot::channel();
+        let waiting = self.packing.push(PackedProof {
+            proof_hash,
+            account: self.settlement_account(signed_tx.tx.rollup_id),
+            call,
+            settled,
+        });
+        if let Some(packing) = &self.config.outbound.rpc.settle.epoch_packing {
+            if waiting >= packing.max_proofs.get() {
+                self.packing.full.notify_one();
+            }
+        }
+
+        match receipt.await {
+            Ok(Ok(receipt)) => Ok(receipt),
+            Ok(Err(PackingFailure {
+                epoch,
+                kind,
+                reason,
+            })) => Err(SettlementError::PackedSettlementFailed {
+                epoch,
+                kind,
+                reason,
+            }),
+            Err(_) => {
+                self.release_settlement_lock(proof_hash).await;
+                Err(SettlementError::Unpacked)
+            }
+        }
+    }
+
+    /// Settle the proofs packed during each epoch ended, or flushed within the
+    /// current epoch, as notified by the given stream, until cancelled. The
+    /// full packs, and the proofs packed at each flush interval if
+    /// configured, are settled within the current epoch, as given by
+    /// `current_epoch`.
+    ///
+    /// The settlement transactions already sent when cancelled are followed
+    /// up to their receipt, the proofs not packed yet are left unsettled.
+    pub(crate) async fn settle_packed_epochs(
+        &self,
+        mut ended_epochs: impl Stream<Item = u64> + Unpin,
+        current_epoch: impl Fn() -> u64,
+        cancellation_token: CancellationToken,
+    ) {
+        let Some(packing) = self.config.outbound.rpc.settle.epoch_packing.clone() else {
+            return;
+        };
+
+        let mut flush = packing.flush_interval.map(|period| {
+            let mut flush = interval_at(Instant::now() + period, period);
+            flush.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            flush
+        });
+
+        let mut settlements = FuturesUnordered::new();
+        loop {
+            let epoch = tokio::select! {
+                epoch = ended_epochs.next() => {
+                    let Some(epoch) = epoch else {
+                        break;
+                    };
+
+                    epoch
+                }
+                _ = self.packing.full.notified() => current_epoch(),
+                _ = async { flush.as_mut().expect("Checked by the guard").tick().await },
+                    if flush.is_some() => current_epoch(),
+                Some(()) = settlements.next(), if !settlements.is_empty() => continue,
+                _ = cancellation_token.cancelled() => break,
+            };
+
+            let mut proofs = self.packing.take();
+            if proofs.is_empty() {
+                continue;
+            }
+            info!("Settling the {} proofs of epoch {epoch}", proofs.len());
+
+            // Split the proofs into packs of the configured size, each settled
+            // from the account of its rollups.
+            proofs.sort_by_key(|proof| proof.account);
+            while !proofs.is_empty() {
+                let account = proofs[0].account;
+                let len = proofs
+                    .iter()
+                    .take(packing.max_proofs.get())
+                    .take_while(|proof| proof.account == account)
+                    .count();
+                let rest = proofs.split_off(len);
+                settlements.push(self.settle_pack(
+                    epoch,
+                    packing.multicall_contract,
+                    account,
+                    proofs,
+                ));
+                proofs = rest;
+            }
+        }
+
+        drop(self.packing.take());
+        while settlements.next().await.is_some() {}
+        debug!("Epoch packing stopped");
+    }
+
+    /// Settle the given proofs of the given epoch in a single transaction
+    /// through the multicall contract, from the given account, and hand its
+    /// outcome to each of them.
+    async fn settle_pack(
+        &self,
+        epoch: u64,
+        multicall_contract: Address,
+        account: Option<Address>,
+        proofs: Vec<PackedProof<RpcProvider>>,
+    ) {
+        let (proof_hashes, calls): (Vec<_>, Vec<_>) = proofs
+            .iter()
+            .map(|proof| (proof.proof_hash, proof.call.clone()))
+            .unzip();
+
+        let settlement = self
+            .send_settlement_call(
+                self.l1.build_multicall(multicall_contract, calls),
+                &proof_hashes,
+                account,
+            )
+            .await;
+
+        match settlement {
+            Ok(mut receipt) => {
+                let gas_used = receipt.gas_used.unwrap_or_default();
+                EPOCH_SETTLEMENTS.add(1, &[]);
+                EPOCH_SETTLED_PROOFS.record(proofs.len() as f64, &[]);
+                EPOCH_SETTLEMENT_GAS_USED.record(gas_used.low_u64() as f64, &[]);
+                info!(
+                    "Settled {} proofs of epoch {epoch} => transaction {:?}, {gas_used} gas used",
+                    proofs.len(),
+                    receipt.transaction_hash
+                );
+
+                // Each proof accounts for its share of the gas used by the
+                // transaction.
+                receipt.gas_used = Some(gas_used / proofs.len());
+                for proof in proofs {
+                    _ = proof.settled.send(Ok(receipt.clone()));
+                }
+            }
+            Err(error) => {
+                EPOCH_SETTLEMENT_FAILURES.add(1, &[]);
+                error!(
+                    "Failed to settle the {} proofs of epoch {epoch}: {error}",
+                    proofs.len()
+                );
+
+                let kind = error.kind();
+                for proof in proofs {
+                    _ = proof.settled.send(Err(PackingFailure {
+                        epoch,
+                        kind,
+                        reason: error.to_string(),
+                    }));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use agglayer_config::{Config, EpochPackingConfig};
+    use ethers::providers::Provider;
+
+    use super::*;
+    use crate::kernel::tests::signed_tx;
+
+    #[tokio::test]
+    async fn unpacked_proofs_are_released() {
+        let mut config = Config::default();
+        config
+            .full_node_rpcs
+            .insert(1, "http://localhost:8123".parse().unwrap());
+        config.outbound.rpc.settle.epoch_packing = Some(EpochPackingConfig {
+            multicall_contract: Address::random(),
+            max_proofs: 16.try_into().unwrap(),
+            flush_interval: None,
+        });
+
+        let (provider, _mock) = Provider::mocked();
+        let kernel = Kernel::new(provider, Arc::new(config));
+        let mut rollup = kernel.rollups().get(1).unwrap();
+        rollup.trusted_sequencer = Some(Address::random());
+        kernel.rollups().update(rollup).unwrap();
+
+        let signed_tx = signed_tx();
+        let cancellation_token = CancellationToken::new();
+
+        // Stop the epoch packing once the proof waits for the end of its epoch.
+        let stop = async {
+            while kernel.packing.proofs.lock().unwrap().is_empty() {
+                tokio::task::yield_now().await;
+            }
+            cancellation_token.cancel();
+        };
+
+        let (settlement, (), ()) = tokio::join!(
+            kernel.settle(&signed_tx),
+            kernel.settle_packed_epochs(
+                futures::stream::pending(),
+                || 0,
+                cancellation_token.clone()
+            ),
+            stop,
+        );
+
+        assert!(matches!(settlement, Err(SettlementError::Unpacked)));
+
+        // The proof is no longer locked, it can be settled again.
+        assert!(kernel
+            .settlement_locks
+            .try_lock(signed_tx.hash())
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn full_packs_are_settled_within_the_epoch() {
+        let mut config = Config::default();
+        config
+            .full_node_rpcs
+            .insert(1, "http://localhost:8123".parse().unwrap());
+        config.outbound.rpc.settle.epoch_packing = Some(EpochPackingConfig {
+            multicall_contract: Address::random(),
+            max_proofs: 1.try_into().unwrap(),
+            flush_interval: None,
+        });
+
+        let (provider, _mock) = Provider::mocked();
+        let kernel = Kernel::new(provider, Arc::new(config));
+        let mut rollup = kernel.rollups().get(1).unwrap();
+        rollup.trusted_sequencer = Some(Address::random());
+        kernel.rollups().update(rollup).unwrap();
+
+        let cancellation_token = CancellationToken::new();
+
+        // The pack is settled as soon as full, in the current epoch, the mocked
+        // L1 failing its settlement.
+        let settle = async {
+            let settlement = kernel.settle(&signed_tx()).await;
+            cancellation_token.cancel();
+            settlement
+        };
+
+        let (settlement, ()) = tokio::join!(
+            settle,
+            kernel.settle_packed_epochs(
+                futures::stream::pending(),
+                || 7,
+                cancellation_token.clone()
+            ),
+        );
+
+        assert!(matches!(
+            settlement,
+            Err(SettlementError::PackedSettlementFailed { epoch: 7, .. })
+        ));
+    }
+}
@@ -0,0 +1,190 @@
+//! Retries of the calls to L1 and to the ZkEVM nodes failing transiently.
+//!
+//! A node being unreachable for a moment, or rate limiting its clients, would
+//! otherwise fail the whole submission. The calls are retried with an
+//! exponential backoff, part of each delay drawn at random so that the calls
+//! failing together don't retry together. The errors answered by the nodes, a
+//! reverted call for instance, are never retried.
+use std::{fmt::Display, future::Future, time::Duration};
+
+use agglayer_config::OutboundRpcRetryConfig;
+use agglayer_telemetry::{KeyValue, OUTBOUND_CALL_FAILURES, OUTBOUND_CALL_RETRIES};
+use ethers::{
+    core::rand::{thread_rng, Rng as _},
+    prelude::{ContractError, Middleware, MiddlewareError},
+    providers::{JsonRpcError, RpcError},
+};
+use tracing::debug;
+
+/// The JSON-RPC error code of the requests exceeding the rate limit of the
+/// node.
+const LIMIT_EXCEEDED_CODE: i64 = -32005;
+
+/// The retry policy of the calls to L1 and to the ZkEVM nodes.
+#[derive(Debug, Clone)]
+pub(super) struct RetryPolicy {
+    config: OutboundRpcRetryConfig,
+}
+
+impl RetryPolicy {
+    pub(super) fn new(config: &OutboundRpcRetryConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Run the given call until it succeeds, fails for good, or runs out of
+    /// attempts.
+    ///
+    /// The retries and the failures are recorded under the given call name.
+    pub(super) async fn retry<T, E, F>(
+        &self,
+        call: &'static str,
+        mut attempt: impl FnMut() -> F,
+        transient: impl Fn(&E) -> bool,
+    ) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+        E: Display,
+    {
+        let mut retries = 0;
+
+        loop {
+            let error = match attempt().await {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+
+            let transient = transient(&error);
+            retries += 1;
+            if !transient || retries >= self.config.max_attempts {
+                OUTBOUND_CALL_FAILURES.add(
+                    1,
+                    &[
+                        KeyValue::new("call", call),
+                        KeyValue::new("transient", transient),
+                    ],
+                );
+
+                return Err(error);
+            }
+
+            let delay = self.delay(retries);
+            debug!("{call} failed transiently: {error}, retrying in {delay:?}");
+            OUTBOUND_CALL_RETRIES.add(1, &[KeyValue::new("call", call)]);
+
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// The delay before the given retry, starting from 1.
+    fn delay(&self, retry: usize) -> Duration {
+        let exponent = u32::try_from(retry.saturating_sub(1)).unwrap_or(u32::MAX);
+        let delay = self
+            .config
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(exponent))
+            .min(self.config.max_delay);
+
+        let jitter = self.config.jitter.clamp(0.0, 1.0);
+        delay.mul_f64(1.0 - jitter * thread_rng().gen::<f64>())
+    }
+}
+
+/// Whether the given JSON-RPC error answered by a node may not happen again.
+fn is_transient_response(error: &JsonRpcError) -> bool {
+    error.code == LIMIT_EXCEEDED_CODE
+}
+
+/// Whether the given error of a transport may not happen again, as it didn't
+/// get an answer from the node, or got rate limited.
+pub(super) fn is_transient<E: RpcError>(error: &E) -> bool {
+    error.as_error_response().is_none_or(is_transient_response)
+}
+
+/// Whether the given error of a middleware may not happen again.
+pub(super) fn is_transient_middleware_error<E: MiddlewareError>(error: &E) -> bool {
+    error.as_error_response().is_none_or(is_transient_response)
+}
+
+/// Whether the given contract call error may not happen again.
+pub(super) fn is_transient_contract_error<M: Middleware>(error: &ContractError<M>) -> bool {
+    match error {
+        ContractError::MiddlewareError { e } => is_transient_middleware_error(e),
+        ContractError::ProviderError { e } => is_transient_middleware_error(e),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use agglayer_config::OutboundRpcRetryConfig;
+
+    use super::RetryPolicy;
+
+    #[tokio::test(start_paused = true)]
+    async fn transient_failures_are_retried_with_backoff() {
+        let policy = RetryPolicy::new(&OutboundRpcRetryConfig {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            jitter: 0.0,
+        });
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(300));
+
+        let attempts = AtomicUsize::new(0);
+        let started_at = tokio::time::Instant::now();
+        let result = policy
+            .retry(
+                "test",
+                || async {
+                    match attempts.fetch_add(1, Ordering::Relaxed) {
+                        0 | 1 => Err("unreachable"),
+                        _ => Ok(()),
+                    }
+                },
+                |_| true,
+            )
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+        assert_eq!(started_at.elapsed(), Duration::from_millis(300));
+
+        // The permanent failures aren't retried.
+        attempts.store(0, Ordering::Relaxed);
+        let result: Result<(), _> = policy
+            .retry(
+                "test",
+                || async {
+                    attempts.fetch_add(1, Ordering::Relaxed);
+                    Err("reverted")
+                },
+                |_| false,
+            )
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+
+        // Nor are the transient failures, once out of attempts.
+        attempts.store(0, Ordering::Relaxed);
+        let result: Result<(), _> = policy
+            .retry(
+                "test",
+                || async {
+                    attempts.fetch_add(1, Ordering::Relaxed);
+                    Err("unreachable")
+                },
+                |_| true,
+            )
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 4);
+    }
+}
@@ -0,0 +1,95 @@
+//! Rotation of the signer of the settlement transactions at runtime.
+//!
+//! The new signer takes over once the settlements in flight with the current
+//! one complete, so that none of the nonces of the previous account is left
+//! pending, and the nonces of the new account are then fetched from L1.
+use agglayer_config::AuthConfig;
+use agglayer_signer::{ConfiguredSigner, EthersSigner};
+use async_trait::async_trait;
+use ethers::{providers::Middleware, signers::Signer, types::Address};
+use serde::Serialize;
+use thiserror::Error;
+use tracing::info;
+
+use super::Kernel;
+use crate::rotating_signer::RotatingSigner;
+
+/// The message signed by the new signers, to check that they sign for their
+/// address before settling with them.
+const PROBE_MESSAGE: &[u8] = b"agglayer signer rotation";
+
+/// The addresses of the settlement signer before and after its rotation.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RotatedSigner {
+    pub(crate) previous: Address,
+    pub(crate) current: Address,
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum SignerRotationError {
+    #[error("failed to load the signer: {0}")]
+    Load(#[from] agglayer_signer::Error),
+    #[error("the signer failed to sign: {0}")]
+    Sign(String),
+    #[error("the signer signs for {signed:?} rather than for its address {address:?}")]
+    WrongAddress { address: Address, signed: Address },
+}
+
+/// Rotation of the settlement signer to the one of an auth configuration.
+#[async_trait]
+pub(crate) trait SignerRotation: Send + Sync {
+    /// Load the signer of the given auth configuration, and settle with it
+    /// from now on.
+    async fn rotate(&self, auth: &AuthConfig) -> Result<RotatedSigner, SignerRotationError>;
+}
+
+impl<M, S> Kernel<RotatingSigner<M, S>>
+where
+    M: Middleware + 'static,
+    S: Signer + 'static,
+{
+    /// Settle with the given signer from now on, once it is checked to sign
+    /// for its address.
+    ///
+    /// The rotation waits for the settlements in flight to be mined or
+    /// dropped, holding off the new ones in the meantime.
+    pub(crate) async fn rotate_signer(
+        &self,
+        signer: S,
+    ) -> Result<RotatedSigner, SignerRotationError> {
+        let address = signer.address();
+        let signed = signer
+            .sign_message(PROBE_MESSAGE)
+            .await
+            .map_err(|error| SignerRotationError::Sign(error.to_string()))?
+            .recover(PROBE_MESSAGE)
+            .map_err(|error| SignerRotationError::Sign(error.to_string()))?;
+        if signed != address {
+            return Err(SignerRotationError::WrongAddress { address, signed });
+        }
+
+        let _settlements = self.signer_rotation.write().await;
+        let previous = self.rpc.rotate(signer).address();
+        self.nonces.reset(previous);
+
+        info!("Rotated the settlement signer from {previous:?} to {address:?}");
+
+        Ok(RotatedSigner {
+            previous,
+            current: address,
+        })
+    }
+}
+
+#[async_trait]
+impl<M> SignerRotation for Kernel<RotatingSigner<M, EthersSigner<ConfiguredSigner>>>
+where
+    M: Middleware + 'static,
+{
+    async fn rotate(&self, auth: &AuthConfig) -> Result<RotatedSigner, SignerRotationError> {
+        let signer = ConfiguredSigner::from_auth(self.config.l1.chain_id, auth).await?;
+
+        self.rotate_signer(EthersSigner::new(signer)).await
+    }
+}
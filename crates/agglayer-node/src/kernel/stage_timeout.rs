@@ -0,0 +1,68 @@
+//! Timeouts of the stages of the processing of the submissions.
+//!
+//! An upstream accepting the connections but never answering would otherwise
+//! hold on to the submissions, and to the connections of their clients,
+//! indefinitely. Each stage reaching out to L1 or to a ZkEVM node gives up
+//! once its configured timeout elapsed, failing with a distinct error.
+use std::{future::Future, time::Duration};
+
+use agglayer_telemetry::{KeyValue, STAGE_TIMEOUTS};
+use tokio::time::{timeout_at, Instant};
+use tracing::warn;
+
+/// The deadline of a stage, possibly spanning several calls.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct StageDeadline {
+    stage: &'static str,
+    at: Instant,
+    timeout: Duration,
+}
+
+impl StageDeadline {
+    /// Start the given stage, to complete within the given timeout.
+    pub(super) fn start(stage: &'static str, timeout: Duration) -> Self {
+        Self {
+            stage,
+            at: Instant::now() + timeout,
+            timeout,
+        }
+    }
+
+    /// Run the given future of the stage, giving up once the deadline of the
+    /// stage is reached.
+    ///
+    /// Returns the timeout of the stage if it timed out.
+    pub(super) async fn run<F: Future>(&self, future: F) -> Result<F::Output, Duration> {
+        timeout_at(self.at, future).await.map_err(|_| {
+            warn!(
+                "The {} stage timed out after {:?}",
+                self.stage, self.timeout
+            );
+            STAGE_TIMEOUTS.add(1, &[KeyValue::new("stage", self.stage)]);
+
+            self.timeout
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::StageDeadline;
+
+    #[tokio::test(start_paused = true)]
+    async fn the_calls_of_a_stage_share_its_deadline() {
+        let deadline = StageDeadline::start("test", Duration::from_secs(10));
+
+        let first = deadline
+            .run(tokio::time::sleep(Duration::from_secs(6)))
+            .await;
+        assert!(first.is_ok());
+
+        let second = deadline
+            .run(tokio::time::sleep(Duration::from_secs(6)))
+            .await;
+        assert_eq!(second, Err(Duration::from_secs(10)));
+    }
+}
@@ -1,7 +1,11 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use agglayer_config::Config;
 use agglayer_config::L1;
+use agglayer_contracts::polygon_rollup_manager::{
+    RollupIDToRollupDataCall, RollupIDToRollupDataReturn, VerifyBatchesTrustedAggregatorCall,
+};
+use agglayer_contracts::polygon_zk_evm::{TrustedSequencerCall, TrustedSequencerReturn};
 use ethers::core::utils;
 use ethers::prelude::*;
 use ethers::signers::LocalWallet;
@@ -13,12 +17,10 @@ use ethers::{
 };
 use jsonrpsee_test_utils::{helpers::ok_response, mocks::Id, TimeoutFutureExt as _};
 
-use crate::contracts::polygon_rollup_manager::{
-    RollupIDToRollupDataCall, RollupIDToRollupDataReturn, VerifyBatchesTrustedAggregatorCall,
-};
-use crate::contracts::polygon_zk_evm::{TrustedSequencerCall, TrustedSequencerReturn};
 use crate::{
-    kernel::{Kernel, ZkevmNodeVerificationError},
+    attestation::Attestation,
+    kernel::{AttestationError, ErrorKind, Kernel, ZkevmNodeVerificationError},
+    rotating_signer::RotatingSigner,
     signed_tx::{Proof, SignedTx, HASH_LENGTH, PROOF_LENGTH},
     zkevm_node_client::BatchByNumberResponse,
 };
@@ -81,6 +83,34 @@ async fn interop_executor_check_tx() {
     ));
 }
 
+#[test]
+fn zkevm_node_clients_are_pooled_per_rollup() {
+    let mut config = Config::default();
+    config
+        .full_node_rpcs
+        .insert(1, "http://zkevm-node-1:8123".parse().unwrap());
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let kernel = Kernel::new(provider, Arc::new(config));
+
+    let client = kernel.get_zkevm_node_client_for_rollup(1).unwrap();
+
+    assert!(Arc::ptr_eq(
+        &client,
+        &kernel.get_zkevm_node_client_for_rollup(1).unwrap()
+    ));
+
+    // A new endpoint gets a new client.
+    let mut rollup = kernel.rollups().get(1).unwrap();
+    rollup.full_node_rpc = "http://zkevm-node-2:8123".parse().unwrap();
+    kernel.rollups().update(rollup).unwrap();
+
+    assert!(!Arc::ptr_eq(
+        &client,
+        &kernel.get_zkevm_node_client_for_rollup(1).unwrap()
+    ));
+}
+
 /// Test the verify_zkp method
 #[tokio::test]
 async fn interop_executor_verify_zkp() {
@@ -124,7 +154,14 @@ async fn interop_executor_verify_zkp() {
             new_local_exit_root: signed_tx.tx.zkp.new_local_exit_root.to_fixed_bytes(),
             new_state_root: signed_tx.tx.zkp.new_state_root.to_fixed_bytes(),
             beneficiary: sequencer_address,
-            proof: signed_tx.tx.zkp.proof.to_fixed_bytes(),
+            proof: signed_tx
+                .tx
+                .zkp
+                .proof
+                .to_words(&Default::default())
+                .unwrap()
+                .try_into()
+                .unwrap(),
         }
     );
 
@@ -137,6 +174,115 @@ async fn interop_executor_verify_zkp() {
         .unwrap();
     mock.assert_request("eth_call", [tx_verify_batch, block])
         .unwrap();
+
+    // The retried submissions are verified without dry-running them again.
+    assert!(kernel.verify_proof_eth_call(&signed_tx).await.is_ok());
+    assert!(mock.assert_request("eth_call", ()).is_err());
+}
+
+/// Test that the attestations are signed with the agglayer key
+#[tokio::test]
+async fn reload_applies_the_fees_and_the_endpoints() {
+    use agglayer_config::{GasBumpConfig, SettlementFees};
+
+    let mut config = Config::default();
+    config
+        .full_node_rpcs
+        .insert(1, "http://zkevm-node-1:8123".parse().unwrap());
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let kernel = Kernel::new(provider, Arc::new(config));
+
+    let gas = kernel.gas.read().unwrap().clone();
+    assert_eq!(gas.fees.estimate(kernel.rpc.as_ref()).await.unwrap(), None);
+    assert!(gas.gas_bump.is_none());
+
+    let mut config = Config::default();
+    config.outbound.rpc.settle.fees = SettlementFees::Static {
+        max_fee_per_gas: 30_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+    };
+    config.outbound.rpc.settle.gas_bump = Some(GasBumpConfig {
+        stuck_after: Duration::from_secs(180),
+        percent: 20,
+        max_gas_price: 500_000_000_000,
+    });
+    config
+        .full_node_rpcs
+        .insert(1, "http://zkevm-node-2:8123".parse().unwrap());
+
+    kernel.reload(&config).unwrap();
+
+    let gas = kernel.gas.read().unwrap().clone();
+    assert_eq!(
+        gas.fees.estimate(kernel.rpc.as_ref()).await.unwrap(),
+        Some((30_000_000_000u64.into(), 1_000_000_000u64.into()))
+    );
+    assert_eq!(
+        gas.gas_bump.as_ref().map(|gas_bump| gas_bump.percent),
+        Some(20)
+    );
+    assert_eq!(
+        kernel.rollups().get(1).unwrap().full_node_rpc.as_str(),
+        "http://zkevm-node-2:8123/"
+    );
+}
+
+#[tokio::test]
+async fn attest_signs_with_the_agglayer_key() {
+    let config = Arc::new(Config::default());
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let agglayer_wallet = LocalWallet::new(&mut rand::thread_rng());
+    let kernel = Kernel::new(
+        SignerMiddleware::new(provider, agglayer_wallet.clone()),
+        config,
+    );
+
+    let signed_tx = signed_tx();
+    let attestation = kernel.attest(&signed_tx, 3).await.unwrap();
+
+    assert_eq!(attestation.tx_hash, signed_tx.hash());
+    assert_eq!(attestation.epoch, 3);
+    assert_eq!(attestation.signer, agglayer_wallet.address());
+    assert_eq!(
+        attestation
+            .signature
+            .recover(Attestation::message(signed_tx.hash(), 3).as_bytes())
+            .unwrap(),
+        agglayer_wallet.address()
+    );
+
+    // Without a signer, nothing can be attested.
+    let (provider, _mock) = providers::Provider::mocked();
+    let kernel = Kernel::new(provider, Arc::new(Config::default()));
+
+    assert!(matches!(
+        kernel.attest(&signed_tx, 3).await,
+        Err(AttestationError::NoSigner)
+    ));
+}
+
+/// Test that the settlements and the attestations are signed with the new key
+/// once the signer is rotated
+#[tokio::test]
+async fn rotated_signer_takes_over() {
+    let (provider, _mock) = providers::Provider::mocked();
+    let previous = LocalWallet::new(&mut rand::thread_rng());
+    let current = LocalWallet::new(&mut rand::thread_rng());
+    let kernel = Kernel::new(
+        RotatingSigner::new(provider, previous.clone()),
+        Arc::new(Config::default()),
+    );
+
+    let rotated = kernel.rotate_signer(current.clone()).await.unwrap();
+
+    assert_eq!(rotated.previous, previous.address());
+    assert_eq!(rotated.current, current.address());
+    assert_eq!(kernel.rpc.default_sender(), Some(current.address()));
+
+    let attestation = kernel.attest(&signed_tx(), 3).await.unwrap();
+    assert_eq!(attestation.signer, current.address());
 }
 
 /// Test that check if the verify_signature method
@@ -156,7 +302,7 @@ async fn interop_executor_verify_signature() {
 
     let response = rollup_data(&l1).encode_hex();
 
-    signed_tx.sign(&sequencer_wallet).unwrap();
+    signed_tx.sign(&sequencer_wallet, &l1).unwrap();
 
     // valid signature with valid sequencer_address
     {
@@ -207,6 +353,91 @@ async fn interop_executor_verify_signature() {
         .unwrap();
 }
 
+/// Test that the signatures of the legacy hash are only accepted during their
+/// deprecation window
+#[tokio::test]
+async fn legacy_signatures_are_accepted_if_configured() {
+    let sequencer_wallet = LocalWallet::new(&mut rand::thread_rng());
+    let mut signed_tx = signed_tx();
+    signed_tx.sign_legacy(&sequencer_wallet).unwrap();
+
+    for accept_legacy_hash in [true, false] {
+        let mut config = Config::default();
+        config
+            .full_node_rpcs
+            .insert(1, "http://localhost:8123".parse().unwrap());
+        config.signatures.accept_legacy_hash = accept_legacy_hash;
+
+        let (provider, _mock) = providers::Provider::mocked();
+        let kernel = Kernel::new(provider, Arc::new(config));
+        let mut rollup = kernel.rollups().get(1).unwrap();
+        rollup.trusted_sequencer = Some(sequencer_wallet.address());
+        kernel.rollups().update(rollup).unwrap();
+
+        assert_eq!(
+            kernel.verify_signature(&signed_tx).await.is_ok(),
+            accept_legacy_hash
+        );
+    }
+}
+
+/// Test that the signatures are bound to the configured L1 network
+#[tokio::test]
+async fn signatures_of_another_network_are_rejected() {
+    let mut config = Config::default();
+    config
+        .full_node_rpcs
+        .insert(1, "http://localhost:8123".parse().unwrap());
+
+    let sequencer_wallet = LocalWallet::new(&mut rand::thread_rng());
+    let mut other_chain = config.l1.clone();
+    other_chain.chain_id += 1;
+    let mut other_rollup_manager = config.l1.clone();
+    other_rollup_manager.rollup_manager_contract = Address::random();
+
+    let l1 = config.l1.clone();
+    let (provider, _mock) = providers::Provider::mocked();
+    let kernel = Kernel::new(provider, Arc::new(config));
+    let mut rollup = kernel.rollups().get(1).unwrap();
+    rollup.trusted_sequencer = Some(sequencer_wallet.address());
+    kernel.rollups().update(rollup).unwrap();
+
+    let mut signed_tx = signed_tx();
+    signed_tx.sign(&sequencer_wallet, &l1).unwrap();
+    assert!(kernel.verify_signature(&signed_tx).await.is_ok());
+
+    for l1 in [other_chain, other_rollup_manager] {
+        signed_tx.sign(&sequencer_wallet, &l1).unwrap();
+        assert!(matches!(
+            kernel.verify_signature(&signed_tx).await,
+            Err(crate::kernel::SignatureVerificationError::InvalidSigner { .. })
+        ));
+    }
+}
+
+#[tokio::test]
+async fn final_l1_block_height_follows_the_configured_finality() {
+    let mut config = Config::default();
+    config.outbound.rpc.settle.finality = agglayer_config::SettlementFinality::Finalized;
+
+    let (provider, mock) = providers::Provider::mocked();
+    let kernel = Kernel::new(provider, Arc::new(config));
+
+    mock.push(Block::<TxHash> {
+        number: Some(90.into()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(kernel.final_l1_block_height().await.unwrap(), 90.into());
+
+    // L1 not supporting the tag falls back to the confirmations.
+    mock.push(U64::from(100)).unwrap();
+    mock.push(serde_json::Value::Null).unwrap();
+
+    assert_eq!(kernel.final_l1_block_height().await.unwrap(), 99.into());
+}
+
 mod interop_executor_execute {
     use std::sync::Arc;
 
@@ -217,7 +448,7 @@ mod interop_executor_execute {
         let mut config = Config::default();
         let sequencer_wallet = LocalWallet::new(&mut rand::thread_rng());
         let mut signed_tx = signed_tx();
-        let _ = signed_tx.sign(&sequencer_wallet);
+        let _ = signed_tx.sign(&sequencer_wallet, &config.l1);
 
         let response = BatchByNumberResponse {
             state_root: signed_tx.tx.zkp.new_state_root,
@@ -246,7 +477,7 @@ mod interop_executor_execute {
         let mut config = Config::default();
         let sequencer_wallet = LocalWallet::new(&mut rand::thread_rng());
         let mut signed_tx = signed_tx();
-        let _ = signed_tx.sign(&sequencer_wallet);
+        let _ = signed_tx.sign(&sequencer_wallet, &config.l1);
 
         let response = ok_response(serde_json::Value::Null, Id::Num(0_u64));
 
@@ -269,12 +500,45 @@ mod interop_executor_execute {
         ));
     }
 
+    #[tokio::test]
+    async fn return_error_when_zkevm_node_hangs() {
+        let mut config = Config::default();
+        config.outbound.rpc.timeouts.zkevm_node = std::time::Duration::from_millis(100);
+        let sequencer_wallet = LocalWallet::new(&mut rand::thread_rng());
+        let mut signed_tx = signed_tx();
+        let _ = signed_tx.sign(&sequencer_wallet, &config.l1);
+
+        // Accept the connections, never answering the requests.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((connection, _)) = listener.accept().await {
+                connections.push(connection);
+            }
+        });
+
+        let uri = format!("http://{server_addr}");
+        config.full_node_rpcs.insert(1, uri.parse().unwrap());
+
+        let (provider, _mock) = providers::Provider::mocked();
+
+        let kernel = Kernel::new(provider, Arc::new(config));
+
+        let result = kernel.verify_proof_zkevm_node(&signed_tx).await;
+        assert!(matches!(
+            result,
+            Err(ZkevmNodeVerificationError::Timeout(_))
+        ));
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::TimedOut);
+    }
+
     #[tokio::test]
     async fn return_error_when_state_root_differ() {
         let mut config = Config::default();
         let sequencer_wallet = LocalWallet::new(&mut rand::thread_rng());
         let mut signed_tx = signed_tx();
-        let _ = signed_tx.sign(&sequencer_wallet);
+        let _ = signed_tx.sign(&sequencer_wallet, &config.l1);
 
         let response = BatchByNumberResponse {
             state_root: H256::zero(),
@@ -307,7 +571,7 @@ mod interop_executor_execute {
         let mut config = Config::default();
         let sequencer_wallet = LocalWallet::new(&mut rand::thread_rng());
         let mut signed_tx = signed_tx();
-        let _ = signed_tx.sign(&sequencer_wallet);
+        let _ = signed_tx.sign(&sequencer_wallet, &config.l1);
 
         let response = BatchByNumberResponse {
             state_root: signed_tx.tx.zkp.new_state_root,
@@ -0,0 +1,99 @@
+//! Cache of the proofs successfully dry-run against the rollup manager
+//! contract.
+//!
+//! The clients retry their submissions on the transient failures, e.g. of the
+//! ZkEVM node, the retried submissions of a proof verified shortly before
+//! skip its `verifyBatchesTrustedAggregator` dry-run. The failed dry-runs
+//! aren't cached, as they may succeed once L1 is reachable again.
+use std::{num::NonZeroUsize, sync::Mutex, time::Duration};
+
+use agglayer_config::OutboundRpcVerifyProofConfig;
+use ethers::types::H256;
+use lru::LruCache;
+use tokio::time::Instant;
+
+/// The proofs recently dry-run successfully, by transaction hash.
+#[derive(Debug)]
+pub(super) struct VerifiedProofs {
+    /// The time at which each proof was verified, `None` if disabled.
+    verified: Option<Mutex<LruCache<H256, Instant>>>,
+    ttl: Duration,
+}
+
+impl VerifiedProofs {
+    pub(super) fn new(config: &OutboundRpcVerifyProofConfig) -> Self {
+        Self {
+            verified: NonZeroUsize::new(config.cache_capacity)
+                .map(|capacity| Mutex::new(LruCache::new(capacity))),
+            ttl: config.cache_ttl,
+        }
+    }
+
+    /// Whether the proof of the given transaction was verified within the
+    /// TTL of the cache.
+    pub(super) fn contains(&self, hash: &H256) -> bool {
+        let Some(verified) = &self.verified else {
+            return false;
+        };
+
+        let mut verified = verified.lock().expect("Verified proofs lock poisoned");
+        match verified.get(hash) {
+            Some(at) if at.elapsed() < self.ttl => true,
+            Some(_) => {
+                verified.pop(hash);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Record the successful verification of the proof of the given
+    /// transaction.
+    pub(super) fn insert(&self, hash: H256) {
+        if let Some(verified) = &self.verified {
+            verified
+                .lock()
+                .expect("Verified proofs lock poisoned")
+                .put(hash, Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use agglayer_config::OutboundRpcVerifyProofConfig;
+    use ethers::types::H256;
+
+    use super::VerifiedProofs;
+
+    #[tokio::test(start_paused = true)]
+    async fn verified_proofs_expire_and_get_evicted() {
+        let proofs = VerifiedProofs::new(&OutboundRpcVerifyProofConfig {
+            cache_capacity: 2,
+            cache_ttl: Duration::from_secs(60),
+        });
+        let [first, second, third] = [H256::random(), H256::random(), H256::random()];
+
+        proofs.insert(first);
+        proofs.insert(second);
+        assert!(proofs.contains(&first));
+
+        // The least recently used proof is evicted.
+        proofs.insert(third);
+        assert!(proofs.contains(&first));
+        assert!(!proofs.contains(&second));
+
+        tokio::time::advance(Duration::from_secs(60)).await;
+        assert!(!proofs.contains(&first));
+        assert!(!proofs.contains(&third));
+
+        let disabled = VerifiedProofs::new(&OutboundRpcVerifyProofConfig {
+            cache_capacity: 0,
+            cache_ttl: Duration::from_secs(60),
+        });
+        disabled.insert(first);
+        assert!(!disabled.contains(&first));
+    }
+}
@@ -0,0 +1,91 @@
+use std::{ffi::OsString, fs::File, io, path::PathBuf};
+
+use async_trait::async_trait;
+
+use super::{unix_millis, ElectionBackend, ElectionError, Lease};
+
+/// A lease stored in a file on a volume shared by all the nodes.
+///
+/// The lease is only read and replaced while holding an exclusive lock on a
+/// sibling `.lock` file, so that a single node is granted a vacant lease when
+/// several campaign for it at once. The lock is released by the system if the
+/// node crashes, and the lease is replaced atomically not to be left half
+/// written.
+pub(crate) struct FileBackend {
+    path: PathBuf,
+}
+
+impl FileBackend {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Lock the lease exclusively against the other nodes, until the returned
+    /// file is dropped.
+    async fn lock(&self) -> Result<File, ElectionError> {
+        let mut path = OsString::from(self.path.clone());
+        path.push(".lock");
+
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)
+            .await?
+            .into_std()
+            .await;
+
+        tokio::task::spawn_blocking(move || file.lock().map(|()| file))
+            .await
+            .map_err(io::Error::other)?
+            .map_err(Into::into)
+    }
+
+    async fn read(&self) -> Result<Option<Lease>, ElectionError> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn write(&self, lease: &Lease) -> Result<(), ElectionError> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(format!(".{}.tmp", lease.holder));
+
+        tokio::fs::write(&tmp, serde_json::to_vec(lease)?).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ElectionBackend for FileBackend {
+    async fn campaign(&self, candidate: &Lease) -> Result<Lease, ElectionError> {
+        let _lock = self.lock().await?;
+
+        if let Some(lease) = self.read().await? {
+            if lease.holder != candidate.holder && !lease.is_expired(unix_millis()) {
+                return Ok(lease);
+            }
+        }
+
+        self.write(candidate).await?;
+
+        Ok(self.read().await?.unwrap_or_else(|| candidate.clone()))
+    }
+
+    async fn resign(&self, holder: &str) -> Result<(), ElectionError> {
+        let _lock = self.lock().await?;
+
+        match self.read().await? {
+            Some(lease) if lease.holder == holder => {
+                tokio::fs::remove_file(&self.path).await?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
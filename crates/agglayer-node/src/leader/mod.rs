@@ -0,0 +1,212 @@
+//! Leader election between the agglayer nodes of a high-availability
+//! deployment.
+//!
+//! The [`LeaderElector`] periodically campaigns for a time-bound [`Lease`]
+//! held by an [`ElectionBackend`] shared by all the nodes. Only the holder of
+//! the lease settles the submissions on L1, the other nodes keep accepting and
+//! verifying them before forwarding them to the leader.
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use agglayer_config::{HighAvailabilityConfig, LeaderElectionBackend};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+use url::Url;
+
+pub(crate) use self::file::FileBackend;
+
+mod file;
+
+#[cfg(test)]
+mod tests;
+
+/// The time-bound right to settle the submissions on L1.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Lease {
+    /// The identifier of the node holding the lease.
+    pub(crate) holder: String,
+    /// The URL of the RPC server of the holder.
+    pub(crate) url: Option<Url>,
+    /// The time at which the lease expires, in milliseconds since the Unix
+    /// epoch.
+    pub(crate) expires_at: u64,
+}
+
+impl Lease {
+    /// Whether the lease is expired at the given time, in milliseconds since
+    /// the Unix epoch.
+    pub(crate) fn is_expired(&self, now: u64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum ElectionError {
+    #[error("lease backend I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("malformed lease: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
+
+/// A backend holding the leadership lease.
+#[async_trait]
+pub(crate) trait ElectionBackend: Send + Sync {
+    /// Acquire the lease for the candidate if it is vacant, expired or already
+    /// held by the candidate, returning the lease in force afterwards.
+    async fn campaign(&self, candidate: &Lease) -> Result<Lease, ElectionError>;
+
+    /// Release the lease if it is held by the given node.
+    async fn resign(&self, holder: &str) -> Result<(), ElectionError>;
+}
+
+/// The role of the node in the deployment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Role {
+    Leader,
+    /// The node doesn't settle the submissions, the current leader does, if
+    /// any.
+    Follower {
+        leader: Option<Lease>,
+    },
+}
+
+/// A handle on the role of the node.
+#[derive(Clone, Debug)]
+pub(crate) struct Leadership(watch::Receiver<Role>);
+
+impl Leadership {
+    /// The leadership of a node not taking part in any election.
+    pub(crate) fn always_leader() -> Self {
+        Self(watch::channel(Role::Leader).1)
+    }
+
+    /// Get the current role of the node.
+    pub(crate) fn role(&self) -> Role {
+        self.0.borrow().clone()
+    }
+}
+
+/// Campaign for the leadership lease on behalf of the node.
+pub(crate) struct LeaderElector {
+    backend: Arc<dyn ElectionBackend>,
+    node_id: String,
+    url: Option<Url>,
+    lease_duration: Duration,
+    renew_interval: Duration,
+    role: watch::Sender<Role>,
+}
+
+impl LeaderElector {
+    /// Create an elector using the backend of the given configuration.
+    pub(crate) fn new(config: &HighAvailabilityConfig) -> Self {
+        let backend = match &config.backend {
+            LeaderElectionBackend::File { path } => Arc::new(FileBackend::new(path.clone())),
+        };
+
+        Self::with_backend(backend, config)
+    }
+
+    pub(crate) fn with_backend(
+        backend: Arc<dyn ElectionBackend>,
+        config: &HighAvailabilityConfig,
+    ) -> Self {
+        Self {
+            backend,
+            node_id: config.node_id.clone(),
+            url: config.advertised_url.clone(),
+            lease_duration: config.lease_duration,
+            renew_interval: config.renew_interval,
+            role: watch::channel(Role::Follower { leader: None }).0,
+        }
+    }
+
+    /// Get a handle on the role of the node.
+    pub(crate) fn leadership(&self) -> Leadership {
+        Leadership(self.role.subscribe())
+    }
+
+    /// Campaign for the lease until cancelled, releasing it on the way out.
+    pub(crate) async fn run(self, cancellation_token: CancellationToken) {
+        let mut interval = tokio::time::interval(self.renew_interval);
+        let mut held_until = None;
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => held_until = self.campaign(held_until).await,
+                _ = cancellation_token.cancelled() => break,
+            }
+        }
+
+        if held_until.is_some() {
+            debug!("Releasing the leadership lease");
+            self.role.send_replace(Role::Follower { leader: None });
+
+            if let Err(error) = self.backend.resign(&self.node_id).await {
+                error!("Failed to release the leadership lease: {error}");
+            }
+        }
+    }
+
+    /// Campaign for the lease once, returning the expiry of the lease if held.
+    async fn campaign(&self, held_until: Option<u64>) -> Option<u64> {
+        let now = unix_millis();
+        let candidate = Lease {
+            holder: self.node_id.clone(),
+            url: self.url.clone(),
+            expires_at: now + self.lease_duration.as_millis() as u64,
+        };
+
+        let (role, held_until) = match self.backend.campaign(&candidate).await {
+            Ok(lease) if lease.holder == self.node_id => (Role::Leader, Some(lease.expires_at)),
+            Ok(lease) => {
+                let leader = (!lease.is_expired(now)).then_some(lease);
+                (Role::Follower { leader }, None)
+            }
+            Err(error) => {
+                warn!("Failed to campaign for the leadership lease: {error}");
+
+                // Remain the leader for as long as the lease lasts.
+                match held_until.filter(|expires_at| *expires_at > now) {
+                    Some(expires_at) => (Role::Leader, Some(expires_at)),
+                    None => (Role::Follower { leader: None }, None),
+                }
+            }
+        };
+
+        self.role.send_if_modified(|current| {
+            if *current == role {
+                return false;
+            }
+
+            match &role {
+                Role::Leader => info!("Elected leader"),
+                Role::Follower {
+                    leader: Some(lease),
+                } => {
+                    info!("Following the leader {}", lease.holder)
+                }
+                Role::Follower { leader: None } => warn!("No leader elected"),
+            }
+
+            *current = role;
+            true
+        });
+
+        held_until
+    }
+}
+
+/// Get the current time, in milliseconds since the Unix epoch.
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
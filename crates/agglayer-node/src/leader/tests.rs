@@ -0,0 +1,118 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use agglayer_config::HighAvailabilityConfig;
+use ethers::types::H256;
+use tokio_util::sync::CancellationToken;
+
+use super::{unix_millis, ElectionBackend, FileBackend, LeaderElector, Lease, Role};
+
+fn lease_path() -> PathBuf {
+    std::env::temp_dir().join(format!("agglayer-leader-{:x}.json", H256::random()))
+}
+
+fn lease(holder: &str, expires_at: u64) -> Lease {
+    Lease {
+        holder: holder.to_string(),
+        url: Some(format!("http://{holder}:9090/").parse().unwrap()),
+        expires_at,
+    }
+}
+
+#[tokio::test]
+async fn file_backend_grants_the_lease_to_a_single_node() {
+    let backend = FileBackend::new(lease_path());
+    let expires_at = unix_millis() + 60_000;
+
+    let a = lease("a", expires_at);
+    let b = lease("b", expires_at);
+
+    assert_eq!(backend.campaign(&a).await.unwrap(), a);
+    assert_eq!(backend.campaign(&b).await.unwrap(), a);
+
+    // The holder renews the lease.
+    let renewed = lease("a", expires_at + 1);
+    assert_eq!(backend.campaign(&renewed).await.unwrap(), renewed);
+
+    // The lease is up for grabs once released.
+    backend.resign("b").await.unwrap();
+    assert_eq!(backend.campaign(&b).await.unwrap(), renewed);
+    backend.resign("a").await.unwrap();
+    assert_eq!(backend.campaign(&b).await.unwrap(), b);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn file_backend_grants_a_vacant_lease_to_a_single_node() {
+    let path = lease_path();
+    let expires_at = unix_millis() + 60_000;
+
+    // The nodes campaign for the vacant lease at once.
+    let campaigns = (0..16)
+        .map(|node| {
+            let backend = FileBackend::new(path.clone());
+            tokio::spawn(async move {
+                backend
+                    .campaign(&lease(&node.to_string(), expires_at))
+                    .await
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut leases = Vec::new();
+    for campaign in campaigns {
+        leases.push(campaign.await.unwrap().unwrap());
+    }
+    assert!(leases.iter().all(|lease| *lease == leases[0]));
+}
+
+#[tokio::test]
+async fn file_backend_grants_expired_leases() {
+    let backend = FileBackend::new(lease_path());
+
+    let expired = lease("a", unix_millis() - 1);
+    let b = lease("b", unix_millis() + 60_000);
+
+    backend.campaign(&expired).await.unwrap();
+    assert_eq!(backend.campaign(&b).await.unwrap(), b);
+}
+
+#[tokio::test]
+async fn followers_take_over_when_the_leader_steps_down() {
+    let backend: Arc<dyn ElectionBackend> = Arc::new(FileBackend::new(lease_path()));
+    let config = |node_id: &str| HighAvailabilityConfig {
+        enabled: true,
+        node_id: node_id.to_string(),
+        advertised_url: Some(format!("http://{node_id}:9090/").parse().unwrap()),
+        lease_duration: Duration::from_secs(60),
+        renew_interval: Duration::from_millis(10),
+        ..Default::default()
+    };
+
+    let a = LeaderElector::with_backend(backend.clone(), &config("a"));
+    let a_leadership = a.leadership();
+    let a_cancellation = CancellationToken::new();
+    let a_handle = tokio::spawn(a.run(a_cancellation.clone()));
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(a_leadership.role(), Role::Leader);
+
+    let b = LeaderElector::with_backend(backend.clone(), &config("b"));
+    let b_leadership = b.leadership();
+    let b_cancellation = CancellationToken::new();
+    let b_handle = tokio::spawn(b.run(b_cancellation.clone()));
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    match b_leadership.role() {
+        Role::Follower {
+            leader: Some(leader),
+        } => assert_eq!(leader.holder, "a"),
+        role => panic!("unexpected role {role:?}"),
+    }
+
+    a_cancellation.cancel();
+    a_handle.await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(b_leadership.role(), Role::Leader);
+
+    b_cancellation.cancel();
+    b_handle.await.unwrap();
+}
@@ -1,21 +1,47 @@
-use std::{future::IntoFuture, path::PathBuf, sync::Arc};
+use std::{
+    future::IntoFuture,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use agglayer_config::Config;
 use anyhow::Result;
 use node::Node;
+use tokio::signal::unix::{signal, SignalKind};
 use tokio_util::sync::CancellationToken;
-use tracing::info;
+use tracing::{error, info};
 
-mod contracts;
+mod access_list;
+mod admission;
+mod attestation;
+mod audit;
+mod certificate;
+mod certifier;
+pub mod codegen;
+pub mod doctor;
+mod failover;
+mod indexer;
 mod kernel;
+mod leader;
 mod logging;
+mod proxy;
+mod reconnect;
+mod refresh;
+mod registry;
+mod rollup_sync;
+mod rotating_signer;
 mod rpc;
+mod settlement_lock;
 mod signed_tx;
+mod spend;
+mod storage;
+mod tx_updates;
 mod zkevm_node_client;
 
 mod node;
 
 use agglayer_telemetry::ServerBuilder as MetricsBuilder;
+use logging::LogFilters;
 
 /// This is the main node entrypoint.
 ///
@@ -23,16 +49,21 @@ use agglayer_telemetry::ServerBuilder as MetricsBuilder;
 /// Starting by a Tokio runtime which can be used by the different components.
 /// The configuration file is parsed and used to configure the node.
 ///
+/// The configuration file is reloaded on SIGHUP, see [`reload`].
+///
 /// This function returns on fatal error or after graceful shutdown has
 /// completed.
 pub fn main(cfg: PathBuf) -> Result<()> {
     // Load the configuration file
-    let config: Arc<Config> = Arc::new(toml::from_str(&std::fs::read_to_string(cfg)?)?);
+    let config: Arc<Config> = Arc::new(Config::load(&cfg)?);
 
     let global_cancellation_token = CancellationToken::new();
 
-    // Initialize the logger
-    logging::tracing(&config.log);
+    // Proxy the outbound connections not configured explicitly, before any
+    // runtime thread gets spawned.
+    if let Some(proxy) = &config.outbound.proxy {
+        proxy::export_to_env(proxy);
+    }
 
     let node_runtime = tokio::runtime::Builder::new_multi_thread()
         .thread_name("agglayer-node-runtime")
@@ -45,6 +76,12 @@ pub fn main(cfg: PathBuf) -> Result<()> {
         .enable_all()
         .build()?;
 
+    // Initialize the logger, within the node runtime to export the traces.
+    let (log_filters, tracer_provider) = {
+        let _guard = node_runtime.enter();
+        logging::tracing(&config.log, config.telemetry.traces.as_ref())?
+    };
+
     // Create the metrics server.
     let metric_server = metrics_runtime.block_on(
         MetricsBuilder::builder()
@@ -76,21 +113,59 @@ pub fn main(cfg: PathBuf) -> Result<()> {
         .enable_all()
         .build()?
         .block_on(async {
-            tokio::select! {
-                _ = tokio::signal::ctrl_c() => {
-                    info!("Received SIGINT (ctrl-c), shutting down...");
-                    // Cancel the global cancellation token to start the shutdown process.
-                    global_cancellation_token.cancel();
-                    // Wait for the node to shutdown.
-                    node.await_shutdown().await;
-                    // Wait for the metrics server to shutdown.
-                    _ = metrics_handle.await;
+            let mut hangups = signal(SignalKind::hangup())?;
+
+            loop {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {
+                        info!("Received SIGINT (ctrl-c), shutting down...");
+                        // Cancel the global cancellation token to start the shutdown process.
+                        global_cancellation_token.cancel();
+                        // Wait for the node to shutdown.
+                        node.await_shutdown().await;
+                        // Wait for the metrics server to shutdown.
+                        _ = metrics_handle.await;
+
+                        return anyhow::Ok(());
+                    }
+                    _ = hangups.recv() => {
+                        info!("Received SIGHUP, reloading the configuration...");
+                        match reload(&cfg, &node, &log_filters) {
+                            Ok(()) => info!("Reloaded the configuration"),
+                            Err(error) => error!(
+                                "Failed to reload the configuration, keeping the current one: \
+                                 {error:#}"
+                            ),
+                        }
+                    }
                 }
             }
-        });
+        })?;
+
+    // Flush the pending traces.
+    if let Some(tracer_provider) = tracer_provider {
+        for result in tracer_provider.force_flush() {
+            if let Err(error) = result {
+                error!("Failed to export the pending traces: {error}");
+            }
+        }
+    }
 
     node_runtime.shutdown_timeout(config.shutdown.runtime_timeout);
     metrics_runtime.shutdown_timeout(config.shutdown.runtime_timeout);
 
     Ok(())
 }
+
+/// Reload the sections of the configuration file that can change at runtime:
+/// the log levels, the rate limits, the gas strategy of the settlements and
+/// the endpoints of the ZkEVM nodes.
+///
+/// The changes to the other sections only apply once the node restarts.
+fn reload(cfg: &Path, node: &Node, log_filters: &LogFilters) -> Result<()> {
+    let config = node.reload(cfg)?;
+
+    log_filters.reload(&config.log)?;
+
+    Ok(())
+}
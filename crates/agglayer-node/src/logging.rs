@@ -1,23 +1,112 @@
-use agglayer_config::log::LogFormat;
-use tracing_subscriber::{prelude::*, util::SubscriberInitExt, EnvFilter};
-
-pub(crate) fn tracing(config: &agglayer_config::Log) {
-    // TODO: Support multiple outputs.
-    let writer = config.outputs.first().cloned().unwrap_or_default();
-
-    let layer = match config.format {
-        LogFormat::Pretty => tracing_subscriber::fmt::layer()
-            .pretty()
-            .with_writer(writer.as_make_writer())
-            .with_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| config.level.into()))
-            .boxed(),
-
-        LogFormat::Json => tracing_subscriber::fmt::layer()
-            .json()
-            .with_writer(writer.as_make_writer())
-            .with_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| config.level.into()))
-            .boxed(),
+use agglayer_config::{log::LogFormat, TracesConfig};
+use opentelemetry::{trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::WithExportConfig as _;
+use opentelemetry_sdk::{
+    runtime,
+    trace::{Config, Sampler, TracerProvider},
+    Resource,
+};
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tracing_subscriber::{prelude::*, reload, util::SubscriberInitExt, EnvFilter, Registry};
+
+/// The filters of the log outputs, reloaded at runtime.
+///
+/// The filter of the exported traces is kept as initialized.
+#[derive(Clone, Debug)]
+pub(crate) struct LogFilters(Vec<reload::Handle<EnvFilter, Registry>>);
+
+impl LogFilters {
+    /// Replace the filters of the log outputs with the levels of the given
+    /// configuration.
+    pub(crate) fn reload(&self, config: &agglayer_config::Log) -> Result<(), reload::Error> {
+        for handle in &self.0 {
+            handle.reload(config.env_filter())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Initialize the logger, exporting the traces if configured.
+///
+/// Must be called within a Tokio runtime if the traces are exported, the
+/// returned provider being shut down to flush the pending traces.
+pub(crate) fn tracing(
+    config: &agglayer_config::Log,
+    traces: Option<&TracesConfig>,
+) -> anyhow::Result<(LogFilters, Option<TracerProvider>)> {
+    let outputs = if config.outputs.is_empty() {
+        vec![Default::default()]
+    } else {
+        config.outputs.clone()
     };
 
-    tracing_subscriber::Registry::default().with(layer).init();
+    let mut filters = Vec::new();
+    let layers = outputs
+        .iter()
+        .map(|output| {
+            let writer = output.as_make_writer(&config.rotation)?;
+            let (filter, handle) = reload::Layer::new(config.env_filter());
+            filters.push(handle);
+
+            Ok(match config.format {
+                LogFormat::Pretty => tracing_subscriber::fmt::layer()
+                    .pretty()
+                    .with_writer(writer)
+                    .with_filter(filter)
+                    .boxed(),
+
+                LogFormat::Json => tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_writer(writer)
+                    .with_filter(filter)
+                    .boxed(),
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let provider = traces.map(tracer_provider).transpose()?;
+    let traces_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("agglayer"))
+            .with_filter(config.env_filter())
+    });
+
+    tracing_subscriber::Registry::default()
+        .with(layers)
+        .with(traces_layer)
+        .init();
+
+    Ok((LogFilters(filters), provider))
+}
+
+/// Build the provider of the tracer exporting the sampled traces to the
+/// configured collector, in batches.
+fn tracer_provider(config: &TracesConfig) -> anyhow::Result<TracerProvider> {
+    let mut metadata = MetadataMap::new();
+    for (name, value) in &config.headers {
+        metadata.insert(
+            MetadataKey::from_bytes(name.as_bytes())?,
+            MetadataValue::try_from(value.as_str())?,
+        );
+    }
+
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(config.endpoint.as_str())
+        .with_metadata(metadata);
+
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+        config.sample_ratio.clamp(0.0, 1.0),
+    )));
+
+    Ok(opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(
+            Config::default()
+                .with_sampler(sampler)
+                .with_resource(Resource::new([KeyValue::new("service.name", "agglayer")])),
+        )
+        .install_batch(runtime::Tokio)?)
 }
@@ -1,27 +1,61 @@
-use std::{num::NonZeroU64, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
 
 use agglayer_certificate_orchestrator::CertificateOrchestrator;
-use agglayer_clock::{Clock, TimeClock};
-use agglayer_config::{Config, Epoch};
-use agglayer_signer::ConfiguredSigner;
+use agglayer_clock::Clock;
+use agglayer_config::{Config, SettlementLockBackend, ValidationError};
+use agglayer_prover::Prover;
+use agglayer_signer::{ConfiguredSigner, EthersSigner};
+use agglayer_storage::{AuditLog, ClockState, PendingSettlementQueue, SettledProofIndex};
 use anyhow::Result;
-use ethers::{
-    middleware::MiddlewareBuilder as _,
-    providers::{Http, Provider},
-};
+use ethers::{providers::Provider, signers::Signer as _};
 use tokio::{join, sync::mpsc, task::JoinHandle};
 use tokio_stream::StreamExt;
 use tokio_util::sync::CancellationToken;
-use tracing::debug;
+use tracing::{debug, info};
 
-use self::notifier::AggregatorNotifier;
-use crate::{kernel::Kernel, rpc::AgglayerImpl};
+use self::{clock::ConfiguredClock, notifier::AggregatorNotifier};
+use crate::{
+    certifier::{Certification, Sp1Certifier},
+    failover::FailoverTransport,
+    kernel::{FeeEstimator, Kernel},
+    leader::{LeaderElector, Leadership},
+    refresh::RefreshingHttp,
+    rotating_signer::RotatingSigner,
+    rpc::{AdminImpl, AgglayerImpl, GrpcImpl},
+};
 
+mod clock;
 mod notifier;
 
+/// The number of ended epochs waiting to be proven, beyond which the
+/// certificates of the next epochs are dropped.
+const MAX_EPOCHS_TO_PROVE: usize = 16;
+
+/// The interval at which the epoch duration read from the rollup manager
+/// contract is checked for changes.
+const EPOCH_DURATION_WATCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The L1 provider of the node, failing over the configured L1 nodes and
+/// signing the settlement transactions with a signer rotated at runtime.
+type L1Provider =
+    RotatingSigner<Provider<FailoverTransport<RefreshingHttp>>, EthersSigner<ConfiguredSigner>>;
+
 pub(crate) struct Node {
+    agglayer: AgglayerImpl<L1Provider>,
     rpc_handle: JoinHandle<()>,
+    grpc_handle: Option<JoinHandle<()>>,
     certificate_orchestrator_handle: JoinHandle<()>,
+    certification_handle: JoinHandle<()>,
+    epoch_settlement_handle: JoinHandle<()>,
+    epoch_packing_handle: Option<JoinHandle<()>>,
+    reorg_handle: JoinHandle<()>,
+    l1_recovery_handle: JoinHandle<()>,
+    settlement_indexer_handle: Option<JoinHandle<()>>,
+    rollup_sync_handle: Option<JoinHandle<()>>,
+    leader_elector_handle: Option<JoinHandle<()>>,
+    clock_state_handle: Option<JoinHandle<()>>,
+    epoch_duration_watch_handle: Option<JoinHandle<()>>,
+    admin_handle: Option<JoinHandle<()>>,
 }
 
 #[buildstructor::buildstructor]
@@ -58,40 +92,182 @@ impl Node {
     /// # Errors
     ///
     /// This function will return an error if:
+    /// - The high availability is enabled with settlement locks held in memory.
     /// - The L1 node URL is invalid.
     /// - The configured signer is invalid.
-    /// - The RPC server failed to start.
-    /// - The [`TimeClock`] failed to start.
+    /// - The rollup registry file is unreadable.
+    /// - The access list file is unreadable.
+    /// - The RPC server, the gRPC server or the admin RPC server failed to
+    ///   start.
+    /// - The epoch duration is to be read from the rollup manager contract,
+    ///   which is unreachable.
+    /// - The configured Clock failed to start, or started on an epoch earlier
+    ///   than the last one it reached.
+    /// - The configured prover failed to start.
     #[builder(entry = "builder", exit = "start", visibility = "pub(crate)")]
     pub(crate) async fn start(
         config: Arc<Config>,
         cancellation_token: CancellationToken,
     ) -> Result<Self> {
-        // Create a new L1 RPC provider with the configured signer.
-        let rpc = Provider::<Http>::try_from(config.l1.node_url.as_str())?
-            .with_signer(ConfiguredSigner::new(config.clone()).await?);
+        // The locks held in memory would let the other nodes settle the same
+        // proofs during a failover.
+        if config.high_availability.enabled
+            && config.high_availability.settlement_locks == SettlementLockBackend::Memory
+        {
+            return Err(ValidationError::SettlementLocksNotShared.into());
+        }
+
+        // Create a new L1 RPC provider with the configured signer, failing over
+        // the configured L1 nodes.
+        let transports = config
+            .l1
+            .node_urls()
+            .map(|url| {
+                let transport = RefreshingHttp::new(
+                    url.clone(),
+                    config.outbound.proxy.clone(),
+                    config.outbound.connections.clone(),
+                )?;
+
+                Ok((url.clone(), transport))
+            })
+            .collect::<reqwest::Result<Vec<_>>>()?;
+        let mut rpc = RotatingSigner::new(
+            Provider::new(FailoverTransport::new(transports)),
+            EthersSigner::new(ConfiguredSigner::new(config.clone()).await?),
+        );
+
+        // Settle the proofs of the rollups with a dedicated signer from its
+        // own account.
+        let mut settlement_accounts = HashMap::new();
+        for settlement_signer in &config.settlement_signers {
+            let signer = EthersSigner::new(
+                ConfiguredSigner::from_auth(config.l1.chain_id, &settlement_signer.auth).await?,
+            );
+            let address = signer.address();
+            info!(
+                "Settling the proofs of rollups {:?} from {address:?}",
+                settlement_signer.rollups
+            );
+
+            settlement_accounts.extend(
+                settlement_signer
+                    .rollups
+                    .iter()
+                    .map(|rollup_id| (*rollup_id, address)),
+            );
+            rpc = rpc.with_signer(signer);
+        }
 
         // Construct the core.
-        let core = Kernel::new(rpc, config.clone());
-
-        // Spawn the TimeClock.
-        let clock_ref = match &config.epoch {
-            Epoch::TimeClock(cfg) => {
-                let duration =
-                    NonZeroU64::new(cfg.epoch_duration.as_secs()).ok_or(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        "EpochDuration is invalid",
-                    ))?;
-                let clock = TimeClock::new_now(duration);
-
-                clock.spawn(cancellation_token.clone()).await?
-            }
+        let mut core =
+            Kernel::new(rpc, config.clone()).with_settlement_accounts(settlement_accounts);
+
+        // Set the fees of the settlement transactions as configured.
+        core = core.with_settlement_fees(FeeEstimator::new(
+            &config.outbound.rpc.settle.fees,
+            config.outbound.proxy.as_ref(),
+        )?);
+
+        // Index the settled proofs, so that they're never settled again.
+        if let Some(path) = &config.storage.settled_proofs_path {
+            core = core.with_settled_proofs(SettledProofIndex::open(path)?);
+        }
+
+        // Record the submissions in the audit log.
+        if let Some(path) = &config.storage.audit_log_path {
+            core = core.with_audit_log(AuditLog::open(path)?);
+        }
+
+        // Settle the proofs whose settlement didn't complete before a crash.
+        if let Some(path) = &config.storage.pending_settlements_path {
+            core = core.with_pending_settlements(PendingSettlementQueue::open(path)?);
+            core.settle_pending().await?;
+        }
+
+        // Restore the rollups registered at runtime.
+        core.rollups().restore()?;
+
+        // Restore the rollups allowed and denied at runtime.
+        core.access_list().restore()?;
+
+        // Restore the L1 costs of the past settlements.
+        core.spending().restore()?;
+
+        // Index the settlement events emitted on L1.
+        let settlement_indexer_handle = config
+            .settlement_indexer
+            .enabled
+            .then(|| tokio::spawn(core.settlement_indexer().run(cancellation_token.clone())));
+
+        // Sync the rollups registered on L1.
+        let rollup_sync_handle = config
+            .rollup_sync
+            .enabled
+            .then(|| tokio::spawn(core.rollup_sync().run(cancellation_token.clone())));
+
+        // Campaign for the leadership if running alongside other nodes.
+        let (leadership, leader_elector_handle) = if config.high_availability.enabled {
+            let elector = LeaderElector::new(&config.high_availability);
+            let leadership = elector.leadership();
+
+            (
+                leadership,
+                Some(tokio::spawn(elector.run(cancellation_token.clone()))),
+            )
+        } else {
+            (Leadership::always_leader(), None)
         };
 
-        let aggregator_task = AggregatorNotifier::new();
-        let clock_subscription =
-            tokio_stream::wrappers::BroadcastStream::new(clock_ref.subscribe()?)
-                .filter_map(|value| value.ok());
+        // Read the epoch duration from the rollup manager contract, if configured
+        // so.
+        let verify_batch_time_target = if clock::from_rollup_manager(&config.epoch) {
+            Some(core.verify_batch_time_target().await?)
+        } else {
+            None
+        };
+
+        let clock_state = config
+            .storage
+            .clock_state_path
+            .as_ref()
+            .map(ClockState::open)
+            .transpose()?;
+
+        // Spawn the configured Clock.
+        let clock_ref = Arc::new(
+            ConfiguredClock::new(
+                &config.epoch,
+                config.outbound.connections.ws_max_reconnects,
+                verify_batch_time_target,
+                clock_state.as_ref(),
+            )
+            .await?
+            .spawn(cancellation_token.clone())
+            .await?,
+        );
+
+        // Refuse to start on an epoch earlier than the last one reached, and
+        // record the epochs reached from now on.
+        let clock_state_handle = match clock_state {
+            Some(state) => Some(
+                clock::persist_epochs(clock_ref.clone(), state, cancellation_token.clone()).await?,
+            ),
+            None => None,
+        };
+
+        // Prove the certificates of the ended epochs, and hand the proofs to the
+        // kernel for settlement.
+        let (epochs_sender, epochs_receiver) = mpsc::channel(MAX_EPOCHS_TO_PROVE);
+        let (proofs_sender, proofs_receiver) = mpsc::channel(MAX_EPOCHS_TO_PROVE);
+        let certifier = Arc::new(Sp1Certifier::new(Prover::new(&config.prover)?));
+        let certification_handle = tokio::spawn(
+            Certification::new(certifier, epochs_receiver, proofs_sender)
+                .run(cancellation_token.clone()),
+        );
+
+        let aggregator_task = AggregatorNotifier::new(epochs_sender);
+        let clock_subscription = clock_ref.events("certificate_orchestrator")?;
 
         let (data_sender, data_receiver) = mpsc::channel(
             config
@@ -107,8 +283,121 @@ impl Node {
             .start()
             .await?;
 
-        // Bind the core to the RPC server.
-        let server_handle = AgglayerImpl::new(core, data_sender).start(config).await?;
+        // Settle the proofs packed during each epoch once it ends, if configured.
+        let ended_epochs = config
+            .outbound
+            .rpc
+            .settle
+            .epoch_packing
+            .is_some()
+            .then(|| clock_ref.events("epoch_packing"))
+            .transpose()?
+            .map(|events| events.filter_map(clock::packing_epoch));
+
+        // The subscribers of the node are attached, the subscription reserved for
+        // the first one must not hold the clock back once nobody took it.
+        clock_ref.release_first_subscription();
+
+        let agglayer =
+            AgglayerImpl::new(core, data_sender, clock_ref.clone()).with_leadership(leadership);
+
+        // Serve the admin RPC server if enabled.
+        let admin_handle = match config.admin.listen {
+            Some(addr) => {
+                let kernel = agglayer.kernel();
+                let server_handle = AdminImpl::new(
+                    kernel.rollups().clone(),
+                    kernel.admission().clone(),
+                    kernel.access_list().clone(),
+                    kernel.auditor().clone(),
+                )
+                .with_signer_rotation(kernel.clone())
+                .start(addr, &config.rpc)
+                .await?;
+                let cancellation_token = cancellation_token.clone();
+
+                Some(tokio::spawn(async move {
+                    tokio::select! {
+                        _ = server_handle.clone().stopped() => {},
+                        _ = cancellation_token.cancelled() => {
+                            debug!("Admin RPC shutdown requested.");
+                            _ = server_handle.stop();
+                        }
+                    }
+                }))
+            }
+            None => None,
+        };
+
+        let epoch_packing_handle = ended_epochs.map(|ended_epochs| {
+            let kernel = agglayer.kernel().clone();
+            let clock_ref = clock_ref.clone();
+            let cancellation_token = cancellation_token.clone();
+
+            tokio::spawn(async move {
+                kernel
+                    .settle_packed_epochs(
+                        ended_epochs,
+                        || clock_ref.current_epoch(),
+                        cancellation_token,
+                    )
+                    .await
+            })
+        });
+
+        // Warn once the epoch duration read from the rollup manager contract
+        // changes on L1.
+        let epoch_duration_watch_handle = verify_batch_time_target.map(|epoch_duration| {
+            let kernel = agglayer.kernel().clone();
+            let cancellation_token = cancellation_token.clone();
+
+            tokio::spawn(async move {
+                clock::watch_epoch_duration(
+                    epoch_duration,
+                    EPOCH_DURATION_WATCH_INTERVAL,
+                    || kernel.verify_batch_time_target(),
+                    |stale| kernel.report_stale_epoch_duration(stale),
+                    cancellation_token,
+                )
+                .await
+            })
+        });
+
+        // Settle again the proofs whose settlement was reorged out.
+        let kernel = agglayer.kernel().clone();
+        let reorg_handle = {
+            let cancellation_token = cancellation_token.clone();
+
+            tokio::spawn(async move { kernel.resettle_reorged(cancellation_token).await })
+        };
+
+        // Probe L1 while it's unreachable, to stop rejecting the submissions once
+        // it recovers.
+        let kernel = agglayer.kernel().clone();
+        let l1_recovery_handle = {
+            let cancellation_token = cancellation_token.clone();
+
+            tokio::spawn(async move { kernel.probe_l1_recovery(cancellation_token).await })
+        };
+
+        // The epoch proofs stop coming once the certification stops.
+        let kernel = agglayer.kernel().clone();
+        let epoch_settlement_handle =
+            tokio::spawn(async move { kernel.settle_epoch_proofs(proofs_receiver).await });
+
+        // Serve the gRPC server alongside the RPC server if enabled.
+        let grpc_handle = match config.grpc.listen {
+            Some(addr) => Some(
+                GrpcImpl::new(agglayer.clone(), &config.rpc)?
+                    .start(addr, cancellation_token.clone())
+                    .await?,
+            ),
+            None => None,
+        };
+
+        // Bind the core to the RPC server, keeping a handle on it to reload the
+        // configuration.
+        let server_handle = agglayer.clone().start(config).await?;
 
         let rpc_handle = tokio::spawn(async move {
             tokio::select! {
@@ -120,16 +409,68 @@ impl Node {
         });
 
         let node = Self {
+            agglayer,
             rpc_handle,
+            grpc_handle,
             certificate_orchestrator_handle,
+            certification_handle,
+            epoch_settlement_handle,
+            epoch_packing_handle,
+            reorg_handle,
+            l1_recovery_handle,
+            settlement_indexer_handle,
+            rollup_sync_handle,
+            leader_elector_handle,
+            clock_state_handle,
+            epoch_duration_watch_handle,
+            admin_handle,
         };
 
         Ok(node)
     }
 
+    /// Reload the sections of the given configuration file that can change at
+    /// runtime: the rate limits, the gas strategy of the settlements and the
+    /// endpoints of the ZkEVM nodes, returning the configuration loaded.
+    pub(crate) fn reload(&self, cfg: &Path) -> Result<Config> {
+        self.agglayer.reload(cfg)
+    }
+
     pub(crate) async fn await_shutdown(self) {
         debug!("Node shutdown started.");
-        _ = join!(self.rpc_handle, self.certificate_orchestrator_handle);
+        _ = join!(
+            self.rpc_handle,
+            self.certificate_orchestrator_handle,
+            self.certification_handle,
+            self.epoch_settlement_handle,
+            self.reorg_handle,
+            self.l1_recovery_handle
+        );
+        if let Some(grpc_handle) = self.grpc_handle {
+            _ = grpc_handle.await;
+        }
+        if let Some(epoch_packing_handle) = self.epoch_packing_handle {
+            _ = epoch_packing_handle.await;
+        }
+        if let Some(settlement_indexer_handle) = self.settlement_indexer_handle {
+            _ = settlement_indexer_handle.await;
+        }
+
+        if let Some(rollup_sync_handle) = self.rollup_sync_handle {
+            _ = rollup_sync_handle.await;
+        }
+        if let Some(leader_elector_handle) = self.leader_elector_handle {
+            _ = leader_elector_handle.await;
+        }
+        if let Some(clock_state_handle) = self.clock_state_handle {
+            _ = clock_state_handle.await;
+        }
+        if let Some(epoch_duration_watch_handle) = self.epoch_duration_watch_handle {
+            _ = epoch_duration_watch_handle.await;
+        }
+        if let Some(admin_handle) = self.admin_handle {
+            _ = admin_handle.await;
+        }
         debug!("Node shutdown completed.");
     }
 }
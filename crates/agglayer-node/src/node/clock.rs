@@ -0,0 +1,507 @@
+use std::{
+    fmt::Display,
+    future::Future,
+    num::{NonZeroU64, NonZeroUsize},
+    sync::Arc,
+    time::Duration,
+};
+
+use agglayer_clock::{
+    BlockClock, CatchUp, Clock, ClockRef, EpochDuration, Error, Event, OverflowPolicy, TimeClock,
+};
+use agglayer_config::{
+    self as config, ClockEventsConfig, Epoch, EpochCatchUp, EpochOverflowPolicy,
+};
+use agglayer_storage::ClockState;
+use anyhow::{bail, Context as _};
+use chrono::{DateTime, Utc};
+use ethers::providers::Provider;
+use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::reconnect::ReconnectingWs;
+
+/// The [`Clock`] driving the epochs of the node, selected from the
+/// configuration at startup.
+pub(crate) enum ConfiguredClock {
+    Time(TimeClock),
+    Block(BlockClock<Provider<ReconnectingWs>>),
+}
+
+impl ConfiguredClock {
+    /// Build the [`Clock`] described by the epoch configuration.
+    ///
+    /// The epoch duration read from the rollup manager contract, if
+    /// configured so, is the given verification time target. The genesis of
+    /// the [`TimeClock`] is the one recorded in the given clock state, if any.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The epoch duration or the epoch ending notice is shorter than a block.
+    /// - The epoch duration is to be read from the rollup manager contract but
+    ///   no verification time target is given.
+    /// - The [`TimeClock`] genesis is invalid, or can't be read from or
+    ///   recorded in the clock state.
+    /// - The L1 websocket endpoint of the [`BlockClock`] is unreachable.
+    ///
+    /// The websocket connection of the [`BlockClock`] is re-established up to
+    /// `ws_max_reconnects` times, re-resolving the endpoint each time, then
+    /// connected again from scratch.
+    pub(crate) async fn new(
+        config: &Epoch,
+        ws_max_reconnects: usize,
+        verify_batch_time_target: Option<Duration>,
+        clock_state: Option<&ClockState>,
+    ) -> anyhow::Result<Self> {
+        match config {
+            Epoch::TimeClock(cfg) => {
+                let epoch_duration = epoch_duration(
+                    cfg.epoch_duration,
+                    TimeClock::BLOCK_TIME,
+                    verify_batch_time_target,
+                )?;
+                let (catch_up, capacity, overflow_policy) = events(&cfg.events);
+                let mut clock = TimeClock::new(time_clock_genesis(clock_state)?, epoch_duration)?
+                    .with_catch_up(catch_up)
+                    .with_broadcast(capacity, overflow_policy);
+                if let Some(notice) = ending_notice(&cfg.events, TimeClock::BLOCK_TIME)? {
+                    clock = clock.with_ending_notice(notice);
+                }
+
+                Ok(Self::Time(clock))
+            }
+            Epoch::BlockClock(cfg) => {
+                let epoch_duration = epoch_duration(
+                    cfg.epoch_duration,
+                    cfg.l1_block_time,
+                    verify_batch_time_target,
+                )?;
+                let (catch_up, capacity, overflow_policy) = events(&cfg.events);
+                let provider = Provider::new(
+                    ReconnectingWs::connect(cfg.ws_node_url.clone(), ws_max_reconnects).await?,
+                );
+
+                let mut clock = BlockClock::new(provider, cfg.genesis_block, epoch_duration)
+                    .with_catch_up(catch_up)
+                    .with_broadcast(capacity, overflow_policy);
+                if let Some(notice) = ending_notice(&cfg.events, cfg.l1_block_time)? {
+                    clock = clock.with_ending_notice(notice);
+                }
+
+                Ok(Self::Block(clock))
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for ConfiguredClock {
+    async fn spawn(self, cancellation_token: CancellationToken) -> Result<ClockRef, Error> {
+        match self {
+            Self::Time(clock) => clock.spawn(cancellation_token).await,
+            Self::Block(clock) => clock.spawn(cancellation_token).await,
+        }
+    }
+}
+
+/// The genesis of the [`TimeClock`], recorded in the given clock state on its
+/// first start so that its epochs carry on across restarts, or now if there is
+/// no clock state.
+fn time_clock_genesis(clock_state: Option<&ClockState>) -> anyhow::Result<DateTime<Utc>> {
+    let Some(state) = clock_state else {
+        return Ok(Utc::now());
+    };
+
+    // The genesis is recorded to the second.
+    state.record_genesis(Utc::now().timestamp())?;
+    let genesis = state
+        .genesis()?
+        .context("The clock genesis is not recorded")?;
+
+    DateTime::from_timestamp(genesis, 0)
+        .with_context(|| format!("Invalid clock genesis recorded: {genesis}"))
+}
+
+/// Keep the epochs of the given [`Clock`] from moving backwards across
+/// restarts, recording the epochs it reaches in the given state until
+/// cancelled.
+///
+/// The epoch the [`Clock`] started on is checked once initialised, the
+/// reserved first subscription to the [`Clock`] is left to the node.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The state is unreadable.
+/// - The [`Clock`] stopped before initialising its epoch.
+/// - The [`Clock`] started on an epoch earlier than the last one it reached,
+///   likely as its genesis or epoch duration changed.
+pub(crate) async fn persist_epochs(
+    clock_ref: Arc<ClockRef>,
+    state: ClockState,
+    cancellation_token: CancellationToken,
+) -> anyhow::Result<JoinHandle<()>> {
+    // Subscribe ahead of the initialisation, not to miss an epoch in between.
+    let mut events = clock_ref.subscribe_from_now();
+    let current_epoch = clock_ref.started().await?;
+
+    if let Some(last_epoch) = state.last_epoch()? {
+        if current_epoch < last_epoch {
+            bail!(
+                "The clock started on epoch {current_epoch}, earlier than the epoch {last_epoch} \
+                 it already reached. Check its genesis and epoch duration."
+            );
+        }
+    }
+    state.record_epoch(current_epoch)?;
+
+    Ok(tokio::spawn(async move {
+        loop {
+            // Record the epochs reached before the cancellation first.
+            tokio::select! {
+                biased;
+
+                event = events.recv() => {
+                    // The lagged events are covered by the current epoch.
+                    if let Err(RecvError::Closed) = event {
+                        break;
+                    }
+
+                    if let Err(error) = state.record_epoch(clock_ref.current_epoch()) {
+                        warn!("Unable to record the epoch reached by the clock: {error}");
+                    }
+                }
+                _ = cancellation_token.cancelled() => {
+                    debug!("Clock state persistence cancelled");
+                    break;
+                }
+            }
+        }
+    }))
+}
+
+/// The epoch whose packed proofs are to be settled on the given clock event,
+/// if any.
+///
+/// The proofs packed during the epochs whose ending was missed, or reverted by
+/// an L1 reorg, are flushed within the current epoch.
+pub(crate) fn packing_epoch(event: Event) -> Option<u64> {
+    match event {
+        Event::EpochEnded { epoch, .. } | Event::MissedEpochs { epoch, .. } => Some(epoch),
+        Event::EpochReverted { to, .. } => Some(to),
+        _ => None,
+    }
+}
+
+/// Whether the epoch duration is read from the rollup manager contract.
+pub(crate) fn from_rollup_manager(config: &Epoch) -> bool {
+    let epoch_duration = match config {
+        Epoch::TimeClock(cfg) => cfg.epoch_duration,
+        Epoch::BlockClock(cfg) => cfg.epoch_duration,
+    };
+
+    epoch_duration == config::EpochDuration::RollupManager
+}
+
+/// Translate the configured epoch duration into blocks of the given duration,
+/// the one read from the rollup manager contract being the given verification
+/// time target.
+fn epoch_duration(
+    epoch_duration: config::EpochDuration,
+    block_time: Duration,
+    verify_batch_time_target: Option<Duration>,
+) -> Result<NonZeroU64, std::io::Error> {
+    let invalid =
+        || std::io::Error::new(std::io::ErrorKind::InvalidInput, "EpochDuration is invalid");
+    let epoch_duration = match epoch_duration {
+        config::EpochDuration::Time(duration) => EpochDuration::Time(duration),
+        config::EpochDuration::Blocks(blocks) => EpochDuration::Blocks(blocks),
+        config::EpochDuration::RollupManager => {
+            EpochDuration::Time(verify_batch_time_target.ok_or_else(invalid)?)
+        }
+    };
+
+    epoch_duration.as_blocks(block_time).ok_or_else(invalid)
+}
+
+/// Warn once the verification time target of the rollup manager contract, the
+/// epoch duration, changes on L1, checking it at the given interval until
+/// cancelled.
+///
+/// The running [`Clock`] keeps the epoch duration it started with, the node
+/// follows the new one once restarted: the epoch duration set on L1 is
+/// reported to `stale` while it differs from the running one, `None`
+/// otherwise.
+pub(crate) async fn watch_epoch_duration<F, Fut, E>(
+    epoch_duration: Duration,
+    interval: Duration,
+    verify_batch_time_target: F,
+    stale: impl Fn(Option<Duration>),
+    cancellation_token: CancellationToken,
+) where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Duration, E>>,
+    E: Display,
+{
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_target = epoch_duration;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = cancellation_token.cancelled() => break,
+        }
+
+        match verify_batch_time_target().await {
+            Ok(target) if target != last_target => {
+                warn!(
+                    "The verification time target of the rollup manager contract changed from {}s \
+                     to {}s, the epochs keep lasting {}s until the node restarts",
+                    last_target.as_secs(),
+                    target.as_secs(),
+                    epoch_duration.as_secs()
+                );
+                last_target = target;
+                stale((target != epoch_duration).then_some(target));
+            }
+            Ok(_) => {}
+            Err(error) => {
+                debug!("Unable to read the verification time target on L1: {error}")
+            }
+        }
+    }
+
+    debug!("Epoch duration watch stopped");
+}
+
+/// Translate the configured notice of the epoch endings, if any, into blocks
+/// of the given duration.
+fn ending_notice(
+    config: &ClockEventsConfig,
+    block_time: Duration,
+) -> Result<Option<NonZeroU64>, std::io::Error> {
+    let Some(notice) = config.ending_notice else {
+        return Ok(None);
+    };
+    let invalid = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "EpochEndingNotice is invalid",
+        )
+    };
+    let notice = match notice {
+        config::EpochDuration::Time(duration) => EpochDuration::Time(duration),
+        config::EpochDuration::Blocks(blocks) => EpochDuration::Blocks(blocks),
+        config::EpochDuration::RollupManager => return Err(invalid()),
+    };
+
+    notice.as_blocks(block_time).map(Some).ok_or_else(invalid)
+}
+
+/// The delivery settings of the clock events.
+fn events(config: &ClockEventsConfig) -> (CatchUp, NonZeroUsize, OverflowPolicy) {
+    let catch_up = match config.catch_up {
+        EpochCatchUp::Resync => CatchUp::Resync,
+        EpochCatchUp::Replay => CatchUp::Replay,
+    };
+    let overflow_policy = match config.overflow_policy {
+        EpochOverflowPolicy::DropOldest => OverflowPolicy::DropOldest,
+        EpochOverflowPolicy::Block => OverflowPolicy::Block,
+    };
+
+    (catch_up, config.broadcast_capacity, overflow_policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::VecDeque,
+        num::NonZeroU64,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use agglayer_clock::{Clock, Event, ManualClock, TimeClock};
+    use agglayer_config as config;
+    use agglayer_storage::ClockState;
+    use chrono::Utc;
+    use ethers::types::H256;
+    use tokio_util::sync::CancellationToken;
+
+    use super::{
+        ending_notice, epoch_duration, packing_epoch, persist_epochs, time_clock_genesis,
+        watch_epoch_duration,
+    };
+
+    #[test]
+    fn epoch_duration_from_rollup_manager() {
+        let block_time = std::time::Duration::from_secs(12);
+        let target = std::time::Duration::from_secs(1800);
+
+        assert_eq!(
+            epoch_duration(
+                config::EpochDuration::RollupManager,
+                block_time,
+                Some(target)
+            )
+            .unwrap(),
+            NonZeroU64::new(150).unwrap()
+        );
+        assert!(epoch_duration(config::EpochDuration::RollupManager, block_time, None).is_err());
+        assert!(epoch_duration(
+            config::EpochDuration::RollupManager,
+            block_time,
+            Some(std::time::Duration::ZERO)
+        )
+        .is_err());
+
+        let events = config::ClockEventsConfig {
+            ending_notice: Some(config::EpochDuration::RollupManager),
+            ..Default::default()
+        };
+        assert!(ending_notice(&events, block_time).is_err());
+    }
+
+    #[test]
+    fn flush_the_packed_proofs_on_missed_or_reverted_epochs() {
+        let ended = Event::EpochEnded {
+            epoch: 4,
+            previous_epoch: Some(3),
+            block_range: 20..25,
+            started_at: Utc::now(),
+            ended_at: Utc::now(),
+        };
+
+        assert_eq!(packing_epoch(ended), Some(4));
+        assert_eq!(
+            packing_epoch(Event::MissedEpochs {
+                skipped_events: 3,
+                epoch: 7
+            }),
+            Some(7)
+        );
+        assert_eq!(
+            packing_epoch(Event::EpochReverted { from: 7, to: 6 }),
+            Some(6)
+        );
+        assert_eq!(packing_epoch(Event::Resynced { epoch: 7 }), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn report_the_stale_epoch_duration_until_back_to_the_running_one() {
+        let token = CancellationToken::new();
+        let targets = Mutex::new(VecDeque::from([
+            Ok(Duration::from_secs(30)),
+            Ok(Duration::from_secs(60)),
+            Err("L1 unreachable"),
+            Ok(Duration::from_secs(60)),
+            Ok(Duration::from_secs(30)),
+        ]));
+        let reported = Mutex::new(Vec::new());
+
+        watch_epoch_duration(
+            Duration::from_secs(30),
+            Duration::from_secs(60),
+            || {
+                let target = targets.lock().unwrap().pop_front();
+                if target.is_none() {
+                    token.cancel();
+                }
+
+                async move { target.unwrap_or(Err("no more targets")) }
+            },
+            |stale| reported.lock().unwrap().push(stale),
+            token.clone(),
+        )
+        .await;
+
+        assert_eq!(
+            reported.into_inner().unwrap(),
+            vec![Some(Duration::from_secs(60)), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn refuse_to_start_on_an_earlier_epoch() {
+        let path = std::env::temp_dir().join(format!("agglayer-clock-{:x}", H256::random()));
+        let epoch_duration = NonZeroU64::new(5).unwrap();
+
+        let token = CancellationToken::new();
+        let clock = ManualClock::new(epoch_duration);
+        let handle = clock.handle();
+        let clock_ref = Arc::new(clock.spawn(token.clone()).await.unwrap());
+        let persisted = persist_epochs(
+            clock_ref.clone(),
+            ClockState::open(&path).unwrap(),
+            token.clone(),
+        )
+        .await
+        .unwrap();
+
+        handle.end_epoch().await.unwrap();
+        handle.end_epoch().await.unwrap();
+        clock_ref.wait_for_epoch(2).await.unwrap();
+
+        token.cancel();
+        persisted.await.unwrap();
+
+        let token = CancellationToken::new();
+        let clock_ref = Arc::new(
+            ManualClock::new(epoch_duration)
+                .spawn(token.clone())
+                .await
+                .unwrap(),
+        );
+        let state = ClockState::open(&path).unwrap();
+        assert_eq!(state.last_epoch().unwrap(), Some(2));
+        assert!(persist_epochs(clock_ref, state, token).await.is_err());
+
+        _ = std::fs::remove_dir_all(path);
+    }
+
+    #[tokio::test]
+    async fn accept_to_start_on_the_same_or_a_later_epoch() {
+        let path = std::env::temp_dir().join(format!("agglayer-clock-{:x}", H256::random()));
+        let state = ClockState::open(&path).unwrap();
+        state.record_epoch(9).unwrap();
+
+        // The TimeClock initialises its epoch 10 once its task runs.
+        let genesis = Utc::now() - chrono::Duration::seconds(105);
+        let start = |state| async move {
+            let token = CancellationToken::new();
+            let clock = TimeClock::new(genesis, NonZeroU64::new(10).unwrap()).unwrap();
+            let clock_ref = Arc::new(clock.spawn(token.clone()).await.unwrap());
+
+            let persisted = persist_epochs(clock_ref, state, token.clone()).await;
+            token.cancel();
+
+            persisted.unwrap().await.unwrap();
+        };
+
+        start(state).await;
+        let state = ClockState::open(&path).unwrap();
+        assert_eq!(state.last_epoch().unwrap(), Some(10));
+
+        start(state).await;
+        let state = ClockState::open(&path).unwrap();
+        assert_eq!(state.last_epoch().unwrap(), Some(10));
+
+        _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn time_clock_genesis_survives_a_restart() {
+        let path = std::env::temp_dir().join(format!("agglayer-clock-{:x}", H256::random()));
+        let state = ClockState::open(&path).unwrap();
+
+        let genesis = time_clock_genesis(Some(&state)).unwrap();
+        assert!(genesis <= Utc::now());
+        assert_eq!(time_clock_genesis(Some(&state)).unwrap(), genesis);
+
+        drop(state);
+        _ = std::fs::remove_dir_all(path);
+    }
+}
@@ -1,34 +1,51 @@
 use agglayer_certificate_orchestrator::{EpochPacker, Error};
 use futures::future::BoxFuture;
+use tokio::sync::mpsc;
 use tracing::debug;
 
+use crate::{certificate::Certificate, certifier::EpochCertificates};
+
+/// Hands the certificates of each ended epoch to the
+/// [`Certification`](crate::certifier::Certification) task.
 #[derive(Clone)]
-pub(crate) struct AggregatorNotifier {}
+pub(crate) struct AggregatorNotifier {
+    epochs: mpsc::Sender<EpochCertificates>,
+}
 
 impl AggregatorNotifier {
-    pub(crate) fn new() -> Self {
-        Self {}
+    pub(crate) fn new(epochs: mpsc::Sender<EpochCertificates>) -> Self {
+        Self { epochs }
     }
 }
 
 impl EpochPacker for AggregatorNotifier {
-    fn pack<T: IntoIterator<Item = ()>>(
+    type Certificate = Certificate;
+
+    fn pack<T: IntoIterator<Item = Certificate>>(
         &self,
         epoch: u64,
         to_pack: T,
-    ) -> Result<BoxFuture<Result<(), Error>>, Error> {
-        // TODO: Implement the aggregator notifier.
+    ) -> Result<BoxFuture<'static, Result<(), Error>>, Error> {
+        let certificates = to_pack.into_iter().collect::<Vec<_>>();
+
+        // Nothing to prove for the epochs without certificates.
+        if certificates.is_empty() {
+            return Ok(Box::pin(async { Ok(()) }));
+        }
 
-        let to_pack = to_pack.into_iter().collect::<Vec<_>>();
+        debug!(
+            "Start packing epoch {} with {} certificates",
+            epoch,
+            certificates.len()
+        );
 
-        Ok(Box::pin(async move {
-            debug!(
-                "Start packing epoch {} with {} certificates",
+        self.epochs
+            .try_send(EpochCertificates {
                 epoch,
-                to_pack.len()
-            );
+                certificates,
+            })
+            .map_err(|_| Error::Unavailable { epoch })?;
 
-            Ok(())
-        }))
+        Ok(Box::pin(async { Ok(()) }))
     }
 }
@@ -0,0 +1,65 @@
+//! Outbound connections through the configured proxies.
+use agglayer_config::{OutboundConnectionsConfig, OutboundProxyConfig};
+use ethers::providers::Http;
+use url::Url;
+
+/// Build an HTTP client reaching the given endpoint through its proxy, if any.
+///
+/// Once a proxy is configured, the endpoints without proxy are reached
+/// directly, regardless of the environment.
+pub(crate) fn http_client(
+    proxy: Option<&OutboundProxyConfig>,
+    endpoint: &Url,
+) -> reqwest::Result<reqwest::Client> {
+    http_client_builder(proxy, endpoint)?.build()
+}
+
+fn http_client_builder(
+    proxy: Option<&OutboundProxyConfig>,
+    endpoint: &Url,
+) -> reqwest::Result<reqwest::ClientBuilder> {
+    let builder = reqwest::Client::builder();
+
+    Ok(match proxy {
+        Some(proxy) => match proxy.proxy_for(endpoint) {
+            Some(url) => builder.proxy(reqwest::Proxy::all(url.as_str())?),
+            None => builder.no_proxy(),
+        },
+        None => builder,
+    })
+}
+
+/// Build a JSON-RPC transport reaching the given endpoint through its proxy,
+/// if any, over a pool of persistent connections.
+pub(crate) fn http_transport(
+    proxy: Option<&OutboundProxyConfig>,
+    connections: &OutboundConnectionsConfig,
+    endpoint: &Url,
+) -> reqwest::Result<Http> {
+    let client = http_client_builder(proxy, endpoint)?
+        .pool_idle_timeout(connections.pool_idle_timeout)
+        .pool_max_idle_per_host(connections.pool_max_idle_per_host)
+        .tcp_keepalive(connections.tcp_keepalive)
+        .build()?;
+
+    Ok(Http::new_with_client(endpoint.clone(), client))
+}
+
+/// Export the proxy to the environment, for the clients without explicit
+/// proxy configuration such as the KMS backends.
+///
+/// Must be called before spawning any thread.
+pub(crate) fn export_to_env(proxy: &OutboundProxyConfig) {
+    if let Some(url) = &proxy.url {
+        for name in ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"] {
+            std::env::set_var(name, url.as_str());
+        }
+    }
+
+    if !proxy.no_proxy.is_empty() {
+        let no_proxy = proxy.no_proxy.join(",");
+        for name in ["NO_PROXY", "no_proxy"] {
+            std::env::set_var(name, &no_proxy);
+        }
+    }
+}
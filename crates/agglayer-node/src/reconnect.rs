@@ -0,0 +1,160 @@
+//! Websocket connection to L1, re-established once dropped.
+//!
+//! The websocket transport reconnects on its own a limited number of times,
+//! after which its requests fail for good and its subscriptions end.
+//! [`ReconnectingWs`] connects again after a request failed on a dropped
+//! connection, so that the subscribers, such as the block clock, re-establish
+//! their subscriptions on the new connection.
+use std::sync::RwLock;
+
+use agglayer_telemetry::{KeyValue, L1_WS_RECONNECTS};
+use async_trait::async_trait;
+use ethers::{
+    providers::{JsonRpcClient, PubsubClient, Ws, WsClientError},
+    types::U256,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{info, warn};
+use url::Url;
+
+/// A JSON-RPC websocket transport connecting again once its connection is
+/// dropped for good.
+#[derive(Debug)]
+pub(crate) struct ReconnectingWs {
+    endpoint: Url,
+    /// The origin of the endpoint, labelling its metrics without leaking the
+    /// credentials its URL may carry.
+    origin: String,
+    /// The number of reconnections of each connection, before connecting
+    /// again.
+    max_reconnects: usize,
+    connection: RwLock<Connection>,
+    /// Held while connecting again, so that the requests failing
+    /// concurrently connect only once.
+    reconnecting: tokio::sync::Mutex<()>,
+}
+
+#[derive(Debug)]
+struct Connection {
+    ws: Ws,
+    /// The number of times the transport connected again.
+    generation: u64,
+}
+
+impl ReconnectingWs {
+    /// Connect to the given endpoint.
+    pub(crate) async fn connect(
+        endpoint: Url,
+        max_reconnects: usize,
+    ) -> Result<Self, WsClientError> {
+        let ws = Ws::connect_with_reconnects(endpoint.as_str(), max_reconnects).await?;
+
+        Ok(Self {
+            origin: endpoint.origin().ascii_serialization(),
+            endpoint,
+            max_reconnects,
+            connection: RwLock::new(Connection { ws, generation: 0 }),
+            reconnecting: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Get the current connection and its generation.
+    fn ws(&self) -> (Ws, u64) {
+        let connection = self.connection.read().expect("Connection lock poisoned");
+
+        (connection.ws.clone(), connection.generation)
+    }
+
+    /// Connect again, unless the connection of the given generation was
+    /// replaced already.
+    async fn reconnect(&self, generation: u64) {
+        let _reconnecting = self.reconnecting.lock().await;
+        if self.ws().1 != generation {
+            return;
+        }
+
+        match Ws::connect_with_reconnects(self.endpoint.as_str(), self.max_reconnects).await {
+            Ok(ws) => {
+                info!("Reconnected to the L1 websocket endpoint {}", self.origin);
+                L1_WS_RECONNECTS.add(1, &[KeyValue::new("endpoint", self.origin.clone())]);
+
+                *self.connection.write().expect("Connection lock poisoned") = Connection {
+                    ws,
+                    generation: generation + 1,
+                };
+            }
+            // Connect again on next failed request.
+            Err(error) => warn!(
+                "Unable to reconnect to the L1 websocket endpoint {}: {error}",
+                self.origin
+            ),
+        }
+    }
+}
+
+/// Whether the error is caused by a connection dropped for good.
+fn is_disconnected(error: &WsClientError) -> bool {
+    matches!(
+        error,
+        WsClientError::UnexpectedClose
+            | WsClientError::DeadChannel
+            | WsClientError::TooManyReconnects
+            | WsClientError::InternalError(_)
+    )
+}
+
+#[async_trait]
+impl JsonRpcClient for ReconnectingWs {
+    type Error = WsClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let (ws, generation) = self.ws();
+        let result = ws.request(method, params).await;
+
+        // The request is not retried, its caller may retry it on the new
+        // connection.
+        if let Err(error) = &result {
+            if is_disconnected(error) {
+                self.reconnect(generation).await;
+            }
+        }
+
+        result
+    }
+}
+
+impl PubsubClient for ReconnectingWs {
+    type NotificationStream = <Ws as PubsubClient>::NotificationStream;
+
+    fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
+        self.ws().0.subscribe(id)
+    }
+
+    fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
+        self.ws().0.unsubscribe(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::providers::WsClientError;
+
+    use super::is_disconnected;
+
+    #[test]
+    fn only_dropped_connections_reconnect() {
+        assert!(is_disconnected(&WsClientError::UnexpectedClose));
+        assert!(is_disconnected(&WsClientError::DeadChannel));
+        assert!(is_disconnected(&WsClientError::TooManyReconnects));
+        assert!(!is_disconnected(&WsClientError::UnknownSubscription(
+            1.into()
+        )));
+        assert!(!is_disconnected(&WsClientError::UnexpectedBinary(
+            Vec::new()
+        )));
+    }
+}
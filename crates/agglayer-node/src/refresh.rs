@@ -0,0 +1,148 @@
+//! Long-lived HTTP connections recycled over time.
+//!
+//! The HTTP clients keep their connections alive as long as they are used, so
+//! an endpoint moving to other addresses, behind a load balancer for instance,
+//! is never re-resolved. [`RefreshingHttp`] drops its connections every
+//! refresh interval, and after a failed request.
+use std::{sync::RwLock, time::Instant};
+
+use agglayer_config::{OutboundConnectionsConfig, OutboundProxyConfig};
+use async_trait::async_trait;
+use ethers::providers::{Http, HttpClientError, JsonRpcClient};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::proxy;
+
+/// A JSON-RPC HTTP transport rebuilding its client, and thus its connections,
+/// periodically.
+#[derive(Debug)]
+pub(crate) struct RefreshingHttp {
+    endpoint: Url,
+    proxy: Option<OutboundProxyConfig>,
+    connections: OutboundConnectionsConfig,
+    transport: RwLock<Transport>,
+}
+
+#[derive(Debug)]
+struct Transport {
+    http: Http,
+    /// When the transport was built, or `None` if it must be rebuilt.
+    built_at: Option<Instant>,
+}
+
+impl RefreshingHttp {
+    /// Create a new transport to the given endpoint, through its proxy if any.
+    pub(crate) fn new(
+        endpoint: Url,
+        proxy: Option<OutboundProxyConfig>,
+        connections: OutboundConnectionsConfig,
+    ) -> reqwest::Result<Self> {
+        let http = proxy::http_transport(proxy.as_ref(), &connections, &endpoint)?;
+
+        Ok(Self {
+            endpoint,
+            proxy,
+            connections,
+            transport: RwLock::new(Transport {
+                http,
+                built_at: Some(Instant::now()),
+            }),
+        })
+    }
+
+    /// Get the current transport, rebuilding it first if it expired.
+    fn http(&self) -> Http {
+        {
+            let transport = self.transport.read().expect("Transport lock poisoned");
+            if transport
+                .built_at
+                .is_some_and(|built_at| built_at.elapsed() < self.connections.refresh_interval)
+            {
+                return transport.http.clone();
+            }
+        }
+
+        let mut transport = self.transport.write().expect("Transport lock poisoned");
+        match proxy::http_transport(self.proxy.as_ref(), &self.connections, &self.endpoint) {
+            Ok(http) => {
+                debug!("Refreshed the connections to {}", self.endpoint);
+
+                transport.http = http;
+                transport.built_at = Some(Instant::now());
+            }
+            // Keep using the current transport, rebuilding it on next request.
+            Err(error) => warn!(
+                "Unable to refresh the connections to {}: {error}",
+                self.endpoint
+            ),
+        }
+
+        transport.http.clone()
+    }
+
+    /// Rebuild the transport on next request.
+    fn expire(&self) {
+        self.transport
+            .write()
+            .expect("Transport lock poisoned")
+            .built_at = None;
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for RefreshingHttp {
+    type Error = HttpClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let result = self.http().request(method, params).await;
+
+        // The backend may be gone, reconnect on next request.
+        if let Err(HttpClientError::ReqwestError(_)) = result {
+            self.expire();
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use agglayer_config::OutboundConnectionsConfig;
+    use ethers::providers::JsonRpcClient as _;
+
+    use super::RefreshingHttp;
+
+    #[tokio::test]
+    async fn failed_requests_expire_the_transport() {
+        let transport = RefreshingHttp::new(
+            "http://127.0.0.1:1".parse().unwrap(),
+            None,
+            OutboundConnectionsConfig {
+                refresh_interval: Duration::from_secs(3600),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert!(transport.transport.read().unwrap().built_at.is_some());
+
+        assert!(transport
+            .request::<_, String>("eth_blockNumber", ())
+            .await
+            .is_err());
+
+        assert!(transport.transport.read().unwrap().built_at.is_none());
+
+        transport.http();
+
+        assert!(transport.transport.read().unwrap().built_at.is_some());
+    }
+}
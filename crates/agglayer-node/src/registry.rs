@@ -0,0 +1,291 @@
+//! Registry of the rollups served by the agglayer.
+//!
+//! The registry is seeded from the configuration and amended at runtime
+//! through the admin RPC server. The amendments are persisted to the
+//! configured registry file, and restored from it on startup.
+use std::{
+    collections::BTreeMap,
+    io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use agglayer_config::{Config, ConsensusType, ProofFormat, ProofSystem};
+use ethers::types::{Address, Bytes};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::Url;
+
+/// The configuration of a rollup served by the agglayer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RollupConfig {
+    pub(crate) rollup_id: u32,
+    /// The consensus of the rollup, determining the pipeline serving its
+    /// submissions.
+    #[serde(default)]
+    pub(crate) consensus_type: ConsensusType,
+    /// The RPC endpoint of the ZkEVM node of the rollup.
+    pub(crate) full_node_rpc: Url,
+    /// The trusted sequencer of the rollup. If absent, the trusted sequencer
+    /// is fetched from the rollup contract.
+    #[serde(default)]
+    pub(crate) trusted_sequencer: Option<Address>,
+    #[serde(default)]
+    pub(crate) proof_system: ProofSystem,
+    /// The number of 32-byte words of the proofs.
+    #[serde(default = "default_proof_length")]
+    pub(crate) proof_length: usize,
+    /// The prefix of the proofs identifying the verifier able to check them.
+    #[serde(default)]
+    pub(crate) verifier_selector: Option<Bytes>,
+    /// Whether the submissions of the rollup are verified without being
+    /// settled.
+    #[serde(default)]
+    pub(crate) shadow: bool,
+    /// The maximum number of submissions of the rollup processed at once.
+    #[serde(default)]
+    pub(crate) max_pending_submissions: Option<u64>,
+}
+
+impl RollupConfig {
+    /// Get the expected shape of the proofs of the rollup.
+    pub(crate) fn proof_format(&self) -> ProofFormat {
+        ProofFormat {
+            proof_system: self.proof_system,
+            proof_length: self.proof_length,
+            verifier_selector: self.verifier_selector.clone(),
+        }
+    }
+}
+
+fn default_proof_length() -> usize {
+    ProofFormat::default().proof_length
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum RegistryError {
+    #[error("rollup {0} is already registered")]
+    AlreadyRegistered(u32),
+    #[error("rollup {0} is not registered")]
+    NotRegistered(u32),
+    #[error("registry I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("malformed registry: {0}")]
+    Malformed(#[from] serde_json::Error),
+}
+
+/// The rollups served by the agglayer, by rollup id.
+#[derive(Clone, Debug)]
+pub(crate) struct RollupRegistry {
+    rollups: Arc<RwLock<BTreeMap<u32, RollupConfig>>>,
+    /// The file persisting the registry.
+    path: Option<PathBuf>,
+}
+
+impl RollupRegistry {
+    /// Seed the registry with the rollups of the given configuration.
+    pub(crate) fn new(config: &Config) -> Self {
+        let rollups = config
+            .full_node_rpcs
+            .iter()
+            .map(|(rollup_id, url)| {
+                let format = config
+                    .proof_formats
+                    .get(rollup_id)
+                    .cloned()
+                    .unwrap_or_default();
+                let rollup = RollupConfig {
+                    rollup_id: *rollup_id,
+                    consensus_type: config
+                        .consensus_types
+                        .get(rollup_id)
+                        .copied()
+                        .unwrap_or_default(),
+                    full_node_rpc: url.clone(),
+                    trusted_sequencer: None,
+                    proof_system: format.proof_system,
+                    proof_length: format.proof_length,
+                    verifier_selector: format.verifier_selector,
+                    shadow: config.shadow_rollups.contains(rollup_id),
+                    max_pending_submissions: None,
+                };
+
+                (*rollup_id, rollup)
+            })
+            .collect();
+
+        Self {
+            rollups: Arc::new(RwLock::new(rollups)),
+            path: config.admin.registry_path.clone(),
+        }
+    }
+
+    /// Restore the rollups persisted in the registry file, if any.
+    pub(crate) fn restore(&self) -> Result<(), RegistryError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let persisted: Vec<RollupConfig> = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+
+        self.write().extend(
+            persisted
+                .into_iter()
+                .map(|rollup| (rollup.rollup_id, rollup)),
+        );
+
+        Ok(())
+    }
+
+    /// Get the configuration of the given rollup, if registered.
+    pub(crate) fn get(&self, rollup_id: u32) -> Option<RollupConfig> {
+        self.read().get(&rollup_id).cloned()
+    }
+
+    /// Get the ids of the registered rollups, in ascending order.
+    pub(crate) fn rollup_ids(&self) -> Vec<u32> {
+        self.read().keys().copied().collect()
+    }
+
+    /// Register a new rollup.
+    pub(crate) fn add(&self, rollup: RollupConfig) -> Result<(), RegistryError> {
+        let mut rollups = self.write();
+        if rollups.contains_key(&rollup.rollup_id) {
+            return Err(RegistryError::AlreadyRegistered(rollup.rollup_id));
+        }
+
+        rollups.insert(rollup.rollup_id, rollup);
+        self.persist(&rollups)
+    }
+
+    /// Replace the configuration of a registered rollup.
+    pub(crate) fn update(&self, rollup: RollupConfig) -> Result<(), RegistryError> {
+        let mut rollups = self.write();
+        match rollups.get_mut(&rollup.rollup_id) {
+            Some(current) => *current = rollup,
+            None => return Err(RegistryError::NotRegistered(rollup.rollup_id)),
+        }
+
+        self.persist(&rollups)
+    }
+
+    /// Update the ZkEVM node endpoints of the registered rollups from the
+    /// given configuration.
+    ///
+    /// The updated endpoints aren't persisted, as they're read from the
+    /// configuration again on restart.
+    pub(crate) fn reload_endpoints(&self, config: &Config) {
+        let mut rollups = self.write();
+        for (rollup_id, url) in &config.full_node_rpcs {
+            if let Some(rollup) = rollups.get_mut(rollup_id) {
+                rollup.full_node_rpc = url.clone();
+            }
+        }
+    }
+
+    fn persist(&self, rollups: &BTreeMap<u32, RollupConfig>) -> Result<(), RegistryError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+
+        std::fs::write(
+            &tmp,
+            serde_json::to_vec_pretty(&rollups.values().collect::<Vec<_>>())?,
+        )?;
+        std::fs::rename(&tmp, path)?;
+
+        Ok(())
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<u32, RollupConfig>> {
+        self.rollups.read().expect("Rollup registry lock poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<u32, RollupConfig>> {
+        self.rollups.write().expect("Rollup registry lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agglayer_config::Config;
+    use ethers::types::H256;
+
+    use super::{RegistryError, RollupConfig, RollupRegistry};
+
+    fn rollup(rollup_id: u32) -> RollupConfig {
+        serde_json::from_value(serde_json::json!({
+            "rollupId": rollup_id,
+            "fullNodeRpc": format!("http://zkevm-node-{rollup_id}:8123/"),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn registry_is_seeded_from_the_config() {
+        let mut config = Config::default();
+        config
+            .full_node_rpcs
+            .insert(2, "http://zkevm-node-2:8123/".parse().unwrap());
+        config.shadow_rollups.insert(2);
+
+        let registry = RollupRegistry::new(&config);
+
+        assert_eq!(registry.rollup_ids(), vec![2]);
+        assert!(registry.get(2).unwrap().shadow);
+        assert_eq!(registry.get(2).unwrap().proof_length, 24);
+
+        config
+            .full_node_rpcs
+            .insert(2, "http://zkevm-node-2-replica:8123/".parse().unwrap());
+        config
+            .full_node_rpcs
+            .insert(3, "http://zkevm-node-3:8123/".parse().unwrap());
+        registry.reload_endpoints(&config);
+
+        assert_eq!(registry.rollup_ids(), vec![2]);
+        assert_eq!(
+            registry.get(2).unwrap().full_node_rpc.as_str(),
+            "http://zkevm-node-2-replica:8123/"
+        );
+    }
+
+    #[test]
+    fn rollups_registered_at_runtime_are_persisted() {
+        let path = std::env::temp_dir().join(format!("agglayer-rollups-{:x}.json", H256::random()));
+        let mut config = Config::default();
+        config.admin.registry_path = Some(path.clone());
+
+        let registry = RollupRegistry::new(&config);
+        registry.add(rollup(1)).unwrap();
+        assert!(matches!(
+            registry.add(rollup(1)),
+            Err(RegistryError::AlreadyRegistered(1))
+        ));
+        assert!(matches!(
+            registry.update(rollup(2)),
+            Err(RegistryError::NotRegistered(2))
+        ));
+        registry
+            .update(RollupConfig {
+                shadow: true,
+                ..rollup(1)
+            })
+            .unwrap();
+
+        let restored = RollupRegistry::new(&config);
+        restored.restore().unwrap();
+        assert_eq!(restored.rollup_ids(), vec![1]);
+        assert!(restored.get(1).unwrap().shadow);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+//! Sync of the rollups registered on the rollup manager contract.
+//!
+//! The [`RollupSync`] fetches every rollup registered on L1 on startup and
+//! periodically after that, and polls the rollup manager contract for the
+//! rollups created, added or updated in between. The [`OnchainRollups`] it
+//! maintains gate the submissions of the rollups, and cache their trusted
+//! sequencer.
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, RwLock},
+};
+
+use agglayer_config::RollupSyncConfig;
+use agglayer_contracts::{polygon_zk_evm::PolygonZkEvm, L1RpcClient, RollupContract};
+use ethers::{contract::ContractError, providers::Middleware, types::Address};
+use thiserror::Error;
+use tokio::time::{interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+use crate::indexer::block_ranges;
+
+/// A rollup registered on the rollup manager contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct OnchainRollup {
+    pub(crate) rollup_contract: Address,
+    pub(crate) chain_id: u64,
+    pub(crate) verifier: Address,
+    pub(crate) fork_id: u64,
+    pub(crate) trusted_sequencer: Address,
+}
+
+/// The rollups registered on L1, by rollup id, as of the last sync.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OnchainRollups {
+    /// `None` until synced for the first time.
+    rollups: Arc<RwLock<Option<BTreeMap<u32, OnchainRollup>>>>,
+}
+
+impl OnchainRollups {
+    /// Whether the given rollup is registered on L1, or may be as the
+    /// rollups were never synced.
+    pub(crate) fn may_be_registered(&self, rollup_id: u32) -> bool {
+        self.read()
+            .as_ref()
+            .is_none_or(|rollups| rollups.contains_key(&rollup_id))
+    }
+
+    /// Get the given rollup, if synced.
+    pub(crate) fn get(&self, rollup_id: u32) -> Option<OnchainRollup> {
+        self.read()
+            .as_ref()
+            .and_then(|rollups| rollups.get(&rollup_id).copied())
+    }
+
+    /// Replace the synced rollups with the given ones.
+    fn replace(&self, rollups: BTreeMap<u32, OnchainRollup>) {
+        let mut synced = self.write();
+        if let Some(previous) = synced.as_ref() {
+            for (rollup_id, rollup) in &rollups {
+                log_changes(*rollup_id, previous.get(rollup_id), rollup);
+            }
+        }
+        *synced = Some(rollups);
+    }
+
+    /// Insert or replace the given rollup.
+    fn upsert(&self, rollup_id: u32, rollup: OnchainRollup) {
+        let mut synced = self.write();
+        let rollups = synced.get_or_insert_with(BTreeMap::new);
+        log_changes(rollup_id, rollups.get(&rollup_id), &rollup);
+        rollups.insert(rollup_id, rollup);
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Option<BTreeMap<u32, OnchainRollup>>> {
+        self.rollups.read().expect("Onchain rollups lock poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Option<BTreeMap<u32, OnchainRollup>>> {
+        self.rollups.write().expect("Onchain rollups lock poisoned")
+    }
+}
+
+fn log_changes(rollup_id: u32, previous: Option<&OnchainRollup>, rollup: &OnchainRollup) {
+    match previous {
+        None => info!(
+            "Rollup {rollup_id} registered on L1 at {:?}, chain id {}",
+            rollup.rollup_contract, rollup.chain_id
+        ),
+        Some(previous) if previous != rollup => info!(
+            "Rollup {rollup_id} updated on L1: verifier {:?}, fork id {}, trusted sequencer {:?}",
+            rollup.verifier, rollup.fork_id, rollup.trusted_sequencer
+        ),
+        Some(_) => {}
+    }
+}
+
+/// Errors that can occur while syncing the rollups.
+#[derive(Error, Debug)]
+pub(crate) enum RollupSyncError<RpcProvider: Middleware> {
+    #[error("middleware error: {0}")]
+    ProviderError(RpcProvider::Error),
+    #[error("contract error: {0}")]
+    ContractError(#[from] ContractError<RpcProvider>),
+}
+
+/// Background task syncing the rollups registered on L1 into
+/// [`OnchainRollups`].
+pub(crate) struct RollupSync<RpcProvider> {
+    rpc: Arc<RpcProvider>,
+    l1: L1RpcClient<RpcProvider>,
+    rollups: OnchainRollups,
+    config: RollupSyncConfig,
+}
+
+impl<RpcProvider> RollupSync<RpcProvider>
+where
+    RpcProvider: Middleware + 'static,
+{
+    pub(crate) fn new(
+        rpc: Arc<RpcProvider>,
+        rollup_manager_contract: Address,
+        rollups: OnchainRollups,
+        config: RollupSyncConfig,
+    ) -> Self {
+        Self {
+            l1: L1RpcClient::new(rpc.clone(), rollup_manager_contract),
+            rpc,
+            rollups,
+            config,
+        }
+    }
+
+    /// Sync the rollups until cancelled.
+    pub(crate) async fn run(self, cancellation_token: CancellationToken) {
+        let mut poll = interval(self.config.poll_interval);
+        poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut resync = interval(self.config.resync_interval);
+        resync.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        // The last block whose events were synced.
+        let mut last_block = None;
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    debug!("Rollup sync cancelled by token");
+                    break;
+                }
+                _ = resync.tick() => match self.sync_all().await {
+                    Ok(block) => last_block = Some(block),
+                    Err(error) => error!("Failed to sync the rollups registered on L1: {error}"),
+                },
+                _ = poll.tick(), if last_block.is_some() => {
+                    match self.sync_updates(last_block.unwrap_or_default()).await {
+                        Ok(block) => last_block = Some(block),
+                        Err(error) => error!("Failed to sync the rollups updated on L1: {error}"),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fetch every rollup registered on L1, returning the block the events
+    /// are to be synced from.
+    async fn sync_all(&self) -> Result<u64, RollupSyncError<RpcProvider>> {
+        let block = self.latest_block().await?;
+
+        let rollup_count = self.l1.get_rollup_count().await?;
+        let mut rollups = BTreeMap::new();
+        for rollup_id in 1..=rollup_count {
+            rollups.insert(rollup_id, self.fetch(rollup_id).await?);
+        }
+        debug!("Synced the {rollup_count} rollups registered on L1");
+
+        self.rollups.replace(rollups);
+
+        Ok(block)
+    }
+
+    /// Fetch the rollups created, added or updated after the given block,
+    /// returning the last block synced.
+    async fn sync_updates(&self, last_block: u64) -> Result<u64, RollupSyncError<RpcProvider>> {
+        let latest_block = self.latest_block().await?;
+        if latest_block <= last_block {
+            return Ok(last_block);
+        }
+
+        for blocks in block_ranges(last_block + 1, latest_block, self.config.max_block_range) {
+            for rollup_id in self
+                .l1
+                .get_updated_rollups(*blocks.start(), *blocks.end())
+                .await?
+            {
+                self.rollups.upsert(rollup_id, self.fetch(rollup_id).await?);
+            }
+        }
+
+        Ok(latest_block)
+    }
+
+    async fn fetch(&self, rollup_id: u32) -> Result<OnchainRollup, RollupSyncError<RpcProvider>> {
+        let metadata = self.l1.get_rollup_metadata(rollup_id).await?;
+        let trusted_sequencer = PolygonZkEvm::new(metadata.rollup_contract, self.rpc.clone())
+            .trusted_sequencer()
+            .await?;
+
+        Ok(OnchainRollup {
+            rollup_contract: metadata.rollup_contract,
+            chain_id: metadata.chain_id,
+            verifier: metadata.verifier,
+            fork_id: metadata.fork_id,
+            trusted_sequencer,
+        })
+    }
+
+    async fn latest_block(&self) -> Result<u64, RollupSyncError<RpcProvider>> {
+        Ok(self
+            .rpc
+            .get_block_number()
+            .await
+            .map_err(RollupSyncError::ProviderError)?
+            .as_u64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rollup(trusted_sequencer: u64) -> OnchainRollup {
+        OnchainRollup {
+            rollup_contract: Address::from_low_u64_be(1),
+            chain_id: 1001,
+            verifier: Address::from_low_u64_be(2),
+            fork_id: 9,
+            trusted_sequencer: Address::from_low_u64_be(trusted_sequencer),
+        }
+    }
+
+    #[test]
+    fn rollups_are_gated_once_synced() {
+        let rollups = OnchainRollups::default();
+
+        // Every rollup may be registered until the first sync.
+        assert!(rollups.may_be_registered(1));
+        assert!(rollups.may_be_registered(2));
+
+        rollups.replace(BTreeMap::from([(1, rollup(3))]));
+        assert!(rollups.may_be_registered(1));
+        assert!(!rollups.may_be_registered(2));
+
+        rollups.upsert(2, rollup(4));
+        rollups.upsert(1, rollup(5));
+        assert!(rollups.may_be_registered(2));
+        assert_eq!(
+            rollups.get(1).unwrap().trusted_sequencer,
+            Address::from_low_u64_be(5)
+        );
+    }
+}
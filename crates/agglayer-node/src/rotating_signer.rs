@@ -0,0 +1,336 @@
+//! Signing of the settlement transactions, with a signer rotated at runtime.
+//!
+//! The [`SignerMiddleware`] of ethers pins its signer, and its address, for
+//! its whole lifetime, while the L1 transports can't be cloned to build a new
+//! one. [`RotatingSigner`] signs the transactions the same way, with a signer
+//! that can be switched at any time, along with the signers dedicated to the
+//! settlements of some rollups.
+//!
+//! [`SignerMiddleware`]: ethers::middleware::SignerMiddleware
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use async_trait::async_trait;
+use ethers::{
+    middleware::signer::SignerMiddlewareError,
+    providers::{Middleware, PendingTransaction},
+    signers::Signer,
+    types::{
+        transaction::{eip2718::TypedTransaction, eip2930::AccessListWithGasUsed},
+        Address, BlockId, Bytes, Chain, Signature, TransactionRequest, U256,
+    },
+};
+
+/// A middleware signing the transactions with a signer that can be rotated.
+///
+/// The transactions are sent from the current signer unless they're sent
+/// from another one of the signers, and are signed by the signer they're
+/// sent from.
+#[derive(Debug)]
+pub(crate) struct RotatingSigner<M, S> {
+    inner: M,
+    signer: RwLock<Arc<S>>,
+    /// The other signers, by address.
+    signers: HashMap<Address, Arc<S>>,
+}
+
+impl<M, S> RotatingSigner<M, S> {
+    pub(crate) fn new(inner: M, signer: S) -> Self {
+        Self {
+            inner,
+            signer: RwLock::new(Arc::new(signer)),
+            signers: HashMap::new(),
+        }
+    }
+
+    /// Returns the current signer.
+    pub(crate) fn signer(&self) -> Arc<S> {
+        self.signer.read().expect("Signer lock poisoned").clone()
+    }
+
+    /// Sign the next transactions with the given signer, returning the
+    /// previous one.
+    pub(crate) fn rotate(&self, signer: S) -> Arc<S> {
+        std::mem::replace(
+            &mut *self.signer.write().expect("Signer lock poisoned"),
+            Arc::new(signer),
+        )
+    }
+}
+
+impl<M, S> RotatingSigner<M, S>
+where
+    M: Middleware,
+    S: Signer,
+{
+    /// Also sign the transactions sent from the address of the given signer.
+    pub(crate) fn with_signer(mut self, signer: S) -> Self {
+        self.signers.insert(signer.address(), Arc::new(signer));
+        self
+    }
+
+    /// Returns the address of the current signer.
+    pub(crate) fn address(&self) -> Address {
+        self.signer().address()
+    }
+
+    /// Returns the signer of the given address, if any.
+    fn signer_of(&self, address: Address) -> Option<Arc<S>> {
+        let signer = self.signer();
+        if signer.address() == address {
+            return Some(signer);
+        }
+        self.signers.get(&address).cloned()
+    }
+
+    fn set_tx_from_if_none(&self, tx: &TypedTransaction) -> TypedTransaction {
+        let mut tx = tx.clone();
+        if tx.from().is_none() {
+            tx.set_from(self.address());
+        }
+        tx
+    }
+}
+
+/// Signs the given transaction with the given signer, returning its RLP
+/// encoding.
+async fn sign_transaction<M: Middleware, S: Signer>(
+    signer: &S,
+    mut tx: TypedTransaction,
+) -> Result<Bytes, SignerMiddlewareError<M, S>> {
+    let chain_id = signer.chain_id();
+    match tx.chain_id() {
+        Some(id) if id.as_u64() != chain_id => return Err(SignerMiddlewareError::DifferentChainID),
+        None => {
+            tx.set_chain_id(chain_id);
+        }
+        _ => {}
+    }
+
+    let signature = signer
+        .sign_transaction(&tx)
+        .await
+        .map_err(SignerMiddlewareError::SignerError)?;
+
+    Ok(tx.rlp_signed(&signature))
+}
+
+#[async_trait]
+impl<M, S> Middleware for RotatingSigner<M, S>
+where
+    M: Middleware,
+    S: Signer,
+{
+    type Error = SignerMiddlewareError<M, S>;
+    type Provider = M::Provider;
+    type Inner = M;
+
+    fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    fn default_sender(&self) -> Option<Address> {
+        Some(self.address())
+    }
+
+    async fn is_signer(&self) -> bool {
+        true
+    }
+
+    async fn sign_transaction(
+        &self,
+        tx: &TypedTransaction,
+        from: Address,
+    ) -> Result<Signature, Self::Error> {
+        self.signer_of(from)
+            .unwrap_or_else(|| self.signer())
+            .sign_transaction(tx)
+            .await
+            .map_err(SignerMiddlewareError::SignerError)
+    }
+
+    async fn fill_transaction(
+        &self,
+        tx: &mut TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<(), Self::Error> {
+        let from = match tx.from() {
+            Some(from) => *from,
+            None => self.address(),
+        };
+        tx.set_from(from);
+
+        if tx.chain_id().is_none() {
+            let signer = self.signer_of(from).unwrap_or_else(|| self.signer());
+            tx.set_chain_id(signer.chain_id());
+        }
+
+        // The known chains not supporting EIP-1559 get legacy transactions.
+        if let Some(chain_id) = tx.chain_id() {
+            if Chain::try_from(chain_id.as_u64())
+                .unwrap_or_default()
+                .is_legacy()
+            {
+                if let TypedTransaction::Eip1559(inner) = tx {
+                    let legacy: TransactionRequest = inner.clone().into();
+                    *tx = TypedTransaction::Legacy(legacy);
+                }
+            }
+        }
+
+        if tx.nonce().is_none() {
+            let nonce = self.get_transaction_count(from, block).await?;
+            tx.set_nonce(nonce);
+        }
+
+        self.inner
+            .fill_transaction(tx, block)
+            .await
+            .map_err(SignerMiddlewareError::MiddlewareError)
+    }
+
+    async fn send_transaction<T: Into<TypedTransaction> + Send + Sync>(
+        &self,
+        tx: T,
+        block: Option<BlockId>,
+    ) -> Result<PendingTransaction<'_, Self::Provider>, Self::Error> {
+        let mut tx = tx.into();
+        if tx.from().is_none() {
+            tx.set_from(self.address());
+        }
+
+        self.fill_transaction(&mut tx, block).await?;
+
+        // Only the transactions of the signers are signed, the others are left
+        // to the inner middleware.
+        let Some(signer) = tx.from().and_then(|from| self.signer_of(*from)) else {
+            return self
+                .inner
+                .send_transaction(tx, block)
+                .await
+                .map_err(SignerMiddlewareError::MiddlewareError);
+        };
+
+        let signed_tx = sign_transaction(signer.as_ref(), tx).await?;
+
+        self.inner
+            .send_raw_transaction(signed_tx)
+            .await
+            .map_err(SignerMiddlewareError::MiddlewareError)
+    }
+
+    async fn sign<T: Into<Bytes> + Send + Sync>(
+        &self,
+        data: T,
+        _: &Address,
+    ) -> Result<Signature, Self::Error> {
+        self.signer()
+            .sign_message(data.into())
+            .await
+            .map_err(SignerMiddlewareError::SignerError)
+    }
+
+    async fn estimate_gas(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<U256, Self::Error> {
+        let tx = self.set_tx_from_if_none(tx);
+        self.inner
+            .estimate_gas(&tx, block)
+            .await
+            .map_err(SignerMiddlewareError::MiddlewareError)
+    }
+
+    async fn create_access_list(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<AccessListWithGasUsed, Self::Error> {
+        let tx = self.set_tx_from_if_none(tx);
+        self.inner
+            .create_access_list(&tx, block)
+            .await
+            .map_err(SignerMiddlewareError::MiddlewareError)
+    }
+
+    async fn call(
+        &self,
+        tx: &TypedTransaction,
+        block: Option<BlockId>,
+    ) -> Result<Bytes, Self::Error> {
+        let tx = self.set_tx_from_if_none(tx);
+        self.inner
+            .call(&tx, block)
+            .await
+            .map_err(SignerMiddlewareError::MiddlewareError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        providers::Provider,
+        signers::LocalWallet,
+        types::{Eip1559TransactionRequest, H256},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn transactions_are_signed_by_the_current_signer() {
+        let (provider, mock) = Provider::mocked();
+        let first = LocalWallet::new(&mut ethers::core::rand::thread_rng()).with_chain_id(1u64);
+        let second = LocalWallet::new(&mut ethers::core::rand::thread_rng()).with_chain_id(1u64);
+        let rpc = RotatingSigner::new(provider, first.clone());
+
+        let tx = Eip1559TransactionRequest::new()
+            .to(Address::from_low_u64_be(1))
+            .gas(21_000)
+            .max_fee_per_gas(2)
+            .max_priority_fee_per_gas(1)
+            .nonce(0);
+
+        for wallet in [first.clone(), second.clone()] {
+            if wallet.address() == second.address() {
+                assert_eq!(rpc.rotate(second.clone()).address(), first.address());
+            }
+            assert_eq!(rpc.default_sender(), Some(wallet.address()));
+
+            mock.push::<H256, _>(H256::zero()).unwrap();
+            rpc.send_transaction(tx.clone(), None).await.unwrap();
+
+            let signed: TypedTransaction = tx.clone().from(wallet.address()).chain_id(1).into();
+            let signature = wallet.sign_transaction_sync(&signed).unwrap();
+            mock.assert_request("eth_sendRawTransaction", [signed.rlp_signed(&signature)])
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn transactions_are_signed_by_the_signer_they_are_sent_from() {
+        let (provider, mock) = Provider::mocked();
+        let default = LocalWallet::new(&mut ethers::core::rand::thread_rng()).with_chain_id(1u64);
+        let dedicated = LocalWallet::new(&mut ethers::core::rand::thread_rng()).with_chain_id(1u64);
+        let rpc = RotatingSigner::new(provider, default.clone()).with_signer(dedicated.clone());
+
+        let tx = Eip1559TransactionRequest::new()
+            .to(Address::from_low_u64_be(1))
+            .from(dedicated.address())
+            .gas(21_000)
+            .max_fee_per_gas(2)
+            .max_priority_fee_per_gas(1)
+            .nonce(0);
+
+        mock.push::<H256, _>(H256::zero()).unwrap();
+        rpc.send_transaction(tx.clone(), None).await.unwrap();
+
+        let signed: TypedTransaction = tx.chain_id(1).into();
+        let signature = dedicated.sign_transaction_sync(&signed).unwrap();
+        mock.assert_request("eth_sendRawTransaction", [signed.rlp_signed(&signature)])
+            .unwrap();
+        assert_eq!(rpc.default_sender(), Some(default.address()));
+    }
+}
@@ -0,0 +1,153 @@
+//! HTTP access logging for the RPC server.
+//!
+//! Access logs are emitted under the `agglayer::access` tracing target, so
+//! that they can be enabled independently of the rest of the node logs.
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use agglayer_config::AccessLogConfig;
+use hyper::body::Body as _;
+use jsonrpsee::server::{HttpRequest, HttpResponse};
+use tower::{Layer, Service};
+use tracing::info;
+
+use super::{request_id::RequestId, PeerAddr};
+
+/// Tower layer producing access logs for a sample of the requests.
+#[derive(Clone, Debug)]
+pub(crate) struct AccessLogLayer {
+    sampler: Option<Arc<Sampler>>,
+}
+
+impl AccessLogLayer {
+    /// Create a new [`AccessLogLayer`]. If no configuration is given, access
+    /// logs are disabled.
+    pub(crate) fn new(config: Option<&AccessLogConfig>) -> Self {
+        Self {
+            sampler: config.map(|config| Arc::new(Sampler::new(config.sample_rate))),
+        }
+    }
+}
+
+impl<S> Layer<S> for AccessLogLayer {
+    type Service = AccessLog<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AccessLog {
+            inner,
+            sampler: self.sampler.clone(),
+        }
+    }
+}
+
+/// Tower service producing access logs for a sample of the requests.
+///
+/// Each log records the method, the peer address, the request ID, the latency,
+/// the response status and the size of the request and response bodies, as
+/// advertised by the `Content-Length` header and the response body
+/// respectively.
+#[derive(Clone, Debug)]
+pub(crate) struct AccessLog<S> {
+    inner: S,
+    sampler: Option<Arc<Sampler>>,
+}
+
+impl<S, B> Service<HttpRequest<B>> for AccessLog<S>
+where
+    S: Service<HttpRequest<B>, Response = HttpResponse>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest<B>) -> Self::Future {
+        if !self
+            .sampler
+            .as_ref()
+            .is_some_and(|sampler| sampler.sample())
+        {
+            return Box::pin(self.inner.call(request));
+        }
+
+        let method = request.method().clone();
+        let path = request.uri().path().to_string();
+        let peer = request
+            .extensions()
+            .get::<PeerAddr>()
+            .map(|peer| peer.0.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let request_id = request
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| id.to_string())
+            .unwrap_or_default();
+        let request_size = request
+            .headers()
+            .get(hyper::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or_default();
+
+        let start = Instant::now();
+        let response = self.inner.call(request);
+
+        Box::pin(async move {
+            let response = response.await?;
+
+            info!(
+                target: "agglayer::access",
+                %method,
+                path,
+                peer,
+                request_id,
+                status = response.status().as_u16(),
+                latency_ms = start.elapsed().as_millis() as u64,
+                request_size,
+                response_size = response.body().size_hint().exact(),
+                "{method} {path} {}",
+                response.status()
+            );
+
+            Ok(response)
+        })
+    }
+}
+
+/// Deterministic sampler selecting a fraction of the requests.
+///
+/// The `n`-th request is sampled whenever `floor((n + 1) * rate)` moves past
+/// `floor(n * rate)`, which spreads the sampled requests evenly without
+/// requiring a random number generator.
+#[derive(Debug)]
+struct Sampler {
+    rate: f64,
+    count: AtomicU64,
+}
+
+impl Sampler {
+    fn new(rate: f64) -> Self {
+        Self {
+            rate: rate.clamp(0.0, 1.0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    fn sample(&self) -> bool {
+        let n = self.count.fetch_add(1, Ordering::Relaxed) as f64;
+
+        ((n + 1.0) * self.rate).floor() > (n * self.rate).floor()
+    }
+}
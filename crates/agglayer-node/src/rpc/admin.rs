@@ -0,0 +1,282 @@
+//! The admin RPC server, onboarding rollups, controlling the admission of the
+//! new proofs and the access lists of the rollups at runtime, rotating the
+//! settlement signer and exporting the audit log.
+use std::{net::SocketAddr, sync::Arc};
+
+use agglayer_config::{deserialize_auth, AuthConfig, RpcConfig};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    server::{ServerBuilder, ServerHandle},
+    types::ErrorObjectOwned,
+};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use super::{
+    auth::AuthLayer, internal_error, invalid_params_error, request_signature::RequestSignatureLayer,
+};
+use crate::{
+    access_list::{AccessListError, AccessLists, RollupAccessList},
+    admission::Admission,
+    audit::{AuditRecord, Auditor, MAX_EXPORTED_ENTRIES},
+    kernel::{ErrorKind, RotatedSigner, SignerRotation, SignerRotationError},
+    registry::{RegistryError, RollupConfig, RollupRegistry},
+};
+
+#[rpc(server, namespace = "admin")]
+trait Admin {
+    #[method(name = "addRollup")]
+    async fn add_rollup(&self, rollup: RollupConfig) -> RpcResult<()>;
+
+    #[method(name = "updateRollup")]
+    async fn update_rollup(&self, rollup: RollupConfig) -> RpcResult<()>;
+
+    #[method(name = "pause")]
+    async fn pause(&self) -> RpcResult<()>;
+
+    #[method(name = "resume")]
+    async fn resume(&self) -> RpcResult<()>;
+
+    #[method(name = "drain")]
+    async fn drain(&self) -> RpcResult<()>;
+
+    #[method(name = "getAccessLists")]
+    async fn get_access_lists(&self) -> RpcResult<AccessLists>;
+
+    #[method(name = "addToAllowlist")]
+    async fn add_to_allowlist(&self, rollup_id: u32) -> RpcResult<()>;
+
+    #[method(name = "removeFromAllowlist")]
+    async fn remove_from_allowlist(&self, rollup_id: u32) -> RpcResult<()>;
+
+    #[method(name = "addToDenylist")]
+    async fn add_to_denylist(&self, rollup_id: u32) -> RpcResult<()>;
+
+    #[method(name = "removeFromDenylist")]
+    async fn remove_from_denylist(&self, rollup_id: u32) -> RpcResult<()>;
+
+    #[method(name = "rotateSigner")]
+    async fn rotate_signer(&self, auth: SignerAuth) -> RpcResult<RotatedSigner>;
+
+    #[method(name = "exportAuditLog")]
+    async fn export_audit_log(&self, from: u64, to: u64) -> RpcResult<Vec<AuditRecord>>;
+}
+
+/// The configuration of a settlement signer, in the form of the `auth`
+/// section of the configuration, e.g. `{"gcpkms": {"ProjectId": ...}}`.
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct SignerAuth(#[serde(deserialize_with = "deserialize_auth")] AuthConfig);
+
+/// The admin RPC service implementation.
+pub(crate) struct AdminImpl {
+    rollups: RollupRegistry,
+    admission: Admission,
+    access_list: RollupAccessList,
+    auditor: Auditor,
+    signer_rotation: Option<Arc<dyn SignerRotation>>,
+}
+
+impl AdminImpl {
+    pub(crate) fn new(
+        rollups: RollupRegistry,
+        admission: Admission,
+        access_list: RollupAccessList,
+        auditor: Auditor,
+    ) -> Self {
+        Self {
+            rollups,
+            admission,
+            access_list,
+            auditor,
+            signer_rotation: None,
+        }
+    }
+
+    /// Rotate the settlement signer with the given [`SignerRotation`].
+    pub(crate) fn with_signer_rotation(mut self, rotation: Arc<dyn SignerRotation>) -> Self {
+        self.signer_rotation = Some(rotation);
+        self
+    }
+
+    /// Serve the admin RPC on the given address, enforcing the request
+    /// signatures and the credentials of the given RPC configuration.
+    pub(crate) async fn start(
+        self,
+        addr: SocketAddr,
+        config: &RpcConfig,
+    ) -> anyhow::Result<ServerHandle> {
+        let middleware = tower::ServiceBuilder::new()
+            .layer(RequestSignatureLayer::new(
+                &config.request_signers,
+                config.max_request_body_size,
+            ))
+            .layer(AuthLayer::new(config));
+        let server = ServerBuilder::new()
+            .set_http_middleware(middleware)
+            .build(addr)
+            .await?;
+
+        info!("Admin RPC listening on {addr}");
+
+        Ok(server.start(self.into_rpc()))
+    }
+}
+
+fn registry_error(error: RegistryError) -> ErrorObjectOwned {
+    match error {
+        RegistryError::AlreadyRegistered(_) | RegistryError::NotRegistered(_) => {
+            invalid_params_error(ErrorKind::InvalidRollup, error.to_string())
+        }
+        RegistryError::Io(_) | RegistryError::Malformed(_) => {
+            error!("Failed to persist the rollup registry: {error}");
+            internal_error(ErrorKind::Internal, error.to_string())
+        }
+    }
+}
+
+fn access_list_error(error: AccessListError) -> ErrorObjectOwned {
+    error!("Failed to persist the access lists: {error}");
+    internal_error(ErrorKind::Internal, error.to_string())
+}
+
+fn signer_rotation_error(error: SignerRotationError) -> ErrorObjectOwned {
+    match error {
+        SignerRotationError::Load(_) => {
+            error!("Failed to load the new settlement signer: {error}");
+            internal_error(ErrorKind::Internal, error.to_string())
+        }
+        SignerRotationError::Sign(_) | SignerRotationError::WrongAddress { .. } => {
+            invalid_params_error(ErrorKind::InvalidSignature, error.to_string())
+        }
+    }
+}
+
+#[async_trait]
+impl AdminServer for AdminImpl {
+    async fn add_rollup(&self, rollup: RollupConfig) -> RpcResult<()> {
+        let rollup_id = rollup.rollup_id;
+        self.rollups.add(rollup).map_err(registry_error)?;
+
+        info!("Registered rollup {rollup_id}");
+
+        Ok(())
+    }
+
+    async fn update_rollup(&self, rollup: RollupConfig) -> RpcResult<()> {
+        let rollup_id = rollup.rollup_id;
+        self.rollups.update(rollup).map_err(registry_error)?;
+
+        info!("Updated rollup {rollup_id}");
+
+        Ok(())
+    }
+
+    async fn pause(&self) -> RpcResult<()> {
+        if self.admission.pause() {
+            info!("Paused the admission of new proofs");
+        }
+
+        Ok(())
+    }
+
+    async fn resume(&self) -> RpcResult<()> {
+        if self.admission.resume() {
+            info!("Resumed the admission of new proofs");
+        }
+
+        Ok(())
+    }
+
+    async fn drain(&self) -> RpcResult<()> {
+        info!(
+            "Draining the {} proofs in flight",
+            self.admission.in_flight()
+        );
+        self.admission.drain().await;
+        info!("Drained the proofs in flight");
+
+        Ok(())
+    }
+
+    async fn get_access_lists(&self) -> RpcResult<AccessLists> {
+        Ok(self.access_list.lists())
+    }
+
+    async fn add_to_allowlist(&self, rollup_id: u32) -> RpcResult<()> {
+        if self
+            .access_list
+            .allow(rollup_id)
+            .map_err(access_list_error)?
+        {
+            info!("Added rollup {rollup_id} to the allowlist");
+        }
+
+        Ok(())
+    }
+
+    async fn remove_from_allowlist(&self, rollup_id: u32) -> RpcResult<()> {
+        if self
+            .access_list
+            .disallow(rollup_id)
+            .map_err(access_list_error)?
+        {
+            info!("Removed rollup {rollup_id} from the allowlist");
+        }
+
+        Ok(())
+    }
+
+    async fn add_to_denylist(&self, rollup_id: u32) -> RpcResult<()> {
+        if self
+            .access_list
+            .deny(rollup_id)
+            .map_err(access_list_error)?
+        {
+            warn!("Added rollup {rollup_id} to the denylist, its submissions are rejected");
+        }
+
+        Ok(())
+    }
+
+    async fn remove_from_denylist(&self, rollup_id: u32) -> RpcResult<()> {
+        if self
+            .access_list
+            .undeny(rollup_id)
+            .map_err(access_list_error)?
+        {
+            info!("Removed rollup {rollup_id} from the denylist");
+        }
+
+        Ok(())
+    }
+
+    async fn rotate_signer(&self, SignerAuth(auth): SignerAuth) -> RpcResult<RotatedSigner> {
+        let Some(rotation) = &self.signer_rotation else {
+            return Err(invalid_params_error(
+                ErrorKind::NotFound,
+                "the signer rotation is not available",
+            ));
+        };
+
+        rotation.rotate(&auth).await.map_err(signer_rotation_error)
+    }
+
+    async fn export_audit_log(&self, from: u64, to: u64) -> RpcResult<Vec<AuditRecord>> {
+        if !self.auditor.is_enabled() {
+            return Err(invalid_params_error(
+                ErrorKind::NotFound,
+                "the audit log is not enabled",
+            ));
+        }
+        // Export the larger ranges by pages, the callers resuming after the
+        // last exported entry.
+        let to = to.min(from.saturating_add(MAX_EXPORTED_ENTRIES - 1));
+
+        self.auditor.export(from, to).map_err(|error| {
+            error!("Failed to export the audit log from {from} to {to}: {error}");
+            internal_error(ErrorKind::Internal, error.to_string())
+        })
+    }
+}
@@ -0,0 +1,242 @@
+//! Authentication of the callers of the RPC server.
+//!
+//! Callers authenticate with either an API key, carried by the `x-api-key`
+//! header, or a JWT bearer token, carried by the `authorization` header. Each
+//! configured API key is bound to a set of rollup ids, while each token is
+//! bound to the rollup ids of its `rollupIds` claim. The HTTP middleware
+//! resolves the credentials of a request into a [`RollupScope`] and attaches
+//! it to the request extensions, so that the submission endpoints can reject
+//! proofs for rollups outside of the caller's scope.
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use agglayer_config::{JwtConfig, RpcConfig};
+use hyper::{
+    header::{HeaderName, AUTHORIZATION},
+    StatusCode,
+};
+use jsonrpsee::server::{HttpBody, HttpRequest, HttpResponse};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use thiserror::Error;
+use tower::{Layer, Service};
+
+/// The header carrying the API key.
+pub(crate) const API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+
+/// The set of rollups a caller is allowed to submit proofs for.
+///
+/// A request without any [`RollupScope`] in its extensions is not subject to
+/// authentication.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RollupScope(Arc<HashSet<u32>>);
+
+impl RollupScope {
+    /// Check if the given rollup id is part of this scope.
+    pub(crate) fn allows(&self, rollup_id: u32) -> bool {
+        self.0.contains(&rollup_id)
+    }
+}
+
+/// The credentials carried by a request are invalid.
+#[derive(Error, Debug)]
+pub(crate) enum InvalidCredentials {
+    #[error("invalid API key")]
+    ApiKey,
+    #[error("invalid bearer token: {0}")]
+    Token(#[from] jsonwebtoken::errors::Error),
+}
+
+/// The claims of a JWT bearer token.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Claims {
+    /// The rollup ids the bearer is allowed to submit proofs for.
+    rollup_ids: HashSet<u32>,
+}
+
+/// The verification of the JWT bearer tokens.
+struct Tokens {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl Tokens {
+    fn new(config: &JwtConfig) -> Self {
+        let mut validation = Validation::new(Algorithm::HS256);
+        if let Some(issuer) = &config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        Self {
+            key: DecodingKey::from_secret(config.secret.as_bytes()),
+            validation,
+        }
+    }
+
+    /// Verify the given token, returning the [`RollupScope`] of its claims.
+    fn verify(&self, token: &str) -> Result<RollupScope, jsonwebtoken::errors::Error> {
+        let claims = jsonwebtoken::decode::<Claims>(token, &self.key, &self.validation)?.claims;
+
+        Ok(RollupScope(Arc::new(claims.rollup_ids)))
+    }
+}
+
+/// The configured API keys and JWT verification, resolving the credentials of
+/// the callers into their [`RollupScope`].
+#[derive(Clone, Default)]
+pub(crate) struct Credentials {
+    api_keys: Arc<HashMap<String, RollupScope>>,
+    tokens: Option<Arc<Tokens>>,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("api_keys", &self.api_keys.len())
+            .field("jwt", &self.tokens.is_some())
+            .finish()
+    }
+}
+
+impl Credentials {
+    pub(crate) fn new(config: &RpcConfig) -> Self {
+        let api_keys = config
+            .api_keys
+            .iter()
+            .map(|api_key| {
+                let scope = RollupScope(Arc::new(api_key.rollup_ids.iter().copied().collect()));
+
+                (api_key.key.clone(), scope)
+            })
+            .collect();
+
+        Self {
+            api_keys: Arc::new(api_keys),
+            tokens: config.jwt.as_ref().map(|jwt| Arc::new(Tokens::new(jwt))),
+        }
+    }
+
+    /// Resolve the API key or the `authorization` header carried by a request
+    /// into its [`RollupScope`].
+    ///
+    /// - Requests without credentials are given an empty scope.
+    /// - Requests with an unknown API key or an invalid token are rejected.
+    /// - Bearer tokens are ignored if JWT verification isn't configured.
+    /// - If no credentials are configured, requests are given no scope at all.
+    pub(crate) fn resolve(
+        &self,
+        api_key: Option<&str>,
+        authorization: Option<&str>,
+    ) -> Result<Option<RollupScope>, InvalidCredentials> {
+        if self.api_keys.is_empty() && self.tokens.is_none() {
+            return Ok(None);
+        }
+
+        let token = authorization.and_then(|value| value.strip_prefix("Bearer "));
+        if let (Some(tokens), Some(token)) = (&self.tokens, token) {
+            return Ok(Some(tokens.verify(token.trim())?));
+        }
+
+        match api_key {
+            None => Ok(Some(RollupScope::default())),
+            Some(key) => self
+                .api_keys
+                .get(key)
+                .cloned()
+                .map(Some)
+                .ok_or(InvalidCredentials::ApiKey),
+        }
+    }
+}
+
+/// Tower layer resolving the credentials of the callers into
+/// [`RollupScope`]s.
+#[derive(Clone, Debug)]
+pub(crate) struct AuthLayer {
+    credentials: Credentials,
+}
+
+impl AuthLayer {
+    /// Create a new [`AuthLayer`] from the configured credentials.
+    pub(crate) fn new(config: &RpcConfig) -> Self {
+        Self {
+            credentials: Credentials::new(config),
+        }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = Auth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Auth {
+            inner,
+            credentials: self.credentials.clone(),
+        }
+    }
+}
+
+/// Tower service resolving the credentials of the callers into
+/// [`RollupScope`]s.
+///
+/// - Requests without credentials are given an empty scope.
+/// - Requests with invalid credentials are rejected with `401 Unauthorized`.
+/// - If no credentials are configured, requests are forwarded untouched.
+#[derive(Clone, Debug)]
+pub(crate) struct Auth<S> {
+    inner: S,
+    credentials: Credentials,
+}
+
+impl<S, B> Service<HttpRequest<B>> for Auth<S>
+where
+    S: Service<HttpRequest<B>, Response = HttpResponse>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest<B>) -> Self::Future {
+        let header = |name: &HeaderName| {
+            request
+                .headers()
+                .get(name)
+                .map(|value| value.to_str().unwrap_or_default())
+        };
+
+        match self
+            .credentials
+            .resolve(header(&API_KEY_HEADER), header(&AUTHORIZATION))
+        {
+            Ok(Some(scope)) => {
+                request.extensions_mut().insert(scope);
+            }
+            Ok(None) => {}
+            Err(error) => {
+                return Box::pin(async move {
+                    Ok(HttpResponse::builder()
+                        .status(StatusCode::UNAUTHORIZED)
+                        .body(HttpBody::from(error.to_string()))
+                        .expect("Unable to build unauthorized response"))
+                });
+            }
+        }
+
+        Box::pin(self.inner.call(request))
+    }
+}
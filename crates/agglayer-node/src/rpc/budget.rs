@@ -0,0 +1,222 @@
+//! Memory budget of the submissions pending verification or settlement.
+//!
+//! Every submission reserves its in-memory footprint for as long as it is
+//! being processed. Once the budget is exhausted, new submissions are either
+//! rejected or make room by evicting the oldest pending submissions of the
+//! same rollup, according to the configured [`EvictionPolicy`].
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use agglayer_config::{EvictionPolicy, PendingSubmissionsConfig};
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+
+/// The submission doesn't fit in the memory budget.
+#[derive(Error, Debug)]
+#[error(
+    "pending submissions memory budget exhausted ({used}/{limit} bytes in use, {size} requested)"
+)]
+pub(crate) struct BudgetExhausted {
+    pub(crate) used: usize,
+    pub(crate) limit: usize,
+    pub(crate) size: usize,
+}
+
+/// A submission holding a share of the budget.
+#[derive(Debug)]
+struct Pending {
+    rollup_id: u32,
+    size: usize,
+    /// Cancelled when the submission gets evicted.
+    evicted: CancellationToken,
+    /// Whether the submission may still be evicted.
+    evictable: bool,
+}
+
+#[derive(Debug, Default)]
+struct BudgetState {
+    /// The number of bytes reserved by the pending submissions.
+    used: usize,
+    /// The id of the next reservation, increasing in admission order.
+    next_id: u64,
+    pending: BTreeMap<u64, Pending>,
+}
+
+/// The memory budget shared by the pending submissions.
+#[derive(Clone, Debug)]
+pub(crate) struct SubmissionBudget {
+    limit: usize,
+    policy: EvictionPolicy,
+    state: Arc<Mutex<BudgetState>>,
+}
+
+impl SubmissionBudget {
+    pub(crate) fn new(config: &PendingSubmissionsConfig) -> Self {
+        Self {
+            limit: config.memory_budget,
+            policy: config.eviction_policy,
+            state: Default::default(),
+        }
+    }
+
+    /// Reserve `size` bytes for a submission of the given rollup, released
+    /// once the returned [`Reservation`] is dropped.
+    pub(crate) fn reserve(
+        &self,
+        rollup_id: u32,
+        size: usize,
+    ) -> Result<Reservation, BudgetExhausted> {
+        let mut state = self.lock();
+
+        let exceeding = (state.used + size).saturating_sub(self.limit);
+        if exceeding > 0 && self.policy == EvictionPolicy::EvictOldestPerRollup {
+            // Only evict if it makes enough room for the new submission.
+            let mut freed = 0;
+            let evicted: Vec<u64> = state
+                .pending
+                .iter()
+                .filter(|(_, pending)| pending.rollup_id == rollup_id && pending.evictable)
+                .take_while(|(_, pending)| {
+                    let needed = freed < exceeding;
+                    freed += pending.size;
+                    needed
+                })
+                .map(|(id, _)| *id)
+                .collect();
+
+            if freed >= exceeding {
+                for id in evicted {
+                    if let Some(pending) = state.pending.remove(&id) {
+                        state.used -= pending.size;
+                        pending.evicted.cancel();
+                    }
+                }
+            }
+        }
+
+        if state.used + size > self.limit {
+            return Err(BudgetExhausted {
+                used: state.used,
+                limit: self.limit,
+                size,
+            });
+        }
+
+        let id = state.next_id;
+        let evicted = CancellationToken::new();
+        state.next_id += 1;
+        state.used += size;
+        state.pending.insert(
+            id,
+            Pending {
+                rollup_id,
+                size,
+                evicted: evicted.clone(),
+                evictable: true,
+            },
+        );
+
+        Ok(Reservation {
+            budget: self.clone(),
+            id,
+            evicted,
+        })
+    }
+
+    /// The number of bytes reserved by the pending submissions.
+    #[cfg(test)]
+    fn used(&self) -> usize {
+        self.lock().used
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BudgetState> {
+        self.state.lock().expect("Submission budget lock poisoned")
+    }
+}
+
+/// The share of the budget reserved by a pending submission.
+#[derive(Debug)]
+pub(crate) struct Reservation {
+    budget: SubmissionBudget,
+    id: u64,
+    evicted: CancellationToken,
+}
+
+impl Reservation {
+    /// Resolve once the submission gets evicted to make room for a newer one.
+    pub(crate) async fn evicted(&self) {
+        self.evicted.cancelled().await
+    }
+
+    /// Prevent the submission from being evicted, once its settlement is
+    /// underway.
+    pub(crate) fn settling(&self) {
+        if let Some(pending) = self.budget.lock().pending.get_mut(&self.id) {
+            pending.evictable = false;
+        }
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        let mut state = self.budget.lock();
+
+        if let Some(pending) = state.pending.remove(&self.id) {
+            state.used -= pending.size;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agglayer_config::{EvictionPolicy, PendingSubmissionsConfig};
+
+    use super::SubmissionBudget;
+
+    fn budget(eviction_policy: EvictionPolicy) -> SubmissionBudget {
+        SubmissionBudget::new(&PendingSubmissionsConfig {
+            memory_budget: 100,
+            eviction_policy,
+        })
+    }
+
+    #[test]
+    fn reservations_are_released_on_drop() {
+        let budget = budget(EvictionPolicy::RejectNew);
+
+        let first = budget.reserve(1, 60).unwrap();
+        assert!(budget.reserve(1, 60).is_err());
+        assert_eq!(budget.used(), 60);
+
+        drop(first);
+        assert_eq!(budget.used(), 0);
+        assert!(budget.reserve(1, 60).is_ok());
+    }
+
+    #[tokio::test]
+    async fn evict_oldest_of_the_same_rollup() {
+        let budget = budget(EvictionPolicy::EvictOldestPerRollup);
+
+        let oldest = budget.reserve(1, 30).unwrap();
+        let other_rollup = budget.reserve(2, 30).unwrap();
+        let newer = budget.reserve(1, 30).unwrap();
+
+        let newest = budget.reserve(1, 30).unwrap();
+        oldest.evicted().await;
+        assert!(!newer.evicted.is_cancelled());
+        assert!(!other_rollup.evicted.is_cancelled());
+        assert_eq!(budget.used(), 90);
+
+        // Submissions being settled are never evicted.
+        newer.settling();
+        newest.settling();
+        assert!(budget.reserve(1, 30).is_err());
+
+        // Evicting the submissions of other rollups isn't allowed.
+        drop(oldest);
+        assert!(budget.reserve(3, 30).is_err());
+        assert_eq!(budget.used(), 90);
+    }
+}
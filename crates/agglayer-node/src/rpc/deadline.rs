@@ -0,0 +1,87 @@
+//! Client deadlines for the RPC server.
+//!
+//! Clients can bound the time they're willing to wait for a response by
+//! setting the `x-request-timeout` header, in milliseconds. The resulting
+//! [`Deadline`] is attached to the request extensions so that the RPC methods
+//! can give up on the work that would complete after the client stopped
+//! waiting.
+//!
+//! Client disconnections don't need any special handling: the in-flight
+//! request futures of a closed connection are dropped, which cancels the work
+//! they were driving.
+use std::{
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use hyper::header::HeaderName;
+use jsonrpsee::server::HttpRequest;
+use tokio::time::Instant;
+use tower::{Layer, Service};
+use tracing::debug;
+
+/// The header carrying the time the client is willing to wait for a response,
+/// in milliseconds.
+pub(crate) const REQUEST_TIMEOUT_HEADER: HeaderName = HeaderName::from_static("x-request-timeout");
+
+/// The instant after which the client is no longer waiting for a response.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Deadline(pub(crate) Instant);
+
+impl Deadline {
+    /// Resolve a request timeout, in milliseconds, into a [`Deadline`].
+    pub(crate) fn from_timeout(timeout: &str) -> Option<Self> {
+        let timeout = timeout.parse::<u64>().ok()?;
+
+        Instant::now()
+            .checked_add(Duration::from_millis(timeout))
+            .map(Deadline)
+    }
+}
+
+/// Tower layer resolving the request timeouts into [`Deadline`]s.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DeadlineLayer;
+
+impl<S> Layer<S> for DeadlineLayer {
+    type Service = DeadlineService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DeadlineService { inner }
+    }
+}
+
+/// Tower service resolving the request timeouts into [`Deadline`]s.
+///
+/// Requests without a timeout, or with a malformed one, are forwarded
+/// untouched.
+#[derive(Clone, Debug)]
+pub(crate) struct DeadlineService<S> {
+    inner: S,
+}
+
+impl<S, B> Service<HttpRequest<B>> for DeadlineService<S>
+where
+    S: Service<HttpRequest<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest<B>) -> Self::Future {
+        if let Some(value) = request.headers().get(REQUEST_TIMEOUT_HEADER) {
+            match value.to_str().ok().and_then(Deadline::from_timeout) {
+                Some(deadline) => {
+                    request.extensions_mut().insert(deadline);
+                }
+                None => debug!("Ignoring malformed {REQUEST_TIMEOUT_HEADER} header: {value:?}"),
+            }
+        }
+
+        self.inner.call(request)
+    }
+}
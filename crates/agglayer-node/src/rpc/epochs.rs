@@ -0,0 +1,136 @@
+//! Epoch changes streamed by `interop_subscribeEpochs`, and the Epoch
+//! configuration returned by `interop_getEpochConfiguration`.
+use agglayer_clock::{ClockConfiguration, ClockRef, Event};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The kind of an [`EpochChange`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum EpochChangeKind {
+    /// An Epoch is about to end, at `endBlock`.
+    Ending,
+    /// An Epoch ended, the next one started.
+    Ended,
+    /// An L1 reorg reverted the Epochs following `epoch`, they will end again.
+    Reverted,
+    /// The Clock started in the middle of `epoch`, the preceding Epochs ended
+    /// without being notified.
+    Resynced,
+    /// The subscription lagged behind the Clock up to `epoch`, some Epoch
+    /// changes were not notified.
+    Missed,
+}
+
+/// A change of Epoch of the agglayer Clock.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EpochChange {
+    pub(crate) kind: EpochChangeKind,
+    /// The Epoch that ended or is about to end, the Epoch the Clock is back in
+    /// after a revert, or the current Epoch after a resync.
+    pub(crate) epoch: u64,
+    /// The Epoch the Clock was in before a revert.
+    pub(crate) reverted_from: Option<u64>,
+    /// The first L1 Block height of the Epoch that ended.
+    pub(crate) start_block: Option<u64>,
+    /// The L1 Block height following the last one of the Epoch that ended, or
+    /// is about to end.
+    pub(crate) end_block: Option<u64>,
+    /// The time at which the Epoch that ended started, in seconds since the
+    /// unix epoch.
+    pub(crate) started_at: Option<i64>,
+    /// The time at which the Epoch ended, in seconds since the unix epoch.
+    pub(crate) ended_at: Option<i64>,
+}
+
+impl From<Event> for EpochChange {
+    fn from(event: Event) -> Self {
+        let change = |kind, epoch| EpochChange {
+            kind,
+            epoch,
+            reverted_from: None,
+            start_block: None,
+            end_block: None,
+            started_at: None,
+            ended_at: None,
+        };
+
+        match event {
+            Event::EpochEnding {
+                epoch,
+                ends_at_block,
+            } => EpochChange {
+                end_block: Some(ends_at_block),
+                ..change(EpochChangeKind::Ending, epoch)
+            },
+            Event::EpochEnded {
+                epoch,
+                block_range,
+                started_at,
+                ended_at,
+                ..
+            } => EpochChange {
+                start_block: Some(block_range.start),
+                end_block: Some(block_range.end),
+                started_at: Some(started_at.timestamp()),
+                ended_at: Some(ended_at.timestamp()),
+                ..change(EpochChangeKind::Ended, epoch)
+            },
+            Event::EpochReverted { from, to } => EpochChange {
+                reverted_from: Some(from),
+                ..change(EpochChangeKind::Reverted, to)
+            },
+            Event::Resynced { epoch } => change(EpochChangeKind::Resynced, epoch),
+            Event::MissedEpochs { epoch, .. } => change(EpochChangeKind::Missed, epoch),
+        }
+    }
+}
+
+/// The kind of Clock pacing the Epochs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ClockKind {
+    /// The Blocks are produced every second from a genesis datetime.
+    Time,
+    /// The Blocks are the L1 Blocks from a genesis Block.
+    Block,
+}
+
+/// The Epoch configuration of the agglayer Clock.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EpochConfiguration {
+    pub(crate) clock_type: ClockKind,
+    /// The time of the first Block of a time Clock, in seconds since the unix
+    /// epoch.
+    pub(crate) genesis_timestamp: Option<i64>,
+    /// The L1 Block number of the first Block of a block Clock.
+    pub(crate) genesis_block: Option<u64>,
+    /// The Epoch duration in Blocks.
+    pub(crate) epoch_duration: u64,
+    /// The current Epoch number.
+    pub(crate) current_epoch: u64,
+}
+
+impl From<&ClockRef> for EpochConfiguration {
+    fn from(clock_ref: &ClockRef) -> Self {
+        let configuration = clock_ref.configuration();
+        let (clock_type, genesis_timestamp, genesis_block) = match configuration {
+            ClockConfiguration::Time { genesis, .. } => {
+                (ClockKind::Time, Some(genesis.timestamp()), None)
+            }
+            ClockConfiguration::Block { genesis_block, .. } => {
+                (ClockKind::Block, None, Some(genesis_block))
+            }
+        };
+
+        EpochConfiguration {
+            clock_type,
+            genesis_timestamp,
+            genesis_block,
+            epoch_duration: configuration.epoch_duration().get(),
+            current_epoch: clock_ref.current_epoch(),
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! The gRPC server, serving the send and query operations of the `interop`
+//! namespace to the integrators preferring protobuf types.
+//!
+//! The operations are delegated to the [`AgglayerImpl`] serving the RPC server,
+//! so that both servers share the kernel and the state of the submissions.
+//! Callers authenticate with the same `x-api-key` metadata and bound their
+//! wait with the same `x-request-timeout` metadata.
+// The errors are tonic `Status`es, large by design.
+#![allow(clippy::result_large_err)]
+
+use std::net::SocketAddr;
+
+use agglayer_config::RpcConfig;
+use ethers::{
+    providers::Middleware,
+    types::{Signature, H256, U64},
+};
+use hyper::header::{HeaderName, AUTHORIZATION};
+use jsonrpsee::{types::ErrorObjectOwned, Extensions};
+use tokio::{net::TcpListener, task::JoinHandle};
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_util::sync::CancellationToken;
+use tonic::{transport::Server, Code, Request, Response, Status};
+use tracing::{error, info};
+
+use super::{
+    auth::{Credentials, API_KEY_HEADER},
+    deadline::{Deadline, REQUEST_TIMEOUT_HEADER},
+    AgglayerImpl, AgglayerServer, ErrorData, PeerAddr,
+};
+use crate::{
+    attestation::Attestation,
+    kernel::ErrorKind,
+    signed_tx::{Proof, ProofManifest, SignedTx, Zkp},
+    spend::{EpochRange, SpendReport},
+};
+
+#[allow(clippy::all)]
+pub(crate) mod proto {
+    tonic::include_proto!("agglayer.interop.v1");
+}
+
+use self::proto::interop_server::{Interop, InteropServer};
+
+/// The gRPC agglayer service implementation.
+pub(crate) struct GrpcImpl<Rpc> {
+    agglayer: AgglayerImpl<Rpc>,
+    credentials: Credentials,
+}
+
+impl<Rpc> GrpcImpl<Rpc> {
+    /// Create an instance of the gRPC agglayer service, sharing the given RPC
+    /// service.
+    ///
+    /// Request signatures cover raw HTTP bodies and can't be verified over
+    /// gRPC, the gRPC server is refused if they're required.
+    pub(crate) fn new(agglayer: AgglayerImpl<Rpc>, config: &RpcConfig) -> anyhow::Result<Self> {
+        if !config.request_signers.is_empty() {
+            anyhow::bail!("the gRPC server doesn't support the request signatures");
+        }
+
+        Ok(Self {
+            agglayer,
+            credentials: Credentials::new(config),
+        })
+    }
+
+    /// Resolve the metadata of the given request into the extensions expected
+    /// by the RPC service.
+    fn extensions<T>(&self, request: &Request<T>) -> Result<Extensions, Status> {
+        let metadata = request.metadata();
+        let mut extensions = Extensions::new();
+
+        let header = |name: &HeaderName| {
+            metadata
+                .get(name.as_str())
+                .map(|value| value.to_str().unwrap_or_default())
+        };
+        match self
+            .credentials
+            .resolve(header(&API_KEY_HEADER), header(&AUTHORIZATION))
+        {
+            Ok(Some(scope)) => {
+                extensions.insert(scope);
+            }
+            Ok(None) => {}
+            Err(error) => return Err(Status::unauthenticated(error.to_string())),
+        }
+
+        if let Some(deadline) = metadata
+            .get(REQUEST_TIMEOUT_HEADER.as_str())
+            .and_then(|value| value.to_str().ok())
+            .and_then(Deadline::from_timeout)
+        {
+            extensions.insert(deadline);
+        }
+
+        if let Some(peer) = request.remote_addr() {
+            extensions.insert(PeerAddr(peer));
+        }
+
+        Ok(extensions)
+    }
+}
+
+impl<Rpc> GrpcImpl<Rpc>
+where
+    Rpc: Middleware + 'static,
+{
+    /// Serve the gRPC service on the given address until cancelled.
+    pub(crate) async fn start(
+        self,
+        addr: SocketAddr,
+        cancellation_token: CancellationToken,
+    ) -> anyhow::Result<JoinHandle<()>> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("gRPC server listening on {addr}");
+
+        let server = Server::builder()
+            .add_service(InteropServer::new(self))
+            .serve_with_incoming_shutdown(
+                TcpListenerStream::new(listener),
+                cancellation_token.cancelled_owned(),
+            );
+
+        Ok(tokio::spawn(async move {
+            if let Err(error) = server.await {
+                error!("gRPC server failed: {error}");
+            }
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl<Rpc> Interop for GrpcImpl<Rpc>
+where
+    Rpc: Middleware + 'static,
+{
+    async fn send_tx(
+        &self,
+        request: Request<proto::SendTxRequest>,
+    ) -> Result<Response<proto::SendTxResponse>, Status> {
+        let extensions = self.extensions(&request)?;
+        let tx = request
+            .into_inner()
+            .tx
+            .ok_or_else(|| Status::invalid_argument("missing tx"))?
+            .try_into()?;
+
+        let hash = AgglayerServer::send_tx(&self.agglayer, &extensions, tx)
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(proto::SendTxResponse {
+            hash: hash.as_bytes().to_vec(),
+        }))
+    }
+
+    async fn get_tx_status(
+        &self,
+        request: Request<proto::GetTxStatusRequest>,
+    ) -> Result<Response<proto::GetTxStatusResponse>, Status> {
+        self.extensions(&request)?;
+        let hash = h256("hash", &request.into_inner().hash)?;
+
+        let status = AgglayerServer::get_tx_status(&self.agglayer, hash)
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(proto::GetTxStatusResponse { status }))
+    }
+
+    async fn get_tx_attestation(
+        &self,
+        request: Request<proto::GetTxAttestationRequest>,
+    ) -> Result<Response<proto::Attestation>, Status> {
+        self.extensions(&request)?;
+        let hash = h256("hash", &request.into_inner().hash)?;
+
+        let attestation = AgglayerServer::get_tx_attestation(&self.agglayer, hash)
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(attestation.into()))
+    }
+
+    async fn get_network_status(
+        &self,
+        request: Request<proto::GetNetworkStatusRequest>,
+    ) -> Result<Response<proto::GetNetworkStatusResponse>, Status> {
+        self.extensions(&request)?;
+
+        let statuses = AgglayerServer::get_network_status(&self.agglayer)
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(proto::GetNetworkStatusResponse {
+            rollups: statuses
+                .into_iter()
+                .map(|status| proto::RollupStatus {
+                    rollup_id: status.rollup_id,
+                    on_chain_last_verified_batch: status.on_chain_last_verified_batch,
+                    last_accepted_batch: status.last_accepted_batch,
+                    pending_submissions: status.pending_submissions,
+                    last_settlement_time: status.last_settlement_time,
+                    consensus_type: format!("{:?}", status.consensus_type),
+                    shadow: status.shadow,
+                    shadowed_submissions: status.shadowed_submissions,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_spend_report(
+        &self,
+        request: Request<proto::GetSpendReportRequest>,
+    ) -> Result<Response<proto::SpendReport>, Status> {
+        self.extensions(&request)?;
+        let request = request.into_inner();
+        let range = EpochRange {
+            from: request.from_epoch,
+            to: request.to_epoch,
+        };
+
+        let report = AgglayerServer::get_spend_report(&self.agglayer, request.rollup_id, range)
+            .await
+            .map_err(status)?;
+
+        Ok(Response::new(report.into()))
+    }
+}
+
+/// Convert an error of the RPC service into a gRPC [`Status`], according to
+/// its [`ErrorKind`].
+fn status(error: ErrorObjectOwned) -> Status {
+    let Some(data) = error
+        .data()
+        .and_then(|data| serde_json::from_str::<ErrorData>(data.get()).ok())
+    else {
+        return Status::internal(error.message());
+    };
+
+    let code = match data.kind {
+        ErrorKind::InvalidRollup
+        | ErrorKind::InvalidProof
+        | ErrorKind::InvalidSignature
+        | ErrorKind::InvalidCertificate => Code::InvalidArgument,
+        ErrorKind::Unauthorized => Code::PermissionDenied,
+        ErrorKind::ProofRejected | ErrorKind::StateMismatch => Code::FailedPrecondition,
+        ErrorKind::L1Unavailable
+        | ErrorKind::ZkevmNodeUnavailable
+        | ErrorKind::Paused
+        | ErrorKind::LeaderUnavailable => Code::Unavailable,
+        ErrorKind::SettlementFailed => Code::Aborted,
+        ErrorKind::NotFound => Code::NotFound,
+        ErrorKind::DeadlineExceeded | ErrorKind::TimedOut => Code::DeadlineExceeded,
+        ErrorKind::Overloaded | ErrorKind::RateLimited => Code::ResourceExhausted,
+        ErrorKind::AlreadySettled => Code::AlreadyExists,
+        ErrorKind::Unsupported => Code::Unimplemented,
+        ErrorKind::Internal => Code::Internal,
+    };
+
+    Status::new(code, data.message)
+}
+
+fn h256(field: &str, bytes: &[u8]) -> Result<H256, Status> {
+    if bytes.len() != H256::len_bytes() {
+        return Err(Status::invalid_argument(format!(
+            "invalid {field}: expected {} bytes, got {}",
+            H256::len_bytes(),
+            bytes.len()
+        )));
+    }
+
+    Ok(H256::from_slice(bytes))
+}
+
+impl TryFrom<proto::SignedTx> for SignedTx {
+    type Error = Status;
+
+    fn try_from(signed_tx: proto::SignedTx) -> Result<Self, Self::Error> {
+        let tx = signed_tx
+            .tx
+            .ok_or_else(|| Status::invalid_argument("missing tx.tx"))?;
+        let zkp = tx
+            .zkp
+            .ok_or_else(|| Status::invalid_argument("missing tx.tx.zkp"))?;
+
+        Ok(SignedTx {
+            tx: ProofManifest {
+                rollup_id: tx.rollup_id,
+                last_verified_batch: U64::from(tx.last_verified_batch),
+                new_verified_batch: U64::from(tx.new_verified_batch),
+                zkp: Zkp {
+                    new_state_root: h256("newStateRoot", &zkp.new_state_root)?,
+                    new_local_exit_root: h256("newLocalExitRoot", &zkp.new_local_exit_root)?,
+                    proof: Proof::from_bytes(zkp.proof),
+                },
+            },
+            signature: Signature::try_from(signed_tx.signature.as_slice())
+                .map_err(|e| Status::invalid_argument(format!("invalid signature: {e}")))?,
+        })
+    }
+}
+
+impl From<Attestation> for proto::Attestation {
+    fn from(attestation: Attestation) -> Self {
+        Self {
+            tx_hash: attestation.tx_hash.as_bytes().to_vec(),
+            epoch: attestation.epoch,
+            signer: attestation.signer.as_bytes().to_vec(),
+            signature: attestation.signature.to_vec(),
+        }
+    }
+}
+
+impl From<SpendReport> for proto::SpendReport {
+    fn from(report: SpendReport) -> Self {
+        Self {
+            rollup_id: report.rollup_id,
+            from_epoch: report.from_epoch,
+            to_epoch: report.to_epoch,
+            epochs: report
+                .epochs
+                .into_iter()
+                .map(|spend| proto::EpochSpend {
+                    epoch: spend.epoch,
+                    settlements: spend.settlements,
+                    gas_used: spend.gas_used.to_string(),
+                    spent_wei: spend.spent_wei.to_string(),
+                })
+                .collect(),
+            gas_used: report.gas_used.to_string(),
+            spent_wei: report.spent_wei.to_string(),
+        }
+    }
+}
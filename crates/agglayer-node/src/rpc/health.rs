@@ -0,0 +1,209 @@
+//! Health of the agglayer components, reported by `system_health`, along
+//! with the readiness and liveness probes served by `system_ready` and
+//! `system_live`.
+use std::{future::Future, time::Duration};
+
+use ethers::providers::Middleware;
+use jsonrpsee::{core::RpcResult, types::ErrorObjectOwned};
+use schemars::JsonSchema;
+use serde::Serialize;
+use tokio::time::timeout;
+
+use super::{internal_error, AgglayerImpl};
+use crate::kernel::ErrorKind;
+
+/// The time given to each component to answer its health check.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The time given to the runtime to schedule a task for the agglayer to be
+/// considered live.
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The overall health of the agglayer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum HealthCode {
+    /// Every component is healthy.
+    Healthy,
+    /// The ZkEVM nodes of some rollups are unreachable, their submissions
+    /// fail to verify.
+    Degraded,
+    /// A component required to settle the proofs is unhealthy.
+    Unhealthy,
+}
+
+/// The health of a single component.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ComponentHealth {
+    pub(crate) healthy: bool,
+    /// What the check found, or why it failed.
+    pub(crate) detail: String,
+}
+
+impl ComponentHealth {
+    fn new(outcome: Result<String, String>) -> Self {
+        match outcome {
+            Ok(detail) => Self {
+                healthy: true,
+                detail,
+            },
+            Err(detail) => Self {
+                healthy: false,
+                detail,
+            },
+        }
+    }
+}
+
+/// The health of the ZkEVM node of a rollup.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ZkevmNodeHealth {
+    pub(crate) rollup_id: u32,
+    #[serde(flatten)]
+    pub(crate) health: ComponentHealth,
+}
+
+/// The health of the agglayer, per component.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HealthReport {
+    /// Whether the agglayer is able to settle the proofs, possibly degraded.
+    pub(crate) health: bool,
+    pub(crate) code: HealthCode,
+    /// The connectivity to the L1 nodes.
+    pub(crate) l1: ComponentHealth,
+    /// The availability of the signer of the settlement transactions.
+    pub(crate) signer: ComponentHealth,
+    /// The liveness of the Clock.
+    pub(crate) clock: ComponentHealth,
+    /// The availability of the configured storage.
+    pub(crate) storage: ComponentHealth,
+    /// The reachability of the ZkEVM node of each registered rollup.
+    pub(crate) zkevm_nodes: Vec<ZkevmNodeHealth>,
+}
+
+impl HealthReport {
+    fn new(
+        l1: ComponentHealth,
+        signer: ComponentHealth,
+        clock: ComponentHealth,
+        storage: ComponentHealth,
+        zkevm_nodes: Vec<ZkevmNodeHealth>,
+    ) -> Self {
+        let code = if ![&l1, &signer, &clock, &storage]
+            .iter()
+            .all(|component| component.healthy)
+        {
+            HealthCode::Unhealthy
+        } else if !zkevm_nodes.iter().all(|node| node.health.healthy) {
+            HealthCode::Degraded
+        } else {
+            HealthCode::Healthy
+        };
+
+        Self {
+            health: code != HealthCode::Unhealthy,
+            code,
+            l1,
+            signer,
+            clock,
+            storage,
+            zkevm_nodes,
+        }
+    }
+}
+
+/// Fail a probe with the given reason.
+fn probe_failed(reason: String) -> ErrorObjectOwned {
+    internal_error(ErrorKind::Internal, reason)
+}
+
+/// Run the given check, failing it if it doesn't complete in time.
+async fn check(outcome: impl Future<Output = Result<String, String>>) -> ComponentHealth {
+    ComponentHealth::new(
+        timeout(HEALTH_CHECK_TIMEOUT, outcome)
+            .await
+            .unwrap_or_else(|_| Err("health check timed out".to_string())),
+    )
+}
+
+impl<Rpc> AgglayerImpl<Rpc>
+where
+    Rpc: Middleware + 'static,
+{
+    /// Check the health of every component, concurrently.
+    pub(crate) async fn health(&self) -> HealthReport {
+        let kernel = &self.kernel;
+
+        let l1 = check(async {
+            kernel
+                .probe_l1()
+                .await
+                .map(|block| format!("block {block}"))
+                .map_err(|e| e.to_string())
+        });
+        let signer = check(async {
+            kernel
+                .probe_signer()
+                .await
+                .map(|address| format!("{address:?}"))
+                .map_err(|e| e.to_string())
+        });
+        let zkevm_nodes = futures::future::join_all(kernel.registered_rollups().into_iter().map(
+            |rollup_id| async move {
+                ZkevmNodeHealth {
+                    rollup_id,
+                    health: check(async {
+                        kernel
+                            .probe_zkevm_node(rollup_id)
+                            .await
+                            .map(|batch| format!("batch {batch}"))
+                            .map_err(|e| e.to_string())
+                    })
+                    .await,
+                }
+            },
+        ));
+        let (l1, signer, zkevm_nodes) = tokio::join!(l1, signer, zkevm_nodes);
+
+        let clock = ComponentHealth::new(if self.clock_ref.is_running() {
+            Ok(format!(
+                "epoch {}, block {}",
+                self.clock_ref.current_epoch(),
+                self.clock_ref.current_block()
+            ))
+        } else {
+            Err("the clock task stopped".to_string())
+        });
+        let storage = ComponentHealth::new(match kernel.probe_storage() {
+            Ok(true) => Ok("readable".to_string()),
+            Ok(false) => Ok("not configured".to_string()),
+            Err(e) => Err(e.to_string()),
+        });
+
+        HealthReport::new(l1, signer, clock, storage, zkevm_nodes)
+    }
+
+    /// Check that the agglayer is fully initialized, and ready to serve the
+    /// submissions.
+    pub(crate) async fn ready(&self) -> RpcResult<serde_json::Value> {
+        timeout(HEALTH_CHECK_TIMEOUT, self.kernel.probe_readiness())
+            .await
+            .map_err(|_| probe_failed("readiness check timed out".to_string()))?
+            .map_err(|e| probe_failed(e.to_string()))?;
+
+        Ok(serde_json::json!({ "ready": true }))
+    }
+
+    /// Check that the runtime is responsive, scheduling a task in time.
+    pub(crate) async fn live(&self) -> RpcResult<serde_json::Value> {
+        timeout(LIVENESS_TIMEOUT, tokio::spawn(async {}))
+            .await
+            .map_err(|_| probe_failed("the runtime is unresponsive".to_string()))?
+            .map_err(|e| probe_failed(e.to_string()))?;
+
+        Ok(serde_json::json!({ "live": true }))
+    }
+}
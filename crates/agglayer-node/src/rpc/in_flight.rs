@@ -0,0 +1,134 @@
+//! Deduplication of the transactions submitted again while being processed.
+//!
+//! The first submission of a transaction runs the verification and settlement
+//! pipeline, the submissions of the same transaction received in the meantime
+//! attach to it and get its outcome, instead of verifying and settling the
+//! transaction once more.
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use ethers::types::H256;
+use jsonrpsee::core::RpcResult;
+use tokio::sync::watch;
+
+type Outcome = Option<RpcResult<H256>>;
+
+/// The transactions being processed, by transaction hash.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct InFlightTxs {
+    txs: Arc<Mutex<HashMap<H256, watch::Receiver<Outcome>>>>,
+}
+
+/// The processing of a transaction, either by this submission or by an
+/// earlier one.
+pub(crate) enum Processing {
+    /// The transaction is to be processed by this submission.
+    Started(InFlightTx),
+    /// The transaction is being processed by an earlier submission.
+    Attached(AttachedTx),
+}
+
+impl InFlightTxs {
+    /// Start processing the given transaction, unless being processed
+    /// already.
+    pub(crate) fn start(&self, hash: H256) -> Processing {
+        let mut txs = self.lock();
+        if let Some(outcome) = txs.get(&hash) {
+            return Processing::Attached(AttachedTx {
+                outcome: outcome.clone(),
+            });
+        }
+
+        let (sender, receiver) = watch::channel(None);
+        txs.insert(hash, receiver);
+
+        Processing::Started(InFlightTx {
+            txs: self.clone(),
+            hash,
+            outcome: sender,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<H256, watch::Receiver<Outcome>>> {
+        self.txs
+            .lock()
+            .expect("In-flight transactions lock poisoned")
+    }
+}
+
+/// A transaction processed by this submission, no longer in flight once
+/// dropped.
+pub(crate) struct InFlightTx {
+    txs: InFlightTxs,
+    hash: H256,
+    outcome: watch::Sender<Outcome>,
+}
+
+impl InFlightTx {
+    /// Share the outcome of the processing with the attached submissions.
+    pub(crate) fn complete(self, outcome: &RpcResult<H256>) {
+        self.outcome.send_replace(Some(outcome.clone()));
+    }
+}
+
+impl Drop for InFlightTx {
+    fn drop(&mut self) {
+        self.txs.lock().remove(&self.hash);
+    }
+}
+
+/// A transaction processed by an earlier submission.
+pub(crate) struct AttachedTx {
+    outcome: watch::Receiver<Outcome>,
+}
+
+impl AttachedTx {
+    /// Wait for the outcome of the earlier submission, if it completes.
+    ///
+    /// Returns `None` if the earlier submission was abandoned, e.g. as its
+    /// client stopped waiting for it.
+    pub(crate) async fn outcome(mut self) -> Option<RpcResult<H256>> {
+        self.outcome
+            .wait_for(Option::is_some)
+            .await
+            .ok()
+            .and_then(|outcome| outcome.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InFlightTxs, Processing};
+
+    #[tokio::test]
+    async fn resubmissions_attach_to_the_transaction_in_flight() {
+        let txs = InFlightTxs::default();
+        let hash = ethers::types::H256::random();
+
+        let Processing::Started(first) = txs.start(hash) else {
+            panic!("the first submission is to be processed");
+        };
+        let Processing::Attached(second) = txs.start(hash) else {
+            panic!("the second submission is to attach to the first");
+        };
+        let Processing::Attached(third) = txs.start(hash) else {
+            panic!("the third submission is to attach to the first");
+        };
+
+        first.complete(&Ok(hash));
+        assert_eq!(second.outcome().await.unwrap().unwrap(), hash);
+        assert_eq!(third.outcome().await.unwrap().unwrap(), hash);
+
+        // The submissions attached to an abandoned one get no outcome.
+        let Processing::Started(first) = txs.start(hash) else {
+            panic!("the transaction is no longer in flight");
+        };
+        let Processing::Attached(second) = txs.start(hash) else {
+            panic!("the second submission is to attach to the first");
+        };
+        drop(first);
+        assert!(second.outcome().await.is_none());
+    }
+}
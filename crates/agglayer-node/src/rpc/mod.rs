@@ -1,13 +1,26 @@
-use std::sync::Arc;
+use std::{
+    future::Future,
+    net::SocketAddr,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use agglayer_config::Config;
-use agglayer_telemetry::KeyValue;
+use agglayer_clock::{ClockRef, Event, EventReceiver};
+use agglayer_config::{Config, ConsensusType, SettlementFinality};
+use agglayer_telemetry::{
+    timed, KeyValue, Timer, EXECUTE_DURATION, SEND_TX_DURATION, SETTLE_DURATION,
+    VERIFY_SIGNATURE_DURATION, VERIFY_ZKEVM_NODE_DURATION,
+};
 use ethers::{providers::Middleware, types::H256};
 use futures::TryFutureExt;
 use jsonrpsee::{
-    core::{async_trait, RpcResult},
+    core::{async_trait, RpcResult, SubscriptionResult},
     proc_macros::rpc,
-    server::{middleware::http::ProxyGetRequestLayer, PingConfig, ServerBuilder, ServerHandle},
+    server::{
+        middleware::http::ProxyGetRequestLayer, serve_with_graceful_shutdown, stop_channel,
+        PingConfig, ServerBuilder, ServerHandle,
+    },
     types::{
         error::{
             CALL_EXECUTION_FAILED_CODE, INTERNAL_ERROR_CODE, INTERNAL_ERROR_MSG,
@@ -15,43 +28,199 @@ use jsonrpsee::{
         },
         ErrorObject, ErrorObjectOwned,
     },
+    Extensions, PendingSubscriptionSink, SubscriptionMessage, SubscriptionSink,
 };
-use tokio::{sync::mpsc, try_join};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    net::TcpListener,
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc,
+    },
+    time::{timeout, timeout_at, Instant},
+    try_join,
+};
+use tower::Service as _;
 use tower_http::cors::CorsLayer;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn, Instrument as _};
 
+use self::{
+    access_log::AccessLogLayer,
+    auth::{AuthLayer, RollupScope},
+    budget::SubmissionBudget,
+    deadline::{Deadline, DeadlineLayer},
+    in_flight::{InFlightTxs, Processing},
+    network_status::{
+        CircuitBreakerState, PendingSubmission, SettlementProgress, SubmissionTracker,
+    },
+    rate_limit::{IpRateLimitLayer, RateLimited, RateLimiter},
+    request_id::{RequestId, RequestIdLayer, REQUEST_ID_HEADER},
+    request_signature::RequestSignatureLayer,
+    tls::{TlsCertificates, TLS_HANDSHAKE_TIMEOUT},
+};
+pub(crate) use self::{
+    admin::AdminImpl,
+    epochs::{EpochChange, EpochConfiguration},
+    grpc::GrpcImpl,
+    health::HealthReport,
+    network_status::{LatestSettledBatch, RollupStatus},
+    send_txs::SendTxResult,
+};
 use crate::{
-    kernel::{Kernel, ZkevmNodeVerificationError},
+    admission::Admitted,
+    attestation::{Attestation, AttestationStore},
+    audit::AuditEvent,
+    certificate::{Certificate, CertificateStore},
+    kernel::{ErrorKind, Kernel, ZkevmNodeVerificationError},
+    leader::{Leadership, Lease, Role},
     signed_tx::SignedTx,
+    spend::{EpochRange, SpendReport},
+    storage::PayloadStore,
+    tx_updates::TxUpdate,
 };
 
+mod access_log;
+mod admin;
+mod auth;
+mod budget;
+mod deadline;
+mod epochs;
+mod grpc;
+mod health;
+mod in_flight;
+mod network_status;
+mod rate_limit;
+mod request_id;
+mod request_signature;
+mod send_txs;
+mod tls;
+
 #[cfg(test)]
 mod tests;
 
 #[rpc(server, namespace = "interop")]
 trait Agglayer {
-    #[method(name = "sendTx")]
+    #[method(name = "sendTx", with_extensions)]
     async fn send_tx(&self, tx: SignedTx) -> RpcResult<H256>;
 
+    #[method(name = "sendTxs", with_extensions)]
+    async fn send_txs(&self, txs: Vec<SignedTx>) -> RpcResult<Vec<SendTxResult>>;
+
     #[method(name = "getTxStatus")]
     async fn get_tx_status(&self, hash: H256) -> RpcResult<TxStatus>;
 
-    #[method(name = "sendCertificate")]
-    async fn send_certificate(&self, certificate: ()) -> RpcResult<()>;
+    #[method(name = "getTxAttestation")]
+    async fn get_tx_attestation(&self, hash: H256) -> RpcResult<Attestation>;
+
+    #[method(name = "getNetworkStatus")]
+    async fn get_network_status(&self) -> RpcResult<Vec<RollupStatus>>;
+
+    #[method(name = "getSpendReport")]
+    async fn get_spend_report(&self, rollup_id: u32, range: EpochRange) -> RpcResult<SpendReport>;
+
+    #[method(name = "getLatestSettledBatch")]
+    async fn get_latest_settled_batch(&self, rollup_id: u32) -> RpcResult<LatestSettledBatch>;
+
+    #[method(name = "getEpochConfiguration")]
+    async fn get_epoch_configuration(&self) -> RpcResult<EpochConfiguration>;
+
+    #[method(name = "sendCertificate", with_extensions)]
+    async fn send_certificate(&self, certificate: Certificate) -> RpcResult<H256>;
+
+    #[subscription(name = "subscribeEpochs" => "epochs", unsubscribe = "unsubscribeEpochs", item = EpochChange)]
+    async fn subscribe_epochs(&self) -> SubscriptionResult;
+
+    #[subscription(name = "subscribeTxUpdates" => "txUpdates", unsubscribe = "unsubscribeTxUpdates", item = TxUpdate)]
+    async fn subscribe_tx_updates(&self, hash: H256) -> SubscriptionResult;
+
+    #[subscription(name = "subscribeAllTxUpdates" => "allTxUpdates", unsubscribe = "unsubscribeAllTxUpdates", item = TxUpdate)]
+    async fn subscribe_all_tx_updates(&self) -> SubscriptionResult;
 }
 
+/// How long the followers wait for the leader to settle the transactions they
+/// forward.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The address of the peer that sent a request.
+///
+/// Inserted in the extensions of every request served by the RPC server.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PeerAddr(pub(crate) SocketAddr);
+
 /// The RPC agglayer service implementation.
 pub(crate) struct AgglayerImpl<Rpc> {
-    kernel: Kernel<Rpc>,
-    certificate_sender: mpsc::Sender<()>,
+    kernel: Arc<Kernel<Rpc>>,
+    certificate_sender: mpsc::Sender<Certificate>,
+    certificates: CertificateStore,
+    clock_ref: Arc<ClockRef>,
+    submissions: SubmissionTracker,
+    in_flight: InFlightTxs,
+    budget: SubmissionBudget,
+    rate_limiter: RateLimiter,
+    ip_rate_limit: IpRateLimitLayer,
+    attestations: AttestationStore,
+    payloads: PayloadStore,
+    leadership: Leadership,
 }
 
 impl<Rpc> AgglayerImpl<Rpc> {
     /// Create an instance of the RPC agglayer service.
-    pub(crate) fn new(kernel: Kernel<Rpc>, certificate_sender: mpsc::Sender<()>) -> Self {
+    pub(crate) fn new(
+        kernel: Kernel<Rpc>,
+        certificate_sender: mpsc::Sender<Certificate>,
+        clock_ref: Arc<ClockRef>,
+    ) -> Self {
+        let budget = SubmissionBudget::new(kernel.pending_submissions_config());
+        let rate_limiter = RateLimiter::new(kernel.rate_limit_config());
+        let ip_rate_limit = IpRateLimitLayer::new(kernel.ip_rate_limit_config());
+
         Self {
-            kernel,
+            kernel: Arc::new(kernel),
             certificate_sender,
+            certificates: CertificateStore::default(),
+            clock_ref,
+            submissions: SubmissionTracker::default(),
+            in_flight: InFlightTxs::default(),
+            budget,
+            rate_limiter,
+            ip_rate_limit,
+            attestations: AttestationStore::default(),
+            payloads: PayloadStore::default(),
+            leadership: Leadership::always_leader(),
+        }
+    }
+
+    /// Settle the transactions only while elected leader, forwarding them to
+    /// the leader otherwise.
+    pub(crate) fn with_leadership(mut self, leadership: Leadership) -> Self {
+        self.leadership = leadership;
+        self
+    }
+
+    /// Get the kernel serving the RPC.
+    pub(crate) fn kernel(&self) -> &Arc<Kernel<Rpc>> {
+        &self.kernel
+    }
+}
+
+// The clones share the kernel and the state of the submissions, so that the
+// RPC and gRPC servers serve the same agglayer.
+impl<Rpc> Clone for AgglayerImpl<Rpc> {
+    fn clone(&self) -> Self {
+        Self {
+            kernel: self.kernel.clone(),
+            certificate_sender: self.certificate_sender.clone(),
+            certificates: self.certificates.clone(),
+            clock_ref: self.clock_ref.clone(),
+            submissions: self.submissions.clone(),
+            in_flight: self.in_flight.clone(),
+            budget: self.budget.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            ip_rate_limit: self.ip_rate_limit.clone(),
+            attestations: self.attestations.clone(),
+            payloads: self.payloads.clone(),
+            leadership: self.leadership.clone(),
         }
     }
 }
@@ -59,14 +228,296 @@ impl<Rpc> AgglayerImpl<Rpc>
 where
     Rpc: Middleware + 'static,
 {
+    /// Reload the rate limits, the gas strategy of the settlements and the
+    /// endpoints of the ZkEVM nodes from the given configuration file,
+    /// returning the configuration loaded.
+    ///
+    /// The running configuration is left untouched if the file fails to load.
+    pub(crate) fn reload(&self, cfg: &Path) -> anyhow::Result<Config> {
+        let config = Config::load(cfg)?;
+
+        self.kernel.reload(&config)?;
+        self.rate_limiter.reload(config.rpc.rate_limit.as_ref());
+        self.ip_rate_limit.reload(config.rpc.ip_rate_limit.as_ref());
+
+        Ok(config)
+    }
+
+    /// Settle the given verified transaction on L1, returning the hash of the
+    /// settlement transaction.
+    async fn settle_verified(
+        &self,
+        tx: &SignedTx,
+        submission: &PendingSubmission,
+    ) -> RpcResult<H256> {
+        let tx_hash = tx.hash().to_string();
+        let metrics_attrs = &[KeyValue::new("rollup_id", tx.tx.rollup_id.to_string())];
+        let _timer = Timer::start(&SETTLE_DURATION, metrics_attrs);
+
+        // Keep the proof until the transaction is settled.
+        if let Err(e) = self.payloads.insert(tx.hash(), tx.tx.zkp.proof.as_bytes()) {
+            error!(
+                tx_hash,
+                "Failed to store the proof of transaction {tx_hash}: {e}"
+            );
+        }
+
+        // Settle the proof on-chain and return the transaction hash.
+        let settlement = self.kernel.settle(tx).await;
+        _ = self.payloads.remove(&tx.hash());
+
+        let receipt = settlement.map_err(|e| {
+            error!(tx_hash, "Failed to settle transaction {tx_hash} on L1: {e}");
+            submission.failed(e.to_string());
+            internal_error(e.kind(), e.to_string())
+        })?;
+
+        agglayer_telemetry::SETTLE.add(1, metrics_attrs);
+        submission.settled(receipt.transaction_hash);
+        if let Err(e) =
+            self.kernel
+                .spending()
+                .record(tx.tx.rollup_id, self.clock_ref.current_epoch(), &receipt)
+        {
+            error!(
+                tx_hash,
+                "Failed to account for the settlement of {tx_hash}: {e}"
+            );
+        }
+        self.kernel
+            .settlements()
+            .link(receipt.transaction_hash, tx.hash());
+
+        info!("Successfully settled transaction {tx_hash} => receipt {receipt:?}");
+
+        Ok(receipt.transaction_hash)
+    }
+
+    /// Verify the given transaction and settle it, unless in shadow mode or
+    /// following the leader.
+    async fn process_tx(
+        &self,
+        ext: &Extensions,
+        tx: SignedTx,
+        admitted: Admitted,
+    ) -> RpcResult<H256> {
+        let tx_hash = tx.hash().to_string();
+        let metrics_attrs = &[KeyValue::new("rollup_id", tx.tx.rollup_id.to_string())];
+        let auditor = self.kernel.auditor();
+
+        // Rate limit the rollup before reaching out to L1 or the ZkEVM node.
+        self.rate_limiter
+            .check(tx.tx.rollup_id)
+            .map_err(|e| rate_limited_error(&tx_hash, e))?;
+
+        // Hold a share of the memory budget until the transaction is settled.
+        let reservation = self
+            .budget
+            .reserve(
+                tx.tx.rollup_id,
+                std::mem::size_of_val(&tx) + tx.tx.zkp.proof.as_bytes().len(),
+            )
+            .map_err(|e| {
+                warn!(tx_hash, "Rejected transaction {tx_hash}: {e}");
+                overloaded_error(e.to_string())
+            })?;
+
+        if let Some(max) = self.kernel.max_pending_submissions(tx.tx.rollup_id) {
+            if self
+                .submissions
+                .activity(tx.tx.rollup_id)
+                .pending_submissions
+                >= max
+            {
+                warn!(
+                    tx_hash,
+                    "Rejected transaction {tx_hash}: too many pending submissions"
+                );
+                return Err(overloaded_error(format!(
+                    "rollup {} has {max} submissions pending already",
+                    tx.tx.rollup_id
+                )));
+            }
+        }
+
+        let Some(submission) = self.submissions.start(tx.tx.rollup_id, tx.hash()) else {
+            warn!(
+                tx_hash,
+                "Rejected transaction {tx_hash}: already being processed"
+            );
+            return Err(invalid_params_error(
+                ErrorKind::AlreadySettled,
+                format!("transaction {tx_hash} is already being processed"),
+            ));
+        };
+
+        agglayer_telemetry::CHECK_TX.add(1, metrics_attrs);
+
+        // Run all the verification checks in parallel, giving up as soon as the
+        // client stops waiting for the response or the transaction gets evicted.
+        let checks = async {
+            try_join!(
+                timed(
+                    &VERIFY_SIGNATURE_DURATION,
+                    metrics_attrs,
+                    self.kernel.verify_signature(&tx)
+                )
+                .map_err(|e| {
+                    error!(
+                        tx_hash,
+                        "Failed to verify the signature of transaction {tx_hash}: {e}"
+                    );
+                    invalid_params_error(e.kind(), e.to_string())
+                })
+                .map_ok(|_| {
+                    agglayer_telemetry::VERIFY_SIGNATURE.add(1, metrics_attrs);
+                }),
+                timed(
+                    &EXECUTE_DURATION,
+                    metrics_attrs,
+                    self.kernel.verify_proof_eth_call(&tx)
+                )
+                .map_err(|e| {
+                    error!(
+                        tx_hash,
+                        "Failed to dry-run the verify_batches_trusted_aggregator for transaction \
+                         {tx_hash}: {e}"
+                    );
+                    invalid_params_error(e.kind(), e.to_string())
+                })
+                .map_ok(|_| {
+                    agglayer_telemetry::EXECUTE.add(1, metrics_attrs);
+                }),
+                timed(
+                    &VERIFY_ZKEVM_NODE_DURATION,
+                    metrics_attrs,
+                    self.kernel.verify_proof_zkevm_node(&tx)
+                )
+                .map_err(|e| {
+                    error!(
+                        tx_hash,
+                        "Failed to verify the batch local_exit_root and state_root of transaction \
+                         {tx_hash}: {e}"
+                    );
+                    invalid_params_error(e.kind(), e.to_string())
+                })
+                .map_ok(|_| {
+                    agglayer_telemetry::VERIFY_ZKP.add(1, metrics_attrs);
+                })
+            )
+        };
+        let verification = async {
+            tokio::select! {
+                res = checks => res,
+                _ = reservation.evicted() => {
+                    warn!(tx_hash, "Evicted transaction {tx_hash} to make room for newer ones");
+                    Err(overloaded_error(format!(
+                        "transaction {tx_hash} was evicted by a newer submission of rollup {}",
+                        tx.tx.rollup_id
+                    )))
+                }
+            }
+        };
+
+        let deadline = ext.get::<Deadline>().copied();
+
+        let verified = match deadline {
+            Some(Deadline(deadline)) => timeout_at(deadline, verification)
+                .await
+                .unwrap_or_else(|_| Err(deadline_exceeded_error(&tx_hash))),
+            None => verification.await,
+        };
+        match &verified {
+            Ok(_) => auditor.record(AuditEvent::Verified { tx_hash: tx.hash() }),
+            Err(error) => {
+                let data = error
+                    .data()
+                    .and_then(|data| serde_json::from_str::<ErrorData>(data.get()).ok());
+                auditor.record(AuditEvent::Rejected {
+                    tx_hash: tx.hash(),
+                    kind: data.as_ref().map_or(ErrorKind::Internal, |data| data.kind),
+                    error: data.map_or_else(|| error.message().to_string(), |data| data.message),
+                });
+                self.kernel
+                    .tx_updates()
+                    .publish(TxUpdate::failed(tx.hash(), error.message()));
+            }
+        }
+        verified?;
+
+        // Don't settle the transaction if the client is no longer waiting for it.
+        if deadline.is_some_and(|Deadline(deadline)| deadline <= Instant::now()) {
+            return Err(deadline_exceeded_error(&tx_hash));
+        }
+
+        reservation.settling();
+        submission.accepted(tx.tx.new_verified_batch.as_u64());
+        self.kernel
+            .tx_updates()
+            .publish(TxUpdate::verified(tx.hash()));
+
+        // Attest the acceptance of the transaction ahead of its settlement.
+        match self
+            .kernel
+            .attest(&tx, self.clock_ref.current_epoch())
+            .await
+        {
+            Ok(attestation) => self.attestations.insert(attestation),
+            Err(e) => error!(tx_hash, "Failed to attest transaction {tx_hash}: {e}"),
+        }
+
+        // Rollups in shadow mode stop short of the settlement, the hash of the
+        // transaction is returned in lieu of the settlement transaction hash.
+        if self.kernel.is_shadow(tx.tx.rollup_id) {
+            agglayer_telemetry::SHADOW_VERIFIED.add(1, metrics_attrs);
+            submission.shadowed(tx.hash());
+            info!("Verified transaction {tx_hash} in shadow mode, skipping the settlement");
+
+            return Ok(tx.hash());
+        }
+
+        // Only the leader settles the transactions, the followers hand them over.
+        if let Role::Follower { leader } = self.leadership.role() {
+            return self.forward_to_leader(&tx, leader).await;
+        }
+
+        // Settle the proof in the background if configured, the client tracking
+        // its settlement by the hash of the transaction.
+        if self.kernel.async_settlement() {
+            let hash = tx.hash();
+            let agglayer = self.clone();
+            tokio::spawn(
+                async move {
+                    // Hold the transaction in flight until it's settled.
+                    let (_admitted, _reservation) = (admitted, reservation);
+                    _ = agglayer.settle_verified(&tx, &submission).await;
+                }
+                .in_current_span(),
+            );
+            info!("Verified transaction {tx_hash}, settling it in the background");
+
+            return Ok(hash);
+        }
+
+        self.settle_verified(&tx, &submission).await
+    }
+
     pub(crate) async fn start(self, config: Arc<Config>) -> anyhow::Result<ServerHandle> {
         // Create the RPC service
+        let ip_rate_limit = self.ip_rate_limit.clone();
         let mut service = self.into_rpc();
 
-        // Register the system_health method to serve health checks.
-        service.register_method("system_health", |_, _, _| {
-            println!("system_health");
-            serde_json::json!({ "health": true })
+        // Register the system_health method to serve health checks, along with
+        // the system_ready and system_live methods to serve the readiness and
+        // liveness probes.
+        service.register_async_method("system_health", |_, agglayer, _| async move {
+            RpcResult::Ok(agglayer.health().await)
+        })?;
+        service.register_async_method("system_ready", |_, agglayer, _| async move {
+            agglayer.ready().await
+        })?;
+        service.register_async_method("system_live", |_, agglayer, _| async move {
+            agglayer.live().await
         })?;
 
         // Create the RPC server.
@@ -99,35 +550,384 @@ where
                 hyper::Method::OPTIONS,
             ])
             .allow_origin(tower_http::cors::Any)
-            .allow_headers([hyper::header::CONTENT_TYPE]);
+            .allow_headers([
+                hyper::header::CONTENT_TYPE,
+                hyper::header::AUTHORIZATION,
+                auth::API_KEY_HEADER,
+                request_signature::REQUEST_SIGNATURE_HEADER,
+                request_signature::REQUEST_TIMESTAMP_HEADER,
+                deadline::REQUEST_TIMEOUT_HEADER,
+                REQUEST_ID_HEADER,
+            ])
+            .expose_headers([REQUEST_ID_HEADER]);
 
-        // Create a middleware stack with the CORS middleware and a proxy layer for
-        // health checks.
+        // Create a middleware stack with the request IDs, the access logs, the
+        // rate limit per IP address, the request signature verification, the CORS
+        // middleware, proxy layers for the health checks and the readiness and
+        // liveness probes, the authentication of the callers and the client
+        // deadlines.
         let middleware = tower::ServiceBuilder::new()
+            .layer(RequestIdLayer)
+            .layer(AccessLogLayer::new(config.rpc.access_log.as_ref()))
+            .layer(ip_rate_limit)
+            .layer(RequestSignatureLayer::new(
+                &config.rpc.request_signers,
+                config.rpc.max_request_body_size,
+            ))
             .layer(ProxyGetRequestLayer::new("/health", "system_health")?)
-            .layer(cors);
+            .layer(ProxyGetRequestLayer::new("/ready", "system_ready")?)
+            .layer(ProxyGetRequestLayer::new("/live", "system_live")?)
+            .layer(cors)
+            .layer(AuthLayer::new(&config.rpc))
+            .layer(DeadlineLayer);
 
-        let addr = config.rpc_addr();
+        let mut listeners = Vec::new();
+        for addr in config.rpc_addrs() {
+            listeners.push(TcpListener::bind(addr).await?);
+            info!("Listening on {addr}");
+        }
 
-        let server = server_builder
+        let service_builder = server_builder
             .set_http_middleware(middleware)
-            .build(addr)
-            .await?;
+            .to_service_builder();
+        let (stop_handle, server_handle) = stop_channel();
+
+        // Terminate TLS if configured, watching the certificate for rotation.
+        let acceptor = match &config.rpc.tls {
+            Some(tls) => {
+                let certificates = TlsCertificates::load(tls)?;
+                let acceptor = certificates.acceptor()?;
+                tokio::spawn(certificates.watch(stop_handle.clone().shutdown()));
+                info!("Serving TLS with {}", tls.cert_path.display());
+
+                Some(acceptor)
+            }
+            None => None,
+        };
+
+        // Accept the connections ourselves in order to make the address of the
+        // peer available to the middlewares and the RPC methods.
+        for listener in listeners {
+            let service = service.clone();
+            let service_builder = service_builder.clone();
+            let stop_handle = stop_handle.clone();
+            let acceptor = acceptor.clone();
+
+            tokio::spawn(async move {
+                loop {
+                    let (socket, peer) = tokio::select! {
+                        res = listener.accept() => match res {
+                            Ok(connection) => connection,
+                            Err(error) => {
+                                error!("Failed to accept connection: {error}");
+                                continue;
+                            }
+                        },
+                        _ = stop_handle.clone().shutdown() => break,
+                    };
+
+                    let rpc_service = service_builder
+                        .clone()
+                        .build(service.clone(), stop_handle.clone());
+
+                    let connection_service = tower::service_fn(
+                        move |mut request: hyper::Request<hyper::body::Incoming>| {
+                            request.extensions_mut().insert(PeerAddr(peer));
+
+                            let mut rpc_service = rpc_service.clone();
+                            async move { rpc_service.call(request).await }
+                        },
+                    );
+
+                    let stopped = stop_handle.clone().shutdown();
+                    let Some(acceptor) = acceptor.clone() else {
+                        tokio::spawn(serve_with_graceful_shutdown(
+                            socket,
+                            connection_service,
+                            stopped,
+                        ));
+                        continue;
+                    };
 
-        info!("Listening on {addr}");
+                    tokio::spawn(async move {
+                        match timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(socket)).await {
+                            Ok(Ok(stream)) => {
+                                tokio::spawn(serve_with_graceful_shutdown(
+                                    stream,
+                                    connection_service,
+                                    stopped,
+                                ));
+                            }
+                            Ok(Err(error)) => debug!("TLS handshake with {peer} failed: {error}"),
+                            Err(_) => debug!("TLS handshake with {peer} timed out"),
+                        }
+                    });
+                }
+            });
+        }
 
-        Ok(server.start(service))
+        Ok(server_handle)
     }
 }
 
+/// The structured data attached to the error responses.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ErrorData {
+    pub(crate) kind: ErrorKind,
+    /// Whether the same call may succeed if retried later on.
+    pub(crate) retriable: bool,
+    pub(crate) message: String,
+    /// The ID of the failed request, to reference it in support requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) request_id: Option<String>,
+}
+
+/// Helper function to create an error with structured data.
+fn error_object(
+    code: i32,
+    message: &str,
+    kind: ErrorKind,
+    msg: impl Into<String>,
+) -> ErrorObjectOwned {
+    ErrorObject::owned(
+        code,
+        message,
+        Some(ErrorData {
+            kind,
+            retriable: kind.retriable(),
+            message: msg.into(),
+            request_id: RequestId::current().map(|id| id.to_string()),
+        }),
+    )
+}
+
 /// Helper function to create an invalid params error with a custom message.
-fn invalid_params_error(msg: impl Into<String>) -> ErrorObjectOwned {
-    ErrorObject::owned(INVALID_PARAMS_CODE, INVALID_PARAMS_MSG, Some(msg.into()))
+fn invalid_params_error(kind: ErrorKind, msg: impl Into<String>) -> ErrorObjectOwned {
+    error_object(INVALID_PARAMS_CODE, INVALID_PARAMS_MSG, kind, msg)
+}
+
+/// The error code returned when the caller isn't allowed to perform the call.
+pub(crate) const UNAUTHORIZED_CODE: i32 = -32001;
+
+/// Helper function to create an unauthorized error with a custom message.
+fn unauthorized_error(msg: impl Into<String>) -> ErrorObjectOwned {
+    error_object(
+        UNAUTHORIZED_CODE,
+        "Unauthorized",
+        ErrorKind::Unauthorized,
+        msg,
+    )
+}
+
+/// The error code returned when the client deadline is exceeded.
+pub(crate) const DEADLINE_EXCEEDED_CODE: i32 = -32002;
+
+/// Helper function to create a deadline exceeded error for the given
+/// transaction.
+fn deadline_exceeded_error(tx_hash: &str) -> ErrorObjectOwned {
+    warn!(
+        tx_hash,
+        "Gave up on transaction {tx_hash}: deadline exceeded"
+    );
+
+    error_object(
+        DEADLINE_EXCEEDED_CODE,
+        "Deadline exceeded",
+        ErrorKind::DeadlineExceeded,
+        format!("deadline exceeded before transaction {tx_hash} got settled"),
+    )
+}
+
+/// The error code returned when the agglayer is out of capacity.
+pub(crate) const OVERLOADED_CODE: i32 = -32003;
+
+/// Helper function to create an overloaded error with a custom message.
+fn overloaded_error(msg: impl Into<String>) -> ErrorObjectOwned {
+    error_object(OVERLOADED_CODE, "Overloaded", ErrorKind::Overloaded, msg)
+}
+
+/// The error code returned when the agglayer is paused by its operators.
+pub(crate) const PAUSED_CODE: i32 = -32004;
+
+/// Helper function to create a paused error for the given transaction.
+fn paused_error(tx_hash: &str) -> ErrorObjectOwned {
+    warn!(tx_hash, "Rejected transaction {tx_hash}: paused");
+
+    error_object(
+        PAUSED_CODE,
+        "Paused",
+        ErrorKind::Paused,
+        "the agglayer is paused and doesn't accept new proofs",
+    )
+}
+
+/// The error code returned when the rollup exceeded its rate limit.
+pub(crate) const RATE_LIMITED_CODE: i32 = -32005;
+
+/// Helper function to create a rate limited error for the given transaction.
+fn rate_limited_error(tx_hash: &str, error: RateLimited) -> ErrorObjectOwned {
+    warn!(tx_hash, "Rejected transaction {tx_hash}: {error}");
+
+    error_object(
+        RATE_LIMITED_CODE,
+        "Rate limited",
+        ErrorKind::RateLimited,
+        error.to_string(),
+    )
+}
+
+/// The error code returned while L1 is unreachable, the L1 circuit breaker
+/// being tripped.
+pub(crate) const L1_UNAVAILABLE_CODE: i32 = -32006;
+
+/// Helper function to create an L1 unavailable error for the given submission.
+fn l1_unavailable_error(hash: &str) -> ErrorObjectOwned {
+    warn!(hash, "Rejected submission {hash}: L1 unreachable");
+
+    error_object(
+        L1_UNAVAILABLE_CODE,
+        "L1 unavailable",
+        ErrorKind::L1Unavailable,
+        "L1 is unreachable, the submissions are rejected until it recovers",
+    )
+}
+
+/// The error code returned for the calls the agglayer doesn't support yet.
+pub(crate) const UNSUPPORTED_CODE: i32 = -32007;
+
+/// Helper function to create an unsupported error with a custom message.
+fn unsupported_error(msg: impl Into<String>) -> ErrorObjectOwned {
+    error_object(UNSUPPORTED_CODE, "Unsupported", ErrorKind::Unsupported, msg)
+}
+
+/// The response of the leader to a forwarded transaction.
+#[derive(Deserialize)]
+struct ForwardedResponse<'a> {
+    result: Option<H256>,
+    #[serde(borrow)]
+    error: Option<ErrorObject<'a>>,
+}
+
+impl<Rpc> AgglayerImpl<Rpc>
+where
+    Rpc: Middleware + 'static,
+{
+    /// Forward a verified transaction to the leader for settlement, with the
+    /// API key of the followers and, if the requests must be signed, a
+    /// signature of the agglayer key.
+    async fn forward_to_leader(&self, tx: &SignedTx, leader: Option<Lease>) -> RpcResult<H256> {
+        let tx_hash = tx.hash().to_string();
+        let Some((holder, url)) = leader.and_then(|lease| Some((lease.holder, lease.url?))) else {
+            warn!(tx_hash, "No leader to settle transaction {tx_hash}");
+            return Err(call_execution_error(
+                ErrorKind::LeaderUnavailable,
+                format!("no leader elected to settle transaction {tx_hash}"),
+            ));
+        };
+
+        debug!(
+            tx_hash,
+            "Forwarding transaction {tx_hash} to the leader {holder}"
+        );
+
+        let body = serde_json::to_vec(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "interop_sendTx",
+            "params": [tx],
+        }))
+        .expect("SignedTx is serializable");
+
+        let mut request = reqwest::Client::new()
+            .post(url)
+            .timeout(FORWARD_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+
+        // Forward the ID of the request, correlating the logs of the leader.
+        if let Some(id) = RequestId::current() {
+            request = request.header(REQUEST_ID_HEADER.as_str(), id.as_str());
+        }
+
+        // Authenticate with the leader like any other caller.
+        if let Some(api_key) = self.kernel.leader_api_key() {
+            request = request.header(auth::API_KEY_HEADER.as_str(), api_key);
+        }
+        if self.kernel.signs_requests() {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let (_, signature) = self
+                .kernel
+                .sign(request_signature::signed_message(timestamp, &body))
+                .await
+                .map_err(|e| {
+                    error!(
+                        tx_hash,
+                        "Failed to sign transaction {tx_hash} for the leader: {e}"
+                    );
+                    internal_error(ErrorKind::Internal, e.to_string())
+                })?;
+
+            request = request
+                .header(
+                    request_signature::REQUEST_TIMESTAMP_HEADER.as_str(),
+                    timestamp.to_string(),
+                )
+                .header(
+                    request_signature::REQUEST_SIGNATURE_HEADER.as_str(),
+                    signature.to_string(),
+                );
+        }
+
+        let unavailable = |e: String| {
+            error!(
+                tx_hash,
+                "Failed to forward transaction {tx_hash} to the leader {holder}: {e}"
+            );
+            call_execution_error(ErrorKind::LeaderUnavailable, e)
+        };
+
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| unavailable(e.to_string()))?;
+        let status = response.status();
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| unavailable(e.to_string()))?;
+        if !status.is_success() {
+            return Err(unavailable(format!(
+                "{status}: {}",
+                String::from_utf8_lossy(&bytes)
+            )));
+        }
+
+        match serde_json::from_slice::<ForwardedResponse>(&bytes) {
+            Ok(ForwardedResponse {
+                result: Some(hash), ..
+            }) => Ok(hash),
+            // Relay the errors of the leader as is.
+            Ok(ForwardedResponse {
+                error: Some(error), ..
+            }) => Err(error.into_owned()),
+            Ok(_) => Err(unavailable("empty response".to_string())),
+            Err(e) => Err(unavailable(e.to_string())),
+        }
+    }
 }
 
 /// Helper function to create an internal error with a custom message.
-fn internal_error(msg: impl Into<String>) -> ErrorObjectOwned {
-    ErrorObject::owned(INTERNAL_ERROR_CODE, INTERNAL_ERROR_MSG, Some(msg.into()))
+fn internal_error(kind: ErrorKind, msg: impl Into<String>) -> ErrorObjectOwned {
+    error_object(INTERNAL_ERROR_CODE, INTERNAL_ERROR_MSG, kind, msg)
+}
+
+/// Helper function to create a call execution error with a custom message.
+fn call_execution_error(kind: ErrorKind, msg: impl Into<String>) -> ErrorObjectOwned {
+    error_object(CALL_EXECUTION_FAILED_CODE, INTERNAL_ERROR_MSG, kind, msg)
 }
 
 #[async_trait]
@@ -135,8 +935,8 @@ impl<Rpc> AgglayerServer for AgglayerImpl<Rpc>
 where
     Rpc: Middleware + 'static,
 {
-    #[instrument(skip(self, tx), fields(hash = tx.hash().to_string(), rollup_id = tx.tx.rollup_id), level = "debug")]
-    async fn send_tx(&self, tx: SignedTx) -> RpcResult<H256> {
+    #[instrument(skip(self, ext, tx), fields(hash = tx.hash().to_string(), rollup_id = tx.tx.rollup_id), level = "debug")]
+    async fn send_tx(&self, ext: &Extensions, tx: SignedTx) -> RpcResult<H256> {
         let tx_hash = tx.hash().to_string();
         debug!(
             "Received transaction {tx_hash} for rollup {}",
@@ -146,118 +946,541 @@ where
         let metrics_attrs = &[KeyValue::new("rollup_id", rollup_id_str)];
 
         agglayer_telemetry::SEND_TX.add(1, metrics_attrs);
+        let _timer = Timer::start(&SEND_TX_DURATION, metrics_attrs);
+
+        let auditor = self.kernel.auditor();
+        if auditor.is_enabled() {
+            auditor.record(AuditEvent::Received {
+                tx_hash: tx.hash(),
+                request_id: RequestId::current().map(|id| id.to_string()),
+                signed_tx: tx.clone(),
+            });
+        }
+
+        // Count the transaction in flight until it's processed, so that draining
+        // the agglayer waits for it.
+        let Some(admitted) = self.kernel.admission().admit() else {
+            return Err(paused_error(&tx_hash));
+        };
+
+        // Reject the inconsistent transactions before any further work.
+        if let Err(e) = tx.validate() {
+            error!(tx_hash, "Rejected transaction {tx_hash}: {e}");
+
+            return Err(invalid_params_error(ErrorKind::InvalidProof, e.to_string()));
+        }
+
+        // Reject the proofs not matching the format of the rollup early, before
+        // reaching out to L1 or the ZkEVM node.
+        if let Err(e) = tx
+            .tx
+            .zkp
+            .proof
+            .check_format(&self.kernel.proof_format(tx.tx.rollup_id))
+        {
+            agglayer_telemetry::REJECTED_PAYLOAD_SIZE.add(1, metrics_attrs);
+            error!(tx_hash, "Rejected transaction {tx_hash}: {e}");
+
+            return Err(invalid_params_error(ErrorKind::InvalidProof, e.to_string()));
+        }
+
+        // Reject the transaction if the credentials of the caller aren't scoped to
+        // the rollup.
+        if let Some(scope) = ext.get::<RollupScope>() {
+            if !scope.allows(tx.tx.rollup_id) {
+                return Err(unauthorized_error(format!(
+                    "Caller is not allowed to submit proofs for rollup {}",
+                    tx.tx.rollup_id
+                )));
+            }
+        }
+
+        if !self.kernel.access_list().allows(tx.tx.rollup_id) {
+            return Err(unauthorized_error(format!(
+                "Rollup {} is not allowed to submit proofs",
+                tx.tx.rollup_id
+            )));
+        }
 
         if !self.kernel.check_rollup_registered(tx.tx.rollup_id) {
             // Return an invalid params error if the rollup is not registered.
+            let error = ZkevmNodeVerificationError::InvalidRollupId(tx.tx.rollup_id);
+            return Err(invalid_params_error(error.kind(), error.to_string()));
+        }
+
+        // Answer the proofs settled already with their settlement, instead of
+        // verifying and settling them again, once the caller and the rollup are
+        // known to be allowed.
+        match self.kernel.settled_proof(tx.hash()) {
+            Ok(Some(settlement_tx_hash)) => {
+                info!("Transaction {tx_hash} already settled => {settlement_tx_hash:?}");
+                return Ok(settlement_tx_hash);
+            }
+            Ok(None) => {}
+            Err(e) => error!(
+                tx_hash,
+                "Failed to look up the settlement of transaction {tx_hash}: {e}"
+            ),
+        }
+
+        // The rollups with a pessimistic consensus are served by the certificate
+        // pipeline only.
+        if self.kernel.consensus_type(tx.tx.rollup_id) == Some(ConsensusType::Pessimistic) {
             return Err(invalid_params_error(
-                ZkevmNodeVerificationError::InvalidRollupId(tx.tx.rollup_id).to_string(),
+                ErrorKind::InvalidRollup,
+                format!(
+                    "rollup {} has a pessimistic consensus, its certificates are to be sent with \
+                     interop_sendCertificate",
+                    tx.tx.rollup_id
+                ),
             ));
         }
 
-        agglayer_telemetry::CHECK_TX.add(1, metrics_attrs);
+        // Reject the transaction right away while L1 is unreachable, rather than
+        // letting its verification time out.
+        if self.kernel.l1_circuit_open() {
+            return Err(l1_unavailable_error(&tx_hash));
+        }
 
-        // Run all the verification checks in parallel.
-        try_join!(
-            self.kernel
-                .verify_signature(&tx)
-                .map_err(|e| {
-                    error!(
-                        tx_hash,
-                        "Failed to verify the signature of transaction {tx_hash}: {e}"
-                    );
-                    invalid_params_error(e.to_string())
-                })
-                .map_ok(|_| {
-                    agglayer_telemetry::VERIFY_SIGNATURE.add(1, metrics_attrs);
-                }),
-            self.kernel
-                .verify_proof_eth_call(&tx)
-                .map_err(|e| {
-                    error!(
-                        tx_hash,
-                        "Failed to dry-run the verify_batches_trusted_aggregator for transaction \
-                         {tx_hash}: {e}"
-                    );
-                    invalid_params_error(e.to_string())
-                })
-                .map_ok(|_| {
-                    agglayer_telemetry::EXECUTE.add(1, metrics_attrs);
-                }),
-            self.kernel
-                .verify_proof_zkevm_node(&tx)
-                .map_err(|e| {
-                    error!(
+        // Attach to the processing of the same transaction if in flight, rather
+        // than verifying and settling it once more.
+        loop {
+            match self.in_flight.start(tx.hash()) {
+                Processing::Started(in_flight) => {
+                    let outcome = self.process_tx(ext, tx, admitted).await;
+                    // The submissions attached to this one don't give up when its
+                    // client does, they process the transaction themselves.
+                    if !matches!(&outcome, Err(error) if error.code() == DEADLINE_EXCEEDED_CODE) {
+                        in_flight.complete(&outcome);
+                    }
+
+                    return outcome;
+                }
+                Processing::Attached(attached) => {
+                    debug!(
                         tx_hash,
-                        "Failed to verify the batch local_exit_root and state_root of transaction \
-                         {tx_hash}: {e}"
+                        "Transaction {tx_hash} is being processed already, waiting for its outcome"
                     );
-                    invalid_params_error(e.to_string())
-                })
-                .map_ok(|_| {
-                    agglayer_telemetry::VERIFY_ZKP.add(1, metrics_attrs);
-                })
-        )?;
-
-        // Settle the proof on-chain and return the transaction hash.
-        let receipt = self.kernel.settle(&tx).await.map_err(|e| {
-            error!(tx_hash, "Failed to settle transaction {tx_hash} on L1: {e}");
-            internal_error(e.to_string())
-        })?;
+                    let outcome = match ext.get::<Deadline>() {
+                        Some(Deadline(deadline)) => timeout_at(*deadline, attached.outcome())
+                            .await
+                            .unwrap_or_else(|_| Some(Err(deadline_exceeded_error(&tx_hash)))),
+                        None => attached.outcome().await,
+                    };
+                    if let Some(outcome) = outcome {
+                        return outcome;
+                    }
+                }
+            }
+        }
+    }
 
-        agglayer_telemetry::SETTLE.add(1, metrics_attrs);
+    #[instrument(skip(self, ext, txs), fields(count = txs.len()), level = "debug")]
+    async fn send_txs(&self, ext: &Extensions, txs: Vec<SignedTx>) -> RpcResult<Vec<SendTxResult>> {
+        debug!("Received {} transactions", txs.len());
 
-        info!("Successfully settled transaction {tx_hash} => receipt {receipt:?}");
+        // Each transaction goes through the pipeline of `interop_sendTx` on its
+        // own, concurrently with the others.
+        let results = txs.into_iter().map(|tx| async move {
+            let tx_hash = tx.hash();
+            SendTxResult::new(tx_hash, self.send_tx(ext, tx).await)
+        });
 
-        Ok(receipt.transaction_hash)
+        Ok(futures::future::join_all(results).await)
     }
 
     #[instrument(skip(self), fields(hash = hash.to_string()), level = "debug")]
     async fn get_tx_status(&self, hash: H256) -> RpcResult<TxStatus> {
         debug!("Received request to get transaction status for hash {hash}");
+
+        if self.submissions.is_shadowed(&hash) {
+            return Ok("shadow".to_string());
+        }
+
+        // The transactions settled in the background are tracked by their own
+        // hash, until their settlement transaction is known.
+        let hash = match self.submissions.settlement(&hash) {
+            Some(SettlementProgress::Pending) => return Ok("pending".to_string()),
+            Some(SettlementProgress::Failed(reason)) => {
+                return Err(call_execution_error(ErrorKind::SettlementFailed, reason))
+            }
+            Some(SettlementProgress::Settled(settlement_tx_hash)) => settlement_tx_hash,
+            None => hash,
+        };
+
+        // Settlements already indexed don't require reaching out to L1, unless
+        // their finality is given by a block tag.
+        let settlements = self.kernel.settlements();
+        if let (Some(settlement), Some(last_indexed_block), SettlementFinality::Confirmations) = (
+            settlements.settlement_by_tx(hash),
+            settlements.last_indexed_block(),
+            self.kernel.settlement_finality(),
+        ) {
+            if settlement.verified_batches.block_number < last_indexed_block {
+                return Ok("done".to_string());
+            }
+        }
+
         let recipt = self.kernel.check_tx_status(hash).await.map_err(|e| {
             error!("Failed to get transaction status for hash {hash}: {e}");
 
-            ErrorObject::owned(
-                CALL_EXECUTION_FAILED_CODE,
-                INTERNAL_ERROR_MSG,
-                Some(format!("failed to get tx, error: {}", e)),
+            call_execution_error(
+                ErrorKind::L1Unavailable,
+                format!("failed to get tx, error: {}", e),
             )
         })?;
 
-        let current_block = self.kernel.current_l1_block_height().await.map_err(|e| {
-            error!("Failed to get current L1 block: {e}");
+        let final_block = self.kernel.final_l1_block_height().await.map_err(|e| {
+            error!("Failed to get the final L1 block: {e}");
 
-            ErrorObject::owned(
-                CALL_EXECUTION_FAILED_CODE,
-                INTERNAL_ERROR_MSG,
-                Some(format!("failed to get current L1 block, error: {}", e)),
+            call_execution_error(
+                ErrorKind::L1Unavailable,
+                format!("failed to get the final L1 block, error: {}", e),
             )
         })?;
 
         recipt
             .map(|recipt| match recipt.block_number {
-                Some(block_number) if block_number < current_block => "done".to_string(),
+                Some(block_number) if block_number <= final_block => "done".to_string(),
                 Some(_) => "pending".to_string(),
                 None => "not found".to_string(),
             })
             .ok_or_else(|| {
-                ErrorObject::owned(
-                    CALL_EXECUTION_FAILED_CODE,
-                    INTERNAL_ERROR_MSG,
-                    Some(format!("tx not found for hash: {}", hash)),
+                call_execution_error(
+                    ErrorKind::NotFound,
+                    format!("tx not found for hash: {}", hash),
                 )
             })
     }
 
-    async fn send_certificate(&self, certificate: ()) -> RpcResult<()> {
+    #[instrument(skip(self), fields(hash = hash.to_string()), level = "debug")]
+    async fn get_tx_attestation(&self, hash: H256) -> RpcResult<Attestation> {
+        self.attestations.get(&hash).ok_or_else(|| {
+            call_execution_error(
+                ErrorKind::NotFound,
+                format!("attestation not found for hash: {}", hash),
+            )
+        })
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn get_network_status(&self) -> RpcResult<Vec<RollupStatus>> {
+        let statuses = self
+            .kernel
+            .registered_rollups()
+            .into_iter()
+            .map(|rollup_id| async move {
+                let on_chain_last_verified_batch =
+                    match self.kernel.settlements().last_verified_batch(rollup_id) {
+                        Some(batch) => Some(batch),
+                        None => self
+                            .kernel
+                            .get_last_verified_batch(rollup_id)
+                            .await
+                            .inspect_err(|e| {
+                                error!(
+                                    "Failed to get the last verified batch of rollup {rollup_id}: \
+                                     {e}"
+                                )
+                            })
+                            .ok(),
+                    };
+                let activity = self.submissions.activity(rollup_id);
+
+                RollupStatus {
+                    rollup_id,
+                    on_chain_last_verified_batch,
+                    last_accepted_batch: activity.last_accepted_batch,
+                    pending_submissions: activity.pending_submissions,
+                    last_settlement_time: activity.last_settlement_time,
+                    circuit_breaker: if self.kernel.l1_circuit_open() {
+                        CircuitBreakerState::Open
+                    } else {
+                        CircuitBreakerState::Closed
+                    },
+                    consensus_type: self.kernel.consensus_type(rollup_id).unwrap_or_default(),
+                    shadow: self.kernel.is_shadow(rollup_id),
+                    shadowed_submissions: activity.shadowed_submissions,
+                }
+            });
+
+        Ok(futures::future::join_all(statuses).await)
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn get_spend_report(&self, rollup_id: u32, range: EpochRange) -> RpcResult<SpendReport> {
+        if !self.kernel.check_rollup_registered(rollup_id) {
+            return Err(invalid_params_error(
+                ErrorKind::InvalidRollup,
+                format!("invalid rollup id: {rollup_id}"),
+            ));
+        }
+
+        Ok(self
+            .kernel
+            .spending()
+            .report(rollup_id, range.from, range.to))
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn get_latest_settled_batch(&self, rollup_id: u32) -> RpcResult<LatestSettledBatch> {
+        if !self.kernel.check_rollup_registered(rollup_id) {
+            return Err(invalid_params_error(
+                ErrorKind::InvalidRollup,
+                format!("invalid rollup id: {rollup_id}"),
+            ));
+        }
+
+        let settled = self.kernel.last_settled_batch(rollup_id).map_err(|e| {
+            error!("Failed to get the last settled batch of rollup {rollup_id}: {e}");
+
+            internal_error(
+                ErrorKind::Internal,
+                format!("failed to get the last settled batch, error: {e}"),
+            )
+        })?;
+
+        let on_chain_last_verified_batch = self
+            .kernel
+            .get_last_verified_batch(rollup_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to get the last verified batch of rollup {rollup_id}: {e}");
+
+                call_execution_error(
+                    ErrorKind::L1Unavailable,
+                    format!("failed to get the last verified batch, error: {e}"),
+                )
+            })?;
+
+        Ok(LatestSettledBatch {
+            rollup_id,
+            settled: settled.map(Into::into),
+            on_chain_last_verified_batch,
+        })
+    }
+
+    #[instrument(skip(self), level = "debug")]
+    async fn get_epoch_configuration(&self) -> RpcResult<EpochConfiguration> {
+        Ok(EpochConfiguration::from(self.clock_ref.as_ref()))
+    }
+
+    #[instrument(skip(self, ext, certificate), fields(hash = certificate.hash().to_string(), network_id = certificate.network_id), level = "debug")]
+    async fn send_certificate(
+        &self,
+        ext: &Extensions,
+        certificate: Certificate,
+    ) -> RpcResult<H256> {
+        let hash = certificate.hash();
+        let hash_str = hash.to_string();
+        let network_id = certificate.network_id;
+        debug!("Received certificate {hash_str} for network {network_id}");
+        let metrics_attrs = &[KeyValue::new("rollup_id", network_id.to_string())];
+
+        agglayer_telemetry::SEND_CERTIFICATE.add(1, metrics_attrs);
+
+        let Some(_admitted) = self.kernel.admission().admit() else {
+            return Err(paused_error(&hash_str));
+        };
+
+        if let Some(scope) = ext.get::<RollupScope>() {
+            if !scope.allows(network_id) {
+                return Err(unauthorized_error(format!(
+                    "Caller is not allowed to submit certificates for network {network_id}"
+                )));
+            }
+        }
+
+        if !self.kernel.access_list().allows(network_id) {
+            return Err(unauthorized_error(format!(
+                "Network {network_id} is not allowed to submit certificates"
+            )));
+        }
+
+        // Only the rollups with a pessimistic consensus are served by the
+        // certificate pipeline.
+        if self.kernel.consensus_type(network_id) != Some(ConsensusType::Pessimistic) {
+            return Err(invalid_params_error(
+                ErrorKind::InvalidRollup,
+                format!("network {network_id} doesn't have a pessimistic consensus"),
+            ));
+        }
+
+        if self.kernel.l1_circuit_open() {
+            return Err(l1_unavailable_error(&hash_str));
+        }
+
+        self.rate_limiter
+            .check(network_id)
+            .map_err(|e| rate_limited_error(&hash_str, e))?;
+
+        certificate.validate().map_err(|e| {
+            error!(hash = hash_str, "Rejected certificate {hash_str}: {e}");
+            invalid_params_error(e.kind(), e.to_string())
+        })?;
+
+        self.kernel
+            .verify_certificate_signature(&certificate)
+            .await
+            .map_err(|e| {
+                error!(
+                    hash = hash_str,
+                    "Failed to verify the signature of certificate {hash_str}: {e}"
+                );
+                invalid_params_error(e.kind(), e.to_string())
+            })?;
+
+        // Don't accept the certificates that would be proven but never settled.
+        if !self.kernel.settles_certificates() {
+            warn!(
+                hash = hash_str,
+                "Rejected certificate {hash_str}: certificates can't be settled yet"
+            );
+            return Err(unsupported_error(
+                "the settlement of the certificates isn't supported until the rollup manager \
+                 contract verifies the pessimistic proofs",
+            ));
+        }
+
+        self.certificates
+            .insert(&certificate)
+            .map_err(|e| invalid_params_error(e.kind(), e.to_string()))?;
+
+        // Schedule the certificate to be packed at the end of the current epoch.
         if let Err(error) = self.certificate_sender.send(certificate).await {
             error!("Failed to send certificate: {error}");
+            self.certificates.remove(&hash);
 
-            return Err(internal_error("Unable to send certificate to collector"));
+            return Err(internal_error(
+                ErrorKind::Internal,
+                "Unable to send certificate to collector",
+            ));
         }
 
-        Ok(())
+        Ok(hash)
+    }
+
+    async fn subscribe_epochs(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+        let events = self.clock_ref.subscribe()?;
+        let clock_ref = self.clock_ref.clone();
+        let sink = pending.accept().await?;
+
+        pipe(
+            sink,
+            events,
+            "epochs",
+            |event| Some(EpochChange::from(event)),
+            |_| false,
+            |skipped| {
+                Some(EpochChange::from(Event::MissedEpochs {
+                    skipped_events: skipped,
+                    epoch: clock_ref.current_epoch(),
+                }))
+            },
+        )
+        .await
+    }
+
+    async fn subscribe_tx_updates(
+        &self,
+        pending: PendingSubscriptionSink,
+        hash: H256,
+    ) -> SubscriptionResult {
+        let updates = self.kernel.tx_updates().subscribe();
+        let sink = pending.accept().await?;
+
+        // Updates of other transactions are skipped, the subscription ends with
+        // the last update of the transaction.
+        pipe(
+            sink,
+            updates,
+            "tx updates",
+            |update| (update.tx_hash == hash).then_some(update),
+            |update: &TxUpdate| update.status.is_terminal(),
+            |_| None,
+        )
+        .await
+    }
+
+    async fn subscribe_all_tx_updates(
+        &self,
+        pending: PendingSubscriptionSink,
+    ) -> SubscriptionResult {
+        let updates = self.kernel.tx_updates().subscribe();
+        let sink = pending.accept().await?;
+
+        pipe(sink, updates, "all tx updates", Some, |_| false, |_| None).await
     }
 }
 
+/// The receiving half of a broadcast channel forwarded by [`pipe`].
+trait Receiver<T> {
+    fn recv(&mut self) -> impl Future<Output = Result<T, RecvError>> + Send;
+}
+
+impl<T: Clone + Send> Receiver<T> for broadcast::Receiver<T> {
+    fn recv(&mut self) -> impl Future<Output = Result<T, RecvError>> + Send {
+        broadcast::Receiver::recv(self)
+    }
+}
+
+impl Receiver<Event> for EventReceiver {
+    fn recv(&mut self) -> impl Future<Output = Result<Event, RecvError>> + Send {
+        EventReceiver::recv(self)
+    }
+}
+
+/// Forward the items of the given broadcast channel selected by `select` to the
+/// subscriber, until it goes away or an item matching `until` is sent.
+///
+/// The items skipped while the subscriber lags behind are notified with the
+/// item built by `missed`, if any.
+async fn pipe<T: Clone, Item: Serialize>(
+    mut sink: SubscriptionSink,
+    mut items: impl Receiver<T>,
+    name: &str,
+    mut select: impl FnMut(T) -> Option<Item>,
+    until: impl Fn(&Item) -> bool,
+    missed: impl Fn(u64) -> Option<Item>,
+) -> SubscriptionResult {
+    loop {
+        let item = tokio::select! {
+            _ = sink.closed() => break,
+            item = items.recv() => item,
+        };
+
+        let item = match item {
+            Ok(item) => select(item),
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(
+                    "The {name} subscription {:?} skipped {skipped} items",
+                    sink.subscription_id()
+                );
+
+                missed(skipped)
+            }
+            Err(RecvError::Closed) => break,
+        };
+        let Some(item) = item else {
+            continue;
+        };
+
+        let message = SubscriptionMessage::from_json(&item)?;
+        // Never hold the publisher back, the subscribers falling behind get
+        // disconnected.
+        if let Err(error) = sink.try_send(message) {
+            warn!(
+                "Dropping the {name} subscription {:?}: {error}",
+                sink.subscription_id()
+            );
+            break;
+        }
+        if until(&item) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
 type TxStatus = String;
@@ -0,0 +1,270 @@
+//! Per-rollup status reported by `interop_getNetworkStatus` and
+//! `interop_getLatestSettledBatch`.
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use agglayer_config::ConsensusType;
+use agglayer_storage::SettledBatch;
+use ethers::types::H256;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// The state of the verification circuit breaker of a rollup.
+///
+/// The breaker trips for every rollup at once, while L1 is unreachable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CircuitBreakerState {
+    /// Submissions are verified as usual.
+    Closed,
+    /// Submissions are rejected right away, until L1 recovers.
+    Open,
+}
+
+/// The status of a single rollup.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RollupStatus {
+    pub(crate) rollup_id: u32,
+    /// The last verified batch according to the rollup manager contract, or
+    /// `None` if L1 couldn't be reached.
+    pub(crate) on_chain_last_verified_batch: Option<u64>,
+    /// The latest batch that passed the agglayer verification.
+    pub(crate) last_accepted_batch: Option<u64>,
+    /// The number of submissions currently being processed.
+    pub(crate) pending_submissions: u64,
+    /// The time of the last successful settlement, in seconds since the unix
+    /// epoch.
+    pub(crate) last_settlement_time: Option<u64>,
+    pub(crate) circuit_breaker: CircuitBreakerState,
+    /// The consensus of the rollup.
+    #[schemars(with = "String")]
+    pub(crate) consensus_type: ConsensusType,
+    /// Whether the rollup is in shadow mode, its submissions being verified
+    /// but never settled on L1.
+    pub(crate) shadow: bool,
+    /// The number of submissions verified in shadow mode.
+    pub(crate) shadowed_submissions: u64,
+}
+
+/// A batch settled by the agglayer.
+#[derive(Clone, Copy, Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SettledBatchStatus {
+    /// The number of the last batch verified by the settlement.
+    pub(crate) batch: u64,
+    #[schemars(with = "String")]
+    pub(crate) state_root: H256,
+    #[schemars(with = "String")]
+    pub(crate) local_exit_root: H256,
+    /// The hash of the settlement transaction.
+    #[schemars(with = "String")]
+    pub(crate) settlement_tx_hash: H256,
+}
+
+impl From<SettledBatch> for SettledBatchStatus {
+    fn from(settled: SettledBatch) -> Self {
+        Self {
+            batch: settled.batch,
+            state_root: settled.state_root,
+            local_exit_root: settled.local_exit_root,
+            settlement_tx_hash: settled.settlement_tx_hash,
+        }
+    }
+}
+
+/// The last batch of a rollup settled by the agglayer, along with the last
+/// batch verified on L1, for the sequencers to resync after some downtime.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LatestSettledBatch {
+    pub(crate) rollup_id: u32,
+    /// The last batch settled by the agglayer, or `None` if it didn't settle
+    /// any batch of the rollup or doesn't index its settled proofs.
+    pub(crate) settled: Option<SettledBatchStatus>,
+    /// The last verified batch according to the rollup manager contract.
+    pub(crate) on_chain_last_verified_batch: u64,
+}
+
+/// The submission activity of a rollup, as observed by the RPC server.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RollupActivity {
+    pub(crate) last_accepted_batch: Option<u64>,
+    pub(crate) pending_submissions: u64,
+    pub(crate) last_settlement_time: Option<u64>,
+    pub(crate) shadowed_submissions: u64,
+}
+
+/// Track the submissions received by the RPC server, per rollup.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SubmissionTracker {
+    rollups: Arc<Mutex<HashMap<u32, RollupActivity>>>,
+    /// The hashes of the submissions verified in shadow mode.
+    shadowed: Arc<Mutex<HashSet<H256>>>,
+    /// The hashes of the submissions being processed.
+    in_flight: Arc<Mutex<HashSet<H256>>>,
+    /// The outcome of the settlement of the submissions, by hash: the hash of
+    /// the settlement transaction, or the reason of the failure.
+    settlements: Arc<Mutex<HashMap<H256, Result<H256, String>>>>,
+}
+
+/// The progress of the settlement of a submission.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SettlementProgress {
+    /// The submission is being verified or settled.
+    Pending,
+    /// The submission was settled with the given L1 transaction.
+    Settled(H256),
+    /// The settlement failed for the given reason.
+    Failed(String),
+}
+
+impl SubmissionTracker {
+    /// Record a new submission of the given proof for the given rollup,
+    /// unless the same proof is being processed already.
+    ///
+    /// The submission is considered pending until the returned guard is
+    /// dropped.
+    pub(crate) fn start(&self, rollup_id: u32, hash: H256) -> Option<PendingSubmission> {
+        if !self
+            .in_flight
+            .lock()
+            .expect("Submission tracker lock poisoned")
+            .insert(hash)
+        {
+            return None;
+        }
+        self.update(rollup_id, |activity| activity.pending_submissions += 1);
+
+        Some(PendingSubmission {
+            tracker: self.clone(),
+            rollup_id,
+            hash,
+        })
+    }
+
+    /// Get the activity of the given rollup.
+    pub(crate) fn activity(&self, rollup_id: u32) -> RollupActivity {
+        self.rollups
+            .lock()
+            .expect("Submission tracker lock poisoned")
+            .get(&rollup_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Check if the given submission was verified in shadow mode.
+    pub(crate) fn is_shadowed(&self, hash: &H256) -> bool {
+        self.shadowed
+            .lock()
+            .expect("Submission tracker lock poisoned")
+            .contains(hash)
+    }
+
+    /// Get the progress of the settlement of the given submission, if known.
+    pub(crate) fn settlement(&self, hash: &H256) -> Option<SettlementProgress> {
+        if self
+            .in_flight
+            .lock()
+            .expect("Submission tracker lock poisoned")
+            .contains(hash)
+        {
+            return Some(SettlementProgress::Pending);
+        }
+
+        self.settlements
+            .lock()
+            .expect("Submission tracker lock poisoned")
+            .get(hash)
+            .cloned()
+            .map(|outcome| match outcome {
+                Ok(settlement_tx_hash) => SettlementProgress::Settled(settlement_tx_hash),
+                Err(reason) => SettlementProgress::Failed(reason),
+            })
+    }
+
+    fn record_settlement(&self, hash: H256, outcome: Result<H256, String>) {
+        self.settlements
+            .lock()
+            .expect("Submission tracker lock poisoned")
+            .insert(hash, outcome);
+    }
+
+    fn update(&self, rollup_id: u32, f: impl FnOnce(&mut RollupActivity)) {
+        f(self
+            .rollups
+            .lock()
+            .expect("Submission tracker lock poisoned")
+            .entry(rollup_id)
+            .or_default())
+    }
+}
+
+/// A submission being processed by the RPC server.
+#[derive(Debug)]
+pub(crate) struct PendingSubmission {
+    tracker: SubmissionTracker,
+    rollup_id: u32,
+    hash: H256,
+}
+
+impl PendingSubmission {
+    /// Record that the submitted batch passed the verification.
+    pub(crate) fn accepted(&self, batch: u64) {
+        self.tracker.update(self.rollup_id, |activity| {
+            activity.last_accepted_batch = activity.last_accepted_batch.max(Some(batch));
+        });
+    }
+
+    /// Record that the submission passed the verification in shadow mode, and
+    /// won't be settled.
+    pub(crate) fn shadowed(&self, hash: H256) {
+        self.tracker
+            .shadowed
+            .lock()
+            .expect("Submission tracker lock poisoned")
+            .insert(hash);
+        self.tracker.update(self.rollup_id, |activity| {
+            activity.shadowed_submissions += 1;
+        });
+    }
+
+    /// Record that the submission got settled on L1 with the given
+    /// transaction.
+    pub(crate) fn settled(&self, settlement_tx_hash: H256) {
+        self.tracker
+            .record_settlement(self.hash, Ok(settlement_tx_hash));
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        self.tracker.update(self.rollup_id, |activity| {
+            activity.last_settlement_time = Some(now);
+        });
+    }
+
+    /// Record that the settlement of the submission failed for the given
+    /// reason.
+    pub(crate) fn failed(&self, reason: impl Into<String>) {
+        self.tracker
+            .record_settlement(self.hash, Err(reason.into()));
+    }
+}
+
+impl Drop for PendingSubmission {
+    fn drop(&mut self) {
+        self.tracker
+            .in_flight
+            .lock()
+            .expect("Submission tracker lock poisoned")
+            .remove(&self.hash);
+        self.tracker.update(self.rollup_id, |activity| {
+            activity.pending_submissions = activity.pending_submissions.saturating_sub(1)
+        });
+    }
+}
@@ -0,0 +1,324 @@
+//! Rate limiting of the submissions, per rollup, and of the requests, per
+//! IP address.
+//!
+//! Every rollup gets a token bucket, so that a rollup flooding the agglayer
+//! only ever gets its own submissions rejected. Likewise, every IP address
+//! gets a token bucket, so that an abusive client only ever gets its own
+//! requests rejected.
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    net::IpAddr,
+    pin::Pin,
+    sync::{Arc, Mutex, RwLock},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use agglayer_config::{RateLimit, RateLimitConfig};
+use hyper::{
+    header::{HeaderValue, RETRY_AFTER},
+    StatusCode,
+};
+use jsonrpsee::server::{HttpBody, HttpRequest, HttpResponse};
+use thiserror::Error;
+use tokio::time::Instant;
+use tower::{Layer, Service};
+
+use super::PeerAddr;
+
+/// The number of IP addresses tracked above which the buckets refilled to
+/// their burst are dropped.
+const MAX_TRACKED_IPS: usize = 10_000;
+
+/// The rollup exceeded its rate limit.
+#[derive(Error, Debug)]
+#[error("rollup {rollup_id} exceeded its rate limit, retry in {retry_after:?}")]
+pub(crate) struct RateLimited {
+    pub(crate) rollup_id: u32,
+    pub(crate) retry_after: Duration,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    /// Refill the bucket up to the given time.
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.rate).min(f64::from(limit.burst));
+        self.refilled_at = now;
+    }
+}
+
+/// A set of token buckets, by key.
+#[derive(Debug)]
+struct TokenBuckets<K> {
+    buckets: Mutex<HashMap<K, Bucket>>,
+}
+
+impl<K> Default for TokenBuckets<K> {
+    fn default() -> Self {
+        Self {
+            buckets: Mutex::default(),
+        }
+    }
+}
+
+impl<K: Eq + Hash> TokenBuckets<K> {
+    /// Take a token from the bucket of the given key, returning the time to
+    /// wait for the next token if the bucket is empty.
+    fn take(&self, key: K, limit: &RateLimit) -> Result<(), Duration> {
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().expect("Rate limiter lock poisoned");
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: f64::from(limit.burst),
+            refilled_at: now,
+        });
+        bucket.refill(limit, now);
+
+        if bucket.tokens < 1.0 {
+            return Err(if limit.rate > 0.0 {
+                Duration::from_secs_f64((1.0 - bucket.tokens) / limit.rate)
+            } else {
+                Duration::MAX
+            });
+        }
+
+        bucket.tokens -= 1.0;
+
+        Ok(())
+    }
+
+    /// Drop the buckets refilled to their burst, if more than `max` are
+    /// tracked, as they're equivalent to new ones.
+    fn prune(&self, limit: &RateLimit, max: usize) {
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().expect("Rate limiter lock poisoned");
+        if buckets.len() > max {
+            buckets.retain(|_, bucket| {
+                bucket.refill(limit, now);
+                bucket.tokens < f64::from(limit.burst)
+            });
+        }
+    }
+}
+
+/// The token buckets of the rollups.
+///
+/// The clones share the buckets and the rate limits, reloaded at runtime.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RateLimiter {
+    config: Arc<RwLock<Option<Arc<RateLimitConfig>>>>,
+    buckets: Arc<TokenBuckets<u32>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(config: Option<&RateLimitConfig>) -> Self {
+        let limiter = Self::default();
+        limiter.reload(config);
+
+        limiter
+    }
+
+    /// Replace the rate limits, keeping the tokens left in the buckets.
+    pub(crate) fn reload(&self, config: Option<&RateLimitConfig>) {
+        *self.config.write().expect("Rate limiter lock poisoned") = config.cloned().map(Arc::new);
+    }
+
+    /// Take a token from the bucket of the given rollup.
+    pub(crate) fn check(&self, rollup_id: u32) -> Result<(), RateLimited> {
+        let Some(config) = self
+            .config
+            .read()
+            .expect("Rate limiter lock poisoned")
+            .clone()
+        else {
+            return Ok(());
+        };
+
+        self.buckets
+            .take(rollup_id, config.of(rollup_id))
+            .map_err(|retry_after| RateLimited {
+                rollup_id,
+                retry_after,
+            })
+    }
+}
+
+/// Tower layer rate limiting the requests, per IP address of the peer.
+///
+/// Behind a reverse proxy, every request comes from the address of the proxy,
+/// so the rate limit applies to all of the clients at once.
+#[derive(Clone, Debug)]
+pub(crate) struct IpRateLimitLayer {
+    limit: Arc<RwLock<Option<RateLimit>>>,
+    buckets: Arc<TokenBuckets<IpAddr>>,
+}
+
+impl IpRateLimitLayer {
+    /// Create a new [`IpRateLimitLayer`]. If no rate limit is configured,
+    /// requests are forwarded untouched.
+    pub(crate) fn new(limit: Option<&RateLimit>) -> Self {
+        Self {
+            limit: Arc::new(RwLock::new(limit.copied())),
+            buckets: Arc::default(),
+        }
+    }
+
+    /// Replace the rate limit of the services built by this layer, keeping
+    /// the tokens left in the buckets.
+    pub(crate) fn reload(&self, limit: Option<&RateLimit>) {
+        *self.limit.write().expect("Rate limiter lock poisoned") = limit.copied();
+    }
+}
+
+impl<S> Layer<S> for IpRateLimitLayer {
+    type Service = IpRateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IpRateLimit {
+            inner,
+            limit: self.limit.clone(),
+            buckets: self.buckets.clone(),
+        }
+    }
+}
+
+/// Tower service rate limiting the requests, per IP address of the peer.
+///
+/// Requests exceeding the rate limit are rejected with `429 Too Many
+/// Requests`, along with a `Retry-After` header.
+#[derive(Clone, Debug)]
+pub(crate) struct IpRateLimit<S> {
+    inner: S,
+    limit: Arc<RwLock<Option<RateLimit>>>,
+    buckets: Arc<TokenBuckets<IpAddr>>,
+}
+
+impl<S, B> Service<HttpRequest<B>> for IpRateLimit<S>
+where
+    S: Service<HttpRequest<B>, Response = HttpResponse>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest<B>) -> Self::Future {
+        let limit = *self.limit.read().expect("Rate limiter lock poisoned");
+        let (Some(limit), Some(PeerAddr(peer))) = (limit, request.extensions().get::<PeerAddr>())
+        else {
+            return Box::pin(self.inner.call(request));
+        };
+
+        self.buckets.prune(&limit, MAX_TRACKED_IPS);
+        if let Err(retry_after) = self.buckets.take(peer.ip(), &limit) {
+            agglayer_telemetry::RPC_RATE_LIMITED_REQUESTS.add(1, &[]);
+
+            let mut response = HttpResponse::builder()
+                .status(StatusCode::TOO_MANY_REQUESTS)
+                .body(HttpBody::from("rate limit exceeded"))
+                .expect("Unable to build rate limited response");
+            if retry_after != Duration::MAX {
+                response.headers_mut().insert(
+                    RETRY_AFTER,
+                    HeaderValue::from(retry_after.as_secs_f64().ceil() as u64),
+                );
+            }
+
+            return Box::pin(async { Ok(response) });
+        }
+
+        Box::pin(self.inner.call(request))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use agglayer_config::RateLimit;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn rollups_are_limited_independently() {
+        let limiter = RateLimiter::new(Some(&RateLimitConfig {
+            default: RateLimit {
+                rate: 1.0,
+                burst: 2,
+            },
+            rollups: HashMap::from([(
+                2,
+                RateLimit {
+                    rate: 0.0,
+                    burst: 1,
+                },
+            )]),
+        }));
+
+        assert!(limiter.check(1).is_ok());
+        assert!(limiter.check(1).is_ok());
+        let limited = limiter.check(1).unwrap_err();
+        assert_eq!(limited.retry_after, Duration::from_secs(1));
+
+        // Rollup 1 being limited leaves rollup 2 alone.
+        assert!(limiter.check(2).is_ok());
+        assert!(limiter.check(2).is_err());
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+
+        assert!(limiter.check(1).is_ok());
+        assert!(limiter.check(1).is_err());
+        assert!(limiter.check(2).is_err());
+
+        assert!(RateLimiter::new(None).check(1).is_ok());
+
+        // The reloaded rate limits apply to the existing buckets.
+        limiter.reload(Some(&RateLimitConfig {
+            default: RateLimit {
+                rate: 1.0,
+                burst: 3,
+            },
+            rollups: HashMap::new(),
+        }));
+        assert!(limiter.check(2).is_err());
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert!(limiter.check(2).is_ok());
+        assert!(limiter.check(2).is_err());
+
+        limiter.reload(None);
+        assert!(limiter.check(1).is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn refilled_buckets_are_pruned() {
+        let limit = RateLimit {
+            rate: 1.0,
+            burst: 1,
+        };
+        let buckets = TokenBuckets::default();
+
+        assert!(buckets.take(1, &limit).is_ok());
+        assert!(buckets.take(2, &limit).is_ok());
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert!(buckets.take(2, &limit).is_ok());
+
+        // Below the maximum, the refilled buckets are kept.
+        buckets.prune(&limit, 2);
+        assert_eq!(buckets.buckets.lock().unwrap().len(), 2);
+
+        buckets.prune(&limit, 1);
+        assert_eq!(buckets.buckets.lock().unwrap().len(), 1);
+        assert!(buckets.take(2, &limit).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Request IDs correlating the logs and the errors of the RPC calls.
+//!
+//! Every request is given the ID carried by its `x-request-id` header, or a
+//! random one if absent or invalid. The ID is attached to the tracing span of
+//! the request, returned in the `x-request-id` header of the response and in
+//! the data of the error objects, so that a failed submission can be looked up
+//! in the logs.
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use ethers::types::H128;
+use hyper::header::{HeaderName, HeaderValue};
+use jsonrpsee::server::{HttpRequest, HttpResponse};
+use tower::{Layer, Service};
+use tracing::{info_span, Instrument as _};
+
+/// The header carrying the request ID.
+pub(crate) const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The maximum length of the request IDs accepted from the clients.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    /// The ID of the request being served by the current task.
+    static CURRENT_REQUEST_ID: RequestId;
+}
+
+/// The ID of a request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct RequestId(Arc<str>);
+
+impl RequestId {
+    /// Generate a random request ID.
+    fn generate() -> Self {
+        Self(format!("{:x}", H128::random()).into())
+    }
+
+    /// Parse the request ID given by a client, made of at most
+    /// [`MAX_REQUEST_ID_LENGTH`] alphanumeric characters, `-`, `_`, `.` or
+    /// `:`.
+    fn parse(value: &HeaderValue) -> Option<Self> {
+        let value = value.to_str().ok()?;
+        let valid = !value.is_empty()
+            && value.len() <= MAX_REQUEST_ID_LENGTH
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+
+        valid.then(|| Self(value.into()))
+    }
+
+    /// Get the ID of the request being served by the current task, if any.
+    pub(crate) fn current() -> Option<Self> {
+        CURRENT_REQUEST_ID.try_with(Self::clone).ok()
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Tower layer giving an ID to every request.
+#[derive(Clone, Debug)]
+pub(crate) struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIds<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIds { inner }
+    }
+}
+
+/// Tower service giving an ID to every request.
+///
+/// The ID is attached to the extensions and the tracing span of the request,
+/// made available to the RPC methods through [`RequestId::current`], and
+/// returned in the `x-request-id` header of the response.
+#[derive(Clone, Debug)]
+pub(crate) struct RequestIds<S> {
+    inner: S,
+}
+
+impl<S, B> Service<HttpRequest<B>> for RequestIds<S>
+where
+    S: Service<HttpRequest<B>, Response = HttpResponse>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: HttpRequest<B>) -> Self::Future {
+        let id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(RequestId::parse)
+            .unwrap_or_else(RequestId::generate);
+        request.extensions_mut().insert(id.clone());
+
+        let span = info_span!("rpc_request", request_id = %id);
+        let response = span.in_scope(|| self.inner.call(request));
+        let header = HeaderValue::from_str(id.as_str()).expect("Request IDs are valid headers");
+
+        Box::pin(
+            CURRENT_REQUEST_ID.scope(
+                id,
+                async move {
+                    let mut response = response.await?;
+                    response.headers_mut().insert(REQUEST_ID_HEADER, header);
+
+                    Ok(response)
+                }
+                .instrument(span),
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_request_ids_are_validated() {
+        let parse = |value: &str| RequestId::parse(&HeaderValue::from_str(value).unwrap());
+
+        assert_eq!(
+            parse("req-42:retry_1.0").map(|id| id.to_string()),
+            Some("req-42:retry_1.0".to_string())
+        );
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("with space"), None);
+        assert_eq!(parse(&"a".repeat(MAX_REQUEST_ID_LENGTH + 1)), None);
+
+        assert_eq!(RequestId::generate().as_str().len(), 32);
+        assert_eq!(RequestId::current(), None);
+    }
+}
@@ -0,0 +1,217 @@
+//! Request-level signature authentication for the RPC server.
+//!
+//! When enabled, every request body must be signed by one of the configured
+//! addresses. The signature is an EIP-191 personal signature of the
+//! `x-request-timestamp` header, a newline and the raw request body, hex
+//! encoded in the `x-request-signature` header. The timestamp, in seconds
+//! since the unix epoch, must be within [`MAX_REQUEST_AGE`] of the time of the
+//! server, so that a captured request can't be replayed later on.
+//!
+//! WebSocket upgrades carry no body, their signature only covers their
+//! timestamp: the messages exchanged over the WebSocket connection once
+//! upgraded aren't signed.
+use std::{
+    collections::HashSet,
+    future::Future,
+    pin::Pin,
+    str::FromStr as _,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ethers::types::{Address, Signature};
+use http_body_util::{BodyExt as _, Limited};
+use hyper::{body::Bytes, header::HeaderName, Method, StatusCode};
+use jsonrpsee::server::{HttpBody, HttpRequest, HttpResponse};
+use tower::{BoxError, Layer, Service};
+use tracing::debug;
+
+/// The header carrying the signature of the request body.
+pub(crate) const REQUEST_SIGNATURE_HEADER: HeaderName =
+    HeaderName::from_static("x-request-signature");
+
+/// The header carrying the signed time of the request, in seconds since the
+/// unix epoch.
+pub(crate) const REQUEST_TIMESTAMP_HEADER: HeaderName =
+    HeaderName::from_static("x-request-timestamp");
+
+/// The maximum difference between the signed time of a request and the time
+/// of the server, either way.
+pub(crate) const MAX_REQUEST_AGE: Duration = Duration::from_secs(300);
+
+/// Tower layer enforcing the signature of the request bodies.
+#[derive(Clone, Debug)]
+pub(crate) struct RequestSignatureLayer {
+    signers: Arc<HashSet<Address>>,
+    max_body_size: usize,
+}
+
+impl RequestSignatureLayer {
+    /// Create a new [`RequestSignatureLayer`] accepting signatures from the
+    /// given addresses.
+    pub(crate) fn new(signers: &[Address], max_body_size: u32) -> Self {
+        Self {
+            signers: Arc::new(signers.iter().copied().collect()),
+            max_body_size: max_body_size as usize,
+        }
+    }
+}
+
+impl<S> Layer<S> for RequestSignatureLayer {
+    type Service = RequestSignature<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestSignature {
+            inner,
+            signers: self.signers.clone(),
+            max_body_size: self.max_body_size,
+        }
+    }
+}
+
+/// Tower service enforcing the signature of the request bodies.
+///
+/// - If no signer is configured, requests are forwarded untouched.
+/// - Plain `GET` requests (health checks) and `OPTIONS` requests (CORS
+///   preflights) carry no body and are forwarded untouched.
+/// - Any other request, including WebSocket upgrades, must carry a recent
+///   timestamp and a valid signature of it along with its body, otherwise it is
+///   rejected with `401 Unauthorized`.
+#[derive(Clone, Debug)]
+pub(crate) struct RequestSignature<S> {
+    inner: S,
+    signers: Arc<HashSet<Address>>,
+    max_body_size: usize,
+}
+
+/// Errors related to the request signature verification.
+#[derive(Debug, thiserror::Error)]
+enum RequestSignatureError {
+    #[error("missing {REQUEST_SIGNATURE_HEADER} header")]
+    MissingSignature,
+    #[error("malformed request signature")]
+    MalformedSignature,
+    #[error("missing {REQUEST_TIMESTAMP_HEADER} header")]
+    MissingTimestamp,
+    #[error("malformed request timestamp")]
+    MalformedTimestamp,
+    #[error("stale request timestamp: {0}")]
+    StaleTimestamp(u64),
+    #[error("unable to read the request body")]
+    UnreadableBody,
+    #[error("unable to recover the request signer")]
+    UnrecoverableSigner,
+    #[error("unauthorized request signer: {0:?}")]
+    UnauthorizedSigner(Address),
+}
+
+impl<S, B> Service<HttpRequest<B>> for RequestSignature<S>
+where
+    S: Service<HttpRequest<HttpBody>, Response = HttpResponse> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    B: hyper::body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: HttpRequest<B>) -> Self::Future {
+        let is_bodyless = matches!(*request.method(), Method::GET | Method::OPTIONS)
+            && !request.headers().contains_key(hyper::header::UPGRADE);
+
+        if self.signers.is_empty() || is_bodyless {
+            return Box::pin(self.inner.call(request.map(HttpBody::new)));
+        }
+
+        // Take the service that was driven to readiness and leave a clone in its
+        // place, as the request needs to be buffered before being forwarded.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let signers = self.signers.clone();
+        let max_body_size = self.max_body_size;
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+
+            let verification = async {
+                let signature = parts
+                    .headers
+                    .get(REQUEST_SIGNATURE_HEADER)
+                    .ok_or(RequestSignatureError::MissingSignature)?
+                    .to_str()
+                    .ok()
+                    .and_then(|value| Signature::from_str(value).ok())
+                    .ok_or(RequestSignatureError::MalformedSignature)?;
+
+                let timestamp = parts
+                    .headers
+                    .get(REQUEST_TIMESTAMP_HEADER)
+                    .ok_or(RequestSignatureError::MissingTimestamp)?
+                    .to_str()
+                    .ok()
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .ok_or(RequestSignatureError::MalformedTimestamp)?;
+                if !is_recent(timestamp, SystemTime::now()) {
+                    return Err(RequestSignatureError::StaleTimestamp(timestamp));
+                }
+
+                let body = Limited::new(body, max_body_size)
+                    .collect()
+                    .await
+                    .map_err(|_| RequestSignatureError::UnreadableBody)?
+                    .to_bytes();
+
+                let signer = signature
+                    .recover(signed_message(timestamp, &body))
+                    .map_err(|_| RequestSignatureError::UnrecoverableSigner)?;
+
+                if !signers.contains(&signer) {
+                    return Err(RequestSignatureError::UnauthorizedSigner(signer));
+                }
+
+                Ok(body)
+            };
+
+            match verification.await {
+                Ok(body) => {
+                    inner
+                        .call(HttpRequest::from_parts(
+                            parts,
+                            HttpBody::from(body.to_vec()),
+                        ))
+                        .await
+                }
+                Err(error) => {
+                    debug!("Rejected request: {error}");
+
+                    Ok(HttpResponse::builder()
+                        .status(StatusCode::UNAUTHORIZED)
+                        .body(HttpBody::from(error.to_string()))
+                        .expect("Unable to build unauthorized response"))
+                }
+            }
+        })
+    }
+}
+
+/// The message signed for a request: its timestamp, a newline and its body.
+pub(crate) fn signed_message(timestamp: u64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{timestamp}\n").into_bytes();
+    message.extend_from_slice(body);
+
+    message
+}
+
+/// Whether the given timestamp is within [`MAX_REQUEST_AGE`] of `now`.
+fn is_recent(timestamp: u64, now: SystemTime) -> bool {
+    let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+
+    now.abs_diff(timestamp) <= MAX_REQUEST_AGE.as_secs()
+}
@@ -0,0 +1,65 @@
+//! Results of the transactions submitted at once with `interop_sendTxs`.
+use ethers::types::H256;
+use jsonrpsee::{core::RpcResult, types::ErrorObjectOwned};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::ErrorData;
+
+/// The result of a transaction submitted with `interop_sendTxs`, either the
+/// hash `interop_sendTx` would have returned, or its error.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SendTxResult {
+    /// The hash of the submitted transaction.
+    #[schemars(with = "String")]
+    pub(crate) tx_hash: H256,
+    /// The hash of the settlement transaction, or of the submitted
+    /// transaction if settled in the background or in shadow mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<String>")]
+    pub(crate) hash: Option<H256>,
+    /// The reason the transaction was rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<SendTxError>,
+}
+
+/// The error of a transaction rejected by `interop_sendTxs`, as returned by
+/// `interop_sendTx`.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SendTxError {
+    pub(crate) code: i32,
+    pub(crate) message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) data: Option<ErrorData>,
+}
+
+impl SendTxResult {
+    pub(crate) fn new(tx_hash: H256, result: RpcResult<H256>) -> Self {
+        match result {
+            Ok(hash) => Self {
+                tx_hash,
+                hash: Some(hash),
+                error: None,
+            },
+            Err(error) => Self {
+                tx_hash,
+                hash: None,
+                error: Some(error.into()),
+            },
+        }
+    }
+}
+
+impl From<ErrorObjectOwned> for SendTxError {
+    fn from(error: ErrorObjectOwned) -> Self {
+        Self {
+            code: error.code(),
+            message: error.message().to_string(),
+            data: error
+                .data()
+                .and_then(|data| serde_json::from_str(data.get()).ok()),
+        }
+    }
+}
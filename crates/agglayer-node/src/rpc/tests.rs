@@ -1,20 +1,50 @@
 use std::net::IpAddr;
+use std::num::NonZeroU64;
 use std::sync::Arc;
 use std::time::Duration;
 
-use agglayer_config::Config;
+use agglayer_clock::{Clock as _, ClockConfiguration, ClockRef, ManualClock, TimeClock};
+use agglayer_config::{
+    AccessLogConfig, ApiKeyConfig, Config, ConsensusType, JwtConfig, ProofFormat, ProofSystem,
+};
+use agglayer_storage::{AuditLog, SettledBatch, SettledProofIndex};
 use ethers::providers::{self, Http, Middleware, Provider, ProviderExt as _};
-use ethers::types::TransactionRequest;
+use ethers::signers::{LocalWallet, Signer as _};
+use ethers::types::{Signature, TransactionRequest, H256};
 use ethers::utils::Anvil;
-use http_body_util::Empty;
+use http_body_util::{Empty, Full};
+use hyper::header::{HeaderMap, HeaderValue};
 use hyper_util::client::legacy::Client;
 use hyper_util::rt::TokioExecutor;
-use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::client::{ClientT, Error as ClientError, SubscriptionClientT};
 use jsonrpsee::http_client::HttpClientBuilder;
 use jsonrpsee::rpc_params;
+use jsonrpsee::types::error::{CALL_EXECUTION_FAILED_CODE, INVALID_PARAMS_CODE};
+use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::ws_client::WsClientBuilder;
+use tokio_util::sync::CancellationToken;
 
-use crate::rpc::TxStatus;
-use crate::{kernel::Kernel, rpc::AgglayerImpl};
+use crate::rpc::{
+    auth::API_KEY_HEADER,
+    deadline::REQUEST_TIMEOUT_HEADER,
+    request_signature::{
+        signed_message, MAX_REQUEST_AGE, REQUEST_SIGNATURE_HEADER, REQUEST_TIMESTAMP_HEADER,
+    },
+    SendTxResult, TxStatus, DEADLINE_EXCEEDED_CODE, L1_UNAVAILABLE_CODE, PAUSED_CODE,
+    UNAUTHORIZED_CODE, UNSUPPORTED_CODE,
+};
+use crate::signed_tx::{SignedTx, HASH_LENGTH, PROOF_LENGTH};
+use crate::{
+    audit::{AuditEvent, AuditRecord},
+    certificate::Certificate,
+    kernel::{ErrorKind, Kernel},
+    leader::Lease,
+    rpc::{
+        grpc::proto::{self, interop_client::InteropClient},
+        AdminImpl, AgglayerImpl, GrpcImpl,
+    },
+    tx_updates::TxUpdate,
+};
 
 #[tokio::test]
 async fn healthcheck_method_can_be_called() {
@@ -30,7 +60,7 @@ async fn healthcheck_method_can_be_called() {
 
     let kernel = Kernel::new(provider, config.clone());
 
-    let _server_handle = AgglayerImpl::new(kernel, certificate_sender)
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
         .start(config.clone())
         .await
         .unwrap();
@@ -50,8 +80,432 @@ async fn healthcheck_method_can_be_called() {
     let bytes = http_body_util::BodyExt::collect(res.into_body())
         .await
         .unwrap();
-    let out = String::from_utf8(bytes.to_bytes().to_vec()).unwrap();
-    assert_eq!(out.as_str(), "{\"health\":true}");
+    let out: serde_json::Value = serde_json::from_slice(&bytes.to_bytes()).unwrap();
+
+    // Neither L1 nor a signer is available.
+    assert_eq!(out["health"], false);
+    assert_eq!(out["code"], "unhealthy");
+    assert_eq!(out["l1"]["healthy"], false);
+    assert_eq!(out["signer"]["healthy"], false);
+    assert_eq!(out["clock"]["healthy"], true);
+    assert_eq!(out["storage"]["healthy"], true);
+}
+
+#[tokio::test]
+async fn readiness_and_liveness_probes_are_served() {
+    use ethers::middleware::MiddlewareBuilder as _;
+    use ethers::providers::MockResponse;
+    use hyper::Request;
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    // The rollup manager contract is not deployed at first, then found.
+    let (provider, mock) = providers::Provider::mocked();
+    mock.push_response(MockResponse::Value(serde_json::json!("0x60")));
+    mock.push_response(MockResponse::Value(serde_json::json!("0x")));
+    let signer: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        .parse()
+        .unwrap();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider.with_signer(signer), config.clone());
+
+    let agglayer = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await);
+    let kernel = agglayer.kernel().clone();
+    let _server_handle = agglayer.start(config.clone()).await.unwrap();
+
+    let http_client = Client::builder(TokioExecutor::new()).build_http();
+    let get = |path: &str| {
+        Request::builder()
+            .method("GET")
+            .uri(format!("http://{}{path}", config.rpc_addr()))
+            .body(Empty::<hyper::body::Bytes>::new())
+            .expect("request builder")
+    };
+
+    let res = http_client.request(get("/live")).await.unwrap();
+    assert!(res.status().is_success());
+
+    let res = http_client.request(get("/ready")).await.unwrap();
+    assert!(res.status().is_server_error());
+
+    let res = http_client.request(get("/ready")).await.unwrap();
+    assert!(res.status().is_success());
+
+    // The contracts are resolved once.
+    let res = http_client.request(get("/ready")).await.unwrap();
+    assert!(res.status().is_success());
+
+    // Not ready while the clock doesn't follow the epoch duration set on L1.
+    kernel.report_stale_epoch_duration(Some(Duration::from_secs(60)));
+    let res = http_client.request(get("/ready")).await.unwrap();
+    assert!(res.status().is_server_error());
+
+    kernel.report_stale_epoch_duration(None);
+    let res = http_client.request(get("/ready")).await.unwrap();
+    assert!(res.status().is_success());
+}
+
+#[tokio::test]
+async fn tls_is_terminated_by_the_server() {
+    use rustls::{
+        pki_types::{pem::PemObject as _, CertificateDer, ServerName},
+        ClientConfig, RootCertStore,
+    };
+    use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+    use tokio_rustls::TlsConnector;
+
+    let testdata = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/rpc/testdata");
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config.rpc.tls = Some(agglayer_config::TlsConfig {
+        cert_path: testdata.join("cert.pem"),
+        key_path: testdata.join("key.pem"),
+        reload_interval: Duration::from_secs(60),
+    });
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    // Plain HTTP is refused.
+    let http_client = Client::builder(TokioExecutor::new()).build_http();
+    let req = hyper::Request::builder()
+        .method("GET")
+        .uri(format!("http://{}/live", config.rpc_addr()))
+        .body(Empty::<hyper::body::Bytes>::new())
+        .expect("request builder");
+    assert!(http_client.request(req).await.is_err());
+
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from_pem_file(testdata.join("cert.pem")).unwrap())
+        .unwrap();
+    let tls_config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+    let socket = tokio::net::TcpStream::connect(config.rpc_addr())
+        .await
+        .unwrap();
+    let mut stream = TlsConnector::from(Arc::new(tls_config))
+        .connect(ServerName::try_from("localhost").unwrap(), socket)
+        .await
+        .unwrap();
+
+    stream
+        .write_all(b"GET /live HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains(r#""live":true"#), "{response}");
+}
+
+#[tokio::test]
+async fn requests_are_rate_limited_per_ip() {
+    use agglayer_config::RateLimit;
+    use hyper::{header::RETRY_AFTER, Request, StatusCode};
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config.rpc.ip_rate_limit = Some(RateLimit {
+        rate: 0.5,
+        burst: 2,
+    });
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let http_client = Client::builder(TokioExecutor::new()).build_http();
+    let live = || {
+        http_client.request(
+            Request::builder()
+                .method("GET")
+                .uri(format!("http://{}/live", config.rpc_addr()))
+                .body(Empty::<hyper::body::Bytes>::new())
+                .expect("request builder"),
+        )
+    };
+
+    assert_eq!(live().await.unwrap().status(), StatusCode::OK);
+    assert_eq!(live().await.unwrap().status(), StatusCode::OK);
+
+    let res = live().await.unwrap();
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(res.headers()[RETRY_AFTER], "2");
+}
+
+#[tokio::test]
+async fn reload_applies_the_rate_limits_of_the_configuration_file() {
+    use agglayer_config::DEFAULT_CONFIG;
+
+    let path = std::env::temp_dir().join(format!("agglayer-reload-{:x}.toml", H256::random()));
+    std::fs::write(&path, DEFAULT_CONFIG).unwrap();
+    let config = Arc::new(Config::load(&path).unwrap());
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+    let agglayer = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await);
+
+    for _ in 0..3 {
+        assert!(agglayer.rate_limiter.check(1).is_ok());
+    }
+
+    let limited = DEFAULT_CONFIG.replace(
+        "# [RPC.RateLimit]\n# Default = { Rate = 1.0, Burst = 10 }",
+        "[RPC.RateLimit]\nDefault = { Rate = 0.001, Burst = 1 }",
+    );
+    assert_ne!(limited, DEFAULT_CONFIG);
+    std::fs::write(&path, limited).unwrap();
+
+    let reloaded = agglayer.reload(&path).unwrap();
+    assert!(reloaded.rpc.rate_limit.is_some());
+    assert!(agglayer.rate_limiter.check(1).is_ok());
+    assert!(agglayer.rate_limiter.check(1).is_err());
+
+    // Would lift the rate limit and move the endpoint of the rollup, if it
+    // parsed.
+    let broken = DEFAULT_CONFIG.replace("http://zkevm-node:8123", "http://zkevm-node-2:8123");
+    std::fs::write(&path, format!("{broken}\n[RPC.RateLimit\n")).unwrap();
+
+    assert!(agglayer.reload(&path).is_err());
+    assert!(agglayer.rate_limiter.check(1).is_err());
+    assert_eq!(
+        agglayer.kernel.rollups().get(1).unwrap().full_node_rpc,
+        config.full_node_rpcs[&1]
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn request_ids_are_returned_with_the_errors() {
+    use hyper::Request;
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let http_client = Client::builder(TokioExecutor::new()).build_http();
+    let call = |request_id: Option<&str>| {
+        let mut req = Request::builder()
+            .method("POST")
+            .uri(format!("http://{}/", config.rpc_addr()))
+            .header(hyper::header::CONTENT_TYPE, "application/json");
+        if let Some(request_id) = request_id {
+            req = req.header("x-request-id", request_id);
+        }
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "interop_getLatestSettledBatch",
+            "params": [42],
+        });
+
+        http_client.request(
+            req.body(Full::new(hyper::body::Bytes::from(body.to_string())))
+                .expect("request builder"),
+        )
+    };
+    let error_data = |res: hyper::Response<hyper::body::Incoming>| async move {
+        let bytes = http_body_util::BodyExt::collect(res.into_body())
+            .await
+            .unwrap();
+        let out: serde_json::Value = serde_json::from_slice(&bytes.to_bytes()).unwrap();
+
+        out["error"]["data"].clone()
+    };
+
+    // The ID given by the client is echoed.
+    let res = call(Some("support-123")).await.unwrap();
+    assert_eq!(res.headers()["x-request-id"], "support-123");
+    assert_eq!(error_data(res).await["requestId"], "support-123");
+
+    // Otherwise, an ID is generated.
+    let res = call(None).await.unwrap();
+    let request_id = res.headers()["x-request-id"].to_str().unwrap().to_string();
+    assert_eq!(request_id.len(), 32);
+    assert_eq!(error_data(res).await["requestId"], request_id);
+}
+
+#[tokio::test]
+async fn healthcheck_reports_the_unreachable_zkevm_nodes() {
+    use ethers::middleware::MiddlewareBuilder as _;
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config.full_node_rpcs.clear();
+    config
+        .full_node_rpcs
+        .insert(1, "http://127.0.0.1:1".parse().unwrap());
+    let config = Arc::new(config);
+
+    let (provider, mock) = providers::Provider::mocked();
+    mock.push(ethers::types::U64::from(42)).unwrap();
+    let signer: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        .parse()
+        .unwrap();
+    let address = signer.address();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider.with_signer(signer), config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let res: serde_json::Value = client
+        .request("system_health", rpc_params![])
+        .await
+        .unwrap();
+
+    assert_eq!(res["health"], true);
+    assert_eq!(res["code"], "degraded");
+    assert_eq!(
+        res["l1"],
+        serde_json::json!({ "healthy": true, "detail": "block 42" })
+    );
+    assert_eq!(
+        res["signer"],
+        serde_json::json!({ "healthy": true, "detail": format!("{address:?}") })
+    );
+    assert_eq!(
+        res["storage"],
+        serde_json::json!({ "healthy": true, "detail": "not configured" })
+    );
+    assert_eq!(res["zkevmNodes"][0]["rollupId"], 1);
+    assert_eq!(res["zkevmNodes"][0]["healthy"], false);
+}
+
+#[tokio::test]
+async fn sampled_access_logs_do_not_alter_responses() {
+    use hyper::Request;
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config.rpc.access_log = Some(AccessLogConfig { sample_rate: 0.5 });
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let http_client = Client::builder(TokioExecutor::new()).build_http();
+    let uri = format!("http://{}/health", config.rpc_addr());
+
+    // Both the sampled and the skipped requests are served.
+    for _ in 0..4 {
+        let req = Request::builder()
+            .method("GET")
+            .uri(&uri)
+            .body(Empty::<hyper::body::Bytes>::new())
+            .expect("request builder");
+        let res = http_client.request(req).await.unwrap();
+
+        assert!(res.status().is_success());
+    }
+}
+
+#[tokio::test]
+async fn healthcheck_is_served_on_every_listen_address() {
+    use hyper::Request;
+
+    let mut config = Config::default();
+    config.rpc.listen = vec![next_available_addr(), next_available_addr()];
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let http_client = Client::builder(TokioExecutor::new()).build_http();
+
+    for addr in config.rpc_addrs() {
+        let req = Request::builder()
+            .method("GET")
+            .uri(format!("http://{addr}/health"))
+            .body(Empty::<hyper::body::Bytes>::new())
+            .expect("request builder");
+        let res = http_client.request(req).await.unwrap();
+
+        assert!(res.status().is_success());
+    }
 }
 
 #[tokio::test]
@@ -89,7 +543,7 @@ async fn check_tx_status() {
     let kernel = Kernel::new(client, config.clone());
 
     let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
-    let _server_handle = AgglayerImpl::new(kernel, certificate_sender)
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
         .start(config.clone())
         .await
         .unwrap();
@@ -116,7 +570,7 @@ async fn check_tx_status() {
 }
 
 #[tokio::test]
-async fn send_certificate_method_can_be_called() {
+async fn send_certificate_is_verified_then_rejected_as_unsupported() {
     let _ = tracing_subscriber::FmtSubscriber::builder()
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .try_init();
@@ -127,14 +581,22 @@ async fn send_certificate_method_can_be_called() {
         config.rpc.host = ip;
     }
     config.rpc.port = addr.port();
+    config
+        .full_node_rpcs
+        .insert(1, "http://zkevm-node:8123".parse().unwrap());
+    config.consensus_types.insert(1, ConsensusType::Pessimistic);
     let config = Arc::new(config);
 
     let (provider, _mock) = providers::Provider::mocked();
     let (certificate_sender, mut certificate_receiver) = tokio::sync::mpsc::channel(1);
 
+    let signer: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        .parse()
+        .unwrap();
     let kernel = Kernel::new(provider, config.clone());
+    trust_sequencer(&kernel, 1, &signer);
 
-    let _server_handle = AgglayerImpl::new(kernel, certificate_sender)
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
         .start(config.clone())
         .await
         .unwrap();
@@ -142,64 +604,1699 @@ async fn send_certificate_method_can_be_called() {
     let url = format!("http://{}/", config.rpc_addr());
     let client = HttpClientBuilder::default().build(url).unwrap();
 
-    let _: () = client
-        .request("interop_sendCertificate", rpc_params![()])
-        .await
-        .unwrap();
+    // The certificates can't be settled, they're rejected rather than proven.
+    let certificate = signed_certificate(1, 0, &signer);
+    let res: Result<H256, _> = client
+        .request("interop_sendCertificate", rpc_params![&certificate])
+        .await;
+    let Err(ClientError::Call(error)) = res else {
+        panic!("Unexpected response: {res:?}");
+    };
+    assert_eq!(error.code(), UNSUPPORTED_CODE);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(error.data().unwrap().get()).unwrap()
+            ["retriable"],
+        false
+    );
 
-    assert!(certificate_receiver.try_recv().is_ok());
+    // The certificates are verified first, the ones signed by another signer than
+    // the trusted sequencer are rejected as such.
+    let other: LocalWallet = "0x8da4ef21b864d2cc526dbdb2a120bd2874c36c9d0a1fb7f8c63d7f7a8b41de8f"
+        .parse()
+        .unwrap();
+    let res: Result<H256, _> = client
+        .request(
+            "interop_sendCertificate",
+            rpc_params![signed_certificate(1, 1, &other)],
+        )
+        .await;
+    let Err(ClientError::Call(error)) = res else {
+        panic!("Unexpected response: {res:?}");
+    };
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(error.data().unwrap().get()).unwrap()["kind"],
+        "invalidSignature"
+    );
+    assert!(certificate_receiver.try_recv().is_err());
 }
 
 #[tokio::test]
-async fn send_certificate_method_can_be_called_and_fail() {
-    let _ = tracing_subscriber::FmtSubscriber::builder()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .try_init();
-
+async fn send_tx_rejected_outside_of_api_key_scope() {
     let mut config = Config::default();
     let addr = next_available_addr();
     if let IpAddr::V4(ip) = addr.ip() {
         config.rpc.host = ip;
     }
     config.rpc.port = addr.port();
-
+    config.rpc.api_keys = vec![ApiKeyConfig {
+        key: "team-a".to_string(),
+        rollup_ids: vec![2],
+    }];
     let config = Arc::new(config);
 
     let (provider, _mock) = providers::Provider::mocked();
-    let (certificate_sender, certificate_receiver) = tokio::sync::mpsc::channel(1);
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
 
     let kernel = Kernel::new(provider, config.clone());
 
-    let _server_handle = AgglayerImpl::new(kernel, certificate_sender)
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
         .start(config.clone())
         .await
         .unwrap();
 
     let url = format!("http://{}/", config.rpc_addr());
-    let client = HttpClientBuilder::default().build(url).unwrap();
 
-    drop(certificate_receiver);
-    let res: Result<(), _> = client
-        .request("interop_sendCertificate", rpc_params![()])
+    // A known API key that isn't scoped to the rollup.
+    let client = client_with_api_key(&url, Some("team-a"));
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
         .await;
 
-    assert!(res.is_err());
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == UNAUTHORIZED_CODE));
+
+    // No API key at all.
+    let client = client_with_api_key(&url, None);
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(2)])
+        .await;
+
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == UNAUTHORIZED_CODE));
+
+    // An unknown API key is rejected before reaching the RPC methods.
+    let client = client_with_api_key(&url, Some("unknown"));
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(2)])
+        .await;
+
+    assert!(matches!(res, Err(ClientError::Transport(_))));
 }
 
-fn next_available_addr() -> std::net::SocketAddr {
-    use std::net::{TcpListener, TcpStream};
+#[tokio::test]
+async fn send_tx_rejected_outside_of_token_scope() {
+    use jsonwebtoken::{EncodingKey, Header};
 
-    let host = "127.0.0.1";
-    // Request a random available port from the OS
-    let listener = TcpListener::bind((host, 0)).expect("Can't bind to an available port");
-    let addr = listener.local_addr().expect("Can't find an available port");
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config.rpc.jwt = Some(JwtConfig {
+        secret: "shared-secret".to_string(),
+        issuer: None,
+        audience: Some("agglayer".to_string()),
+    });
+    let config = Arc::new(config);
 
-    // Create and accept a connection (which we'll promptly drop) in order to force
-    // the port into the TIME_WAIT state, ensuring that the port will be
-    // reserved from some limited amount of time (roughly 60s on some Linux
-    // systems)
-    let _sender = TcpStream::connect(addr).expect("Can't connect to an available port");
-    let _incoming = listener.accept().expect("Can't accept an available port");
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
 
-    addr
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let exp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + 3600;
+    let token = |secret: &str, audience: &str| {
+        jsonwebtoken::encode(
+            &Header::default(),
+            &serde_json::json!({ "rollupIds": [2], "aud": audience, "exp": exp }),
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    };
+    let client_with_token = |token: String| {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            hyper::header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+
+        HttpClientBuilder::default()
+            .set_headers(headers)
+            .build(&url)
+            .unwrap()
+    };
+
+    // A valid token that isn't scoped to the rollup.
+    let client = client_with_token(token("shared-secret", "agglayer"));
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == UNAUTHORIZED_CODE));
+
+    // A valid token scoped to the rollup gets through, up to the registration
+    // of the rollup.
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(2)])
+        .await;
+
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == INVALID_PARAMS_CODE));
+
+    // No token at all.
+    let client = client_with_api_key(&url, None);
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(2)])
+        .await;
+
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == UNAUTHORIZED_CODE));
+
+    // Tokens signed with another secret, or for another audience, are rejected
+    // before reaching the RPC methods.
+    for token in [
+        token("other-secret", "agglayer"),
+        token("shared-secret", "other"),
+    ] {
+        let res: Result<H256, _> = client_with_token(token)
+            .request("interop_sendTx", rpc_params![signed_tx_json(2)])
+            .await;
+
+        assert!(matches!(res, Err(ClientError::Transport(_))));
+    }
+}
+
+#[tokio::test]
+async fn followers_forward_to_the_leader_with_their_credentials() {
+    use ethers::middleware::MiddlewareBuilder as _;
+
+    let signer: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        .parse()
+        .unwrap();
+    let addr = next_available_addr();
+    let config = |leader_api_key: Option<&str>, signs_requests: bool| {
+        let mut config = Config::default();
+        if let IpAddr::V4(ip) = addr.ip() {
+            config.rpc.host = ip;
+        }
+        config.rpc.port = addr.port();
+        config.rpc.api_keys = vec![ApiKeyConfig {
+            key: "followers".to_string(),
+            rollup_ids: vec![2],
+        }];
+        if signs_requests {
+            config.rpc.request_signers = vec![signer.address()];
+        }
+        config.high_availability.leader_api_key = leader_api_key.map(str::to_string);
+
+        Arc::new(config)
+    };
+
+    let leader_config = config(None, true);
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+    let _server_handle = AgglayerImpl::new(
+        Kernel::new(provider, leader_config.clone()),
+        certificate_sender.clone(),
+        clock_ref().await,
+    )
+    .start(leader_config.clone())
+    .await
+    .unwrap();
+
+    let leader = Lease {
+        holder: "leader".to_string(),
+        url: Some(
+            format!("http://{}/", leader_config.rpc_addr())
+                .parse()
+                .unwrap(),
+        ),
+        expires_at: u64::MAX,
+    };
+    let tx = serde_json::from_value::<SignedTx>(signed_tx_json(2)).unwrap();
+
+    for (leader_api_key, signs_requests, code) in [
+        // The followers get through, up to the registration of the rollup.
+        (Some("followers"), true, INVALID_PARAMS_CODE),
+        // Without API key, the transaction is out of scope.
+        (None, true, UNAUTHORIZED_CODE),
+        // Without signature, the leader rejects the request.
+        (Some("followers"), false, CALL_EXECUTION_FAILED_CODE),
+    ] {
+        let (provider, _mock) = providers::Provider::mocked();
+        let follower = AgglayerImpl::new(
+            Kernel::new(
+                provider.with_signer(signer.clone()),
+                config(leader_api_key, signs_requests),
+            ),
+            certificate_sender.clone(),
+            clock_ref().await,
+        );
+
+        let error = follower
+            .forward_to_leader(&tx, Some(leader.clone()))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), code, "{error:?}");
+    }
+}
+
+#[tokio::test]
+async fn send_tx_rejects_oversized_proofs() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let mut tx = signed_tx_json(1);
+    tx["tx"]["ZKP"]["proof"] = format!("0x{}", "00".repeat(HASH_LENGTH * PROOF_LENGTH + 1)).into();
+
+    let res: Result<H256, _> = client.request("interop_sendTx", rpc_params![tx]).await;
+
+    let expected = format!(
+        "proof is too large: the limit is {} bytes, got {} bytes",
+        HASH_LENGTH * PROOF_LENGTH,
+        HASH_LENGTH * PROOF_LENGTH + 1
+    );
+    let Err(ClientError::Call(error)) = res else {
+        panic!("Unexpected response: {res:?}");
+    };
+
+    assert_eq!(error.code(), INVALID_PARAMS_CODE);
+    assert_eq!(
+        error_data_without_request_id(&error),
+        serde_json::json!({
+            "kind": "invalidProof",
+            "retriable": false,
+            "message": expected,
+        })
+    );
+}
+
+#[tokio::test]
+async fn send_tx_rejects_proofs_not_matching_the_rollup_format() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config.proof_formats.insert(
+        1,
+        ProofFormat {
+            proof_system: ProofSystem::Plonk,
+            proof_length: PROOF_LENGTH + 3,
+            verifier_selector: None,
+        },
+    );
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    // A fflonk proof is too short for the format of the rollup.
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+
+    let Err(ClientError::Call(error)) = res else {
+        panic!("Unexpected response: {res:?}");
+    };
+
+    assert_eq!(error.code(), INVALID_PARAMS_CODE);
+    assert_eq!(
+        error_data_without_request_id(&error),
+        serde_json::json!({
+            "kind": "invalidProof",
+            "retriable": false,
+            "message": format!(
+                "invalid proof length: expected {}, got {}",
+                HASH_LENGTH * (PROOF_LENGTH + 3),
+                HASH_LENGTH * PROOF_LENGTH
+            ),
+        })
+    );
+}
+
+#[tokio::test]
+async fn send_tx_answers_settled_transactions_with_their_settlement() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config.rpc.api_keys = vec![
+        ApiKeyConfig {
+            key: "team-a".to_string(),
+            rollup_ids: vec![1],
+        },
+        ApiKeyConfig {
+            key: "team-b".to_string(),
+            rollup_ids: vec![2],
+        },
+    ];
+    config
+        .full_node_rpcs
+        .insert(1, "http://localhost:8123".parse().unwrap());
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let path = std::env::temp_dir().join(format!("agglayer-settled-{:x}", H256::random()));
+    let index = SettledProofIndex::open(&path).unwrap();
+    let tx = signed_tx_json(1);
+    let settlement_tx_hash = H256::random();
+    index
+        .insert(
+            serde_json::from_value::<SignedTx>(tx.clone())
+                .unwrap()
+                .hash(),
+            1,
+            &SettledBatch {
+                batch: 1,
+                state_root: H256::random(),
+                local_exit_root: H256::random(),
+                settlement_tx_hash,
+            },
+        )
+        .unwrap();
+
+    let kernel = Kernel::new(provider, config.clone()).with_settled_proofs(index);
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+
+    // The settlement isn't disclosed to the callers outside of the rollup scope.
+    let client = client_with_api_key(&url, Some("team-b"));
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![tx.clone()])
+        .await;
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == UNAUTHORIZED_CODE));
+
+    // The settled transaction is neither verified nor settled again.
+    let client = client_with_api_key(&url, Some("team-a"));
+    let res: H256 = client
+        .request("interop_sendTx", rpc_params![tx])
+        .await
+        .unwrap();
+    assert_eq!(res, settlement_tx_hash);
+
+    _ = std::fs::remove_dir_all(path);
+}
+
+#[tokio::test]
+async fn send_txs_reports_the_result_of_each_transaction() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config
+        .full_node_rpcs
+        .insert(1, "http://localhost:8123".parse().unwrap());
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let path = std::env::temp_dir().join(format!("agglayer-settled-{:x}", H256::random()));
+    let index = SettledProofIndex::open(&path).unwrap();
+    let settled = serde_json::from_value::<SignedTx>(signed_tx_json(1)).unwrap();
+    let settlement_tx_hash = H256::random();
+    index
+        .insert(
+            settled.hash(),
+            1,
+            &SettledBatch {
+                batch: 1,
+                state_root: H256::random(),
+                local_exit_root: H256::random(),
+                settlement_tx_hash,
+            },
+        )
+        .unwrap();
+
+    let kernel = Kernel::new(provider, config.clone()).with_settled_proofs(index);
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    // The first transaction is settled already, the rollup of the second one
+    // isn't registered.
+    let mut unregistered = signed_tx_json(2);
+    unregistered["tx"]["newVerifiedBatch"] = "0x2".into();
+    let res: Vec<SendTxResult> = client
+        .request(
+            "interop_sendTxs",
+            rpc_params![vec![signed_tx_json(1), unregistered.clone()]],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(res.len(), 2);
+    assert_eq!(res[0].tx_hash, settled.hash());
+    assert_eq!(res[0].hash, Some(settlement_tx_hash));
+    assert!(res[0].error.is_none());
+    assert_eq!(
+        res[1].tx_hash,
+        serde_json::from_value::<SignedTx>(unregistered)
+            .unwrap()
+            .hash()
+    );
+    assert_eq!(res[1].hash, None);
+    let error = res[1].error.as_ref().unwrap();
+    assert_eq!(error.code, INVALID_PARAMS_CODE);
+    assert_eq!(error.data.as_ref().unwrap().kind, ErrorKind::InvalidRollup);
+
+    _ = std::fs::remove_dir_all(path);
+}
+
+#[tokio::test]
+async fn get_tx_status_tracks_the_settlements_by_transaction_hash() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+    let agglayer = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await);
+    let submissions = agglayer.submissions.clone();
+
+    let _server_handle = agglayer.start(config.clone()).await.unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let hash = H256::random();
+    let submission = submissions.start(1, hash).unwrap();
+
+    let status: TxStatus = client
+        .request("interop_getTxStatus", rpc_params![hash])
+        .await
+        .unwrap();
+    assert_eq!(status, "pending");
+
+    submission.failed("execution reverted");
+    drop(submission);
+
+    let res: Result<TxStatus, _> = client
+        .request("interop_getTxStatus", rpc_params![hash])
+        .await;
+    let Err(ClientError::Call(error)) = res else {
+        panic!("Unexpected response: {res:?}");
+    };
+    assert_eq!(error.code(), CALL_EXECUTION_FAILED_CODE);
+    assert!(error.data().unwrap().get().contains("execution reverted"));
+}
+
+#[tokio::test]
+async fn send_tx_rejects_inconsistent_transactions() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let rejection = |tx: serde_json::Value| {
+        let client = &client;
+        async move {
+            let res: Result<H256, _> = client.request("interop_sendTx", rpc_params![tx]).await;
+            let Err(ClientError::Call(error)) = res else {
+                panic!("Unexpected response: {res:?}");
+            };
+            assert_eq!(error.code(), INVALID_PARAMS_CODE);
+
+            error
+        }
+    };
+
+    let mut tx = signed_tx_json(1);
+    tx["tx"]["ZKP"]["extra"] = "0x00".into();
+    let error = rejection(tx).await;
+    assert!(error
+        .data()
+        .unwrap()
+        .get()
+        .contains("unknown field `extra`"));
+
+    let mut tx = signed_tx_json(1);
+    tx["tx"]["newVerifiedBatch"] = "0x0".into();
+    let error = rejection(tx).await;
+    assert_eq!(
+        error_data_without_request_id(&error),
+        serde_json::json!({
+            "kind": "invalidProof",
+            "retriable": false,
+            "message": "invalid batch range: the new verified batch 0 doesn't follow the last \
+                        verified batch 0",
+        })
+    );
+
+    let mut tx = signed_tx_json(1);
+    tx["tx"]["ZKP"]["newLocalExitRoot"] = serde_json::json!(H256::zero());
+    let error = rejection(tx).await;
+    assert!(error
+        .data()
+        .unwrap()
+        .get()
+        .contains("the new local exit root is zero"));
+}
+
+#[tokio::test]
+async fn send_tx_rejects_rollups_with_a_pessimistic_consensus() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config
+        .full_node_rpcs
+        .insert(1, "http://zkevm-node:8123".parse().unwrap());
+    config.consensus_types.insert(1, ConsensusType::Pessimistic);
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+
+    let Err(ClientError::Call(error)) = res else {
+        panic!("Unexpected response: {res:?}");
+    };
+
+    assert_eq!(error.code(), INVALID_PARAMS_CODE);
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(error.data().unwrap().get()).unwrap()["kind"],
+        "invalidRollup"
+    );
+}
+
+#[tokio::test]
+async fn get_spend_report_of_registered_rollups() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config
+        .full_node_rpcs
+        .insert(1, "http://localhost:8123".parse().unwrap());
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+    kernel
+        .spending()
+        .record(
+            1,
+            2,
+            &ethers::types::TransactionReceipt {
+                gas_used: Some(100.into()),
+                effective_gas_price: Some(3.into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let res: serde_json::Value = client
+        .request(
+            "interop_getSpendReport",
+            rpc_params![1, serde_json::json!({ "from": 0, "to": 10 })],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        res,
+        serde_json::json!({
+            "rollupId": 1,
+            "fromEpoch": 0,
+            "toEpoch": 10,
+            "epochs": [{ "epoch": 2, "settlements": 1, "gasUsed": "0x64", "spentWei": "0x12c" }],
+            "gasUsed": "0x64",
+            "spentWei": "0x12c",
+        })
+    );
+
+    let res: Result<serde_json::Value, _> = client
+        .request(
+            "interop_getSpendReport",
+            rpc_params![2, serde_json::json!({ "from": 0, "to": 10 })],
+        )
+        .await;
+
+    assert!(matches!(res, Err(ClientError::Call(e)) if e.code() == INVALID_PARAMS_CODE));
+}
+
+#[tokio::test]
+async fn get_epoch_configuration_describes_the_clock() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let clock_ref = clock_ref().await;
+    let ClockConfiguration::Time { genesis, .. } = clock_ref.configuration() else {
+        panic!("The test Clock is time based");
+    };
+
+    let kernel = Kernel::new(provider, config.clone());
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let res: serde_json::Value = client
+        .request("interop_getEpochConfiguration", rpc_params![])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        res,
+        serde_json::json!({
+            "clockType": "time",
+            "genesisTimestamp": genesis.timestamp(),
+            "genesisBlock": null,
+            "epochDuration": 60,
+            "currentEpoch": 0,
+        })
+    );
+}
+
+#[tokio::test]
+async fn get_network_status_reports_registered_rollups() {
+    use agglayer_contracts::polygon_rollup_manager::RollupIDToRollupDataReturn;
+    use ethers::abi::AbiEncode as _;
+    use ethers::providers::MockResponse;
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config
+        .full_node_rpcs
+        .insert(1, "http://localhost:8123".parse().unwrap());
+    let config = Arc::new(config);
+
+    let (provider, mock) = providers::Provider::mocked();
+    mock.push_response(MockResponse::Value(serde_json::Value::String(
+        RollupIDToRollupDataReturn {
+            rollup_contract: Default::default(),
+            chain_id: 1,
+            verifier: Default::default(),
+            fork_id: 0,
+            last_local_exit_root: [0; 32],
+            last_batch_sequenced: 42,
+            last_verified_batch: 41,
+            last_pending_state: 0,
+            last_pending_state_consolidated: 0,
+            last_verified_batch_before_upgrade: 0,
+            rollup_type_id: 1,
+            rollup_compatibility_id: 0,
+        }
+        .encode_hex(),
+    )));
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let res: serde_json::Value = client
+        .request("interop_getNetworkStatus", rpc_params![])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        res,
+        serde_json::json!([{
+            "rollupId": 1,
+            "onChainLastVerifiedBatch": 41,
+            "lastAcceptedBatch": null,
+            "pendingSubmissions": 0,
+            "lastSettlementTime": null,
+            "circuitBreaker": "closed",
+            "consensusType": "Fep",
+            "shadow": false,
+            "shadowedSubmissions": 0,
+        }])
+    );
+}
+
+#[tokio::test]
+async fn get_latest_settled_batch_reports_the_settled_and_verified_batches() {
+    use agglayer_contracts::polygon_rollup_manager::RollupIDToRollupDataReturn;
+    use ethers::abi::AbiEncode as _;
+    use ethers::providers::MockResponse;
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config
+        .full_node_rpcs
+        .insert(1, "http://localhost:8123".parse().unwrap());
+    let config = Arc::new(config);
+
+    let (provider, mock) = providers::Provider::mocked();
+    mock.push_response(MockResponse::Value(serde_json::Value::String(
+        RollupIDToRollupDataReturn {
+            rollup_contract: Default::default(),
+            chain_id: 1,
+            verifier: Default::default(),
+            fork_id: 0,
+            last_local_exit_root: [0; 32],
+            last_batch_sequenced: 42,
+            last_verified_batch: 41,
+            last_pending_state: 0,
+            last_pending_state_consolidated: 0,
+            last_verified_batch_before_upgrade: 0,
+            rollup_type_id: 1,
+            rollup_compatibility_id: 0,
+        }
+        .encode_hex(),
+    )));
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let path = std::env::temp_dir().join(format!("agglayer-settled-{:x}", H256::random()));
+    let index = SettledProofIndex::open(&path).unwrap();
+    let settled = SettledBatch {
+        batch: 41,
+        state_root: H256::repeat_byte(1),
+        local_exit_root: H256::repeat_byte(2),
+        settlement_tx_hash: H256::repeat_byte(3),
+    };
+    index.insert(H256::random(), 1, &settled).unwrap();
+
+    let kernel = Kernel::new(provider, config.clone()).with_settled_proofs(index);
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let res: serde_json::Value = client
+        .request("interop_getLatestSettledBatch", rpc_params![1])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        res,
+        serde_json::json!({
+            "rollupId": 1,
+            "settled": {
+                "batch": 41,
+                "stateRoot": H256::repeat_byte(1),
+                "localExitRoot": H256::repeat_byte(2),
+                "settlementTxHash": H256::repeat_byte(3),
+            },
+            "onChainLastVerifiedBatch": 41,
+        })
+    );
+
+    let res: Result<serde_json::Value, _> = client
+        .request("interop_getLatestSettledBatch", rpc_params![2])
+        .await;
+    assert!(matches!(res, Err(ClientError::Call(e)) if e.code() == INVALID_PARAMS_CODE));
+
+    _ = std::fs::remove_dir_all(path);
+}
+
+#[tokio::test]
+async fn admin_add_rollup_registers_the_rollup() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+    let admin_addr = next_available_addr();
+    let _admin_handle = AdminImpl::new(
+        kernel.rollups().clone(),
+        kernel.admission().clone(),
+        kernel.access_list().clone(),
+        kernel.auditor().clone(),
+    )
+    .start(admin_addr, &config.rpc)
+    .await
+    .unwrap();
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let admin = HttpClientBuilder::default()
+        .build(format!("http://{admin_addr}/"))
+        .unwrap();
+    let rollup = serde_json::json!({
+        "rollupId": 7,
+        "fullNodeRpc": "http://zkevm-node-7:8123/",
+        "shadow": true,
+    });
+
+    let _: () = admin
+        .request("admin_addRollup", rpc_params![rollup.clone()])
+        .await
+        .unwrap();
+
+    let res: Result<(), _> = admin.request("admin_addRollup", rpc_params![rollup]).await;
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == INVALID_PARAMS_CODE));
+
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{}/", config.rpc_addr()))
+        .unwrap();
+    let res: serde_json::Value = client
+        .request("interop_getNetworkStatus", rpc_params![])
+        .await
+        .unwrap();
+
+    assert_eq!(res[0]["rollupId"], 7);
+    assert_eq!(res[0]["shadow"], true);
+}
+
+#[tokio::test]
+async fn subscribe_epochs_streams_the_epoch_changes() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+    let clock = ManualClock::new(NonZeroU64::new(1).unwrap());
+    let clock_handle = clock.handle();
+    let clock_ref = Arc::new(clock.spawn(CancellationToken::new()).await.unwrap());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let client = WsClientBuilder::default()
+        .build(format!("ws://{}/", config.rpc_addr()))
+        .await
+        .unwrap();
+    let mut epochs = client
+        .subscribe::<serde_json::Value, _>(
+            "interop_subscribeEpochs",
+            rpc_params![],
+            "interop_unsubscribeEpochs",
+        )
+        .await
+        .unwrap();
+    clock_handle.end_epoch().await.unwrap();
+
+    let change = tokio::time::timeout(Duration::from_secs(5), epochs.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(change["kind"], "ended");
+    assert_eq!(change["epoch"], 0);
+    assert_eq!(change["startBlock"], 0);
+    assert_eq!(change["endBlock"], 1);
+    assert_eq!(change["revertedFrom"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn subscribe_tx_updates_streams_the_updates_of_the_transaction() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+    let tx_updates = kernel.tx_updates().clone();
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let client = WsClientBuilder::default()
+        .build(format!("ws://{}/", config.rpc_addr()))
+        .await
+        .unwrap();
+    let tx_hash = H256::repeat_byte(1);
+    let settlement_tx_hash = H256::repeat_byte(2);
+    let mut updates = client
+        .subscribe::<serde_json::Value, _>(
+            "interop_subscribeTxUpdates",
+            rpc_params![tx_hash],
+            "interop_unsubscribeTxUpdates",
+        )
+        .await
+        .unwrap();
+
+    tx_updates.publish(TxUpdate::verified(H256::repeat_byte(3)));
+    tx_updates.publish(TxUpdate::submitted(tx_hash, settlement_tx_hash));
+    tx_updates.publish(TxUpdate::failed(tx_hash, "dropped"));
+
+    let update = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(update["txHash"], serde_json::to_value(tx_hash).unwrap());
+    assert_eq!(update["status"], "submitted");
+    assert_eq!(
+        update["settlementTxHash"],
+        serde_json::to_value(settlement_tx_hash).unwrap()
+    );
+
+    let update = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(update["status"], "failed");
+    assert_eq!(update["error"], "dropped");
+}
+
+#[tokio::test]
+async fn grpc_serves_the_interop_operations() {
+    let mut config = Config::default();
+    config.rpc.api_keys = vec![ApiKeyConfig {
+        key: "team-a".to_string(),
+        rollup_ids: vec![1],
+    }];
+    config
+        .full_node_rpcs
+        .insert(1, "http://localhost:8123".parse().unwrap());
+
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+    let kernel = Kernel::new(provider, config.clone());
+
+    let addr = next_available_addr();
+    let cancellation_token = CancellationToken::new();
+    let agglayer = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await);
+    let _grpc_handle = GrpcImpl::new(agglayer, &config.rpc)
+        .unwrap()
+        .start(addr, cancellation_token.clone())
+        .await
+        .unwrap();
+
+    let mut client = InteropClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let mut request = tonic::Request::new(proto::GetNetworkStatusRequest {});
+    request
+        .metadata_mut()
+        .insert("x-api-key", "team-b".parse().unwrap());
+    let status = client.get_network_status(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+    let rollups = client
+        .get_network_status(proto::GetNetworkStatusRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .rollups;
+    assert_eq!(rollups.len(), 1);
+    assert_eq!(rollups[0].rollup_id, 1);
+    assert_eq!(rollups[0].consensus_type, "Fep");
+
+    // The proofs are checked the same way as over JSON-RPC.
+    let status = client
+        .send_tx(proto::SendTxRequest {
+            tx: Some(proto::SignedTx {
+                tx: Some(proto::ProofManifest {
+                    rollup_id: 1,
+                    last_verified_batch: 0,
+                    new_verified_batch: 1,
+                    zkp: Some(proto::Zkp {
+                        new_state_root: vec![1; 32],
+                        new_local_exit_root: vec![2; 32],
+                        proof: vec![0; 32],
+                    }),
+                }),
+                signature: vec![0; 65],
+            }),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    assert!(status.message().contains("invalid proof length"));
+
+    let status = client
+        .get_tx_status(proto::GetTxStatusRequest { hash: vec![0; 20] })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    cancellation_token.cancel();
+}
+
+#[tokio::test]
+async fn admin_pause_rejects_new_transactions() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+    let admin_addr = next_available_addr();
+    let _admin_handle = AdminImpl::new(
+        kernel.rollups().clone(),
+        kernel.admission().clone(),
+        kernel.access_list().clone(),
+        kernel.auditor().clone(),
+    )
+    .start(admin_addr, &config.rpc)
+    .await
+    .unwrap();
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let admin = HttpClientBuilder::default()
+        .build(format!("http://{admin_addr}/"))
+        .unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{}/", config.rpc_addr()))
+        .unwrap();
+
+    let _: () = admin.request("admin_pause", rpc_params![]).await.unwrap();
+
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == PAUSED_CODE));
+
+    // Nothing is in flight, the drain completes right away.
+    let _: () = admin.request("admin_drain", rpc_params![]).await.unwrap();
+    let _: () = admin.request("admin_resume", rpc_params![]).await.unwrap();
+
+    // The rollup isn't registered, the transaction is admitted then rejected.
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == INVALID_PARAMS_CODE));
+}
+
+#[tokio::test]
+async fn admin_denylist_rejects_the_transactions_of_the_rollup() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+    let admin_addr = next_available_addr();
+    let _admin_handle = AdminImpl::new(
+        kernel.rollups().clone(),
+        kernel.admission().clone(),
+        kernel.access_list().clone(),
+        kernel.auditor().clone(),
+    )
+    .start(admin_addr, &config.rpc)
+    .await
+    .unwrap();
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let admin = HttpClientBuilder::default()
+        .build(format!("http://{admin_addr}/"))
+        .unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{}/", config.rpc_addr()))
+        .unwrap();
+
+    let _: () = admin
+        .request("admin_addToDenylist", rpc_params![1])
+        .await
+        .unwrap();
+    let lists: serde_json::Value = admin
+        .request("admin_getAccessLists", rpc_params![])
+        .await
+        .unwrap();
+    assert_eq!(lists, serde_json::json!({ "allowed": [], "denied": [1] }));
+
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == UNAUTHORIZED_CODE));
+
+    // The rollup isn't registered, the transaction is let through then
+    // rejected.
+    let _: () = admin
+        .request("admin_removeFromDenylist", rpc_params![1])
+        .await
+        .unwrap();
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == INVALID_PARAMS_CODE));
+
+    // Once a rollup is allowed, the others are rejected.
+    let _: () = admin
+        .request("admin_addToAllowlist", rpc_params![2])
+        .await
+        .unwrap();
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == UNAUTHORIZED_CODE));
+}
+
+#[tokio::test]
+async fn admin_export_audit_log_returns_the_received_transactions() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let path = std::env::temp_dir().join(format!("agglayer-audit-{:x}", H256::random()));
+    let kernel =
+        Kernel::new(provider, config.clone()).with_audit_log(AuditLog::open(&path).unwrap());
+    let admin_addr = next_available_addr();
+    let _admin_handle = AdminImpl::new(
+        kernel.rollups().clone(),
+        kernel.admission().clone(),
+        kernel.access_list().clone(),
+        kernel.auditor().clone(),
+    )
+    .start(admin_addr, &config.rpc)
+    .await
+    .unwrap();
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let admin = HttpClientBuilder::default()
+        .build(format!("http://{admin_addr}/"))
+        .unwrap();
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{}/", config.rpc_addr()))
+        .unwrap();
+
+    // The rollup isn't registered, the transaction is received then rejected
+    // before its verification.
+    let tx = signed_tx_json(1);
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![tx.clone()])
+        .await;
+    assert!(res.is_err());
+
+    let records: Vec<AuditRecord> = admin
+        .request("admin_exportAuditLog", rpc_params![0, 100])
+        .await
+        .unwrap();
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].prev_hash, H256::zero());
+    assert!(matches!(
+        &records[0].event,
+        Some(AuditEvent::Received { tx_hash, request_id: Some(_), .. })
+            if *tx_hash == serde_json::from_value::<SignedTx>(tx).unwrap().hash()
+    ));
+
+    _ = std::fs::remove_dir_all(path);
+}
+
+#[tokio::test]
+async fn send_tx_gives_up_after_client_deadline() {
+    // A server accepting connections without ever answering, standing for
+    // both L1 and the ZkEVM node.
+    let unresponsive = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unresponsive_url = format!("http://{}", unresponsive.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = unresponsive.accept().await {
+            connections.push(socket);
+        }
+    });
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config
+        .full_node_rpcs
+        .insert(1, unresponsive_url.parse().unwrap());
+    let config = Arc::new(config);
+
+    let provider = Provider::<Http>::try_from(unresponsive_url.as_str()).unwrap();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(REQUEST_TIMEOUT_HEADER, HeaderValue::from_static("100"));
+    let client = HttpClientBuilder::default()
+        .set_headers(headers)
+        .build(format!("http://{}/", config.rpc_addr()))
+        .unwrap();
+
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+
+    assert!(
+        matches!(res, Err(ClientError::Call(ref error)) if error.code() == DEADLINE_EXCEEDED_CODE),
+        "unexpected response: {res:?}"
+    );
+}
+
+#[tokio::test]
+async fn request_signature_is_enforced() {
+    use hyper::{Request, StatusCode};
+
+    let signer: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+        .parse()
+        .unwrap();
+    let other: LocalWallet = "0x8da4ef21b864d2cc526dbdb2a120bd2874c36c9d0a1fb7f8c63d7f7a8b41de8f"
+        .parse()
+        .unwrap();
+
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config.rpc.request_signers = vec![signer.address()];
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(10);
+
+    let kernel = Kernel::new(provider, config.clone());
+    let admin_addr = next_available_addr();
+    let _admin_handle = AdminImpl::new(
+        kernel.rollups().clone(),
+        kernel.admission().clone(),
+        kernel.access_list().clone(),
+        kernel.auditor().clone(),
+    )
+    .start(admin_addr, &config.rpc)
+    .await
+    .unwrap();
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let http_client = Client::builder(TokioExecutor::new()).build_http();
+    let uri = format!("http://{}/", config.rpc_addr());
+    let body = r#"{"jsonrpc":"2.0","id":1,"method":"interop_sendCertificate","params":[null]}"#;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let send = |uri: &str, signature: Option<(u64, Signature)>| {
+        let mut req = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header(hyper::header::CONTENT_TYPE, "application/json");
+        if let Some((timestamp, signature)) = signature {
+            req = req
+                .header(REQUEST_TIMESTAMP_HEADER, timestamp.to_string())
+                .header(REQUEST_SIGNATURE_HEADER, signature.to_string());
+        }
+        http_client.request(
+            req.body(Full::new(hyper::body::Bytes::from(body)))
+                .expect("request builder"),
+        )
+    };
+    let sign = |signer: &LocalWallet, timestamp: u64| {
+        let signer = signer.clone();
+
+        async move {
+            let signature = signer
+                .sign_message(signed_message(timestamp, body.as_bytes()))
+                .await
+                .unwrap();
+
+            (timestamp, signature)
+        }
+    };
+
+    // Signed by a registered signer.
+    let res = send(&uri, Some(sign(&signer, now).await)).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Signed by an unknown signer.
+    let res = send(&uri, Some(sign(&other, now).await)).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // Signed too long ago, or for another time.
+    let stale = now - MAX_REQUEST_AGE.as_secs() - 60;
+    let res = send(&uri, Some(sign(&signer, stale).await)).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let (_, signature) = sign(&signer, now).await;
+    let res = send(&uri, Some((now + 1, signature))).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // Not signed at all.
+    let res = send(&uri, None).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // The admin RPC enforces the signatures as well.
+    let admin_uri = format!("http://{admin_addr}/");
+    let res = send(&admin_uri, None).await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let res = send(&admin_uri, Some(sign(&signer, now).await))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // Health checks don't require any signature.
+    let req = Request::builder()
+        .method("GET")
+        .uri(format!("http://{}/health", config.rpc_addr()))
+        .body(Full::new(hyper::body::Bytes::new()))
+        .expect("request builder");
+    let res = http_client.request(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn get_tx_attestation_of_unknown_tx() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    let config = Arc::new(config);
+
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let url = format!("http://{}/", config.rpc_addr());
+    let client = HttpClientBuilder::default().build(url).unwrap();
+
+    let res: Result<serde_json::Value, _> = client
+        .request("interop_getTxAttestation", rpc_params![H256::zero()])
+        .await;
+
+    let Err(ClientError::Call(error)) = res else {
+        panic!("Unexpected response: {res:?}");
+    };
+
+    assert_eq!(error.code(), CALL_EXECUTION_FAILED_CODE);
+}
+
+/// Spawn a [`TimeClock`] to provide the current Epoch to the RPC service.
+async fn clock_ref() -> Arc<ClockRef> {
+    Arc::new(
+        TimeClock::new_now(NonZeroU64::new(60).unwrap())
+            .unwrap()
+            .spawn(CancellationToken::new())
+            .await
+            .unwrap(),
+    )
+}
+
+/// Get the data of the given error, without the ID of the request that failed.
+fn error_data_without_request_id(error: &ErrorObjectOwned) -> serde_json::Value {
+    let mut data = serde_json::from_str::<serde_json::Value>(error.data().unwrap().get()).unwrap();
+    data.as_object_mut()
+        .unwrap()
+        .remove("requestId")
+        .expect("errors carry the ID of the request");
+
+    data
+}
+
+fn client_with_api_key(url: &str, api_key: Option<&str>) -> jsonrpsee::http_client::HttpClient {
+    let mut headers = HeaderMap::new();
+    if let Some(api_key) = api_key {
+        headers.insert(API_KEY_HEADER, HeaderValue::from_str(api_key).unwrap());
+    }
+
+    HttpClientBuilder::default()
+        .set_headers(headers)
+        .build(url)
+        .unwrap()
+}
+
+/// Build the JSON representation of a [`SignedTx`](crate::signed_tx::SignedTx)
+/// for the given rollup.
+fn signed_tx_json(rollup_id: u32) -> serde_json::Value {
+    serde_json::json!({
+        "tx": {
+            "RollupID": rollup_id,
+            "lastVerifiedBatch": "0x0",
+            "newVerifiedBatch": "0x1",
+            "ZKP": {
+                "newStateRoot": H256::repeat_byte(1),
+                "newLocalExitRoot": H256::repeat_byte(2),
+                "proof": format!("0x{}", "00".repeat(HASH_LENGTH * PROOF_LENGTH)),
+            },
+        },
+        "signature": format!("0x{}", "00".repeat(65)),
+    })
+}
+
+/// Register the given signer as the trusted sequencer of the given rollup.
+fn trust_sequencer<Rpc>(kernel: &Kernel<Rpc>, rollup_id: u32, signer: &LocalWallet) {
+    let mut rollup = kernel.rollups().get(rollup_id).unwrap();
+    rollup.trusted_sequencer = Some(signer.address());
+    kernel.rollups().update(rollup).unwrap();
+}
+
+/// Build a [`Certificate`] of the given network, signed by the given signer.
+fn signed_certificate(network_id: u32, height: u64, signer: &LocalWallet) -> Certificate {
+    let mut certificate = Certificate {
+        network_id,
+        height,
+        prev_local_exit_root: H256::zero(),
+        new_local_exit_root: H256::zero(),
+        bridge_exits: Vec::new(),
+        imported_bridge_exits: Vec::new(),
+        signature: Signature {
+            r: 0.into(),
+            s: 0.into(),
+            v: 0,
+        },
+    };
+    certificate.sign(signer).unwrap();
+
+    certificate
+}
+
+fn next_available_addr() -> std::net::SocketAddr {
+    use std::net::{TcpListener, TcpStream};
+
+    let host = "127.0.0.1";
+    // Request a random available port from the OS
+    let listener = TcpListener::bind((host, 0)).expect("Can't bind to an available port");
+    let addr = listener.local_addr().expect("Can't find an available port");
+
+    // Create and accept a connection (which we'll promptly drop) in order to force
+    // the port into the TIME_WAIT state, ensuring that the port will be
+    // reserved from some limited amount of time (roughly 60s on some Linux
+    // systems)
+    let _sender = TcpStream::connect(addr).expect("Can't connect to an available port");
+    let _incoming = listener.accept().expect("Can't accept an available port");
+
+    addr
+}
+
+#[tokio::test]
+async fn l1_circuit_breaker_rejects_the_transactions_right_away() {
+    let mut config = Config::default();
+    let addr = next_available_addr();
+    if let IpAddr::V4(ip) = addr.ip() {
+        config.rpc.host = ip;
+    }
+    config.rpc.port = addr.port();
+    config
+        .full_node_rpcs
+        .insert(1, "http://127.0.0.1:1".parse().unwrap());
+    config.outbound.rpc.retry.max_attempts = 1;
+    config.outbound.rpc.l1_circuit_breaker.failure_threshold = 1;
+    let config = Arc::new(config);
+
+    // The mocked L1 has no response to give, as if unreachable.
+    let (provider, _mock) = providers::Provider::mocked();
+    let (certificate_sender, _certificate_receiver) = tokio::sync::mpsc::channel(1);
+
+    let kernel = Kernel::new(provider, config.clone());
+    assert!(kernel.get_last_verified_batch(1).await.is_err());
+    assert!(kernel.l1_circuit_open());
+
+    let _server_handle = AgglayerImpl::new(kernel, certificate_sender, clock_ref().await)
+        .start(config.clone())
+        .await
+        .unwrap();
+
+    let client = HttpClientBuilder::default()
+        .build(format!("http://{}/", config.rpc_addr()))
+        .unwrap();
+
+    let res: Result<H256, _> = client
+        .request("interop_sendTx", rpc_params![signed_tx_json(1)])
+        .await;
+    assert!(matches!(res, Err(ClientError::Call(error)) if error.code() == L1_UNAVAILABLE_CODE));
 }
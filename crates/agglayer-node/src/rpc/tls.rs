@@ -0,0 +1,231 @@
+//! TLS termination of the RPC server.
+//!
+//! The certificate and the private key are checked for rotation on disk
+//! periodically. Once rotated, they're reloaded and the connections accepted
+//! afterwards are served with the new certificate, without restarting the
+//! server.
+use std::{
+    fmt,
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+
+use agglayer_config::TlsConfig;
+use rustls::{
+    crypto::{ring, CryptoProvider},
+    pki_types::{pem::PemObject as _, CertificateDer, PrivateKeyDer},
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+    ServerConfig,
+};
+use thiserror::Error;
+use tokio_rustls::TlsAcceptor;
+use tracing::{error, info};
+
+/// The time given to the peers to complete the TLS handshake.
+pub(crate) const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Errors that can occur while loading the TLS certificate and private key.
+#[derive(Error, Debug)]
+pub(crate) enum TlsError {
+    #[error("failed to read {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("invalid PEM in {path}: {reason}")]
+    InvalidPem { path: PathBuf, reason: String },
+    #[error("no certificate in {0}")]
+    NoCertificate(PathBuf),
+    #[error("tls error: {0}")]
+    Rustls(#[from] rustls::Error),
+}
+
+/// The PEM encoded certificate chain and private key, as read from disk.
+#[derive(PartialEq, Eq)]
+struct PemFiles {
+    cert: Vec<u8>,
+    key: Vec<u8>,
+}
+
+impl PemFiles {
+    fn read(config: &TlsConfig) -> Result<Self, TlsError> {
+        let read = |path: &Path| {
+            std::fs::read(path).map_err(|source| TlsError::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+        };
+
+        Ok(Self {
+            cert: read(&config.cert_path)?,
+            key: read(&config.key_path)?,
+        })
+    }
+}
+
+/// The certificate served by the RPC server, reloaded once rotated.
+pub(crate) struct TlsCertificates {
+    config: TlsConfig,
+    provider: Arc<CryptoProvider>,
+    current: RwLock<Arc<CertifiedKey>>,
+    /// The files the current certificate was loaded from.
+    loaded: Mutex<PemFiles>,
+}
+
+impl TlsCertificates {
+    /// Load the configured certificate and private key.
+    pub(crate) fn load(config: &TlsConfig) -> Result<Arc<Self>, TlsError> {
+        let provider = Arc::new(ring::default_provider());
+        let files = PemFiles::read(config)?;
+        let certified_key = certified_key(config, &provider, &files)?;
+
+        Ok(Arc::new(Self {
+            config: config.clone(),
+            provider,
+            current: RwLock::new(Arc::new(certified_key)),
+            loaded: Mutex::new(files),
+        }))
+    }
+
+    /// Build the acceptor terminating the TLS connections with the current
+    /// certificate.
+    pub(crate) fn acceptor(self: &Arc<Self>) -> Result<TlsAcceptor, TlsError> {
+        let mut config = ServerConfig::builder_with_provider(self.provider.clone())
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_cert_resolver(self.clone());
+        config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+
+    /// Reload the certificate and the private key if rotated on disk.
+    ///
+    /// Returns whether they were reloaded. The current certificate is kept if
+    /// the rotated files are invalid.
+    pub(crate) fn reload_if_rotated(&self) -> Result<bool, TlsError> {
+        let files = PemFiles::read(&self.config)?;
+        let mut loaded = self.loaded.lock().expect("TLS files lock poisoned");
+        if *loaded == files {
+            return Ok(false);
+        }
+
+        let certified_key = certified_key(&self.config, &self.provider, &files)?;
+        *self.current.write().expect("TLS certificate lock poisoned") = Arc::new(certified_key);
+        *loaded = files;
+
+        Ok(true)
+    }
+
+    /// Check the certificate and the private key for rotation at the
+    /// configured interval, until stopped.
+    pub(crate) async fn watch(self: Arc<Self>, stopped: impl Future<Output = ()>) {
+        let mut interval = tokio::time::interval(self.config.reload_interval);
+        interval.tick().await;
+        tokio::pin!(stopped);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => match self.reload_if_rotated() {
+                    Ok(true) => info!(
+                        "Reloaded the rotated TLS certificate {}",
+                        self.config.cert_path.display()
+                    ),
+                    Ok(false) => {}
+                    Err(error) => error!("Failed to reload the TLS certificate: {error}"),
+                },
+                _ = &mut stopped => break,
+            }
+        }
+    }
+}
+
+impl ResolvesServerCert for TlsCertificates {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(
+            self.current
+                .read()
+                .expect("TLS certificate lock poisoned")
+                .clone(),
+        )
+    }
+}
+
+impl fmt::Debug for TlsCertificates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsCertificates")
+            .field("cert_path", &self.config.cert_path)
+            .field("key_path", &self.config.key_path)
+            .finish()
+    }
+}
+
+/// Parse the given PEM files into a certified key.
+fn certified_key(
+    config: &TlsConfig,
+    provider: &CryptoProvider,
+    files: &PemFiles,
+) -> Result<CertifiedKey, TlsError> {
+    let invalid_pem = |path: &Path| {
+        let path = path.to_path_buf();
+        move |error: rustls::pki_types::pem::Error| TlsError::InvalidPem {
+            path,
+            reason: error.to_string(),
+        }
+    };
+
+    let cert_chain = CertificateDer::pem_slice_iter(&files.cert)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(invalid_pem(&config.cert_path))?;
+    if cert_chain.is_empty() {
+        return Err(TlsError::NoCertificate(config.cert_path.clone()));
+    }
+    let key = PrivateKeyDer::from_pem_slice(&files.key).map_err(invalid_pem(&config.key_path))?;
+
+    Ok(CertifiedKey::from_der(cert_chain, key, provider)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use agglayer_config::TlsConfig;
+    use ethers::types::H256;
+
+    use super::TlsCertificates;
+
+    fn testdata(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(format!("src/rpc/testdata/{name}"))
+    }
+
+    #[test]
+    fn rotated_certificates_are_reloaded() {
+        let dir = std::env::temp_dir().join(format!("agglayer-tls-{:x}", H256::random()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = TlsConfig {
+            cert_path: dir.join("tls.crt"),
+            key_path: dir.join("tls.key"),
+            reload_interval: Duration::from_secs(60),
+        };
+        std::fs::copy(testdata("cert.pem"), &config.cert_path).unwrap();
+        std::fs::copy(testdata("key.pem"), &config.key_path).unwrap();
+
+        let certificates = TlsCertificates::load(&config).unwrap();
+        let served = || certificates.current.read().unwrap().cert.clone();
+        let initial = served();
+
+        assert!(!certificates.reload_if_rotated().unwrap());
+
+        // A certificate rotated without its key is rejected.
+        std::fs::copy(testdata("cert-rotated.pem"), &config.cert_path).unwrap();
+        assert!(certificates.reload_if_rotated().is_err());
+        assert_eq!(served(), initial);
+
+        std::fs::copy(testdata("key-rotated.pem"), &config.key_path).unwrap();
+        assert!(certificates.reload_if_rotated().unwrap());
+        assert_ne!(served(), initial);
+
+        _ = std::fs::remove_dir_all(dir);
+    }
+}
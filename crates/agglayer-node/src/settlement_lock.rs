@@ -0,0 +1,362 @@
+//! Locks guaranteeing that a proof is never settled twice.
+//!
+//! A proof is locked before its settlement transaction is sent to L1 and the
+//! lock is only released if the transaction is known not to have been sent,
+//! or to have been dropped from the mempool. Sharing the locks between the
+//! nodes of a high-availability deployment prevents a proof from being
+//! settled by two nodes during a failover.
+//!
+//! The locks are leases expiring after a configured duration, so that the
+//! proofs locked by a node that crashed can be settled again eventually, or
+//! right away by the same node once it restarted.
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use agglayer_config::{HighAvailabilityConfig, SettlementLockBackend};
+use async_trait::async_trait;
+use ethers::types::H256;
+use tokio::{io::AsyncWriteExt as _, time::Instant};
+
+/// A store of settlement locks, by proof hash.
+#[async_trait]
+pub(crate) trait SettlementLocks: Debug + Send + Sync {
+    /// Lock the settlement of the given proof, returning `false` if it is
+    /// already locked and its lease didn't expire.
+    async fn try_lock(&self, proof_hash: H256) -> io::Result<bool>;
+
+    /// Release the lock of the given proof.
+    async fn release(&self, proof_hash: H256) -> io::Result<()>;
+
+    /// Release the lock of the given proof left by a previous run of this
+    /// node, e.g. one that crashed in the middle of the settlement.
+    async fn release_stale(&self, proof_hash: H256) -> io::Result<()>;
+}
+
+/// Build the settlement locks of the given high-availability configuration.
+pub(crate) fn settlement_locks(config: &HighAvailabilityConfig) -> Arc<dyn SettlementLocks> {
+    let lease = config.settlement_lock_lease;
+    match &config.settlement_locks {
+        SettlementLockBackend::Memory => Arc::new(MemoryLocks::new(lease)),
+        SettlementLockBackend::Directory { path } => Arc::new(DirectoryLocks::new(
+            path.clone(),
+            lease,
+            config.node_id.clone(),
+        )),
+    }
+}
+
+/// Settlement locks held in memory.
+#[derive(Debug)]
+pub(crate) struct MemoryLocks {
+    lease: Duration,
+    /// The expiry of the lease of the locked proofs.
+    locked: Mutex<HashMap<H256, Instant>>,
+}
+
+impl MemoryLocks {
+    pub(crate) fn new(lease: Duration) -> Self {
+        Self {
+            lease,
+            locked: Mutex::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<H256, Instant>> {
+        self.locked.lock().expect("Settlement locks lock poisoned")
+    }
+}
+
+#[async_trait]
+impl SettlementLocks for MemoryLocks {
+    async fn try_lock(&self, proof_hash: H256) -> io::Result<bool> {
+        let now = Instant::now();
+        let mut locked = self.lock();
+        locked.retain(|_, expiry| *expiry > now);
+
+        if locked.contains_key(&proof_hash) {
+            return Ok(false);
+        }
+        locked.insert(proof_hash, now + self.lease);
+
+        Ok(true)
+    }
+
+    async fn release(&self, proof_hash: H256) -> io::Result<()> {
+        self.lock().remove(&proof_hash);
+
+        Ok(())
+    }
+
+    async fn release_stale(&self, _proof_hash: H256) -> io::Result<()> {
+        // The locks of a previous run are gone with its memory.
+        Ok(())
+    }
+}
+
+/// Settlement locks held as files in a directory shared by the nodes.
+///
+/// The successive leases of a proof are numbered files in the directory of
+/// the proof, the last one holding the lock until its modification time is
+/// older than the lease. Each lease is created exclusively, so that a single
+/// node takes over an expired lease, and a released lease is expired rather
+/// than removed, so that the numbers never go back. A lease holds the
+/// identifier of the node that took it.
+#[derive(Debug)]
+pub(crate) struct DirectoryLocks {
+    path: PathBuf,
+    lease: Duration,
+    node_id: String,
+    /// The number of the leases held by this node.
+    held: Mutex<HashMap<H256, u64>>,
+}
+
+impl DirectoryLocks {
+    pub(crate) fn new(path: PathBuf, lease: Duration, node_id: String) -> Self {
+        Self {
+            path,
+            lease,
+            node_id,
+            held: Mutex::default(),
+        }
+    }
+
+    fn proof_path(&self, proof_hash: H256) -> PathBuf {
+        self.path.join(format!("{proof_hash:x}"))
+    }
+
+    fn held(&self) -> std::sync::MutexGuard<'_, HashMap<H256, u64>> {
+        self.held.lock().expect("Settlement locks lock poisoned")
+    }
+}
+
+#[async_trait]
+impl SettlementLocks for DirectoryLocks {
+    async fn try_lock(&self, proof_hash: H256) -> io::Result<bool> {
+        let path = self.proof_path(proof_hash);
+        tokio::fs::create_dir_all(&path).await?;
+
+        let last = last_lease(&path).await?;
+        let next = match last {
+            Some(last) => {
+                let modified = match tokio::fs::metadata(path.join(last.to_string())).await {
+                    Ok(metadata) => metadata.modified()?,
+                    // Removed by the node that just took the lease over.
+                    Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(false),
+                    Err(error) => return Err(error),
+                };
+                if modified + self.lease > SystemTime::now() {
+                    return Ok(false);
+                }
+
+                last + 1
+            }
+            None => 0,
+        };
+
+        let mut file = match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path.join(next.to_string()))
+            .await
+        {
+            Ok(file) => file,
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => return Ok(false),
+            Err(error) => return Err(error),
+        };
+        self.held().insert(proof_hash, next);
+        file.write_all(self.node_id.as_bytes()).await?;
+
+        // The expired lease is of no use anymore.
+        if let Some(last) = last {
+            _ = tokio::fs::remove_file(path.join(last.to_string())).await;
+        }
+
+        Ok(true)
+    }
+
+    async fn release(&self, proof_hash: H256) -> io::Result<()> {
+        let Some(lease) = self.held().remove(&proof_hash) else {
+            return Ok(());
+        };
+
+        expire(&self.proof_path(proof_hash).join(lease.to_string())).await
+    }
+
+    async fn release_stale(&self, proof_hash: H256) -> io::Result<()> {
+        let path = self.proof_path(proof_hash);
+        let last = match last_lease(&path).await {
+            Ok(Some(last)) => last,
+            Ok(None) => return Ok(()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error),
+        };
+        if self.held().get(&proof_hash) == Some(&last) {
+            return Ok(());
+        }
+
+        let lease = path.join(last.to_string());
+        match tokio::fs::read_to_string(&lease).await {
+            Ok(holder) if holder == self.node_id => expire(&lease).await,
+            // Held by another node, or taken over by it already.
+            Ok(_) => Ok(()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Expire the given lease, unless it was taken over already.
+async fn expire(lease: &Path) -> io::Result<()> {
+    match tokio::fs::OpenOptions::new().write(true).open(lease).await {
+        Ok(file) => file.into_std().await.set_modified(SystemTime::UNIX_EPOCH),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error),
+    }
+}
+
+/// The number of the last lease of a proof, if any.
+async fn last_lease(path: &Path) -> io::Result<Option<u64>> {
+    let mut entries = tokio::fs::read_dir(path).await?;
+    let mut last = None;
+    while let Some(entry) = entries.next_entry().await? {
+        if let Some(lease) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse::<u64>().ok())
+        {
+            last = last.max(Some(lease));
+        }
+    }
+
+    Ok(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ethers::types::H256;
+
+    use super::{DirectoryLocks, MemoryLocks, SettlementLocks};
+
+    const LEASE: Duration = Duration::from_secs(3600);
+
+    async fn lock_once(locks: &dyn SettlementLocks) {
+        let proof_hash = H256::random();
+
+        assert!(locks.try_lock(proof_hash).await.unwrap());
+        assert!(!locks.try_lock(proof_hash).await.unwrap());
+        assert!(locks.try_lock(H256::random()).await.unwrap());
+
+        locks.release(proof_hash).await.unwrap();
+        assert!(locks.try_lock(proof_hash).await.unwrap());
+        assert!(!locks.try_lock(proof_hash).await.unwrap());
+    }
+
+    fn node_id() -> String {
+        format!("agglayer-{:x}", H256::random())
+    }
+
+    fn temp_dir() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("agglayer-settlements-{:x}", H256::random()))
+    }
+
+    #[tokio::test]
+    async fn memory_locks_are_exclusive() {
+        lock_once(&MemoryLocks::new(LEASE)).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn memory_locks_expire() {
+        let locks = MemoryLocks::new(LEASE);
+        let proof_hash = H256::random();
+
+        assert!(locks.try_lock(proof_hash).await.unwrap());
+        tokio::time::advance(LEASE - Duration::from_secs(1)).await;
+        assert!(!locks.try_lock(proof_hash).await.unwrap());
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert!(locks.try_lock(proof_hash).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn directory_locks_are_exclusive() {
+        let path = temp_dir();
+
+        lock_once(&DirectoryLocks::new(path.clone(), LEASE, node_id())).await;
+
+        // The locks are shared by the nodes using the same directory.
+        let proof_hash = H256::random();
+        let node = DirectoryLocks::new(path.clone(), LEASE, node_id());
+        let other = DirectoryLocks::new(path.clone(), LEASE, node_id());
+        assert!(node.try_lock(proof_hash).await.unwrap());
+        assert!(!other.try_lock(proof_hash).await.unwrap());
+
+        // Releasing a lock not held is a no-op.
+        other.release(proof_hash).await.unwrap();
+        assert!(!other.try_lock(proof_hash).await.unwrap());
+
+        node.release(proof_hash).await.unwrap();
+        assert!(other.try_lock(proof_hash).await.unwrap());
+        assert!(!node.try_lock(proof_hash).await.unwrap());
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn directory_locks_expire() {
+        let path = temp_dir();
+        let proof_hash = H256::random();
+
+        // A crashed node never releases its lock, which expires after the lease.
+        let crashed = DirectoryLocks::new(path.clone(), LEASE, node_id());
+        assert!(crashed.try_lock(proof_hash).await.unwrap());
+
+        let node = DirectoryLocks::new(path.clone(), Duration::ZERO, node_id());
+        assert!(node.try_lock(proof_hash).await.unwrap());
+        let other = DirectoryLocks::new(path.clone(), LEASE, node_id());
+        assert!(!other.try_lock(proof_hash).await.unwrap());
+
+        // The expired lease is replaced by the next one.
+        let leases = std::fs::read_dir(path.join(format!("{proof_hash:x}")))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(leases, ["1"]);
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn directory_locks_of_a_previous_run_are_released() {
+        let path = temp_dir();
+        let proof_hash = H256::random();
+
+        let crashed = DirectoryLocks::new(path.clone(), LEASE, "agglayer-0".to_string());
+        assert!(crashed.try_lock(proof_hash).await.unwrap());
+
+        // Another node leaves the lock alone.
+        let other = DirectoryLocks::new(path.clone(), LEASE, "agglayer-1".to_string());
+        other.release_stale(proof_hash).await.unwrap();
+        assert!(!other.try_lock(proof_hash).await.unwrap());
+
+        // The same node restarted takes it over.
+        let restarted = DirectoryLocks::new(path.clone(), LEASE, "agglayer-0".to_string());
+        restarted.release_stale(proof_hash).await.unwrap();
+        assert!(restarted.try_lock(proof_hash).await.unwrap());
+
+        // Its lock of the current run is not stale.
+        restarted.release_stale(proof_hash).await.unwrap();
+        assert!(!other.try_lock(proof_hash).await.unwrap());
+
+        // Releasing the locks of proofs never locked is a no-op.
+        restarted.release_stale(H256::random()).await.unwrap();
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -2,61 +2,140 @@
 //!
 //! Systems that wish to submit proofs to the agglayer must produce a
 //! [`SignedProof`] conforming to the type definitions specified herein.
-use ethers::{prelude::*, utils::keccak256};
-use serde::{Deserialize, Deserializer};
+use agglayer_config::{ProofFormat, L1};
+use ethers::{
+    abi::{self, Token},
+    prelude::*,
+    types::transaction::eip712::EIP712Domain,
+    utils::keccak256,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_with::{serde_as, DisplayFromStr};
 use thiserror::Error;
 
 pub(crate) const HASH_LENGTH: usize = 32;
+/// The number of words of the fflonk proofs expected by default.
+#[cfg(test)]
 pub(crate) const PROOF_LENGTH: usize = 24;
 
 /// Raw proof bytes.
 ///
-/// This is a fixed-size array of fixed-size arrays, where each inner array is a
-/// 32-byte hash.
-#[derive(Debug)]
-pub(crate) struct Proof([[u8; HASH_LENGTH]; PROOF_LENGTH]);
+/// The proof is expected to be a fixed-size array of fixed-size arrays, where
+/// each inner array is a 32-byte hash. The decoded bytes are kept as-is upon
+/// deserialization so that malformed proofs can be rejected with a precise
+/// error once the submission is known, see [`Proof::check_size`].
+#[derive(Clone, Debug)]
+pub(crate) struct Proof(Bytes);
 
 #[derive(Error, Debug)]
 pub(crate) enum ProofEncodingError {
+    #[error("proof is too large: the limit is {limit} bytes, got {actual} bytes")]
+    TooLarge { limit: usize, actual: usize },
     #[error("invalid proof length: expected {expected}, got {got}")]
     InvalidLength { expected: usize, got: usize },
     #[error("invalid hash at index {index}")]
     InvalidHash { index: usize },
+    #[error("invalid verifier selector: expected the proof to start with {expected}")]
+    InvalidVerifierSelector { expected: Bytes },
+}
+
+/// Errors related to the consistency of a [`SignedTx`].
+#[derive(Error, Debug)]
+pub(crate) enum SignedTxError {
+    #[error(
+        "invalid batch range: the new verified batch {new} doesn't follow the last verified batch \
+         {last}"
+    )]
+    InvalidBatchRange { last: u64, new: u64 },
+    #[error("the new state root is zero")]
+    ZeroStateRoot,
+    #[error("the new local exit root is zero")]
+    ZeroLocalExitRoot,
 }
 
 impl Proof {
-    /// Convert the proof into a byte array.
-    pub(crate) fn as_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(HASH_LENGTH * PROOF_LENGTH);
-        for hash in &self.0 {
-            bytes.extend_from_slice(&hash[..]);
+    /// The raw bytes of the proof.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// The size of the decoded proof, in bytes.
+    pub(crate) fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check that the decoded proof has the shape expected by the given
+    /// [`ProofFormat`].
+    pub(crate) fn check_format(&self, format: &ProofFormat) -> Result<(), ProofEncodingError> {
+        let size = format.proof_length.saturating_mul(HASH_LENGTH);
+
+        match self.len() {
+            actual if actual > size => {
+                return Err(ProofEncodingError::TooLarge {
+                    limit: size,
+                    actual,
+                })
+            }
+            got if got < size => {
+                return Err(ProofEncodingError::InvalidLength {
+                    expected: size,
+                    got,
+                })
+            }
+            _ => {}
+        }
+
+        match &format.verifier_selector {
+            Some(selector) if !self.0.starts_with(selector) => {
+                Err(ProofEncodingError::InvalidVerifierSelector {
+                    expected: selector.clone(),
+                })
+            }
+            _ => Ok(()),
         }
-        bytes
     }
 
-    /// Convert the proof into a fixed-size array of byte arrays.
-    pub(crate) fn to_fixed_bytes(&self) -> [[u8; HASH_LENGTH]; PROOF_LENGTH] {
+    /// Convert the proof into its 32-byte words, checking it against the given
+    /// [`ProofFormat`].
+    pub(crate) fn to_words(
+        &self,
+        format: &ProofFormat,
+    ) -> Result<Vec<[u8; HASH_LENGTH]>, ProofEncodingError> {
+        self.check_format(format)?;
+
         self.0
+            .chunks_exact(HASH_LENGTH)
+            .enumerate()
+            .map(|(i, hash)| {
+                hash.try_into()
+                    .map_err(|_| ProofEncodingError::InvalidHash { index: i })
+            })
+            .collect()
+    }
+
+    /// Convert a byte array into a proof, leaving the checks of its shape to
+    /// [`Proof::check_format`].
+    pub(crate) fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(Bytes::from(bytes))
     }
 
     /// Convert a byte array into a proof.
+    #[cfg(test)]
     pub(crate) fn try_from_slice(slice: &[u8]) -> Result<Self, ProofEncodingError> {
-        if slice.len() != HASH_LENGTH * PROOF_LENGTH {
-            return Err(ProofEncodingError::InvalidLength {
-                expected: HASH_LENGTH * PROOF_LENGTH,
-                got: slice.len(),
-            });
-        }
+        let proof = Self(Bytes::from(slice.to_vec()));
+        proof.check_format(&ProofFormat::default())?;
 
-        let mut proof = [[0; HASH_LENGTH]; PROOF_LENGTH];
-        for (i, hash) in slice.chunks_exact(HASH_LENGTH).enumerate() {
-            proof[i] = hash
-                .try_into()
-                .map_err(|_| ProofEncodingError::InvalidHash { index: i })?;
-        }
+        Ok(proof)
+    }
+}
 
-        Ok(Self(proof))
+impl Serialize for Proof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(serializer)
     }
 }
 
@@ -65,45 +144,93 @@ impl<'de> Deserialize<'de> for Proof {
     where
         D: Deserializer<'de>,
     {
-        Proof::try_from_slice(&Bytes::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+        Ok(Proof(Bytes::deserialize(deserializer)?))
     }
 }
 
 /// The zero-knowledge proof.
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub(crate) struct Zkp {
+    #[schemars(with = "String")]
     pub(crate) new_state_root: H256,
+    #[schemars(with = "String")]
     pub(crate) new_local_exit_root: H256,
+    #[schemars(with = "String")]
     pub(crate) proof: Proof,
 }
 
 /// Proof metadata along with its zero-knowledge proof.
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub(crate) struct ProofManifest {
     #[serde(rename = "RollupID")]
     pub(crate) rollup_id: u32,
+    #[schemars(with = "String")]
     pub(crate) last_verified_batch: U64,
+    #[schemars(with = "String")]
     pub(crate) new_verified_batch: U64,
     #[serde(rename = "ZKP")]
     pub(crate) zkp: Zkp,
 }
 
+/// The EIP-712 encoding of the proof manifest, signed by the trusted
+/// sequencers.
+impl ProofManifest {
+    /// The EIP-712 domain of the proofs settled on the given L1 network.
+    ///
+    /// The domain binds the signatures to the chain id of the L1 network and
+    /// to its rollup manager contract, so that they can't be replayed against
+    /// another network.
+    fn domain(l1: &L1) -> EIP712Domain {
+        EIP712Domain {
+            name: Some("Agglayer".to_string()),
+            version: Some("1".to_string()),
+            chain_id: Some(l1.chain_id.into()),
+            verifying_contract: Some(l1.rollup_manager_contract),
+            salt: None,
+        }
+    }
+
+    fn type_hash() -> [u8; 32] {
+        keccak256(
+            "ProofManifest(uint32 rollupId,uint64 lastVerifiedBatch,uint64 \
+             newVerifiedBatch,bytes32 newStateRoot,bytes32 newLocalExitRoot,bytes proof)",
+        )
+    }
+
+    fn struct_hash(&self) -> [u8; 32] {
+        keccak256(abi::encode(&[
+            Token::FixedBytes(Self::type_hash().to_vec()),
+            Token::Uint(self.rollup_id.into()),
+            Token::Uint(self.last_verified_batch.as_u64().into()),
+            Token::Uint(self.new_verified_batch.as_u64().into()),
+            Token::FixedBytes(self.zkp.new_state_root.as_bytes().to_vec()),
+            Token::FixedBytes(self.zkp.new_local_exit_root.as_bytes().to_vec()),
+            Token::FixedBytes(keccak256(self.zkp.proof.as_bytes()).to_vec()),
+        ]))
+    }
+}
+
 /// A [`SignedTx`] is the core input type of the agglayer.
 ///
 /// Systems that wish to submit proofs to the agglayer must produce a
 /// [`SignedTx`] conforming to the type definitions specified herein.
 #[serde_as]
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub(crate) struct SignedTx {
     pub(crate) tx: ProofManifest,
     #[serde_as(as = "DisplayFromStr")]
+    #[schemars(with = "String")]
     pub(crate) signature: Signature,
 }
 
 impl SignedTx {
     /// Generate a hash that uniquely identifies this proof.
+    ///
+    /// This is also the legacy hash signed by the trusted sequencers, before
+    /// they sign the [`SignedTx::typed_data_hash`].
     pub(crate) fn hash(&self) -> H256 {
         let last_verified_batch_hex = format!("0x{:x}", self.tx.last_verified_batch.as_u64());
         let new_verified_batch_hex = format!("0x{:x}", self.tx.new_verified_batch.as_u64());
@@ -121,8 +248,50 @@ impl SignedTx {
         keccak256(data).into()
     }
 
-    /// Attempt to recover the address of the signer.
-    pub(crate) fn signer(&self) -> Result<Address, SignatureError> {
+    /// Check that the proof manifest is consistent: the batch range is not
+    /// empty, and the new roots are set.
+    pub(crate) fn validate(&self) -> Result<(), SignedTxError> {
+        let (last, new) = (self.tx.last_verified_batch, self.tx.new_verified_batch);
+        if new <= last {
+            return Err(SignedTxError::InvalidBatchRange {
+                last: last.as_u64(),
+                new: new.as_u64(),
+            });
+        }
+
+        if self.tx.zkp.new_state_root.is_zero() {
+            return Err(SignedTxError::ZeroStateRoot);
+        }
+
+        if self.tx.zkp.new_local_exit_root.is_zero() {
+            return Err(SignedTxError::ZeroLocalExitRoot);
+        }
+
+        Ok(())
+    }
+
+    /// The EIP-712 hash of the proof manifest, to be signed by the trusted
+    /// sequencer for the proof to be settled on the given L1 network.
+    pub(crate) fn typed_data_hash(&self, l1: &L1) -> H256 {
+        let data = [
+            &[0x19, 0x01][..],
+            &ProofManifest::domain(l1).separator(),
+            &self.tx.struct_hash(),
+        ]
+        .concat();
+
+        keccak256(data).into()
+    }
+
+    /// Attempt to recover the address of the signer of the
+    /// [`SignedTx::typed_data_hash`] for the given L1 network.
+    pub(crate) fn signer(&self, l1: &L1) -> Result<Address, SignatureError> {
+        self.signature.recover(self.typed_data_hash(l1))
+    }
+
+    /// Attempt to recover the address of the signer of the legacy
+    /// [`SignedTx::hash`].
+    pub(crate) fn legacy_signer(&self) -> Result<Address, SignatureError> {
         self.signature.recover(self.hash())
     }
 
@@ -130,6 +299,17 @@ impl SignedTx {
     pub(crate) fn sign(
         &mut self,
         signer: &Wallet<k256::ecdsa::SigningKey>,
+        l1: &L1,
+    ) -> Result<(), SignatureError> {
+        self.signature = signer.sign_hash(self.typed_data_hash(l1)).unwrap();
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn sign_legacy(
+        &mut self,
+        signer: &Wallet<k256::ecdsa::SigningKey>,
     ) -> Result<(), SignatureError> {
         self.signature = signer.sign_hash(self.hash()).unwrap();
 
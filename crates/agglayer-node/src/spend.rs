@@ -0,0 +1,229 @@
+//! Accounting of the L1 costs of the settlements, per rollup and per epoch.
+//!
+//! Operators running the agglayer as a service attribute the gas and the ETH
+//! spent settling the proofs to the rollups that submitted them.
+use std::{
+    collections::BTreeMap,
+    io,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use agglayer_config::SpendReportsConfig;
+use agglayer_telemetry::{KeyValue, SETTLEMENT_GAS_USED, SETTLEMENT_SPENT_ETH};
+use ethers::types::{TransactionReceipt, U256};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The L1 costs of the settlements of a rollup during an epoch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EpochSpend {
+    pub(crate) epoch: u64,
+    /// The number of settlement transactions.
+    pub(crate) settlements: u64,
+    #[schemars(with = "String")]
+    pub(crate) gas_used: U256,
+    #[schemars(with = "String")]
+    pub(crate) spent_wei: U256,
+}
+
+/// The L1 costs of the settlements of a rollup over a range of epochs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SpendReport {
+    pub(crate) rollup_id: u32,
+    pub(crate) from_epoch: u64,
+    pub(crate) to_epoch: u64,
+    /// The epochs of the range with at least one settlement.
+    pub(crate) epochs: Vec<EpochSpend>,
+    #[schemars(with = "String")]
+    pub(crate) gas_used: U256,
+    #[schemars(with = "String")]
+    pub(crate) spent_wei: U256,
+}
+
+/// A range of epochs, inclusive.
+#[derive(Clone, Copy, Debug, Deserialize, JsonSchema)]
+pub(crate) struct EpochRange {
+    pub(crate) from: u64,
+    pub(crate) to: u64,
+}
+
+/// An [`EpochSpend`] along with its rollup, as persisted.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PersistedSpend {
+    rollup_id: u32,
+    #[serde(flatten)]
+    spend: EpochSpend,
+}
+
+/// The ledger of the L1 costs of the settlements, by rollup and by epoch.
+#[derive(Clone, Debug)]
+pub(crate) struct SpendLedger {
+    spends: Arc<RwLock<BTreeMap<(u32, u64), EpochSpend>>>,
+    path: Option<PathBuf>,
+}
+
+impl SpendLedger {
+    pub(crate) fn new(config: &SpendReportsConfig) -> Self {
+        Self {
+            spends: Arc::default(),
+            path: config.path.clone(),
+        }
+    }
+
+    /// Load the persisted costs, if any.
+    pub(crate) fn restore(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let persisted: Vec<PersistedSpend> = match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error),
+        };
+
+        self.write().extend(
+            persisted
+                .into_iter()
+                .map(|p| ((p.rollup_id, p.spend.epoch), p.spend)),
+        );
+
+        Ok(())
+    }
+
+    /// Account for the settlement of a proof of the given rollup during the
+    /// given epoch.
+    pub(crate) fn record(
+        &self,
+        rollup_id: u32,
+        epoch: u64,
+        receipt: &TransactionReceipt,
+    ) -> io::Result<()> {
+        let gas_used = receipt.gas_used.unwrap_or_default();
+        let spent_wei = gas_used.saturating_mul(receipt.effective_gas_price.unwrap_or_default());
+
+        let attributes = [KeyValue::new("rollup_id", rollup_id.to_string())];
+        SETTLEMENT_GAS_USED.add(gas_used.low_u64(), &attributes);
+        SETTLEMENT_SPENT_ETH.add(spent_wei.low_u128() as f64 / 1e18, &attributes);
+
+        let mut spends = self.write();
+        let spend = spends.entry((rollup_id, epoch)).or_insert(EpochSpend {
+            epoch,
+            ..Default::default()
+        });
+        spend.settlements += 1;
+        spend.gas_used = spend.gas_used.saturating_add(gas_used);
+        spend.spent_wei = spend.spent_wei.saturating_add(spent_wei);
+
+        self.persist(&spends)
+    }
+
+    /// Report the costs of the given rollup between the given epochs,
+    /// inclusive.
+    pub(crate) fn report(&self, rollup_id: u32, from_epoch: u64, to_epoch: u64) -> SpendReport {
+        let epochs: Vec<EpochSpend> = if from_epoch <= to_epoch {
+            self.read()
+                .range((rollup_id, from_epoch)..=(rollup_id, to_epoch))
+                .map(|(_, spend)| *spend)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        SpendReport {
+            rollup_id,
+            from_epoch,
+            to_epoch,
+            gas_used: epochs.iter().fold(U256::zero(), |total, spend| {
+                total.saturating_add(spend.gas_used)
+            }),
+            spent_wei: epochs.iter().fold(U256::zero(), |total, spend| {
+                total.saturating_add(spend.spent_wei)
+            }),
+            epochs,
+        }
+    }
+
+    fn persist(&self, spends: &BTreeMap<(u32, u64), EpochSpend>) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let persisted: Vec<PersistedSpend> = spends
+            .iter()
+            .map(|(&(rollup_id, _), &spend)| PersistedSpend { rollup_id, spend })
+            .collect();
+
+        // Write then rename, so that the persisted costs are never truncated.
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&persisted)?)?;
+        std::fs::rename(tmp, path)
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<(u32, u64), EpochSpend>> {
+        self.spends.read().expect("Spend ledger lock poisoned")
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<(u32, u64), EpochSpend>> {
+        self.spends.write().expect("Spend ledger lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::*;
+
+    fn receipt(gas_used: u64, gas_price: u64) -> TransactionReceipt {
+        TransactionReceipt {
+            gas_used: Some(gas_used.into()),
+            effective_gas_price: Some(gas_price.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn spend_is_reported_per_rollup_and_epoch() {
+        let path = std::env::temp_dir().join(format!("agglayer-spend-{:x}.json", H256::random()));
+        let ledger = SpendLedger::new(&SpendReportsConfig {
+            path: Some(path.clone()),
+        });
+
+        ledger.record(1, 3, &receipt(100, 2)).unwrap();
+        ledger.record(1, 3, &receipt(50, 4)).unwrap();
+        ledger.record(1, 5, &receipt(10, 1)).unwrap();
+        ledger.record(2, 4, &receipt(1000, 1)).unwrap();
+
+        let report = ledger.report(1, 0, 4);
+
+        assert_eq!(
+            report.epochs,
+            vec![EpochSpend {
+                epoch: 3,
+                settlements: 2,
+                gas_used: 150.into(),
+                spent_wei: 400.into(),
+            }]
+        );
+        assert_eq!(report.gas_used, 150.into());
+        assert_eq!(report.spent_wei, 400.into());
+        assert_eq!(ledger.report(1, 0, 10).spent_wei, 410.into());
+        assert!(ledger.report(1, 10, 0).epochs.is_empty());
+
+        // The costs survive a restart.
+        let restored = SpendLedger::new(&SpendReportsConfig {
+            path: Some(path.clone()),
+        });
+        restored.restore().unwrap();
+
+        assert_eq!(restored.report(1, 0, 10), ledger.report(1, 0, 10));
+        assert_eq!(restored.report(2, 0, 10), ledger.report(2, 0, 10));
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+//! Storage of the payloads of the submissions accepted by the agglayer.
+//!
+//! Proofs dominate the size of the stored data, so the payloads are stored
+//! zstd-compressed and transparently decompressed on read.
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, Mutex},
+};
+
+use agglayer_telemetry::PROOF_COMPRESSION_RATIO;
+use ethers::types::H256;
+
+/// The zstd compression level of the stored payloads.
+const COMPRESSION_LEVEL: i32 = zstd::DEFAULT_COMPRESSION_LEVEL;
+
+/// Store the raw payloads of the submissions, by submission hash.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PayloadStore {
+    payloads: Arc<Mutex<HashMap<H256, Vec<u8>>>>,
+}
+
+impl PayloadStore {
+    /// Compress and store the payload of the given submission.
+    pub(crate) fn insert(&self, hash: H256, payload: &[u8]) -> io::Result<()> {
+        let compressed = zstd::bulk::compress(payload, COMPRESSION_LEVEL)?;
+
+        if !compressed.is_empty() {
+            PROOF_COMPRESSION_RATIO.record(payload.len() as f64 / compressed.len() as f64, &[]);
+        }
+
+        self.lock().insert(hash, compressed);
+
+        Ok(())
+    }
+
+    /// Remove the payload of the given submission, returning it
+    /// decompressed.
+    pub(crate) fn remove(&self, hash: &H256) -> io::Result<Option<Vec<u8>>> {
+        self.lock()
+            .remove(hash)
+            .map(|compressed| zstd::decode_all(compressed.as_slice()))
+            .transpose()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<H256, Vec<u8>>> {
+        self.payloads.lock().expect("Payload store lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::PayloadStore;
+
+    #[test]
+    fn payloads_are_decompressed_on_read() {
+        let store = PayloadStore::default();
+        let hash = H256::random();
+        let payload = [[0u8; 32], [1u8; 32]].concat().repeat(12);
+
+        store.insert(hash, &payload).unwrap();
+
+        assert!(store.lock()[&hash].len() < payload.len());
+        assert_eq!(store.remove(&hash).unwrap(), Some(payload));
+        assert_eq!(store.remove(&hash).unwrap(), None);
+    }
+}
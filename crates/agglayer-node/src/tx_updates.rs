@@ -0,0 +1,123 @@
+//! Status transitions of the transactions submitted to the agglayer.
+//!
+//! Streamed by `interop_subscribeTxUpdates` and `interop_subscribeAllTxUpdates`
+//! so that the clients don't have to poll `interop_getTxStatus`.
+use ethers::types::{TransactionReceipt, H256};
+use schemars::JsonSchema;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// The number of updates buffered for the subscribers falling behind.
+const CAPACITY: usize = 1024;
+
+/// The status of a transaction, in the order of its lifecycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TxUpdateStatus {
+    /// The proof passed the verification checks.
+    Verified,
+    /// The settlement transaction was sent to L1, or replaced.
+    Submitted,
+    /// The settlement transaction was mined.
+    Mined,
+    /// The settlement transaction is final.
+    Finalized,
+    /// The proof was rejected, or its settlement failed.
+    Failed,
+}
+
+impl TxUpdateStatus {
+    /// Whether no update follows this one.
+    pub(crate) fn is_terminal(self) -> bool {
+        matches!(self, TxUpdateStatus::Finalized | TxUpdateStatus::Failed)
+    }
+}
+
+/// A status transition of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TxUpdate {
+    /// The hash of the transaction, as returned by `interop_sendTx`.
+    #[schemars(with = "String")]
+    pub(crate) tx_hash: H256,
+    pub(crate) status: TxUpdateStatus,
+    /// The hash of the L1 settlement transaction.
+    #[schemars(with = "Option<String>")]
+    pub(crate) settlement_tx_hash: Option<H256>,
+    /// The L1 Block height the settlement transaction was mined at.
+    pub(crate) block_number: Option<u64>,
+    /// The reason of the failure.
+    pub(crate) error: Option<String>,
+}
+
+impl TxUpdate {
+    fn new(tx_hash: H256, status: TxUpdateStatus) -> Self {
+        Self {
+            tx_hash,
+            status,
+            settlement_tx_hash: None,
+            block_number: None,
+            error: None,
+        }
+    }
+
+    pub(crate) fn verified(tx_hash: H256) -> Self {
+        Self::new(tx_hash, TxUpdateStatus::Verified)
+    }
+
+    pub(crate) fn submitted(tx_hash: H256, settlement_tx_hash: H256) -> Self {
+        Self {
+            settlement_tx_hash: Some(settlement_tx_hash),
+            ..Self::new(tx_hash, TxUpdateStatus::Submitted)
+        }
+    }
+
+    pub(crate) fn mined(tx_hash: H256, receipt: &TransactionReceipt) -> Self {
+        Self::settled(tx_hash, TxUpdateStatus::Mined, receipt)
+    }
+
+    pub(crate) fn finalized(tx_hash: H256, receipt: &TransactionReceipt) -> Self {
+        Self::settled(tx_hash, TxUpdateStatus::Finalized, receipt)
+    }
+
+    pub(crate) fn failed(tx_hash: H256, error: impl Into<String>) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..Self::new(tx_hash, TxUpdateStatus::Failed)
+        }
+    }
+
+    fn settled(tx_hash: H256, status: TxUpdateStatus, receipt: &TransactionReceipt) -> Self {
+        Self {
+            settlement_tx_hash: Some(receipt.transaction_hash),
+            block_number: receipt.block_number.map(|number| number.as_u64()),
+            ..Self::new(tx_hash, status)
+        }
+    }
+}
+
+/// Broadcast the [`TxUpdate`]s to the subscribers.
+#[derive(Clone, Debug)]
+pub(crate) struct TxUpdates {
+    sender: broadcast::Sender<TxUpdate>,
+}
+
+impl Default for TxUpdates {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(CAPACITY).0,
+        }
+    }
+}
+
+impl TxUpdates {
+    /// Publish the given update, to the subscribers if any.
+    pub(crate) fn publish(&self, update: TxUpdate) {
+        _ = self.sender.send(update);
+    }
+
+    /// Subscribe to the updates published from now on.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<TxUpdate> {
+        self.sender.subscribe()
+    }
+}
@@ -1,23 +1,43 @@
 //! The ZkEVM node JSON RPC client.
-use ethers::types::H256;
-use jsonrpsee::{
-    core::client::{error::Error, ClientT},
-    rpc_params,
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use agglayer_config::{OutboundConnectionsConfig, OutboundProxyConfig};
+use agglayer_telemetry::{KeyValue, ZKEVM_NODE_REQUEST_DURATION, ZKEVM_NODE_REQUEST_ERRORS};
+use ethers::{
+    providers::JsonRpcClient,
+    types::{H256, U64},
 };
 use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+use url::Url;
+
+use crate::refresh::RefreshingHttp;
 
 /// The ZkEVM node JSON RPC client.
 ///
 /// This client provides functionality for interacting with the ZkEVM node.
 /// The ZkEVM node JSON RPC methods are defined [here](https://github.com/0xPolygonHermez/zkevm-node/blob/aae30e9c79bdf363814e7fe2a3df9b34e855c998/jsonrpc/endpoints_zkevm.openrpc.json).
+#[derive(Debug)]
 pub(crate) struct ZkevmNodeClient<C> {
     client: C,
+    /// The rollup served by the ZkEVM node, labelling the metrics.
+    rollup_id: u32,
+    /// The slots of the concurrent requests.
+    permits: Semaphore,
 }
 
 impl<C> ZkevmNodeClient<C> {
     /// Create a new instance of the ZkEVM node client.
-    pub(crate) fn new(client: C) -> Self {
-        Self { client }
+    pub(crate) fn new(client: C, rollup_id: u32, max_concurrent_requests: usize) -> Self {
+        Self {
+            client,
+            rollup_id,
+            permits: Semaphore::new(max_concurrent_requests.clamp(1, Semaphore::MAX_PERMITS)),
+        }
     }
 }
 
@@ -30,17 +50,93 @@ pub(crate) struct BatchByNumberResponse {
 
 impl<C> ZkevmNodeClient<C>
 where
-    C: ClientT,
+    C: JsonRpcClient,
 {
     pub(crate) async fn batch_by_number(
         &self,
         batch_number: u64,
-    ) -> Result<BatchByNumberResponse, Error> {
-        self.client
-            .request(
-                "zkevm_getBatchByNumber",
-                rpc_params![format!("0x{:x}", batch_number), false],
-            )
+    ) -> Result<BatchByNumberResponse, C::Error> {
+        self.request(
+            "zkevm_getBatchByNumber",
+            (format!("0x{:x}", batch_number), false),
+        )
+        .await
+    }
+
+    /// Get the number of the latest batch known by the ZkEVM node.
+    pub(crate) async fn batch_number(&self) -> Result<U64, C::Error> {
+        self.request("zkevm_batchNumber", ()).await
+    }
+
+    /// Send a request once a slot is available, recording its duration and
+    /// outcome.
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, C::Error>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: serde::de::DeserializeOwned + Send,
+    {
+        let _permit = self
+            .permits
+            .acquire()
             .await
+            .expect("ZkEVM node client semaphore closed");
+
+        let started_at = Instant::now();
+        let result = self.client.request(method, params).await;
+
+        let attributes = [
+            KeyValue::new("rollup_id", self.rollup_id.to_string()),
+            KeyValue::new("method", method.to_string()),
+        ];
+        ZKEVM_NODE_REQUEST_DURATION.record(started_at.elapsed().as_secs_f64(), &attributes);
+        if result.is_err() {
+            ZKEVM_NODE_REQUEST_ERRORS.add(1, &attributes);
+        }
+
+        result
+    }
+}
+
+/// A pooled [`ZkevmNodeClient`], along with the endpoint it is connected to.
+type PooledClient = (Url, Arc<ZkevmNodeClient<RefreshingHttp>>);
+
+/// The pooled [`ZkevmNodeClient`]s, one per rollup, reusing their connections
+/// across the verifications.
+#[derive(Debug, Default)]
+pub(crate) struct ZkevmNodeClients {
+    clients: Mutex<HashMap<u32, PooledClient>>,
+}
+
+impl ZkevmNodeClients {
+    /// Get the client of the given rollup, building it on first use or when
+    /// its endpoint changed.
+    pub(crate) fn get_or_connect(
+        &self,
+        rollup_id: u32,
+        endpoint: &Url,
+        proxy: Option<&OutboundProxyConfig>,
+        connections: &OutboundConnectionsConfig,
+        max_concurrent_requests: usize,
+    ) -> reqwest::Result<Arc<ZkevmNodeClient<RefreshingHttp>>> {
+        let mut clients = self
+            .clients
+            .lock()
+            .expect("ZkEVM node clients lock poisoned");
+
+        match clients.get(&rollup_id) {
+            Some((url, client)) if url == endpoint => Ok(client.clone()),
+            _ => {
+                let transport =
+                    RefreshingHttp::new(endpoint.clone(), proxy.cloned(), connections.clone())?;
+                let client = Arc::new(ZkevmNodeClient::new(
+                    transport,
+                    rollup_id,
+                    max_concurrent_requests,
+                ));
+                clients.insert(rollup_id, (endpoint.clone(), client.clone()));
+
+                Ok(client)
+            }
+        }
     }
 }
@@ -0,0 +1,63 @@
+//! Builds the `pessimistic-proof-program` with the `succinct` toolchain, and
+//! places its ELF in `OUT_DIR` to be embedded by the prover.
+//!
+//! The toolchain is installed with `sp1up`, see https://docs.succinct.xyz.
+use std::{env, fs, path::PathBuf, process::Command};
+
+const PACKAGE_NAME: &str = "pessimistic-proof-program";
+const BUILD_TARGET: &str = "riscv32im-succinct-zkvm-elf";
+const RUSTUP_TOOLCHAIN: &str = "succinct";
+const RUST_FLAGS: [&str; 6] = [
+    "-C",
+    "passes=loweratomic",
+    "-C",
+    "link-arg=-Ttext=0x00200800",
+    "-C",
+    "panic=abort",
+];
+
+fn main() {
+    let crates = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("..");
+    let program = crates.join(PACKAGE_NAME);
+
+    for path in [
+        program.join("src"),
+        program.join("Cargo.toml"),
+        program.join("Cargo.lock"),
+        crates.join("pessimistic-proof/src"),
+        crates.join("pessimistic-proof/Cargo.toml"),
+    ] {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+
+    // The program is built on its own, out of the workspace, so the variables
+    // set by cargo for this build script must not leak into its build.
+    let status = Command::new("cargo")
+        .args(["build", "--release", "--target", BUILD_TARGET, "--locked"])
+        .current_dir(&program)
+        .env("RUSTUP_TOOLCHAIN", RUSTUP_TOOLCHAIN)
+        .env("CARGO_ENCODED_RUSTFLAGS", RUST_FLAGS.join("\x1f"))
+        .env_remove("RUSTC")
+        .env_remove("RUSTC_WRAPPER")
+        .env_remove("RUSTC_WORKSPACE_WRAPPER")
+        .env_remove("RUSTFLAGS")
+        .env_remove("CARGO_TARGET_DIR")
+        .env_remove("CARGO_BUILD_TARGET")
+        .status()
+        .expect("unable to run cargo to build the pessimistic-proof-program");
+
+    assert!(
+        status.success(),
+        "failed to build the {PACKAGE_NAME}, make sure the `{RUSTUP_TOOLCHAIN}` toolchain is \
+         installed with `sp1up`"
+    );
+
+    let elf = program
+        .join("target")
+        .join(BUILD_TARGET)
+        .join("release")
+        .join(PACKAGE_NAME);
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join(BUILD_TARGET);
+    fs::copy(&elf, &out)
+        .unwrap_or_else(|error| panic!("unable to copy the ELF {}: {error}", elf.display()));
+}
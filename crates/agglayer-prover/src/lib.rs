@@ -0,0 +1,143 @@
+//! Prover of the pessimistic proofs.
+//!
+//! The [`Prover`] runs the `pessimistic-proof-program` in the SP1 zkVM over
+//! the certificates of an epoch, each carrying the state of its network prior
+//! to the certificate, and proves the new local exit and balance roots of the
+//! networks.
+//!
+//! The ELF of the program is built and embedded by the build script, which
+//! requires the `succinct` toolchain installed with `sp1up`.
+use std::sync::Arc;
+
+use agglayer_config::ProverConfig;
+use pessimistic_proof::{
+    certificate::Certificate, generate_full_proof, FullProofOutput, ProofError,
+};
+use sp1_sdk::{
+    NetworkProver, ProverClient, SP1ProofWithPublicValues, SP1ProvingKey, SP1Stdin,
+    SP1VerificationError, SP1VerifyingKey,
+};
+use tracing::{debug, instrument};
+
+/// The ELF of the `pessimistic-proof-program`.
+pub const PESSIMISTIC_PROOF_ELF: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/riscv32im-succinct-zkvm-elf"));
+
+/// Errors related to the proving of the certificates.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The private key of the prover network account isn't set.
+    #[error("missing prover network private key: {0} is not set")]
+    MissingPrivateKey(String),
+    /// The certificates don't satisfy the pessimistic program.
+    #[error("certificates rejected by the pessimistic program: {0:?}")]
+    Rejected(ProofError),
+    /// The prover failed to produce the proof.
+    #[error("proving failed: {0}")]
+    Proving(anyhow::Error),
+    /// The proof doesn't verify against the pessimistic program.
+    #[error("invalid proof: {0}")]
+    Verification(#[from] SP1VerificationError),
+    /// The proving task panicked or was cancelled.
+    #[error("proving task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+}
+
+/// A proof of the pessimistic program, along with the new roots it commits
+/// to.
+pub struct PessimisticProof {
+    /// The SP1 proof, with the public values committed by the program.
+    pub proof: SP1ProofWithPublicValues,
+    /// The new local exit and balance roots of the networks.
+    pub output: FullProofOutput,
+}
+
+impl PessimisticProof {
+    /// Encode the SP1 proof, along with its public values.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(&self.proof).expect("SP1 proofs are serializable")
+    }
+}
+
+/// The prover of the pessimistic program.
+///
+/// Clones share the same prover client and keys.
+#[derive(Clone)]
+pub struct Prover {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client: ProverClient,
+    proving_key: SP1ProvingKey,
+    verifying_key: SP1VerifyingKey,
+}
+
+impl Prover {
+    /// Create the prover selected by the given configuration, and set up the
+    /// keys of the pessimistic program.
+    pub fn new(config: &ProverConfig) -> Result<Self, Error> {
+        let client = match config {
+            ProverConfig::Local => ProverClient::local(),
+            ProverConfig::Network { private_key_env } => {
+                let private_key = std::env::var(private_key_env)
+                    .map_err(|_| Error::MissingPrivateKey(private_key_env.clone()))?;
+
+                ProverClient {
+                    prover: Box::new(NetworkProver::new_from_key(&private_key)),
+                }
+            }
+        };
+
+        let (proving_key, verifying_key) = client.setup(PESSIMISTIC_PROOF_ELF);
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                client,
+                proving_key,
+                verifying_key,
+            }),
+        })
+    }
+
+    /// The verifying key of the pessimistic program.
+    pub fn verifying_key(&self) -> &SP1VerifyingKey {
+        &self.inner.verifying_key
+    }
+
+    /// Prove the given certificates.
+    ///
+    /// The certificates are first run through the pessimistic program
+    /// natively, so that the ones it rejects don't take up the prover.
+    #[instrument(skip_all, fields(certificates = certificates.len()))]
+    pub async fn prove(&self, certificates: Vec<Certificate>) -> Result<PessimisticProof, Error> {
+        generate_full_proof(&certificates).map_err(Error::Rejected)?;
+
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&certificates);
+
+        // Proving takes minutes of CPU, or blocks on the prover network.
+        let inner = self.inner.clone();
+        let mut proof = tokio::task::spawn_blocking(move || {
+            inner
+                .client
+                .prove(&inner.proving_key, stdin)
+                .map_err(Error::Proving)
+        })
+        .await??;
+        debug!("Proved {} certificates", certificates.len());
+
+        let output = proof.public_values.read::<FullProofOutput>();
+
+        Ok(PessimisticProof { proof, output })
+    }
+
+    /// Verify the given proof against the pessimistic program.
+    pub fn verify(&self, proof: &PessimisticProof) -> Result<(), Error> {
+        self.inner
+            .client
+            .verify(&proof.proof, &self.inner.verifying_key)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! The [`AgglayerSigner`] implementations of the supported backends.
+
+use agglayer_azure_key_vault::AzureKeyVaultSigner;
+use agglayer_gcp_kms::KmsSigner;
+use agglayer_vault::VaultSigner;
+use agglayer_web3signer::Web3Signer;
+use async_trait::async_trait;
+#[cfg(feature = "ledger")]
+use ethers::signers::Ledger;
+use ethers::{
+    abi::Address,
+    signers::{LocalWallet, Signer},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Signature,
+    },
+};
+
+use crate::{AgglayerSigner, Error};
+
+/// Implements [`AgglayerSigner`] for ethers signers, converting their errors
+/// into [`Error`].
+macro_rules! ethers_signer {
+    ($signer:ty) => {
+        #[async_trait]
+        impl AgglayerSigner for $signer {
+            async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+                &self,
+                message: S,
+            ) -> Result<Signature, Error> {
+                Ok(Signer::sign_message(self, message).await?)
+            }
+
+            async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Error> {
+                Ok(Signer::sign_transaction(self, tx).await?)
+            }
+
+            async fn sign_typed_data<T: Eip712 + Send + Sync>(
+                &self,
+                payload: &T,
+            ) -> Result<Signature, Error> {
+                Ok(Signer::sign_typed_data(self, payload).await?)
+            }
+
+            fn address(&self) -> Address {
+                Signer::address(self)
+            }
+
+            fn chain_id(&self) -> u64 {
+                Signer::chain_id(self)
+            }
+
+            fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+                Signer::with_chain_id(self, chain_id)
+            }
+        }
+    };
+}
+
+/// Implements [`AgglayerSigner`] for the signers of the agglayer backend
+/// crates, which expose the same methods as inherent ones.
+macro_rules! backend_signer {
+    ($signer:ty) => {
+        #[async_trait]
+        impl AgglayerSigner for $signer {
+            async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+                &self,
+                message: S,
+            ) -> Result<Signature, Error> {
+                Ok(<$signer>::sign_message(self, message).await?)
+            }
+
+            async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Error> {
+                Ok(<$signer>::sign_transaction(self, tx).await?)
+            }
+
+            async fn sign_typed_data<T: Eip712 + Send + Sync>(
+                &self,
+                payload: &T,
+            ) -> Result<Signature, Error> {
+                Ok(<$signer>::sign_typed_data(self, payload).await?)
+            }
+
+            fn address(&self) -> Address {
+                <$signer>::address(self)
+            }
+
+            fn chain_id(&self) -> u64 {
+                <$signer>::chain_id(self)
+            }
+
+            fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+                <$signer>::with_chain_id(self, chain_id)
+            }
+        }
+    };
+}
+
+ethers_signer!(LocalWallet);
+#[cfg(feature = "ledger")]
+ethers_signer!(Ledger);
+
+backend_signer!(KmsSigner);
+backend_signer!(VaultSigner);
+backend_signer!(AzureKeyVaultSigner);
+backend_signer!(Web3Signer);
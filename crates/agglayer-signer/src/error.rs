@@ -1,17 +1,41 @@
+use std::{io, path::PathBuf};
+
+use agglayer_azure_key_vault::Error as AzureKeyVaultError;
 use agglayer_gcp_kms::Error as GcpKmsError;
+use agglayer_vault::Error as VaultError;
+use agglayer_web3signer::Error as Web3SignerError;
+#[cfg(feature = "ledger")]
+use ethers::signers::LedgerError;
 use ethers::signers::WalletError;
 use thiserror::Error;
 
 /// Errors that can occur when using a
 /// [`ConfiguredSigner`](enum@super::ConfiguredSigner).
 ///
-/// This is simply a union of either a [`WalletError`] or a [`GcpKmsError`].
+/// This is simply a union of either a [`WalletError`], a [`GcpKmsError`], a
+/// [`VaultError`], an [`AzureKeyVaultError`], a [`Web3SignerError`] or, with
+/// the `ledger` feature, a `LedgerError`.
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("no private keys specified in the configuration")]
     NoPk,
+    #[error("keystore passphrase environment variable {0} is not set")]
+    MissingPasswordEnv(String),
+    #[error("unable to read the keystore passphrase file {path:?}: {source}")]
+    PasswordFile { path: PathBuf, source: io::Error },
     #[error("wallet error: {0}")]
     Wallet(#[from] WalletError),
     #[error("GcpKMS error: {0}")]
     GcpKms(#[from] GcpKmsError),
+    #[error("Vault error: {0}")]
+    Vault(#[from] VaultError),
+    #[error("Azure Key Vault error: {0}")]
+    AzureKeyVault(#[from] AzureKeyVaultError),
+    #[error("Web3Signer error: {0}")]
+    Web3Signer(#[from] Web3SignerError),
+    #[cfg(feature = "ledger")]
+    #[error("Ledger error: {0}")]
+    Ledger(#[from] LedgerError),
+    #[error("the Ledger signer requires the agglayer to be built with the `ledger` feature")]
+    LedgerDisabled,
 }
@@ -1,84 +1,150 @@
-//! This crate provides a [`Signer`](trait@ethers::signers::Signer)
-//! implementation that can house either a local keystore or a GCP KMS signer.
-//! (more signers can be added in the future)
+//! This crate provides the [`AgglayerSigner`] abstraction over the signing
+//! backends, implemented for a local keystore, a GCP KMS signer, a Vault
+//! transit signer, an Azure Key Vault signer, a remote Web3Signer and, with
+//! the `ledger` feature, a Ledger device. (more signers can be added in the
+//! future)
 //!
-//! See: [`ConfiguredSigner`](enum@ConfiguredSigner)
+//! The [`ConfiguredSigner`](enum@ConfiguredSigner) houses the backend selected
+//! by the configuration, [`EthersSigner`] plugs any [`AgglayerSigner`] into
+//! the ethers middlewares, and [`AlloyWallet`] into the alloy providers.
 
 use std::sync::Arc;
 
-use agglayer_config::{AuthConfig, Config, LocalConfig};
+use agglayer_azure_key_vault::AzureKeyVaultSigner;
+use agglayer_config::{AuthConfig, Config, LocalConfig, PrivateKey};
 use agglayer_gcp_kms::{KmsSigner, KMS};
+use agglayer_vault::VaultSigner;
+use agglayer_web3signer::Web3Signer;
 use async_trait::async_trait;
+#[cfg(feature = "ledger")]
+use ethers::signers::{HDPath, Ledger};
 use ethers::{
     abi::Address,
-    signers::{LocalWallet, Signer},
+    signers::LocalWallet,
     types::{
         transaction::{eip2718::TypedTransaction, eip712::Eip712},
         Signature,
     },
 };
 
+mod adapters;
 mod error;
+mod signer;
+mod wallet;
 
 pub use error::Error;
+pub use signer::{AgglayerSigner, EthersSigner};
+pub use wallet::AlloyWallet;
 
-/// A an ethers [`Signer`] that can house either a local keystore or a KMS
-/// signer.
+/// An [`AgglayerSigner`] that can house either a local keystore, a KMS
+/// signer, a Vault transit signer, an Azure Key Vault signer, a remote
+/// Web3Signer or a Ledger device.
 ///
-/// An ethers [`Provider`][ethers::prelude::Provider] using a
-/// [`SignerMiddleware`][ethers::prelude::SignerMiddleware] must have its
-/// [`Signer`] type specified at compile time, and the Signer type is not object
-/// safe, so we cannot use a `Box<dyn Signer>`. As such, we define this enum to
-/// accommodate a runtime configured signer.
+/// The callers of an [`AgglayerSigner`] must have its type specified at
+/// compile time, and the trait is not object safe, so we cannot use a
+/// `Box<dyn AgglayerSigner>`. As such, we define this enum to accommodate a
+/// runtime configured signer.
 #[derive(Debug)]
 pub enum ConfiguredSigner {
     Local(LocalWallet),
     Kms(KmsSigner),
+    Vault(VaultSigner),
+    AzureKeyVault(AzureKeyVaultSigner),
+    Web3Signer(Web3Signer),
+    #[cfg(feature = "ledger")]
+    Ledger(Ledger),
 }
 
 impl ConfiguredSigner {
     /// Decrypt the first local keystore specified in the configuration.
     pub(crate) fn local_wallet(chain_id: u64, local: &LocalConfig) -> Result<LocalWallet, Error> {
         let pk = local.private_keys.first().ok_or(Error::NoPk)?;
-        Ok(LocalWallet::decrypt_keystore(&pk.path, &pk.password)?.with_chain_id(chain_id))
+        let password = Self::keystore_password(pk)?;
+        Ok(LocalWallet::decrypt_keystore(&pk.path, password)?.with_chain_id(chain_id))
     }
 
-    /// Get either a local wallet or GCP KMS signer based on the configuration.
+    /// Get the passphrase of the keystore, from the environment, a file, or
+    /// the configuration itself, in this order of precedence.
+    fn keystore_password(pk: &PrivateKey) -> Result<String, Error> {
+        if let Some(var) = &pk.password_env {
+            return std::env::var(var).map_err(|_| Error::MissingPasswordEnv(var.clone()));
+        }
+
+        if let Some(path) = &pk.password_file {
+            let password = std::fs::read_to_string(path).map_err(|source| Error::PasswordFile {
+                path: path.clone(),
+                source,
+            })?;
+            return Ok(password.trim_end_matches(['\r', '\n']).to_string());
+        }
+
+        Ok(pk.password.clone())
+    }
+
+    /// Get either a local wallet, a GCP KMS signer, a Vault transit signer, an
+    /// Azure Key Vault signer, a remote Web3Signer or a Ledger device based on
+    /// the configuration.
     pub async fn new(config: Arc<Config>) -> Result<Self, Error> {
-        match &config.auth {
+        Self::from_auth(config.l1.chain_id, &config.auth).await
+    }
+
+    /// Get the signer of the given auth configuration, for the given chain.
+    pub async fn from_auth(chain_id: u64, auth: &AuthConfig) -> Result<Self, Error> {
+        match auth {
             AuthConfig::GcpKms(ref kms) => {
-                let kms = KMS::new(config.l1.chain_id, kms.clone());
+                let kms = KMS::new(chain_id, kms.clone());
                 Ok(Self::Kms(kms.gcp_kms_signer().await?))
             }
-            AuthConfig::Local(ref local) => {
-                Ok(Self::Local(Self::local_wallet(config.l1.chain_id, local)?))
+            AuthConfig::Vault(ref vault) => {
+                Ok(Self::Vault(VaultSigner::new(chain_id, vault).await?))
             }
+            AuthConfig::AzureKeyVault(ref azure) => Ok(Self::AzureKeyVault(
+                AzureKeyVaultSigner::new(chain_id, azure).await?,
+            )),
+            AuthConfig::Web3Signer(ref web3signer) => Ok(Self::Web3Signer(
+                Web3Signer::new(chain_id, web3signer).await?,
+            )),
+            #[cfg(feature = "ledger")]
+            AuthConfig::Ledger(ref ledger) => Ok(Self::Ledger(
+                Ledger::new(HDPath::Other(ledger.derivation_path.clone()), chain_id).await?,
+            )),
+            #[cfg(not(feature = "ledger"))]
+            AuthConfig::Ledger(_) => Err(Error::LedgerDisabled),
+            AuthConfig::Local(ref local) => Ok(Self::Local(Self::local_wallet(chain_id, local)?)),
         }
     }
 }
 
-/// [`Signer`] implementation for [`ConfiguredSigner`].
+/// [`AgglayerSigner`] implementation for [`ConfiguredSigner`].
 ///
 /// This implementation simply delegates to the underlying signer.
 #[async_trait]
-impl Signer for ConfiguredSigner {
-    type Error = Error;
-
+impl AgglayerSigner for ConfiguredSigner {
     async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
         &self,
         message: S,
-    ) -> Result<Signature, Self::Error> {
+    ) -> Result<Signature, Error> {
         Ok(match self {
             ConfiguredSigner::Local(wallet) => wallet.sign_message(message).await?,
             ConfiguredSigner::Kms(signer) => signer.sign_message(message).await?,
+            ConfiguredSigner::Vault(signer) => signer.sign_message(message).await?,
+            ConfiguredSigner::AzureKeyVault(signer) => signer.sign_message(message).await?,
+            ConfiguredSigner::Web3Signer(signer) => signer.sign_message(message).await?,
+            #[cfg(feature = "ledger")]
+            ConfiguredSigner::Ledger(signer) => signer.sign_message(message).await?,
         })
     }
 
     /// Signs the transaction
-    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Self::Error> {
+    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Error> {
         Ok(match self {
             ConfiguredSigner::Local(wallet) => wallet.sign_transaction(message).await?,
             ConfiguredSigner::Kms(signer) => signer.sign_transaction(message).await?,
+            ConfiguredSigner::Vault(signer) => signer.sign_transaction(message).await?,
+            ConfiguredSigner::AzureKeyVault(signer) => signer.sign_transaction(message).await?,
+            ConfiguredSigner::Web3Signer(signer) => signer.sign_transaction(message).await?,
+            #[cfg(feature = "ledger")]
+            ConfiguredSigner::Ledger(signer) => signer.sign_transaction(message).await?,
         })
     }
 
@@ -87,10 +153,15 @@ impl Signer for ConfiguredSigner {
     async fn sign_typed_data<T: Eip712 + Send + Sync>(
         &self,
         payload: &T,
-    ) -> Result<Signature, Self::Error> {
+    ) -> Result<Signature, Error> {
         Ok(match self {
             ConfiguredSigner::Local(wallet) => wallet.sign_typed_data(payload).await?,
             ConfiguredSigner::Kms(signer) => signer.sign_typed_data(payload).await?,
+            ConfiguredSigner::Vault(signer) => signer.sign_typed_data(payload).await?,
+            ConfiguredSigner::AzureKeyVault(signer) => signer.sign_typed_data(payload).await?,
+            ConfiguredSigner::Web3Signer(signer) => signer.sign_typed_data(payload).await?,
+            #[cfg(feature = "ledger")]
+            ConfiguredSigner::Ledger(signer) => signer.sign_typed_data(payload).await?,
         })
     }
 
@@ -99,6 +170,11 @@ impl Signer for ConfiguredSigner {
         match self {
             ConfiguredSigner::Local(wallet) => wallet.address(),
             ConfiguredSigner::Kms(signer) => signer.address(),
+            ConfiguredSigner::Vault(signer) => signer.address(),
+            ConfiguredSigner::AzureKeyVault(signer) => signer.address(),
+            ConfiguredSigner::Web3Signer(signer) => signer.address(),
+            #[cfg(feature = "ledger")]
+            ConfiguredSigner::Ledger(signer) => signer.address(),
         }
     }
 
@@ -107,6 +183,11 @@ impl Signer for ConfiguredSigner {
         match self {
             ConfiguredSigner::Local(wallet) => wallet.chain_id(),
             ConfiguredSigner::Kms(signer) => signer.chain_id(),
+            ConfiguredSigner::Vault(signer) => signer.chain_id(),
+            ConfiguredSigner::AzureKeyVault(signer) => signer.chain_id(),
+            ConfiguredSigner::Web3Signer(signer) => signer.chain_id(),
+            #[cfg(feature = "ledger")]
+            ConfiguredSigner::Ledger(signer) => signer.chain_id(),
         }
     }
 
@@ -118,6 +199,75 @@ impl Signer for ConfiguredSigner {
                 ConfiguredSigner::Local(wallet.with_chain_id(chain_id))
             }
             ConfiguredSigner::Kms(signer) => ConfiguredSigner::Kms(signer.with_chain_id(chain_id)),
+            ConfiguredSigner::Vault(signer) => {
+                ConfiguredSigner::Vault(signer.with_chain_id(chain_id))
+            }
+            ConfiguredSigner::AzureKeyVault(signer) => {
+                ConfiguredSigner::AzureKeyVault(signer.with_chain_id(chain_id))
+            }
+            ConfiguredSigner::Web3Signer(signer) => {
+                ConfiguredSigner::Web3Signer(signer.with_chain_id(chain_id))
+            }
+            #[cfg(feature = "ledger")]
+            ConfiguredSigner::Ledger(signer) => {
+                ConfiguredSigner::Ledger(signer.with_chain_id(chain_id))
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keystore_password_sources_precedence() {
+        let file = std::env::temp_dir().join(format!("agglayer-keystore-{}", std::process::id()));
+        std::fs::write(&file, "from-file\n").unwrap();
+        std::env::set_var("AGGLAYER_TEST_KEYSTORE_PASSWORD", "from-env");
+
+        let mut pk = PrivateKey {
+            path: "/pk/agglayer.keystore".into(),
+            password: "inline".to_string(),
+            password_env: None,
+            password_file: None,
+        };
+        assert_eq!(ConfiguredSigner::keystore_password(&pk).unwrap(), "inline");
+
+        pk.password_file = Some(file.clone());
+        assert_eq!(
+            ConfiguredSigner::keystore_password(&pk).unwrap(),
+            "from-file"
+        );
+
+        pk.password_env = Some("AGGLAYER_TEST_KEYSTORE_PASSWORD".to_string());
+        assert_eq!(
+            ConfiguredSigner::keystore_password(&pk).unwrap(),
+            "from-env"
+        );
+
+        pk.password_env = Some("AGGLAYER_TEST_KEYSTORE_PASSWORD_UNSET".to_string());
+        assert!(matches!(
+            ConfiguredSigner::keystore_password(&pk),
+            Err(Error::MissingPasswordEnv(_))
+        ));
+
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ethers_signer_delegates_to_the_agglayer_signer() {
+        use ethers::signers::Signer as _;
+
+        let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+        let address = AgglayerSigner::address(&wallet);
+        let signer = EthersSigner::new(ConfiguredSigner::Local(wallet)).with_chain_id(5u64);
+
+        assert_eq!(signer.address(), address);
+        assert_eq!(signer.chain_id(), 5);
+        assert_eq!(signer.inner().chain_id(), 5);
+
+        let signature = signer.sign_message("agglayer").await.unwrap();
+        assert_eq!(signature.recover("agglayer").unwrap(), address);
+    }
+}
@@ -0,0 +1,110 @@
+//! The [`AgglayerSigner`] trait abstracts the signing backends away from the
+//! rest of the agglayer, and [`EthersSigner`] plugs any of them into the
+//! ethers middlewares.
+
+use async_trait::async_trait;
+use ethers::{
+    abi::Address,
+    signers::Signer,
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Signature,
+    },
+};
+
+use crate::Error;
+
+/// A signer of the agglayer, signing the settlement transactions and the
+/// messages of the agglayer with a key of one of the supported backends.
+///
+/// The errors of every backend are unified into [`Error`], so that the
+/// callers are generic over the backend.
+#[async_trait]
+pub trait AgglayerSigner: std::fmt::Debug + Send + Sync {
+    /// Signs a message, prefixed as per EIP-191.
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Error>;
+
+    /// Signs a transaction, with `v` normalized as per EIP-155.
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Error>;
+
+    /// Signs typed data, encoded as per EIP-712.
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Error>;
+
+    /// Returns the address of the key.
+    fn address(&self) -> Address;
+
+    /// Returns the chain ID associated with the signer.
+    fn chain_id(&self) -> u64;
+
+    /// Sets a new chain ID for the signer.
+    #[must_use]
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self;
+}
+
+/// An ethers [`Signer`] signing with an [`AgglayerSigner`], e.g. for a
+/// [`SignerMiddleware`][ethers::prelude::SignerMiddleware] to sign the
+/// settlement transactions.
+#[derive(Debug)]
+pub struct EthersSigner<S>(S);
+
+impl<S: AgglayerSigner> EthersSigner<S> {
+    pub fn new(signer: S) -> Self {
+        Self(signer)
+    }
+
+    /// Returns the underlying signer.
+    pub fn inner(&self) -> &S {
+        &self.0
+    }
+}
+
+/// [`Signer`] implementation for [`EthersSigner`].
+///
+/// This implementation simply delegates to the underlying signer.
+#[async_trait]
+impl<S: AgglayerSigner> Signer for EthersSigner<S> {
+    type Error = Error;
+
+    async fn sign_message<M: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: M,
+    ) -> Result<Signature, Self::Error> {
+        self.0.sign_message(message).await
+    }
+
+    /// Signs the transaction
+    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Self::Error> {
+        self.0.sign_transaction(message).await
+    }
+
+    /// Encodes and signs the typed data according EIP-712.
+    /// Payload must implement Eip712 trait.
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        self.0.sign_typed_data(payload).await
+    }
+
+    /// Returns the signer's Ethereum Address
+    fn address(&self) -> Address {
+        self.0.address()
+    }
+
+    /// Returns the signer's chain id
+    fn chain_id(&self) -> u64 {
+        self.0.chain_id()
+    }
+
+    /// Sets the signer's chain id
+    #[must_use]
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        Self(self.0.with_chain_id(chain_id))
+    }
+}
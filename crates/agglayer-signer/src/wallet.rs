@@ -0,0 +1,251 @@
+//! [`AlloyWallet`] plugs any [`AgglayerSigner`] into the alloy providers, so
+//! that the backends which only exist for ethers, e.g. the GCP KMS signer,
+//! sign the alloy transactions through their [`AgglayerSigner`] adapter.
+
+use alloy_consensus::{TxEip1559, TxEip2930, TxEnvelope, TxLegacy, Typed2718, TypedTransaction};
+use alloy_network::{Ethereum, NetworkWallet};
+use alloy_primitives::{Address, Signature, TxKind, U256};
+use ethers::types::{
+    transaction::{eip2718, eip2930},
+    Eip1559TransactionRequest, NameOrAddress, TransactionRequest, H160, U256 as EthersU256,
+};
+
+use crate::AgglayerSigner;
+
+/// An alloy [`NetworkWallet`] signing with an [`AgglayerSigner`], e.g. for a
+/// provider built with [`ProviderBuilder::wallet`] to sign the settlement
+/// transactions.
+///
+/// The transactions are converted to their ethers counterpart before being
+/// signed by the underlying signer. Only the legacy, EIP-2930 and EIP-1559
+/// transactions are supported.
+///
+/// [`ProviderBuilder::wallet`]: https://docs.rs/alloy-provider/latest/alloy_provider/struct.ProviderBuilder.html#method.wallet
+#[derive(Debug)]
+pub struct AlloyWallet<S>(S);
+
+impl<S: AgglayerSigner> AlloyWallet<S> {
+    pub fn new(signer: S) -> Self {
+        Self(signer)
+    }
+
+    /// Returns the underlying signer.
+    pub fn inner(&self) -> &S {
+        &self.0
+    }
+
+    /// Signs the transaction with the underlying signer, after converting it
+    /// to its ethers counterpart.
+    async fn sign(
+        &self,
+        sender: Address,
+        tx: &TypedTransaction,
+    ) -> alloy_signer::Result<Signature> {
+        let tx = match tx {
+            TypedTransaction::Legacy(tx) => eip2718::TypedTransaction::Legacy(legacy(sender, tx)),
+            TypedTransaction::Eip2930(tx) => {
+                eip2718::TypedTransaction::Eip2930(eip2930(sender, tx))
+            }
+            TypedTransaction::Eip1559(tx) => {
+                eip2718::TypedTransaction::Eip1559(eip1559(sender, tx))
+            }
+            tx => {
+                return Err(alloy_signer::Error::other(format!(
+                    "unsupported transaction type {}",
+                    tx.ty()
+                )))
+            }
+        };
+
+        let signature = self
+            .0
+            .sign_transaction(&tx)
+            .await
+            .map_err(alloy_signer::Error::other)?;
+
+        Ok(Signature::new(
+            u256(signature.r),
+            u256(signature.s),
+            y_parity(signature.v)?,
+        ))
+    }
+}
+
+impl<S: AgglayerSigner> NetworkWallet<Ethereum> for AlloyWallet<S> {
+    fn default_signer_address(&self) -> Address {
+        Address::from(self.0.address().0)
+    }
+
+    fn has_signer_for(&self, address: &Address) -> bool {
+        *address == self.default_signer_address()
+    }
+
+    fn signer_addresses(&self) -> impl Iterator<Item = Address> {
+        std::iter::once(self.default_signer_address())
+    }
+
+    async fn sign_transaction_from(
+        &self,
+        sender: Address,
+        tx: TypedTransaction,
+    ) -> alloy_signer::Result<TxEnvelope> {
+        if !self.has_signer_for(&sender) {
+            return Err(alloy_signer::Error::other(format!(
+                "missing signer for address {sender}"
+            )));
+        }
+
+        let signature = self.sign(sender, &tx).await?;
+
+        Ok(tx.into_envelope(signature))
+    }
+}
+
+fn legacy(sender: Address, tx: &TxLegacy) -> TransactionRequest {
+    TransactionRequest {
+        from: Some(H160(sender.into_array())),
+        to: to(tx.to),
+        gas: Some(tx.gas_limit.into()),
+        gas_price: Some(tx.gas_price.into()),
+        value: Some(ethers_u256(tx.value)),
+        data: Some(tx.input.to_vec().into()),
+        nonce: Some(tx.nonce.into()),
+        chain_id: tx.chain_id.map(Into::into),
+    }
+}
+
+fn eip2930(sender: Address, tx: &TxEip2930) -> eip2930::Eip2930TransactionRequest {
+    let request = TransactionRequest {
+        from: Some(H160(sender.into_array())),
+        to: to(tx.to),
+        gas: Some(tx.gas_limit.into()),
+        gas_price: Some(tx.gas_price.into()),
+        value: Some(ethers_u256(tx.value)),
+        data: Some(tx.input.to_vec().into()),
+        nonce: Some(tx.nonce.into()),
+        chain_id: Some(tx.chain_id.into()),
+    };
+
+    eip2930::Eip2930TransactionRequest::new(request, access_list(&tx.access_list))
+}
+
+fn eip1559(sender: Address, tx: &TxEip1559) -> Eip1559TransactionRequest {
+    Eip1559TransactionRequest {
+        from: Some(H160(sender.into_array())),
+        to: to(tx.to),
+        gas: Some(tx.gas_limit.into()),
+        value: Some(ethers_u256(tx.value)),
+        data: Some(tx.input.to_vec().into()),
+        nonce: Some(tx.nonce.into()),
+        access_list: access_list(&tx.access_list),
+        max_priority_fee_per_gas: Some(tx.max_priority_fee_per_gas.into()),
+        max_fee_per_gas: Some(tx.max_fee_per_gas.into()),
+        chain_id: Some(tx.chain_id.into()),
+    }
+}
+
+fn to(kind: TxKind) -> Option<NameOrAddress> {
+    kind.to()
+        .map(|address| NameOrAddress::Address(H160(address.into_array())))
+}
+
+fn access_list(access_list: &alloy_eips::eip2930::AccessList) -> eip2930::AccessList {
+    eip2930::AccessList(
+        access_list
+            .iter()
+            .map(|item| eip2930::AccessListItem {
+                address: H160(item.address.into_array()),
+                storage_keys: item.storage_keys.iter().map(|key| key.0.into()).collect(),
+            })
+            .collect(),
+    )
+}
+
+fn ethers_u256(value: U256) -> EthersU256 {
+    EthersU256::from_big_endian(&value.to_be_bytes::<32>())
+}
+
+fn u256(value: EthersU256) -> U256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    U256::from_be_bytes(bytes)
+}
+
+/// Recovers the parity of `y` from a `v` that is either raw, as per the
+/// typed transactions, offset by 27, or normalized as per EIP-155.
+fn y_parity(v: u64) -> alloy_signer::Result<bool> {
+    match v {
+        0 | 1 => Ok(v == 1),
+        27 | 28 => Ok(v == 28),
+        v => v
+            .checked_sub(35)
+            .map(|v| v % 2 == 1)
+            .ok_or_else(|| alloy_signer::Error::other(format!("invalid signature v {v}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_consensus::transaction::SignerRecoverable as _;
+    use alloy_primitives::{address, Bytes};
+    use ethers::signers::LocalWallet;
+
+    use super::*;
+    use crate::ConfiguredSigner;
+
+    #[test]
+    fn y_parity_of_every_v_encoding() {
+        assert!(!y_parity(0).unwrap());
+        assert!(y_parity(1).unwrap());
+        assert!(!y_parity(27).unwrap());
+        assert!(y_parity(28).unwrap());
+        assert!(!y_parity(5 * 2 + 35).unwrap());
+        assert!(y_parity(5 * 2 + 36).unwrap());
+
+        for v in (2..=26).chain(29..=34) {
+            assert!(y_parity(v).is_err(), "v = {v}");
+        }
+    }
+
+    #[tokio::test]
+    async fn alloy_wallet_signs_with_the_agglayer_signer() {
+        let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+        let wallet = AlloyWallet::new(ConfiguredSigner::Local(wallet).with_chain_id(5u64));
+        let sender = wallet.default_signer_address();
+
+        let eip1559 = TxEip1559 {
+            chain_id: 5,
+            nonce: 7,
+            gas_limit: 21_000,
+            max_fee_per_gas: 30_000_000_000,
+            max_priority_fee_per_gas: 1_000_000_000,
+            to: TxKind::Call(address!("00000000000000000000000000000000000000aa")),
+            value: U256::from(1_000),
+            input: Bytes::from_static(b"agglayer"),
+            ..Default::default()
+        };
+        let legacy = TxLegacy {
+            chain_id: Some(5),
+            nonce: 8,
+            gas_price: 30_000_000_000,
+            gas_limit: 21_000,
+            to: TxKind::Create,
+            value: U256::ZERO,
+            input: Bytes::from_static(b"agglayer"),
+        };
+
+        for tx in [
+            TypedTransaction::Eip1559(eip1559),
+            TypedTransaction::Legacy(legacy),
+        ] {
+            let envelope = wallet.sign_transaction_from(sender, tx).await.unwrap();
+            assert_eq!(envelope.recover_signer().unwrap(), sender);
+        }
+
+        let other = address!("00000000000000000000000000000000000000bb");
+        assert!(wallet
+            .sign_transaction_from(other, TypedTransaction::Legacy(TxLegacy::default()))
+            .await
+            .is_err());
+    }
+}
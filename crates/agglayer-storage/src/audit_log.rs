@@ -0,0 +1,240 @@
+//! The [`AuditLog`] persists a tamper-evident record of the events of the
+//! agglayer, by sequence number.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ethers::{types::H256, utils::keccak256};
+use rocksdb::{Direction, IteratorMode, Options, DB};
+
+use crate::{synced, Error};
+
+/// An entry of the audit log.
+///
+/// Every entry is chained to the previous one by including its hash in its
+/// own, so that altering, inserting or removing an entry breaks the chain of
+/// every entry following it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// The sequence number of the entry, starting from 0.
+    pub sequence: u64,
+    /// The UNIX timestamp of the entry, in seconds.
+    pub timestamp: u64,
+    /// The hash of the previous entry, zero for the first one.
+    pub prev_hash: H256,
+    /// The hash of the entry, chaining it to the previous one.
+    pub hash: H256,
+    /// The recorded event.
+    pub payload: Vec<u8>,
+}
+
+impl AuditEntry {
+    /// The length of the encoded entry, without its payload.
+    const HEADER_LEN: usize = 8 + 2 * 32;
+
+    /// Compute the hash of an entry, over its sequence number, timestamp,
+    /// previous hash and payload.
+    fn compute_hash(sequence: u64, timestamp: u64, prev_hash: H256, payload: &[u8]) -> H256 {
+        H256(keccak256(
+            [
+                &sequence.to_be_bytes()[..],
+                &timestamp.to_be_bytes(),
+                prev_hash.as_bytes(),
+                payload,
+            ]
+            .concat(),
+        ))
+    }
+
+    /// Whether the hash of the entry matches its content.
+    pub fn is_consistent(&self) -> bool {
+        self.hash
+            == Self::compute_hash(self.sequence, self.timestamp, self.prev_hash, &self.payload)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        [
+            &self.timestamp.to_be_bytes()[..],
+            self.prev_hash.as_bytes(),
+            self.hash.as_bytes(),
+            &self.payload,
+        ]
+        .concat()
+    }
+
+    fn decode(key: &[u8], value: &[u8]) -> Result<Self, Error> {
+        let sequence = u64::from_be_bytes(
+            key.try_into()
+                .map_err(|_| Error::InvalidAuditEntry(key.len()))?,
+        );
+        if value.len() < Self::HEADER_LEN {
+            return Err(Error::InvalidAuditEntry(value.len()));
+        }
+        let (timestamp, rest) = value.split_at(8);
+
+        Ok(Self {
+            sequence,
+            timestamp: u64::from_be_bytes(timestamp.try_into().expect("8 bytes timestamp")),
+            prev_hash: H256::from_slice(&rest[..32]),
+            hash: H256::from_slice(&rest[32..64]),
+            payload: rest[64..].to_vec(),
+        })
+    }
+}
+
+/// The sequence number and hash of the next entry's predecessor.
+#[derive(Debug)]
+struct Head {
+    next_sequence: u64,
+    hash: H256,
+}
+
+/// An append-only log of hash-chained entries, backed by a RocksDB database.
+///
+/// Every write is synced to disk before returning, so that an event recorded
+/// in the log survives a crash of the node.
+pub struct AuditLog {
+    db: DB,
+    head: Mutex<Head>,
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Open the log stored in the given directory, creating it if missing.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        let db = DB::open(&options, path.as_ref())?;
+
+        let head = match db.iterator(IteratorMode::End).next() {
+            Some(entry) => {
+                let (key, value) = entry?;
+                let last = AuditEntry::decode(&key, &value)?;
+
+                Head {
+                    next_sequence: last.sequence + 1,
+                    hash: last.hash,
+                }
+            }
+            None => Head {
+                next_sequence: 0,
+                hash: H256::zero(),
+            },
+        };
+
+        Ok(Self {
+            db,
+            head: Mutex::new(head),
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Append the given event to the log, chained to the last entry.
+    pub fn append(&self, payload: &[u8]) -> Result<AuditEntry, Error> {
+        let mut head = self.head.lock().expect("Audit log lock poisoned");
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let entry = AuditEntry {
+            sequence: head.next_sequence,
+            timestamp,
+            prev_hash: head.hash,
+            hash: AuditEntry::compute_hash(head.next_sequence, timestamp, head.hash, payload),
+            payload: payload.to_vec(),
+        };
+
+        self.db
+            .put_opt(entry.sequence.to_be_bytes(), entry.encode(), &synced())?;
+        head.next_sequence += 1;
+        head.hash = entry.hash;
+
+        Ok(entry)
+    }
+
+    /// Get the entries whose sequence number is within the given inclusive
+    /// range, checking that they're chained together.
+    pub fn range(&self, from: u64, to: u64) -> Result<Vec<AuditEntry>, Error> {
+        let start = from.to_be_bytes();
+        let mut entries: Vec<AuditEntry> = Vec::new();
+
+        for entry in self
+            .db
+            .iterator(IteratorMode::From(&start, Direction::Forward))
+        {
+            let (key, value) = entry?;
+            let entry = AuditEntry::decode(&key, &value)?;
+            if entry.sequence > to {
+                break;
+            }
+
+            let chained = match entries.last() {
+                Some(prev) => prev.sequence + 1 == entry.sequence && prev.hash == entry.prev_hash,
+                None => entry.sequence == from && (from > 0 || entry.prev_hash.is_zero()),
+            };
+            if !chained || !entry.is_consistent() {
+                return Err(Error::BrokenAuditChain(entry.sequence));
+            }
+
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::{AuditEntry, AuditLog};
+    use crate::{synced, Error};
+
+    #[test]
+    fn audit_entries_are_chained_across_restarts() {
+        let path = std::env::temp_dir().join(format!("agglayer-audit-{:x}", H256::random()));
+
+        {
+            let log = AuditLog::open(&path).unwrap();
+            log.append(b"received").unwrap();
+            log.append(b"verified").unwrap();
+        }
+
+        let log = AuditLog::open(&path).unwrap();
+        let settled = log.append(b"settled").unwrap();
+        let entries = log.range(0, 10).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].prev_hash, H256::zero());
+        assert_eq!(entries[2], settled);
+        assert_eq!(settled.prev_hash, entries[1].hash);
+        assert_eq!(log.range(1, 1).unwrap(), vec![entries[1].clone()]);
+
+        // Tamper with the payload of the second entry.
+        let tampered = AuditEntry {
+            payload: b"rejected".to_vec(),
+            ..entries[1].clone()
+        };
+        log.db
+            .put_opt(1u64.to_be_bytes(), tampered.encode(), &synced())
+            .unwrap();
+
+        assert!(matches!(log.range(0, 10), Err(Error::BrokenAuditChain(1))));
+
+        drop(log);
+        _ = std::fs::remove_dir_all(path);
+    }
+}
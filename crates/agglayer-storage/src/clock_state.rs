@@ -0,0 +1,132 @@
+//! The [`ClockState`] persists the genesis and the last epoch reached by the
+//! clock of the agglayer.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use rocksdb::{Options, DB};
+
+use crate::{synced, Error};
+
+/// The key of the last epoch reached.
+const LAST_EPOCH_KEY: &[u8] = b"last-epoch";
+
+/// The key of the genesis of the clock.
+const GENESIS_KEY: &[u8] = b"genesis";
+
+/// The state of the clock of the agglayer, backed by a RocksDB database, so
+/// that its epoch numbering never moves backwards across restarts.
+///
+/// Every write is synced to disk before returning, so that an epoch reached
+/// is never reached again, even after a crash of the node.
+pub struct ClockState {
+    db: DB,
+    path: PathBuf,
+}
+
+impl ClockState {
+    /// Open the state stored in the given directory, creating it if missing.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+
+        Ok(Self {
+            db: DB::open(&options, path.as_ref())?,
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Record the epoch reached by the clock, unless a later epoch was
+    /// reached already.
+    pub fn record_epoch(&self, epoch: u64) -> Result<(), Error> {
+        if self.last_epoch()?.is_some_and(|last| last >= epoch) {
+            return Ok(());
+        }
+
+        Ok(self
+            .db
+            .put_opt(LAST_EPOCH_KEY, epoch.to_be_bytes(), &synced())?)
+    }
+
+    /// Get the last epoch reached by the clock, if any.
+    pub fn last_epoch(&self) -> Result<Option<u64>, Error> {
+        self.db
+            .get(LAST_EPOCH_KEY)?
+            .map(|value| {
+                let epoch = <[u8; 8]>::try_from(value.as_slice())
+                    .map_err(|_| Error::InvalidEpoch(value.len()))?;
+
+                Ok(u64::from_be_bytes(epoch))
+            })
+            .transpose()
+    }
+
+    /// Record the genesis of the clock, as a unix timestamp in seconds, unless
+    /// a genesis was recorded already.
+    pub fn record_genesis(&self, genesis: i64) -> Result<(), Error> {
+        if self.genesis()?.is_some() {
+            return Ok(());
+        }
+
+        Ok(self
+            .db
+            .put_opt(GENESIS_KEY, genesis.to_be_bytes(), &synced())?)
+    }
+
+    /// Get the genesis of the clock, as a unix timestamp in seconds, if any.
+    pub fn genesis(&self) -> Result<Option<i64>, Error> {
+        self.db
+            .get(GENESIS_KEY)?
+            .map(|value| {
+                let genesis = <[u8; 8]>::try_from(value.as_slice())
+                    .map_err(|_| Error::InvalidGenesis(value.len()))?;
+
+                Ok(i64::from_be_bytes(genesis))
+            })
+            .transpose()
+    }
+}
+
+impl fmt::Debug for ClockState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClockState")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::ClockState;
+
+    #[test]
+    fn clock_state_survives_a_restart_and_only_moves_forward() {
+        let path = std::env::temp_dir().join(format!("agglayer-clock-{:x}", H256::random()));
+
+        {
+            let state = ClockState::open(&path).unwrap();
+            assert_eq!(state.last_epoch().unwrap(), None);
+            assert_eq!(state.genesis().unwrap(), None);
+
+            state.record_epoch(7).unwrap();
+            state.record_epoch(5).unwrap();
+            state.record_genesis(1_700_000_000).unwrap();
+        }
+
+        let state = ClockState::open(&path).unwrap();
+        assert_eq!(state.last_epoch().unwrap(), Some(7));
+
+        state.record_genesis(1_800_000_000).unwrap();
+        assert_eq!(state.genesis().unwrap(), Some(1_700_000_000));
+
+        state.record_epoch(8).unwrap();
+        assert_eq!(state.last_epoch().unwrap(), Some(8));
+
+        drop(state);
+        _ = std::fs::remove_dir_all(path);
+    }
+}
@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+/// Errors that can occur when reading or writing the storage.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("rocksdb error: {0}")]
+    RocksDb(#[from] rocksdb::Error),
+    #[error("invalid key of {0} bytes, expected a 32 bytes hash")]
+    InvalidKey(usize),
+    #[error("invalid value of {0} bytes, expected a 32 bytes hash")]
+    InvalidValue(usize),
+    #[error("invalid settled batch of {0} bytes, expected 104 bytes")]
+    InvalidSettledBatch(usize),
+    #[error("invalid epoch of {0} bytes, expected 8 bytes")]
+    InvalidEpoch(usize),
+    #[error("invalid genesis of {0} bytes, expected 8 bytes")]
+    InvalidGenesis(usize),
+    #[error("invalid audit entry of {0} bytes")]
+    InvalidAuditEntry(usize),
+    #[error("audit log chain broken at entry {0}")]
+    BrokenAuditChain(u64),
+}
@@ -0,0 +1,36 @@
+//! Persistent storage of the agglayer.
+//!
+//! The proofs accepted by the agglayer are kept on disk until their settlement
+//! completes, so that the proofs of a node crashing between their
+//! verification and their settlement are settled once it restarts. The
+//! settled proofs are kept on disk as well, so that they're never settled
+//! again.
+//!
+//! The events of the submissions are recorded in a tamper-evident audit log,
+//! and the last epoch reached by the clock is kept so that the epochs never
+//! move backwards across restarts.
+//!
+//! See: [`PendingSettlementQueue`], [`SettledProofIndex`], [`AuditLog`],
+//! [`ClockState`]
+
+use rocksdb::WriteOptions;
+
+mod audit_log;
+mod clock_state;
+mod error;
+mod pending_settlement;
+mod settled_proofs;
+
+pub use audit_log::{AuditEntry, AuditLog};
+pub use clock_state::ClockState;
+pub use error::Error;
+pub use pending_settlement::PendingSettlementQueue;
+pub use settled_proofs::{SettledBatch, SettledProofIndex};
+
+/// Write options syncing the write-ahead log before returning.
+fn synced() -> WriteOptions {
+    let mut options = WriteOptions::default();
+    options.set_sync(true);
+
+    options
+}
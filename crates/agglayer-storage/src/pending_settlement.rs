@@ -0,0 +1,96 @@
+//! The [`PendingSettlementQueue`] persists the proofs being settled, by proof
+//! hash.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use ethers::types::H256;
+use rocksdb::{IteratorMode, Options, DB};
+
+use crate::{synced, Error};
+
+/// The proofs verified by the agglayer whose settlement didn't complete yet,
+/// backed by a RocksDB database.
+///
+/// Every write is synced to disk before returning, so that a proof pushed to
+/// the queue survives a crash of the node.
+pub struct PendingSettlementQueue {
+    db: DB,
+    path: PathBuf,
+}
+
+impl PendingSettlementQueue {
+    /// Open the queue stored in the given directory, creating it if missing.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+
+        Ok(Self {
+            db: DB::open(&options, path.as_ref())?,
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Persist the payload of the proof about to be settled.
+    pub fn push(&self, hash: H256, payload: &[u8]) -> Result<(), Error> {
+        Ok(self.db.put_opt(hash, payload, &synced())?)
+    }
+
+    /// Remove the proof whose settlement completed.
+    pub fn remove(&self, hash: H256) -> Result<(), Error> {
+        Ok(self.db.delete_opt(hash, &synced())?)
+    }
+
+    /// Get the payloads of the proofs whose settlement didn't complete, by
+    /// proof hash.
+    pub fn pending(&self) -> Result<Vec<(H256, Vec<u8>)>, Error> {
+        self.db
+            .iterator(IteratorMode::Start)
+            .map(|entry| {
+                let (key, value) = entry?;
+                if key.len() != H256::len_bytes() {
+                    return Err(Error::InvalidKey(key.len()));
+                }
+
+                Ok((H256::from_slice(&key), value.into_vec()))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for PendingSettlementQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PendingSettlementQueue")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::PendingSettlementQueue;
+
+    #[test]
+    fn pending_settlements_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("agglayer-pending-{:x}", H256::random()));
+        let (first, second) = (H256::random(), H256::random());
+
+        {
+            let queue = PendingSettlementQueue::open(&path).unwrap();
+            queue.push(first, b"first").unwrap();
+            queue.push(second, b"second").unwrap();
+            queue.remove(first).unwrap();
+        }
+
+        let queue = PendingSettlementQueue::open(&path).unwrap();
+
+        assert_eq!(queue.pending().unwrap(), vec![(second, b"second".to_vec())]);
+
+        drop(queue);
+        _ = std::fs::remove_dir_all(path);
+    }
+}
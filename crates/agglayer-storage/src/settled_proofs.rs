@@ -0,0 +1,192 @@
+//! The [`SettledProofIndex`] persists the settlement transaction of the
+//! settled proofs, by proof hash, along with the last batch settled for each
+//! rollup.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use ethers::types::H256;
+use rocksdb::{Options, WriteBatch, DB};
+
+use crate::{synced, Error};
+
+/// The prefix of the keys of the last batch settled for each rollup, which
+/// never collide with the 32 bytes proof hashes.
+const LAST_BATCH_PREFIX: &[u8] = b"last-batch/";
+
+/// A batch settled by the agglayer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SettledBatch {
+    /// The number of the last batch verified by the settlement.
+    pub batch: u64,
+    pub state_root: H256,
+    pub local_exit_root: H256,
+    /// The hash of the settlement transaction.
+    pub settlement_tx_hash: H256,
+}
+
+impl SettledBatch {
+    const ENCODED_LEN: usize = 8 + 3 * 32;
+
+    fn encode(&self) -> Vec<u8> {
+        [
+            &self.batch.to_be_bytes()[..],
+            self.state_root.as_bytes(),
+            self.local_exit_root.as_bytes(),
+            self.settlement_tx_hash.as_bytes(),
+        ]
+        .concat()
+    }
+
+    fn decode(value: &[u8]) -> Result<Self, Error> {
+        if value.len() != Self::ENCODED_LEN {
+            return Err(Error::InvalidSettledBatch(value.len()));
+        }
+        let (batch, roots) = value.split_at(8);
+
+        Ok(Self {
+            batch: u64::from_be_bytes(batch.try_into().expect("8 bytes batch number")),
+            state_root: H256::from_slice(&roots[..32]),
+            local_exit_root: H256::from_slice(&roots[32..64]),
+            settlement_tx_hash: H256::from_slice(&roots[64..]),
+        })
+    }
+}
+
+fn last_batch_key(rollup_id: u32) -> Vec<u8> {
+    [LAST_BATCH_PREFIX, &rollup_id.to_be_bytes()].concat()
+}
+
+/// The proofs settled by the agglayer, along with the hash of their settlement
+/// transaction, backed by a RocksDB database.
+///
+/// Every write is synced to disk before returning, so that a settled proof is
+/// never settled again, even after a crash of the node.
+pub struct SettledProofIndex {
+    db: DB,
+    path: PathBuf,
+}
+
+impl SettledProofIndex {
+    /// Open the index stored in the given directory, creating it if missing.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+
+        Ok(Self {
+            db: DB::open(&options, path.as_ref())?,
+            path: path.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Record the settlement of the given proof of the given rollup.
+    ///
+    /// The settled batch becomes the last one of the rollup unless a later
+    /// batch was settled already.
+    pub fn insert(
+        &self,
+        proof_hash: H256,
+        rollup_id: u32,
+        settled: &SettledBatch,
+    ) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+        batch.put(proof_hash, settled.settlement_tx_hash);
+        if self
+            .last_batch(rollup_id)?
+            .is_none_or(|last| last.batch <= settled.batch)
+        {
+            batch.put(last_batch_key(rollup_id), settled.encode());
+        }
+
+        Ok(self.db.write_opt(batch, &synced())?)
+    }
+
+    /// Get the last batch settled for the given rollup, if any.
+    pub fn last_batch(&self, rollup_id: u32) -> Result<Option<SettledBatch>, Error> {
+        self.db
+            .get(last_batch_key(rollup_id))?
+            .map(|value| SettledBatch::decode(&value))
+            .transpose()
+    }
+
+    /// Get the hash of the settlement transaction of the given proof, if
+    /// settled.
+    pub fn get(&self, proof_hash: H256) -> Result<Option<H256>, Error> {
+        self.db
+            .get(proof_hash)?
+            .map(|value| {
+                if value.len() != H256::len_bytes() {
+                    return Err(Error::InvalidValue(value.len()));
+                }
+
+                Ok(H256::from_slice(&value))
+            })
+            .transpose()
+    }
+}
+
+impl fmt::Debug for SettledProofIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SettledProofIndex")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::H256;
+
+    use super::{SettledBatch, SettledProofIndex};
+
+    fn settled(batch: u64) -> SettledBatch {
+        SettledBatch {
+            batch,
+            state_root: H256::random(),
+            local_exit_root: H256::random(),
+            settlement_tx_hash: H256::random(),
+        }
+    }
+
+    #[test]
+    fn settled_proofs_survive_a_restart() {
+        let path = std::env::temp_dir().join(format!("agglayer-settled-{:x}", H256::random()));
+        let proof_hash = H256::random();
+        let settled = settled(3);
+
+        {
+            let index = SettledProofIndex::open(&path).unwrap();
+            index.insert(proof_hash, 1, &settled).unwrap();
+        }
+
+        let index = SettledProofIndex::open(&path).unwrap();
+
+        assert_eq!(
+            index.get(proof_hash).unwrap(),
+            Some(settled.settlement_tx_hash)
+        );
+        assert_eq!(index.get(H256::random()).unwrap(), None);
+        assert_eq!(index.last_batch(1).unwrap(), Some(settled));
+        assert_eq!(index.last_batch(2).unwrap(), None);
+
+        drop(index);
+        _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn last_batches_only_move_forward() {
+        let path = std::env::temp_dir().join(format!("agglayer-settled-{:x}", H256::random()));
+        let index = SettledProofIndex::open(&path).unwrap();
+        let (later, earlier) = (settled(5), settled(4));
+
+        index.insert(H256::random(), 1, &later).unwrap();
+        index.insert(H256::random(), 1, &earlier).unwrap();
+
+        assert_eq!(index.last_batch(1).unwrap(), Some(later));
+
+        drop(index);
+        _ = std::fs::remove_dir_all(path);
+    }
+}
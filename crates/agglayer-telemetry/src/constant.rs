@@ -1,2 +1,4 @@
 pub(crate) const AGGLAYER_RPC_OTEL_SCOPE_NAME: &str = "rpc";
 pub(crate) const AGGLAYER_KERNEL_OTEL_SCOPE_NAME: &str = "kernel";
+pub(crate) const AGGLAYER_CLOCK_OTEL_SCOPE_NAME: &str = "clock";
+pub(crate) const AGGLAYER_STORAGE_OTEL_SCOPE_NAME: &str = "storage";
@@ -1,4 +1,9 @@
-use std::net::SocketAddr;
+use std::{
+    future::Future,
+    net::SocketAddr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
 
 use axum::{
     extract::State,
@@ -9,13 +14,16 @@ use axum::{
 };
 use lazy_static::lazy_static;
 use opentelemetry::global;
-use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::metrics::{new_view, Aggregation, Instrument, SdkMeterProvider, Stream};
 use prometheus::{Encoder as _, Registry, TextEncoder};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, info};
 
 use crate::{
-    constant::{AGGLAYER_KERNEL_OTEL_SCOPE_NAME, AGGLAYER_RPC_OTEL_SCOPE_NAME},
+    constant::{
+        AGGLAYER_CLOCK_OTEL_SCOPE_NAME, AGGLAYER_KERNEL_OTEL_SCOPE_NAME,
+        AGGLAYER_RPC_OTEL_SCOPE_NAME, AGGLAYER_STORAGE_OTEL_SCOPE_NAME,
+    },
     error::MetricsError,
 };
 
@@ -57,6 +65,241 @@ lazy_static! {
         .u64_counter("settle")
         .with_description("Number of transactions settled")
         .init();
+
+    pub static ref REJECTED_PAYLOAD_SIZE: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_RPC_OTEL_SCOPE_NAME)
+        .u64_counter("rejected_payload_size")
+        .with_description("Number of submissions rejected because of the size of their payload")
+        .init();
+
+    pub static ref CLOCK_MISSED_TICKS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_CLOCK_OTEL_SCOPE_NAME)
+        .u64_counter("clock_missed_ticks")
+        .with_description("Number of clock ticks missed because the clock task or the L1 stream fell behind")
+        .init();
+
+    pub static ref CLOCK_SUBSCRIBER_LAG: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_CLOCK_OTEL_SCOPE_NAME)
+        .u64_counter("clock_subscriber_lag")
+        .with_description("Number of clock events skipped by lagging subscribers")
+        .init();
+
+    pub static ref CLOCK_BLOCK_RESUBSCRIPTIONS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_CLOCK_OTEL_SCOPE_NAME)
+        .u64_counter("clock_block_resubscriptions")
+        .with_description("Number of subscriptions to the L1 block stream re-established after it ended")
+        .init();
+
+    pub static ref SHADOW_VERIFIED: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("shadow_verified")
+        .with_description("Number of transactions of rollups in shadow mode verified without being settled")
+        .init();
+
+    pub static ref SETTLEMENT_GAS_USED: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("settlement_gas_used")
+        .with_description("Gas used by the settlement transactions")
+        .init();
+
+    pub static ref SETTLEMENT_SPENT_ETH: opentelemetry::metrics::Counter<f64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .f64_counter("settlement_spent_eth")
+        .with_description("ETH spent on the settlement transactions")
+        .init();
+
+    pub static ref SETTLEMENT_NONCE_GAPS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("settlement_nonce_gaps")
+        .with_description("Number of nonce gaps left by settlement transactions dropped or reorged out")
+        .init();
+
+    pub static ref SETTLEMENT_GAS_BUMPS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("settlement_gas_bumps")
+        .with_description("Number of stuck settlement transactions replaced with an escalated gas price")
+        .init();
+
+    pub static ref SETTLEMENT_REORGS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("settlement_reorgs")
+        .with_description("Number of settlement transactions reorged out of L1, and settled again")
+        .init();
+
+    pub static ref ZKEVM_NODE_REQUEST_DURATION: opentelemetry::metrics::Histogram<f64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .f64_histogram("zkevm_node_request_duration")
+        .with_description("Duration of the requests to the ZkEVM nodes, in seconds")
+        .init();
+
+    pub static ref ZKEVM_NODE_REQUEST_ERRORS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("zkevm_node_request_errors")
+        .with_description("Number of failed requests to the ZkEVM nodes")
+        .init();
+
+    pub static ref PROOF_COMPRESSION_RATIO: opentelemetry::metrics::Histogram<f64> = global::meter(AGGLAYER_STORAGE_OTEL_SCOPE_NAME)
+        .f64_histogram("proof_compression_ratio")
+        .with_description("Ratio between the raw and the compressed size of the stored proof payloads")
+        .init();
+
+    pub static ref RPC_RATE_LIMITED_REQUESTS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_RPC_OTEL_SCOPE_NAME)
+        .u64_counter("rpc_rate_limited_requests")
+        .with_description("Number of requests rejected for exceeding the rate limit of their IP address")
+        .init();
+
+    pub static ref SEND_CERTIFICATE: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_RPC_OTEL_SCOPE_NAME)
+        .u64_counter("send_certificate")
+        .with_description("Number of certificates received on the RPC")
+        .init();
+
+    pub static ref PROVEN_CERTIFICATES: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("proven_certificates")
+        .with_description("Number of certificates proven at the end of their epoch")
+        .init();
+
+    pub static ref LEGACY_SIGNATURES: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("legacy_signatures")
+        .with_description("Number of proofs accepted with a signature of their legacy hash")
+        .init();
+
+    pub static ref EPOCH_SETTLEMENTS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("epoch_settlements")
+        .with_description("Number of packed settlement transactions mined at the end of the epochs")
+        .init();
+
+    pub static ref EPOCH_SETTLEMENT_FAILURES: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("epoch_settlement_failures")
+        .with_description("Number of packed settlement transactions that failed to be mined")
+        .init();
+
+    pub static ref EPOCH_SETTLED_PROOFS: opentelemetry::metrics::Histogram<f64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .f64_histogram("epoch_settled_proofs")
+        .with_description("Number of proofs settled by each packed settlement transaction")
+        .init();
+
+    pub static ref EPOCH_SETTLEMENT_GAS_USED: opentelemetry::metrics::Histogram<f64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .f64_histogram("epoch_settlement_gas_used")
+        .with_description("Gas used by each packed settlement transaction")
+        .init();
+
+    pub static ref L1_REQUESTS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("l1_requests")
+        .with_description("Number of requests sent to each L1 node")
+        .init();
+
+    pub static ref L1_REQUEST_ERRORS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("l1_request_errors")
+        .with_description("Number of requests to each L1 node that failed to get a response")
+        .init();
+
+    pub static ref L1_FAILOVERS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("l1_failovers")
+        .with_description("Number of switches from an unreachable L1 node to the next one")
+        .init();
+
+    pub static ref L1_WS_RECONNECTS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("l1_ws_reconnects")
+        .with_description("Number of websocket connections to L1 re-established after being dropped")
+        .init();
+
+    pub static ref OUTBOUND_CALL_RETRIES: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("outbound_call_retries")
+        .with_description("Number of calls to L1 and to the ZkEVM nodes retried after a transient failure")
+        .init();
+
+    pub static ref OUTBOUND_CALL_FAILURES: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("outbound_call_failures")
+        .with_description("Number of calls to L1 and to the ZkEVM nodes that failed for good, once retried if transient")
+        .init();
+
+    pub static ref L1_CIRCUIT_BREAKER_TRIPS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("l1_circuit_breaker_trips")
+        .with_description("Number of times the L1 circuit breaker tripped, rejecting the submissions until L1 recovers")
+        .init();
+
+    pub static ref STAGE_TIMEOUTS: opentelemetry::metrics::Counter<u64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .u64_counter("stage_timeouts")
+        .with_description("Number of stages of the processing of the submissions that timed out, by stage")
+        .init();
+
+    pub static ref SEND_TX_DURATION: opentelemetry::metrics::Histogram<f64> = global::meter(AGGLAYER_RPC_OTEL_SCOPE_NAME)
+        .f64_histogram("send_tx_duration")
+        .with_description("Duration of the handling of the transactions received on the RPC, in seconds")
+        .init();
+
+    pub static ref VERIFY_SIGNATURE_DURATION: opentelemetry::metrics::Histogram<f64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .f64_histogram("verify_signature_duration")
+        .with_description("Duration of the verification of the transaction signatures, in seconds")
+        .init();
+
+    pub static ref EXECUTE_DURATION: opentelemetry::metrics::Histogram<f64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .f64_histogram("execute_duration")
+        .with_description("Duration of the dry-run of the proof verification on L1, in seconds")
+        .init();
+
+    pub static ref VERIFY_ZKEVM_NODE_DURATION: opentelemetry::metrics::Histogram<f64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .f64_histogram("verify_zkevm_node_duration")
+        .with_description("Duration of the verification of the batches against the ZkEVM nodes, in seconds")
+        .init();
+
+    pub static ref SETTLE_DURATION: opentelemetry::metrics::Histogram<f64> = global::meter(AGGLAYER_KERNEL_OTEL_SCOPE_NAME)
+        .f64_histogram("settle_duration")
+        .with_description("Duration of the settlement of the transactions on L1, in seconds")
+        .init();
+
+    static ref CLOCK_DRIFT: opentelemetry::metrics::ObservableGauge<f64> = global::meter(AGGLAYER_CLOCK_OTEL_SCOPE_NAME)
+        .f64_observable_gauge("clock_drift")
+        .with_description("Last measured drift of the clock versus the wall-clock time, in seconds")
+        .with_callback(|observer| {
+            observer.observe(f64::from_bits(CLOCK_DRIFT_SECONDS.load(Ordering::Relaxed)), &[])
+        })
+        .init();
+}
+
+/// The last clock drift recorded with [`record_clock_drift`], stored as the
+/// bits of an `f64`.
+static CLOCK_DRIFT_SECONDS: AtomicU64 = AtomicU64::new(0);
+
+/// The bucket boundaries of the histograms of durations, in seconds.
+const DURATION_BUCKETS: [f64; 14] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0,
+];
+
+/// Records the time elapsed since its start in a histogram, once dropped.
+#[must_use = "the duration is recorded once the timer is dropped"]
+pub struct Timer {
+    histogram: &'static opentelemetry::metrics::Histogram<f64>,
+    attributes: Vec<KeyValue>,
+    started_at: Instant,
+}
+
+impl Timer {
+    /// Start timing, recording the duration in the given histogram with the
+    /// given attributes.
+    pub fn start(
+        histogram: &'static opentelemetry::metrics::Histogram<f64>,
+        attributes: &[KeyValue],
+    ) -> Self {
+        Self {
+            histogram,
+            attributes: attributes.to_vec(),
+            started_at: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        self.histogram
+            .record(self.started_at.elapsed().as_secs_f64(), &self.attributes);
+    }
+}
+
+/// Record the duration of the given future in the given histogram.
+pub async fn timed<T>(
+    histogram: &'static opentelemetry::metrics::Histogram<f64>,
+    attributes: &[KeyValue],
+    future: impl Future<Output = T>,
+) -> T {
+    let _timer = Timer::start(histogram, attributes);
+    future.await
+}
+
+/// Record the last measured drift of the clock, in seconds.
+///
+/// A positive drift means that the clock is behind the wall-clock time.
+pub fn record_clock_drift(drift: f64) {
+    CLOCK_DRIFT_SECONDS.store(drift.to_bits(), Ordering::Relaxed);
+    lazy_static::initialize(&CLOCK_DRIFT);
 }
 
 pub struct ServerBuilder {}
@@ -147,8 +390,20 @@ impl ServerBuilder {
             .with_registry(registry.clone())
             .build()?;
 
+        // Bucket the durations in seconds, from milliseconds to minutes.
+        let durations = new_view(
+            Instrument::new().name("*_duration"),
+            Stream::new().aggregation(Aggregation::ExplicitBucketHistogram {
+                boundaries: DURATION_BUCKETS.to_vec(),
+                record_min_max: true,
+            }),
+        )?;
+
         // set up a meter meter to create instruments
-        let provider = SdkMeterProvider::builder().with_reader(exporter).build();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(exporter)
+            .with_view(durations)
+            .build();
 
         global::set_meter_provider(provider);
         Ok(())
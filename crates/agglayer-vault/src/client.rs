@@ -0,0 +1,370 @@
+//! The [`VaultClient`] calls the HTTP API of Vault, authenticated with a token
+//! renewed in the background before it expires.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use agglayer_config::VaultAuth;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ethers::types::H256;
+use k256::{ecdsa::VerifyingKey, pkcs8::DecodePublicKey as _};
+use reqwest::Method;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use tracing::{debug, error, warn};
+use url::Url;
+
+use crate::Error;
+
+/// The minimum interval between two renewals, or renewal attempts.
+const MIN_RENEW_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The lease of a token.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Lease {
+    /// The time to live of the token, zero if it never expires.
+    ttl: Duration,
+    renewable: bool,
+}
+
+#[derive(Deserialize)]
+struct AuthResponse {
+    auth: AuthData,
+}
+
+#[derive(Deserialize)]
+struct AuthData {
+    client_token: String,
+    lease_duration: u64,
+    renewable: bool,
+}
+
+#[derive(Deserialize)]
+struct DataResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct TokenData {
+    ttl: u64,
+    renewable: bool,
+}
+
+#[derive(Deserialize)]
+struct KeyData {
+    latest_version: u64,
+    keys: HashMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct SignatureData {
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+/// A client of the HTTP API of Vault.
+pub(crate) struct VaultClient {
+    http: reqwest::Client,
+    address: Url,
+    namespace: Option<String>,
+    auth: VaultAuth,
+    token: RwLock<String>,
+}
+
+impl VaultClient {
+    /// Authenticate to the given Vault server, returning the client along with
+    /// the lease of its token.
+    pub(crate) async fn login(
+        address: Url,
+        namespace: Option<String>,
+        auth: VaultAuth,
+    ) -> Result<(Self, Lease), Error> {
+        let client = Self {
+            http: reqwest::Client::new(),
+            address,
+            namespace,
+            auth,
+            token: RwLock::new(String::new()),
+        };
+
+        let lease = match &client.auth {
+            VaultAuth::Token { token } => {
+                let token = match token {
+                    Some(token) => token.clone(),
+                    None => std::env::var("VAULT_TOKEN")
+                        .map_err(|_| Error::VaultConfig("VAULT_TOKEN"))?,
+                };
+                client.set_token(token);
+                client.lookup().await?
+            }
+            VaultAuth::AppRole { .. } => client.approle_login().await?,
+        };
+
+        Ok((client, lease))
+    }
+
+    fn set_token(&self, token: String) {
+        *self.token.write().expect("Vault token lock poisoned") = token;
+    }
+
+    /// Send a request to the given path of the API, with the current token.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<T, Error> {
+        let url = self
+            .address
+            .join(&format!("v1/{path}"))
+            .map_err(|error| Error::InvalidResponse(error.to_string()))?;
+        let token = self
+            .token
+            .read()
+            .expect("Vault token lock poisoned")
+            .clone();
+
+        let mut request = self.http.request(method, url);
+        if !token.is_empty() {
+            request = request.header("X-Vault-Token", token);
+        }
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let errors = response
+                .json::<ErrorResponse>()
+                .await
+                .map(|response| response.errors)
+                .unwrap_or_default();
+
+            return Err(Error::Vault {
+                status: status.as_u16(),
+                errors,
+            });
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|error| Error::InvalidResponse(error.to_string()))
+    }
+
+    /// Log in with the AppRole auth method, replacing the current token.
+    async fn approle_login(&self) -> Result<Lease, Error> {
+        let VaultAuth::AppRole {
+            mount,
+            role_id,
+            secret_id,
+        } = &self.auth
+        else {
+            return Err(Error::VaultConfig("AppRole"));
+        };
+
+        let response: AuthResponse = self
+            .request(
+                Method::POST,
+                &format!("auth/{mount}/login"),
+                Some(json!({ "role_id": role_id, "secret_id": secret_id })),
+            )
+            .await?;
+        self.set_token(response.auth.client_token);
+
+        Ok(Lease {
+            ttl: Duration::from_secs(response.auth.lease_duration),
+            renewable: response.auth.renewable,
+        })
+    }
+
+    /// Get the lease of the current token.
+    async fn lookup(&self) -> Result<Lease, Error> {
+        let response: DataResponse<TokenData> = self
+            .request(Method::GET, "auth/token/lookup-self", None)
+            .await?;
+
+        Ok(Lease {
+            ttl: Duration::from_secs(response.data.ttl),
+            renewable: response.data.renewable,
+        })
+    }
+
+    /// Renew the current token, or log in again if it can't be renewed.
+    async fn renew(&self, lease: Lease) -> Result<Lease, Error> {
+        if lease.renewable {
+            match self
+                .request::<AuthResponse>(Method::POST, "auth/token/renew-self", Some(json!({})))
+                .await
+            {
+                Ok(response) => {
+                    let renewed = Lease {
+                        ttl: Duration::from_secs(response.auth.lease_duration),
+                        renewable: response.auth.renewable,
+                    };
+
+                    // The token reached its maximum TTL if renewing it didn't
+                    // extend it, in which case a new one is needed.
+                    if renewed.ttl >= lease.ttl || !self.is_approle() {
+                        return Ok(renewed);
+                    }
+                }
+                Err(error) if !self.is_approle() => return Err(error),
+                Err(error) => warn!("Failed to renew the Vault token, logging in again: {error}"),
+            }
+        }
+
+        if self.is_approle() {
+            self.approle_login().await
+        } else {
+            Err(Error::VaultConfig("a renewable VAULT_TOKEN"))
+        }
+    }
+
+    fn is_approle(&self) -> bool {
+        matches!(self.auth, VaultAuth::AppRole { .. })
+    }
+
+    /// Get the version and the public key of the given transit key, at the
+    /// given version or at its latest one.
+    pub(crate) async fn public_key(
+        &self,
+        mount: &str,
+        key_name: &str,
+        version: Option<u64>,
+    ) -> Result<(u64, VerifyingKey), Error> {
+        let response: DataResponse<KeyData> = self
+            .request(Method::GET, &format!("{mount}/keys/{key_name}"), None)
+            .await?;
+        let version = version.unwrap_or(response.data.latest_version);
+
+        let pem = response
+            .data
+            .keys
+            .get(&version.to_string())
+            .and_then(|key| key.get("public_key"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                Error::InvalidPublicKey(format!("no public key for version {version}"))
+            })?;
+
+        Ok((version, parse_public_key(pem)?))
+    }
+
+    /// Sign the given digest with the given transit key, returning the DER
+    /// encoded signature.
+    pub(crate) async fn sign(
+        &self,
+        mount: &str,
+        key_name: &str,
+        version: u64,
+        digest: H256,
+    ) -> Result<Vec<u8>, Error> {
+        let response: DataResponse<SignatureData> = self
+            .request(
+                Method::POST,
+                &format!("{mount}/sign/{key_name}"),
+                Some(json!({
+                    "input": STANDARD.encode(digest),
+                    "prehashed": true,
+                    "key_version": version,
+                    "marshaling_algorithm": "asn1",
+                })),
+            )
+            .await?;
+
+        // The signatures are formatted as `vault:v<version>:<base64>`.
+        let signature = response
+            .data
+            .signature
+            .rsplit(':')
+            .next()
+            .unwrap_or_default();
+
+        STANDARD
+            .decode(signature)
+            .map_err(|error| Error::InvalidResponse(error.to_string()))
+    }
+}
+
+/// Parse the PEM encoded public key of a transit key.
+fn parse_public_key(pem: &str) -> Result<VerifyingKey, Error> {
+    let der: String = pem
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    let der = STANDARD
+        .decode(der.trim())
+        .map_err(|error| Error::InvalidPublicKey(error.to_string()))?;
+
+    VerifyingKey::from_public_key_der(&der)
+        .map_err(|error| Error::InvalidPublicKey(error.to_string()))
+}
+
+/// Renew the token of the given client in the background, until the client
+/// is dropped.
+pub(crate) fn spawn_renewal(client: &Arc<VaultClient>, mut lease: Lease) {
+    // Tokens without a TTL never expire.
+    if lease.ttl.is_zero() {
+        return;
+    }
+    if !lease.renewable && !client.is_approle() {
+        warn!(
+            "The Vault token isn't renewable, it expires in {:?}",
+            lease.ttl
+        );
+        return;
+    }
+
+    let client = Arc::downgrade(client);
+    tokio::spawn(async move {
+        let mut delay = renew_delay(lease);
+        loop {
+            tokio::time::sleep(delay).await;
+
+            let Some(client) = client.upgrade() else {
+                return;
+            };
+
+            match client.renew(lease).await {
+                Ok(renewed) if renewed.ttl.is_zero() => return,
+                Ok(renewed) => {
+                    debug!("Renewed the Vault token for {:?}", renewed.ttl);
+                    lease = renewed;
+                    delay = renew_delay(lease);
+                }
+                Err(error) => {
+                    error!("Failed to renew the Vault token: {error}");
+                    delay = MIN_RENEW_INTERVAL;
+                }
+            }
+        }
+    });
+}
+
+/// The delay after which a token is renewed, two thirds into its lease.
+fn renew_delay(lease: Lease) -> Duration {
+    (lease.ttl * 2 / 3).max(MIN_RENEW_INTERVAL)
+}
+
+impl std::fmt::Debug for VaultClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Do not log the token.
+        f.debug_struct("VaultClient")
+            .field("address", &self.address.as_str())
+            .field("namespace", &self.namespace)
+            .finish()
+    }
+}
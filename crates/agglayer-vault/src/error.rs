@@ -0,0 +1,40 @@
+//! The [`Error`] enum represents errors that can occur in the Vault
+//! operations. It includes errors from the Vault server and configuration
+//! errors.
+
+use thiserror::Error;
+
+/// Represents errors that can occur in the Vault operations.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// An error occurred with the Vault configuration.
+    ///
+    /// This variant is used when a required value or environment variable is
+    /// missing.
+    #[error("Vault configuration error: missing value or env {0}")]
+    VaultConfig(&'static str),
+
+    /// The Vault server couldn't be reached.
+    #[error("Vault request error: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// The Vault server rejected a request.
+    #[error("Vault responded with status {status}: {}", errors.join(", "))]
+    Vault { status: u16, errors: Vec<String> },
+
+    /// The Vault server responded with an unexpected payload.
+    #[error("invalid Vault response: {0}")]
+    InvalidResponse(String),
+
+    /// The transit key isn't a secp256k1 key.
+    #[error("invalid public key of the transit key: {0}")]
+    InvalidPublicKey(String),
+
+    /// The signature doesn't match the public key of the transit key.
+    #[error("invalid signature of the transit key")]
+    InvalidSignature,
+
+    /// The typed data couldn't be encoded.
+    #[error("EIP-712 encoding error: {0}")]
+    Eip712(String),
+}
@@ -0,0 +1,209 @@
+//! The [`VaultSigner`] signs with a secp256k1 key of a HashiCorp Vault
+//! transit secrets engine, the key never leaving Vault.
+//!
+//! The stock transit engine lacks secp256k1 keys, the transit mount must be
+//! served by a transit-compatible plugin supporting them. The signer
+//! authenticates with a token or an AppRole, the token being renewed in the
+//! background before it expires.
+
+use std::sync::Arc;
+
+use agglayer_config::VaultConfig;
+use ethers::{
+    signers::to_eip155_v,
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature, H256, U256,
+    },
+    utils::{hash_message, public_key_to_address},
+};
+use k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, VerifyingKey};
+use url::Url;
+
+pub(crate) mod client;
+pub(crate) mod error;
+
+use client::VaultClient;
+pub use error::Error;
+
+/// A signer backed by a Vault transit key.
+#[derive(Debug)]
+pub struct VaultSigner {
+    client: Arc<VaultClient>,
+    mount: String,
+    key_name: String,
+    /// The version of the transit key signing the digests.
+    key_version: u64,
+    public_key: VerifyingKey,
+    address: Address,
+    chain_id: u64,
+}
+
+impl VaultSigner {
+    /// Creates a Vault transit signer from the configuration.
+    ///
+    /// The address and the namespace of the Vault server default to the
+    /// `VAULT_ADDR` and `VAULT_NAMESPACE` environment variables, and the token
+    /// to the `VAULT_TOKEN` one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if it fails to authenticate to Vault
+    /// or if the transit key isn't a secp256k1 key.
+    pub async fn new(chain_id: u64, config: &VaultConfig) -> Result<Self, Error> {
+        let address = match &config.address {
+            Some(address) => address.clone(),
+            None => std::env::var("VAULT_ADDR")
+                .ok()
+                .and_then(|address| Url::parse(&address).ok())
+                .ok_or(Error::VaultConfig("VAULT_ADDR"))?,
+        };
+        let namespace = config
+            .namespace
+            .clone()
+            .or_else(|| std::env::var("VAULT_NAMESPACE").ok());
+
+        let (client, lease) = VaultClient::login(address, namespace, config.auth.clone()).await?;
+        let client = Arc::new(client);
+        client::spawn_renewal(&client, lease);
+
+        let (key_version, public_key) = client
+            .public_key(&config.mount, &config.key_name, config.key_version)
+            .await?;
+
+        Ok(Self {
+            client,
+            mount: config.mount.clone(),
+            key_name: config.key_name.clone(),
+            key_version,
+            address: public_key_to_address(&public_key),
+            public_key,
+            chain_id,
+        })
+    }
+
+    /// Signs the given digest, with `v` set to the recovery id plus 27.
+    async fn sign_hash(&self, hash: H256) -> Result<Signature, Error> {
+        let der = self
+            .client
+            .sign(&self.mount, &self.key_name, self.key_version, hash)
+            .await?;
+
+        recoverable_signature(&self.public_key, hash, &der)
+    }
+
+    /// Signs a message, prefixed as per EIP-191.
+    pub async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Error> {
+        self.sign_hash(hash_message(message)).await
+    }
+
+    /// Signs a transaction, with `v` normalized as per EIP-155 using the
+    /// chain id of the transaction, or the one of the signer if it has none.
+    pub async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Error> {
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        let mut tx = tx.clone();
+        tx.set_chain_id(chain_id);
+
+        let mut signature = self.sign_hash(tx.sighash()).await?;
+        signature.v = to_eip155_v(signature.v as u8 - 27, chain_id);
+
+        Ok(signature)
+    }
+
+    /// Signs typed data, encoded as per EIP-712.
+    pub async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Error> {
+        let encoded = payload
+            .encode_eip712()
+            .map_err(|error| Error::Eip712(error.to_string()))?;
+
+        self.sign_hash(H256::from(encoded)).await
+    }
+
+    /// Returns the address of the transit key.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns the chain ID associated with the signer.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Sets a new chain ID for the signer.
+    pub fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// Turn the DER encoded signature of the given digest into a recoverable
+/// signature, with a low `s` and `v` set to the recovery id plus 27.
+fn recoverable_signature(
+    public_key: &VerifyingKey,
+    hash: H256,
+    der: &[u8],
+) -> Result<Signature, Error> {
+    let signature = EcdsaSignature::from_der(der).map_err(|_| Error::InvalidSignature)?;
+    // Ethereum only accepts the signatures with a low `s`.
+    let signature = signature.normalize_s().unwrap_or(signature);
+
+    let recovery_id = [0, 1]
+        .into_iter()
+        .filter_map(RecoveryId::from_byte)
+        .find(|recovery_id| {
+            VerifyingKey::recover_from_prehash(hash.as_bytes(), &signature, *recovery_id)
+                .is_ok_and(|recovered| recovered == *public_key)
+        })
+        .ok_or(Error::InvalidSignature)?;
+
+    Ok(Signature {
+        r: U256::from_big_endian(&signature.r().to_bytes()),
+        s: U256::from_big_endian(&signature.s().to_bytes()),
+        v: u64::from(recovery_id.to_byte()) + 27,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::core::rand::thread_rng;
+    use k256::ecdsa::{signature::hazmat::PrehashSigner as _, SigningKey};
+
+    use super::*;
+
+    #[test]
+    fn der_signatures_are_made_recoverable() {
+        let signing_key = SigningKey::random(&mut thread_rng());
+        let public_key = *signing_key.verifying_key();
+        let address = public_key_to_address(&public_key);
+        let hash = hash_message("agglayer");
+
+        // The signatures produced by k256 have a low `s`.
+        let signature: EcdsaSignature = signing_key.sign_prehash(hash.as_bytes()).unwrap();
+        // Vault doesn't normalize the signatures, `s` may be high.
+        let high_s =
+            EcdsaSignature::from_scalars(signature.r().to_bytes(), (-*signature.s()).to_bytes())
+                .unwrap();
+
+        for der in [signature.to_der(), high_s.to_der()] {
+            let recovered = recoverable_signature(&public_key, hash, der.as_bytes()).unwrap();
+
+            assert_eq!(recovered.recover(hash).unwrap(), address);
+            assert_eq!(
+                recovered.s,
+                U256::from_big_endian(&signature.s().to_bytes())
+            );
+        }
+
+        let other_key = *SigningKey::random(&mut thread_rng()).verifying_key();
+        assert!(matches!(
+            recoverable_signature(&other_key, hash, signature.to_der().as_bytes()),
+            Err(Error::InvalidSignature)
+        ));
+    }
+}
@@ -0,0 +1,97 @@
+//! The [`Web3SignerClient`] calls the `eth1` JSON-RPC API of a Web3Signer, or
+//! of a Consensys EthSigner, over HTTP or HTTPS.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use agglayer_config::Web3SignerConfig;
+use reqwest::{Certificate, Identity, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use crate::Error;
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+/// A JSON-RPC client of a Web3Signer.
+#[derive(Debug)]
+pub(crate) struct Web3SignerClient {
+    http: reqwest::Client,
+    url: Url,
+    next_id: AtomicU64,
+}
+
+impl Web3SignerClient {
+    /// Build a client of the configured signer, trusting the configured CA
+    /// certificate and authenticating with the configured client identity.
+    pub(crate) fn new(config: &Web3SignerConfig) -> Result<Self, Error> {
+        let read = |path: &std::path::PathBuf| {
+            std::fs::read(path).map_err(|source| Error::TlsFile {
+                path: path.clone(),
+                source,
+            })
+        };
+
+        let mut builder = reqwest::Client::builder().timeout(config.timeout);
+        if let Some(path) = &config.ca_cert {
+            builder = builder.add_root_certificate(Certificate::from_pem(&read(path)?)?);
+        }
+        if let Some(path) = &config.client_identity {
+            builder = builder.identity(Identity::from_pem(&read(path)?)?);
+        }
+
+        Ok(Self {
+            http: builder.build()?,
+            url: config.url.clone(),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Call the given method of the signer.
+    pub(crate) async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, Error> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let response: RpcResponse<T> = self
+            .http
+            .post(self.url.clone())
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await?
+            .json()
+            .await
+            .map_err(|error| Error::InvalidResponse(error.to_string()))?;
+
+        match response {
+            RpcResponse {
+                error: Some(error), ..
+            } => Err(Error::Rpc {
+                code: error.code,
+                message: error.message,
+            }),
+            RpcResponse {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            RpcResponse { result: None, .. } => {
+                Err(Error::InvalidResponse(format!("no result to {method}")))
+            }
+        }
+    }
+}
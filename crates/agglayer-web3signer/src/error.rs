@@ -0,0 +1,45 @@
+//! The [`Error`] enum represents errors that can occur in the Web3Signer
+//! operations. It includes errors from the remote signer, TLS and
+//! configuration errors.
+
+use std::{io, path::PathBuf};
+
+use ethers::types::Address;
+use thiserror::Error;
+
+/// Represents errors that can occur in the Web3Signer operations.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A TLS certificate or identity couldn't be read.
+    #[error("unable to read the Web3Signer TLS file {path:?}: {source}")]
+    TlsFile { path: PathBuf, source: io::Error },
+
+    /// The signer couldn't be reached, or a TLS certificate or identity is
+    /// invalid.
+    #[error("Web3Signer request error: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// The signer rejected a request.
+    #[error("Web3Signer responded with error {code}: {message}")]
+    Rpc { code: i64, message: String },
+
+    /// The signer responded with an unexpected payload.
+    #[error("invalid Web3Signer response: {0}")]
+    InvalidResponse(String),
+
+    /// The signer doesn't serve the key of the configured address.
+    #[error("Web3Signer doesn't serve the account {0:?}")]
+    UnknownAccount(Address),
+
+    /// The signer doesn't serve any key.
+    #[error("Web3Signer doesn't serve any account")]
+    NoAccount,
+
+    /// The signature doesn't match the signed payload or the signer address.
+    #[error("invalid signature of the Web3Signer")]
+    InvalidSignature,
+
+    /// The operation isn't supported by the `eth1` API of the signer.
+    #[error("unsupported by Web3Signer: {0}")]
+    Unsupported(&'static str),
+}
@@ -0,0 +1,268 @@
+//! The [`Web3Signer`] delegates the signing to a remote Web3Signer, or a
+//! Consensys EthSigner, over its `eth1` JSON-RPC API, the keys never residing
+//! on the agglayer host.
+//!
+//! The signatures returned by the remote signer are checked against the
+//! signed payloads and the address of the key before being used.
+
+use agglayer_config::Web3SignerConfig;
+use ethers::{
+    signers::to_eip155_v,
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Bytes, Signature, Transaction,
+    },
+    utils::rlp,
+};
+use serde_json::{json, Map, Value};
+
+pub(crate) mod client;
+pub(crate) mod error;
+
+use client::Web3SignerClient;
+pub use error::Error;
+
+/// A signer delegating to a remote Web3Signer.
+#[derive(Debug)]
+pub struct Web3Signer {
+    client: Web3SignerClient,
+    address: Address,
+    chain_id: u64,
+}
+
+impl Web3Signer {
+    /// Creates a Web3Signer client from the configuration, signing with the
+    /// key of the configured address, or of the first account of the signer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the TLS files can't be read, if
+    /// the signer can't be reached, or if it doesn't serve the configured
+    /// address.
+    pub async fn new(chain_id: u64, config: &Web3SignerConfig) -> Result<Self, Error> {
+        let client = Web3SignerClient::new(config)?;
+
+        let accounts: Vec<Address> = client.request("eth_accounts", json!([])).await?;
+        let address = match config.address {
+            Some(address) if accounts.contains(&address) => address,
+            Some(address) => return Err(Error::UnknownAccount(address)),
+            None => *accounts.first().ok_or(Error::NoAccount)?,
+        };
+
+        Ok(Self {
+            client,
+            address,
+            chain_id,
+        })
+    }
+
+    /// Signs a message, prefixed as per EIP-191 by the remote signer.
+    pub async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Error> {
+        let message = message.as_ref();
+        let signature: Bytes = self
+            .client
+            .request(
+                "eth_sign",
+                json!([self.address, Bytes::from(message.to_vec())]),
+            )
+            .await?;
+        let signature = Signature::try_from(signature.as_ref())
+            .map_err(|error| Error::InvalidResponse(error.to_string()))?;
+
+        signature
+            .verify(message, self.address)
+            .map_err(|_| Error::InvalidSignature)?;
+
+        Ok(signature)
+    }
+
+    /// Signs a transaction, with `v` normalized as per EIP-155 using the
+    /// chain id of the transaction, or the one of the signer if it has none.
+    pub async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Error> {
+        let chain_id = tx.chain_id().map(|id| id.as_u64()).unwrap_or(self.chain_id);
+        let mut tx = tx.clone();
+        tx.set_chain_id(chain_id);
+
+        let signed: Bytes = self
+            .client
+            .request(
+                "eth_signTransaction",
+                json!([transaction_params(&tx, self.address)?]),
+            )
+            .await?;
+
+        transaction_signature(&tx, chain_id, self.address, &signed)
+    }
+
+    /// Signing typed data requires its JSON encoding, which the [`Eip712`]
+    /// trait doesn't provide.
+    pub async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        _payload: &T,
+    ) -> Result<Signature, Error> {
+        Err(Error::Unsupported("signing EIP-712 typed data"))
+    }
+
+    /// Returns the address of the remote key.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Returns the chain ID associated with the signer.
+    pub fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    /// Sets a new chain ID for the signer.
+    pub fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+/// Build the parameters of `eth_signTransaction` for the given transaction,
+/// which only supports the legacy and the EIP-1559 ones.
+fn transaction_params(tx: &TypedTransaction, from: Address) -> Result<Value, Error> {
+    let mut params = Map::new();
+    params.insert("from".to_string(), json!(from));
+    if let Some(to) = tx.to_addr() {
+        params.insert("to".to_string(), json!(to));
+    }
+    if let Some(gas) = tx.gas() {
+        params.insert("gas".to_string(), json!(gas));
+    }
+    match tx {
+        TypedTransaction::Legacy(tx) => {
+            if let Some(gas_price) = tx.gas_price {
+                params.insert("gasPrice".to_string(), json!(gas_price));
+            }
+        }
+        TypedTransaction::Eip1559(tx) => {
+            if let Some(max_fee) = tx.max_fee_per_gas {
+                params.insert("maxFeePerGas".to_string(), json!(max_fee));
+            }
+            if let Some(max_priority_fee) = tx.max_priority_fee_per_gas {
+                params.insert("maxPriorityFeePerGas".to_string(), json!(max_priority_fee));
+            }
+        }
+        TypedTransaction::Eip2930(_) => return Err(Error::Unsupported("EIP-2930 transactions")),
+    }
+    if let Some(nonce) = tx.nonce() {
+        params.insert("nonce".to_string(), json!(nonce));
+    }
+    if let Some(value) = tx.value() {
+        params.insert("value".to_string(), json!(value));
+    }
+    params.insert(
+        "data".to_string(),
+        json!(tx.data().cloned().unwrap_or_default()),
+    );
+
+    Ok(Value::Object(params))
+}
+
+/// Extract the signature of the given transaction from its signed encoding,
+/// with `v` normalized as per EIP-155, checking that it was signed by the
+/// given address.
+fn transaction_signature(
+    tx: &TypedTransaction,
+    chain_id: u64,
+    address: Address,
+    signed: &[u8],
+) -> Result<Signature, Error> {
+    let signed: Transaction =
+        rlp::decode(signed).map_err(|error| Error::InvalidResponse(error.to_string()))?;
+
+    // Typed transactions carry the parity of `y`, legacy ones either 27 or 28
+    // or their EIP-155 `v`.
+    let parity = match signed.v.as_u64() {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        v => (v - 35) % 2,
+    };
+    let signature = Signature {
+        r: signed.r,
+        s: signed.s,
+        v: to_eip155_v(parity as u8, chain_id),
+    };
+
+    signature
+        .verify(tx.sighash(), address)
+        .map_err(|_| Error::InvalidSignature)?;
+
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        signers::{LocalWallet, Signer as _},
+        types::{Eip1559TransactionRequest, TransactionRequest},
+    };
+
+    use super::*;
+
+    #[test]
+    fn transaction_signatures_are_checked() {
+        let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng()).with_chain_id(1u64);
+        let other = Address::from_low_u64_be(1);
+
+        let legacy: TypedTransaction = TransactionRequest::new()
+            .to(other)
+            .gas(21_000)
+            .gas_price(1)
+            .nonce(0)
+            .chain_id(1)
+            .into();
+        let eip1559: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(other)
+            .gas(21_000)
+            .max_fee_per_gas(2)
+            .max_priority_fee_per_gas(1)
+            .nonce(0)
+            .chain_id(1)
+            .into();
+
+        for tx in [legacy, eip1559] {
+            let expected = wallet.sign_transaction_sync(&tx).unwrap();
+            let signed = tx.rlp_signed(&expected);
+
+            assert_eq!(
+                transaction_signature(&tx, 1, wallet.address(), &signed).unwrap(),
+                expected
+            );
+            assert!(matches!(
+                transaction_signature(&tx, 1, other, &signed),
+                Err(Error::InvalidSignature)
+            ));
+        }
+    }
+
+    #[test]
+    fn transaction_params_are_the_eth1_ones() {
+        let from = Address::from_low_u64_be(1);
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::from_low_u64_be(2))
+            .gas(21_000)
+            .max_fee_per_gas(2)
+            .max_priority_fee_per_gas(1)
+            .nonce(3)
+            .into();
+
+        assert_eq!(
+            transaction_params(&tx, from).unwrap(),
+            json!({
+                "from": from,
+                "to": Address::from_low_u64_be(2),
+                "gas": "0x5208",
+                "maxFeePerGas": "0x2",
+                "maxPriorityFeePerGas": "0x1",
+                "nonce": "0x3",
+                "data": "0x",
+            })
+        );
+    }
+}
@@ -1,7 +1,7 @@
 //! Agglayer command line interface.
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand, ValueHint};
+use clap::{Parser, Subcommand, ValueEnum, ValueHint};
 
 /// Agglayer command line interface.
 #[derive(Parser)]
@@ -17,4 +17,59 @@ pub(crate) enum Commands {
         #[arg(long, short, value_hint = ValueHint::FilePath, default_value = "agglayer.toml", env = "CONFIG_PATH")]
         cfg: PathBuf,
     },
+    /// Run the preflight checks of a configuration, before putting a node
+    /// into rotation.
+    Doctor {
+        /// The path to the configuration file.
+        #[arg(long, short, value_hint = ValueHint::FilePath, default_value = "agglayer.toml", env = "CONFIG_PATH")]
+        cfg: PathBuf,
+    },
+    /// Validate or generate a configuration file.
+    Config {
+        #[command(subcommand)]
+        cmd: ConfigCommands,
+    },
+    /// Generate the client types of the agglayer RPC.
+    Codegen {
+        /// The language to generate the types for.
+        #[arg(long, short, value_enum)]
+        lang: CodegenLanguage,
+        /// The file to write the types to. Defaults to the standard output.
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum ConfigCommands {
+    /// Parse and validate a configuration, resolving its contracts on L1 and
+    /// checking its signer.
+    Validate {
+        /// The path to the configuration file.
+        #[arg(long, short, value_hint = ValueHint::FilePath, default_value = "agglayer.toml", env = "CONFIG_PATH")]
+        cfg: PathBuf,
+    },
+    /// Generate the default configuration, with every field commented.
+    Generate {
+        /// The file to write the configuration to. Defaults to the standard
+        /// output.
+        #[arg(long, short, value_hint = ValueHint::FilePath)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub(crate) enum CodegenLanguage {
+    #[value(name = "typescript", alias = "ts")]
+    TypeScript,
+    Go,
+}
+
+impl From<CodegenLanguage> for agglayer_node::codegen::Language {
+    fn from(language: CodegenLanguage) -> Self {
+        match language {
+            CodegenLanguage::TypeScript => Self::TypeScript,
+            CodegenLanguage::Go => Self::Go,
+        }
+    }
 }
@@ -10,6 +10,38 @@ fn main() -> anyhow::Result<()> {
 
     match cli.cmd {
         cli::Commands::Run { cfg } => agglayer_node::main(cfg)?,
+        cli::Commands::Doctor { cfg } => {
+            let report = agglayer_node::doctor::run(cfg)?;
+            print!("{report}");
+
+            if !report.passed() {
+                anyhow::bail!("preflight checks failed");
+            }
+        }
+        cli::Commands::Config {
+            cmd: cli::ConfigCommands::Validate { cfg },
+        } => {
+            let report = agglayer_node::doctor::validate(cfg)?;
+            print!("{report}");
+
+            if !report.passed() {
+                anyhow::bail!("invalid configuration");
+            }
+        }
+        cli::Commands::Config {
+            cmd: cli::ConfigCommands::Generate { output },
+        } => match output {
+            Some(path) => std::fs::write(path, agglayer_node::doctor::DEFAULT_CONFIG)?,
+            None => print!("{}", agglayer_node::doctor::DEFAULT_CONFIG),
+        },
+        cli::Commands::Codegen { lang, output } => {
+            let types = agglayer_node::codegen::generate(lang.into());
+
+            match output {
+                Some(path) => std::fs::write(path, types)?,
+                None => print!("{types}"),
+            }
+        }
     }
 
     Ok(())
@@ -2,12 +2,13 @@ pub mod keccak;
 pub mod local_exit_tree;
 
 mod proof;
-pub use proof::{generate_full_proof, ProofError};
+pub use proof::{generate_full_proof, FullProofOutput, ProofError};
 
 pub mod test_utils;
 
 mod bridge_exit;
 pub use bridge_exit::{BridgeExit, NetworkId, TokenInfo};
+pub use reth_primitives::{Address, U256};
 
 pub mod certificate;
 